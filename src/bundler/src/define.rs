@@ -0,0 +1,38 @@
+/// This file contains the parsing of the values of the define option.
+///
+/// A define replaces a global variable, or a member chain on one, with an
+/// expression. The values are written as JavaScript, which includes JSON,
+/// so `"production"`, `false`, `{ "a": 1 }` and `window` are all valid.
+use std::collections::HashMap;
+
+use js_ast::{Expression, Statement};
+use js_lexer::Lexer;
+use js_parser::Parser;
+use logger::LoggerImpl;
+
+/// Parses the values of the defines, returns an error for the first value that
+/// is not a single expression. Syntax errors are reported by the parser itself.
+pub fn parse_defines(defines: &[(String, String)]) -> Result<HashMap<String, Expression>, String> {
+    defines
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), parse_define(name, value)?)))
+        .collect()
+}
+
+fn parse_define(name: &str, value: &str) -> Result<Expression, String> {
+    // The value is wrapped in parentheses so that objects are not parsed as blocks.
+    let content = format!("({})", value);
+    let logger = LoggerImpl::new();
+    let lexer = Lexer::new(&content, &logger);
+    let mut ast = Parser::new(lexer, &logger).parse_program();
+    match ast.statements.pop() {
+        Some(Statement::Expression(e)) if ast.statements.is_empty() => match e.expression {
+            Expression::Sequence(mut s) if s.expressions.len() == 1 => Ok(s.expressions.remove(0)),
+            expression => Ok(expression),
+        },
+        _ => Err(format!(
+            "Invalid value for the define of \"{}\": \"{}\"",
+            name, value
+        )),
+    }
+}
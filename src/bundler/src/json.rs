@@ -0,0 +1,272 @@
+/// A small JSON parser, it is only used to read package.json files
+/// and therefore only supports what is needed for that. Values can
+/// also be written, which is used for the files describing the output.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JSONValue {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JSONValue>),
+    /// The properties are kept in the order they were defined.
+    Object(Vec<(String, JSONValue)>),
+}
+
+impl JSONValue {
+    /// Returns the value of the given property if this is an object.
+    pub fn get(&self, key: &str) -> Option<&JSONValue> {
+        match self {
+            JSONValue::Object(properties) => properties
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JSONValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Writes the value as JSON, arrays and objects are indented with two spaces.
+    pub fn stringify(&self) -> String {
+        let mut text = String::new();
+        self.write(&mut text, 0);
+        text
+    }
+
+    fn write(&self, text: &mut String, indent: usize) {
+        match self {
+            JSONValue::Null => text.push_str("null"),
+            JSONValue::Boolean(value) => text.push_str(&value.to_string()),
+            JSONValue::Number(value) if value.is_finite() => text.push_str(&value.to_string()),
+            JSONValue::Number(_) => text.push_str("null"),
+            JSONValue::String(value) => text.push_str(&quote_json(value)),
+            JSONValue::Array(items) if items.is_empty() => text.push_str("[]"),
+            JSONValue::Array(items) => {
+                text.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    text.push_str(&" ".repeat(indent + 2));
+                    item.write(text, indent + 2);
+                    if index < items.len() - 1 {
+                        text.push(',');
+                    }
+                    text.push('\n');
+                }
+                text.push_str(&" ".repeat(indent));
+                text.push(']');
+            }
+            JSONValue::Object(properties) if properties.is_empty() => text.push_str("{}"),
+            JSONValue::Object(properties) => {
+                text.push_str("{\n");
+                for (index, (key, value)) in properties.iter().enumerate() {
+                    text.push_str(&" ".repeat(indent + 2));
+                    text.push_str(&quote_json(key));
+                    text.push_str(": ");
+                    value.write(text, indent + 2);
+                    if index < properties.len() - 1 {
+                        text.push(',');
+                    }
+                    text.push('\n');
+                }
+                text.push_str(&" ".repeat(indent));
+                text.push('}');
+            }
+        }
+    }
+}
+
+/// Quotes the string as a JSON string.
+fn quote_json(value: &str) -> String {
+    let mut text = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            '\r' => text.push_str("\\r"),
+            '\t' => text.push_str("\\t"),
+            c if (c as u32) < 0x20 => text.push_str(&format!("\\u{:04x}", c as u32)),
+            c => text.push(c),
+        }
+    }
+    text.push('"');
+    text
+}
+
+/// Parses the given text as JSON.
+pub fn parse_json(text: &str) -> Result<JSONValue, String> {
+    let mut parser = JSONParser {
+        chars: text.chars().collect(),
+        index: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.index != parser.chars.len() {
+        return Err(format!("Unexpected character at {}", parser.index));
+    }
+    Ok(value)
+}
+
+struct JSONParser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl JSONParser {
+    fn parse_value(&mut self) -> Result<JSONValue, String> {
+        self.skip_whitespace();
+        match self.current() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => self.parse_string().map(JSONValue::String),
+            Some('t') => self.parse_keyword("true", JSONValue::Boolean(true)),
+            Some('f') => self.parse_keyword("false", JSONValue::Boolean(false)),
+            Some('n') => self.parse_keyword("null", JSONValue::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) => Err(format!("Unexpected character \"{}\" at {}", c, self.index)),
+            None => Err("Unexpected end of input".into()),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JSONValue, String> {
+        self.index += 1;
+        let mut properties = Vec::new();
+        self.skip_whitespace();
+        if self.current() == Some('}') {
+            self.index += 1;
+            return Ok(JSONValue::Object(properties));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            properties.push((key, value));
+            self.skip_whitespace();
+            match self.current() {
+                Some(',') => self.index += 1,
+                Some('}') => {
+                    self.index += 1;
+                    return Ok(JSONValue::Object(properties));
+                }
+                _ => return Err(format!("Expected \",\" or \"}}\" at {}", self.index)),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JSONValue, String> {
+        self.index += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.current() == Some(']') {
+            self.index += 1;
+            return Ok(JSONValue::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.current() {
+                Some(',') => self.index += 1,
+                Some(']') => {
+                    self.index += 1;
+                    return Ok(JSONValue::Array(items));
+                }
+                _ => return Err(format!("Expected \",\" or \"]\" at {}", self.index)),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.current() {
+                Some('"') => {
+                    self.index += 1;
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.index += 1;
+                    let escaped = match self.current() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex: String =
+                                self.chars.iter().skip(self.index + 1).take(4).collect();
+                            let code = u32::from_str_radix(&hex, 16)
+                                .map_err(|_| format!("Invalid unicode escape at {}", self.index))?;
+                            self.index += 4;
+                            std::char::from_u32(code).unwrap_or('\u{FFFD}')
+                        }
+                        _ => return Err(format!("Invalid escape at {}", self.index)),
+                    };
+                    value.push(escaped);
+                    self.index += 1;
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.index += 1;
+                }
+                None => return Err("Unterminated string".into()),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JSONValue, String> {
+        let start = self.index;
+        while let Some(c) = self.current() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+        let text: String = self.chars[start..self.index].iter().collect();
+        text.parse::<f64>()
+            .map(JSONValue::Number)
+            .map_err(|_| format!("Invalid number \"{}\"", text))
+    }
+
+    fn parse_keyword(&mut self, keyword: &str, value: JSONValue) -> Result<JSONValue, String> {
+        for expected in keyword.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.current() == Some(expected) {
+            self.index += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected \"{}\" at {}", expected, self.index))
+        }
+    }
+
+    fn current(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.current() {
+            if c.is_whitespace() {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+    }
+}
@@ -1,12 +1,37 @@
+use std::collections::HashMap;
+
 use fs::FS;
-use js_ast::AST;
+use js_ast::scope::replace_globals;
+use js_ast::{ExportNamedSpecifiers, Expression, Statement, AST};
 use js_lexer::Lexer;
+use js_lowering::{insert_helpers, lower_syntax_with_options, LoweringOptions, Target};
+use js_minifier::{fold_constants, minify_syntax};
 use js_parser::Parser;
+use js_printer::LegalComments;
 use logger::LoggerImpl;
 
+mod define;
+mod json;
+mod linker;
+mod output;
+mod parts;
+mod resolver;
+mod runtime;
+mod tree_shaking;
+
+pub use define::parse_defines;
+
 #[derive(Debug)]
 pub struct File {
-    representation: FileRepresentation,
+    /// The path of the file, as resolved from the importer.
+    pub path: String,
+    /// The size of the source of the file in bytes.
+    pub size: usize,
+    pub representation: FileRepresentation,
+    pub import_records: Vec<ImportRecord>,
+    /// False if the package.json of the file says that it has no side effects,
+    /// in that case the file is only included if something it exports is used.
+    pub side_effects: bool,
 }
 
 #[derive(Debug)]
@@ -19,54 +44,343 @@ pub struct FileRepresentationJS {
     pub ast: AST,
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ImportKind {
+    /// import a from "b", export * from "b"
+    Static,
+    /// require("a")
+    Require,
+    /// import("a")
+    Dynamic,
+}
+
+/// An import of another file, the import path is kept exactly as
+/// written in the source so that it can be matched with the statement.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportRecord {
+    pub import_path: String,
+    pub kind: ImportKind,
+    /// The index of the imported file, None if the path could not be resolved.
+    pub source_index: Option<usize>,
+    /// The name of the chunk from a `webpackChunkName` comment in `import()`.
+    pub chunk_name: Option<String>,
+}
+
+/// The shape of the bundle, decides how the exports of the entry points are exposed.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Format {
+    /// export { a }
+    #[default]
+    ESM,
+    /// module.exports = { ... }
+    CommonJS,
+    /// (function() { ... })()
+    IIFE,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundlerOptions {
+    pub format: Format,
+    /// The variable, or property like "window.MyLib", that the exports of the
+    /// entry point are assigned to. Only used by the IIFE format.
+    pub global_name: Option<String>,
+    /// Splits the bundle into chunks that import each other, only done for the ESM format.
+    pub splitting: bool,
+    /// The template for the paths of the chunks of the entry points, and of the single
+    /// file without splitting. "[name]" is replaced with the name of the chunk and
+    /// "[hash]" with the hash of its contents, for example "[name]-[hash].js".
+    pub entry_names: String,
+    /// The template for the paths of the other chunks, for example "chunks/[name]-[hash].js".
+    pub chunk_names: String,
+    /// What happens to legal comments in the chunks.
+    pub legal_comments: LegalComments,
+    /// Adds a "metafile.json" to the output files, describing the inputs of the
+    /// bundle and how many bytes each of them contributed to the output files.
+    pub metafile: bool,
+    /// Replaces global variables, or member chains on them like `process.env.NODE_ENV`,
+    /// with the expression written in the value, such as `"production"`.
+    pub define: Vec<(String, String)>,
+    /// Folds constants and removes dead code while parsing, so that code only reached
+    /// from removed branches is left out of the bundle, and rewrites the output into
+    /// shorter equivalent code.
+    pub minify_syntax: bool,
+    /// The version of JavaScript the output should run in, newer syntax is lowered
+    /// to older syntax. Syntax that can not be lowered yet is an error.
+    pub target: Target,
+    /// Assumes that the values iterated by lowered `for-of` loops and spread are
+    /// arrays, which gives smaller output.
+    pub assume_arrays: bool,
+    /// Escapes the characters outside of ASCII in the chunks, for pages that
+    /// are served with the wrong charset.
+    pub ascii_only: bool,
+}
+
+impl Default for BundlerOptions {
+    fn default() -> Self {
+        BundlerOptions {
+            format: Format::default(),
+            global_name: None,
+            splitting: false,
+            entry_names: "[name].js".to_string(),
+            chunk_names: "[name].js".to_string(),
+            legal_comments: LegalComments::default(),
+            metafile: false,
+            define: Vec::new(),
+            minify_syntax: false,
+            target: Target::default(),
+            assume_arrays: false,
+            ascii_only: false,
+        }
+    }
+}
+
+/// A file of the output, such as a chunk, the manifest or the metafile.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OutputFile {
+    /// The path of the file, relative to the output directory.
+    pub path: String,
+    pub contents: String,
+}
+
 pub struct Bundler {
     fs: Box<dyn FS>,
+    options: BundlerOptions,
+    /// The parsed values of the define option.
+    defines: HashMap<String, Expression>,
     // TODO: This queue should be processed in a async manner.
     queue: Vec<String>,
     files: Vec<File>,
+    /// Maps the path of every file to its index in files.
+    source_indices: HashMap<String, usize>,
+    entry_points: Vec<usize>,
+    /// The errors in the options and the files that could not be read or resolved,
+    /// they are reported when the bundle is linked.
+    errors: Vec<String>,
 }
 
 impl Bundler {
     pub fn new(fs: Box<dyn FS>) -> Bundler {
+        Bundler::with_options(fs, BundlerOptions::default())
+    }
+
+    pub fn with_options(fs: Box<dyn FS>, options: BundlerOptions) -> Bundler {
+        let (defines, errors) = match parse_defines(&options.define) {
+            Ok(defines) => (defines, Vec::new()),
+            Err(error) => (HashMap::new(), vec![error]),
+        };
         Bundler {
-            fs: fs,
+            fs,
+            defines,
+            options,
             queue: Vec::new(),
             files: Vec::new(),
+            source_indices: HashMap::new(),
+            entry_points: Vec::new(),
+            errors,
         }
     }
 
     pub fn scan(&mut self, entry_files: Vec<&str>) {
         for file_path in entry_files {
-            self.parse_file(file_path);
+            let source_index = self.enqueue(file_path);
+            self.entry_points.push(source_index);
         }
 
-        println!("{:?}", self.files);
+        while !self.queue.is_empty() {
+            let path = self.queue.remove(0);
+            self.parse_file(&path);
+        }
+    }
+
+    /// Removes the code that is never used by the entry points.
+    pub fn tree_shake(&mut self) {
+        tree_shaking::tree_shake(&mut self.files, &self.entry_points);
+    }
+
+    /// Joins all of the files into a single program in the format of the options.
+    /// Returns the errors in the options, the files that could not be read or resolved
+    /// and the syntax that could not be lowered.
+    pub fn link(&self) -> Result<AST, Vec<String>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.clone());
+        }
+        let mut ast = linker::link(&self.files, &self.entry_points, &self.options);
+        lower(&mut ast, self.lowering_options())?;
+        if self.options.minify_syntax {
+            minify(&mut ast);
+        }
+        Ok(ast)
+    }
+
+    /// Splits the bundle into chunks, one for each entry point and module imported with
+    /// `import()`, and shared chunks for the modules reached from several of them.
+    /// The chunks import each other so splitting is only done for the ESM format and
+    /// when enabled in the options, otherwise the output is a single file named after
+    /// the first entry point.
+    ///
+    /// The chunks are printed and named with the naming templates of the options. When
+    /// splitting, a manifest mapping the entry points to their files is included as
+    /// "manifest.json".
+    /// Returns the errors like `link`, and the errors of printing the chunks.
+    pub fn output_files(&self) -> Result<Vec<OutputFile>, Vec<String>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.clone());
+        }
+        let mut chunks = match self.options.format {
+            Format::ESM if self.options.splitting => {
+                linker::link_chunks(&self.files, &self.entry_points, &self.options)
+            }
+            _ => vec![linker::link_chunk(
+                &self.files,
+                &self.entry_points,
+                &self.options,
+            )],
+        };
+        let mut errors = Vec::new();
+        for chunk in chunks.iter_mut() {
+            if let Err(chunk_errors) = lower(&mut chunk.ast, self.lowering_options()) {
+                errors.extend(chunk_errors);
+            }
+            if self.options.minify_syntax {
+                minify(&mut chunk.ast);
+            }
+
+            // The inputs are only printed to measure them, the errors and
+            // the helpers belong to the chunk.
+            for (_, statements) in chunk.inputs.iter_mut() {
+                let mut ast = AST {
+                    statements: std::mem::take(statements),
+                };
+                let _ = lower_syntax_with_options(&mut ast, self.lowering_options());
+                if self.options.minify_syntax {
+                    minify(&mut ast);
+                }
+                *statements = ast.statements;
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        output::output_files(chunks, &self.files, &self.options)
+    }
+
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
+    fn lowering_options(&self) -> LoweringOptions {
+        LoweringOptions {
+            target: self.options.target,
+            assume_arrays: self.options.assume_arrays,
+        }
     }
 }
 
 impl Bundler {
+    /// Reserves an index for the file and adds it to the queue unless it has been seen before.
+    fn enqueue(&mut self, path: &str) -> usize {
+        if let Some(source_index) = self.source_indices.get(path) {
+            return *source_index;
+        }
+
+        let source_index = self.source_indices.len();
+        self.source_indices.insert(path.to_string(), source_index);
+        self.queue.push(path.to_string());
+        source_index
+    }
+
     fn parse_file(&mut self, path: &str) {
+        // The file is still added when it can not be read, so that the indices of the
+        // files stay the same, the error fails the build.
         let content = match self.fs.read_file(path) {
-            Ok(c) => c,
-            Err(_) => {
-                return;
+            Ok(content) => content,
+            Err(error) => {
+                self.errors
+                    .push(format!("Could not read file \"{}\": {}", path, error));
+                String::new()
             }
         };
 
         let logger = LoggerImpl::new();
         let lexer = Lexer::new(&content, &logger);
-        let ast = Parser::new(lexer, &logger).parse_program();
+        let mut ast = Parser::new(lexer, &logger).parse_program();
+        if !self.defines.is_empty() {
+            replace_globals(&mut ast, &self.defines);
+        }
+        if self.options.minify_syntax {
+            fold_constants(&mut ast);
+        }
+
+        let mut import_paths = Vec::new();
+        for statement in &ast.statements {
+            match statement {
+                Statement::ImportDeclaration(i) => {
+                    import_paths.push((i.source.value.clone(), ImportKind::Static, None))
+                }
+                Statement::ExportAllDeclaration(e) => {
+                    import_paths.push((e.source.value.clone(), ImportKind::Static, None))
+                }
+                Statement::ExportNamedSpecifiers(ExportNamedSpecifiers {
+                    source: Some(source),
+                    ..
+                }) => import_paths.push((source.value.clone(), ImportKind::Static, None)),
+                _ => {}
+            }
+        }
+        import_paths.extend(parts::import_calls(&mut ast));
 
-        // TODO: Look at the import records and push them to the queue.
+        let mut import_records: Vec<ImportRecord> = Vec::new();
+        for (import_path, kind, chunk_name) in import_paths {
+            if import_records
+                .iter()
+                .any(|r| r.import_path == import_path && r.kind == kind)
+            {
+                continue;
+            }
+
+            let source_index = resolver::resolve(self.fs.as_ref(), path, &import_path)
+                .map(|resolved| self.enqueue(&resolved));
+            // Only the bare import paths can be external modules.
+            if source_index.is_none() && resolver::is_relative(&import_path) {
+                self.errors.push(format!(
+                    "Could not resolve \"{}\" from \"{}\"",
+                    import_path, path
+                ));
+            }
+            import_records.push(ImportRecord {
+                import_path,
+                kind,
+                source_index,
+                chunk_name,
+            });
+        }
+
+        let side_effects =
+            resolver::package_side_effects(self.fs.as_ref(), path).has_side_effects(path);
 
         self.files.push(File {
+            path: path.to_string(),
+            size: content.len(),
             representation: FileRepresentation::JS(FileRepresentationJS { ast }),
+            import_records,
+            side_effects,
         });
+    }
+}
 
-        // Pop off the next item on the queue and process it.
-        let next_file = self.queue.pop();
-        if let Some(path) = next_file {
-            self.parse_file(&path);
+/// Lowers the syntax that the target does not support and declares the helpers
+/// used by the lowered code after the imports.
+fn lower(ast: &mut AST, options: LoweringOptions) -> Result<(), Vec<String>> {
+    match lower_syntax_with_options(ast, options) {
+        Ok(helpers) => {
+            insert_helpers(ast, &helpers);
+            Ok(())
         }
+        Err(errors) => Err(errors.iter().map(|error| error.to_string()).collect()),
     }
 }
+
+fn minify(ast: &mut AST) {
+    fold_constants(ast);
+    minify_syntax(ast);
+}
@@ -0,0 +1,1447 @@
+/// This file contains the linker, it joins all of the modules of the
+/// bundle into a single program.
+///
+/// ES modules are scope hoisted, their top level statements are placed
+/// directly in the top level scope of the bundle. Imports are replaced
+/// with references to the variables of the exporting module and top
+/// level variables that collide with each other, or with globals used
+/// by any module, are renamed.
+///
+/// Modules that can not be hoisted are wrapped in a function instead.
+/// CommonJS modules need their own `module` and `exports` variables and
+/// modules that call `eval` may reference their variables by name from
+/// the evaluated code, so their variables can not be renamed.
+///
+/// CommonJS modules are wrapped with the `__commonJS` helper which runs the
+/// module the first time it is required. Imports of CommonJS modules go
+/// through `__toESM` and `require` calls of ES modules through `__toCommonJS`,
+/// the helpers are only added to the bundle if they are used.
+///
+/// Finally the bundle is shaped by the format. The exports of the entry points
+/// are exported with `export { ... }` for ESM, assigned to `module.exports` for
+/// CommonJS and returned from the function wrapping the bundle for IIFE. The
+/// formats that can not contain import statements `require` the external
+/// modules instead.
+///
+/// With code splitting the modules are linked the same way, but instead of
+/// joining them into one program they are placed in chunks. Every entry point
+/// and every module imported with `import()` gets a chunk, modules reached from
+/// several of them are placed in shared chunks. Since the top level names are
+/// unique across all chunks, the chunks import the names they use from each
+/// other as they are.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use js_ast::scope::{rename_symbols, top_level_names, unbound_names};
+use js_ast::visit::{walk_expression, VisitorMut};
+use js_ast::*;
+
+use crate::parts::import_call;
+use crate::parts::{
+    analyze_module, ExportTarget, ImportBinding, ImportedName, ModuleInfo, DEFAULT_EXPORT,
+};
+use crate::runtime::{helper_statements, COMMON_JS, HELPERS, TO_COMMON_JS, TO_ESM};
+use crate::{BundlerOptions, File, FileRepresentation, Format, ImportKind};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ModuleKind {
+    /// Placed directly in the top level scope of the bundle.
+    Hoisted,
+    /// Wrapped in a function that receives `exports` and `module`.
+    CommonJS,
+    /// An ES module that uses `eval`, wrapped in a function that returns its exports.
+    WrappedESM,
+}
+
+/// What an imported name resolves to.
+#[derive(Debug, PartialEq, Clone)]
+enum ExportRef {
+    /// A top level variable of a hoisted module.
+    Symbol(usize, String),
+    /// A property of the exports object of a wrapped module.
+    Property(usize, String),
+    /// The namespace object of a module.
+    Namespace(usize),
+    /// An import of an external module that is required.
+    External(ImportBinding),
+    /// Nothing is exported with the name.
+    Missing,
+}
+
+struct Module {
+    kind: ModuleKind,
+    info: ModuleInfo,
+    ast: AST,
+    /// Used to generate the names of the variables created for the module.
+    base_name: String,
+    /// The names used by the module that are not declared by it.
+    globals: BTreeSet<String>,
+    /// The name of the variable holding the default export.
+    default_name: Option<String>,
+    /// The final names of the top level variables of the module.
+    names: HashMap<String, String>,
+    /// The variable holding the exports object of a wrapped module, the
+    /// namespace object of a hoisted module or the require function of a
+    /// CommonJS module.
+    exports_name: Option<String>,
+    /// The variable holding the namespace object of a CommonJS module
+    /// that is imported by an ES module.
+    import_name: Option<String>,
+}
+
+/// A chunk of the output, the import paths of other chunks are placeholders.
+pub struct Chunk {
+    /// The name of the chunk used by the naming templates.
+    pub name: String,
+    /// The path of the entry point the chunk was created for, None for
+    /// shared chunks and the chunks of modules imported with `import()`.
+    pub entry_point: Option<String>,
+    pub ast: AST,
+    /// The statements each module contributed to the chunk, by the path of the module.
+    pub inputs: Vec<(String, Vec<Statement>)>,
+}
+
+/// Returns the placeholder used for the path of the chunk until the paths are known.
+pub fn chunk_placeholder(chunk: usize) -> String {
+    format!("__PACKET_CHUNK_{}__", chunk)
+}
+
+/// Returns the chunk of a placeholder created by `chunk_placeholder`.
+pub fn placeholder_chunk(path: &str) -> Option<usize> {
+    path.strip_prefix("__PACKET_CHUNK_")?
+        .strip_suffix("__")?
+        .parse()
+        .ok()
+}
+
+/// The statements of a module after linking.
+struct LinkedModule {
+    source_index: usize,
+    /// The imports of external modules.
+    externals: Vec<Statement>,
+    statements: Vec<Statement>,
+}
+
+/// The contents of a chunk while the chunks are linked.
+#[derive(Default)]
+struct ChunkParts {
+    externals: Vec<Statement>,
+    statements: Vec<Statement>,
+    /// The chunks that have to run before this chunk, in the order they are imported.
+    dependencies: Vec<usize>,
+    /// The names imported from other chunks.
+    imports: BTreeMap<usize, BTreeSet<String>>,
+    /// The names used by other chunks.
+    exports: BTreeSet<String>,
+    helpers: Vec<&'static str>,
+    inputs: Vec<(String, Vec<Statement>)>,
+}
+
+pub fn link(files: &[File], entry_points: &[usize], options: &BundlerOptions) -> AST {
+    let mut linker = Linker::new(files, entry_points.to_vec(), options);
+    // The modules imported with import() run after the entry points.
+    linker.prepare(&chunk_roots(files, entry_points));
+    linker.link().ast
+}
+
+/// Links all of the modules into a single chunk, used for the formats that can not be split.
+pub fn link_chunk(files: &[File], entry_points: &[usize], options: &BundlerOptions) -> Chunk {
+    let mut linker = Linker::new(files, entry_points.to_vec(), options);
+    linker.prepare(&chunk_roots(files, entry_points));
+    linker.link()
+}
+
+pub fn link_chunks(files: &[File], entry_points: &[usize], options: &BundlerOptions) -> Vec<Chunk> {
+    // Every module imported with import() is an entry point of its own.
+    let roots = chunk_roots(files, entry_points);
+    let mut linker = Linker::new(files, roots.clone(), options);
+    linker.splitting = true;
+    linker.prepare(&roots);
+    linker.link_chunks(entry_points.len())
+}
+
+/// Returns the entry points followed by the modules imported with `import()`,
+/// in the order they are found when following the imports from the entry points.
+fn chunk_roots(files: &[File], entry_points: &[usize]) -> Vec<usize> {
+    let mut roots = entry_points.to_vec();
+    let mut visited = HashSet::new();
+    let mut index = 0;
+    while index < roots.len() {
+        let mut stack = vec![roots[index]];
+        while let Some(source_index) = stack.pop() {
+            if !visited.insert(source_index) {
+                continue;
+            }
+            for record in &files[source_index].import_records {
+                if let Some(import_index) = record.source_index {
+                    if record.kind != ImportKind::Dynamic {
+                        stack.push(import_index);
+                    } else if !roots.contains(&import_index) {
+                        roots.push(import_index);
+                    }
+                }
+            }
+        }
+        index += 1;
+    }
+    roots
+}
+
+struct Linker<'a> {
+    files: &'a [File],
+    /// The modules whose exports are exported from the bundle.
+    entry_points: Vec<usize>,
+    options: &'a BundlerOptions,
+    /// True if the modules are linked into chunks.
+    splitting: bool,
+    /// The order the modules are evaluated in, a module
+    /// always comes after the modules that it imports.
+    order: Vec<usize>,
+    modules: Vec<Module>,
+    /// What the imports of every module resolve to.
+    import_refs: Vec<Vec<(String, ExportRef)>>,
+    /// What the exports of every entry point resolve to.
+    entry_refs: Vec<Vec<(String, ExportRef)>>,
+    /// Names that can not be used for new top level variables.
+    taken: HashSet<String>,
+    /// Hoisted modules that are imported with `import * as` or required.
+    needs_namespace: BTreeSet<usize>,
+    /// CommonJS modules that are imported by ES modules,
+    /// these run at their place in the bundle.
+    imported_common_js: BTreeSet<usize>,
+    /// The variables holding the required external modules, by import path.
+    external_names: BTreeMap<String, String>,
+    /// The placeholders for the paths of the chunks of the entry points when splitting.
+    chunk_paths: HashMap<usize, String>,
+    /// The runtime helpers used by the bundle.
+    helpers: Vec<&'static str>,
+}
+
+fn create_module(file: &File) -> Module {
+    let FileRepresentation::JS(js) = &file.representation;
+    let mut ast = js.ast.clone();
+    let info = analyze_module(&mut ast);
+    let top_level: HashSet<String> = top_level_names(&ast).into_iter().collect();
+    let globals: BTreeSet<String> = unbound_names(&mut ast)
+        .into_iter()
+        .filter(|name| !top_level.contains(name))
+        .collect();
+
+    let has_esm_syntax = ast.statements.iter().any(|statement| {
+        matches!(
+            statement,
+            Statement::ImportDeclaration(_)
+                | Statement::ExportAllDeclaration(_)
+                | Statement::ExportDefaultDeclaration(_)
+                | Statement::ExportNamedDeclaration(_)
+                | Statement::ExportNamedSpecifiers(_)
+        )
+    });
+    let kind = if !has_esm_syntax
+        && ["module", "exports", "require"]
+            .iter()
+            .any(|name| globals.contains(*name))
+    {
+        ModuleKind::CommonJS
+    } else if globals.contains("eval") {
+        ModuleKind::WrappedESM
+    } else {
+        ModuleKind::Hoisted
+    };
+
+    let base_name = module_name(&file.path);
+    let mut module = Module {
+        kind,
+        info,
+        ast,
+        globals,
+        default_name: None,
+        names: HashMap::new(),
+        exports_name: None,
+        import_name: None,
+        base_name,
+    };
+    convert_exports(&mut module, &top_level);
+    module
+}
+
+/// Replaces the export declarations with the declarations they export,
+/// the default export is stored in a variable if it has no name.
+fn convert_exports(module: &mut Module, top_level: &HashSet<String>) {
+    let mut default_name = format!("{}_default", module.base_name);
+    let mut index = 2;
+    while top_level.contains(&default_name) || module.globals.contains(&default_name) {
+        default_name = format!("{}_default{}", module.base_name, index);
+        index += 1;
+    }
+
+    let statements = std::mem::take(&mut module.ast.statements);
+    for statement in statements {
+        let statement = match statement {
+            Statement::ExportNamedDeclaration(e) => match e.declaration {
+                ExportNamedDeclarationKind::FunctionDeclaration(f) => {
+                    Statement::FunctionDeclaration(f)
+                }
+                ExportNamedDeclarationKind::ClassDeclaration(c) => Statement::ClassDeclaration(c),
+                ExportNamedDeclarationKind::VariableDeclaration(v) => {
+                    Statement::VariableDeclaration(v)
+                }
+            },
+
+            Statement::ExportDefaultDeclaration(e) => match e.declaration {
+                ExportDefaultDeclarationKind::FunctionDeclaration(f) => {
+                    module.default_name = Some(f.identifier.name.clone());
+                    Statement::FunctionDeclaration(f)
+                }
+                ExportDefaultDeclarationKind::ClassDeclaration(c) => {
+                    module.default_name = Some(c.identifier.name.clone());
+                    Statement::ClassDeclaration(c)
+                }
+                ExportDefaultDeclarationKind::AnonymousDefaultExportedFunctionDeclaration(f) => {
+                    module.default_name = Some(default_name.clone());
+                    Statement::FunctionDeclaration(FunctionDeclaration {
+                        identifier: Identifier {
+                            name: default_name.clone(),
+                        },
+                        parameters: f.parameters,
+                        body: f.body,
+                        generator: f.generator,
+                        is_async: f.is_async,
+                        no_side_effects: false,
+                    })
+                }
+                ExportDefaultDeclarationKind::AnonymousDefaultExportedClassDeclaration(c) => {
+                    module.default_name = Some(default_name.clone());
+                    Statement::ClassDeclaration(ClassDeclaration {
+                        identifier: Identifier {
+                            name: default_name.clone(),
+                        },
+                        extends: c.extends,
+                        body: c.body,
+                    })
+                }
+                ExportDefaultDeclarationKind::Expression(expression) => {
+                    module.default_name = Some(default_name.clone());
+                    Statement::VariableDeclaration(VariableDeclaration {
+                        kind: VariableDeclarationKind::Var,
+                        declarations: vec![VariableDeclarator {
+                            binding: Binding::Identifier(Identifier {
+                                name: default_name.clone(),
+                            }),
+                            initializer: Some(expression),
+                        }],
+                    })
+                }
+            },
+
+            statement => statement,
+        };
+        module.ast.statements.push(statement);
+    }
+}
+
+impl<'a> Linker<'a> {
+    fn new(files: &'a [File], entry_points: Vec<usize>, options: &'a BundlerOptions) -> Self {
+        Linker {
+            files,
+            entry_points,
+            options,
+            splitting: false,
+            order: Vec::new(),
+            modules: files.iter().map(create_module).collect(),
+            import_refs: Vec::new(),
+            entry_refs: Vec::new(),
+            taken: HashSet::new(),
+            needs_namespace: BTreeSet::new(),
+            imported_common_js: BTreeSet::new(),
+            external_names: BTreeMap::new(),
+            chunk_paths: HashMap::new(),
+            helpers: Vec::new(),
+        }
+    }
+
+    /// Orders the modules, resolves all of the imports and names the variables.
+    fn prepare(&mut self, roots: &[usize]) {
+        let mut visited = HashSet::new();
+        for root in roots {
+            self.visit(*root, &mut visited);
+        }
+
+        // Resolve all of the imports up front since the names given
+        // to the modules depend on which namespace objects are needed.
+        let mut import_refs: Vec<Vec<(String, ExportRef)>> = Vec::new();
+        for source_index in 0..self.modules.len() {
+            let mut refs = Vec::new();
+            for (local, binding) in &self.modules[source_index].info.imports {
+                if let Some(export_ref) = self.resolve_binding(source_index, binding) {
+                    refs.push((local.clone(), export_ref));
+                }
+            }
+            import_refs.push(refs);
+        }
+        let entry_refs: Vec<Vec<(String, ExportRef)>> = self
+            .entry_points
+            .iter()
+            .map(|entry_point| {
+                self.export_names(*entry_point, &mut Vec::new())
+                    .into_iter()
+                    .map(|name| {
+                        let export_ref = self.resolve_export(*entry_point, &name, &mut Vec::new());
+                        (name, export_ref)
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut external_paths = BTreeSet::new();
+        for (_, export_ref) in import_refs.iter().chain(entry_refs.iter()).flatten() {
+            match export_ref {
+                ExportRef::Namespace(source_index)
+                    if self.modules[*source_index].kind == ModuleKind::Hoisted =>
+                {
+                    self.needs_namespace.insert(*source_index);
+                }
+                ExportRef::External(binding) => {
+                    external_paths.insert(binding.import_path.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let files = self.files;
+        for source_index in self.order.clone() {
+            let is_common_js = self.modules[source_index].kind == ModuleKind::CommonJS;
+            if is_common_js {
+                self.use_helper(COMMON_JS);
+            }
+            for record in &files[source_index].import_records {
+                let import_index = match record.source_index {
+                    Some(import_index) => import_index,
+                    None => continue,
+                };
+                let import_kind = self.modules[import_index].kind;
+                match record.kind {
+                    ImportKind::Static => {
+                        if import_kind == ModuleKind::CommonJS && !is_common_js {
+                            self.imported_common_js.insert(import_index);
+                        }
+                    }
+                    ImportKind::Require => {
+                        if import_kind != ModuleKind::CommonJS {
+                            self.use_helper(TO_COMMON_JS);
+                            if import_kind == ModuleKind::Hoisted {
+                                self.needs_namespace.insert(import_index);
+                            }
+                        }
+                    }
+                    // Without code splitting import() resolves to the namespace object.
+                    ImportKind::Dynamic if !self.splitting => match import_kind {
+                        ModuleKind::Hoisted => {
+                            self.needs_namespace.insert(import_index);
+                        }
+                        ModuleKind::CommonJS => self.use_helper(TO_ESM),
+                        ModuleKind::WrappedESM => {}
+                    },
+                    ImportKind::Dynamic => {}
+                }
+            }
+        }
+
+        self.assign_names(&import_refs);
+        for import_path in external_paths {
+            let name = self.assign_name(&format!("import_{}", module_name(&import_path)));
+            self.external_names.insert(import_path, name);
+        }
+        self.import_refs = import_refs;
+        self.entry_refs = entry_refs;
+    }
+
+    /// Links the modules in the order they are evaluated in.
+    fn link_modules(&mut self) -> Vec<LinkedModule> {
+        let order = self.order.clone();
+        order
+            .into_iter()
+            .map(|source_index| self.link_module(source_index))
+            .collect()
+    }
+
+    /// Renames the variables of the module, replaces its imports and wraps it if needed.
+    fn link_module(&mut self, source_index: usize) -> LinkedModule {
+        let mut renames = HashMap::new();
+        for (local, export_ref) in &self.import_refs[source_index] {
+            renames.insert(local.clone(), self.ref_expression(export_ref));
+        }
+        for (local, name) in &self.modules[source_index].names {
+            renames.insert(local.clone(), identifier(name));
+        }
+
+        let mut ast = std::mem::replace(
+            &mut self.modules[source_index].ast,
+            AST {
+                statements: Vec::new(),
+            },
+        );
+        rename_symbols(&mut ast, &renames);
+        let mut rewriter = ImportCallRewriter {
+            replacements: self.import_call_replacements(source_index),
+        };
+        rewriter.visit_program(&mut ast);
+        let mut externals = Vec::new();
+        let body = self.remove_import_statements(source_index, ast.statements, &mut externals);
+        let mut statements = Vec::new();
+
+        let module = &self.modules[source_index];
+        match module.kind {
+            ModuleKind::Hoisted => {
+                if let Some(exports_name) = &module.exports_name {
+                    statements.push(var(
+                        exports_name,
+                        self.namespace_object(source_index, &renames),
+                    ));
+                }
+                statements.extend(body);
+            }
+
+            ModuleKind::CommonJS => {
+                let require_name = module.exports_name.clone().unwrap_or_default();
+                statements.push(var(
+                    &require_name,
+                    call(
+                        identifier(COMMON_JS),
+                        vec![function(vec!["exports", "module"], body)],
+                    ),
+                ));
+
+                // An imported CommonJS module runs where it is imported.
+                let require = call(identifier(&require_name), vec![]);
+                if let Some(import_name) = &module.import_name {
+                    statements.push(var(import_name, call(identifier(TO_ESM), vec![require])));
+                } else if self.imported_common_js.contains(&source_index) {
+                    statements.push(Statement::Expression(ExpressionStatement {
+                        expression: require,
+                    }));
+                }
+            }
+
+            ModuleKind::WrappedESM => {
+                let mut body = body;
+                body.push(return_statement(
+                    self.namespace_object(source_index, &renames),
+                ));
+                let exports_name = module.exports_name.clone().unwrap_or_default();
+                statements.push(var(&exports_name, call(function(vec![], body), vec![])));
+            }
+        }
+        LinkedModule {
+            source_index,
+            externals,
+            statements,
+        }
+    }
+
+    /// Returns the exports of the entry point and, for a CommonJS entry point, the call
+    /// running it. CommonJS entry points export whatever they assign to module.exports.
+    fn entry_exports(&self, entry_index: usize) -> (Vec<(String, Expression)>, Option<Expression>) {
+        let module = &self.modules[self.entry_points[entry_index]];
+        let common_js_entry = (module.kind == ModuleKind::CommonJS).then(|| {
+            let require_name = module.exports_name.as_deref().unwrap_or_default();
+            call(identifier(require_name), vec![])
+        });
+        let exports = self.entry_refs[entry_index]
+            .iter()
+            .map(|(name, export_ref)| (name.clone(), self.ref_expression(export_ref)))
+            .collect();
+        (exports, common_js_entry)
+    }
+
+    /// Joins all of the modules into a single chunk in the format of the options.
+    fn link(&mut self) -> Chunk {
+        let mut externals = Vec::new();
+        let mut statements = Vec::new();
+        let mut inputs = Vec::new();
+        for module in self.link_modules() {
+            let path = self.files[module.source_index].path.clone();
+            inputs.push((path, module.statements.clone()));
+            externals.extend(module.externals);
+            statements.extend(module.statements);
+        }
+
+        // CommonJS entry points are run at the end of the bundle.
+        let mut entry_exports = Vec::new();
+        let mut common_js_entry = None;
+        for entry_index in 0..self.entry_points.len() {
+            let (exports, require) = self.entry_exports(entry_index);
+            entry_exports.extend(exports);
+            common_js_entry = common_js_entry.or(require);
+        }
+
+        match self.options.format {
+            Format::ESM => statements.extend(self.esm_exports(entry_exports, common_js_entry)),
+
+            Format::CommonJS => {
+                let exports = match common_js_entry {
+                    Some(require) => Some(require),
+                    None if !entry_exports.is_empty() => {
+                        self.use_helper(TO_COMMON_JS);
+                        let namespace = getters_object(entry_exports);
+                        Some(call(identifier(TO_COMMON_JS), vec![namespace]))
+                    }
+                    None => None,
+                };
+                if let Some(exports) = exports {
+                    let module_exports = member(identifier("module"), "exports");
+                    statements.push(expression_statement(assign(module_exports, exports)));
+                }
+            }
+
+            // The exports are returned from the function wrapping the bundle,
+            // they are only needed if they are assigned to the global name.
+            Format::IIFE => match (&self.options.global_name, common_js_entry) {
+                (Some(_), Some(require)) => statements.push(return_statement(require)),
+                (Some(_), None) if !entry_exports.is_empty() => {
+                    statements.push(return_statement(getters_object(entry_exports)))
+                }
+                (None, Some(require)) => statements.push(expression_statement(require)),
+                _ => {}
+            },
+        }
+
+        // The imports of external modules come first. The formats that can not contain
+        // import statements require them instead, after the helpers that they use.
+        let mut program = Vec::new();
+        if self.options.format == Format::ESM {
+            program.extend(externals);
+            program.extend(helper_statements(&self.helpers));
+        } else {
+            let externals = self.require_externals(externals);
+            program.extend(helper_statements(&self.helpers));
+            program.extend(externals);
+        }
+        program.extend(statements);
+
+        if self.options.format == Format::IIFE {
+            let iife = call(function(vec![], program), vec![]);
+            program = match &self.options.global_name {
+                Some(global_name) => assign_global(global_name, iife),
+                None => vec![expression_statement(iife)],
+            };
+        }
+        // The chunk is named after the first entry point.
+        let entry_point = self.entry_points.first().map(|e| &self.files[*e].path);
+        Chunk {
+            name: entry_point.map_or("bundle".to_string(), |path| module_name(path)),
+            entry_point: entry_point.cloned(),
+            ast: AST {
+                statements: program,
+            },
+            inputs,
+        }
+    }
+
+    /// Places the modules in chunks, every chunk is a program in the ESM format.
+    fn link_chunks(&mut self, user_entry_points: usize) -> Vec<Chunk> {
+        let files = self.files;
+
+        // The entry points that reach each of the modules without going through import().
+        let mut reached_by = vec![Vec::new(); files.len()];
+        for (entry_index, entry_point) in self.entry_points.iter().enumerate() {
+            let mut visited = HashSet::new();
+            let mut stack = vec![*entry_point];
+            while let Some(source_index) = stack.pop() {
+                if !visited.insert(source_index) {
+                    continue;
+                }
+                reached_by[source_index].push(entry_index);
+                for record in &files[source_index].import_records {
+                    if let (Some(import_index), false) =
+                        (record.source_index, record.kind == ImportKind::Dynamic)
+                    {
+                        stack.push(import_index);
+                    }
+                }
+            }
+        }
+
+        // The chunks of the entry points come first, followed by a shared chunk for each
+        // set of entry points reaching the same modules. The chunks only depend on the
+        // module graph so the same modules always end up in the same chunks.
+        let mut chunk_keys: Vec<Vec<usize>> =
+            (0..self.entry_points.len()).map(|i| vec![i]).collect();
+        let shared_keys: BTreeSet<&Vec<usize>> = self
+            .order
+            .iter()
+            .map(|source_index| &reached_by[*source_index])
+            .filter(|key| key.len() > 1)
+            .collect();
+        chunk_keys.extend(shared_keys.into_iter().cloned());
+        let chunk_of: HashMap<usize, usize> = self
+            .order
+            .iter()
+            .map(|source_index| {
+                let key = &reached_by[*source_index];
+                let chunk = chunk_keys.iter().position(|k| k == key).unwrap_or_default();
+                (*source_index, chunk)
+            })
+            .collect();
+
+        let mut names: Vec<String> = Vec::new();
+        for chunk in 0..chunk_keys.len() {
+            let base_name = match self.entry_points.get(chunk) {
+                Some(entry_point) if chunk >= user_entry_points => chunk_name(files, *entry_point)
+                    .unwrap_or_else(|| module_name(&files[*entry_point].path)),
+                Some(entry_point) => module_name(&files[*entry_point].path),
+                None => "chunk".to_string(),
+            };
+            let mut name = base_name.clone();
+            let mut index = 2;
+            while names.contains(&name) {
+                name = format!("{}{}", base_name, index);
+                index += 1;
+            }
+            names.push(name);
+        }
+        for (entry_index, entry_point) in self.entry_points.iter().enumerate() {
+            self.chunk_paths
+                .insert(*entry_point, chunk_placeholder(entry_index));
+        }
+
+        let mut chunks: Vec<ChunkParts> =
+            chunk_keys.iter().map(|_| ChunkParts::default()).collect();
+        for module in self.link_modules() {
+            let chunk = chunk_of[&module.source_index];
+            let parts = &mut chunks[chunk];
+            let path = files[module.source_index].path.clone();
+            parts.inputs.push((path, module.statements.clone()));
+            parts.externals.extend(module.externals);
+            parts.statements.extend(module.statements);
+            for record in &files[module.source_index].import_records {
+                if let (Some(import_index), false) =
+                    (record.source_index, record.kind == ImportKind::Dynamic)
+                {
+                    let dependency = chunk_of[&import_index];
+                    if dependency != chunk && !parts.dependencies.contains(&dependency) {
+                        parts.dependencies.push(dependency);
+                    }
+                }
+            }
+        }
+
+        // The entry point may be in a shared chunk, its chunk then only re-exports it.
+        for (entry_index, entry_point) in self.entry_points.clone().into_iter().enumerate() {
+            let dependency = chunk_of[&entry_point];
+            if dependency != entry_index && !chunks[entry_index].dependencies.contains(&dependency)
+            {
+                chunks[entry_index].dependencies.push(dependency);
+            }
+            let (exports, common_js_entry) = self.entry_exports(entry_index);
+            let statements = self.esm_exports(exports, common_js_entry);
+            chunks[entry_index].statements.extend(statements);
+        }
+
+        // The names used by a chunk that are declared by another chunk are imported from it.
+        let mut declared_by = HashMap::new();
+        for (chunk, parts) in chunks.iter().enumerate() {
+            let statements = parts.externals.iter().chain(&parts.statements).cloned();
+            for name in top_level_names(&AST {
+                statements: statements.collect(),
+            }) {
+                declared_by.insert(name, chunk);
+            }
+        }
+        for chunk in 0..chunks.len() {
+            let mut ast = AST {
+                statements: chunks[chunk].statements.clone(),
+            };
+            for name in unbound_names(&mut ast) {
+                match declared_by.get(&name) {
+                    Some(declaring_chunk) if *declaring_chunk != chunk => {
+                        let parts = &mut chunks[chunk];
+                        if !parts.dependencies.contains(declaring_chunk) {
+                            parts.dependencies.push(*declaring_chunk);
+                        }
+                        parts
+                            .imports
+                            .entry(*declaring_chunk)
+                            .or_default()
+                            .insert(name.clone());
+                        chunks[*declaring_chunk].exports.insert(name);
+                    }
+                    // Every chunk declares the helpers that it uses.
+                    None => {
+                        if let Some(helper) = HELPERS.iter().find(|helper| **helper == name) {
+                            chunks[chunk].helpers.push(helper);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut output = Vec::new();
+        for (chunk, parts) in chunks.into_iter().enumerate() {
+            let mut statements = parts.externals;
+            for dependency in &parts.dependencies {
+                let names = parts.imports.get(dependency).cloned().unwrap_or_default();
+                statements.push(Statement::ImportDeclaration(ImportDeclaration {
+                    default: None,
+                    namespace: None,
+                    specifiers: names
+                        .into_iter()
+                        .map(|name| ImportDeclarationSpecifier {
+                            local: Identifier { name: name.clone() },
+                            imported: Identifier { name },
+                        })
+                        .collect(),
+                    source: StringLiteral {
+                        value: chunk_placeholder(*dependency),
+                    },
+                }));
+            }
+            statements.extend(helper_statements(&parts.helpers));
+            statements.extend(parts.statements);
+            if !parts.exports.is_empty() {
+                statements.push(Statement::ExportNamedSpecifiers(ExportNamedSpecifiers {
+                    specifiers: parts
+                        .exports
+                        .into_iter()
+                        .map(|name| ExportNamedSpecifier {
+                            local: Identifier { name: name.clone() },
+                            exported: Identifier { name },
+                        })
+                        .collect(),
+                    source: None,
+                }));
+            }
+
+            output.push(Chunk {
+                name: names[chunk].clone(),
+                entry_point: self.entry_points[..user_entry_points]
+                    .get(chunk)
+                    .map(|entry_point| files[*entry_point].path.clone()),
+                ast: AST { statements },
+                inputs: parts.inputs,
+            });
+        }
+        output
+    }
+
+    /// Exports the exports of the entry points with an export statement, exports that
+    /// are not variables are stored in a variable first since only those can be exported.
+    fn esm_exports(
+        &mut self,
+        entry_exports: Vec<(String, Expression)>,
+        common_js_entry: Option<Expression>,
+    ) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        if let Some(require) = common_js_entry {
+            statements.push(Statement::ExportDefaultDeclaration(
+                ExportDefaultDeclaration {
+                    declaration: ExportDefaultDeclarationKind::Expression(require),
+                },
+            ));
+            return statements;
+        }
+
+        let mut specifiers = Vec::new();
+        for (name, expression) in entry_exports {
+            let local = match expression {
+                Expression::Identifier(i) => i.name,
+                expression => {
+                    let alias = self.assign_name(&name);
+                    statements.push(var(&alias, expression));
+                    alias
+                }
+            };
+            specifiers.push(ExportNamedSpecifier {
+                local: Identifier { name: local },
+                exported: Identifier { name },
+            });
+        }
+        if !specifiers.is_empty() {
+            statements.push(Statement::ExportNamedSpecifiers(ExportNamedSpecifiers {
+                specifiers,
+                source: None,
+            }));
+        }
+        statements
+    }
+
+    /// Replaces the import statements of external modules with `require` calls,
+    /// every external module is required once. Used by the formats that can not
+    /// contain import statements.
+    fn require_externals(&mut self, externals: Vec<Statement>) -> Vec<Statement> {
+        let mut required = HashSet::new();
+        let mut statements = Vec::new();
+        for statement in externals {
+            let import_path = match statement {
+                Statement::ImportDeclaration(i) => i.source.value,
+                Statement::ExportAllDeclaration(e) => e.source.value,
+                Statement::ExportNamedSpecifiers(ExportNamedSpecifiers {
+                    source: Some(source),
+                    ..
+                }) => source.value,
+                _ => continue,
+            };
+            if !required.insert(import_path.clone()) {
+                continue;
+            }
+
+            let require = call(identifier("require"), vec![string(&import_path)]);
+            match self.external_names.get(&import_path).cloned() {
+                Some(name) => {
+                    self.use_helper(TO_ESM);
+                    statements.push(var(&name, call(identifier(TO_ESM), vec![require])));
+                }
+                None => statements.push(expression_statement(require)),
+            }
+        }
+        statements
+    }
+
+    fn use_helper(&mut self, helper: &'static str) {
+        if !self.helpers.contains(&helper) {
+            self.helpers.push(helper);
+        }
+    }
+
+    /// Returns the expressions that replace the `require` and `import()` calls of the module.
+    fn import_call_replacements(
+        &self,
+        source_index: usize,
+    ) -> HashMap<(String, ImportKind), Expression> {
+        let mut replacements = HashMap::new();
+        for record in &self.files[source_index].import_records {
+            let import_index = match record.source_index {
+                Some(import_index) => import_index,
+                None => continue,
+            };
+            let module = &self.modules[import_index];
+            let exports_name = module.exports_name.as_deref().unwrap_or_default();
+            let replacement = match (record.kind, module.kind) {
+                (ImportKind::Static, _) => continue,
+                (ImportKind::Require, ModuleKind::CommonJS) => {
+                    call(identifier(exports_name), vec![])
+                }
+                (ImportKind::Require, _) => {
+                    call(identifier(TO_COMMON_JS), vec![identifier(exports_name)])
+                }
+                // The chunk of the module exports the exports of the module.
+                (ImportKind::Dynamic, _) if self.splitting => {
+                    let chunk_path = &self.chunk_paths[&import_index];
+                    Expression::Import(ImportExpression {
+                        source: Box::new(string(chunk_path)),
+                        chunk_name: None,
+                    })
+                }
+                // The module is already in the bundle,
+                // `Promise.resolve().then(function() { return namespace; })`
+                (ImportKind::Dynamic, kind) => {
+                    let namespace = match kind {
+                        ModuleKind::CommonJS => call(
+                            identifier(TO_ESM),
+                            vec![call(identifier(exports_name), vec![])],
+                        ),
+                        _ => identifier(exports_name),
+                    };
+                    let promise = call(member(identifier("Promise"), "resolve"), vec![]);
+                    call(
+                        member(promise, "then"),
+                        vec![function(vec![], vec![return_statement(namespace)])],
+                    )
+                }
+            };
+            replacements.insert((record.import_path.clone(), record.kind), replacement);
+        }
+        replacements
+    }
+
+    /// Adds the module and the modules it imports to the evaluation order.
+    fn visit(&mut self, source_index: usize, visited: &mut HashSet<usize>) {
+        if !visited.insert(source_index) {
+            return;
+        }
+        let files = self.files;
+        for record in &files[source_index].import_records {
+            // Modules imported with import() are visited as roots of their own.
+            if let (Some(import_index), false) =
+                (record.source_index, record.kind == ImportKind::Dynamic)
+            {
+                self.visit(import_index, visited);
+            }
+        }
+        self.order.push(source_index);
+    }
+
+    fn assign_names(&mut self, import_refs: &[Vec<(String, ExportRef)>]) {
+        self.taken
+            .extend(HELPERS.iter().map(|helper| helper.to_string()));
+        for module in &self.modules {
+            self.taken.extend(module.globals.iter().cloned());
+            if module.kind != ModuleKind::Hoisted {
+                self.taken.extend(top_level_names(&module.ast));
+            }
+        }
+
+        for source_index in self.order.clone() {
+            let kind = self.modules[source_index].kind;
+            let imported: HashSet<&String> = import_refs[source_index]
+                .iter()
+                .map(|(local, _)| local)
+                .collect();
+
+            // The variables of wrapped modules are never renamed, the names used
+            // by the hoisted modules are chosen to not collide with them.
+            let mut names = HashMap::new();
+            if kind == ModuleKind::Hoisted {
+                for name in top_level_names(&self.modules[source_index].ast) {
+                    if imported.contains(&name) || names.contains_key(&name) {
+                        continue;
+                    }
+                    let assigned = self.assign_name(&name);
+                    names.insert(name, assigned);
+                }
+            }
+
+            let base_name = self.modules[source_index].base_name.clone();
+            let exports_name = match kind {
+                ModuleKind::Hoisted if !self.needs_namespace.contains(&source_index) => None,
+                ModuleKind::CommonJS => Some(self.assign_name(&format!("require_{}", base_name))),
+                _ => Some(self.assign_name(&format!("{}_exports", base_name))),
+            };
+            let is_referenced = import_refs.iter().flatten().any(|(_, export_ref)| {
+                matches!(export_ref, ExportRef::Property(i, _) | ExportRef::Namespace(i) if *i == source_index)
+            });
+            let import_name = if kind == ModuleKind::CommonJS
+                && self.imported_common_js.contains(&source_index)
+                && is_referenced
+            {
+                self.use_helper(TO_ESM);
+                Some(self.assign_name(&format!("import_{}", base_name)))
+            } else {
+                None
+            };
+
+            let module = &mut self.modules[source_index];
+            module.names = names;
+            module.exports_name = exports_name;
+            module.import_name = import_name;
+        }
+    }
+
+    /// Returns the name if it is free, otherwise a number is appended to it.
+    fn assign_name(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut index = 2;
+        while self.taken.contains(&candidate) {
+            candidate = format!("{}{}", name, index);
+            index += 1;
+        }
+        self.taken.insert(candidate.clone());
+        candidate
+    }
+
+    /// Removes the import and export statements, the ones that
+    /// import external modules are moved to the top of the bundle.
+    fn remove_import_statements(
+        &self,
+        source_index: usize,
+        statements: Vec<Statement>,
+        externals: &mut Vec<Statement>,
+    ) -> Vec<Statement> {
+        let is_external =
+            |import_path: &str| self.source_index(source_index, import_path).is_none();
+
+        let mut body = Vec::new();
+        for statement in statements {
+            match &statement {
+                Statement::ImportDeclaration(i) => {
+                    if is_external(&i.source.value) {
+                        externals.push(statement);
+                    }
+                }
+                Statement::ExportAllDeclaration(e) => {
+                    if is_external(&e.source.value) {
+                        externals.push(statement);
+                    }
+                }
+                Statement::ExportNamedSpecifiers(e) => {
+                    if e.source.as_ref().is_some_and(|s| is_external(&s.value)) {
+                        externals.push(statement);
+                    }
+                }
+                _ => body.push(statement),
+            }
+        }
+        body
+    }
+
+    /// Creates an object with a getter for every export of the module.
+    fn namespace_object(
+        &self,
+        source_index: usize,
+        renames: &HashMap<String, Expression>,
+    ) -> Expression {
+        let module = &self.modules[source_index];
+        let mut getters = Vec::new();
+        for name in self.export_names(source_index, &mut Vec::new()) {
+            let value = match module.info.exports.get(&name) {
+                // The local variables of wrapped modules are
+                // referenced directly from inside of the wrapper.
+                Some(ExportTarget::Local(local)) if module.kind == ModuleKind::WrappedESM => {
+                    let local = if local == DEFAULT_EXPORT {
+                        module.default_name.clone().unwrap_or_default()
+                    } else {
+                        local.clone()
+                    };
+                    renames
+                        .get(&local)
+                        .cloned()
+                        .unwrap_or_else(|| identifier(&local))
+                }
+                _ => {
+                    let export_ref =
+                        self.resolve_export_hoisted(source_index, &name, &mut Vec::new());
+                    self.ref_expression(&export_ref)
+                }
+            };
+            getters.push((name, value));
+        }
+        getters_object(getters)
+    }
+
+    fn resolve_binding(&self, source_index: usize, binding: &ImportBinding) -> Option<ExportRef> {
+        let import_index = match self.source_index(source_index, &binding.import_path) {
+            Some(import_index) => import_index,
+            // External modules are imported as they are with the ESM format.
+            None if self.options.format == Format::ESM => return None,
+            None => return Some(ExportRef::External(binding.clone())),
+        };
+        Some(match &binding.name {
+            ImportedName::Name(name) => self.resolve_export(import_index, name, &mut Vec::new()),
+            ImportedName::Namespace => ExportRef::Namespace(import_index),
+        })
+    }
+
+    fn resolve_export(
+        &self,
+        source_index: usize,
+        name: &str,
+        visited: &mut Vec<usize>,
+    ) -> ExportRef {
+        match self.modules[source_index].kind {
+            ModuleKind::CommonJS => ExportRef::Property(source_index, name.to_string()),
+            ModuleKind::WrappedESM => {
+                if self
+                    .export_names(source_index, &mut Vec::new())
+                    .contains(name)
+                {
+                    ExportRef::Property(source_index, name.to_string())
+                } else {
+                    ExportRef::Missing
+                }
+            }
+            ModuleKind::Hoisted => self.resolve_export_hoisted(source_index, name, visited),
+        }
+    }
+
+    /// Follows the exports of the module as if it was hoisted, re-exports
+    /// are followed to the module that declares the exported variable.
+    fn resolve_export_hoisted(
+        &self,
+        source_index: usize,
+        name: &str,
+        visited: &mut Vec<usize>,
+    ) -> ExportRef {
+        if visited.contains(&source_index) {
+            return ExportRef::Missing;
+        }
+        visited.push(source_index);
+
+        let info = &self.modules[source_index].info;
+        match info.exports.get(name) {
+            Some(ExportTarget::Local(local)) => match info.imports.get(local) {
+                Some(binding) => self
+                    .resolve_binding(source_index, binding)
+                    .unwrap_or_else(|| ExportRef::Symbol(source_index, local.clone())),
+                None => ExportRef::Symbol(source_index, local.clone()),
+            },
+            Some(ExportTarget::ReExport(binding)) => self
+                .resolve_binding(source_index, binding)
+                .unwrap_or(ExportRef::Missing),
+            None => {
+                // The default export is never re-exported by export *.
+                if name == DEFAULT_EXPORT {
+                    return ExportRef::Missing;
+                }
+                for import_path in &info.star_exports {
+                    if let Some(import_index) = self.source_index(source_index, import_path) {
+                        let export_ref = self.resolve_export(import_index, name, visited);
+                        if export_ref != ExportRef::Missing {
+                            return export_ref;
+                        }
+                    }
+                }
+                ExportRef::Missing
+            }
+        }
+    }
+
+    /// Returns all of the names exported by the module, including the ones from export *.
+    fn export_names(&self, source_index: usize, visited: &mut Vec<usize>) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        if visited.contains(&source_index) {
+            return names;
+        }
+        visited.push(source_index);
+
+        let info = &self.modules[source_index].info;
+        names.extend(info.exports.keys().cloned());
+        for import_path in &info.star_exports {
+            if let Some(import_index) = self.source_index(source_index, import_path) {
+                for name in self.export_names(import_index, visited) {
+                    if name != DEFAULT_EXPORT {
+                        names.insert(name);
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Returns the expression that references the export.
+    fn ref_expression(&self, export_ref: &ExportRef) -> Expression {
+        match export_ref {
+            ExportRef::Symbol(source_index, local) => {
+                let module = &self.modules[*source_index];
+                let local = if local == DEFAULT_EXPORT {
+                    module.default_name.as_ref().unwrap_or(local)
+                } else {
+                    local
+                };
+                identifier(module.names.get(local).unwrap_or(local))
+            }
+            ExportRef::Property(source_index, name) => {
+                member(self.exports_expression(*source_index), name)
+            }
+            ExportRef::Namespace(source_index) => self.exports_expression(*source_index),
+            ExportRef::External(binding) => {
+                let name = &self.external_names[&binding.import_path];
+                match &binding.name {
+                    ImportedName::Name(imported) => member(identifier(name), imported),
+                    ImportedName::Namespace => identifier(name),
+                }
+            }
+            ExportRef::Missing => Expression::Unary(UnaryExpression {
+                operator: UnaryExpressionOperator::Void,
+                argument: Box::new(Expression::NumericLiteral(NumericLiteral { value: 0. })),
+            }),
+        }
+    }
+
+    fn exports_expression(&self, source_index: usize) -> Expression {
+        let module = &self.modules[source_index];
+        match module.kind {
+            ModuleKind::CommonJS => identifier(module.import_name.as_deref().unwrap_or_default()),
+            _ => identifier(module.exports_name.as_deref().unwrap_or_default()),
+        }
+    }
+
+    fn source_index(&self, source_index: usize, import_path: &str) -> Option<usize> {
+        self.files[source_index]
+            .import_records
+            .iter()
+            .find(|r| r.import_path == import_path)
+            .and_then(|r| r.source_index)
+    }
+}
+
+/// Replaces `require` and `import()` calls with the expressions for the imported modules.
+struct ImportCallRewriter {
+    replacements: HashMap<(String, ImportKind), Expression>,
+}
+
+impl VisitorMut for ImportCallRewriter {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Some(replacement) = import_call(expression)
+            .and_then(|(path, kind)| self.replacements.get(&(path.to_string(), kind)))
+        {
+            *expression = replacement.clone();
+            return;
+        }
+        walk_expression(self, expression);
+    }
+}
+
+/// Returns the name given to the chunk of a module imported with `import()`
+/// in a `webpackChunkName` comment, the first import naming it is used.
+fn chunk_name(files: &[File], source_index: usize) -> Option<String> {
+    files
+        .iter()
+        .flat_map(|file| &file.import_records)
+        .filter(|record| record.kind == ImportKind::Dynamic)
+        .filter(|record| record.source_index == Some(source_index))
+        .find_map(|record| record.chunk_name.clone())
+}
+
+/// Creates a name for the variables generated for a module
+/// from its path, "src/a-b.js" => "a_b", "lib/index.js" => "lib".
+fn module_name(path: &str) -> String {
+    let mut segments = path.rsplit('/');
+    let file = segments.next().unwrap_or(path);
+    let mut stem = file.split('.').next().unwrap_or(file);
+    if stem == "index" {
+        if let Some(directory) = segments.next() {
+            stem = directory;
+        }
+    }
+
+    let mut name: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Creates an object with a getter returning each of the values.
+fn getters_object(getters: Vec<(String, Expression)>) -> Expression {
+    let properties = getters
+        .into_iter()
+        .map(|(name, value)| {
+            ObjectExpressionPropertyKind::MethodGet(ObjectExpressionMethodGet {
+                key: LiteralPropertyName::Identifier(Identifier { name }),
+                parameters: Vec::new(),
+                body: BlockStatement {
+                    statements: vec![Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(value),
+                    })],
+                },
+            })
+        })
+        .collect();
+    Expression::Object(ObjectExpression { properties })
+}
+
+/// Assigns the value to the global name, "a.b.c" => `var a = a || {};
+/// a.b = a.b || {}; a.b.c = value;`. The objects on the way are only
+/// created if they do not exist already, the global object is never declared.
+fn assign_global(global_name: &str, value: Expression) -> Vec<Statement> {
+    let segments: Vec<&str> = global_name.split('.').collect();
+    if segments.len() == 1 {
+        return vec![var(global_name, value)];
+    }
+
+    let mut statements = Vec::new();
+    let mut target = identifier(segments[0]);
+    if !GLOBAL_OBJECTS.contains(&segments[0]) {
+        statements.push(var(segments[0], or_empty_object(target.clone())));
+    }
+    for (index, segment) in segments.iter().enumerate().skip(1) {
+        target = member(target, segment);
+        let right = if index == segments.len() - 1 {
+            value.clone()
+        } else {
+            or_empty_object(target.clone())
+        };
+        statements.push(expression_statement(assign(target.clone(), right)));
+    }
+    statements
+}
+
+/// The names of the global object, which always exists.
+const GLOBAL_OBJECTS: [&str; 4] = ["window", "self", "global", "globalThis"];
+
+/// `value || {}`
+fn or_empty_object(value: Expression) -> Expression {
+    Expression::Logical(LogicalExpression {
+        operator: LogicalExpressionOperator::Or,
+        left: Box::new(value),
+        right: Box::new(Expression::Object(ObjectExpression {
+            properties: Vec::new(),
+        })),
+    })
+}
+
+/// `left = right`
+fn assign(left: Expression, right: Expression) -> Expression {
+    Expression::Assignment(AssignmentExpression {
+        left: AssignmentExpressionLeft::Expression(Box::new(left)),
+        operator: AssignmentExpressionOperator::Assign,
+        right: Box::new(right),
+    })
+}
+
+fn return_statement(expression: Expression) -> Statement {
+    Statement::ReturnStatement(ReturnStatement {
+        expression: Some(expression),
+    })
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(ExpressionStatement { expression })
+}
+
+fn string(value: &str) -> Expression {
+    Expression::StringLiteral(StringLiteral {
+        value: value.to_string(),
+    })
+}
+
+fn identifier(name: &str) -> Expression {
+    Expression::Identifier(Identifier {
+        name: name.to_string(),
+    })
+}
+
+fn member(object: Expression, property: &str) -> Expression {
+    Expression::Member(MemberExpression {
+        object: Box::new(object),
+        property: Box::new(identifier(property)),
+        computed: false,
+    })
+}
+
+fn call(callee: Expression, arguments: Vec<Expression>) -> Expression {
+    Expression::Call(CallExpression {
+        callee: Box::new(callee),
+        arguments: arguments
+            .into_iter()
+            .map(ArgumentKind::Expression)
+            .collect(),
+        pure: false,
+    })
+}
+
+fn function(parameters: Vec<&str>, statements: Vec<Statement>) -> Expression {
+    Expression::Function(FunctionExpression {
+        identifier: None,
+        parameters: parameters
+            .into_iter()
+            .map(|name| {
+                ParameterKind::Parameter(Parameter {
+                    binding: Binding::Identifier(Identifier { name: name.into() }),
+                    initializer: None,
+                })
+            })
+            .collect(),
+        body: BlockStatement { statements },
+        generator: false,
+        is_async: false,
+    })
+}
+
+fn var(name: &str, initializer: Expression) -> Statement {
+    Statement::VariableDeclaration(VariableDeclaration {
+        kind: VariableDeclarationKind::Var,
+        declarations: vec![VariableDeclarator {
+            binding: Binding::Identifier(Identifier { name: name.into() }),
+            initializer: Some(initializer),
+        }],
+    })
+}
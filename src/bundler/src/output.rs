@@ -0,0 +1,275 @@
+/// This file contains the output stage of code splitting, it prints the chunks,
+/// names them with the naming templates and creates the manifest and metafile.
+///
+/// The chunks import each other, but their paths are not known until they have
+/// been printed since the paths contain the hashes of their contents. The linker
+/// therefore uses placeholders for the paths, which are replaced once every chunk
+/// has a path. Only the import paths are replaced, in the AST, so code that happens
+/// to contain the text of a placeholder is left as it is. The hash of a chunk covers its own contents and the contents of
+/// every chunk it imports, directly or indirectly, so a change to a chunk changes
+/// the paths of all of the chunks that depend on it.
+///
+/// The metafile lists every input with its size and imports, and every output with
+/// the number of bytes each input contributed to it. The contribution of an input
+/// is measured by printing the statements that were kept for it on their own.
+use js_ast::visit::{walk_expression, walk_statement, VisitorMut};
+use js_ast::{Expression, Statement, StringLiteral, AST};
+use js_lowering::Target;
+use js_printer::{LegalComments, Printer, PrinterOptions};
+
+use crate::json::JSONValue;
+use crate::linker::{placeholder_chunk, Chunk};
+use crate::resolver::{dirname, relative_path};
+use crate::{BundlerOptions, File, ImportKind, OutputFile};
+
+/// The path of the manifest, relative to the other output files.
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// The path of the metafile, relative to the other output files.
+pub const METAFILE_PATH: &str = "metafile.json";
+
+fn printer_options(options: &BundlerOptions) -> PrinterOptions {
+    PrinterOptions {
+        legal_comments: options.legal_comments,
+        minify_syntax: options.minify_syntax,
+        ascii_only: options.ascii_only,
+        es5: options.target == Target::ES5,
+    }
+}
+
+/// Prints and names the chunks, returns the errors of the printer if a chunk
+/// can not be printed with the options.
+pub fn output_files(
+    mut chunks: Vec<Chunk>,
+    inputs: &[File],
+    options: &BundlerOptions,
+) -> Result<Vec<OutputFile>, Vec<String>> {
+    let mut contents = Vec::new();
+    let mut legal_comments = Vec::new();
+    let mut errors = Vec::new();
+    for chunk in &chunks {
+        let mut printer = Printer::with_options(printer_options(options));
+        contents.push(printer.print_program(&chunk.ast));
+        errors.extend(printer.errors().iter().cloned());
+        legal_comments.push(printer.legal_comments().to_vec());
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let dependencies: Vec<Vec<usize>> = chunks
+        .iter_mut()
+        .map(|chunk| {
+            let mut dependencies = Vec::new();
+            replace_chunk_paths(&mut chunk.ast.statements, |dependency| {
+                dependencies.push(dependency);
+                None
+            });
+            dependencies
+        })
+        .collect();
+
+    let own_hashes: Vec<u64> = contents.iter().map(|content| hash(content)).collect();
+    let mut paths = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        // The chunks are hashed in order of their index so the
+        // hash does not depend on the order they are found in.
+        let mut reachable = vec![false; chunks.len()];
+        let mut stack = vec![index];
+        while let Some(current) = stack.pop() {
+            if !reachable[current] {
+                reachable[current] = true;
+                stack.extend(&dependencies[current]);
+            }
+        }
+        let hashes: String = (0..chunks.len())
+            .filter(|index| reachable[*index])
+            .map(|index| format!("{:016x}", own_hashes[index]))
+            .collect();
+
+        let template = match chunk.entry_point {
+            Some(_) => &options.entry_names,
+            None => &options.chunk_names,
+        };
+        let path = template
+            .replace("[name]", &chunk.name)
+            .replace("[hash]", &format!("{:016x}", hash(&hashes))[..8]);
+        paths.push(path);
+    }
+
+    let import_path = |index: usize, dependency: usize| {
+        let mut import_path = relative_path(dirname(&paths[index]), &paths[dependency]);
+        if !import_path.starts_with("../") {
+            import_path.insert_str(0, "./");
+        }
+        Some(import_path)
+    };
+
+    let mut files = Vec::new();
+    let mut manifest = Vec::new();
+    let mut outputs = Vec::new();
+    for (index, chunk) in chunks.iter_mut().enumerate() {
+        // The chunks without imports of other chunks are printed as they are.
+        let content = match dependencies[index].is_empty() {
+            true => std::mem::take(&mut contents[index]),
+            false => {
+                replace_chunk_paths(&mut chunk.ast.statements, |dependency| {
+                    import_path(index, dependency)
+                });
+                Printer::with_options(printer_options(options)).print_program(&chunk.ast)
+            }
+        };
+        if options.metafile {
+            let mut output = vec![("bytes".to_string(), JSONValue::Number(content.len() as f64))];
+            if let Some(entry_point) = &chunk.entry_point {
+                output.push((
+                    "entryPoint".to_string(),
+                    JSONValue::String(entry_point.clone()),
+                ));
+            }
+            let mut output_inputs = Vec::new();
+            for (path, statements) in &chunk.inputs {
+                let mut statements = statements.clone();
+                replace_chunk_paths(&mut statements, |dependency| import_path(index, dependency));
+                let mut printer = Printer::with_options(printer_options(options));
+                let bytes = printer.print_program(&AST { statements }).len();
+                if bytes > 0 {
+                    let input =
+                        vec![("bytesInOutput".to_string(), JSONValue::Number(bytes as f64))];
+                    output_inputs.push((path.clone(), JSONValue::Object(input)));
+                }
+            }
+            output.push(("inputs".to_string(), JSONValue::Object(output_inputs)));
+            outputs.push((paths[index].clone(), JSONValue::Object(output)));
+        }
+        files.push(OutputFile {
+            path: paths[index].clone(),
+            contents: content,
+        });
+
+        if options.legal_comments == LegalComments::External && !legal_comments[index].is_empty() {
+            let mut text = legal_comments[index].join("\n");
+            text.push('\n');
+            files.push(OutputFile {
+                path: format!("{}.LEGAL.txt", paths[index]),
+                contents: text,
+            });
+        }
+
+        if let Some(entry_point) = &chunk.entry_point {
+            manifest.push((entry_point.clone(), JSONValue::String(paths[index].clone())));
+        }
+    }
+
+    // Without splitting there is a single file, which the caller has named.
+    if options.splitting {
+        let mut text = JSONValue::Object(manifest).stringify();
+        text.push('\n');
+        files.push(OutputFile {
+            path: MANIFEST_PATH.to_string(),
+            contents: text,
+        });
+    }
+
+    if options.metafile {
+        let metafile = JSONValue::Object(vec![
+            ("inputs".to_string(), metafile_inputs(inputs)),
+            ("outputs".to_string(), JSONValue::Object(outputs)),
+        ]);
+        let mut text = metafile.stringify();
+        text.push('\n');
+        files.push(OutputFile {
+            path: METAFILE_PATH.to_string(),
+            contents: text,
+        });
+    }
+    Ok(files)
+}
+
+/// Calls `replace` with the chunk of every placeholder in the import paths of the statements,
+/// the import declarations and `import()` calls created by the linker, and replaces the
+/// placeholder with the path it returns.
+fn replace_chunk_paths(
+    statements: &mut Vec<Statement>,
+    replace: impl FnMut(usize) -> Option<String>,
+) {
+    ChunkPathReplacer { replace }.visit_statements(statements);
+}
+
+struct ChunkPathReplacer<F: FnMut(usize) -> Option<String>> {
+    replace: F,
+}
+
+impl<F: FnMut(usize) -> Option<String>> ChunkPathReplacer<F> {
+    fn replace_source(&mut self, source: &mut StringLiteral) {
+        if let Some(path) = placeholder_chunk(&source.value).and_then(&mut self.replace) {
+            source.value = path;
+        }
+    }
+}
+
+impl<F: FnMut(usize) -> Option<String>> VisitorMut for ChunkPathReplacer<F> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if let Statement::ImportDeclaration(i) = statement {
+            self.replace_source(&mut i.source);
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Import(i) = expression {
+            if let Expression::StringLiteral(source) = &mut *i.source {
+                self.replace_source(source);
+            }
+        }
+        walk_expression(self, expression);
+    }
+}
+
+/// Describes every file of the bundle with its size and the files it imports, imports
+/// that could not be resolved are kept as written and marked as external.
+fn metafile_inputs(files: &[File]) -> JSONValue {
+    let mut inputs = Vec::new();
+    for file in files {
+        let mut imports = Vec::new();
+        for record in &file.import_records {
+            let kind = match record.kind {
+                ImportKind::Static => "static",
+                ImportKind::Require => "require",
+                ImportKind::Dynamic => "dynamic",
+            };
+            let mut import = Vec::new();
+            match record.source_index {
+                Some(source_index) => import.push((
+                    "path".to_string(),
+                    JSONValue::String(files[source_index].path.clone()),
+                )),
+                None => {
+                    import.push((
+                        "path".to_string(),
+                        JSONValue::String(record.import_path.clone()),
+                    ));
+                    import.push(("external".to_string(), JSONValue::Boolean(true)));
+                }
+            }
+            import.push(("kind".to_string(), JSONValue::String(kind.to_string())));
+            imports.push(JSONValue::Object(import));
+        }
+        let input = vec![
+            ("bytes".to_string(), JSONValue::Number(file.size as f64)),
+            ("imports".to_string(), JSONValue::Array(imports)),
+        ];
+        inputs.push((file.path.clone(), JSONValue::Object(input)));
+    }
+    JSONValue::Object(inputs)
+}
+
+/// The 64 bit FNV-1a hash of the text.
+fn hash(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
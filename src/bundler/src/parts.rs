@@ -0,0 +1,298 @@
+/// This file splits a module into parts, one for each top level statement,
+/// and records which top level names every part declares and references.
+/// It also records what the module imports and exports, this is what the
+/// tree shaking and the linker use to follow names across modules.
+use std::collections::{BTreeMap, BTreeSet};
+
+use js_ast::scope::binding_names;
+use js_ast::side_effects::statement_has_side_effects;
+use js_ast::visit::{walk_expression, VisitorMut};
+use js_ast::*;
+
+use crate::ImportKind;
+
+/// The name used for the default export, it can never collide
+/// with a variable since it is a reserved word.
+pub const DEFAULT_EXPORT: &str = "default";
+
+#[derive(Debug, Clone)]
+pub struct Part {
+    /// The top level names declared by the statement.
+    pub declared: Vec<String>,
+    /// All names referenced by the statement, including names
+    /// that are shadowed by local variables.
+    pub references: BTreeSet<String>,
+    pub has_side_effects: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ImportedName {
+    Name(String),
+    /// import * as a from "b"
+    Namespace,
+}
+
+/// A local name that is bound to an export of another module.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportBinding {
+    pub import_path: String,
+    pub name: ImportedName,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExportTarget {
+    /// export { a }, export function a() {}
+    Local(String),
+    /// export { a } from "b"
+    ReExport(ImportBinding),
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleInfo {
+    /// One part for each top level statement.
+    pub parts: Vec<Part>,
+    /// Local names bound by import declarations.
+    pub imports: BTreeMap<String, ImportBinding>,
+    pub exports: BTreeMap<String, ExportTarget>,
+    /// The import paths of export * from "a".
+    pub star_exports: Vec<String>,
+}
+
+impl ModuleInfo {
+    /// Returns the index of the part that declares the given top level name.
+    pub fn declaring_part(&self, name: &str) -> Option<usize> {
+        self.parts
+            .iter()
+            .position(|part| part.declared.iter().any(|d| d == name))
+    }
+
+    /// Returns true if the module references the global `eval`, the linker
+    /// wraps such modules since the names they use can not be known.
+    pub fn uses_eval(&self) -> bool {
+        self.declaring_part("eval").is_none()
+            && !self.imports.contains_key("eval")
+            && self
+                .parts
+                .iter()
+                .any(|part| part.references.contains("eval"))
+    }
+}
+
+pub fn analyze_module(ast: &mut AST) -> ModuleInfo {
+    let mut info = ModuleInfo {
+        parts: Vec::new(),
+        imports: BTreeMap::new(),
+        exports: BTreeMap::new(),
+        star_exports: Vec::new(),
+    };
+
+    for statement in ast.statements.iter_mut() {
+        let mut declared = Vec::new();
+        let mut has_side_effects = statement_has_side_effects(statement);
+        match statement {
+            Statement::ImportDeclaration(i) => {
+                // Imports are handled by following the bindings.
+                has_side_effects = false;
+                let import_path = &i.source.value;
+                if let Some(default) = &i.default {
+                    declared.push(default.name.clone());
+                    info.imports.insert(
+                        default.name.clone(),
+                        ImportBinding {
+                            import_path: import_path.clone(),
+                            name: ImportedName::Name(DEFAULT_EXPORT.into()),
+                        },
+                    );
+                }
+                if let Some(namespace) = &i.namespace {
+                    declared.push(namespace.name.clone());
+                    info.imports.insert(
+                        namespace.name.clone(),
+                        ImportBinding {
+                            import_path: import_path.clone(),
+                            name: ImportedName::Namespace,
+                        },
+                    );
+                }
+                // Note that the parser stores the imported name
+                // in `local` and the local name in `imported`.
+                for specifier in &i.specifiers {
+                    declared.push(specifier.imported.name.clone());
+                    info.imports.insert(
+                        specifier.imported.name.clone(),
+                        ImportBinding {
+                            import_path: import_path.clone(),
+                            name: ImportedName::Name(specifier.local.name.clone()),
+                        },
+                    );
+                }
+            }
+
+            Statement::ExportAllDeclaration(e) => {
+                has_side_effects = false;
+                info.star_exports.push(e.source.value.clone());
+            }
+
+            Statement::ExportNamedSpecifiers(e) => {
+                has_side_effects = false;
+                for specifier in &e.specifiers {
+                    let target = match &e.source {
+                        Some(source) => ExportTarget::ReExport(ImportBinding {
+                            import_path: source.value.clone(),
+                            name: ImportedName::Name(specifier.local.name.clone()),
+                        }),
+                        None => ExportTarget::Local(specifier.local.name.clone()),
+                    };
+                    info.exports.insert(specifier.exported.name.clone(), target);
+                }
+            }
+
+            Statement::ExportNamedDeclaration(e) => {
+                match &e.declaration {
+                    ExportNamedDeclarationKind::FunctionDeclaration(f) => {
+                        declared.push(f.identifier.name.clone())
+                    }
+                    ExportNamedDeclarationKind::ClassDeclaration(c) => {
+                        declared.push(c.identifier.name.clone())
+                    }
+                    ExportNamedDeclarationKind::VariableDeclaration(v) => {
+                        for declarator in &v.declarations {
+                            binding_names(&declarator.binding, &mut declared);
+                        }
+                    }
+                }
+                for name in &declared {
+                    info.exports
+                        .insert(name.clone(), ExportTarget::Local(name.clone()));
+                }
+            }
+
+            Statement::ExportDefaultDeclaration(e) => {
+                has_side_effects = match &e.declaration {
+                    ExportDefaultDeclarationKind::Expression(e) => {
+                        js_ast::side_effects::has_side_effects(e)
+                    }
+                    ExportDefaultDeclarationKind::ClassDeclaration(ClassDeclaration {
+                        extends,
+                        ..
+                    })
+                    | ExportDefaultDeclarationKind::AnonymousDefaultExportedClassDeclaration(
+                        AnonymousDefaultExportedClassDeclaration { extends, .. },
+                    ) => extends.is_some(),
+                    _ => false,
+                };
+                match &e.declaration {
+                    ExportDefaultDeclarationKind::FunctionDeclaration(f) => {
+                        declared.push(f.identifier.name.clone())
+                    }
+                    ExportDefaultDeclarationKind::ClassDeclaration(c) => {
+                        declared.push(c.identifier.name.clone())
+                    }
+                    _ => {}
+                }
+                declared.push(DEFAULT_EXPORT.into());
+                info.exports.insert(
+                    DEFAULT_EXPORT.into(),
+                    ExportTarget::Local(DEFAULT_EXPORT.into()),
+                );
+            }
+
+            Statement::FunctionDeclaration(f) => declared.push(f.identifier.name.clone()),
+            Statement::ClassDeclaration(c) => declared.push(c.identifier.name.clone()),
+            Statement::VariableDeclaration(v) => {
+                for declarator in &v.declarations {
+                    binding_names(&declarator.binding, &mut declared);
+                }
+            }
+            _ => {}
+        }
+
+        let mut collector = ReferenceCollector {
+            references: BTreeSet::new(),
+        };
+        collector.visit_statement(statement);
+
+        info.parts.push(Part {
+            declared,
+            references: collector.references,
+            has_side_effects,
+        });
+    }
+
+    info
+}
+
+struct ReferenceCollector {
+    references: BTreeSet<String>,
+}
+
+impl VisitorMut for ReferenceCollector {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier(i) = expression {
+            self.references.insert(i.name.clone());
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_shorthand_property(&mut self, property: &mut ObjectExpressionPropertyShorthand) {
+        self.references.insert(property.key.name.clone());
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        // export { a } references a
+        if let Statement::ExportNamedSpecifiers(e) = statement {
+            if e.source.is_none() {
+                for specifier in &e.specifiers {
+                    self.references.insert(specifier.local.name.clone());
+                }
+            }
+        }
+        js_ast::visit::walk_statement(self, statement);
+    }
+}
+
+/// Returns the import path and kind if the expression is
+/// a call like `require("a")` or an import like `import("a")`.
+pub fn import_call(expression: &Expression) -> Option<(&str, ImportKind)> {
+    match expression {
+        Expression::Call(CallExpression {
+            callee, arguments, ..
+        }) => match (&**callee, arguments.as_slice()) {
+            (
+                Expression::Identifier(Identifier { name }),
+                [ArgumentKind::Expression(Expression::StringLiteral(path))],
+            ) if name == "require" => Some((&path.value, ImportKind::Require)),
+            _ => None,
+        },
+        Expression::Import(ImportExpression { source, .. }) => match &**source {
+            Expression::StringLiteral(path) => Some((&path.value, ImportKind::Dynamic)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the import paths of all of the `require` and `import()` calls in the module,
+/// with the chunk names given to the `import()` calls.
+pub fn import_calls(ast: &mut AST) -> Vec<(String, ImportKind, Option<String>)> {
+    let mut collector = ImportCallCollector { paths: Vec::new() };
+    collector.visit_program(ast);
+    collector.paths
+}
+
+struct ImportCallCollector {
+    paths: Vec<(String, ImportKind, Option<String>)>,
+}
+
+impl VisitorMut for ImportCallCollector {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Some((path, kind)) = import_call(expression) {
+            let chunk_name = match &*expression {
+                Expression::Import(i) => i.chunk_name.clone(),
+                _ => None,
+            };
+            self.paths.push((path.to_string(), kind, chunk_name));
+        }
+        walk_expression(self, expression);
+    }
+}
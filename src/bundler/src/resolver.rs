@@ -0,0 +1,243 @@
+/// This file contains the logic for turning an import path into the path
+/// of a file and for reading the "sideEffects" field of package.json files.
+///
+/// Paths are always separated by forward slashes, relative paths are
+/// resolved against the directory of the importing file.
+use fs::FS;
+
+use crate::json::{parse_json, JSONValue};
+
+/// Resolves the import path as written in the importer to the path of a file,
+/// returns None if no file could be found.
+pub fn resolve(fs: &dyn FS, importer: &str, import_path: &str) -> Option<String> {
+    if is_relative(import_path) {
+        let path = join_paths(dirname(importer), import_path);
+        return resolve_file(fs, &path);
+    }
+
+    // Bare import paths are looked up in the node_modules directories
+    // of the importer and all of its parent directories.
+    let (package_name, subpath) = split_package_name(import_path);
+    let mut directory = dirname(importer).to_string();
+    loop {
+        let package_directory = join_paths(&directory, &format!("node_modules/{}", package_name));
+        let resolved = match subpath {
+            Some(subpath) => resolve_file(fs, &join_paths(&package_directory, subpath)),
+            None => resolve_package_entry(fs, &package_directory),
+        };
+        if resolved.is_some() {
+            return resolved;
+        }
+
+        if directory.is_empty() || directory == "/" {
+            return None;
+        }
+        directory = dirname(&directory).to_string();
+    }
+}
+
+/// True if the import path is a path to a file rather than the name of a package.
+pub fn is_relative(import_path: &str) -> bool {
+    import_path.starts_with("./") || import_path.starts_with("../") || import_path.starts_with('/')
+}
+
+/// The "sideEffects" field of a package.json file.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SideEffects {
+    /// The files of the package may have side effects, this is the default.
+    All,
+    /// "sideEffects": false
+    None,
+    /// "sideEffects": ["*.css"], the globs are relative to the package directory.
+    Globs(String, Vec<String>),
+}
+
+impl SideEffects {
+    /// Returns true if the file at the given path may have side effects.
+    pub fn has_side_effects(&self, path: &str) -> bool {
+        match self {
+            SideEffects::All => true,
+            SideEffects::None => false,
+            SideEffects::Globs(directory, globs) => {
+                let relative = path
+                    .strip_prefix(directory.as_str())
+                    .map(|p| p.trim_start_matches('/'))
+                    .unwrap_or(path);
+                globs.iter().any(|glob| {
+                    let glob = glob.trim_start_matches("./");
+                    // Globs without a slash match files in any directory.
+                    if glob.contains('/') {
+                        matches_glob(glob, relative)
+                    } else {
+                        matches_glob(&format!("**/{}", glob), relative)
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Reads the "sideEffects" field of the package.json that is closest to the file.
+pub fn package_side_effects(fs: &dyn FS, path: &str) -> SideEffects {
+    let mut directory = dirname(path).to_string();
+    loop {
+        let package_json_path = join_paths(&directory, "package.json");
+        if let Ok(content) = fs.read_file(&package_json_path) {
+            return match parse_json(&content)
+                .ok()
+                .and_then(|json| json.get("sideEffects").cloned())
+            {
+                Some(JSONValue::Boolean(false)) => SideEffects::None,
+                Some(JSONValue::Array(items)) => SideEffects::Globs(
+                    directory,
+                    items
+                        .iter()
+                        .filter_map(|i| i.as_str().map(String::from))
+                        .collect(),
+                ),
+                _ => SideEffects::All,
+            };
+        }
+
+        if directory.is_empty() || directory == "/" {
+            return SideEffects::All;
+        }
+        directory = dirname(&directory).to_string();
+    }
+}
+
+/// Matches a path against a glob, `*` matches anything but a slash,
+/// `**` matches anything and `?` matches a single character.
+pub fn matches_glob(glob: &str, path: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    matches_glob_chars(&glob, &path)
+}
+
+fn matches_glob_chars(glob: &[char], path: &[char]) -> bool {
+    match glob.first() {
+        None => path.is_empty(),
+        Some('*') if glob.get(1) == Some(&'*') => {
+            // "**/" also matches no directories at all.
+            let rest = &glob[2..];
+            if rest.first() == Some(&'/') && matches_glob_chars(&rest[1..], path) {
+                return true;
+            }
+            (0..=path.len()).any(|i| matches_glob_chars(rest, &path[i..]))
+        }
+        Some('*') => {
+            let rest = &glob[1..];
+            for i in 0..=path.len() {
+                if matches_glob_chars(rest, &path[i..]) {
+                    return true;
+                }
+                if path.get(i) == Some(&'/') {
+                    return false;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            !path.is_empty() && path[0] != '/' && matches_glob_chars(&glob[1..], &path[1..])
+        }
+        Some(c) => path.first() == Some(c) && matches_glob_chars(&glob[1..], &path[1..]),
+    }
+}
+
+/// Returns the directory part of a path, "a/b.js" => "a".
+pub fn dirname(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) => "/",
+        Some(index) => &path[..index],
+        None => "",
+    }
+}
+
+/// Joins two paths and normalizes the result, removing "." and ".." segments.
+pub fn join_paths(base: &str, path: &str) -> String {
+    let absolute = path.starts_with('/') || base.starts_with('/');
+    let full = if path.starts_with('/') || base.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", base, path)
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in full.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if segments.last().is_none_or(|s| *s == "..") {
+                    if !absolute {
+                        segments.push("..");
+                    }
+                } else {
+                    segments.pop();
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let joined = segments.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+/// Returns the path of `to` relative to the directory `from`,
+/// ("a/b", "a/c/d.js") => "../c/d.js".
+pub fn relative_path(from: &str, to: &str) -> String {
+    let from: Vec<&str> = from.split('/').filter(|s| !s.is_empty()).collect();
+    let to: Vec<&str> = to.split('/').filter(|s| !s.is_empty()).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut segments = vec![".."; from.len() - common];
+    segments.extend(&to[common..]);
+    segments.join("/")
+}
+
+fn resolve_file(fs: &dyn FS, path: &str) -> Option<String> {
+    let candidates = [
+        path.to_string(),
+        format!("{}.js", path),
+        format!("{}.mjs", path),
+        format!("{}/index.js", path),
+    ];
+    candidates
+        .iter()
+        .find(|candidate| fs.read_file(candidate).is_ok())
+        .cloned()
+}
+
+/// Resolves the entry of a package using the "module" or "main" fields
+/// of its package.json, falling back to index.js.
+fn resolve_package_entry(fs: &dyn FS, directory: &str) -> Option<String> {
+    if let Ok(content) = fs.read_file(&join_paths(directory, "package.json")) {
+        if let Ok(json) = parse_json(&content) {
+            for field in &["module", "main"] {
+                if let Some(main) = json.get(field).and_then(|v| v.as_str()) {
+                    if let Some(path) = resolve_file(fs, &join_paths(directory, main)) {
+                        return Some(path);
+                    }
+                }
+            }
+        }
+    }
+    resolve_file(fs, &join_paths(directory, "index"))
+}
+
+/// Splits "@scope/name/sub/path" into "@scope/name" and "sub/path".
+fn split_package_name(import_path: &str) -> (&str, Option<&str>) {
+    let segments = if import_path.starts_with('@') { 2 } else { 1 };
+    let mut index = 0;
+    for _ in 0..segments {
+        match import_path[index..].find('/') {
+            Some(i) => index += i + 1,
+            None => return (import_path, None),
+        }
+    }
+    (&import_path[..index - 1], Some(&import_path[index..]))
+}
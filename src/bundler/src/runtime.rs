@@ -0,0 +1,74 @@
+/// This file contains the runtime helpers that the linker injects
+/// into the bundle, a helper is only included if it is used.
+use js_ast::Statement;
+use js_lexer::Lexer;
+use js_parser::Parser;
+use logger::LoggerImpl;
+
+/// Wraps a CommonJS module in a function that runs
+/// the module the first time it is called.
+pub const COMMON_JS: &str = "__commonJS";
+
+/// Converts the exports of a CommonJS module to a namespace object,
+/// the exports become the default export unless `__esModule` is set.
+pub const TO_ESM: &str = "__toESM";
+
+/// Converts the namespace object of an ES module to the exports
+/// object seen by CommonJS modules that `require` it.
+pub const TO_COMMON_JS: &str = "__toCommonJS";
+
+pub const HELPERS: [&str; 3] = [COMMON_JS, TO_ESM, TO_COMMON_JS];
+
+fn helper_source(name: &str) -> &'static str {
+    match name {
+        COMMON_JS => {
+            "var __commonJS = function(callback) {
+                var module;
+                return function() {
+                    if (!module) {
+                        module = { exports: {} };
+                        callback(module.exports, module);
+                    }
+                    return module.exports;
+                };
+            };"
+        }
+        TO_ESM => {
+            "var __toESM = function(exports) {
+                if (exports && exports.__esModule) {
+                    return exports;
+                }
+                var namespace = { default: exports };
+                if (exports != null) {
+                    Object.keys(exports).forEach(function(key) {
+                        if (key !== \"default\") {
+                            Object.defineProperty(namespace, key, { get: function() { return exports[key]; }, enumerable: true });
+                        }
+                    });
+                }
+                return namespace;
+            };"
+        }
+        TO_COMMON_JS => {
+            "var __toCommonJS = function(namespace) {
+                var exports = Object.defineProperty({}, \"__esModule\", { value: true });
+                Object.keys(namespace).forEach(function(key) {
+                    Object.defineProperty(exports, key, { get: function() { return namespace[key]; }, enumerable: true });
+                });
+                return exports;
+            };"
+        }
+        _ => unreachable!("Unknown runtime helper \"{}\"", name),
+    }
+}
+
+/// Returns the statements declaring the helpers, in the order of `HELPERS`.
+pub fn helper_statements(names: &[&str]) -> Vec<Statement> {
+    let logger = LoggerImpl::new();
+    let mut statements = Vec::new();
+    for name in HELPERS.iter().filter(|helper| names.contains(helper)) {
+        let lexer = Lexer::new(helper_source(name), &logger);
+        statements.extend(Parser::new(lexer, &logger).parse_program().statements);
+    }
+    statements
+}
@@ -0,0 +1,301 @@
+/// This file contains the tree shaking, it removes the code that is
+/// never used from the modules in the bundle.
+///
+/// Starting from the entry points, every export of an entry point is marked
+/// as used. Using an export includes the statement that declares it, which in
+/// turn uses all of the names referenced by that statement, both local ones
+/// and the ones imported from other modules. Statements with side effects are
+/// always kept for modules that are included in the bundle.
+///
+/// A module is included if one of its exports is used or if it is imported
+/// and may have side effects. Modules marked with `"sideEffects": false` in
+/// their package.json are therefore dropped completely when nothing they
+/// export is used.
+///
+/// A direct `eval` can reference any top level name of its module, so every
+/// statement of a module that uses it is kept once the module is included.
+use std::collections::BTreeSet;
+
+use js_ast::*;
+
+use crate::parts::{analyze_module, ExportTarget, ImportBinding, ImportedName, ModuleInfo};
+use crate::{File, FileRepresentation, ImportKind};
+
+enum Work {
+    IncludeFile(usize),
+    IncludePart(usize, usize),
+    UseExport(usize, String),
+    UseAllExports(usize),
+    UseLocal(usize, String),
+}
+
+struct TreeShaker<'a> {
+    files: &'a [File],
+    infos: Vec<ModuleInfo>,
+    included: Vec<bool>,
+    live_parts: Vec<Vec<bool>>,
+    used_exports: Vec<BTreeSet<String>>,
+    all_exports_used: Vec<bool>,
+    used_imports: Vec<BTreeSet<String>>,
+    work: Vec<Work>,
+}
+
+/// Removes unused statements from all of the files, files that are not
+/// included in the bundle are left without any statements.
+pub fn tree_shake(files: &mut [File], entry_points: &[usize]) {
+    let infos: Vec<ModuleInfo> = files
+        .iter_mut()
+        .map(|file| {
+            let FileRepresentation::JS(js) = &mut file.representation;
+            analyze_module(&mut js.ast)
+        })
+        .collect();
+
+    let mut shaker = TreeShaker {
+        included: vec![false; files.len()],
+        live_parts: infos.iter().map(|i| vec![false; i.parts.len()]).collect(),
+        used_exports: vec![BTreeSet::new(); files.len()],
+        all_exports_used: vec![false; files.len()],
+        used_imports: vec![BTreeSet::new(); files.len()],
+        work: Vec::new(),
+        files,
+        infos,
+    };
+
+    for entry_point in entry_points {
+        shaker.work.push(Work::UseAllExports(*entry_point));
+        shaker.work.push(Work::IncludeFile(*entry_point));
+    }
+    shaker.run();
+
+    let TreeShaker {
+        included,
+        live_parts,
+        used_exports,
+        all_exports_used,
+        used_imports,
+        ..
+    } = shaker;
+
+    let included_paths: Vec<Vec<bool>> = files
+        .iter()
+        .map(|file| {
+            file.import_records
+                .iter()
+                .map(|r| r.source_index.is_none_or(|index| included[index]))
+                .collect()
+        })
+        .collect();
+
+    for (index, file) in files.iter_mut().enumerate() {
+        let import_records = &file.import_records;
+        let is_path_included = |path: &str| {
+            import_records
+                .iter()
+                .position(|r| r.import_path == path)
+                .is_none_or(|r| included_paths[index][r])
+        };
+
+        let FileRepresentation::JS(js) = &mut file.representation;
+        if !included[index] {
+            js.ast.statements.clear();
+            continue;
+        }
+
+        let statements = std::mem::take(&mut js.ast.statements);
+        for (part, statement) in statements.into_iter().enumerate() {
+            let statement = match statement {
+                Statement::ImportDeclaration(mut i) => {
+                    let used = &used_imports[index];
+                    i.default = i.default.filter(|d| used.contains(&d.name));
+                    i.namespace = i.namespace.filter(|n| used.contains(&n.name));
+                    i.specifiers.retain(|s| used.contains(&s.imported.name));
+                    let is_empty =
+                        i.default.is_none() && i.namespace.is_none() && i.specifiers.is_empty();
+                    if is_empty && !is_path_included(&i.source.value) {
+                        continue;
+                    }
+                    Statement::ImportDeclaration(i)
+                }
+
+                Statement::ExportAllDeclaration(e) => {
+                    if !is_path_included(&e.source.value) {
+                        continue;
+                    }
+                    Statement::ExportAllDeclaration(e)
+                }
+
+                Statement::ExportNamedSpecifiers(mut e) => {
+                    if !all_exports_used[index] {
+                        let used = &used_exports[index];
+                        e.specifiers.retain(|s| used.contains(&s.exported.name));
+                    }
+                    if e.specifiers.is_empty() {
+                        continue;
+                    }
+                    Statement::ExportNamedSpecifiers(e)
+                }
+
+                statement => {
+                    if !live_parts[index][part] {
+                        continue;
+                    }
+                    statement
+                }
+            };
+            js.ast.statements.push(statement);
+        }
+    }
+}
+
+impl<'a> TreeShaker<'a> {
+    fn run(&mut self) {
+        while let Some(work) = self.work.pop() {
+            match work {
+                Work::IncludeFile(file) => self.include_file(file),
+                Work::IncludePart(file, part) => self.include_part(file, part),
+                Work::UseExport(file, name) => self.use_export(file, name),
+                Work::UseAllExports(file) => self.use_all_exports(file),
+                Work::UseLocal(file, name) => self.use_local(file, name),
+            }
+        }
+    }
+
+    fn include_file(&mut self, file: usize) {
+        if self.included[file] {
+            return;
+        }
+        self.included[file] = true;
+
+        let uses_eval = self.infos[file].uses_eval();
+        for (index, part) in self.infos[file].parts.iter().enumerate() {
+            if part.has_side_effects || uses_eval {
+                self.work.push(Work::IncludePart(file, index));
+            }
+        }
+
+        // Importing a module runs it, so modules that may have
+        // side effects are included even if nothing is used.
+        for record in &self.files[file].import_records {
+            if let Some(source_index) = record.source_index {
+                match record.kind {
+                    ImportKind::Static => {
+                        if self.files[source_index].side_effects {
+                            self.work.push(Work::IncludeFile(source_index));
+                        }
+                    }
+                    // The exports of a required or dynamically imported module
+                    // are accessed dynamically so all of them have to be kept.
+                    ImportKind::Require | ImportKind::Dynamic => {
+                        self.work.push(Work::UseAllExports(source_index));
+                        self.work.push(Work::IncludeFile(source_index));
+                    }
+                }
+            }
+        }
+    }
+
+    fn include_part(&mut self, file: usize, part: usize) {
+        if self.live_parts[file][part] {
+            return;
+        }
+        self.live_parts[file][part] = true;
+        self.work.push(Work::IncludeFile(file));
+
+        let info = &self.infos[file];
+        for reference in &info.parts[part].references {
+            if info.imports.contains_key(reference) || info.declaring_part(reference).is_some() {
+                self.work.push(Work::UseLocal(file, reference.clone()));
+            }
+        }
+    }
+
+    fn use_export(&mut self, file: usize, name: String) {
+        if !self.used_exports[file].insert(name.clone()) {
+            return;
+        }
+        self.work.push(Work::IncludeFile(file));
+
+        match self.infos[file].exports.get(&name).cloned() {
+            Some(ExportTarget::Local(local)) => self.work.push(Work::UseLocal(file, local)),
+            Some(ExportTarget::ReExport(binding)) => self.use_binding(file, &binding),
+            None => {
+                for import_path in self.infos[file].star_exports.clone() {
+                    if let Some(source_index) = self.source_index(file, &import_path) {
+                        if self.has_export(source_index, &name, &mut Vec::new()) {
+                            self.work.push(Work::UseExport(source_index, name.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn use_all_exports(&mut self, file: usize) {
+        if self.all_exports_used[file] {
+            return;
+        }
+        self.all_exports_used[file] = true;
+        self.work.push(Work::IncludeFile(file));
+
+        for name in self.infos[file].exports.keys() {
+            self.work.push(Work::UseExport(file, name.clone()));
+        }
+        for import_path in &self.infos[file].star_exports {
+            if let Some(source_index) = self.source_index(file, import_path) {
+                self.work.push(Work::UseAllExports(source_index));
+            }
+        }
+    }
+
+    fn use_local(&mut self, file: usize, name: String) {
+        if let Some(binding) = self.infos[file].imports.get(&name).cloned() {
+            if self.used_imports[file].insert(name) {
+                self.use_binding(file, &binding);
+            }
+            return;
+        }
+
+        if let Some(part) = self.infos[file].declaring_part(&name) {
+            self.work.push(Work::IncludePart(file, part));
+        }
+    }
+
+    fn use_binding(&mut self, file: usize, binding: &ImportBinding) {
+        if let Some(source_index) = self.source_index(file, &binding.import_path) {
+            match &binding.name {
+                ImportedName::Name(name) => {
+                    self.work.push(Work::UseExport(source_index, name.clone()))
+                }
+                ImportedName::Namespace => self.work.push(Work::UseAllExports(source_index)),
+            }
+        }
+    }
+
+    fn has_export(&self, file: usize, name: &str, visited: &mut Vec<usize>) -> bool {
+        if visited.contains(&file) {
+            return false;
+        }
+        visited.push(file);
+
+        if self.infos[file].exports.contains_key(name) {
+            return true;
+        }
+        // The default export is never re-exported by export *.
+        if name == crate::parts::DEFAULT_EXPORT {
+            return false;
+        }
+        self.infos[file].star_exports.iter().any(|import_path| {
+            self.source_index(file, import_path)
+                .is_some_and(|source_index| self.has_export(source_index, name, visited))
+        })
+    }
+
+    fn source_index(&self, file: usize, import_path: &str) -> Option<usize> {
+        self.files[file]
+            .import_records
+            .iter()
+            .find(|r| r.import_path == import_path)
+            .and_then(|r| r.source_index)
+    }
+}
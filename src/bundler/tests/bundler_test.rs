@@ -1,7 +1,40 @@
 use std::collections::HashMap;
 
-use bundler::Bundler;
+use bundler::{Bundler, BundlerOptions, FileRepresentation, Format};
 use fs::FSMock;
+use js_lowering::Target;
+use js_printer::Printer;
+
+fn create_bundler(files: Vec<(&str, &str)>) -> Bundler {
+    create_bundler_with_options(files, BundlerOptions::default())
+}
+
+fn create_bundler_with_options(files: Vec<(&str, &str)>, options: BundlerOptions) -> Bundler {
+    let files: HashMap<String, String> = files
+        .into_iter()
+        .map(|(path, content)| (path.to_string(), content.to_string()))
+        .collect();
+    Bundler::with_options(Box::new(FSMock::new(files)), options)
+}
+
+/// Tree shakes the bundle starting from entry.js and prints
+/// every file that still contains any code.
+fn expect_tree_shaken(files: Vec<(&str, &str)>, expected: &str) {
+    let mut bundler = create_bundler(files);
+    bundler.scan(vec!["entry.js"]);
+    bundler.tree_shake();
+
+    let mut output = String::new();
+    for file in bundler.files() {
+        let FileRepresentation::JS(js) = &file.representation;
+        if js.ast.statements.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("// {}\n", file.path));
+        output.push_str(&Printer::new().print_program(&js.ast));
+    }
+    assert_eq!(output, expected);
+}
 
 #[test]
 fn test_bundler() {
@@ -12,3 +45,872 @@ fn test_bundler() {
     let mut bundler = Bundler::new(Box::new(fs));
     bundler.scan(vec!["a.js"]);
 }
+
+#[test]
+fn test_scan_resolves_imports() {
+    let mut bundler = create_bundler(vec![
+        (
+            "src/entry.js",
+            "import a from \"./a\"; import b from \"pkg\";",
+        ),
+        (
+            "src/a/index.js",
+            "import b from \"pkg/b\"; export default b;",
+        ),
+        (
+            "node_modules/pkg/package.json",
+            "{ \"main\": \"./main.js\" }",
+        ),
+        ("node_modules/pkg/main.js", "export default 1;"),
+        ("node_modules/pkg/b.js", "export default 2;"),
+    ]);
+    bundler.scan(vec!["src/entry.js"]);
+
+    let paths: Vec<&str> = bundler.files().iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "src/entry.js",
+            "src/a/index.js",
+            "node_modules/pkg/main.js",
+            "node_modules/pkg/b.js"
+        ]
+    );
+    let sources: Vec<Option<usize>> = bundler.files()[0]
+        .import_records
+        .iter()
+        .map(|r| r.source_index)
+        .collect();
+    assert_eq!(sources, vec![Some(1), Some(2)]);
+}
+
+#[test]
+fn test_tree_shaking_unused_exports() {
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import { a } from \"./lib\"; a();"),
+            (
+                "lib.js",
+                "export function a() { return b(); } function b() {} export function c() {} export const d = 1;",
+            ),
+        ],
+        "// entry.js\nimport { a } from \"./lib\";\na();\n// lib.js\nexport function a() { return b();\n }function b() {}",
+    );
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import { a, b } from \"./lib\"; export { a };"),
+            ("lib.js", "export const a = 1, b = 2; export default a;"),
+        ],
+        "// entry.js\nimport { a } from \"./lib\";\nexport { a };\n// lib.js\nexport const a = 1, b = 2;\n",
+    );
+}
+
+#[test]
+fn test_tree_shaking_keeps_side_effects() {
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import \"./lib\";"),
+            (
+                "lib.js",
+                "export function a() {} console.log(\"lib\"); let b = c(); function c() {}",
+            ),
+        ],
+        "// entry.js\nimport \"./lib\";\n// lib.js\nconsole.log(\"lib\");\nlet b = c();\nfunction c() {}",
+    );
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import { a } from \"./lib\";"),
+            (
+                "lib.js",
+                "export const a = /* @__PURE__ */ b(); export const c = d();",
+            ),
+        ],
+        "// entry.js\nimport \"./lib\";\n// lib.js\nexport const c = d();\n",
+    );
+}
+
+#[test]
+fn test_tree_shaking_package_side_effects() {
+    let files = vec![
+        (
+            "node_modules/pkg/package.json",
+            "{ \"sideEffects\": false }",
+        ),
+        (
+            "node_modules/pkg/index.js",
+            "export { a } from \"./a\"; export { b } from \"./b\";",
+        ),
+        (
+            "node_modules/pkg/a.js",
+            "console.log(\"a\"); export const a = 1;",
+        ),
+        (
+            "node_modules/pkg/b.js",
+            "console.log(\"b\"); export const b = 2;",
+        ),
+    ];
+
+    let mut unused = files.clone();
+    unused.push(("entry.js", "import \"pkg\";"));
+    expect_tree_shaken(unused, "");
+
+    let mut used = files;
+    used.push(("entry.js", "import { a } from \"pkg\"; console.log(a);"));
+    expect_tree_shaken(
+        used,
+        "// entry.js\nimport { a } from \"pkg\";\nconsole.log(a);\n// node_modules/pkg/index.js\nexport { a } from \"./a\";\n// node_modules/pkg/a.js\nconsole.log(\"a\");\nexport const a = 1;\n",
+    );
+}
+
+#[test]
+fn test_tree_shaking_side_effects_globs() {
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import \"pkg/a\"; import \"pkg/b.css.js\"; import \"pkg/polyfills/c\";"),
+            (
+                "node_modules/pkg/package.json",
+                "{ \"sideEffects\": [\"*.css.js\", \"./polyfills/**\"] }",
+            ),
+            ("node_modules/pkg/a.js", "console.log(\"a\");"),
+            ("node_modules/pkg/b.css.js", "console.log(\"b\");"),
+            ("node_modules/pkg/polyfills/c.js", "console.log(\"c\");"),
+        ],
+        "// entry.js\nimport \"pkg/b.css.js\";\nimport \"pkg/polyfills/c\";\n// node_modules/pkg/b.css.js\nconsole.log(\"b\");\n// node_modules/pkg/polyfills/c.js\nconsole.log(\"c\");\n",
+    );
+}
+
+#[test]
+fn test_tree_shaking_re_exports() {
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import { a, c } from \"./b\"; a(c);"),
+            ("b.js", "export * from \"./c\"; export { d as a } from \"./d\";"),
+            ("c.js", "export const c = 1; export const unused = 2;"),
+            ("d.js", "export function d() {} export default function e() {}"),
+        ],
+        "// entry.js\nimport { a, c } from \"./b\";\na(c);\n// b.js\nexport * from \"./c\";\nexport { d as a } from \"./d\";\n// c.js\nexport const c = 1;\n// d.js\nexport function d() {}",
+    );
+}
+
+#[test]
+fn test_tree_shaking_namespace_imports() {
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import * as b from \"./b\"; b.a;"),
+            ("b.js", "export const a = 1; export const c = 2; const d = 3;"),
+        ],
+        "// entry.js\nimport * as b from \"./b\";\nb.a;\n// b.js\nexport const a = 1;\nexport const c = 2;\n",
+    );
+}
+
+#[test]
+fn test_tree_shaking_eval() {
+    // Any top level name may be referenced by eval, so nothing is removed.
+    expect_tree_shaken(
+        vec![
+            ("entry.js", "import { usesEval } from \"./lib\"; usesEval();"),
+            (
+                "lib.js",
+                "var secret = 42; export function usesEval() { return eval(\"secret\"); } export const unused = 1;",
+            ),
+        ],
+        "// entry.js\nimport { usesEval } from \"./lib\";\nusesEval();\n// lib.js\nvar secret = 42;\nexport function usesEval() { return eval(\"secret\");\n }export const unused = 1;\n",
+    );
+}
+
+/// Links the bundle starting from entry.js and prints the result.
+fn bundle(files: Vec<(&str, &str)>) -> String {
+    let mut bundler = create_bundler(files);
+    bundler.scan(vec!["entry.js"]);
+    Printer::new().print_program(&bundler.link().unwrap())
+}
+
+fn expect_bundled(files: Vec<(&str, &str)>, expected: &str) {
+    assert_eq!(bundle(files), expected);
+}
+
+#[test]
+fn test_scope_hoisting() {
+    expect_bundled(
+        vec![
+            (
+                "entry.js",
+                "import { a, b as c } from \"./lib\"; console.log(a, c);",
+            ),
+            ("lib.js", "export let a = 1; let b = 2; export { b };"),
+        ],
+        "let a = 1;\nlet b = 2;\nconsole.log(a, b);\n",
+    );
+    expect_bundled(
+        vec![
+            (
+                "entry.js",
+                "import b from \"./b\"; import c from \"./c\"; b(c);",
+            ),
+            ("b.js", "export default function() {}"),
+            ("c.js", "export default 1 + 1;"),
+        ],
+        "function b_default() {}var c_default = 1 + 1;\nb_default(c_default);\n",
+    );
+}
+
+#[test]
+fn test_scope_hoisting_renames_collisions() {
+    expect_bundled(
+        vec![
+            (
+                "entry.js",
+                "import { a as b } from \"./lib\"; let a = 1; console.log(a, b);",
+            ),
+            ("lib.js", "let a = 2; export { a };"),
+        ],
+        "let a = 2;\nlet a2 = 1;\nconsole.log(a2, a);\n",
+    );
+    // Globals used by any module are never shadowed.
+    expect_bundled(
+        vec![
+            ("entry.js", "import \"./lib\"; console.log(1);"),
+            ("lib.js", "let console = 2;"),
+        ],
+        "let console2 = 2;\nconsole.log(1);\n",
+    );
+    // Nested variables that would capture a renamed reference are renamed.
+    expect_bundled(
+        vec![
+            (
+                "entry.js",
+                "import { x as y } from \"./lib\"; function f(x) { return x + y; }",
+            ),
+            ("lib.js", "export let x = 1;"),
+        ],
+        "let x = 1;\nfunction f(x2) { return x2 + x;\n }",
+    );
+}
+
+#[test]
+fn test_scope_hoisting_exports() {
+    expect_bundled(
+        vec![
+            ("entry.js", "export * from \"./lib\"; export const b = 2; export default b;"),
+            ("lib.js", "export const a = 1;"),
+        ],
+        "const a = 1;\nconst b = 2;\nvar entry_default = b;\nexport { a, b, entry_default as default };\n",
+    );
+}
+
+#[test]
+fn test_scope_hoisting_namespace_imports() {
+    expect_bundled(
+        vec![
+            (
+                "entry.js",
+                "import * as lib from \"./lib\"; console.log(lib);",
+            ),
+            ("lib.js", "export const a = 1;"),
+        ],
+        "var lib_exports = { get a() { return a;\n } };\nconst a = 1;\nconsole.log(lib_exports);\n",
+    );
+}
+
+#[test]
+fn test_scope_hoisting_externals() {
+    expect_bundled(
+        vec![(
+            "entry.js",
+            "import { a } from \"external\"; let a2 = 1; console.log(a, a2);",
+        )],
+        "import { a } from \"external\";\nlet a2 = 1;\nconsole.log(a, a2);\n",
+    );
+}
+
+#[test]
+fn test_wrapped_eval_modules() {
+    expect_bundled(
+        vec![
+            ("entry.js", "import { a } from \"./lib\"; console.log(a);"),
+            ("lib.js", "let b = 1; export const a = eval(\"b\");"),
+        ],
+        "var lib_exports = function() { let b = 1;\nconst a = eval(\"b\");\nreturn { get a() { return a;\n } };\n }();\nconsole.log(lib_exports.a);\n",
+    );
+}
+
+#[test]
+fn test_common_js_interop() {
+    let output = bundle(vec![
+        (
+            "entry.js",
+            "import a, { b } from \"./cjs\"; console.log(a, b);",
+        ),
+        ("cjs.js", "module.exports = { b: require(\"./other\") };"),
+        ("other.js", "exports.c = 1;"),
+    ]);
+    assert!(output.starts_with("var __commonJS = function(callback) {"));
+    assert!(output.contains("var __toESM = function(exports) {"));
+    assert!(!output.contains("var __toCommonJS"));
+    assert!(output.ends_with(
+        "var require_other = __commonJS(function(exports, module) { exports.c = 1;\n });\nvar require_cjs = __commonJS(function(exports, module) { module.exports = { b: require_other() };\n });\nvar import_cjs = __toESM(require_cjs());\nconsole.log(import_cjs.default, import_cjs.b);\n"
+    ));
+
+    // CommonJS entry points default export what they assign to module.exports.
+    let output = bundle(vec![
+        (
+            "entry.js",
+            "const lib = require(\"./lib\"); console.log(lib.a);",
+        ),
+        ("lib.js", "export const a = 1;"),
+    ]);
+    assert!(output.contains("var __toCommonJS = function(namespace) {"));
+    assert!(!output.contains("var __toESM"));
+    assert!(output.ends_with(
+        "var lib_exports = { get a() { return a;\n } };\nconst a = 1;\nvar require_entry = __commonJS(function(exports, module) { const lib = __toCommonJS(lib_exports);\nconsole.log(lib.a);\n });\nexport default require_entry();\n"
+    ));
+
+    // Importing a CommonJS module for its side effects only runs it.
+    let output = bundle(vec![
+        ("entry.js", "import \"./cjs\";"),
+        ("cjs.js", "module.exports = 1;"),
+    ]);
+    assert!(!output.contains("var __toESM"));
+    assert!(output.ends_with(
+        "var require_cjs = __commonJS(function(exports, module) { module.exports = 1;\n });\nrequire_cjs();\n"
+    ));
+}
+
+/// Links the bundle starting from entry.js in the format and prints the result.
+fn bundle_format(files: Vec<(&str, &str)>, format: Format, global_name: Option<&str>) -> String {
+    let options = BundlerOptions {
+        format,
+        global_name: global_name.map(String::from),
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(files, options);
+    bundler.scan(vec!["entry.js"]);
+    Printer::new().print_program(&bundler.link().unwrap())
+}
+
+#[test]
+fn test_format_esm() {
+    let output = bundle_format(
+        vec![
+            ("entry.js", "export { a } from \"./lib\"; export default 1;"),
+            ("lib.js", "export const a = 1;"),
+        ],
+        Format::ESM,
+        None,
+    );
+    assert_eq!(
+        output,
+        "const a = 1;\nvar entry_default = 1;\nexport { a, entry_default as default };\n"
+    );
+}
+
+#[test]
+fn test_format_common_js() {
+    let output = bundle_format(
+        vec![("entry.js", "export const a = 1;")],
+        Format::CommonJS,
+        None,
+    );
+    assert!(output.starts_with("var __toCommonJS = function(namespace) {"));
+    assert!(output
+        .ends_with("const a = 1;\nmodule.exports = __toCommonJS({ get a() { return a;\n } });\n"));
+
+    let output = bundle_format(
+        vec![("entry.js", "module.exports = 1;")],
+        Format::CommonJS,
+        None,
+    );
+    assert!(output.ends_with(
+        "var require_entry = __commonJS(function(exports, module) { module.exports = 1;\n });\nmodule.exports = require_entry();\n"
+    ));
+
+    // External modules are required after the helpers.
+    let output = bundle_format(
+        vec![(
+            "entry.js",
+            "import a, { b } from \"external\"; import \"polyfill\"; console.log(a, b);",
+        )],
+        Format::CommonJS,
+        None,
+    );
+    assert!(output.starts_with("var __toESM = function(exports) {"));
+    assert!(output.ends_with(
+        "var import_external = __toESM(require(\"external\"));\nrequire(\"polyfill\");\nconsole.log(import_external.default, import_external.b);\n"
+    ));
+}
+
+#[test]
+fn test_format_iife() {
+    assert_eq!(
+        bundle_format(
+            vec![("entry.js", "export const a = 1; console.log(a);")],
+            Format::IIFE,
+            None
+        ),
+        "(function() { const a = 1;\nconsole.log(a);\n })();\n"
+    );
+    assert_eq!(
+        bundle_format(
+            vec![("entry.js", "export const a = 1;")],
+            Format::IIFE,
+            Some("MyLib")
+        ),
+        "var MyLib = function() { const a = 1;\nreturn { get a() { return a;\n } };\n }();\n"
+    );
+    assert_eq!(
+        bundle_format(
+            vec![("entry.js", "export const a = 1;")],
+            Format::IIFE,
+            Some("window.MyLib")
+        ),
+        "window.MyLib = function() { const a = 1;\nreturn { get a() { return a;\n } };\n }();\n"
+    );
+
+    let output = bundle_format(
+        vec![("entry.js", "module.exports = 1;")],
+        Format::IIFE,
+        Some("a.b.c"),
+    );
+    assert!(output.starts_with("var a = a || {};\na.b = a.b || {};\na.b.c = function() {"));
+    assert!(output.ends_with("return require_entry();\n }();\n"));
+}
+
+/// Splits the bundle starting from the entry points and prints every chunk.
+fn split(files: Vec<(&str, &str)>, entry_points: Vec<&str>) -> String {
+    let options = BundlerOptions {
+        splitting: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(files, options);
+    bundler.scan(entry_points);
+    let mut output = String::new();
+    for file in bundler.output_files().unwrap() {
+        if file.path != "manifest.json" {
+            output.push_str(&format!("// {}\n{}", file.path, file.contents));
+        }
+    }
+    output
+}
+
+#[test]
+fn test_dynamic_import() {
+    expect_bundled(
+        vec![
+            (
+                "entry.js",
+                "import(\"./lazy\").then(function(lazy) { console.log(lazy.a); });",
+            ),
+            ("lazy.js", "export const a = 1;"),
+        ],
+        "Promise.resolve().then(function() { return lazy_exports;\n }).then(function(lazy) { console.log(lazy.a);\n });\nvar lazy_exports = { get a() { return a;\n } };\nconst a = 1;\n",
+    );
+}
+
+#[test]
+fn test_code_splitting() {
+    let output = split(
+        vec![
+            (
+                "a.js",
+                "import { shared } from \"./shared\"; import(\"./lazy\"); console.log(shared);",
+            ),
+            (
+                "b.js",
+                "import { shared } from \"./shared\"; export const b = shared;",
+            ),
+            ("shared.js", "export const shared = 1;"),
+            (
+                "lazy.js",
+                "import { shared } from \"./shared\"; export default shared;",
+            ),
+        ],
+        vec!["a.js", "b.js"],
+    );
+    assert_eq!(
+        output,
+        "// a.js\nimport { shared } from \"./chunk.js\";\nimport(\"./lazy.js\");\nconsole.log(shared);\n// b.js\nimport { shared } from \"./chunk.js\";\nconst b = shared;\nexport { b };\n// lazy.js\nimport { shared } from \"./chunk.js\";\nvar lazy_default = shared;\nexport { lazy_default as default };\n// chunk.js\nconst shared = 1;\nexport { shared };\n"
+    );
+
+    // An entry point imported by another entry point is placed in a shared chunk.
+    let output = split(
+        vec![
+            ("a.js", "export const a = 1;"),
+            ("b.js", "import { a } from \"./a\"; console.log(a);"),
+        ],
+        vec!["a.js", "b.js"],
+    );
+    assert_eq!(
+        output,
+        "// a.js\nimport { a } from \"./chunk.js\";\nexport { a };\n// b.js\nimport { a } from \"./chunk.js\";\nconsole.log(a);\n// chunk.js\nconst a = 1;\nexport { a };\n"
+    );
+}
+
+#[test]
+fn test_webpack_chunk_name() {
+    let output = split(
+        vec![
+            (
+                "entry.js",
+                "import(/* webpackChunkName: \"settings\" */ \"./lazy\");",
+            ),
+            ("lazy.js", "export default 1;"),
+        ],
+        vec!["entry.js"],
+    );
+    assert_eq!(output, "// entry.js\nimport(\"./settings.js\");\n// settings.js\nvar lazy_default = 1;\nexport { lazy_default as default };\n");
+}
+
+#[test]
+fn test_chunk_placeholders_in_code() {
+    // Only the import paths of the chunks are replaced.
+    let output = split(
+        vec![
+            (
+                "entry.js",
+                "import(\"./lazy\"); console.log(\"__PACKET_CHUNK_1__\");",
+            ),
+            ("lazy.js", "export default 1;"),
+        ],
+        vec!["entry.js"],
+    );
+    assert_eq!(output, "// entry.js\nimport(\"./lazy.js\");\nconsole.log(\"__PACKET_CHUNK_1__\");\n// lazy.js\nvar lazy_default = 1;\nexport { lazy_default as default };\n");
+}
+
+/// Splits the bundle with hashed names and returns the output files by path.
+fn split_hashed(files: Vec<(&str, &str)>, entry_points: Vec<&str>) -> HashMap<String, String> {
+    let options = BundlerOptions {
+        splitting: true,
+        entry_names: "[name]-[hash].js".to_string(),
+        chunk_names: "chunks/[name]-[hash].js".to_string(),
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(files, options);
+    bundler.scan(entry_points);
+    bundler
+        .output_files()
+        .unwrap()
+        .into_iter()
+        .map(|file| (file.path, file.contents))
+        .collect()
+}
+
+#[test]
+fn test_hashed_chunk_names() {
+    let files = vec![
+        (
+            "a.js",
+            "import { shared } from \"./shared\"; import(\"./lazy\"); console.log(shared);",
+        ),
+        (
+            "b.js",
+            "import { shared } from \"./shared\"; console.log(shared);",
+        ),
+        ("shared.js", "export const shared = 1;"),
+        ("lazy.js", "export default 1;"),
+    ];
+    let output = split_hashed(files.clone(), vec!["a.js", "b.js"]);
+    assert_eq!(
+        output["manifest.json"],
+        "{\n  \"a.js\": \"a-73472dd2.js\",\n  \"b.js\": \"b-83e83dcf.js\"\n}\n"
+    );
+    assert_eq!(
+        output["a-73472dd2.js"],
+        "import { shared } from \"./chunks/chunk-5204685e.js\";\nimport(\"./chunks/lazy-37b65e5b.js\");\nconsole.log(shared);\n"
+    );
+    assert!(output.contains_key("chunks/chunk-5204685e.js"));
+    assert!(output.contains_key("chunks/lazy-37b65e5b.js"));
+
+    // A change to a chunk changes the hashes of the chunks importing it, and only those.
+    let mut changed = files.clone();
+    changed[3] = ("lazy.js", "export default 2;");
+    let output = split_hashed(changed, vec!["a.js", "b.js"]);
+    assert!(!output.contains_key("a-73472dd2.js"));
+    assert!(!output.contains_key("chunks/lazy-37b65e5b.js"));
+    assert!(output.contains_key("b-83e83dcf.js"));
+    assert!(output.contains_key("chunks/chunk-5204685e.js"));
+}
+
+#[test]
+fn test_metafile() {
+    let options = BundlerOptions {
+        splitting: true,
+        metafile: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![
+            (
+                "entry.js",
+                "import { a } from \"./a\"; import \"external\"; import(\"./lazy\"); console.log(a);",
+            ),
+            ("a.js", "export const a = 1; export const unused = 2;"),
+            ("lazy.js", "export default require(\"./b\");"),
+            ("b.js", "module.exports = 1;"),
+        ],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    bundler.tree_shake();
+    let output_files = bundler.output_files().unwrap();
+    let metafile = output_files
+        .iter()
+        .find(|file| file.path == "metafile.json")
+        .unwrap();
+    let contents = &metafile.contents;
+
+    assert!(contents.contains(
+        "\"entry.js\": {\n      \"bytes\": 77,\n      \"imports\": [\n        {\n          \"path\": \"a.js\",\n          \"kind\": \"static\"\n        },\n        {\n          \"path\": \"external\",\n          \"external\": true,\n          \"kind\": \"static\"\n        },\n        {\n          \"path\": \"lazy.js\",\n          \"kind\": \"dynamic\"\n        }\n      ]\n    }"
+    ));
+    assert!(contents.contains("\"path\": \"b.js\",\n          \"kind\": \"require\""));
+    // Only the statements kept by the tree shaking count towards the output.
+    assert!(contents.contains("\"a.js\": {\n          \"bytesInOutput\": 13\n        }"));
+    assert!(contents.contains("\"lazy.js\": {\n      \"bytes\": 326,\n      \"inputs\""));
+
+    // The formats that can not be split produce a single file with the same metafile.
+    let options = BundlerOptions {
+        format: Format::CommonJS,
+        metafile: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![
+            ("entry.js", "import { a } from \"./a\"; console.log(a);"),
+            ("a.js", "export const a = 1;"),
+        ],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    let paths: Vec<String> = bundler
+        .output_files()
+        .unwrap()
+        .into_iter()
+        .map(|file| file.path)
+        .collect();
+    assert_eq!(paths, vec!["entry.js", "metafile.json"]);
+
+    // So does ESM without splitting, the modules imported with `import()` are included.
+    let options = BundlerOptions {
+        metafile: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![
+            ("entry.js", "import(\"./lazy\");"),
+            ("lazy.js", "export default 1;"),
+        ],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    let output_files = bundler.output_files().unwrap();
+    let paths: Vec<&str> = output_files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(paths, vec!["entry.js", "metafile.json"]);
+    assert!(output_files[1]
+        .contents
+        .contains("\"lazy.js\": {\n          \"bytesInOutput\": "));
+}
+
+#[test]
+fn test_define() {
+    let options = BundlerOptions {
+        define: vec![
+            ("__DEV__".to_string(), "false".to_string()),
+            (
+                "process.env.NODE_ENV".to_string(),
+                "\"production\"".to_string(),
+            ),
+        ],
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![
+            (
+                "entry.js",
+                "import { a } from \"./a\"; console.log(__DEV__, process.env.NODE_ENV, a);",
+            ),
+            ("a.js", "const __DEV__ = 1; export const a = __DEV__;"),
+        ],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    bundler.tree_shake();
+    assert_eq!(
+        Printer::new().print_program(&bundler.link().unwrap()),
+        "const __DEV__ = 1;\nconst a = __DEV__;\nconsole.log(false, \"production\", a);\n"
+    );
+}
+
+#[test]
+fn test_invalid_define() {
+    let options = BundlerOptions {
+        define: vec![("__DEV__".to_string(), "a); (b".to_string())],
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(vec![("entry.js", "__DEV__;")], options);
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        bundler.link().unwrap_err(),
+        vec!["Invalid value for the define of \"__DEV__\": \"a); (b\""]
+    );
+}
+
+#[test]
+fn test_missing_files() {
+    let mut bundler = create_bundler(vec![(
+        "entry.js",
+        "import \"./missing\"; import \"external\";",
+    )]);
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        bundler.link().unwrap_err(),
+        vec!["Could not resolve \"./missing\" from \"entry.js\""]
+    );
+
+    let mut bundler = create_bundler(vec![]);
+    bundler.scan(vec!["entry.js"]);
+    bundler.tree_shake();
+    assert_eq!(
+        bundler.output_files().unwrap_err(),
+        vec!["Could not read file \"entry.js\": File not found"]
+    );
+}
+
+#[test]
+fn test_minify_syntax() {
+    let options = BundlerOptions {
+        define: vec![(
+            "process.env.NODE_ENV".to_string(),
+            "\"production\"".to_string(),
+        )],
+        minify_syntax: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![
+            (
+                "entry.js",
+                "if (process.env.NODE_ENV !== \"production\") { require(\"./dev\"); } a(true); b();",
+            ),
+            ("dev.js", "console.log(\"dev\");"),
+        ],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    bundler.tree_shake();
+    // The dead branch is removed before the imports are collected.
+    assert_eq!(bundler.files().len(), 1);
+    assert_eq!(
+        Printer::new().print_program(&bundler.link().unwrap()),
+        "a(!0), b();\n"
+    );
+}
+
+#[test]
+fn test_minify_syntax_removes_unused_pure_calls() {
+    let options = BundlerOptions {
+        minify_syntax: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![(
+            "entry.js",
+            "/* @__PURE__ */ a(); export function b() { /* @__PURE__ */ c(d()); }",
+        )],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        Printer::new().print_program(&bundler.link().unwrap()),
+        "function b() { d();\n }export { b };\n"
+    );
+}
+
+#[test]
+fn test_target() {
+    let options = BundlerOptions {
+        target: Target::ES5,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![
+            (
+                "entry.js",
+                "import { double } from \"./lib\"; a(double(`${b}`));",
+            ),
+            ("lib.js", "export var double = (x) => x * 2;"),
+        ],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        Printer::new().print_program(&bundler.link().unwrap()),
+        "var double = function(x) { return x * 2;\n };\na(double(\"\" + b));\n"
+    );
+}
+
+#[test]
+fn test_assume_arrays() {
+    let options = BundlerOptions {
+        target: Target::ES5,
+        assume_arrays: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler =
+        create_bundler_with_options(vec![("entry.js", "for (var a of b) c(...a);")], options);
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        Printer::new().print_program(&bundler.link().unwrap()),
+        "for (var _a = 0, _b = b; _a < _b.length; _a++) { var a = _b[_a];\nc.apply(void 0, a);\n }"
+    );
+}
+
+#[test]
+fn test_target_unsupported_syntax() {
+    let options = BundlerOptions {
+        target: Target::ES5,
+        ..BundlerOptions::default()
+    };
+    let mut bundler =
+        create_bundler_with_options(vec![("entry.js", "async function* a() {}")], options);
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        bundler.link().unwrap_err(),
+        vec!["Transforming async generator functions to the configured target environment (\"es5\") is not supported yet"]
+    );
+}
+
+#[test]
+fn test_ascii_only() {
+    let options = BundlerOptions {
+        ascii_only: true,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(
+        vec![("entry.js", "console.log(\"ñ\", `𠮷${a}`, /ñ/);")],
+        options,
+    );
+    bundler.scan(vec!["entry.js"]);
+    let output_files = bundler.output_files().unwrap();
+    let entry = output_files
+        .iter()
+        .find(|file| file.path == "entry.js")
+        .unwrap();
+    assert_eq!(
+        entry.contents,
+        "console.log(\"\\u00F1\", `\\u{20BB7}${a}`, /\\u00F1/);\n"
+    );
+}
+
+#[test]
+fn test_ascii_only_es5_identifier() {
+    let options = BundlerOptions {
+        ascii_only: true,
+        target: Target::ES5,
+        ..BundlerOptions::default()
+    };
+    let mut bundler = create_bundler_with_options(vec![("entry.js", "𠮷();")], options);
+    bundler.scan(vec!["entry.js"]);
+    assert_eq!(
+        bundler.output_files().unwrap_err(),
+        vec!["Cannot use \"𠮷\" as an identifier in ES5 with ASCII-only output, it contains characters that can not be escaped"]
+    );
+}
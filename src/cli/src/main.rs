@@ -1,7 +1,13 @@
+use ::fs::FSImpl;
+use bundler::{parse_defines, Bundler, BundlerOptions, Format};
+use js_ast::scope::replace_globals;
+use js_lowering::{insert_helpers, lower_syntax_with_options, LoweringOptions, Target};
+use js_minifier::{fold_constants, minify_syntax};
 use js_parser::parse;
-use js_printer::Printer;
+use js_printer::{LegalComments, Printer, PrinterOptions};
 use logger::LoggerImpl;
 use source::Source;
+use std::fmt::Display;
 use std::fs;
 use std::path::PathBuf;
 use std::{env, time::Instant};
@@ -9,29 +15,222 @@ use std::{env, time::Instant};
 struct Arguments {
     input_file: PathBuf,
     out_file: Option<PathBuf>,
+    legal_comments: LegalComments,
+    /// Bundles the input file together with the files it imports.
+    bundle: bool,
+    /// Splits the bundle into chunks, the output is then a directory.
+    splitting: bool,
+    bundler_options: BundlerOptions,
+}
+
+fn is_valid_global_name(global_name: &str) -> bool {
+    global_name.split('.').all(|segment| {
+        let mut chars = segment.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+    })
+}
+
+/// Prints the errors, prefixed with the path of the input file, and exits.
+fn exit_with_errors<T: Display>(input_file: &str, errors: &[T]) -> ! {
+    for error in errors {
+        eprintln!("{}: {}", input_file, error);
+    }
+    std::process::exit(1);
+}
+
+/// Prints an error in the arguments and exits.
+fn exit_with_argument_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn parse_arguments() -> Arguments {
+    let mut positional: Vec<String> = Vec::new();
+    let mut legal_comments = LegalComments::Inline;
+    let mut bundle = false;
+    let mut splitting = false;
+    let mut naming_templates = false;
+    let mut bundler_options = BundlerOptions::default();
+
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("--legal-comments=") {
+            legal_comments = match value {
+                "inline" => LegalComments::Inline,
+                "eof" => LegalComments::EndOfFile,
+                "external" => LegalComments::External,
+                "none" => LegalComments::None,
+                _ => exit_with_argument_error(&format!(
+                    "Invalid value for --legal-comments: \"{}\"",
+                    value
+                )),
+            };
+        } else if arg == "--bundle" {
+            bundle = true;
+        } else if arg == "--splitting" {
+            splitting = true;
+        } else if arg == "--minify-syntax" {
+            bundler_options.minify_syntax = true;
+        } else if arg == "--ascii-only" {
+            bundler_options.ascii_only = true;
+        } else if arg == "--assume-arrays" {
+            bundler_options.assume_arrays = true;
+        } else if arg == "--metafile" {
+            bundler_options.metafile = true;
+        } else if let Some(value) = arg.strip_prefix("--format=") {
+            bundler_options.format = match value {
+                "esm" => Format::ESM,
+                "cjs" => Format::CommonJS,
+                "iife" => Format::IIFE,
+                _ => {
+                    exit_with_argument_error(&format!("Invalid value for --format: \"{}\"", value))
+                }
+            };
+        } else if let Some(value) = arg.strip_prefix("--target=") {
+            bundler_options.target = Target::from_name(value).unwrap_or_else(|| {
+                exit_with_argument_error(&format!("Invalid value for --target: \"{}\"", value))
+            });
+        } else if let Some(value) = arg.strip_prefix("--global-name=") {
+            if !is_valid_global_name(value) {
+                exit_with_argument_error(&format!(
+                    "Invalid value for --global-name: \"{}\"",
+                    value
+                ));
+            }
+            bundler_options.global_name = Some(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("--define:") {
+            let (name, value) = value.split_once('=').unwrap_or_else(|| {
+                exit_with_argument_error(&format!("Invalid value for --define: \"{}\"", value))
+            });
+            bundler_options
+                .define
+                .push((name.to_string(), value.to_string()));
+        } else if let Some(value) = arg.strip_prefix("--entry-names=") {
+            bundler_options.entry_names = value.to_string();
+            naming_templates = true;
+        } else if let Some(value) = arg.strip_prefix("--chunk-names=") {
+            bundler_options.chunk_names = value.to_string();
+            naming_templates = true;
+        } else if arg.starts_with("--") {
+            exit_with_argument_error(&format!("Unknown flag \"{}\"", arg));
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    if splitting && !bundle {
+        exit_with_argument_error("--splitting requires --bundle");
+    }
+    // Without splitting the bundle is written to the output file, which names it.
+    if naming_templates && !splitting {
+        exit_with_argument_error("--entry-names and --chunk-names require --splitting");
+    }
+
+    bundler_options.legal_comments = legal_comments;
+    let mut positional = positional.into_iter();
+    let input_file = positional
+        .next()
+        .unwrap_or_else(|| exit_with_argument_error("Input file is required"));
+    Arguments {
+        input_file: PathBuf::from(input_file),
+        out_file: positional.next().map(PathBuf::from),
+        legal_comments,
+        bundle,
+        splitting,
+        bundler_options,
+    }
 }
 
 fn main() {
     let now = Instant::now();
-    let input_file = env::args().nth(1).expect("Input file is required");
-    let out_file = env::args().nth(2).map(PathBuf::from);
-    let args = Arguments {
-        input_file: PathBuf::from(&input_file),
-        out_file: out_file,
-    };
-    let content = fs::read_to_string(args.input_file).expect("Failed to read file");
+    let args = parse_arguments();
+    let input_file = args.input_file.to_string_lossy().to_string();
+
+    if args.bundle {
+        let mut bundler_options = args.bundler_options;
+        bundler_options.splitting = args.splitting;
+        // With splitting the output is a directory with a file for every chunk, otherwise
+        // the bundle is written to the output file and the other files are placed next to it.
+        let out_dir = match args.out_file {
+            Some(out_file) if !args.splitting => {
+                let name = out_file
+                    .file_name()
+                    .unwrap_or_else(|| exit_with_argument_error("Invalid output file"));
+                bundler_options.entry_names = name.to_string_lossy().to_string();
+                out_file.parent().map(PathBuf::from)
+            }
+            out_dir => out_dir,
+        };
+        if args.splitting && out_dir.is_none() {
+            exit_with_argument_error("An output directory is required");
+        }
+
+        let mut bundler = Bundler::with_options(Box::new(FSImpl), bundler_options);
+        bundler.scan(vec![&input_file]);
+        bundler.tree_shake();
+        let files = bundler
+            .output_files()
+            .unwrap_or_else(|errors| exit_with_errors(&input_file, &errors));
+        if let Some(out_dir) = out_dir {
+            for file in files {
+                let path = out_dir.join(&file.path);
+                if let Some(directory) = path.parent() {
+                    fs::create_dir_all(directory).expect("Failed to create directory");
+                }
+                fs::write(path, file.contents).expect("Failed to write to file");
+            }
+        }
+        println!("Done in {}ms", now.elapsed().as_millis());
+        return;
+    }
 
+    let content = fs::read_to_string(&args.input_file).expect("Failed to read file");
     let source = Source {
         absolute_path: &input_file,
         pretty_path: &input_file,
         content: &content,
     };
-
     let logger = LoggerImpl::new();
-    let ast = parse(&source, &logger);
+    let mut ast = parse(&source, &logger);
+    let defines = parse_defines(&args.bundler_options.define)
+        .unwrap_or_else(|error| exit_with_errors(&input_file, &[error]));
+    if !defines.is_empty() {
+        replace_globals(&mut ast, &defines);
+    }
+    let options = LoweringOptions {
+        target: args.bundler_options.target,
+        assume_arrays: args.bundler_options.assume_arrays,
+    };
+    match lower_syntax_with_options(&mut ast, options) {
+        Ok(helpers) => insert_helpers(&mut ast, &helpers),
+        Err(errors) => exit_with_errors(&input_file, &errors),
+    }
+    if args.bundler_options.minify_syntax {
+        fold_constants(&mut ast);
+        minify_syntax(&mut ast);
+    }
     if let Some(out_file) = args.out_file {
-        let output = Printer::new().print_program(&ast);
-        fs::write(out_file, output).expect("Failed to write to file");
+        let mut printer = Printer::with_options(PrinterOptions {
+            legal_comments: args.legal_comments,
+            minify_syntax: args.bundler_options.minify_syntax,
+            ascii_only: args.bundler_options.ascii_only,
+            es5: args.bundler_options.target == Target::ES5,
+        });
+        let output = printer.print_program(&ast);
+        if !printer.errors().is_empty() {
+            exit_with_errors(&input_file, printer.errors());
+        }
+        fs::write(&out_file, output).expect("Failed to write to file");
+
+        if args.legal_comments == LegalComments::External && !printer.legal_comments().is_empty() {
+            let mut legal_file = out_file.into_os_string();
+            legal_file.push(".LEGAL.txt");
+            let mut text = printer.legal_comments().join("\n");
+            text.push('\n');
+            fs::write(legal_file, text).expect("Failed to write to file");
+        }
     }
     println!("Done in {}ms", now.elapsed().as_millis());
 }
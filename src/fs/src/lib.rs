@@ -27,7 +27,7 @@ impl FS for FSMock {
 pub struct FSImpl;
 
 impl FS for FSImpl {
-    fn read_file(&self, _: &str) -> Result<String, std::io::Error> {
-        todo!()
+    fn read_file(&self, path: &str) -> Result<String, std::io::Error> {
+        std::fs::read_to_string(path)
     }
 }
@@ -41,6 +41,9 @@
 use precedence::{Precedence, PrecedenceInfo};
 
 pub mod precedence;
+pub mod scope;
+pub mod side_effects;
+pub mod visit;
 
 /// The AST is the top level node that contains all of the statements
 /// and expression the program contains.
@@ -91,6 +94,7 @@ pub enum Expression {
     Assignment(AssignmentExpression),
     ArrowFunction(ArrowFunctionExpression),
     Array(ArrayExpression),
+    Await(AwaitExpression),
     BigIntLiteral(BigIntLiteral),
     Binary(BinaryExpression),
     BooleanLiteral(BooleanLiteral),
@@ -99,6 +103,8 @@ pub enum Expression {
     Conditional(ConditionalExpression),
     Function(FunctionExpression),
     Identifier(Identifier),
+    Import(ImportExpression),
+    ImportMeta(ImportMetaExpression),
     Logical(LogicalExpression),
     Member(MemberExpression),
     New(NewExpression),
@@ -113,6 +119,7 @@ pub enum Expression {
     This(ThisExpression),
     Unary(UnaryExpression),
     Update(UpdateExpression),
+    Yield(YieldExpression),
 }
 
 /// This is the top level literal enum,
@@ -139,6 +146,7 @@ pub enum Statement {
     BlockStatement(BlockStatement),
     BreakStatement(BreakStatement),
     ClassDeclaration(ClassDeclaration),
+    Comment(Comment),
     ContinueStatement(ContinueStatement),
     DebuggerStatement(DebuggerStatement),
     DoWhileStatement(DoWhileStatement),
@@ -183,6 +191,7 @@ pub struct AnonymousDefaultExportedClassDeclaration {
 #[derive(Debug, PartialEq, Clone)]
 pub struct AnonymousDefaultExportedFunctionDeclaration {
     pub generator: bool,
+    pub is_async: bool,
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
 }
@@ -262,6 +271,7 @@ pub struct ArrayBindingItem {
 pub struct ArrowFunctionExpression {
     pub parameters: Vec<ParameterKind>,
     pub body: ArrowFunctionExpressionBody,
+    pub is_async: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -270,11 +280,18 @@ pub enum ArrowFunctionExpressionBody {
     Expression(Box<Expression>),
 }
 
+/// await a
+#[derive(Debug, PartialEq, Clone)]
+pub struct AwaitExpression {
+    pub argument: Box<Expression>,
+}
+
 /// 1n
 ///
 /// The value is stored as a string to avoid precision loss.
 ///
-/// The string does not include the n suffix.
+/// The string is the digits as written, including a radix prefix such as `0x`,
+/// but does not include the numeric separators or the n suffix.
 #[derive(Debug, PartialEq, Clone)]
 pub struct BigIntLiteral {
     pub value: String,
@@ -367,17 +384,11 @@ impl PrecedenceInfo for BinaryExpressionOperator {
     }
 
     fn is_right_associative(&self) -> bool {
-        match self {
-            &BinaryExpressionOperator::Exponentiation => true,
-            _ => false,
-        }
+        matches!(self, BinaryExpressionOperator::Exponentiation)
     }
 
     fn is_left_associative(&self) -> bool {
-        match self {
-            &BinaryExpressionOperator::Exponentiation => false,
-            _ => true,
-        }
+        !matches!(self, BinaryExpressionOperator::Exponentiation)
     }
 }
 
@@ -398,10 +409,15 @@ pub struct BreakStatement {
 }
 
 /// a()
+///
+/// A call is pure if it was marked with a `/* @__PURE__ */` comment
+/// or if it calls a function marked with `/* #__NO_SIDE_EFFECTS__ */`,
+/// it can then be removed if the result is unused.
 #[derive(Debug, PartialEq, Clone)]
 pub struct CallExpression {
     pub callee: Box<Expression>,
     pub arguments: Vec<ArgumentKind>,
+    pub pure: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -455,6 +471,8 @@ pub struct ClassConstructor {
 #[derive(Debug, PartialEq, Clone)]
 pub struct ClassMethod {
     pub is_static: bool,
+    pub is_async: bool,
+    pub generator: bool,
     pub identifier: LiteralPropertyName,
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
@@ -463,6 +481,8 @@ pub struct ClassMethod {
 #[derive(Debug, PartialEq, Clone)]
 pub struct ClassMethodComputed {
     pub is_static: bool,
+    pub is_async: bool,
+    pub generator: bool,
     pub key: Expression,
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
@@ -500,6 +520,15 @@ pub struct ClassMethodSetComputed {
     pub body: BlockStatement,
 }
 
+/// A comment that is kept in the output, such as a legal comment.
+/// The text includes the comment delimiters.
+///
+/// /*! Copyright */
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    pub text: String,
+}
+
 /// a ? b : c
 #[derive(Debug, PartialEq, Clone)]
 pub struct ConditionalExpression {
@@ -597,6 +626,8 @@ pub struct ForOfStatement {
     pub left: Box<Statement>,
     pub right: Expression,
     pub body: Box<Statement>,
+    /// for await (a of b) {}
+    pub is_await: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -620,6 +651,9 @@ pub struct FunctionDeclaration {
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
     pub generator: bool,
+    pub is_async: bool,
+    /// Set by a `/* #__NO_SIDE_EFFECTS__ */` comment.
+    pub no_side_effects: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -628,6 +662,7 @@ pub struct FunctionExpression {
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
     pub generator: bool,
+    pub is_async: bool,
 }
 
 /// a
@@ -643,6 +678,19 @@ pub struct IfStatement {
     pub alternate: Option<Box<Statement>>,
 }
 
+/// import("a")
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportExpression {
+    pub source: Box<Expression>,
+    /// The name of the chunk from a `/* webpackChunkName: "name" */` comment
+    /// in front of the source, the bundler names the chunk of the module with it.
+    pub chunk_name: Option<String>,
+}
+
+/// import.meta
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportMetaExpression {}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ImportDeclaration {
     pub default: Option<Identifier>,
@@ -709,10 +757,14 @@ pub struct MemberExpression {
     pub computed: bool,
 }
 
+/// new a()
+///
+/// Just like call expressions, new expressions can be marked as pure.
 #[derive(Debug, PartialEq, Clone)]
 pub struct NewExpression {
     pub callee: Box<Expression>,
     pub arguments: Vec<ArgumentKind>,
+    pub pure: bool,
 }
 
 /// null
@@ -763,6 +815,8 @@ pub struct ObjectExpressionPropertyShorthand {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ObjectExpressionMethod {
+    pub is_async: bool,
+    pub generator: bool,
     pub key: LiteralPropertyName,
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
@@ -770,6 +824,8 @@ pub struct ObjectExpressionMethod {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ObjectExpressionMethodComputed {
+    pub is_async: bool,
+    pub generator: bool,
     pub key: Expression,
     pub parameters: Vec<ParameterKind>,
     pub body: BlockStatement,
@@ -1053,3 +1109,11 @@ pub struct WithStatement {
     pub object: Expression,
     pub body: Box<Statement>,
 }
+
+/// yield a
+/// yield* a
+#[derive(Debug, PartialEq, Clone)]
+pub struct YieldExpression {
+    pub argument: Option<Box<Expression>>,
+    pub delegate: bool,
+}
@@ -3,7 +3,6 @@
 ///
 /// The MDN specification for the precedence rules can be found here:
 /// https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/Operator_Precedence#table
-
 pub trait PrecedenceInfo {
     fn precedence(&self) -> Precedence;
     fn is_right_associative(&self) -> bool;
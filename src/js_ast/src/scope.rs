@@ -0,0 +1,713 @@
+/// This file contains the scope analysis used when renaming variables.
+///
+/// The AST does not store any scope information so the scopes are
+/// rebuilt while walking the tree. Function bodies, blocks, loops with
+/// lexical declarations, catch clauses and named function and class
+/// expressions all create a new scope.
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::visit::{
+    walk_arguments, walk_arrow_function, walk_binding, walk_block, walk_class, walk_expression,
+    walk_function, walk_statement, VisitorMut,
+};
+use crate::*;
+
+/// Collects the names of all of the identifiers in a binding.
+pub fn binding_names(binding: &Binding, names: &mut Vec<String>) {
+    match binding {
+        Binding::Identifier(i) => names.push(i.name.clone()),
+        Binding::Object(o) => {
+            for property in &o.properties {
+                match property {
+                    ObjectBindingPropertyKind::Property(p) => binding_names(&p.binding, names),
+                    ObjectBindingPropertyKind::Computed(p) => binding_names(&p.binding, names),
+                    ObjectBindingPropertyKind::Rest(r) => names.push(r.key.name.clone()),
+                    ObjectBindingPropertyKind::Shorthand(s) => names.push(s.key.name.clone()),
+                }
+            }
+        }
+        Binding::Array(a) => {
+            for item in a.items.iter().flatten() {
+                match item {
+                    ArrayBindingItemKind::Item(i) => binding_names(&i.binding, names),
+                    ArrayBindingItemKind::Rest(r) => binding_names(&r.binding, names),
+                }
+            }
+        }
+    }
+}
+
+/// Collects the names declared by a variable declaration.
+pub fn variable_names(declaration: &VariableDeclaration, names: &mut Vec<String>) {
+    for declarator in &declaration.declarations {
+        binding_names(&declarator.binding, names);
+    }
+}
+
+/// Collects the names declared with `var` in the statements, including the ones
+/// in nested blocks and loops since they are hoisted to the function scope.
+pub fn var_names(statements: &[Statement], names: &mut Vec<String>) {
+    for statement in statements {
+        var_names_in_statement(statement, names);
+    }
+}
+
+fn var_names_in_statement(statement: &Statement, names: &mut Vec<String>) {
+    match statement {
+        Statement::VariableDeclaration(v) if v.kind == VariableDeclarationKind::Var => {
+            variable_names(v, names)
+        }
+        Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+            declaration: ExportNamedDeclarationKind::VariableDeclaration(v),
+        }) if v.kind == VariableDeclarationKind::Var => variable_names(v, names),
+        Statement::BlockStatement(b) => var_names(&b.statements, names),
+        Statement::DoWhileStatement(d) => var_names_in_statement(&d.body, names),
+        Statement::WhileStatement(w) => var_names_in_statement(&w.body, names),
+        Statement::WithStatement(w) => var_names_in_statement(&w.body, names),
+        Statement::LabeledStatement(l) => var_names_in_statement(&l.body, names),
+        Statement::IfStatement(i) => {
+            var_names_in_statement(&i.consequent, names);
+            if let Some(alternate) = &i.alternate {
+                var_names_in_statement(alternate, names);
+            }
+        }
+        Statement::ForStatement(f) => {
+            if let Some(init) = &f.init {
+                var_names_in_statement(init, names);
+            }
+            var_names_in_statement(&f.body, names);
+        }
+        Statement::ForInStatement(f) => {
+            var_names_in_statement(&f.left, names);
+            var_names_in_statement(&f.body, names);
+        }
+        Statement::ForOfStatement(f) => {
+            var_names_in_statement(&f.left, names);
+            var_names_in_statement(&f.body, names);
+        }
+        Statement::SwitchStatement(s) => {
+            for case in &s.cases {
+                for consequent in &case.consequent {
+                    var_names_in_statement(consequent, names);
+                }
+            }
+        }
+        Statement::TryStatement(t) => {
+            var_names(&t.block.statements, names);
+            if let Some(handler) = &t.handler {
+                var_names(&handler.body.statements, names);
+            }
+            if let Some(finalizer) = &t.finalizer {
+                var_names(&finalizer.statements, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the names declared directly in a list of statements with
+/// `let`, `const`, `class` and `function`, and the ones bound by imports.
+pub fn lexical_names(statements: &[Statement], names: &mut Vec<String>) {
+    for statement in statements {
+        match statement {
+            Statement::VariableDeclaration(v) if v.kind != VariableDeclarationKind::Var => {
+                variable_names(v, names)
+            }
+            Statement::FunctionDeclaration(f) => names.push(f.identifier.name.clone()),
+            Statement::ClassDeclaration(c) => names.push(c.identifier.name.clone()),
+            Statement::ExportNamedDeclaration(e) => match &e.declaration {
+                ExportNamedDeclarationKind::FunctionDeclaration(f) => {
+                    names.push(f.identifier.name.clone())
+                }
+                ExportNamedDeclarationKind::ClassDeclaration(c) => {
+                    names.push(c.identifier.name.clone())
+                }
+                ExportNamedDeclarationKind::VariableDeclaration(v) => {
+                    if v.kind != VariableDeclarationKind::Var {
+                        variable_names(v, names)
+                    }
+                }
+            },
+            Statement::ExportDefaultDeclaration(e) => match &e.declaration {
+                ExportDefaultDeclarationKind::FunctionDeclaration(f) => {
+                    names.push(f.identifier.name.clone())
+                }
+                ExportDefaultDeclarationKind::ClassDeclaration(c) => {
+                    names.push(c.identifier.name.clone())
+                }
+                _ => {}
+            },
+            Statement::ImportDeclaration(i) => {
+                if let Some(default) = &i.default {
+                    names.push(default.name.clone());
+                }
+                if let Some(namespace) = &i.namespace {
+                    names.push(namespace.name.clone());
+                }
+                for specifier in &i.specifiers {
+                    names.push(specifier.imported.name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns all of the names declared at the top level of the program.
+pub fn top_level_names(program: &AST) -> Vec<String> {
+    let mut names = Vec::new();
+    var_names(&program.statements, &mut names);
+    lexical_names(&program.statements, &mut names);
+    names
+}
+
+/// Returns the names declared in the scope of a function, that is the
+/// parameters and all of the declarations in the body.
+pub fn function_scope_names(parameters: &[ParameterKind], body: &[Statement]) -> Vec<String> {
+    let mut names = Vec::new();
+    for parameter in parameters {
+        match parameter {
+            ParameterKind::Parameter(p) => binding_names(&p.binding, &mut names),
+            ParameterKind::Rest(r) => binding_names(&r.binding, &mut names),
+        }
+    }
+    var_names(body, &mut names);
+    lexical_names(body, &mut names);
+    names
+}
+
+/// Replaces every reference to a top level or global variable that has an entry
+/// in `renames` with the given expression, which is usually an identifier.
+/// Top level declarations are renamed as well when the replacement is an identifier.
+///
+/// Variables in nested scopes that would capture one of the new names
+/// are renamed so that the references keep pointing to the same variable.
+pub fn rename_symbols(program: &mut AST, renames: &HashMap<String, Expression>) {
+    let mut renamer = Renamer::new(program, renames);
+    renamer.visit_program(program);
+}
+
+/// Replaces every reference to a global variable, or member chain on one such as
+/// `process.env.NODE_ENV` or `import.meta.env.MODE`, that has an entry in `defines`.
+/// Variables declared by the program shadow the globals and are left as they are.
+pub fn replace_globals(program: &mut AST, defines: &HashMap<String, Expression>) {
+    let mut renamer = Renamer::new(program, defines);
+    renamer.push_scope(top_level_names(program));
+    renamer.visit_program(program);
+}
+
+/// Returns the names that are used in the program without being declared in a
+/// nested scope. These are either declared at the top level or are globals.
+pub fn unbound_names(program: &mut AST) -> BTreeSet<String> {
+    let renames = HashMap::new();
+    let mut renamer = Renamer {
+        renames: &renames,
+        targets: HashSet::new(),
+        reserved: HashSet::new(),
+        scopes: Vec::new(),
+        unbound: BTreeSet::new(),
+    };
+    renamer.visit_program(program);
+    renamer.unbound
+}
+
+/// Returns the dotted name of a member chain like `a.b.c`, None for other expressions.
+fn member_chain(expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Identifier(i) => Some(i.name.clone()),
+        Expression::ImportMeta(_) => Some("import.meta".to_string()),
+        Expression::Member(MemberExpression {
+            object,
+            property,
+            computed: false,
+        }) => match &**property {
+            Expression::Identifier(i) => {
+                member_chain(object).map(|chain| format!("{}.{}", chain, i.name))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the identifier at the root of a member chain.
+fn member_root(expression: &Expression) -> Option<&str> {
+    match expression {
+        Expression::Identifier(i) => Some(&i.name),
+        Expression::Member(m) => member_root(&m.object),
+        _ => None,
+    }
+}
+
+fn expression_names(expression: &Expression, names: &mut HashSet<String>) {
+    match expression {
+        Expression::Identifier(i) => {
+            names.insert(i.name.clone());
+        }
+        Expression::Member(m) => {
+            expression_names(&m.object, names);
+            if m.computed {
+                expression_names(&m.property, names);
+            }
+        }
+        Expression::Call(c) => {
+            expression_names(&c.callee, names);
+            for argument in &c.arguments {
+                if let ArgumentKind::Expression(e) = argument {
+                    expression_names(e, names);
+                }
+            }
+        }
+        Expression::Sequence(s) => {
+            for expression in &s.expressions {
+                expression_names(expression, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct NameCollector<'a> {
+    names: &'a mut HashSet<String>,
+}
+
+impl<'a> VisitorMut for NameCollector<'a> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        let mut names = Vec::new();
+        lexical_names(std::slice::from_ref(statement), &mut names);
+        self.names.extend(names);
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(i) => {
+                self.names.insert(i.name.clone());
+            }
+            Expression::Function(FunctionExpression {
+                identifier: Some(i),
+                ..
+            })
+            | Expression::Class(ClassExpression {
+                identifier: Some(i),
+                ..
+            }) => {
+                self.names.insert(i.name.clone());
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        let mut names = Vec::new();
+        binding_names(binding, &mut names);
+        self.names.extend(names);
+        walk_binding(self, binding);
+    }
+
+    fn visit_shorthand_property(&mut self, property: &mut ObjectExpressionPropertyShorthand) {
+        self.names.insert(property.key.name.clone());
+    }
+}
+
+struct Renamer<'a> {
+    renames: &'a HashMap<String, Expression>,
+    /// The names used by the replacements, nested declarations
+    /// with these names have to be renamed.
+    targets: HashSet<String>,
+    /// Names that can not be used when renaming nested declarations.
+    reserved: HashSet<String>,
+    /// The nested scopes, mapping each declared name to its new name.
+    scopes: Vec<HashMap<String, String>>,
+    unbound: BTreeSet<String>,
+}
+
+impl<'a> Renamer<'a> {
+    fn new(program: &mut AST, renames: &'a HashMap<String, Expression>) -> Renamer<'a> {
+        let mut targets = HashSet::new();
+        for (name, expression) in renames {
+            if !matches!(expression, Expression::Identifier(i) if &i.name == name) {
+                expression_names(expression, &mut targets);
+            }
+        }
+
+        let mut reserved = targets.clone();
+        if !targets.is_empty() {
+            NameCollector {
+                names: &mut reserved,
+            }
+            .visit_program(program);
+        }
+
+        Renamer {
+            renames,
+            targets,
+            reserved,
+            scopes: Vec::new(),
+            unbound: BTreeSet::new(),
+        }
+    }
+
+    fn push_scope(&mut self, names: Vec<String>) {
+        let mut scope = HashMap::new();
+        for name in names {
+            if scope.contains_key(&name) {
+                continue;
+            }
+            let renamed = if self.targets.contains(&name) {
+                self.fresh_name(&name)
+            } else {
+                name.clone()
+            };
+            scope.insert(name, renamed);
+        }
+        self.scopes.push(scope);
+    }
+
+    fn fresh_name(&mut self, name: &str) -> String {
+        let mut index = 2;
+        loop {
+            let candidate = format!("{}{}", name, index);
+            if self.reserved.insert(candidate.clone()) {
+                return candidate;
+            }
+            index += 1;
+        }
+    }
+
+    /// Returns the replacement for a reference to the name, None if it is unchanged.
+    fn resolve(&mut self, name: &str) -> Option<Expression> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(renamed) = scope.get(name) {
+                if renamed == name {
+                    return None;
+                }
+                return Some(Expression::Identifier(Identifier {
+                    name: renamed.clone(),
+                }));
+            }
+        }
+
+        self.unbound.insert(name.to_string());
+        self.renames.get(name).cloned()
+    }
+
+    /// Returns the replacement for a member chain, None if it is unchanged.
+    fn resolve_member(&self, expression: &Expression) -> Option<Expression> {
+        let chain = member_chain(expression)?;
+        let replacement = self.renames.get(&chain)?;
+        let is_shadowed = member_root(expression)
+            .is_some_and(|root| self.scopes.iter().any(|scope| scope.contains_key(root)));
+        if is_shadowed {
+            return None;
+        }
+        Some(replacement.clone())
+    }
+
+    /// Renames a declaration, declarations can only be renamed to other identifiers.
+    fn resolve_identifier(&mut self, identifier: &mut Identifier) {
+        if let Some(Expression::Identifier(renamed)) = self.resolve(&identifier.name) {
+            *identifier = renamed;
+        }
+    }
+
+    /// Visits a statement in its own block scope, this is used for
+    /// the bodies of loops and the blocks of try statements.
+    fn visit_block(&mut self, block: &mut BlockStatement, extra: Vec<String>) {
+        let mut names = extra;
+        lexical_names(&block.statements, &mut names);
+        self.push_scope(names);
+        walk_block(self, block);
+        self.scopes.pop();
+    }
+
+    fn visit_loop_head(&mut self, statement: &Statement) -> bool {
+        let mut names = Vec::new();
+        if let Statement::VariableDeclaration(v) = statement {
+            if v.kind != VariableDeclarationKind::Var {
+                for declarator in &v.declarations {
+                    binding_names(&declarator.binding, &mut names);
+                }
+            }
+        }
+        if names.is_empty() {
+            return false;
+        }
+        self.push_scope(names);
+        true
+    }
+}
+
+impl<'a> VisitorMut for Renamer<'a> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::BlockStatement(b) => self.visit_block(b, Vec::new()),
+
+            Statement::FunctionDeclaration(f) => {
+                self.resolve_identifier(&mut f.identifier);
+                walk_statement(self, statement);
+            }
+
+            Statement::ClassDeclaration(c) => {
+                self.resolve_identifier(&mut c.identifier);
+                walk_statement(self, statement);
+            }
+
+            Statement::ExportNamedDeclaration(e) => {
+                match &mut e.declaration {
+                    ExportNamedDeclarationKind::FunctionDeclaration(f) => {
+                        self.resolve_identifier(&mut f.identifier)
+                    }
+                    ExportNamedDeclarationKind::ClassDeclaration(c) => {
+                        self.resolve_identifier(&mut c.identifier)
+                    }
+                    ExportNamedDeclarationKind::VariableDeclaration(_) => {}
+                }
+                walk_statement(self, statement);
+            }
+
+            Statement::ExportDefaultDeclaration(e) => {
+                match &mut e.declaration {
+                    ExportDefaultDeclarationKind::FunctionDeclaration(f) => {
+                        self.resolve_identifier(&mut f.identifier)
+                    }
+                    ExportDefaultDeclarationKind::ClassDeclaration(c) => {
+                        self.resolve_identifier(&mut c.identifier)
+                    }
+                    _ => {}
+                }
+                walk_statement(self, statement);
+            }
+
+            Statement::ExportNamedSpecifiers(e) => {
+                if e.source.is_none() {
+                    for specifier in e.specifiers.iter_mut() {
+                        self.resolve_identifier(&mut specifier.local);
+                    }
+                }
+            }
+
+            Statement::ImportDeclaration(i) => {
+                if let Some(default) = &mut i.default {
+                    self.resolve_identifier(default);
+                }
+                if let Some(namespace) = &mut i.namespace {
+                    self.resolve_identifier(namespace);
+                }
+                for specifier in i.specifiers.iter_mut() {
+                    self.resolve_identifier(&mut specifier.imported);
+                }
+            }
+
+            Statement::ForStatement(f) => {
+                let has_scope = f.init.as_ref().is_some_and(|i| self.visit_loop_head(i));
+                walk_statement(self, statement);
+                if has_scope {
+                    self.scopes.pop();
+                }
+            }
+
+            Statement::ForInStatement(f) => {
+                let has_scope = self.visit_loop_head(&f.left);
+                walk_statement(self, statement);
+                if has_scope {
+                    self.scopes.pop();
+                }
+            }
+
+            Statement::ForOfStatement(f) => {
+                let has_scope = self.visit_loop_head(&f.left);
+                walk_statement(self, statement);
+                if has_scope {
+                    self.scopes.pop();
+                }
+            }
+
+            Statement::SwitchStatement(s) => {
+                self.visit_expression(&mut s.discriminant);
+                let mut names = Vec::new();
+                for case in &s.cases {
+                    for consequent in &case.consequent {
+                        lexical_names(std::slice::from_ref(&**consequent), &mut names);
+                    }
+                }
+                self.push_scope(names);
+                for case in s.cases.iter_mut() {
+                    if let Some(test) = &mut case.test {
+                        self.visit_expression(test);
+                    }
+                    for consequent in case.consequent.iter_mut() {
+                        self.visit_statement(consequent);
+                    }
+                }
+                self.scopes.pop();
+            }
+
+            Statement::TryStatement(t) => {
+                self.visit_block(&mut t.block, Vec::new());
+                if let Some(handler) = &mut t.handler {
+                    let mut names = Vec::new();
+                    binding_names(&handler.param, &mut names);
+                    lexical_names(&handler.body.statements, &mut names);
+                    self.push_scope(names);
+                    self.visit_binding(&mut handler.param);
+                    walk_block(self, &mut handler.body);
+                    self.scopes.pop();
+                }
+                if let Some(finalizer) = &mut t.finalizer {
+                    self.visit_block(finalizer, Vec::new());
+                }
+            }
+
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_function(&mut self, parameters: &mut Vec<ParameterKind>, body: &mut BlockStatement) {
+        self.push_scope(function_scope_names(parameters, &body.statements));
+        walk_function(self, parameters, body);
+        self.scopes.pop();
+    }
+
+    fn visit_arrow_function(&mut self, arrow: &mut ArrowFunctionExpression) {
+        let body: &[Statement] = match &arrow.body {
+            ArrowFunctionExpressionBody::BlockStatement(b) => &b.statements,
+            ArrowFunctionExpressionBody::Expression(_) => &[],
+        };
+        self.push_scope(function_scope_names(&arrow.parameters, body));
+        walk_arrow_function(self, arrow);
+        self.scopes.pop();
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(i) => {
+                if let Some(renamed) = self.resolve(&i.name) {
+                    *expression = renamed;
+                }
+            }
+
+            Expression::Call(c) => {
+                if let Expression::Identifier(i) = &*c.callee {
+                    if let Some(renamed) = self.resolve(&i.name) {
+                        // Calling a member expression would change the value of `this`.
+                        *c.callee = if let Expression::Member(_) = renamed {
+                            Expression::Sequence(SequenceExpression {
+                                expressions: vec![
+                                    Expression::NumericLiteral(NumericLiteral { value: 0. }),
+                                    renamed,
+                                ],
+                            })
+                        } else {
+                            renamed
+                        };
+                    }
+                } else {
+                    self.visit_expression(&mut c.callee);
+                }
+                walk_arguments(self, &mut c.arguments);
+            }
+
+            Expression::Member(_) | Expression::ImportMeta(_) => {
+                match self.resolve_member(expression) {
+                    Some(replacement) => *expression = replacement,
+                    None => walk_expression(self, expression),
+                }
+            }
+
+            // Only references can be assigned to, a target that would be
+            // replaced with something else is left as it is.
+            Expression::Assignment(a) => {
+                let left = a.left.clone();
+                walk_expression(self, expression);
+                if let Expression::Assignment(a) = expression {
+                    if let AssignmentExpressionLeft::Expression(e) = &a.left {
+                        if !matches!(**e, Expression::Identifier(_) | Expression::Member(_)) {
+                            a.left = left;
+                        }
+                    }
+                }
+            }
+
+            // The name of a function or class expression is only visible inside of it.
+            Expression::Function(f) => {
+                let has_scope = f.identifier.is_some();
+                if let Some(identifier) = &mut f.identifier {
+                    self.push_scope(vec![identifier.name.clone()]);
+                    self.resolve_identifier(identifier);
+                }
+                self.visit_function(&mut f.parameters, &mut f.body);
+                if has_scope {
+                    self.scopes.pop();
+                }
+            }
+
+            Expression::Class(c) => {
+                let has_scope = c.identifier.is_some();
+                if let Some(identifier) = &mut c.identifier {
+                    self.push_scope(vec![identifier.name.clone()]);
+                    self.resolve_identifier(identifier);
+                }
+                walk_class(self, c.extends.as_deref_mut(), &mut c.body);
+                if has_scope {
+                    self.scopes.pop();
+                }
+            }
+
+            Expression::Object(_) => {
+                walk_expression(self, expression);
+                // Shorthand properties are expanded if the variable is renamed.
+                if let Expression::Object(o) = expression {
+                    for property in o.properties.iter_mut() {
+                        if let ObjectExpressionPropertyKind::Shorthand(s) = property {
+                            if let Some(value) = self.resolve(&s.key.name) {
+                                *property = ObjectExpressionPropertyKind::Property(
+                                    ObjectExpressionProperty {
+                                        key: LiteralPropertyName::Identifier(s.key.clone()),
+                                        value,
+                                    },
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        match binding {
+            Binding::Identifier(i) => self.resolve_identifier(i),
+            Binding::Object(_) => {
+                walk_binding(self, binding);
+                if let Binding::Object(o) = binding {
+                    for property in o.properties.iter_mut() {
+                        match property {
+                            ObjectBindingPropertyKind::Shorthand(s) => {
+                                if let Some(Expression::Identifier(renamed)) =
+                                    self.resolve(&s.key.name)
+                                {
+                                    *property = ObjectBindingPropertyKind::Property(
+                                        ObjectBindingProperty {
+                                            key: LiteralPropertyName::Identifier(s.key.clone()),
+                                            binding: Binding::Identifier(renamed),
+                                            initializer: s.initializer.take(),
+                                        },
+                                    );
+                                }
+                            }
+                            ObjectBindingPropertyKind::Rest(r) => {
+                                self.resolve_identifier(&mut r.key)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Binding::Array(_) => walk_binding(self, binding),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+/// This file contains the side effect analysis used when removing
+/// unused code, both when minifying and when tree shaking.
+///
+/// The analysis is conservative, anything that could run user code
+/// (calls, getters, setters, `valueOf`, iterators) is considered to
+/// have side effects unless it has been explicitly marked as pure.
+/// Reading a variable is assumed not to throw.
+use crate::visit::{walk_statements, VisitorMut};
+use crate::*;
+
+/// Returns true if evaluating the expression could have side effects.
+pub fn has_side_effects(expression: &Expression) -> bool {
+    match expression {
+        Expression::BigIntLiteral(_)
+        | Expression::BooleanLiteral(_)
+        | Expression::NullLiteral(_)
+        | Expression::NumericLiteral(_)
+        | Expression::RegexpLiteral(_)
+        | Expression::StringLiteral(_)
+        | Expression::Identifier(_)
+        | Expression::This(_)
+        | Expression::ImportMeta(_)
+        | Expression::Function(_)
+        | Expression::ArrowFunction(_) => false,
+
+        Expression::Class(c) => {
+            c.extends.is_some() || c.body.iter().any(class_property_has_side_effects)
+        }
+
+        Expression::Array(a) => a.items.iter().flatten().any(|item| match item {
+            ArrayExpressionItem::Expression(e) => has_side_effects(e),
+            // Spreading calls the iterator of the value.
+            ArrayExpressionItem::Spread(_) => true,
+        }),
+
+        Expression::Object(o) => o.properties.iter().any(|property| match property {
+            ObjectExpressionPropertyKind::Property(p) => has_side_effects(&p.value),
+            ObjectExpressionPropertyKind::Shorthand(_) => false,
+            ObjectExpressionPropertyKind::Computed(p) => {
+                !is_primitive(&p.key) || has_side_effects(&p.value)
+            }
+            ObjectExpressionPropertyKind::Method(_)
+            | ObjectExpressionPropertyKind::MethodGet(_)
+            | ObjectExpressionPropertyKind::MethodSet(_) => false,
+            ObjectExpressionPropertyKind::MethodComputed(m) => !is_primitive(&m.key),
+            ObjectExpressionPropertyKind::MethodGetComputed(m) => !is_primitive(&m.key),
+            ObjectExpressionPropertyKind::MethodSetComputed(m) => !is_primitive(&m.key),
+            // Spreading an object may call getters.
+            ObjectExpressionPropertyKind::Spread(_) => true,
+        }),
+
+        Expression::TemplateLiteral(t) => t
+            .parts
+            .iter()
+            .any(|p| !is_primitive(&p.expression) || has_side_effects(&p.expression)),
+
+        Expression::Unary(u) => match u.operator {
+            UnaryExpressionOperator::Delete => true,
+            UnaryExpressionOperator::Void
+            | UnaryExpressionOperator::Typeof
+            | UnaryExpressionOperator::LogicalNot => has_side_effects(&u.argument),
+            // These convert the argument to a number which may call `valueOf`.
+            UnaryExpressionOperator::Positive
+            | UnaryExpressionOperator::Negative
+            | UnaryExpressionOperator::BinaryNot => !is_primitive(&u.argument),
+        },
+
+        Expression::Binary(b) => match b.operator {
+            BinaryExpressionOperator::StrictEquals
+            | BinaryExpressionOperator::StrictNotEquals
+            | BinaryExpressionOperator::NullishCoalescing => {
+                has_side_effects(&b.left) || has_side_effects(&b.right)
+            }
+            // `in` and `instanceof` throw for non-objects.
+            BinaryExpressionOperator::In | BinaryExpressionOperator::Instanceof => true,
+            _ => !is_primitive(&b.left) || !is_primitive(&b.right),
+        },
+
+        Expression::Logical(l) => has_side_effects(&l.left) || has_side_effects(&l.right),
+
+        Expression::Conditional(c) => {
+            has_side_effects(&c.test)
+                || has_side_effects(&c.consequence)
+                || has_side_effects(&c.alternate)
+        }
+
+        Expression::Sequence(s) => s.expressions.iter().any(has_side_effects),
+
+        Expression::Call(c) => !c.pure || arguments_have_side_effects(&c.arguments),
+
+        Expression::New(n) => !n.pure || arguments_have_side_effects(&n.arguments),
+
+        // Importing a module runs it.
+        Expression::Assignment(_)
+        | Expression::Import(_)
+        | Expression::Update(_)
+        | Expression::Await(_)
+        | Expression::Yield(_)
+        | Expression::Member(_)
+        | Expression::Super(_) => true,
+    }
+}
+
+/// Returns true if the statement could have side effects when executed.
+/// Legal comments are considered to have side effects so that they are kept.
+pub fn statement_has_side_effects(statement: &Statement) -> bool {
+    match statement {
+        Statement::EmptyStatement(_) | Statement::FunctionDeclaration(_) => false,
+        Statement::Expression(e) => has_side_effects(&e.expression),
+        Statement::VariableDeclaration(v) => variable_declaration_has_side_effects(v),
+        Statement::ClassDeclaration(c) => {
+            c.extends.is_some() || c.body.iter().any(class_property_has_side_effects)
+        }
+        Statement::BlockStatement(b) => b.statements.iter().any(statement_has_side_effects),
+        Statement::ExportNamedDeclaration(e) => match &e.declaration {
+            ExportNamedDeclarationKind::FunctionDeclaration(_) => false,
+            ExportNamedDeclarationKind::VariableDeclaration(v) => {
+                variable_declaration_has_side_effects(v)
+            }
+            ExportNamedDeclarationKind::ClassDeclaration(c) => {
+                c.extends.is_some() || c.body.iter().any(class_property_has_side_effects)
+            }
+        },
+        Statement::ExportNamedSpecifiers(e) => e.source.is_some(),
+        _ => true,
+    }
+}
+
+/// Returns the parts of an expression whose value is unused that have
+/// to be kept for their side effects, or None if nothing has to be kept.
+///
+/// For example the unused pure call `/* @__PURE__ */ a(b(), 1)` is
+/// simplified to `b()`.
+pub fn simplify_unused_expression(expression: Expression) -> Option<Expression> {
+    if !has_side_effects(&expression) {
+        return None;
+    }
+
+    match expression {
+        Expression::Call(CallExpression {
+            arguments,
+            pure: true,
+            ..
+        })
+        | Expression::New(NewExpression {
+            arguments,
+            pure: true,
+            ..
+        }) => {
+            let mut expressions = Vec::new();
+            for argument in arguments {
+                match argument {
+                    ArgumentKind::Expression(e) => {
+                        if let Some(e) = simplify_unused_expression(e) {
+                            expressions.push(e);
+                        }
+                    }
+                    // A spread is only a side effect because of the
+                    // iterator, keep it as an array spread.
+                    ArgumentKind::Spread(s) => {
+                        expressions.push(Expression::Array(ArrayExpression {
+                            items: vec![Some(ArrayExpressionItem::Spread(s))],
+                        }))
+                    }
+                }
+            }
+            join_with_comma(expressions)
+        }
+
+        Expression::Sequence(s) => {
+            let expressions = s
+                .expressions
+                .into_iter()
+                .filter_map(simplify_unused_expression)
+                .collect();
+            join_with_comma(expressions)
+        }
+
+        expression => Some(expression),
+    }
+}
+
+/// Removes expression statements whose values are unused and that have no side effects,
+/// including unused pure calls, from every list of statements in the program. The strings
+/// at the start of a list are kept since they may be directives such as "use strict".
+pub fn remove_unused_expressions(program: &mut AST) {
+    RemoveUnusedExpressions {}.visit_program(program);
+}
+
+struct RemoveUnusedExpressions {}
+
+impl VisitorMut for RemoveUnusedExpressions {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        walk_statements(self, statements);
+        let old = std::mem::take(statements);
+        for statement in old {
+            let is_directive = statements.iter().all(is_string_statement);
+            match statement {
+                Statement::Expression(e)
+                    if !(is_directive && matches!(e.expression, Expression::StringLiteral(_))) =>
+                {
+                    if let Some(expression) = simplify_unused_expression(e.expression) {
+                        statements.push(Statement::Expression(ExpressionStatement { expression }));
+                    }
+                }
+                statement => statements.push(statement),
+            }
+        }
+    }
+}
+
+fn is_string_statement(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Expression(ExpressionStatement {
+            expression: Expression::StringLiteral(_)
+        })
+    )
+}
+
+fn join_with_comma(mut expressions: Vec<Expression>) -> Option<Expression> {
+    match expressions.len() {
+        0 => None,
+        1 => expressions.pop(),
+        _ => Some(Expression::Sequence(SequenceExpression { expressions })),
+    }
+}
+
+fn arguments_have_side_effects(arguments: &[ArgumentKind]) -> bool {
+    arguments.iter().any(|argument| match argument {
+        ArgumentKind::Expression(e) => has_side_effects(e),
+        ArgumentKind::Spread(_) => true,
+    })
+}
+
+fn variable_declaration_has_side_effects(declaration: &VariableDeclaration) -> bool {
+    declaration.declarations.iter().any(|d| {
+        // Destructuring may call getters and iterators.
+        !matches!(d.binding, Binding::Identifier(_))
+            || d.initializer.as_ref().is_some_and(has_side_effects)
+    })
+}
+
+fn class_property_has_side_effects(property: &ClassPropertyKind) -> bool {
+    match property {
+        ClassPropertyKind::MethodComputed(m) => !is_primitive(&m.key),
+        ClassPropertyKind::MethodGetComputed(m) => !is_primitive(&m.key),
+        ClassPropertyKind::MethodSetComputed(m) => !is_primitive(&m.key),
+        _ => false,
+    }
+}
+
+/// Primitive literals can be converted to strings and numbers
+/// without running any user code.
+fn is_primitive(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::BigIntLiteral(_)
+            | Expression::BooleanLiteral(_)
+            | Expression::NullLiteral(_)
+            | Expression::NumericLiteral(_)
+            | Expression::StringLiteral(_)
+    )
+}
@@ -0,0 +1,427 @@
+/// This file contains a mutable visitor for the AST.
+///
+/// Passes that need to look at or rewrite parts of the AST implement
+/// `VisitorMut` and override the methods for the nodes they care about.
+/// The default implementations call the matching `walk_*` function which
+/// visits all of the children of the node, an overridden method can call
+/// the `walk_*` function itself to continue into the children.
+use crate::*;
+
+pub trait VisitorMut: Sized {
+    fn visit_program(&mut self, program: &mut AST) {
+        self.visit_statements(&mut program.statements);
+    }
+
+    /// Called for every list of statements, such as the top level
+    /// of the program, block bodies and function bodies.
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        walk_statements(self, statements);
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        walk_binding(self, binding);
+    }
+
+    /// Called for every function-like node with a block body: function
+    /// declarations and expressions, class and object methods.
+    fn visit_function(&mut self, parameters: &mut Vec<ParameterKind>, body: &mut BlockStatement) {
+        walk_function(self, parameters, body);
+    }
+
+    fn visit_arrow_function(&mut self, arrow: &mut ArrowFunctionExpression) {
+        walk_arrow_function(self, arrow);
+    }
+
+    /// Shorthand properties `({ a })` reference a variable without
+    /// going through an identifier expression.
+    fn visit_shorthand_property(&mut self, _property: &mut ObjectExpressionPropertyShorthand) {}
+}
+
+pub fn walk_statements<V: VisitorMut>(visitor: &mut V, statements: &mut [Statement]) {
+    for statement in statements.iter_mut() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_block<V: VisitorMut>(visitor: &mut V, block: &mut BlockStatement) {
+    visitor.visit_statements(&mut block.statements);
+}
+
+pub fn walk_function<V: VisitorMut>(
+    visitor: &mut V,
+    parameters: &mut [ParameterKind],
+    body: &mut BlockStatement,
+) {
+    walk_parameters(visitor, parameters);
+    walk_block(visitor, body);
+}
+
+pub fn walk_arrow_function<V: VisitorMut>(visitor: &mut V, arrow: &mut ArrowFunctionExpression) {
+    walk_parameters(visitor, &mut arrow.parameters);
+    match &mut arrow.body {
+        ArrowFunctionExpressionBody::BlockStatement(b) => walk_block(visitor, b),
+        ArrowFunctionExpressionBody::Expression(e) => visitor.visit_expression(e),
+    }
+}
+
+pub fn walk_parameters<V: VisitorMut>(visitor: &mut V, parameters: &mut [ParameterKind]) {
+    for parameter in parameters.iter_mut() {
+        match parameter {
+            ParameterKind::Parameter(p) => {
+                visitor.visit_binding(&mut p.binding);
+                if let Some(initializer) = &mut p.initializer {
+                    visitor.visit_expression(initializer);
+                }
+            }
+            ParameterKind::Rest(r) => visitor.visit_binding(&mut r.binding),
+        }
+    }
+}
+
+pub fn walk_binding<V: VisitorMut>(visitor: &mut V, binding: &mut Binding) {
+    match binding {
+        Binding::Identifier(_) => {}
+        Binding::Object(o) => {
+            for property in o.properties.iter_mut() {
+                match property {
+                    ObjectBindingPropertyKind::Property(p) => {
+                        visitor.visit_binding(&mut p.binding);
+                        if let Some(initializer) = &mut p.initializer {
+                            visitor.visit_expression(initializer);
+                        }
+                    }
+                    ObjectBindingPropertyKind::Computed(p) => {
+                        visitor.visit_expression(&mut p.key);
+                        visitor.visit_binding(&mut p.binding);
+                        if let Some(initializer) = &mut p.initializer {
+                            visitor.visit_expression(initializer);
+                        }
+                    }
+                    ObjectBindingPropertyKind::Rest(_) => {}
+                    ObjectBindingPropertyKind::Shorthand(p) => {
+                        if let Some(initializer) = &mut p.initializer {
+                            visitor.visit_expression(initializer);
+                        }
+                    }
+                }
+            }
+        }
+        Binding::Array(a) => {
+            for item in a.items.iter_mut().flatten() {
+                match item {
+                    ArrayBindingItemKind::Item(i) => {
+                        visitor.visit_binding(&mut i.binding);
+                        if let Some(initializer) = &mut i.initializer {
+                            visitor.visit_expression(initializer);
+                        }
+                    }
+                    ArrayBindingItemKind::Rest(r) => visitor.visit_binding(&mut r.binding),
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_variable_declaration<V: VisitorMut>(
+    visitor: &mut V,
+    declaration: &mut VariableDeclaration,
+) {
+    for declarator in declaration.declarations.iter_mut() {
+        visitor.visit_binding(&mut declarator.binding);
+        if let Some(initializer) = &mut declarator.initializer {
+            visitor.visit_expression(initializer);
+        }
+    }
+}
+
+pub fn walk_class<V: VisitorMut>(
+    visitor: &mut V,
+    extends: Option<&mut Expression>,
+    body: &mut [ClassPropertyKind],
+) {
+    if let Some(extends) = extends {
+        visitor.visit_expression(extends);
+    }
+
+    for property in body.iter_mut() {
+        match property {
+            ClassPropertyKind::Constructor(c) => {
+                visitor.visit_function(&mut c.parameters, &mut c.body)
+            }
+            ClassPropertyKind::Method(m) => visitor.visit_function(&mut m.parameters, &mut m.body),
+            ClassPropertyKind::MethodGet(m) => {
+                visitor.visit_function(&mut m.parameters, &mut m.body)
+            }
+            ClassPropertyKind::MethodSet(m) => {
+                visitor.visit_function(&mut m.parameters, &mut m.body)
+            }
+            ClassPropertyKind::MethodComputed(m) => {
+                visitor.visit_expression(&mut m.key);
+                visitor.visit_function(&mut m.parameters, &mut m.body);
+            }
+            ClassPropertyKind::MethodGetComputed(m) => {
+                visitor.visit_expression(&mut m.key);
+                visitor.visit_function(&mut m.parameters, &mut m.body);
+            }
+            ClassPropertyKind::MethodSetComputed(m) => {
+                visitor.visit_expression(&mut m.key);
+                visitor.visit_function(&mut m.parameters, &mut m.body);
+            }
+        }
+    }
+}
+
+pub fn walk_statement<V: VisitorMut>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::BlockStatement(b) => walk_block(visitor, b),
+        Statement::BreakStatement(_)
+        | Statement::Comment(_)
+        | Statement::ContinueStatement(_)
+        | Statement::DebuggerStatement(_)
+        | Statement::EmptyStatement(_)
+        | Statement::ExportAllDeclaration(_)
+        | Statement::ExportNamedSpecifiers(_)
+        | Statement::ImportDeclaration(_) => {}
+        Statement::ClassDeclaration(c) => {
+            walk_class(visitor, c.extends.as_mut(), &mut c.body);
+        }
+        Statement::DoWhileStatement(d) => {
+            visitor.visit_statement(&mut d.body);
+            visitor.visit_expression(&mut d.test);
+        }
+        Statement::ExportDefaultDeclaration(e) => match &mut e.declaration {
+            ExportDefaultDeclarationKind::AnonymousDefaultExportedFunctionDeclaration(f) => {
+                visitor.visit_function(&mut f.parameters, &mut f.body)
+            }
+            ExportDefaultDeclarationKind::AnonymousDefaultExportedClassDeclaration(c) => {
+                walk_class(visitor, c.extends.as_mut(), &mut c.body)
+            }
+            ExportDefaultDeclarationKind::FunctionDeclaration(f) => {
+                visitor.visit_function(&mut f.parameters, &mut f.body)
+            }
+            ExportDefaultDeclarationKind::Expression(e) => visitor.visit_expression(e),
+            ExportDefaultDeclarationKind::ClassDeclaration(c) => {
+                walk_class(visitor, c.extends.as_mut(), &mut c.body)
+            }
+        },
+        Statement::ExportNamedDeclaration(e) => match &mut e.declaration {
+            ExportNamedDeclarationKind::FunctionDeclaration(f) => {
+                visitor.visit_function(&mut f.parameters, &mut f.body)
+            }
+            ExportNamedDeclarationKind::VariableDeclaration(v) => {
+                walk_variable_declaration(visitor, v)
+            }
+            ExportNamedDeclarationKind::ClassDeclaration(c) => {
+                walk_class(visitor, c.extends.as_mut(), &mut c.body)
+            }
+        },
+        Statement::Expression(e) => visitor.visit_expression(&mut e.expression),
+        Statement::ForInStatement(f) => {
+            visitor.visit_statement(&mut f.left);
+            visitor.visit_expression(&mut f.right);
+            visitor.visit_statement(&mut f.body);
+        }
+        Statement::ForOfStatement(f) => {
+            visitor.visit_statement(&mut f.left);
+            visitor.visit_expression(&mut f.right);
+            visitor.visit_statement(&mut f.body);
+        }
+        Statement::ForStatement(f) => {
+            if let Some(init) = &mut f.init {
+                visitor.visit_statement(init);
+            }
+            if let Some(test) = &mut f.test {
+                visitor.visit_expression(test);
+            }
+            if let Some(update) = &mut f.update {
+                visitor.visit_expression(update);
+            }
+            visitor.visit_statement(&mut f.body);
+        }
+        Statement::FunctionDeclaration(f) => visitor.visit_function(&mut f.parameters, &mut f.body),
+        Statement::IfStatement(i) => {
+            visitor.visit_expression(&mut i.test);
+            visitor.visit_statement(&mut i.consequent);
+            if let Some(alternate) = &mut i.alternate {
+                visitor.visit_statement(alternate);
+            }
+        }
+        Statement::LabeledStatement(l) => visitor.visit_statement(&mut l.body),
+        Statement::ReturnStatement(r) => {
+            if let Some(expression) = &mut r.expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        Statement::SwitchStatement(s) => {
+            visitor.visit_expression(&mut s.discriminant);
+            for case in s.cases.iter_mut() {
+                if let Some(test) = &mut case.test {
+                    visitor.visit_expression(test);
+                }
+                for consequent in case.consequent.iter_mut() {
+                    visitor.visit_statement(consequent);
+                }
+            }
+        }
+        Statement::ThrowStatement(t) => visitor.visit_expression(&mut t.argument),
+        Statement::TryStatement(t) => {
+            walk_block(visitor, &mut t.block);
+            if let Some(handler) = &mut t.handler {
+                visitor.visit_binding(&mut handler.param);
+                walk_block(visitor, &mut handler.body);
+            }
+            if let Some(finalizer) = &mut t.finalizer {
+                walk_block(visitor, finalizer);
+            }
+        }
+        Statement::VariableDeclaration(v) => walk_variable_declaration(visitor, v),
+        Statement::WhileStatement(w) => {
+            visitor.visit_expression(&mut w.test);
+            visitor.visit_statement(&mut w.body);
+        }
+        Statement::WithStatement(w) => {
+            visitor.visit_expression(&mut w.object);
+            visitor.visit_statement(&mut w.body);
+        }
+    }
+}
+
+pub fn walk_arguments<V: VisitorMut>(visitor: &mut V, arguments: &mut [ArgumentKind]) {
+    for argument in arguments.iter_mut() {
+        match argument {
+            ArgumentKind::Expression(e) => visitor.visit_expression(e),
+            ArgumentKind::Spread(s) => visitor.visit_expression(&mut s.element),
+        }
+    }
+}
+
+pub fn walk_expression<V: VisitorMut>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Assignment(a) => {
+            match &mut a.left {
+                AssignmentExpressionLeft::Binding(b) => visitor.visit_binding(b),
+                AssignmentExpressionLeft::Expression(e) => visitor.visit_expression(e),
+            }
+            visitor.visit_expression(&mut a.right);
+        }
+        Expression::ArrowFunction(a) => visitor.visit_arrow_function(a),
+        Expression::Array(a) => {
+            for item in a.items.iter_mut().flatten() {
+                match item {
+                    ArrayExpressionItem::Spread(s) => visitor.visit_expression(&mut s.element),
+                    ArrayExpressionItem::Expression(e) => visitor.visit_expression(e),
+                }
+            }
+        }
+        Expression::BigIntLiteral(_)
+        | Expression::BooleanLiteral(_)
+        | Expression::Identifier(_)
+        | Expression::NullLiteral(_)
+        | Expression::NumericLiteral(_)
+        | Expression::RegexpLiteral(_)
+        | Expression::StringLiteral(_)
+        | Expression::Super(_)
+        | Expression::This(_)
+        | Expression::ImportMeta(_) => {}
+        Expression::Binary(b) => {
+            visitor.visit_expression(&mut b.left);
+            visitor.visit_expression(&mut b.right);
+        }
+        Expression::Call(c) => {
+            visitor.visit_expression(&mut c.callee);
+            walk_arguments(visitor, &mut c.arguments);
+        }
+        Expression::Import(i) => visitor.visit_expression(&mut i.source),
+        Expression::Class(c) => {
+            walk_class(visitor, c.extends.as_deref_mut(), &mut c.body);
+        }
+        Expression::Conditional(c) => {
+            visitor.visit_expression(&mut c.test);
+            visitor.visit_expression(&mut c.consequence);
+            visitor.visit_expression(&mut c.alternate);
+        }
+        Expression::Function(f) => visitor.visit_function(&mut f.parameters, &mut f.body),
+        Expression::Logical(l) => {
+            visitor.visit_expression(&mut l.left);
+            visitor.visit_expression(&mut l.right);
+        }
+        Expression::Member(m) => {
+            visitor.visit_expression(&mut m.object);
+            // The property of a non-computed member expression is a
+            // name and not a reference to a variable.
+            if m.computed {
+                visitor.visit_expression(&mut m.property);
+            }
+        }
+        Expression::New(n) => {
+            visitor.visit_expression(&mut n.callee);
+            walk_arguments(visitor, &mut n.arguments);
+        }
+        Expression::Object(o) => {
+            for property in o.properties.iter_mut() {
+                match property {
+                    ObjectExpressionPropertyKind::Spread(s) => {
+                        visitor.visit_expression(&mut s.element)
+                    }
+                    ObjectExpressionPropertyKind::Property(p) => {
+                        visitor.visit_expression(&mut p.value)
+                    }
+                    ObjectExpressionPropertyKind::Shorthand(p) => {
+                        visitor.visit_shorthand_property(p)
+                    }
+                    ObjectExpressionPropertyKind::Computed(p) => {
+                        visitor.visit_expression(&mut p.key);
+                        visitor.visit_expression(&mut p.value);
+                    }
+                    ObjectExpressionPropertyKind::Method(m) => {
+                        visitor.visit_function(&mut m.parameters, &mut m.body)
+                    }
+                    ObjectExpressionPropertyKind::MethodGet(m) => {
+                        visitor.visit_function(&mut m.parameters, &mut m.body)
+                    }
+                    ObjectExpressionPropertyKind::MethodSet(m) => {
+                        visitor.visit_function(&mut m.parameters, &mut m.body)
+                    }
+                    ObjectExpressionPropertyKind::MethodComputed(m) => {
+                        visitor.visit_expression(&mut m.key);
+                        visitor.visit_function(&mut m.parameters, &mut m.body);
+                    }
+                    ObjectExpressionPropertyKind::MethodGetComputed(m) => {
+                        visitor.visit_expression(&mut m.key);
+                        visitor.visit_function(&mut m.parameters, &mut m.body);
+                    }
+                    ObjectExpressionPropertyKind::MethodSetComputed(m) => {
+                        visitor.visit_expression(&mut m.key);
+                        visitor.visit_function(&mut m.parameters, &mut m.body);
+                    }
+                }
+            }
+        }
+        Expression::Sequence(s) => {
+            for expression in s.expressions.iter_mut() {
+                visitor.visit_expression(expression);
+            }
+        }
+        Expression::TemplateLiteral(t) => {
+            for part in t.parts.iter_mut() {
+                visitor.visit_expression(&mut part.expression);
+            }
+        }
+        Expression::Unary(u) => visitor.visit_expression(&mut u.argument),
+        Expression::Update(u) => visitor.visit_expression(&mut u.argument),
+        Expression::Await(a) => visitor.visit_expression(&mut a.argument),
+        Expression::Yield(y) => {
+            if let Some(argument) = &mut y.argument {
+                visitor.visit_expression(argument);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use span::Span;
 
-use crate::Expression;
+use crate::{identifier_reference::IdentifierReference, Expression};
 
 #[derive(Debug, Clone)]
 pub struct AssignmentExpression {
@@ -10,8 +10,11 @@ pub struct AssignmentExpression {
     pub argument: Box<Expression>,
 }
 
+/// The left hand side of an assignment, a simple target such as `a` in `a = 1`
 #[derive(Debug, Clone)]
-pub enum AssignmentExpressionTarget {}
+pub enum AssignmentExpressionTarget {
+    IdentifierReference(IdentifierReference),
+}
 
 #[derive(Debug, Clone)]
 pub enum AssignmentExpressionOperator {
@@ -32,3 +35,26 @@ pub enum AssignmentExpressionOperator {
     LogicalOrAssign,
     LogicalAndAssign,
 }
+
+impl AssignmentExpressionOperator {
+    pub fn to_str<'a>(&self) -> &'a str {
+        match self {
+            AssignmentExpressionOperator::Assign => "=",
+            AssignmentExpressionOperator::AdditionAssign => "+=",
+            AssignmentExpressionOperator::SubstitutionAssign => "-=",
+            AssignmentExpressionOperator::MultiplicationAssign => "*=",
+            AssignmentExpressionOperator::DivisionAssign => "/=",
+            AssignmentExpressionOperator::ModulusAssign => "%=",
+            AssignmentExpressionOperator::ExponentiationAssign => "**=",
+            AssignmentExpressionOperator::LeftShiftAssign => "<<=",
+            AssignmentExpressionOperator::RightShiftAssign => ">>=",
+            AssignmentExpressionOperator::UnsignedRightShiftAssign => ">>>=",
+            AssignmentExpressionOperator::BitwiseOrAssign => "|=",
+            AssignmentExpressionOperator::BitwiseAndAssign => "&=",
+            AssignmentExpressionOperator::BitwiseXorAssign => "^=",
+            AssignmentExpressionOperator::NullishCoalescingAssign => "??=",
+            AssignmentExpressionOperator::LogicalOrAssign => "||=",
+            AssignmentExpressionOperator::LogicalAndAssign => "&&=",
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use span::Span;
+
+/// The value is stored as the digits of the literal, including the radix
+/// prefix, to avoid precision loss. It does not include the numeric
+/// separators or the n suffix.
+#[derive(Debug, Clone)]
+pub struct BigIntLiteral {
+    pub span: Span,
+    pub value: String,
+}
@@ -58,6 +58,10 @@ pub enum BinaryExpressionOperator {
     BitwiseAnd,
     /// ^
     BitwiseXor,
+    /// &&
+    LogicalAnd,
+    /// ||
+    LogicalOr,
 }
 
 impl BinaryExpressionOperator {
@@ -86,6 +90,8 @@ impl BinaryExpressionOperator {
             BinaryExpressionOperator::BitwiseOr => Precedence::BitwiseOr,
             BinaryExpressionOperator::BitwiseAnd => Precedence::BitwiseAnd,
             BinaryExpressionOperator::BitwiseXor => Precedence::BitwiseXor,
+            BinaryExpressionOperator::LogicalAnd => Precedence::LogicalAnd,
+            BinaryExpressionOperator::LogicalOr => Precedence::LogicalOr,
         }
     }
 
@@ -114,13 +120,15 @@ impl BinaryExpressionOperator {
             BinaryExpressionOperator::BitwiseOr => "|",
             BinaryExpressionOperator::BitwiseAnd => "&",
             BinaryExpressionOperator::BitwiseXor => "^",
+            BinaryExpressionOperator::LogicalAnd => "&&",
+            BinaryExpressionOperator::LogicalOr => "||",
         }
     }
 
     pub fn is_keyword(&self) -> bool {
-        match self {
-            BinaryExpressionOperator::In | BinaryExpressionOperator::Instanceof => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            BinaryExpressionOperator::In | BinaryExpressionOperator::Instanceof
+        )
     }
 }
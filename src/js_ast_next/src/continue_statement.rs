@@ -0,0 +1,9 @@
+use span::Span;
+
+use crate::label_identifier::LabelIdentifier;
+
+#[derive(Debug, Clone)]
+pub struct ContinueStatement {
+    pub span: Span,
+    pub id: Option<LabelIdentifier>,
+}
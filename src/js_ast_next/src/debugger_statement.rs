@@ -0,0 +1,6 @@
+use span::Span;
+
+#[derive(Debug, Clone)]
+pub struct DebuggerStatement {
+    pub span: Span,
+}
@@ -0,0 +1,10 @@
+use span::Span;
+
+use crate::{Expression, Statement};
+
+#[derive(Debug, Clone)]
+pub struct DoWhileStatement {
+    pub span: Span,
+    pub body: Box<Statement>,
+    pub test: Expression,
+}
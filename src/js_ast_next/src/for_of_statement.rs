@@ -0,0 +1,11 @@
+use span::Span;
+
+use crate::{for_statement::ForStatementInit, Expression, Statement};
+
+#[derive(Debug, Clone)]
+pub struct ForOfStatement {
+    pub span: Span,
+    pub left: ForStatementInit,
+    pub right: Expression,
+    pub body: Box<Statement>,
+}
@@ -0,0 +1,24 @@
+use span::Span;
+
+use crate::{
+    lexical_declaration::LexicalDeclaration, variable_statement::VariableStatement, Expression,
+    Statement,
+};
+
+/// The part before the first `;` of a for statement, or before the `in`
+/// or `of` of a for in or a for of statement.
+#[derive(Debug, Clone)]
+pub enum ForStatementInit {
+    VariableStatement(VariableStatement),
+    LexicalDeclaration(LexicalDeclaration),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone)]
+pub struct ForStatement {
+    pub span: Span,
+    pub init: Option<ForStatementInit>,
+    pub test: Option<Expression>,
+    pub update: Option<Expression>,
+    pub body: Box<Statement>,
+}
@@ -0,0 +1,21 @@
+use span::Span;
+
+use crate::{binding_identifier::BindingIdentifier, Expression, Statement, TargetBindingPattern};
+
+#[derive(Debug, Clone)]
+pub struct FormalParameter {
+    pub span: Span,
+    pub binding: TargetBindingPattern,
+    pub initializer: Option<Expression>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionDeclaration {
+    pub span: Span,
+    pub id: BindingIdentifier,
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub parameters: Vec<FormalParameter>,
+    pub rest: Option<TargetBindingPattern>,
+    pub body: Vec<Statement>,
+}
@@ -0,0 +1,11 @@
+use span::Span;
+
+use crate::{Expression, Statement};
+
+#[derive(Debug, Clone)]
+pub struct IfStatement {
+    pub span: Span,
+    pub test: Expression,
+    pub consequent: Box<Statement>,
+    pub alternate: Option<Box<Statement>>,
+}
@@ -0,0 +1,10 @@
+use span::Span;
+
+use crate::{label_identifier::LabelIdentifier, Statement};
+
+#[derive(Debug, Clone)]
+pub struct LabelledStatement {
+    pub span: Span,
+    pub label: LabelIdentifier,
+    pub body: Box<Statement>,
+}
@@ -2,17 +2,27 @@ pub mod array_binding_pattern;
 pub mod array_expression;
 pub mod array_hole;
 pub mod assignment_expression;
+pub mod big_int_literal;
 pub mod binary_expression;
 pub mod binding_identifier;
 pub mod block_statement;
 pub mod boolean_literal;
 pub mod break_statement;
 pub mod computed_property_name;
+pub mod continue_statement;
+pub mod debugger_statement;
+pub mod do_while_statement;
 pub mod empty_statement;
 pub mod expression_statement;
+pub mod for_in_statement;
+pub mod for_of_statement;
+pub mod for_statement;
+pub mod function_declaration;
 pub mod identifier_name;
 pub mod identifier_reference;
+pub mod if_statement;
 pub mod label_identifier;
+pub mod labelled_statement;
 pub mod lexical_binding;
 pub mod lexical_declaration;
 pub mod null_literal;
@@ -20,36 +30,61 @@ pub mod numeric_literal;
 pub mod object_binding_pattern;
 pub mod precedence;
 pub mod regexp_literal;
+pub mod return_statement;
 pub mod sequence_expression;
 pub mod spread_element;
 pub mod string_literal;
+pub mod switch_statement;
+pub mod throw_statement;
+pub mod try_statement;
 pub mod unary_expression;
 pub mod update_expression;
 pub mod variable_declaration;
 pub mod variable_statement;
+pub mod while_statement;
+pub mod with_statement;
+pub mod yield_expression;
 
 use array_binding_pattern::ArrayBindingPattern;
 use array_expression::ArrayExpression;
 use assignment_expression::AssignmentExpression;
+use big_int_literal::BigIntLiteral;
 use binary_expression::BinaryExpression;
 use binding_identifier::BindingIdentifier;
 use block_statement::BlockStatement;
 use boolean_literal::BooleanLiteral;
 use break_statement::BreakStatement;
 use computed_property_name::ComputedPropertyName;
+use continue_statement::ContinueStatement;
+use debugger_statement::DebuggerStatement;
+use do_while_statement::DoWhileStatement;
 use empty_statement::EmptyStatement;
 use expression_statement::ExpressionStatement;
+use for_in_statement::ForInStatement;
+use for_of_statement::ForOfStatement;
+use for_statement::ForStatement;
+use function_declaration::FunctionDeclaration;
 use identifier_name::IdentifierName;
+use identifier_reference::IdentifierReference;
+use if_statement::IfStatement;
+use labelled_statement::LabelledStatement;
 use lexical_declaration::LexicalDeclaration;
 use null_literal::NullLiteral;
 use numeric_literal::NumericLiteral;
 use object_binding_pattern::ObjectBindingPattern;
 use regexp_literal::RegexpLiteral;
+use return_statement::ReturnStatement;
 use span::Span;
 use string_literal::StringLiteral;
+use switch_statement::SwitchStatement;
+use throw_statement::ThrowStatement;
+use try_statement::TryStatement;
 use unary_expression::UnaryExpression;
 use update_expression::UpdateExpression;
 use variable_statement::VariableStatement;
+use while_statement::WhileStatement;
+use with_statement::WithStatement;
+use yield_expression::YieldExpression;
 
 /// The top level ast node
 #[derive(Debug, Clone)]
@@ -62,14 +97,17 @@ pub struct AST {
 pub enum Expression {
     ArrayExpression(ArrayExpression),
     AssignmentExpression(AssignmentExpression),
+    BigIntLiteral(BigIntLiteral),
     BinaryExpression(BinaryExpression),
     BooleanLiteral(BooleanLiteral),
+    IdentifierReference(IdentifierReference),
     NullLiteral(NullLiteral),
     NumericLiteral(NumericLiteral),
     RegexpLiteral(RegexpLiteral),
     StringLiteral(StringLiteral),
     UnaryExpression(UnaryExpression),
     UpdateExpression(UpdateExpression),
+    YieldExpression(YieldExpression),
 }
 
 #[derive(Debug, Clone)]
@@ -90,10 +128,25 @@ pub enum ObjectPropertyKey {
 pub enum Statement {
     BreakStatement(BreakStatement),
     BlockStatement(BlockStatement),
+    ContinueStatement(ContinueStatement),
+    DebuggerStatement(DebuggerStatement),
+    DoWhileStatement(DoWhileStatement),
     EmptyStatement(EmptyStatement),
     ExpressionStatement(ExpressionStatement),
+    ForInStatement(ForInStatement),
+    ForOfStatement(ForOfStatement),
+    ForStatement(ForStatement),
+    FunctionDeclaration(FunctionDeclaration),
+    IfStatement(IfStatement),
+    LabelledStatement(LabelledStatement),
+    ReturnStatement(ReturnStatement),
+    SwitchStatement(SwitchStatement),
+    ThrowStatement(ThrowStatement),
+    TryStatement(TryStatement),
     VariableStatement(VariableStatement),
     LexicalDeclaration(LexicalDeclaration),
+    WhileStatement(WhileStatement),
+    WithStatement(WithStatement),
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,9 @@
+use span::Span;
+
+use crate::Expression;
+
+#[derive(Debug, Clone)]
+pub struct ReturnStatement {
+    pub span: Span,
+    pub argument: Option<Expression>,
+}
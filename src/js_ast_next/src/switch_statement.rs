@@ -0,0 +1,18 @@
+use span::Span;
+
+use crate::{Expression, Statement};
+
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    pub span: Span,
+    /// The test is missing for the default case
+    pub test: Option<Expression>,
+    pub consequent: Vec<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchStatement {
+    pub span: Span,
+    pub discriminant: Expression,
+    pub cases: Vec<SwitchCase>,
+}
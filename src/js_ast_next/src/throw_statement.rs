@@ -0,0 +1,9 @@
+use span::Span;
+
+use crate::Expression;
+
+#[derive(Debug, Clone)]
+pub struct ThrowStatement {
+    pub span: Span,
+    pub argument: Expression,
+}
@@ -0,0 +1,20 @@
+use span::Span;
+
+use crate::{block_statement::BlockStatement, TargetBindingPattern};
+
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    pub span: Span,
+    /// The binding is optional, as in `try {} catch {}`
+    pub param: Option<TargetBindingPattern>,
+    pub body: BlockStatement,
+}
+
+/// A try statement has a handler, a finalizer or both
+#[derive(Debug, Clone)]
+pub struct TryStatement {
+    pub span: Span,
+    pub block: BlockStatement,
+    pub handler: Option<CatchClause>,
+    pub finalizer: Option<BlockStatement>,
+}
@@ -16,3 +16,12 @@ pub enum UpdateExpressionOperator {
     Increment,
     Decrement,
 }
+
+impl UpdateExpressionOperator {
+    pub fn to_str<'a>(&self) -> &'a str {
+        match self {
+            UpdateExpressionOperator::Increment => "++",
+            UpdateExpressionOperator::Decrement => "--",
+        }
+    }
+}
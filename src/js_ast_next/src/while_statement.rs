@@ -0,0 +1,10 @@
+use span::Span;
+
+use crate::{Expression, Statement};
+
+#[derive(Debug, Clone)]
+pub struct WhileStatement {
+    pub span: Span,
+    pub test: Expression,
+    pub body: Box<Statement>,
+}
@@ -0,0 +1,10 @@
+use span::Span;
+
+use crate::{Expression, Statement};
+
+#[derive(Debug, Clone)]
+pub struct WithStatement {
+    pub span: Span,
+    pub object: Expression,
+    pub body: Box<Statement>,
+}
@@ -0,0 +1,12 @@
+use span::Span;
+
+use crate::Expression;
+
+/// yield a or yield* a
+#[derive(Debug, Clone)]
+pub struct YieldExpression {
+    pub span: Span,
+    pub argument: Option<Box<Expression>>,
+    /// Is this a yield* delegating to another iterator
+    pub delegate: bool,
+}
@@ -1,11 +1,22 @@
 use js_ast_next::{
     array_binding_pattern::{ArrayBindingElementKind, ArrayBindingPattern},
     array_expression::{ArrayExpression, ArrayExpressionElement},
-    binary_expression::BinaryExpression,
+    assignment_expression::{AssignmentExpression, AssignmentExpressionTarget},
+    big_int_literal::BigIntLiteral,
+    binary_expression::{BinaryExpression, BinaryExpressionOperator},
     binding_identifier::BindingIdentifier,
     boolean_literal::BooleanLiteral,
+    break_statement::BreakStatement,
     computed_property_name::ComputedPropertyName,
+    continue_statement::ContinueStatement,
+    do_while_statement::DoWhileStatement,
     expression_statement::ExpressionStatement,
+    for_in_statement::ForInStatement,
+    for_of_statement::ForOfStatement,
+    for_statement::{ForStatement, ForStatementInit},
+    function_declaration::FunctionDeclaration,
+    if_statement::IfStatement,
+    labelled_statement::LabelledStatement,
     lexical_declaration::LexicalDeclaration,
     numeric_literal::NumericLiteral,
     object_binding_pattern::{
@@ -13,14 +24,31 @@ use js_ast_next::{
     },
     precedence::Precedence,
     regexp_literal::RegexpLiteral,
+    return_statement::ReturnStatement,
     string_literal::StringLiteral,
+    switch_statement::SwitchStatement,
+    throw_statement::ThrowStatement,
+    try_statement::TryStatement,
+    update_expression::UpdateExpression,
     variable_statement::VariableStatement,
+    while_statement::WhileStatement,
+    with_statement::WithStatement,
+    yield_expression::YieldExpression,
     Expression, LiteralPropertyName, ObjectPropertyKey, Statement, TargetBindingPattern, AST,
 };
 
 pub struct Codegen {
     source: String,
     statement_start: usize,
+    indent: usize,
+    /// Is an `in` expression printed in a for statement initializer where it has to be wrapped
+    forbid_in: bool,
+}
+
+impl Default for Codegen {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Codegen {
@@ -29,6 +57,8 @@ impl Codegen {
         Codegen {
             source: String::new(),
             statement_start: 0,
+            indent: 0,
+            forbid_in: false,
         }
     }
 
@@ -60,18 +90,87 @@ impl Codegen {
         self.source.push_str(text);
     }
 
-    /// Prints a statement
+    fn print_indent(&mut self) {
+        for _ in 0..self.indent {
+            self.print("  ");
+        }
+    }
+
+    /// Prints a statement on its own line
     fn print_statement(&mut self, statement: &Statement) {
+        self.print_indent();
+        self.print_statement_without_indent(statement);
+    }
+
+    /// Prints a statement that continues the current line
+    fn print_statement_without_indent(&mut self, statement: &Statement) {
         match statement {
             Statement::ExpressionStatement(e) => self.print_expression_statement(e),
-            Statement::EmptyStatement(_) => todo!(),
-            Statement::BreakStatement(_) => todo!(),
-            Statement::BlockStatement(_) => todo!(),
+            Statement::EmptyStatement(_) => self.print_semicolon_after_statement(),
+            Statement::BreakStatement(b) => self.print_break_statement(b),
+            Statement::ContinueStatement(c) => self.print_continue_statement(c),
+            Statement::DebuggerStatement(_) => {
+                self.print("debugger");
+                self.print_semicolon_after_statement();
+            }
+            Statement::BlockStatement(b) => {
+                self.print_block(&b.statements);
+                self.print_newline();
+            }
+            Statement::IfStatement(i) => self.print_if_statement(i),
+            Statement::DoWhileStatement(d) => self.print_do_while_statement(d),
+            Statement::WhileStatement(w) => self.print_while_statement(w),
+            Statement::ForStatement(f) => self.print_for_statement(f),
+            Statement::ForInStatement(f) => self.print_for_in_statement(f),
+            Statement::ForOfStatement(f) => self.print_for_of_statement(f),
+            Statement::ReturnStatement(r) => self.print_return_statement(r),
+            Statement::ThrowStatement(t) => self.print_throw_statement(t),
+            Statement::TryStatement(t) => self.print_try_statement(t),
+            Statement::SwitchStatement(s) => self.print_switch_statement(s),
+            Statement::WithStatement(w) => self.print_with_statement(w),
+            Statement::LabelledStatement(l) => self.print_labelled_statement(l),
+            Statement::FunctionDeclaration(f) => self.print_function_declaration(f),
             Statement::VariableStatement(v) => self.print_variable_statement(v),
             Statement::LexicalDeclaration(l) => self.print_lexical_declaration(l),
         }
     }
 
+    /// Prints the statements in braces, an empty block is printed as `{}`
+    fn print_block(&mut self, statements: &[Statement]) {
+        if statements.is_empty() {
+            self.print("{}");
+            return;
+        }
+
+        self.print("{");
+        self.print_newline();
+        self.indent += 1;
+        for statement in statements {
+            self.print_statement(statement);
+        }
+        self.indent -= 1;
+        self.print_indent();
+        self.print("}");
+    }
+
+    /// Prints the body of an if statement, a loop, a with statement or a labelled statement.
+    /// A block is kept on the same line, other statements are indented on the next line.
+    fn print_body(&mut self, body: &Statement) {
+        match body {
+            Statement::BlockStatement(b) => {
+                self.print_space();
+                self.print_block(&b.statements);
+                self.print_newline();
+            }
+            _ => {
+                self.print_newline();
+                self.indent += 1;
+                self.print_statement(body);
+                self.indent -= 1;
+            }
+        }
+    }
+
     /// Prints an expression statement
     fn print_expression_statement(&mut self, expression_statement: &ExpressionStatement) {
         self.statement_start = self.source.len();
@@ -79,45 +178,354 @@ impl Codegen {
         self.print_semicolon_after_statement();
     }
 
-    /// Prints a variable statement
-    fn print_variable_statement(&mut self, variable_statement: &VariableStatement) {
-        self.print("var ");
-        for (idx, declaration) in variable_statement.declarations.iter().enumerate() {
+    /// Prints a break statement
+    fn print_break_statement(&mut self, break_statement: &BreakStatement) {
+        self.print("break");
+        if let Some(id) = &break_statement.id {
+            self.print(" ");
+            self.print(&id.name);
+        }
+        self.print_semicolon_after_statement();
+    }
+
+    /// Prints a continue statement
+    fn print_continue_statement(&mut self, continue_statement: &ContinueStatement) {
+        self.print("continue");
+        if let Some(id) = &continue_statement.id {
+            self.print(" ");
+            self.print(&id.name);
+        }
+        self.print_semicolon_after_statement();
+    }
+
+    /// Prints an if statement, else if chains are kept on the same line
+    fn print_if_statement(&mut self, if_statement: &IfStatement) {
+        self.print("if");
+        self.print_space();
+        self.print("(");
+        self.print_expression(&if_statement.test, &Precedence::Comma);
+        self.print(")");
+
+        let alternate = match &if_statement.alternate {
+            Some(alternate) => alternate,
+            None => return self.print_body(&if_statement.consequent),
+        };
+        match if_statement.consequent.as_ref() {
+            Statement::BlockStatement(b) => {
+                self.print_space();
+                self.print_block(&b.statements);
+                self.print_space();
+            }
+            consequent => {
+                self.print_body(consequent);
+                self.print_indent();
+            }
+        }
+        self.print("else");
+        match alternate.as_ref() {
+            Statement::IfStatement(i) => {
+                self.print(" ");
+                self.print_if_statement(i);
+            }
+            alternate => self.print_body(alternate),
+        }
+    }
+
+    /// Prints a do while statement
+    fn print_do_while_statement(&mut self, do_while_statement: &DoWhileStatement) {
+        self.print("do");
+        match do_while_statement.body.as_ref() {
+            Statement::BlockStatement(b) => {
+                self.print_space();
+                self.print_block(&b.statements);
+                self.print_space();
+            }
+            body => {
+                self.print_body(body);
+                self.print_indent();
+            }
+        }
+        self.print("while");
+        self.print_space();
+        self.print("(");
+        self.print_expression(&do_while_statement.test, &Precedence::Comma);
+        self.print(")");
+        self.print_semicolon_after_statement();
+    }
+
+    /// Prints a while statement
+    fn print_while_statement(&mut self, while_statement: &WhileStatement) {
+        self.print("while");
+        self.print_space();
+        self.print("(");
+        self.print_expression(&while_statement.test, &Precedence::Comma);
+        self.print(")");
+        self.print_body(&while_statement.body);
+    }
+
+    /// Prints a for statement
+    fn print_for_statement(&mut self, for_statement: &ForStatement) {
+        self.print("for");
+        self.print_space();
+        self.print("(");
+        if let Some(init) = &for_statement.init {
+            self.forbid_in = true;
+            self.print_for_statement_init(init);
+            self.forbid_in = false;
+        }
+        self.print(";");
+        if let Some(test) = &for_statement.test {
+            self.print_space();
+            self.print_expression(test, &Precedence::Comma);
+        }
+        self.print(";");
+        if let Some(update) = &for_statement.update {
+            self.print_space();
+            self.print_expression(update, &Precedence::Comma);
+        }
+        self.print(")");
+        self.print_body(&for_statement.body);
+    }
+
+    /// Prints a for in statement
+    fn print_for_in_statement(&mut self, for_in_statement: &ForInStatement) {
+        self.print("for");
+        self.print_space();
+        self.print("(");
+        self.print_for_statement_init(&for_in_statement.left);
+        self.print(" in ");
+        self.print_expression(&for_in_statement.right, &Precedence::Comma);
+        self.print(")");
+        self.print_body(&for_in_statement.body);
+    }
+
+    /// Prints a for of statement
+    fn print_for_of_statement(&mut self, for_of_statement: &ForOfStatement) {
+        self.print("for");
+        self.print_space();
+        self.print("(");
+        self.print_for_statement_init(&for_of_statement.left);
+        self.print(" of ");
+        self.print_expression(&for_of_statement.right, &Precedence::Comma);
+        self.print(")");
+        self.print_body(&for_of_statement.body);
+    }
+
+    /// Prints the declaration or the expression at the start of a for statement
+    fn print_for_statement_init(&mut self, init: &ForStatementInit) {
+        match init {
+            ForStatementInit::VariableStatement(v) => {
+                self.print("var ");
+                self.print_declarations(
+                    v.declarations
+                        .iter()
+                        .map(|d| (&d.binding, d.initializer.as_ref())),
+                );
+            }
+            ForStatementInit::LexicalDeclaration(l) => {
+                match l.is_const {
+                    true => self.print("const "),
+                    false => self.print("let "),
+                };
+                self.print_declarations(
+                    l.declarations
+                        .iter()
+                        .map(|d| (&d.binding, d.initializer.as_ref())),
+                );
+            }
+            ForStatementInit::Expression(e) => self.print_expression(e, &Precedence::Comma),
+        }
+    }
+
+    /// Prints a return statement
+    fn print_return_statement(&mut self, return_statement: &ReturnStatement) {
+        self.print("return");
+        if let Some(argument) = &return_statement.argument {
+            self.print(" ");
+            self.print_expression(argument, &Precedence::Comma);
+        }
+        self.print_semicolon_after_statement();
+    }
+
+    /// Prints a throw statement
+    fn print_throw_statement(&mut self, throw_statement: &ThrowStatement) {
+        self.print("throw ");
+        self.print_expression(&throw_statement.argument, &Precedence::Comma);
+        self.print_semicolon_after_statement();
+    }
+
+    /// Prints a try statement
+    fn print_try_statement(&mut self, try_statement: &TryStatement) {
+        self.print("try");
+        self.print_space();
+        self.print_block(&try_statement.block.statements);
+        if let Some(handler) = &try_statement.handler {
+            self.print_space();
+            self.print("catch");
+            self.print_space();
+            if let Some(param) = &handler.param {
+                self.print("(");
+                self.print_target_binding_pattern(param);
+                self.print(")");
+                self.print_space();
+            }
+            self.print_block(&handler.body.statements);
+        }
+        if let Some(finalizer) = &try_statement.finalizer {
+            self.print_space();
+            self.print("finally");
+            self.print_space();
+            self.print_block(&finalizer.statements);
+        }
+        self.print_newline();
+    }
+
+    /// Prints a switch statement
+    fn print_switch_statement(&mut self, switch_statement: &SwitchStatement) {
+        self.print("switch");
+        self.print_space();
+        self.print("(");
+        self.print_expression(&switch_statement.discriminant, &Precedence::Comma);
+        self.print(")");
+        self.print_space();
+        if switch_statement.cases.is_empty() {
+            self.print("{}");
+            self.print_newline();
+            return;
+        }
+
+        self.print("{");
+        self.print_newline();
+        self.indent += 1;
+        for case in &switch_statement.cases {
+            self.print_indent();
+            match &case.test {
+                Some(test) => {
+                    self.print("case ");
+                    self.print_expression(test, &Precedence::Comma);
+                }
+                None => self.print("default"),
+            }
+            self.print(":");
+            self.print_newline();
+            self.indent += 1;
+            for statement in &case.consequent {
+                self.print_statement(statement);
+            }
+            self.indent -= 1;
+        }
+        self.indent -= 1;
+        self.print_indent();
+        self.print("}");
+        self.print_newline();
+    }
+
+    /// Prints a with statement
+    fn print_with_statement(&mut self, with_statement: &WithStatement) {
+        self.print("with");
+        self.print_space();
+        self.print("(");
+        self.print_expression(&with_statement.object, &Precedence::Comma);
+        self.print(")");
+        self.print_body(&with_statement.body);
+    }
+
+    /// Prints a labelled statement
+    fn print_labelled_statement(&mut self, labelled_statement: &LabelledStatement) {
+        self.print(&labelled_statement.label.name);
+        self.print(":");
+        match labelled_statement.body.as_ref() {
+            Statement::BlockStatement(_) => self.print_body(&labelled_statement.body),
+            body => {
+                self.print_space();
+                self.print_statement_without_indent(body);
+            }
+        }
+    }
+
+    /// Prints a function declaration
+    fn print_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+        if function_declaration.is_async {
+            self.print("async ");
+        }
+        self.print("function");
+        if function_declaration.is_generator {
+            self.print("*");
+        }
+        self.print(" ");
+        self.print_binding_identifier(&function_declaration.id);
+        self.print("(");
+        for (idx, parameter) in function_declaration.parameters.iter().enumerate() {
             if idx != 0 {
                 self.print(",");
                 self.print_space();
             }
-            self.print_target_binding_pattern(&declaration.binding);
-            if let Some(initializer) = &declaration.initializer {
+            self.print_target_binding_pattern(&parameter.binding);
+            if let Some(initializer) = &parameter.initializer {
                 self.print_space();
                 self.print("=");
                 self.print_space();
                 self.print_expression(initializer, &Precedence::Comma);
             }
         }
-        self.print_semicolon_after_statement();
+        if let Some(rest) = &function_declaration.rest {
+            if !function_declaration.parameters.is_empty() {
+                self.print(",");
+                self.print_space();
+            }
+            self.print("...");
+            self.print_target_binding_pattern(rest);
+        }
+        self.print(")");
+        self.print_space();
+        self.print_block(&function_declaration.body);
+        self.print_newline();
     }
 
-    /// Prints a lexical declaration
-    fn print_lexical_declaration(&mut self, lexical_declaration: &LexicalDeclaration) {
-        match lexical_declaration.is_const {
-            true => self.print("const "),
-            false => self.print("let "),
-        };
-
-        for (idx, declaration) in lexical_declaration.declarations.iter().enumerate() {
+    /// Prints the declarations of a variable statement or a lexical declaration
+    fn print_declarations<'b>(
+        &mut self,
+        declarations: impl Iterator<Item = (&'b TargetBindingPattern, Option<&'b Expression>)>,
+    ) {
+        for (idx, (binding, initializer)) in declarations.enumerate() {
             if idx != 0 {
                 self.print(",");
                 self.print_space();
             }
-            self.print_target_binding_pattern(&declaration.binding);
-            if let Some(initializer) = &declaration.initializer {
+            self.print_target_binding_pattern(binding);
+            if let Some(initializer) = initializer {
                 self.print_space();
                 self.print("=");
                 self.print_space();
                 self.print_expression(initializer, &Precedence::Comma);
             }
         }
+    }
+
+    /// Prints a variable statement
+    fn print_variable_statement(&mut self, variable_statement: &VariableStatement) {
+        self.print("var ");
+        self.print_declarations(
+            variable_statement
+                .declarations
+                .iter()
+                .map(|d| (&d.binding, d.initializer.as_ref())),
+        );
+        self.print_semicolon_after_statement();
+    }
+
+    /// Prints a lexical declaration
+    fn print_lexical_declaration(&mut self, lexical_declaration: &LexicalDeclaration) {
+        match lexical_declaration.is_const {
+            true => self.print("const "),
+            false => self.print("let "),
+        };
+        self.print_declarations(
+            lexical_declaration
+                .declarations
+                .iter()
+                .map(|d| (&d.binding, d.initializer.as_ref())),
+        );
         self.print_semicolon_after_statement();
     }
 
@@ -185,7 +593,7 @@ impl Codegen {
             }
         }
         if let Some(rest) = &obp.rest {
-            if obp.properties.len() > 0 {
+            if !obp.properties.is_empty() {
                 self.print(",");
                 self.print_space();
             }
@@ -244,10 +652,15 @@ impl Codegen {
         match expression {
             Expression::ArrayExpression(a) => self.print_array_expression(a),
             Expression::NumericLiteral(n) => self.print_numeric_expression(n),
+            Expression::BigIntLiteral(b) => self.print_big_int_literal(b),
             Expression::StringLiteral(s) => self.print_string_literal(s),
             Expression::RegexpLiteral(r) => self.print_regexp_literal(r),
             Expression::BooleanLiteral(b) => self.print_boolean_literal(b),
             Expression::BinaryExpression(b) => self.print_binary_expression(b, precedence),
+            Expression::AssignmentExpression(a) => self.print_assignment_expression(a, precedence),
+            Expression::UpdateExpression(u) => self.print_update_expression(u, precedence),
+            Expression::YieldExpression(y) => self.print_yield_expression(y, precedence),
+            Expression::IdentifierReference(i) => self.print(&i.name),
             _ => todo!(),
         }
     }
@@ -258,12 +671,27 @@ impl Codegen {
         binary_expression: &BinaryExpression,
         precedence: &Precedence,
     ) {
-        let wrap = precedence >= &binary_expression.operator.precedence();
+        let wrap = precedence >= &binary_expression.operator.precedence()
+            || (self.forbid_in
+                && matches!(binary_expression.operator, BinaryExpressionOperator::In));
         if wrap {
             self.print("(");
         }
+        let forbid_in = self.forbid_in;
+        if wrap {
+            self.forbid_in = false;
+        }
+
+        // Operators are left associative except for exponentiation
+        let operator_precedence = binary_expression.operator.precedence();
+        let (left_precedence, right_precedence) = match binary_expression.operator {
+            BinaryExpressionOperator::Exponentiation => {
+                (operator_precedence.clone(), operator_precedence.lower())
+            }
+            _ => (operator_precedence.lower(), operator_precedence.clone()),
+        };
 
-        self.print_expression(binary_expression.left.as_ref(), precedence);
+        self.print_expression(binary_expression.left.as_ref(), &left_precedence);
 
         if binary_expression.operator.is_keyword() {
             self.print(" ");
@@ -275,7 +703,90 @@ impl Codegen {
             self.print_space();
         }
 
-        self.print_expression(binary_expression.right.as_ref(), precedence);
+        self.print_expression(binary_expression.right.as_ref(), &right_precedence);
+
+        self.forbid_in = forbid_in;
+        if wrap {
+            self.print(")");
+        }
+    }
+
+    /// Prints an assignment expression
+    fn print_assignment_expression(
+        &mut self,
+        assignment_expression: &AssignmentExpression,
+        precedence: &Precedence,
+    ) {
+        let wrap = precedence >= &Precedence::Assignment;
+        if wrap {
+            self.print("(");
+        }
+
+        match &assignment_expression.target {
+            AssignmentExpressionTarget::IdentifierReference(i) => self.print(&i.name),
+        }
+        self.print_space();
+        self.print(assignment_expression.operator.to_str());
+        self.print_space();
+        self.print_expression(
+            assignment_expression.argument.as_ref(),
+            &Precedence::Assignment.lower(),
+        );
+
+        if wrap {
+            self.print(")");
+        }
+    }
+
+    /// Prints a prefix or postfix update expression
+    fn print_update_expression(
+        &mut self,
+        update_expression: &UpdateExpression,
+        precedence: &Precedence,
+    ) {
+        let operator_precedence = match update_expression.prefix {
+            true => Precedence::Prefix,
+            false => Precedence::Postfix,
+        };
+        let wrap = precedence >= &operator_precedence;
+        if wrap {
+            self.print("(");
+        }
+
+        if update_expression.prefix {
+            self.print(update_expression.operator.to_str());
+            self.print_expression(update_expression.argument.as_ref(), &Precedence::Prefix);
+        } else {
+            self.print_expression(update_expression.argument.as_ref(), &Precedence::Postfix);
+            self.print(update_expression.operator.to_str());
+        }
+
+        if wrap {
+            self.print(")");
+        }
+    }
+
+    /// Prints a yield expression
+    fn print_yield_expression(
+        &mut self,
+        yield_expression: &YieldExpression,
+        precedence: &Precedence,
+    ) {
+        let wrap = precedence >= &Precedence::Assignment;
+        if wrap {
+            self.print("(");
+        }
+
+        self.print("yield");
+        if let Some(argument) = &yield_expression.argument {
+            if yield_expression.delegate {
+                self.print("*");
+                self.print_space();
+            } else {
+                self.print(" ");
+            }
+            self.print_expression(argument.as_ref(), &Precedence::Yield);
+        }
 
         if wrap {
             self.print(")");
@@ -323,6 +834,12 @@ impl Codegen {
         self.print(&numeric_expression.value.to_string());
     }
 
+    /// Prints a big int expression
+    fn print_big_int_literal(&mut self, big_int_literal: &BigIntLiteral) {
+        self.print(&big_int_literal.value);
+        self.print("n");
+    }
+
     /// Prints a string expression
     fn print_string_literal(&mut self, string_literal: &StringLiteral) {
         self.print("\"");
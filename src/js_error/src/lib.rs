@@ -18,11 +18,49 @@ impl JSError {
 pub enum JSErrorKind {
     SyntaxError,
     IdentifierAfterNumber,
+    InvalidBigInt,
+    InvalidUnicodeEscape,
+    InvalidEscapedIdentifier,
+    EscapedKeyword,
+    MissingExponent,
+    MissingRadixDigits,
+    InvalidBinaryDigit,
+    InvalidOctalDigit,
+    ConsecutiveNumericSeparators,
+    TrailingNumericSeparator,
+    InvalidNumericSeparator,
+    LegacyOctalInStrictMode,
+    LeadingZeroInStrictMode,
     UnterminatedBlockComment,
     UnterminatedStringLiteral,
     UnterminatedTemplateLiteral,
     UnterminatedRegexp,
     InvalidRegexpFlag,
+    DuplicateRegexpFlag,
+    IncompatibleRegexpFlags,
+    RegexpNothingToRepeat,
+    InvalidRegexpQuantifier,
+    IncompleteRegexpQuantifier,
+    RegexpQuantifierOutOfOrder,
+    LoneRegexpQuantifierBracket,
+    UnterminatedRegexpGroup,
+    UnmatchedRegexpParenthesis,
+    InvalidRegexpGroup,
+    InvalidRegexpGroupName,
+    DuplicateRegexpGroupName,
+    InvalidRegexpNamedReference,
+    UndefinedRegexpGroupName,
+    InvalidRegexpEscape,
+    InvalidRegexpDecimalEscape,
+    InvalidRegexpClassEscape,
+    InvalidRegexpUnicodeEscape,
+    InvalidRegexpPropertyName,
+    InvalidRegexpCharacterClass,
+    RegexpRangeOutOfOrder,
+    UnterminatedRegexpCharacterClass,
+    InvalidRegexpSetOperation,
+    InvalidRegexpClassCharacter,
+    NegatedRegexpClassWithStrings,
     MissingConstInitializer,
     StrictModeReserved,
     UnexpectedYieldAsBindingIdentifier,
@@ -31,6 +69,19 @@ pub enum JSErrorKind {
     InvalidShorthandPropertyKey,
     RestElementMustBeLast,
     TrailingCommaAfterRestElement,
+    ReturnOutsideFunction,
+    IllegalBreak,
+    IllegalContinue,
+    UndefinedLabel,
+    DuplicateLabel,
+    NewlineAfterThrow,
+    MultipleDefaultsInSwitch,
+    MissingCatchOrFinally,
+    StrictModeWith,
+    InvalidForInOfDeclaration,
+    DeclarationInStatementPosition,
+    ExpectedSemicolon,
+    InvalidAssignmentTarget,
 }
 
 impl fmt::Display for JSErrorKind {
@@ -40,11 +91,85 @@ impl fmt::Display for JSErrorKind {
             JSErrorKind::IdentifierAfterNumber => {
                 write!(f, "Identifiers are not allowed directly after a number")
             }
+            JSErrorKind::InvalidBigInt => write!(f, "Invalid BigInt literal"),
+            JSErrorKind::InvalidUnicodeEscape => write!(f, "Invalid unicode escape sequence"),
+            JSErrorKind::InvalidEscapedIdentifier => {
+                write!(f, "The escaped character is not allowed in an identifier")
+            }
+            JSErrorKind::EscapedKeyword => {
+                write!(f, "Keywords cannot contain escape characters")
+            }
+            JSErrorKind::MissingExponent => write!(f, "The exponent is missing its digits"),
+            JSErrorKind::MissingRadixDigits => {
+                write!(f, "Expected digits after the number prefix")
+            }
+            JSErrorKind::InvalidBinaryDigit => write!(f, "Invalid digit in binary number"),
+            JSErrorKind::InvalidOctalDigit => write!(f, "Invalid digit in octal number"),
+            JSErrorKind::ConsecutiveNumericSeparators => {
+                write!(f, "Only one underscore is allowed as numeric separator")
+            }
+            JSErrorKind::TrailingNumericSeparator => {
+                write!(
+                    f,
+                    "Numeric separators are not allowed at the end of numbers"
+                )
+            }
+            JSErrorKind::InvalidNumericSeparator => {
+                write!(f, "Numeric separators are only allowed between digits")
+            }
+            JSErrorKind::LegacyOctalInStrictMode => {
+                write!(f, "Legacy octal literals are not allowed in strict mode")
+            }
+            JSErrorKind::LeadingZeroInStrictMode => {
+                write!(
+                    f,
+                    "Decimals with leading zeros are not allowed in strict mode"
+                )
+            }
             JSErrorKind::UnterminatedBlockComment => write!(f, "Unterminated block comment"),
             JSErrorKind::UnterminatedStringLiteral => write!(f, "Unterminated string literal"),
             JSErrorKind::UnterminatedTemplateLiteral => write!(f, "Unterminated template literal"),
             JSErrorKind::UnterminatedRegexp => write!(f, "Unterminated regexp"),
             JSErrorKind::InvalidRegexpFlag => write!(f, "The regexp flag is invalid"),
+            JSErrorKind::DuplicateRegexpFlag => write!(f, "The regexp flag is repeated"),
+            JSErrorKind::IncompatibleRegexpFlags => {
+                write!(f, "The regexp flags \"u\" and \"v\" cannot be combined")
+            }
+            JSErrorKind::RegexpNothingToRepeat => write!(f, "Nothing to repeat"),
+            JSErrorKind::InvalidRegexpQuantifier => write!(f, "The assertion cannot be quantified"),
+            JSErrorKind::IncompleteRegexpQuantifier => write!(f, "Incomplete quantifier"),
+            JSErrorKind::RegexpQuantifierOutOfOrder => {
+                write!(f, "Numbers out of order in {{}} quantifier")
+            }
+            JSErrorKind::LoneRegexpQuantifierBracket => write!(f, "Lone quantifier brackets"),
+            JSErrorKind::UnterminatedRegexpGroup => write!(f, "Unterminated group"),
+            JSErrorKind::UnmatchedRegexpParenthesis => write!(f, "Unmatched ')'"),
+            JSErrorKind::InvalidRegexpGroup => write!(f, "Invalid group"),
+            JSErrorKind::InvalidRegexpGroupName => write!(f, "Invalid capture group name"),
+            JSErrorKind::DuplicateRegexpGroupName => write!(f, "Duplicate capture group name"),
+            JSErrorKind::InvalidRegexpNamedReference => write!(f, "Invalid named reference"),
+            JSErrorKind::UndefinedRegexpGroupName => write!(f, "Invalid named capture referenced"),
+            JSErrorKind::InvalidRegexpEscape => write!(f, "Invalid escape"),
+            JSErrorKind::InvalidRegexpDecimalEscape => write!(f, "Invalid decimal escape"),
+            JSErrorKind::InvalidRegexpClassEscape => write!(f, "Invalid class escape"),
+            JSErrorKind::InvalidRegexpUnicodeEscape => write!(f, "Invalid Unicode escape"),
+            JSErrorKind::InvalidRegexpPropertyName => write!(f, "Invalid property name"),
+            JSErrorKind::InvalidRegexpCharacterClass => write!(f, "Invalid character class"),
+            JSErrorKind::RegexpRangeOutOfOrder => {
+                write!(f, "Range out of order in character class")
+            }
+            JSErrorKind::UnterminatedRegexpCharacterClass => {
+                write!(f, "Unterminated character class")
+            }
+            JSErrorKind::InvalidRegexpSetOperation => {
+                write!(f, "Invalid set operation in character class")
+            }
+            JSErrorKind::InvalidRegexpClassCharacter => {
+                write!(f, "Invalid character in character class")
+            }
+            JSErrorKind::NegatedRegexpClassWithStrings => {
+                write!(f, "Negated character class may contain strings")
+            }
             JSErrorKind::MissingConstInitializer => write!(f, "Missing const initializer"),
             JSErrorKind::StrictModeReserved => write!(f, "Unexpected reserved word in struct mode"),
             JSErrorKind::UnexpectedYieldAsBindingIdentifier => {
@@ -61,6 +186,32 @@ impl fmt::Display for JSErrorKind {
             JSErrorKind::TrailingCommaAfterRestElement => {
                 write!(f, "Unexpected trailing comma after rest element")
             }
+            JSErrorKind::ReturnOutsideFunction => {
+                write!(f, "A return statement is only allowed inside a function")
+            }
+            JSErrorKind::IllegalBreak => write!(f, "Illegal break statement"),
+            JSErrorKind::IllegalContinue => write!(f, "Illegal continue statement"),
+            JSErrorKind::UndefinedLabel => write!(f, "Undefined label"),
+            JSErrorKind::DuplicateLabel => write!(f, "The label has already been declared"),
+            JSErrorKind::NewlineAfterThrow => write!(f, "Illegal newline after throw"),
+            JSErrorKind::MultipleDefaultsInSwitch => {
+                write!(f, "More than one default clause in switch statement")
+            }
+            JSErrorKind::MissingCatchOrFinally => write!(f, "Missing catch or finally after try"),
+            JSErrorKind::StrictModeWith => {
+                write!(f, "With statements are not allowed in strict mode")
+            }
+            JSErrorKind::InvalidForInOfDeclaration => {
+                write!(f, "Invalid declaration in for-in or for-of loop")
+            }
+            JSErrorKind::DeclarationInStatementPosition => {
+                write!(
+                    f,
+                    "Declarations are not allowed in a single-statement context"
+                )
+            }
+            JSErrorKind::ExpectedSemicolon => write!(f, "Expected \";\""),
+            JSErrorKind::InvalidAssignmentTarget => write!(f, "Invalid assignment target"),
         }
     }
 }
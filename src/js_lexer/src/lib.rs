@@ -14,13 +14,59 @@
 /// call the lexer differently depending on the context.
 use std::str::Chars;
 
+use js_error::{JSError, JSErrorKind};
+use js_regexp::parse_regexp;
 use js_token::{lookup_identifer, Token};
 use logger::Logger;
-mod unicode;
+use span::Span;
 
 /// This means we've hit the end of the file
 pub const EOF_CHAR: char = '\0';
 
+/// A comment encountered while scanning. Comments are not tokens,
+/// instead they are attached to the token that follows them.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    /// The raw text of the comment, including the delimiters.
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Comment {
+    /// Legal comments are comments that should be kept in the output,
+    /// such as `/*! ... */` or comments containing `@license` or `@preserve`.
+    pub fn is_legal(&self) -> bool {
+        self.text.starts_with("/*!")
+            || self.text.starts_with("//!")
+            || self.text.contains("@license")
+            || self.text.contains("@preserve")
+    }
+
+    /// Pure comments mark the call or new expression that follows
+    /// them as free of side effects: `/* @__PURE__ */ a()`.
+    pub fn is_pure(&self) -> bool {
+        self.text.contains("@__PURE__") || self.text.contains("#__PURE__")
+    }
+
+    /// Marks the function declaration that follows the comment as free
+    /// of side effects, making every call to it pure.
+    pub fn is_no_side_effects(&self) -> bool {
+        self.text.contains("@__NO_SIDE_EFFECTS__") || self.text.contains("#__NO_SIDE_EFFECTS__")
+    }
+
+    /// Returns the chunk name from a `/* webpackChunkName: "name" */` annotation.
+    pub fn webpack_chunk_name(&self) -> Option<String> {
+        let index = self.text.find("webpackChunkName")?;
+        let rest = self.text[index + "webpackChunkName".len()..].trim_start();
+        let rest = rest.strip_prefix(':')?.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let rest = &rest[1..];
+        let end = rest.find(quote)?;
+        Some(rest[..end].into())
+    }
+}
+
 pub struct Lexer<'a, L: Logger> {
     input: &'a str,
     chars: Chars<'a>,
@@ -38,6 +84,17 @@ pub struct Lexer<'a, L: Logger> {
     pub number: f64,
     /// The currently parsed token
     pub token: Token,
+    /// The comments found between the previous token and the current one.
+    pub comments_before_token: Vec<Comment>,
+    /// The legal comments found since the parser last took them, including the
+    /// ones inside of expressions that `comments_before_token` has moved past.
+    pub legal_comments: Vec<Comment>,
+    /// Strict mode code can not use legacy octal numbers such as `010`.
+    pub strict_mode: bool,
+    /// True if there is a line terminator between the previous token and the current one.
+    pub has_newline_before: bool,
+    /// True if the current identifier or keyword contains escape sequences, such as `\u0061sync`.
+    pub has_escape: bool,
 
     logger: &'a L,
 }
@@ -51,6 +108,11 @@ impl<'a, L: Logger> Lexer<'a, L> {
             identifier: String::new(),
             number: 0.,
             token: Token::EndOfFile,
+            comments_before_token: Vec::new(),
+            legal_comments: Vec::new(),
+            strict_mode: false,
+            has_newline_before: false,
+            has_escape: false,
             start: 0,
             current: 0,
             end: 0,
@@ -61,19 +123,22 @@ impl<'a, L: Logger> Lexer<'a, L> {
 
         lexer.step();
         lexer.next_token();
-        return lexer;
+        lexer
     }
 
     /// Asserts that the current token matches the provided one
     pub fn expect_token(&self, token: Token) {
         if self.token != token {
             self.logger.add_error(
-                &self.input,
+                self.input,
                 logger::Range {
                     start: self.start,
                     end: self.end,
                 },
-                format!("Expected \"{}\" but found \"{}\"", token, self.token),
+                match self.token {
+                    Token::EscapedKeyword => JSErrorKind::EscapedKeyword.to_string(),
+                    _ => format!("Expected \"{}\" but found \"{}\"", token, self.token),
+                },
             );
             std::process::exit(1);
         }
@@ -89,90 +154,123 @@ impl<'a, L: Logger> Lexer<'a, L> {
     /// Returns a boolean indicating if the current token
     /// is either an identifier or a keyword.
     pub fn is_identifier_or_keyword(&self) -> bool {
-        match &self.token {
-            Token::Identifier => true,
-            Token::Await => true,
-            Token::As => true,
-            Token::Break => true,
-            Token::Case => true,
-            Token::Catch => true,
-            Token::Class => true,
-            Token::Const => true,
-            Token::Continue => true,
-            Token::Debugger => true,
-            Token::Default => true,
-            Token::Delete => true,
-            Token::Do => true,
-            Token::Else => true,
-            Token::Enum => true,
-            Token::Export => true,
-            Token::Extends => true,
-            Token::From => true,
-            Token::False => true,
-            Token::Finally => true,
-            Token::For => true,
-            Token::Function => true,
-            Token::Let => true,
-            Token::If => true,
-            Token::Import => true,
-            Token::In => true,
-            Token::Instanceof => true,
-            Token::New => true,
-            Token::Null => true,
-            Token::Of => true,
-            Token::Return => true,
-            Token::Super => true,
-            Token::Switch => true,
-            Token::This => true,
-            Token::Throw => true,
-            Token::True => true,
-            Token::Try => true,
-            Token::Typeof => true,
-            Token::Var => true,
-            Token::Void => true,
-            Token::While => true,
-            Token::With => true,
-
-            _ => false,
-        }
+        matches!(
+            &self.token,
+            Token::Identifier
+                | Token::EscapedKeyword
+                | Token::Await
+                | Token::As
+                | Token::Break
+                | Token::Case
+                | Token::Catch
+                | Token::Class
+                | Token::Const
+                | Token::Continue
+                | Token::Debugger
+                | Token::Default
+                | Token::Delete
+                | Token::Do
+                | Token::Else
+                | Token::Enum
+                | Token::Export
+                | Token::Extends
+                | Token::From
+                | Token::False
+                | Token::Finally
+                | Token::For
+                | Token::Function
+                | Token::Let
+                | Token::If
+                | Token::Import
+                | Token::In
+                | Token::Instanceof
+                | Token::New
+                | Token::Null
+                | Token::Of
+                | Token::Return
+                | Token::Super
+                | Token::Switch
+                | Token::This
+                | Token::Throw
+                | Token::True
+                | Token::Try
+                | Token::Typeof
+                | Token::Var
+                | Token::Void
+                | Token::While
+                | Token::With
+        )
     }
 
     /// Reports the current token as unexpected.
     /// Calls exit and will therefor never return.
     pub fn unexpected(&self) -> ! {
+        let text = match self.token {
+            Token::EscapedKeyword => JSErrorKind::EscapedKeyword.to_string(),
+            _ => format!("Unexpected token \"{}\"", self.token),
+        };
         self.logger.add_error(
-            &self.input,
+            self.input,
             logger::Range {
                 start: self.start,
                 end: self.end,
             },
-            format!("Unexpected token \"{}\"", self.token),
+            text,
         );
         std::process::exit(1);
     }
 
+    /// Returns true if a pure comment was found before the current token.
+    pub fn has_pure_comment_before(&self) -> bool {
+        self.comments_before_token.iter().any(|c| c.is_pure())
+    }
+
+    /// Returns true if a `#__NO_SIDE_EFFECTS__` comment was found before the current token.
+    pub fn has_no_side_effects_comment_before(&self) -> bool {
+        self.comments_before_token
+            .iter()
+            .any(|c| c.is_no_side_effects())
+    }
+
     /// Returns the raw slice of input related to the current token.
     pub fn raw(&self) -> String {
         self.input[self.start..self.end].into()
     }
 
-    /// Scans the current token as a regexp
+    /// Enters or leaves strict mode code. The current token has already been
+    /// scanned, so a legacy octal or a decimal with a leading zero, such as a
+    /// module starting with `010`, is scanned again to be reported.
+    pub fn set_strict_mode(&mut self, strict_mode: bool) {
+        self.strict_mode = strict_mode;
+        let mut raw = self.input[self.start..self.end].chars();
+        if strict_mode
+            && self.token == Token::NumericLiteral
+            && raw.next() == Some('0')
+            && raw.next().is_some_and(|c| c.is_ascii_digit())
+        {
+            self.chars = self.input[self.start..].chars();
+            self.current = self.start;
+            self.step();
+            self.read_number();
+        }
+    }
+
+    /// Scans the current token as a regexp, the pattern and flags are validated
+    /// once the end of the regexp has been found.
     pub fn scan_regexp(&mut self) {
         loop {
             match self.character {
                 '/' => {
                     self.step();
-                    'inner: loop {
-                        if is_identifier_continue(self.character) {
-                            match self.character {
-                                'g' | 'i' | 'm' | 's' | 'u' | 'y' => self.step(),
-                                _ => self.unexpected(),
-                            }
-                        } else {
-                            break 'inner;
-                        }
+                    while is_identifier_continue(self.character) {
+                        self.step();
                     }
 
+                    if let Err(error) = parse_regexp(&self.input[self.start..self.end]) {
+                        let span =
+                            Span::new(self.start + error.span.start, self.start + error.span.end);
+                        self.report(JSError::new(error.kind, span));
+                    }
                     return;
                 }
 
@@ -211,10 +309,15 @@ impl<'a, L: Logger> Lexer<'a, L> {
 
     /// Scans the next token
     pub fn next_token(&mut self) {
+        self.comments_before_token.clear();
+        self.has_newline_before = false;
+        self.has_escape = false;
         loop {
             self.start = self.end;
 
-            self.consume_comment();
+            if self.consume_comment() {
+                continue;
+            }
 
             match self.character {
                 c if is_whitespace(c) => {
@@ -223,14 +326,26 @@ impl<'a, L: Logger> Lexer<'a, L> {
                 }
 
                 c if is_line_terminator(c) => {
+                    self.has_newline_before = true;
                     self.step();
                     continue;
                 }
 
-                c if is_identifier_start(c) => {
-                    let identifier = self.read_identifier();
-                    self.token = lookup_identifer(&identifier);
+                c if is_identifier_start(c) || c == '\\' => {
+                    let (identifier, has_escape) = self.read_identifier();
+                    self.token = match lookup_identifer(&identifier) {
+                        // Escaped contextual keywords are plain identifiers, but let
+                        // and await are reserved words in strict mode code.
+                        Token::As | Token::From | Token::Of if has_escape => Token::Identifier,
+                        Token::Let | Token::Await if has_escape && !self.strict_mode => {
+                            Token::Identifier
+                        }
+                        Token::Identifier => Token::Identifier,
+                        _ if has_escape => Token::EscapedKeyword,
+                        token => token,
+                    };
                     self.identifier = identifier;
+                    self.has_escape = has_escape;
                 }
 
                 '~' => {
@@ -494,7 +609,7 @@ impl<'a, L: Logger> Lexer<'a, L> {
                 }
 
                 '"' | '\'' => {
-                    let quote = self.character.clone();
+                    let quote = self.character;
                     self.step();
 
                     'string_literal: loop {
@@ -561,9 +676,9 @@ impl<'a, L: Logger> Lexer<'a, L> {
                 }
 
                 '0' => match self.peek() {
-                    'b' => self.read_radix_number(2),
-                    'o' => self.read_radix_number(8),
-                    'x' => self.read_radix_number(16),
+                    'b' | 'B' => self.read_radix_number(2),
+                    'o' | 'O' => self.read_radix_number(8),
+                    'x' | 'X' => self.read_radix_number(16),
                     _ => self.read_number(),
                 },
 
@@ -619,7 +734,16 @@ impl<'a, L: Logger> Lexer<'a, L> {
     fn step(&mut self) {
         self.character = self.chars.next().unwrap_or(EOF_CHAR);
         self.end = self.current;
-        self.current += 1;
+        self.current += self.character.len_utf8();
+    }
+
+    /// Returns where the current character ends, which
+    /// is the end of the input once the file has ended.
+    fn character_end(&self) -> usize {
+        match self.character {
+            EOF_CHAR => self.end,
+            _ => self.current,
+        }
     }
 
     // Returns the next token without moving the current.
@@ -628,11 +752,16 @@ impl<'a, L: Logger> Lexer<'a, L> {
     }
 
     /// Skip over the comment if the current character marks
-    /// the start of a comment.
-    fn consume_comment(&mut self) {
+    /// the start of a comment. The comment is recorded in
+    /// `comments_before_token` so the parser can act on it.
+    /// Returns true if a comment was consumed.
+    fn consume_comment(&mut self) -> bool {
+        let start = self.end;
+        let mut text = String::new();
         match (self.character, self.peek()) {
             // Single line comment
             ('/', '/') => {
+                text.push_str("//");
                 self.step(); // First /
                 self.step(); // Second /
 
@@ -640,6 +769,7 @@ impl<'a, L: Logger> Lexer<'a, L> {
                 'single_line_comment: loop {
                     match self.character {
                         c if is_line_terminator(c) => {
+                            self.has_newline_before = true;
                             self.step();
                             break 'single_line_comment;
                         }
@@ -647,19 +777,24 @@ impl<'a, L: Logger> Lexer<'a, L> {
                             self.step();
                             break 'single_line_comment;
                         }
-                        _ => self.step(),
+                        c => {
+                            text.push(c);
+                            self.step();
+                        }
                     }
                 }
             }
 
             // Multi-line comment
             ('/', '*') => {
+                text.push_str("/*");
                 self.step(); // /
                 self.step(); // *
 
                 'multi_line_comment: loop {
                     match (self.character, self.peek()) {
                         ('*', '/') => {
+                            text.push_str("*/");
                             self.step(); // *
                             self.step(); // /
                             break 'multi_line_comment;
@@ -667,171 +802,351 @@ impl<'a, L: Logger> Lexer<'a, L> {
                         (EOF_CHAR, _) | (_, EOF_CHAR) => {
                             panic!("File ended without terminating multi-line comment")
                         }
-                        _ => self.step(),
+                        (c, _) => {
+                            if is_line_terminator(c) {
+                                self.has_newline_before = true;
+                            }
+                            text.push(c);
+                            self.step();
+                        }
                     }
                 }
             }
 
             // For anything else, ignore.
-            _ => {}
+            _ => return false,
         };
+
+        let comment = Comment {
+            text,
+            start,
+            end: self.end,
+        };
+        if comment.is_legal() {
+            self.legal_comments.push(comment.clone());
+        }
+        self.comments_before_token.push(comment);
+        true
     }
 
-    fn read_identifier(&mut self) -> String {
+    /// Reads an identifier and decodes the unicode escapes in it, `\u0061` and `\u{61}`
+    /// are both `a`. Returns the name and whether it contained any escapes.
+    fn read_identifier(&mut self) -> (String, bool) {
         let mut word = String::new();
-        while self.character != EOF_CHAR {
-            if is_identifier_continue(self.character) {
-                word.push(self.character);
-                self.step();
-            } else {
-                break;
+        let mut has_escape = false;
+        loop {
+            match self.character {
+                '\\' => {
+                    let start = self.end;
+                    let character = self.read_unicode_escape();
+                    let is_valid = match word.is_empty() {
+                        true => is_identifier_start(character),
+                        false => is_identifier_continue(character),
+                    };
+                    if !is_valid {
+                        self.report(JSError::new(
+                            JSErrorKind::InvalidEscapedIdentifier,
+                            Span::new(start, self.end),
+                        ));
+                    }
+                    has_escape = true;
+                    word.push(character);
+                }
+                c if c != EOF_CHAR && is_identifier_continue(c) => {
+                    word.push(c);
+                    self.step();
+                }
+                _ => break,
             }
         }
-        return word;
+        (word, has_escape)
     }
 
-    fn read_number(&mut self) {
-        // 00
-        if self.character == '0' && self.peek() == '0' {
-            panic!("Legacy octal literals are not supported in strict mode");
+    /// Reads a unicode escape sequence, `\u0061` or `\u{61}`, and returns the character.
+    fn read_unicode_escape(&mut self) -> char {
+        let start = self.end;
+        self.step(); // \
+        if self.character != 'u' {
+            self.report(JSError::new(
+                JSErrorKind::InvalidUnicodeEscape,
+                Span::new(start, self.character_end()),
+            ));
         }
+        self.step();
 
-        // Means we've hit a fractal number .012
-        if self.character == '.' {
+        let mut digits = String::new();
+        if self.character == '{' {
             self.step();
-            let number = self.read_decimal_number();
-
-            // Exponent
-            if self.character == 'e' || self.character == 'E' {
-                todo!()
+            while self.character.is_ascii_hexdigit() {
+                digits.push(self.character);
+                self.step();
             }
+            if self.character != '}' || digits.is_empty() {
+                self.report(JSError::new(
+                    JSErrorKind::InvalidUnicodeEscape,
+                    Span::new(start, self.character_end()),
+                ));
+            }
+            self.step();
+        } else {
+            for _ in 0..4 {
+                if !self.character.is_ascii_hexdigit() {
+                    self.report(JSError::new(
+                        JSErrorKind::InvalidUnicodeEscape,
+                        Span::new(start, self.character_end()),
+                    ));
+                }
+                digits.push(self.character);
+                self.step();
+            }
+        }
 
-            self.token = Token::NumericLiteral;
-            self.number = format!("0.{}", number)
-                .parse::<f64>()
-                .expect(&format!("Failed to parse .{} into an f64", number));
+        // Values above 10FFFF and lone surrogates are not characters.
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .unwrap_or_else(|| {
+                self.report(JSError::new(
+                    JSErrorKind::InvalidUnicodeEscape,
+                    Span::new(start, self.end),
+                ))
+            })
+    }
 
-            return;
-        }
+    /// Reports an error found while scanning the current token.
+    /// Calls exit and will therefor never return.
+    fn report(&self, error: JSError) -> ! {
+        self.logger.add_error(
+            self.input,
+            logger::Range {
+                start: error.span.start,
+                end: error.span.end,
+            },
+            error.kind.to_string(),
+        );
+        std::process::exit(1);
+    }
 
-        let mut number = self.read_decimal_number();
+    /// Reads a decimal number, such as `1_000`, `.5e-3` or `5.`, as well
+    /// as the legacy octal numbers `010` and the decimals with leading zeros `08`.
+    fn read_number(&mut self) {
+        let start = self.end;
+        let mut text = String::new();
+        let mut is_integer = true;
+
+        // 010 or 08, neither of them can contain separators
+        if self.character == '0' && self.peek().is_ascii_digit() {
+            while self.character.is_ascii_digit() {
+                text.push(self.character);
+                self.step();
+            }
+            if self.character == '_' {
+                self.report(JSError::new(
+                    JSErrorKind::InvalidNumericSeparator,
+                    Span::new(self.end, self.current),
+                ));
+            }
 
-        // Exponent
-        if self.character == 'e' || self.character == 'E' {
-            todo!()
+            let is_octal = text.chars().all(|c| c < '8');
+            if self.strict_mode {
+                let kind = match is_octal {
+                    true => JSErrorKind::LegacyOctalInStrictMode,
+                    false => JSErrorKind::LeadingZeroInStrictMode,
+                };
+                self.report(JSError::new(kind, Span::new(start, self.end)));
+            }
+
+            // Only the decimals can have a fraction or an exponent, 010.5 is 8 followed by .5
+            if is_octal {
+                self.expect_end_of_number(start, false);
+                self.token = Token::NumericLiteral;
+                self.number = text
+                    .chars()
+                    .fold(0., |value, c| value * 8. + c.to_digit(8).unwrap() as f64);
+                return;
+            }
+        } else if self.character != '.' {
+            // A zero can not be followed by a separator, 0_1
+            if self.character == '0' && self.peek() == '_' {
+                self.step();
+                self.report(JSError::new(
+                    JSErrorKind::InvalidNumericSeparator,
+                    Span::new(self.end, self.current),
+                ));
+            }
+            text.push_str(&self.read_digits(10));
         }
 
-        // Fractal 1.1
+        // Fraction 1.5, .5 or 5.
         if self.character == '.' {
+            is_integer = false;
+            text.push('.');
             self.step();
-            number = format!("{}.{}", number, self.read_decimal_number());
+            if self.character == '_' {
+                self.report(JSError::new(
+                    JSErrorKind::InvalidNumericSeparator,
+                    Span::new(self.end, self.current),
+                ));
+            }
+            if self.character.is_ascii_digit() {
+                text.push_str(&self.read_digits(10));
+            }
         }
 
-        // BitInt
-        if self.character == 'n' {
+        // Exponent 1e3, 1e+3 or 1e-3
+        if self.character == 'e' || self.character == 'E' {
+            is_integer = false;
+            text.push('e');
+            self.step();
+            if self.character == '+' || self.character == '-' {
+                text.push(self.character);
+                self.step();
+            }
+            match self.character {
+                '0'..='9' => text.push_str(&self.read_digits(10)),
+                '_' => self.report(JSError::new(
+                    JSErrorKind::InvalidNumericSeparator,
+                    Span::new(self.end, self.current),
+                )),
+                _ => self.report(JSError::new(
+                    JSErrorKind::MissingExponent,
+                    Span::new(start, self.end),
+                )),
+            }
+        }
+
+        // BigInt, only integers without leading zeros can be one, 0n but not 01n or 1.5n
+        if self.character == 'n' && is_integer && (text == "0" || !text.starts_with('0')) {
             self.step();
+            self.expect_end_of_number(start, true);
             self.token = Token::BigIntegerLiteral;
-            self.identifier = number;
+            self.identifier = text;
             return;
         }
 
+        self.expect_end_of_number(start, false);
         self.token = Token::NumericLiteral;
-        self.number = number
+        self.number = text
             .parse::<f64>()
-            .expect(&format!("Failed to parse .{} into an f64", number));
+            .unwrap_or_else(|_| panic!("Failed to parse {} into an f64", text));
     }
 
-    /// Reads a radix number (0b, 0x, 0o)
+    /// Reads a binary (0b), octal (0o) or hexadecimal (0x) number.
     fn read_radix_number(&mut self, radix: u32) {
+        let start = self.end;
         self.step(); // 0
         self.step(); // x/b/o
 
-        let number = match radix {
-            2 => self.read_binary_number(),
-            8 => self.read_octal_number(),
-            16 => self.read_hexadecimal_number(),
-
-            _ => self.unexpected(),
-        };
-
-        // Exponent
-        if self.character == 'e' || self.character == 'E' {
-            todo!()
+        match self.character {
+            '_' => self.report(JSError::new(
+                JSErrorKind::InvalidNumericSeparator,
+                Span::new(self.end, self.current),
+            )),
+            c if c.is_digit(radix) => {}
+            c if c.is_ascii_digit() => self.report_invalid_digit(radix),
+            _ => self.report(JSError::new(
+                JSErrorKind::MissingRadixDigits,
+                Span::new(start, self.end),
+            )),
+        }
+        let number = self.read_digits(radix);
+        if self.character.is_ascii_digit() {
+            self.report_invalid_digit(radix);
         }
 
         // Means we've hit a big int literal
         // We do not attempt to convert the string into
         // a number since that could mean precision loss.
         if self.character == 'n' {
+            self.identifier = format!("{}{}", &self.input[start..start + 2], number);
             self.step();
             self.token = Token::BigIntegerLiteral;
-            self.identifier = match radix {
-                2 => format!("0b{}", number),
-                8 => format!("0o{}", number),
-                16 => format!("0x{}", number),
-                _ => self.unexpected(),
-            };
+            self.expect_end_of_number(start, true);
             return;
         }
 
-        self.number = i64::from_str_radix(&number, radix)
-            .expect(&format!("[Packet]: Failed to convert {} to an i64", number))
-            as f64;
+        self.expect_end_of_number(start, false);
         self.token = Token::NumericLiteral;
+        self.number = radix_digits_to_f64(&number, radix);
     }
 
-    fn read_binary_number(&mut self) -> String {
-        let mut num = String::new();
+    /// Reads the digits of a number in the given radix, without the numeric separators.
+    /// A separator must be placed between two digits, `1__0`, `1_` and `1_.0` are errors.
+    fn read_digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
         loop {
             match self.character {
-                '0' | '1' => num.push(self.character),
-                '_' => {}
+                c if c.is_digit(radix) => digits.push(c),
+                '_' => {
+                    let next = self.peek();
+                    if next == '_' {
+                        self.report(JSError::new(
+                            JSErrorKind::ConsecutiveNumericSeparators,
+                            Span::new(self.end, self.current + 1),
+                        ));
+                    }
+                    if !next.is_digit(radix) {
+                        let kind = match next == '.' || (radix == 10 && matches!(next, 'e' | 'E')) {
+                            true => JSErrorKind::InvalidNumericSeparator,
+                            false => JSErrorKind::TrailingNumericSeparator,
+                        };
+                        self.report(JSError::new(kind, Span::new(self.end, self.current)));
+                    }
+                }
                 _ => break,
             }
             self.step();
         }
-        return num;
+        digits
     }
 
-    fn read_octal_number(&mut self) -> String {
-        let mut num = String::new();
-        loop {
-            match self.character {
-                '0'..='8' => num.push(self.character),
-                '_' => {}
-                _ => break,
-            }
-            self.step();
-        }
-        return num;
+    /// Reports a digit that is not valid in the radix, such as the 2 in `0b12`.
+    fn report_invalid_digit(&self, radix: u32) -> ! {
+        let kind = match radix {
+            2 => JSErrorKind::InvalidBinaryDigit,
+            _ => JSErrorKind::InvalidOctalDigit,
+        };
+        self.report(JSError::new(kind, Span::new(self.end, self.current)));
     }
 
-    fn read_decimal_number(&mut self) -> String {
-        let mut num = String::new();
-        loop {
-            match self.character {
-                '0'..='9' => num.push(self.character),
-
-                '_' => {}
-                _ => break,
-            }
-            self.step();
-        }
-        return num;
+    /// Reports an error if the number is directly followed by an identifier or a
+    /// digit, as in `3in` and `1n_`. A trailing `n` after a number that is not a
+    /// big integer is reported as an invalid big integer since it is only reached
+    /// when the number can not be one, such as `1.5n` and `01n`.
+    fn expect_end_of_number(&self, start: usize, is_big_int: bool) {
+        let kind = match self.character {
+            'n' if !is_big_int => JSErrorKind::InvalidBigInt,
+            c if is_identifier_start(c) || c.is_ascii_digit() => JSErrorKind::IdentifierAfterNumber,
+            _ => return,
+        };
+        self.report(JSError::new(kind, Span::new(start, self.current)));
     }
+}
 
-    fn read_hexadecimal_number(&mut self) -> String {
-        let mut num = String::new();
-        loop {
-            match self.character {
-                '0'..='9' | 'a'..='f' | 'A'..='F' => num.push(self.character),
-                '_' => {}
-                _ => break,
-            }
-            self.step();
-        }
-        return num;
+/// Converts the digits of a binary, octal or hexadecimal number into the nearest
+/// f64. The bits are rounded once, numbers wider than 64 bits keep their first 64
+/// bits and a sticky bit for the rest so that the conversion rounds to nearest even.
+fn radix_digits_to_f64(digits: &str, radix: u32) -> f64 {
+    let bits_per_digit = radix.trailing_zeros();
+    let bits: Vec<bool> = digits
+        .chars()
+        .flat_map(|c| {
+            let digit = c.to_digit(radix).unwrap();
+            (0..bits_per_digit)
+                .rev()
+                .map(move |bit| (digit >> bit) & 1 == 1)
+        })
+        .skip_while(|bit| !bit)
+        .collect();
+
+    let width = bits.len().min(64);
+    let mut mantissa = bits[..width]
+        .iter()
+        .fold(0u64, |value, bit| (value << 1) | *bit as u64);
+    if bits[width..].iter().any(|bit| *bit) {
+        mantissa |= 1;
     }
+    mantissa as f64 * 2f64.powi((bits.len() - width) as i32)
 }
 
 /// True if `c` is considered whitespace according to the ECMAScript specification
@@ -883,11 +1198,7 @@ fn is_line_terminator(c: char) -> bool {
 fn is_identifier_start(c: char) -> bool {
     // We start by fast-checking the ASCII characters and
     // if no match is find we run the slower unicode check.
-    ('a'..='z').contains(&c)
-        || ('A'..='Z').contains(&c)
-        || c == '_'
-        || c == '$'
-        || unicode::id_start(c)
+    c.is_ascii_lowercase() || c.is_ascii_uppercase() || c == '_' || c == '$' || unicode::id_start(c)
 }
 
 /// True if `c` is considered a identifier start according to the ECMAScript specification.
@@ -896,9 +1207,9 @@ fn is_identifier_start(c: char) -> bool {
 fn is_identifier_continue(c: char) -> bool {
     // We start by fast-checking the ASCII characters and
     // if no match is find we run the slower unicode check.
-    ('a'..='z').contains(&c)
-        || ('A'..='Z').contains(&c)
-        || ('0'..='9').contains(&c)
+    c.is_ascii_lowercase()
+        || c.is_ascii_uppercase()
+        || c.is_ascii_digit()
         || c == '\u{200C}'
         || c == '\u{200D}'
         || c == '_'
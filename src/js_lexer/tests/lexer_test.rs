@@ -1,6 +1,6 @@
 use js_lexer::Lexer;
 use js_token::Token;
-use logger::LoggerImpl;
+use logger::{Logger, LoggerImpl, Message, Range};
 
 enum StringOrFloat<'a> {
     String(&'a str),
@@ -64,6 +64,8 @@ fn test_string_literal() {
     expect_string_literal("'\"'", "\"");
     expect_string_literal("\"'\"", "'");
     expect_string_literal("\"\\\"\"", "\\\"");
+    // Characters before the string take more than one byte.
+    expect_string_literal("/* – */ 'a–b'", "a–b");
 }
 
 fn expect_identifier(content: &str, expected: &str) {
@@ -80,7 +82,69 @@ fn test_identifiers() {
     expect_identifier("a_a", "a_a");
     expect_identifier("$", "$");
     expect_identifier("_$", "_$");
-    // expect_identifier("\\u0061s", "\\u0061"); // TODO
+    expect_identifier("ñ", "ñ");
+    expect_identifier("\\u0061", "a");
+    expect_identifier("\\u0061b\\u0063", "abc");
+    expect_identifier("\\u{61}", "a");
+    expect_identifier("a\\u{000031}", "a1");
+    expect_identifier("\\u{20BB7}", "\u{20BB7}");
+    expect_identifier("\\u00f1", "ñ");
+    // Escaped contextual keywords are identifiers.
+    expect_identifier("\\u0061s", "as");
+    expect_identifier("l\\u0065t", "let");
+}
+
+fn expect_escaped_keyword(content: &str, expected: &str) {
+    let logger = LoggerImpl::new();
+    let lexer = Lexer::new(content, &logger);
+    assert_eq!(lexer.token, Token::EscapedKeyword);
+    assert_eq!(lexer.identifier, expected);
+}
+
+#[test]
+fn test_escaped_keywords() {
+    expect_escaped_keyword("\\u0069f", "if");
+    expect_escaped_keyword("n\\u{65}w", "new");
+    expect_escaped_keyword("\\u0074his", "this");
+}
+
+#[test]
+fn test_has_escape() {
+    let logger = LoggerImpl::new();
+    let mut lexer = Lexer::new("\\u0061sync async", &logger);
+    assert!(lexer.has_escape);
+    lexer.next_token();
+    assert!(!lexer.has_escape);
+
+    // Let and await are reserved words in strict mode code.
+    let mut lexer = Lexer::new("a l\\u0065t \\u0061wait", &logger);
+    lexer.strict_mode = true;
+    lexer.next_token();
+    assert_eq!(lexer.token, Token::EscapedKeyword);
+    lexer.next_token();
+    assert_eq!(lexer.token, Token::EscapedKeyword);
+}
+
+#[test]
+fn test_invalid_identifier_escapes() {
+    expect_error("\\u00", "0..4: Invalid unicode escape sequence");
+    expect_error("\\x61", "0..2: Invalid unicode escape sequence");
+    expect_error("\\u{}", "0..4: Invalid unicode escape sequence");
+    expect_error("\\u{61", "0..5: Invalid unicode escape sequence");
+    expect_error("\\u{110000}", "0..10: Invalid unicode escape sequence");
+    expect_error("a\\uD800", "1..7: Invalid unicode escape sequence");
+    expect_error(
+        "\\u0031a",
+        "0..6: The escaped character is not allowed in an identifier",
+    );
+    expect_error(
+        "a\\u{1F600}",
+        "1..10: The escaped character is not allowed in an identifier",
+    );
+    expect_error(
+        "a\\u002E",
+        "1..7: The escaped character is not allowed in an identifier",
+    );
 }
 
 fn expect_regexp(content: &str, expected: &str) {
@@ -231,6 +295,164 @@ fn test_numeric_literals() {
     expect_number("0b10", 2.);
     expect_number("0o10", 8.);
     expect_number("0x10", 16.);
+    expect_number("0B10", 2.);
+    expect_number("0O10", 8.);
+    expect_number("0XfF", 255.);
+    expect_number("0xFFFF_FFFF", 4294967295.);
+    expect_number("0b1010_1010", 170.);
+    expect_number("0o7_7", 63.);
+    expect_number("1_000_000", 1000000.);
+    expect_number("0xFFFFFFFFFFFFFFFFFFFF", 1208925819614629174706175.);
+    // Wider than 128 bits, rounded once to the nearest f64.
+    expect_number(
+        "0x100000000000008000000000000000000001",
+        1.3937965749081643e42,
+    );
+    expect_number(&format!("0x{}", "F".repeat(256)), f64::INFINITY);
+    expect_number("5.", 5.);
+    expect_number("0.5", 0.5);
+    expect_number("1e3", 1000.);
+    expect_number("1E3", 1000.);
+    expect_number("1.5e+3", 1500.);
+    expect_number(".5e-3", 0.0005);
+    expect_number("5.e1", 50.);
+    expect_number("1e1_0", 1e10);
+    expect_number("1_0.0_1", 10.01);
+    expect_number("0", 0.);
+    expect_number("0.0", 0.);
+
+    // Legacy octal numbers and decimals with leading zeros in sloppy mode.
+    expect_number("010", 8.);
+    expect_number("00", 0.);
+    expect_number("0777", 511.);
+    expect_number("08", 8.);
+    expect_number("019", 19.);
+    expect_number("08.5", 8.5);
+    expect_number("09e1", 90.);
+}
+
+/// A logger that panics with the error, so that the errors of the lexer
+/// can be asserted on instead of exiting the process.
+struct PanicLogger;
+
+impl Logger for PanicLogger {
+    fn add_message(&self, _message: Message) {}
+
+    fn has_errors(&self) -> bool {
+        false
+    }
+
+    fn has_warnings(&self) -> bool {
+        false
+    }
+
+    fn flush(&self) {}
+
+    fn add_error(&self, _source: &str, range: Range, text: String) {
+        panic!("{}..{}: {}", range.start, range.end, text);
+    }
+}
+
+/// Asserts that the first token of the content is reported
+/// with the expected error, formatted as `start..end: message`.
+fn expect_error(content: &str, expected: &str) {
+    let result = std::panic::catch_unwind(|| {
+        Lexer::new(content, &PanicLogger);
+    });
+    expect_error_message(result, expected);
+}
+
+/// Asserts that the second token of the content is reported with the expected error
+/// when scanned in strict mode. The first token is scanned before strict mode is set.
+fn expect_strict_error(content: &str, expected: &str) {
+    let result = std::panic::catch_unwind(|| {
+        let mut lexer = Lexer::new(content, &PanicLogger);
+        lexer.strict_mode = true;
+        lexer.next_token();
+    });
+    expect_error_message(result, expected);
+}
+
+fn expect_error_message(result: std::thread::Result<()>, expected: &str) {
+    let error = result.expect_err("Expected the token to be reported as an error");
+    let error = error
+        .downcast_ref::<String>()
+        .expect("Expected the error to be a message");
+    assert_eq!(error, expected);
+}
+
+#[test]
+fn test_invalid_numeric_literals() {
+    expect_error(
+        "1__0",
+        "1..3: Only one underscore is allowed as numeric separator",
+    );
+    expect_error(
+        "1_",
+        "1..2: Numeric separators are not allowed at the end of numbers",
+    );
+    expect_error(
+        "0x1_",
+        "3..4: Numeric separators are not allowed at the end of numbers",
+    );
+    expect_error(
+        "1_.0",
+        "1..2: Numeric separators are only allowed between digits",
+    );
+    expect_error(
+        "1._0",
+        "2..3: Numeric separators are only allowed between digits",
+    );
+    expect_error(
+        "1_e3",
+        "1..2: Numeric separators are only allowed between digits",
+    );
+    expect_error(
+        "1e_3",
+        "2..3: Numeric separators are only allowed between digits",
+    );
+    expect_error(
+        "0_1",
+        "1..2: Numeric separators are only allowed between digits",
+    );
+    expect_error(
+        "0x_1",
+        "2..3: Numeric separators are only allowed between digits",
+    );
+    expect_error(
+        "01_0",
+        "2..3: Numeric separators are only allowed between digits",
+    );
+    expect_error("1e", "0..2: The exponent is missing its digits");
+    expect_error("1e+a", "0..3: The exponent is missing its digits");
+    expect_error("0x", "0..2: Expected digits after the number prefix");
+    expect_error("0bn", "0..2: Expected digits after the number prefix");
+    expect_error("1.5n", "0..4: Invalid BigInt literal");
+    expect_error("1e3n", "0..4: Invalid BigInt literal");
+    expect_error("01n", "0..3: Invalid BigInt literal");
+    expect_error(
+        "3in",
+        "0..2: Identifiers are not allowed directly after a number",
+    );
+    expect_error(
+        "1n_",
+        "0..3: Identifiers are not allowed directly after a number",
+    );
+    expect_error(
+        "0b1nn",
+        "0..5: Identifiers are not allowed directly after a number",
+    );
+    expect_error("0b12", "3..4: Invalid digit in binary number");
+    expect_error("0b2", "2..3: Invalid digit in binary number");
+    expect_error("0o78", "3..4: Invalid digit in octal number");
+    expect_strict_error(
+        "a 010",
+        "2..5: Legacy octal literals are not allowed in strict mode",
+    );
+    expect_strict_error(
+        "a 08",
+        "2..4: Decimals with leading zeros are not allowed in strict mode",
+    );
 }
 
 fn expect_big_int(content: &str, expected: &str) {
@@ -247,6 +469,13 @@ fn test_big_int_literal() {
     expect_big_int("0b10n", "0b10");
     expect_big_int("0o10n", "0o10");
     expect_big_int("0x10n", "0x10");
+    expect_big_int("0n", "0");
+    expect_big_int("1_000n", "1000");
+    expect_big_int("0XFF_FFn", "0XFFFF");
+    expect_big_int(
+        "123456789012345678901234567890n",
+        "123456789012345678901234567890",
+    );
 }
 
 fn expect_eof(content: &str) {
@@ -268,6 +497,24 @@ fn test_comments() {
     );
 }
 
+fn expect_newline_before(content: &str, expected: bool) {
+    let logger = LoggerImpl::new();
+    let mut lexer = Lexer::new(content, &logger);
+    assert!(!lexer.has_newline_before);
+    lexer.next_token();
+    assert_eq!(lexer.has_newline_before, expected, "{}", content);
+}
+
+#[test]
+fn test_newline_before() {
+    expect_newline_before("a b", false);
+    expect_newline_before("a /* */ b", false);
+    expect_newline_before("a\nb", true);
+    expect_newline_before("a // comment\nb", true);
+    expect_newline_before("a /*\n*/ b", true);
+    expect_newline_before("a\u{2028}b", true);
+}
+
 fn expect_no_substitution_template_literal(content: &str, expected: &str) {
     let logger = LoggerImpl::new();
     let lexer = Lexer::new(content, &logger);
@@ -322,3 +569,23 @@ fn test_template_literals() {
         ],
     )
 }
+
+#[test]
+fn test_comments_before_token() {
+    let logger = LoggerImpl::new();
+    let mut lexer = Lexer::new("/*! a */ // b\nc /* webpackChunkName: \"d\" */ e", &logger);
+    assert_eq!(lexer.token, Token::Identifier);
+    assert_eq!(lexer.comments_before_token.len(), 2);
+    assert_eq!(lexer.comments_before_token[0].text, "/*! a */");
+    assert!(lexer.comments_before_token[0].is_legal());
+    assert_eq!(lexer.comments_before_token[1].text, "// b");
+    assert!(!lexer.comments_before_token[1].is_legal());
+
+    lexer.next_token();
+    assert_eq!(lexer.identifier, "e");
+    assert_eq!(lexer.comments_before_token.len(), 1);
+    assert_eq!(
+        lexer.comments_before_token[0].webpack_chunk_name(),
+        Some("d".into())
+    );
+}
@@ -0,0 +1,93 @@
+use span::Span;
+
+/// A comment found while scanning. Comments are not tokens, they are
+/// instead collected in `Lexer::comments_before_token` for the token
+/// that follows them so that the parser can act on annotations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment<'a> {
+    /// The raw text of the comment, including the delimiters
+    pub text: &'a str,
+    pub span: Span,
+}
+
+impl<'a> Comment<'a> {
+    /// True if the comment should be kept in the output,
+    /// e.g. `/*! ... */` or comments containing `@license` or `@preserve`
+    pub fn is_legal(&self) -> bool {
+        self.text.starts_with("/*!")
+            || self.text.starts_with("//!")
+            || self.text.contains("@license")
+            || self.text.contains("@preserve")
+    }
+
+    /// True if the comment is a `/* @__PURE__ */` or `/* #__PURE__ */` annotation
+    pub fn is_pure(&self) -> bool {
+        self.text.contains("@__PURE__") || self.text.contains("#__PURE__")
+    }
+
+    /// True if the comment is a `/* #__NO_SIDE_EFFECTS__ */` annotation
+    pub fn is_no_side_effects(&self) -> bool {
+        self.text.contains("@__NO_SIDE_EFFECTS__") || self.text.contains("#__NO_SIDE_EFFECTS__")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Lexer;
+
+    #[test]
+    fn test_comments_before_token() {
+        let mut lexer = Lexer::new("/*! legal */ // line\na /* b */");
+        lexer.next().unwrap();
+        assert_eq!(lexer.comments_before_token.len(), 2);
+        assert_eq!(lexer.comments_before_token[0].text, "/*! legal */");
+        assert_eq!(lexer.comments_before_token[0].span.start, 0);
+        assert_eq!(lexer.comments_before_token[0].span.end, 12);
+        assert!(lexer.comments_before_token[0].is_legal());
+        assert_eq!(lexer.comments_before_token[1].text, "// line");
+        assert!(!lexer.comments_before_token[1].is_legal());
+
+        lexer.next().unwrap();
+        assert_eq!(lexer.comments_before_token.len(), 1);
+        assert_eq!(lexer.comments_before_token[0].text, "/* b */");
+    }
+
+    #[test]
+    fn test_legal_comments() {
+        let tests = vec![
+            ("/*! a */", true),
+            ("//! a", true),
+            ("/** @license MIT */", true),
+            ("// @preserve", true),
+            ("/* a */", false),
+            ("// a", false),
+        ];
+
+        for (input, expected) in tests {
+            let mut lexer = Lexer::new(input);
+            lexer.next().unwrap();
+            assert_eq!(lexer.comments_before_token[0].is_legal(), expected);
+        }
+    }
+
+    #[test]
+    fn test_annotations() {
+        let tests = vec![
+            ("/* @__PURE__ */", true, false),
+            ("/* #__PURE__ */", true, false),
+            ("/* #__NO_SIDE_EFFECTS__ */", false, true),
+            ("/* @__NO_SIDE_EFFECTS__ */", false, true),
+            ("/* __PURE__ */", false, false),
+        ];
+
+        for (input, pure, no_side_effects) in tests {
+            let mut lexer = Lexer::new(input);
+            lexer.next().unwrap();
+            assert_eq!(lexer.comments_before_token[0].is_pure(), pure);
+            assert_eq!(
+                lexer.comments_before_token[0].is_no_side_effects(),
+                no_side_effects
+            );
+        }
+    }
+}
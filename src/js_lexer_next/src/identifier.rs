@@ -1,3 +1,6 @@
+use js_error::{JSError, JSErrorKind};
+use span::Span;
+
 use crate::{Lexer, LexerResult, Token};
 
 /// True if `c` is considered a identifier start according to the ECMAScript specification
@@ -6,11 +9,7 @@ use crate::{Lexer, LexerResult, Token};
 pub fn is_identifier_start(c: char) -> bool {
     // We start by fast-checking the ASCII characters and
     // if no match is find we run the slower unicode check.
-    ('a'..='z').contains(&c)
-        || ('A'..='Z').contains(&c)
-        || c == '_'
-        || c == '$'
-        || unicode::id_start(c)
+    c.is_ascii_lowercase() || c.is_ascii_uppercase() || c == '_' || c == '$' || unicode::id_start(c)
 }
 
 /// True if `c` is considered a identifier start according to the ECMAScript specification.
@@ -19,9 +18,9 @@ pub fn is_identifier_start(c: char) -> bool {
 pub fn is_identifier_continue(c: char) -> bool {
     // We start by fast-checking the ASCII characters and
     // if no match is find we run the slower unicode check.
-    ('a'..='z').contains(&c)
-        || ('A'..='Z').contains(&c)
-        || ('0'..='9').contains(&c)
+    c.is_ascii_lowercase()
+        || c.is_ascii_uppercase()
+        || c.is_ascii_digit()
         || c == '\u{200C}'
         || c == '\u{200D}'
         || c == '_'
@@ -30,24 +29,105 @@ pub fn is_identifier_continue(c: char) -> bool {
 }
 
 impl<'a> Lexer<'a> {
+    /// Scans an identifier or a keyword
+    /// a, \u0061, \u{61}
     pub(crate) fn scan_identifier(&mut self) -> LexerResult<Token> {
+        // The decoded name, only used once an escape has been found.
+        let mut decoded: Option<String> = None;
         loop {
-            let character = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
+            match self.current_character() {
+                Some('\\') => {
+                    let start = self.current_position();
+                    let character = self.scan_unicode_escape()?;
+                    let name = decoded
+                        .get_or_insert_with(|| self.input[self.token_start..start].to_string());
+                    let is_valid = match name.is_empty() {
+                        true => is_identifier_start(character),
+                        false => is_identifier_continue(character),
+                    };
+                    if !is_valid {
+                        return Err(JSError::new(
+                            JSErrorKind::InvalidEscapedIdentifier,
+                            Span::new(start, self.current_position()),
+                        ));
+                    }
+                    name.push(character);
+                }
+                Some(character) if is_identifier_continue(character) => {
+                    if let Some(name) = &mut decoded {
+                        name.push(character);
+                    }
+                    self.index += 1;
+                }
+                _ => break,
+            }
+        }
 
-            if !is_identifier_continue(character) {
-                break;
+        match decoded {
+            None => {
+                let end = self.current_position();
+                let identifier = &self.input[self.token_start..end];
+                self.token_text = identifier.into();
+                Ok(Token::from_potential_keyword(identifier))
             }
+            // Escaped keywords are never keywords, escaped contextual
+            // keywords such as `\u0061sync` are plain identifiers.
+            Some(name) => {
+                let token = match Token::from_potential_keyword(&name).is_keyword() {
+                    true => Token::EscapedKeyword,
+                    false => Token::Identifier,
+                };
+                self.token_text = name.into();
+                Ok(token)
+            }
+        }
+    }
 
-            self.index += 1;
+    /// Scans a unicode escape sequence and returns the character
+    /// \u0061, \u{61}
+    fn scan_unicode_escape(&mut self) -> LexerResult<char> {
+        let start = self.current_position();
+        let error = |lexer: &Lexer| {
+            JSError::new(
+                JSErrorKind::InvalidUnicodeEscape,
+                Span::new(start, lexer.current_position()),
+            )
+        };
+
+        self.index += 1; // \
+        if self.current_character() != Some('u') {
+            return Err(error(self));
         }
+        self.index += 1;
 
-        let end = self.current_position();
-        let identifier = &self.input[self.token_start..end];
-        self.token_text = identifier;
-        Ok(Token::from_potential_keyword(identifier))
+        let digits = if self.current_character() == Some('{') {
+            self.index += 1;
+            let digits_start = self.current_position();
+            while matches!(self.current_character(), Some(c) if c.is_ascii_hexdigit()) {
+                self.index += 1;
+            }
+            let digits_end = self.current_position();
+            if self.current_character() != Some('}') || digits_start == digits_end {
+                return Err(error(self));
+            }
+            self.index += 1;
+            &self.input[digits_start..digits_end]
+        } else {
+            let digits_start = self.current_position();
+            for _ in 0..4 {
+                if !matches!(self.current_character(), Some(c) if c.is_ascii_hexdigit()) {
+                    return Err(error(self));
+                }
+                self.index += 1;
+            }
+            &self.input[digits_start..self.current_position()]
+        };
+
+        // Values above 10FFFF and lone surrogates are not characters.
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| error(self))
     }
 }
 
@@ -57,7 +137,17 @@ mod tests {
 
     #[test]
     fn test_valid_identifiers() {
-        let tests = vec![("a;", "a"), ("_a", "_a"), ("$a", "$a"), ("a_b", "a_b")];
+        let tests = vec![
+            ("a;", "a"),
+            ("_a", "_a"),
+            ("$a", "$a"),
+            ("a_b", "a_b"),
+            ("\\u0061", "a"),
+            ("\\u{61}b\\u0063", "abc"),
+            ("\\u{20BB7}", "\u{20BB7}"),
+            ("\\u0061sync", "async"),
+            ("yi\\u0065ld", "yield"),
+        ];
 
         for test in tests {
             let mut lexer = Lexer::new(test.0);
@@ -67,6 +157,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_escaped_keywords() {
+        let tests = vec![("\\u0069f", "if"), ("v\\u{61}r", "var")];
+
+        for test in tests {
+            let mut lexer = Lexer::new(test.0);
+            assert_eq!(lexer.next(), Ok(()));
+            assert_eq!(lexer.token, Token::EscapedKeyword);
+            assert_eq!(lexer.token_text, test.1);
+        }
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        let tests = vec![
+            ("\\u00", JSErrorKind::InvalidUnicodeEscape),
+            ("\\x61", JSErrorKind::InvalidUnicodeEscape),
+            ("\\u{}", JSErrorKind::InvalidUnicodeEscape),
+            ("\\u{110000}", JSErrorKind::InvalidUnicodeEscape),
+            ("a\\uDC00", JSErrorKind::InvalidUnicodeEscape),
+            ("\\u0031", JSErrorKind::InvalidEscapedIdentifier),
+            ("a\\u{1F600}", JSErrorKind::InvalidEscapedIdentifier),
+        ];
+
+        for test in tests {
+            let mut lexer = Lexer::new(test.0);
+            assert_eq!(lexer.next().unwrap_err().kind, test.1, "{}", test.0);
+        }
+    }
+
     #[test]
     fn test_valid_keywords() {
         let tests = vec![
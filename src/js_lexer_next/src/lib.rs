@@ -1,3 +1,4 @@
+mod comment;
 mod identifier;
 mod number;
 mod punctuators;
@@ -7,6 +8,9 @@ mod template;
 mod token;
 mod whitespace;
 
+use std::borrow::Cow;
+
+pub use comment::Comment;
 use identifier::is_identifier_start;
 use js_error::JSError;
 use span::Span;
@@ -30,15 +34,19 @@ pub struct Lexer<'a> {
     pub token_start: usize,
     /// The end position of the token,
     pub token_end: usize,
-    /// The string value of the token
-    pub token_text: &'a str,
+    /// The string value of the token, identifiers with escapes are decoded
+    pub token_text: Cow<'a, str>,
     /// The numeric value of the token
     pub token_number: f64,
+    /// The comments found between the previous token and the current one
+    pub comments_before_token: Vec<Comment<'a>>,
+    /// Is there a line terminator between the previous token and the current one
+    pub has_newline_before: bool,
 }
 
 impl<'a> Lexer<'a> {
     /// Creates a new lexer
-    pub fn new(input: &'a str) -> Lexer {
+    pub fn new(input: &'a str) -> Lexer<'a> {
         let characters: Vec<(usize, char)> = input.char_indices().collect();
         let last_position: usize = characters
             .last()
@@ -53,13 +61,18 @@ impl<'a> Lexer<'a> {
             token: Token::Eof,
             token_start: 0,
             token_end: 0,
-            token_text: "",
+            token_text: Cow::Borrowed(""),
             token_number: 0.,
+            comments_before_token: Vec::new(),
+            has_newline_before: false,
         }
     }
 
     /// Scans the next token and advances the lexer
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> LexerResult<()> {
+        self.comments_before_token.clear();
+        self.has_newline_before = false;
         self.skip_whitespace()?;
 
         self.token_start = self.current_position();
@@ -72,7 +85,7 @@ impl<'a> Lexer<'a> {
         };
 
         self.token = match character {
-            c if is_identifier_start(c) => self.scan_identifier()?,
+            c if is_identifier_start(c) || c == '\\' => self.scan_identifier()?,
             '0' => self.scan_zero()?,
             '1'..='9' => self.scan_decimal_number()?,
             '"' | '\'' => self.scan_string(character)?,
@@ -129,18 +142,12 @@ impl<'a> Lexer<'a> {
 
     /// Returns the current character
     fn current_character(&self) -> Option<char> {
-        match self.characters.get(self.index) {
-            Some(v) => Some(v.1),
-            None => None,
-        }
+        self.characters.get(self.index).map(|v| v.1)
     }
 
     /// Returns the next character
     fn next_character(&self) -> Option<char> {
-        match self.characters.get(self.index + 1) {
-            Some(v) => Some(v.1),
-            None => None,
-        }
+        self.characters.get(self.index + 1).map(|v| v.1)
     }
 
     /// Returns the current position in the source
@@ -164,7 +171,6 @@ impl<'a> Lexer<'a> {
 
     /// Returns the position in the source of the
     /// next character.
-    #[allow(dead_code)]
     fn next_position(&self) -> usize {
         match self.characters.get(self.index + 1) {
             Some(v) => v.0,
@@ -1,3 +1,8 @@
+use std::borrow::Cow;
+
+use js_error::{JSError, JSErrorKind};
+use span::Span;
+
 use crate::Lexer;
 use crate::Token;
 use crate::{identifier::is_identifier_start, LexerResult};
@@ -8,13 +13,8 @@ impl<'a> Lexer<'a> {
     pub(crate) fn scan_floating_point(&mut self) -> LexerResult<Token> {
         let start = self.current_position();
         self.index += 1; // .
-        loop {
-            let character = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
-
-            if matches!(character, '0'..='9') {
+        while let Some(character) = self.current_character() {
+            if character.is_ascii_digit() {
                 self.index += 1;
                 continue;
             }
@@ -25,26 +25,21 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            // Break on exponentiation
-            if matches!(character, 'e' | 'E') {
-                break;
-            }
-
-            // Floating points cannot be a big integer
-            if character == 'n' {
-                panic!("Invalid big integer")
-            }
-
-            if is_identifier_start(character) {
-                panic!("Identifiers are not allowed directly after a number")
-            }
-
             // Any other character is breaking
             break;
         }
+
+        if matches!(self.current_character(), Some('e') | Some('E')) {
+            self.scan_exponent()?;
+        }
+
+        // Floating points cannot be a big integer
+        self.expect_end_of_number(start)?;
         let end = self.current_position();
         let text = &self.input[start..end];
-        self.token_number = format!("0{}", text).parse::<f64>().unwrap();
+        self.token_number = format!("0{}", text.replace('_', ""))
+            .parse::<f64>()
+            .expect("A scanned floating point is a valid f64");
         Ok(Token::Number)
     }
 
@@ -63,12 +58,7 @@ impl<'a> Lexer<'a> {
     pub(crate) fn scan_binary_number(&mut self) -> LexerResult<Token> {
         self.index += 2; // 0b
         let start = self.current_position();
-        loop {
-            let c = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
-
+        while let Some(c) = self.current_character() {
             if matches!(c, '0' | '1') {
                 self.index += 1;
                 continue;
@@ -79,24 +69,17 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            if matches!(c, 'n') {
-                break;
-            }
-
-            if is_identifier_start(c) {
-                panic!("Identifiers are not allowed directly after a number")
-            }
-
             break;
         }
 
-        let c = self.current_character();
-        if c == Some('n') {
+        if self.current_character() == Some('n') {
             let end = self.current_position();
-            self.token_text = &self.input[start - 2..end];
+            self.token_text = Cow::Borrowed(&self.input[start - 2..end]);
             self.index += 1;
+            self.expect_end_of_number(start - 2)?;
             return Ok(Token::BigInt);
         }
+        self.expect_end_of_number(start - 2)?;
 
         let end = self.current_position();
         let text = &self.input[start..end];
@@ -109,12 +92,7 @@ impl<'a> Lexer<'a> {
     pub(crate) fn scan_octal_number(&mut self) -> LexerResult<Token> {
         self.index += 2; // 0o
         let start = self.current_position();
-        loop {
-            let c = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
-
+        while let Some(c) = self.current_character() {
             if matches!(c, '0'..='7') {
                 self.index += 1;
                 continue;
@@ -125,24 +103,17 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            if matches!(c, 'n') {
-                break;
-            }
-
-            if is_identifier_start(c) {
-                panic!("Identifiers are not allowed directly after a number")
-            }
-
             break;
         }
 
-        let c = self.current_character();
-        if c == Some('n') {
+        if self.current_character() == Some('n') {
             let end = self.current_position();
-            self.token_text = &self.input[start - 2..end];
+            self.token_text = Cow::Borrowed(&self.input[start - 2..end]);
             self.index += 1;
+            self.expect_end_of_number(start - 2)?;
             return Ok(Token::BigInt);
         }
+        self.expect_end_of_number(start - 2)?;
 
         let end = self.current_position();
         let text = &self.input[start..end];
@@ -153,14 +124,10 @@ impl<'a> Lexer<'a> {
     /// Scans a decimal number
     /// 123
     pub(crate) fn scan_decimal_number(&mut self) -> LexerResult<Token> {
-        let start = self.index;
-        loop {
-            let c = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
-
-            if matches!(c, '0'..='9') {
+        let start = self.current_position();
+        let mut is_integer = true;
+        while let Some(c) = self.current_character() {
+            if c.is_ascii_digit() {
                 self.index += 1;
                 continue;
             }
@@ -170,38 +137,43 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            if c == '.' {
+            if c == '.' && is_integer {
+                is_integer = false;
                 self.index += 1;
                 continue;
             }
 
-            if matches!(c, 'n' | 'e' | 'E') {
-                break;
-            }
-
-            if is_identifier_start(c) {
-                panic!("Identifiers are not allowed directly after a number")
-            }
-
             break;
         }
 
-        let c = self.current_character();
-
-        if matches!(c, Some('e') | Some('E')) {
-            todo!()
+        if matches!(self.current_character(), Some('e') | Some('E')) {
+            is_integer = false;
+            self.scan_exponent()?;
         }
 
-        if c == Some('n') {
-            let end = self.current_position();
-            self.token_text = &self.input[start..end];
+        let end = self.current_position();
+        let text = &self.input[start..end];
+
+        // A big integer is an integer without leading zeros, 0n is fine but 01n is not.
+        if self.current_character() == Some('n') {
+            let has_leading_zero = text.len() > 1 && text.starts_with('0');
+            if !is_integer || has_leading_zero {
+                return Err(JSError::new(
+                    JSErrorKind::InvalidBigInt,
+                    Span::new(start, self.next_position()),
+                ));
+            }
+            self.token_text = text.into();
             self.index += 1;
+            self.expect_end_of_number(start)?;
             return Ok(Token::BigInt);
         }
 
-        let end = self.current_position();
-        let text = &self.input[start..end];
-        self.token_number = text.replace('_', "").parse::<f64>().unwrap();
+        self.expect_end_of_number(start)?;
+        self.token_number = text
+            .replace('_', "")
+            .parse::<f64>()
+            .expect("A scanned decimal number is a valid f64");
         Ok(Token::Number)
     }
 
@@ -210,13 +182,8 @@ impl<'a> Lexer<'a> {
     pub(crate) fn scan_hexadecimal_number(&mut self) -> LexerResult<Token> {
         self.index += 2; // 0x
         let start = self.current_position();
-        loop {
-            let c = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
-
-            if matches!(c, '0'..='9' | 'a'..='f' | 'A'..='F') {
+        while let Some(c) = self.current_character() {
+            if c.is_ascii_hexdigit() {
                 self.index += 1;
                 continue;
             }
@@ -226,30 +193,73 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            if matches!(c, 'n') {
-                break;
-            }
-
-            if is_identifier_start(c) {
-                panic!("Identifiers are not allowed directly after a number")
-            }
-
             break;
         }
 
-        let c = self.current_character();
-        if c == Some('n') {
+        if self.current_character() == Some('n') {
             let end = self.current_position();
-            self.token_text = &self.input[start - 2..end];
+            self.token_text = Cow::Borrowed(&self.input[start - 2..end]);
             self.index += 1;
+            self.expect_end_of_number(start - 2)?;
             return Ok(Token::BigInt);
         }
+        self.expect_end_of_number(start - 2)?;
 
         let end = self.current_position();
         let text = &self.input[start..end];
         self.token_number = i64::from_str_radix(&text.replace('_', ""), 16).unwrap() as f64;
         Ok(Token::Number)
     }
+
+    /// Scans the exponent of a decimal number
+    /// e10, E+10, e-10
+    fn scan_exponent(&mut self) -> LexerResult<()> {
+        let start = self.current_position();
+        self.index += 1; // e
+        if matches!(self.current_character(), Some('+') | Some('-')) {
+            self.index += 1;
+        }
+
+        let mut has_digits = false;
+        while let Some(c) = self.current_character() {
+            if c.is_ascii_digit() {
+                has_digits = true;
+                self.index += 1;
+                continue;
+            }
+
+            if c == '_' {
+                self.index += 1;
+                continue;
+            }
+
+            break;
+        }
+
+        if !has_digits {
+            return Err(JSError::new(
+                JSErrorKind::MissingExponent,
+                Span::new(start, self.current_position()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if the number is directly followed by an identifier, as in
+    /// `3in`. A trailing `n` means the number was meant as a big integer but can not be one.
+    fn expect_end_of_number(&self, start: usize) -> LexerResult<()> {
+        match self.current_character() {
+            Some('n') => Err(JSError::new(
+                JSErrorKind::InvalidBigInt,
+                Span::new(start, self.next_position()),
+            )),
+            Some(c) if is_identifier_start(c) => Err(JSError::new(
+                JSErrorKind::IdentifierAfterNumber,
+                Span::new(start, self.current_position()),
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -258,7 +268,12 @@ mod tests {
 
     #[test]
     fn test_floating_points() {
-        let tests = vec![(".12", 0.12), (".0001", 0.0001)];
+        let tests = vec![
+            (".12", 0.12),
+            (".0001", 0.0001),
+            (".5e3", 500.),
+            (".5E-3", 0.0005),
+        ];
 
         for test in tests {
             let mut lexer = Lexer::new(test.0);
@@ -279,6 +294,11 @@ mod tests {
             ("0b10", 2.),
             ("0o10", 8.),
             ("0x10", 16.),
+            ("1_000", 1000.),
+            ("1e3", 1000.),
+            ("1.5e+3", 1500.),
+            ("15e-1", 1.5),
+            ("0.5e1_0", 5e9),
         ];
 
         for test in tests {
@@ -297,6 +317,13 @@ mod tests {
             ("0b11n", "0b11"),
             ("0o11n", "0o11"),
             ("0x11n", "0x11"),
+            ("0n", "0"),
+            ("1_000n", "1_000"),
+            ("0xFF_FFn", "0xFF_FF"),
+            (
+                "123456789012345678901234567890n",
+                "123456789012345678901234567890",
+            ),
         ];
 
         for test in tests {
@@ -306,4 +333,24 @@ mod tests {
             assert_eq!(test.1, lexer.token_text);
         }
     }
+
+    #[test]
+    fn test_invalid_numbers() {
+        let tests = vec![
+            ("1.5n", JSErrorKind::InvalidBigInt),
+            (".5n", JSErrorKind::InvalidBigInt),
+            ("1e3n", JSErrorKind::InvalidBigInt),
+            ("01n", JSErrorKind::InvalidBigInt),
+            ("1nn", JSErrorKind::InvalidBigInt),
+            ("3in", JSErrorKind::IdentifierAfterNumber),
+            ("0x1g", JSErrorKind::IdentifierAfterNumber),
+            ("1e", JSErrorKind::MissingExponent),
+            ("1e+", JSErrorKind::MissingExponent),
+        ];
+
+        for test in tests {
+            let mut lexer = Lexer::new(test.0);
+            assert_eq!(lexer.next().unwrap_err().kind, test.1, "{}", test.0);
+        }
+    }
 }
@@ -1,6 +1,9 @@
-use crate::{identifier::is_identifier_continue, Token};
+use std::borrow::Cow;
+
+use crate::{identifier::is_identifier_continue, whitespace::is_line_terminator, Token};
 use crate::{Lexer, LexerResult};
 use js_error::{JSError, JSErrorKind};
+use js_regexp::parse_regexp;
 use span::Span;
 
 impl<'a> Lexer<'a> {
@@ -10,21 +13,35 @@ impl<'a> Lexer<'a> {
         self.token_start = self.previous_position(); // Include the leading slash
         self.scan_regexp_pattern()?;
         self.index += 1; // Skip over the ending slash
-        self.scan_regexp_flags()?;
+        while self.current_character().is_some_and(is_identifier_continue) {
+            self.index += 1;
+        }
         self.token_end = self.current_position();
 
-        self.token_text = &self.input[self.token_start..self.token_end];
+        // The pattern and the flags are validated once the end of the regexp is known.
+        let text = &self.input[self.token_start..self.token_end];
+        if let Err(error) = parse_regexp(text) {
+            let span = Span::new(
+                self.token_start + error.span.start,
+                self.token_start + error.span.end,
+            );
+            return Err(JSError::new(error.kind, span));
+        }
+
+        self.token_text = Cow::Borrowed(text);
         self.token = Token::Regexp;
         Ok(())
     }
 
+    /// Finds the slash that ends the pattern, a slash in a class or after
+    /// a backslash is a part of the pattern.
     fn scan_regexp_pattern(&mut self) -> LexerResult<()> {
         let start = self.current_position();
-        // Scan pattern
+        let mut in_class = false;
         loop {
             let c = match self.current_character() {
-                Some(c) => c,
-                None => {
+                Some(c) if !is_line_terminator(c) => c,
+                _ => {
                     return Err(JSError::new(
                         JSErrorKind::UnterminatedRegexp,
                         Span::new(start, self.current_position()),
@@ -32,46 +49,22 @@ impl<'a> Lexer<'a> {
                 }
             };
 
-            if c == '/' {
-                break;
+            match c {
+                '/' if !in_class => break,
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '\\' if self
+                    .next_character()
+                    .is_some_and(|c| !is_line_terminator(c)) =>
+                {
+                    self.index += 1;
+                }
+                _ => {}
             }
-
             self.index += 1;
         }
         Ok(())
     }
-
-    fn scan_regexp_flags(&mut self) -> LexerResult<()> {
-        let start = self.current_position();
-        loop {
-            let c = match self.current_character() {
-                Some(c) => c,
-                None => break,
-            };
-
-            // If the character is not an identifier, break.
-            if !is_identifier_continue(c) {
-                break;
-            }
-
-            if matches!(c, 'i' | 'g' | 'm' | 's' | 'u' | 'y') {
-                self.index += 1;
-                continue;
-            }
-
-            return Err(JSError::new(
-                JSErrorKind::InvalidRegexpFlag,
-                Span::new(start, self.current_position()),
-            ));
-        }
-
-        let end = self.current_position();
-        if start == end {
-            return Ok(());
-        }
-
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -80,7 +73,12 @@ mod tests {
 
     #[test]
     fn test_valid_regexp() {
-        let tests = vec![("/abc/", "/abc/"), ("/abc/g", "/abc/g")];
+        let tests = vec![
+            ("/abc/", "/abc/"),
+            ("/abc/g", "/abc/g"),
+            ("/[/]/", "/[/]/"),
+            ("/a\\/b/", "/a\\/b/"),
+        ];
 
         for test in tests {
             let mut lexer = Lexer::new(test.0);
@@ -96,6 +94,10 @@ mod tests {
         let tests = vec![
             ("/abc", JSErrorKind::UnterminatedRegexp),
             ("/abc/bc", JSErrorKind::InvalidRegexpFlag),
+            ("/abc/gg", JSErrorKind::DuplicateRegexpFlag),
+            ("/a\nb/", JSErrorKind::UnterminatedRegexp),
+            ("/(?<a>b)(?<a>c)/", JSErrorKind::DuplicateRegexpGroupName),
+            ("/a{2,1}/", JSErrorKind::RegexpQuantifierOutOfOrder),
         ];
 
         for test in tests {
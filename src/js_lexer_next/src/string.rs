@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use js_error::{JSError, JSErrorKind};
 use span::Span;
 
@@ -46,7 +48,7 @@ impl<'a> Lexer<'a> {
             self.index += 1;
         }
 
-        self.token_text = &self.input[start..end];
+        self.token_text = Cow::Borrowed(&self.input[start..end]);
         Ok(Token::String)
     }
 }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use js_error::{JSError, JSErrorKind};
 use span::Span;
 
@@ -23,7 +25,7 @@ impl<'a> Lexer<'a> {
             if c == '`' {
                 let end = self.current_position();
                 self.index += 1;
-                self.token_text = &self.input[start..end];
+                self.token_text = Cow::Borrowed(&self.input[start..end]);
                 return Ok(Token::String);
             }
 
@@ -32,7 +34,7 @@ impl<'a> Lexer<'a> {
                 if self.current_character() == Some('{') {
                     let end = self.previous_position();
                     self.index += 1;
-                    self.token_text = &self.input[start..end];
+                    self.token_text = Cow::Borrowed(&self.input[start..end]);
                     return Ok(Token::TemplateHead);
                 } else {
                     continue;
@@ -62,7 +64,7 @@ impl<'a> Lexer<'a> {
             if c == '`' {
                 let end = self.current_position();
                 self.index += 1;
-                self.token_text = &self.input[self.token_start..end];
+                self.token_text = Cow::Borrowed(&self.input[self.token_start..end]);
                 self.token = Token::TemplateTail;
                 self.token_end = end;
                 return Ok(());
@@ -73,7 +75,7 @@ impl<'a> Lexer<'a> {
                 if self.current_character() == Some('{') {
                     let end = self.previous_position();
                     self.index += 1;
-                    self.token_text = &self.input[self.token_start..end];
+                    self.token_text = Cow::Borrowed(&self.input[self.token_start..end]);
                     self.token = Token::TemplateMiddle;
                     self.token_end = end;
                     return Ok(());
@@ -25,6 +25,9 @@ pub enum Token {
 
     // Identifiers
     Identifier,
+    /// A keyword written with unicode escapes, such as `\u0069f`. It is
+    /// not a keyword and is only allowed where any name is, `a.\u0069f`.
+    EscapedKeyword,
 
     // Tokens
     /// "&"
@@ -269,92 +272,92 @@ impl Token {
 
     /// Returns true if the token might indicate the start of a property key
     pub fn is_property_key(&self) -> bool {
-        match self {
+        matches!(
+            self,
             Token::OpenBracket
-            | Token::Identifier
-            | Token::String
-            | Token::Number
-            | Token::False
-            | Token::True => true,
-            _ => false,
-        }
+                | Token::Identifier
+                | Token::String
+                | Token::Number
+                | Token::False
+                | Token::True
+        )
     }
 
     /// Does the token indicate the potential start of pattern
     pub fn is_pattern_start(&self) -> bool {
-        match self {
-            Token::OpenBracket | Token::OpenBrace => true,
-            _ => false,
-        }
+        matches!(self, Token::OpenBracket | Token::OpenBrace)
     }
 
     pub fn is_identifier(&self) -> bool {
-        match self {
+        matches!(
+            self,
             Token::Identifier
-            | Token::As
-            | Token::Async
-            | Token::Await
-            | Token::Constructor
-            | Token::Get
-            | Token::Set
-            | Token::From
-            | Token::Of => true,
-            _ => false,
-        }
+                | Token::As
+                | Token::Async
+                | Token::Await
+                | Token::Constructor
+                | Token::Get
+                | Token::Set
+                | Token::From
+                | Token::Of
+        )
     }
 
     pub fn is_keyword(&self) -> bool {
-        match self {
+        matches!(
+            self,
             Token::Break
-            | Token::Case
-            | Token::Catch
-            | Token::Class
-            | Token::Continue
-            | Token::Debugger
-            | Token::Default
-            | Token::Delete
-            | Token::Do
-            | Token::Else
-            | Token::Export
-            | Token::Extends
-            | Token::False
-            | Token::Finally
-            | Token::For
-            | Token::Function
-            | Token::If
-            | Token::Import
-            | Token::In
-            | Token::Instanceof
-            | Token::New
-            | Token::Null
-            | Token::Return
-            | Token::Super
-            | Token::Switch
-            | Token::This
-            | Token::Throw
-            | Token::True
-            | Token::Try
-            | Token::Typeof
-            | Token::Void
-            | Token::While
-            | Token::With => true,
-            _ => false,
-        }
+                | Token::Case
+                | Token::Catch
+                | Token::Class
+                | Token::Const
+                | Token::Continue
+                | Token::Debugger
+                | Token::Default
+                | Token::Delete
+                | Token::Do
+                | Token::Else
+                | Token::Enum
+                | Token::Export
+                | Token::Extends
+                | Token::False
+                | Token::Finally
+                | Token::For
+                | Token::Function
+                | Token::If
+                | Token::Import
+                | Token::In
+                | Token::Instanceof
+                | Token::New
+                | Token::Null
+                | Token::Return
+                | Token::Super
+                | Token::Switch
+                | Token::This
+                | Token::Throw
+                | Token::True
+                | Token::Try
+                | Token::Typeof
+                | Token::Var
+                | Token::Void
+                | Token::While
+                | Token::With
+        )
     }
 
     pub fn is_future_reserved(&self) -> bool {
-        match self {
+        matches!(
+            self,
             Token::Implements
-            | Token::Interface
-            | Token::Package
-            | Token::Private
-            | Token::Protected
-            | Token::Public
-            | Token::Static
-            | Token::Yield
-            | Token::Let => true,
-            _ => false,
-        }
+                | Token::Interface
+                | Token::Package
+                | Token::Private
+                | Token::Protected
+                | Token::Public
+                | Token::Static
+                | Token::Yield
+                | Token::Let
+        )
     }
 
     pub fn is_identifier_or_pattern(&self) -> bool {
@@ -1,7 +1,7 @@
 use js_error::{JSError, JSErrorKind};
 use span::Span;
 
-use crate::{Lexer, LexerResult};
+use crate::{Comment, Lexer, LexerResult};
 
 /// True if `c` is considered whitespace according to the ECMAScript specification
 ///
@@ -56,6 +56,7 @@ impl<'a> Lexer<'a> {
                 }
 
                 c if is_line_terminator(c) => {
+                    self.has_newline_before = true;
                     self.index += 1;
                 }
 
@@ -78,6 +79,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn skip_single_line_comment(&mut self) {
+        let start = self.current_position();
         self.index += 2;
 
         while let Some(character) = self.current_character() {
@@ -87,6 +89,8 @@ impl<'a> Lexer<'a> {
 
             self.index += 1;
         }
+
+        self.add_comment(start);
     }
 
     fn skip_block_comment(&mut self) -> LexerResult<()> {
@@ -104,18 +108,29 @@ impl<'a> Lexer<'a> {
                 }
             };
 
-            if c == '*' {
-                if self.next_character() == Some('/') {
-                    self.index += 2;
-                    break;
-                }
+            if c == '*' && self.next_character() == Some('/') {
+                self.index += 2;
+                break;
+            }
+
+            if is_line_terminator(c) {
+                self.has_newline_before = true;
             }
 
             self.index += 1;
         }
 
+        self.add_comment(start);
         Ok(())
     }
+
+    fn add_comment(&mut self, start: usize) {
+        let end = self.current_position();
+        self.comments_before_token.push(Comment {
+            text: &self.input[start..end],
+            span: Span::new(start, end),
+        });
+    }
 }
 
 #[cfg(test)]
@@ -185,6 +200,26 @@ mod tests {
         assert_eq!(lexer.token_text, "identifier");
     }
 
+    #[test]
+    fn test_newline_before() {
+        let tests = vec![
+            ("a b", false),
+            ("a /* */ b", false),
+            ("a\nb", true),
+            ("a // comment\nb", true),
+            ("a /*\n*/ b", true),
+            ("a\u{2028}b", true),
+        ];
+
+        for test in tests {
+            let mut lexer = Lexer::new(test.0);
+            assert_eq!(lexer.next(), Ok(()));
+            assert!(!lexer.has_newline_before);
+            assert_eq!(lexer.next(), Ok(()));
+            assert_eq!(lexer.has_newline_before, test.1, "{}", test.0);
+        }
+    }
+
     #[test]
     fn test_invalid_comments() {
         let tests = vec![
@@ -0,0 +1,191 @@
+/// This file contains the lowering of async functions and `for await` loops.
+///
+/// The body of an async function is moved into a generator function that is
+/// run by the `__async` helper, every `await` becomes a `yield` which gives
+/// the promise to the helper and resumes with its result:
+///
+/// ```js
+/// function a(b) {
+///     return __async(this, null, function* () { return yield b; });
+/// }
+/// ```
+///
+/// `arguments` is passed to the helper when the body uses it. For targets
+/// without generators the generator function is lowered further like any other.
+use js_ast::visit::{walk_expression, walk_statement, VisitorMut};
+use js_ast::*;
+
+use crate::generator::uses_arguments;
+use crate::helpers::{ASYNC, FOR_AWAIT};
+use crate::iterators::{add_labels, close_on_exit, for_of_loop, loop_body, split_labels};
+use crate::lower::{assign, call, declarator, identifier, logical, member, void_zero, Lowerer};
+
+impl Lowerer {
+    /// Replaces the body of an async function with a call to `__async`.
+    pub(crate) fn lower_async_body(&mut self, body: &mut BlockStatement) {
+        let mut statements = std::mem::take(&mut body.statements);
+        let mut lowering = AwaitToYield { lowerer: self };
+        lowering.visit_statements(&mut statements);
+
+        // Directives apply to the generator function too, so they stay in the outer function.
+        let directives = split_directives(&mut statements);
+        let arguments = match uses_arguments(&mut statements) {
+            true => identifier("arguments"),
+            false => Expression::NullLiteral(NullLiteral {}),
+        };
+        let generator = Expression::Function(FunctionExpression {
+            identifier: None,
+            parameters: Vec::new(),
+            body: BlockStatement { statements },
+            generator: true,
+            is_async: false,
+        });
+        let helper = self.use_helper(ASYNC);
+        body.statements = directives;
+        body.statements
+            .push(Statement::ReturnStatement(ReturnStatement {
+                expression: Some(call(
+                    helper,
+                    vec![Expression::This(ThisExpression {}), arguments, generator],
+                )),
+            }));
+    }
+
+    /// Lowers a `for await` loop, which may be labeled, to a `for` loop that
+    /// calls the methods of the async iterator itself:
+    ///
+    /// ```js
+    /// try {
+    ///     for (var _a = __forAwait(b), _b, _c, _d; _b = !(_c = await _a.next()).done; _b = false) {
+    ///         const a = _c.value;
+    ///     }
+    /// } catch (_c) {
+    ///     _d = [_c];
+    /// } finally {
+    ///     try {
+    ///         _b && (_c = _a.return) && await _c.call(_a);
+    ///     } finally {
+    ///         if (_d) throw _d[0];
+    ///     }
+    /// }
+    /// ```
+    pub(crate) fn lower_for_await(&mut self, statement: &mut Statement) {
+        let (labels, f) = split_labels(statement);
+        let iterator = self.temp();
+        let more = self.temp();
+        let result = self.temp();
+        let error = self.temp();
+
+        let next = await_expression(call(member(identifier(&iterator), "next"), Vec::new()));
+        let lowered = Statement::ForStatement(ForStatement {
+            init: Some(Box::new(Statement::VariableDeclaration(
+                VariableDeclaration {
+                    kind: VariableDeclarationKind::Var,
+                    declarations: vec![
+                        declarator(
+                            &iterator,
+                            Some(call(self.use_helper(FOR_AWAIT), vec![f.right])),
+                        ),
+                        declarator(&more, None),
+                        declarator(&result, None),
+                        declarator(&error, None),
+                    ],
+                },
+            ))),
+            test: Some(assign(&more, not(member(assign(&result, next), "done")))),
+            update: Some(assign(
+                &more,
+                Expression::BooleanLiteral(BooleanLiteral { value: false }),
+            )),
+            body: Box::new(loop_body(
+                *f.left,
+                member(identifier(&result), "value"),
+                *f.body,
+            )),
+        });
+
+        let close = logical(
+            logical(
+                identifier(&more),
+                LogicalExpressionOperator::And,
+                assign(&result, member(identifier(&iterator), "return")),
+            ),
+            LogicalExpressionOperator::And,
+            await_expression(call(
+                member(identifier(&result), "call"),
+                vec![identifier(&iterator)],
+            )),
+        );
+        let lowered = add_labels(labels, lowered);
+        *statement = close_on_exit(lowered, expression_statement(close), &result, &error);
+    }
+}
+
+/// Returns whether the statement is a `for await` loop, which may be labeled.
+pub(crate) fn is_for_await(statement: &Statement) -> bool {
+    for_of_loop(statement).is_some_and(|f| f.is_await)
+}
+
+/// Removes the directives at the start of a function body and returns them.
+pub(crate) fn split_directives(statements: &mut Vec<Statement>) -> Vec<Statement> {
+    let count = statements
+        .iter()
+        .take_while(|statement| {
+            matches!(
+                statement,
+                Statement::Expression(ExpressionStatement {
+                    expression: Expression::StringLiteral(_),
+                })
+            )
+        })
+        .count();
+    let rest = statements.split_off(count);
+    std::mem::replace(statements, rest)
+}
+
+/// Replaces `await` with `yield` in the body of an async function and lowers
+/// its `for await` loops. Nested functions are lowered on their own.
+struct AwaitToYield<'a> {
+    lowerer: &'a mut Lowerer,
+}
+
+impl VisitorMut for AwaitToYield<'_> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if is_for_await(statement) {
+            self.lowerer.lower_for_await(statement);
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+        if let Expression::Await(a) = expression {
+            let argument = std::mem::replace(&mut *a.argument, void_zero());
+            *expression = Expression::Yield(YieldExpression {
+                argument: Some(Box::new(argument)),
+                delegate: false,
+            });
+        }
+    }
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+
+    fn visit_arrow_function(&mut self, _: &mut ArrowFunctionExpression) {}
+}
+
+fn await_expression(argument: Expression) -> Expression {
+    Expression::Await(AwaitExpression {
+        argument: Box::new(argument),
+    })
+}
+
+fn not(argument: Expression) -> Expression {
+    Expression::Unary(UnaryExpression {
+        operator: UnaryExpressionOperator::LogicalNot,
+        argument: Box::new(argument),
+    })
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(ExpressionStatement { expression })
+}
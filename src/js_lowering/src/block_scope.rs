@@ -0,0 +1,1075 @@
+/// This file contains the lowering of `let`, `const` and `class` declarations
+/// to `var`, it runs before the rest of the lowering.
+///
+/// Variables declared with `var` are hoisted to the top of the function, so a
+/// block scoped declaration whose name is also used outside of its block is
+/// renamed first: `let a = 1; { let a = 2; }` becomes `var a = 1; { var a2 = 2; }`.
+///
+/// Loops create a new binding for every iteration, which is visible to the
+/// functions created in the loop. When a loop variable, or a block scoped
+/// declaration in the body, is captured by a function the body is moved into
+/// a function that is called for every iteration:
+///
+/// ```js
+/// var _loop = (i) => { a.push(() => i); };
+/// for (var i = 0; i < 3; i++) _loop(i);
+/// ```
+///
+/// The arrow function is lowered by the rest of the lowering like any other.
+use std::collections::{HashMap, HashSet};
+
+use js_ast::scope::{binding_names, function_scope_names, lexical_names, var_names};
+use js_ast::visit::{
+    walk_binding, walk_block, walk_expression, walk_function, walk_parameters, walk_statement,
+    walk_statements, VisitorMut,
+};
+use js_ast::*;
+
+use crate::generator::contains_yield;
+use crate::lower::{assign, call, declarator, identifier, member, string, void_zero, Lowerer};
+
+impl Lowerer {
+    pub(crate) fn lower_block_scoping(&mut self, program: &mut AST) {
+        let mut scoping = BlockScoping {
+            lowerer: self,
+            scopes: Vec::new(),
+            loop_depth: 0,
+            label: None,
+            pending: Vec::new(),
+        };
+        scoping.visit_program(program);
+    }
+}
+
+struct BlockScoping<'a> {
+    lowerer: &'a mut Lowerer,
+    /// The names declared by the scopes around the current node, true for constants.
+    scopes: Vec<HashMap<String, bool>>,
+    /// The number of loops around the current node in the current function.
+    loop_depth: usize,
+    /// The label of the loop that is visited next.
+    label: Option<String>,
+    /// Declarations created for a loop, they are added before the
+    /// statement containing the loop.
+    pending: Vec<Statement>,
+}
+
+impl<'a> BlockScoping<'a> {
+    fn push_scope(&mut self, names: Vec<String>, constants: Vec<String>) {
+        let mut scope: HashMap<String, bool> =
+            names.into_iter().map(|name| (name, false)).collect();
+        for name in constants {
+            scope.insert(name, true);
+        }
+        self.scopes.push(scope);
+    }
+
+    fn check_assignment(&mut self, name: &str) {
+        let is_constant = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+            .unwrap_or(false);
+        if is_constant {
+            self.lowerer.assignment_to_constant(name);
+        }
+    }
+
+    /// Renames the block scoped declarations of a function whose names are used
+    /// outside of their block. Nested functions are renamed when they are visited.
+    fn rename_block_scoped(
+        &mut self,
+        parameters: &mut [ParameterKind],
+        statements: &mut [Statement],
+    ) {
+        let mut counter = NameCounter::default();
+        walk_parameters(&mut counter, parameters);
+        walk_statements(&mut counter, statements);
+
+        let mut renamer = BlockRenamer {
+            lowerer: self.lowerer,
+            totals: counter.counts,
+        };
+        walk_statements(&mut renamer, statements);
+    }
+
+    fn lower_declaration(&mut self, declaration: &mut VariableDeclaration) {
+        if declaration.kind == VariableDeclarationKind::Var {
+            return;
+        }
+        declaration.kind = VariableDeclarationKind::Var;
+        // A variable is undefined at the start of every iteration.
+        if self.loop_depth > 0 {
+            for declarator in declaration.declarations.iter_mut() {
+                if declarator.initializer.is_none() {
+                    declarator.initializer = Some(void_zero());
+                }
+            }
+        }
+    }
+
+    fn visit_loop(&mut self, statement: &mut Statement) {
+        let label = self.label.take();
+        let mut head = Vec::new();
+        let mut constants = Vec::new();
+        let left = match statement {
+            Statement::ForStatement(f) => f.init.as_deref_mut(),
+            Statement::ForInStatement(f) => Some(&mut *f.left),
+            Statement::ForOfStatement(f) => Some(&mut *f.left),
+            _ => None,
+        };
+        match left {
+            Some(Statement::VariableDeclaration(v)) if v.kind != VariableDeclarationKind::Var => {
+                for declarator in &v.declarations {
+                    binding_names(&declarator.binding, &mut head);
+                }
+                if v.kind == VariableDeclarationKind::Const {
+                    constants = head.clone();
+                }
+                v.kind = VariableDeclarationKind::Var;
+            }
+            Some(Statement::Expression(ExpressionStatement {
+                expression: Expression::Identifier(i),
+            })) => {
+                let name = i.name.clone();
+                self.check_assignment(&name);
+            }
+            _ => {}
+        }
+
+        let body = loop_body(statement);
+        let mut names = head.clone();
+        let mut collector = BodyNames { names: &mut names };
+        collector.visit_statement(body);
+
+        let mut finder = CaptureFinder {
+            names: &names,
+            depth: 0,
+            found: false,
+        };
+        finder.visit_statement(statement);
+        let wrap = finder.found;
+        if wrap {
+            self.hoist_vars(loop_body(statement));
+        }
+
+        let pending = self.pending.len();
+        self.push_scope(head.clone(), constants);
+        self.loop_depth += 1;
+        walk_statement(self, statement);
+        self.loop_depth -= 1;
+        self.scopes.pop();
+
+        // The functions created for nested loops stay in the body of this loop.
+        if self.pending.len() > pending {
+            let body = loop_body(statement);
+            let mut statements = self.pending.split_off(pending);
+            statements.push(std::mem::replace(body, empty()));
+            *body = Statement::BlockStatement(BlockStatement { statements });
+        }
+
+        if wrap {
+            self.wrap_loop(statement, head, label);
+        }
+    }
+
+    /// Declares the `var` variables of the body of a loop before the loop, since
+    /// the body is moved into a function they would otherwise be local to it.
+    fn hoist_vars(&mut self, body: &mut Statement) {
+        let mut names = Vec::new();
+        var_names(std::slice::from_ref(body), &mut names);
+        if names.is_empty() {
+            return;
+        }
+
+        let mut hoister = VarHoister;
+        hoister.visit_statement(body);
+        let mut seen = HashSet::new();
+        names.retain(|name| seen.insert(name.clone()));
+        self.pending
+            .push(Statement::VariableDeclaration(VariableDeclaration {
+                kind: VariableDeclarationKind::Var,
+                declarations: names.iter().map(|name| declarator(name, None)).collect(),
+            }));
+    }
+
+    /// Moves the body of the loop into a function called for every iteration,
+    /// the loop variables are passed to it.
+    fn wrap_loop(&mut self, statement: &mut Statement, head: Vec<String>, label: Option<String>) {
+        if let Statement::ForStatement(f) = statement {
+            let mut assignments = HeadAssignments {
+                names: &head,
+                found: false,
+            };
+            assignments.visit_statement(&mut f.body);
+            if assignments.found {
+                self.lowerer
+                    .unsupported("assignments to loop variables captured by functions");
+            }
+        }
+
+        let body = loop_body(statement);
+        let mut statements = match std::mem::replace(body, empty()) {
+            Statement::BlockStatement(b) => b.statements,
+            statement => vec![statement],
+        };
+        let mut jumps = Jumps {
+            label,
+            loops: 0,
+            breakables: 0,
+            labels: Vec::new(),
+            breaks: false,
+            returns: false,
+            outside: false,
+        };
+        jumps.visit_statements(&mut statements);
+        if jumps.outside {
+            self.lowerer
+                .unsupported("jumps to labels outside of loops capturing variables");
+        }
+        if statements.iter_mut().any(contains_yield) {
+            self.lowerer
+                .unsupported("await and yield in loops capturing variables");
+        }
+
+        let name = self.lowerer.generate_name("_loop");
+        let parameters = head
+            .iter()
+            .map(|name| {
+                ParameterKind::Parameter(Parameter {
+                    binding: Binding::Identifier(Identifier { name: name.clone() }),
+                    initializer: None,
+                })
+            })
+            .collect();
+        let function = Expression::ArrowFunction(ArrowFunctionExpression {
+            parameters,
+            body: ArrowFunctionExpressionBody::BlockStatement(BlockStatement { statements }),
+            is_async: false,
+        });
+        self.pending
+            .push(Statement::VariableDeclaration(VariableDeclaration {
+                kind: VariableDeclarationKind::Var,
+                declarations: vec![declarator(&name, Some(function))],
+            }));
+
+        let call = call(
+            identifier(&name),
+            head.iter().map(|name| identifier(name)).collect(),
+        );
+        let statements = if jumps.breaks || jumps.returns {
+            // var _ret = _loop(i); if (_ret === "break") break;
+            // if (typeof _ret === "object") return _ret.v;
+            let result = self.lowerer.generate_name("_ret");
+            let mut statements = vec![Statement::VariableDeclaration(VariableDeclaration {
+                kind: VariableDeclarationKind::Var,
+                declarations: vec![declarator(&result, Some(call))],
+            })];
+            if jumps.breaks {
+                statements.push(if_statement(
+                    strict_equals(identifier(&result), string("break")),
+                    Statement::BreakStatement(BreakStatement { label: None }),
+                ));
+            }
+            if jumps.returns {
+                let type_of = Expression::Unary(UnaryExpression {
+                    operator: UnaryExpressionOperator::Typeof,
+                    argument: Box::new(identifier(&result)),
+                });
+                statements.push(if_statement(
+                    strict_equals(type_of, string("object")),
+                    Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(member(identifier(&result), "v")),
+                    }),
+                ));
+            }
+            statements
+        } else {
+            vec![Statement::Expression(ExpressionStatement {
+                expression: call,
+            })]
+        };
+        *loop_body(statement) = Statement::BlockStatement(BlockStatement { statements });
+    }
+}
+
+impl<'a> VisitorMut for BlockScoping<'a> {
+    fn visit_program(&mut self, program: &mut AST) {
+        self.rename_block_scoped(&mut [], &mut program.statements);
+        let mut names = Vec::new();
+        lexical_names(&program.statements, &mut names);
+        var_names(&program.statements, &mut names);
+        self.push_scope(names, constant_names(&program.statements));
+        self.visit_statements(&mut program.statements);
+        self.scopes.pop();
+    }
+
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        let old = std::mem::take(statements);
+        for mut statement in old {
+            let pending = self.pending.len();
+            self.visit_statement(&mut statement);
+            statements.extend(self.pending.drain(pending..));
+            statements.push(statement);
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::BlockStatement(b) => {
+                let mut names = Vec::new();
+                lexical_names(&b.statements, &mut names);
+                self.push_scope(names, constant_names(&b.statements));
+                walk_statement(self, statement);
+                self.scopes.pop();
+            }
+            Statement::VariableDeclaration(v) => {
+                self.lower_declaration(v);
+                walk_statement(self, statement);
+            }
+            Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration: ExportNamedDeclarationKind::VariableDeclaration(v),
+            }) => {
+                v.kind = VariableDeclarationKind::Var;
+                walk_statement(self, statement);
+            }
+            Statement::LabeledStatement(l) => {
+                if is_loop(&l.body) {
+                    self.label = Some(l.identifier.name.clone());
+                }
+                walk_statement(self, statement);
+            }
+            Statement::ForStatement(_)
+            | Statement::ForInStatement(_)
+            | Statement::ForOfStatement(_)
+            | Statement::WhileStatement(_)
+            | Statement::DoWhileStatement(_) => self.visit_loop(statement),
+            Statement::SwitchStatement(s) => {
+                self.visit_expression(&mut s.discriminant);
+                let mut names = Vec::new();
+                let mut constants = Vec::new();
+                for case in &s.cases {
+                    for consequent in &case.consequent {
+                        let consequent = std::slice::from_ref(&**consequent);
+                        lexical_names(consequent, &mut names);
+                        constants.extend(constant_names(consequent));
+                    }
+                }
+                self.push_scope(names, constants);
+                for case in s.cases.iter_mut() {
+                    if let Some(test) = &mut case.test {
+                        self.visit_expression(test);
+                    }
+                    let old = std::mem::take(&mut case.consequent);
+                    for mut consequent in old {
+                        let pending = self.pending.len();
+                        self.visit_statement(&mut consequent);
+                        case.consequent
+                            .extend(self.pending.drain(pending..).map(Box::new));
+                        case.consequent.push(consequent);
+                    }
+                }
+                self.scopes.pop();
+            }
+            Statement::TryStatement(t) => {
+                for block in std::iter::once(&mut t.block).chain(t.finalizer.as_mut()) {
+                    let mut names = Vec::new();
+                    lexical_names(&block.statements, &mut names);
+                    self.push_scope(names, constant_names(&block.statements));
+                    walk_block(self, block);
+                    self.scopes.pop();
+                }
+                if let Some(handler) = &mut t.handler {
+                    let mut names = Vec::new();
+                    binding_names(&handler.param, &mut names);
+                    lexical_names(&handler.body.statements, &mut names);
+                    self.push_scope(names, constant_names(&handler.body.statements));
+                    walk_block(self, &mut handler.body);
+                    self.scopes.pop();
+                }
+            }
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_function(&mut self, parameters: &mut Vec<ParameterKind>, body: &mut BlockStatement) {
+        self.rename_block_scoped(parameters, &mut body.statements);
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let names = function_scope_names(parameters, &body.statements);
+        self.push_scope(names, constant_names(&body.statements));
+        walk_function(self, parameters, body);
+        self.scopes.pop();
+        self.loop_depth = loop_depth;
+    }
+
+    fn visit_arrow_function(&mut self, arrow: &mut ArrowFunctionExpression) {
+        match &mut arrow.body {
+            ArrowFunctionExpressionBody::BlockStatement(body) => {
+                self.visit_function(&mut arrow.parameters, body)
+            }
+            ArrowFunctionExpressionBody::Expression(body) => {
+                let names = function_scope_names(&arrow.parameters, &[]);
+                self.push_scope(names, Vec::new());
+                walk_parameters(self, &mut arrow.parameters);
+                self.visit_expression(body);
+                self.scopes.pop();
+            }
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Binding(binding),
+                ..
+            }) => {
+                let mut names = Vec::new();
+                binding_names(binding, &mut names);
+                for name in names {
+                    self.check_assignment(&name);
+                }
+            }
+            Expression::Update(u) => {
+                if let Expression::Identifier(i) = &*u.argument {
+                    let name = i.name.clone();
+                    self.check_assignment(&name);
+                }
+            }
+            // The name of a function or class expression is only visible inside of it.
+            Expression::Function(FunctionExpression {
+                identifier: Some(i),
+                ..
+            })
+            | Expression::Class(ClassExpression {
+                identifier: Some(i),
+                ..
+            }) => {
+                self.push_scope(vec![i.name.clone()], Vec::new());
+                walk_expression(self, expression);
+                self.scopes.pop();
+                return;
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+}
+
+/// Renames the `let`, `const` and `class` declarations in the blocks of a function
+/// whose names are also used outside of the block.
+struct BlockRenamer<'a> {
+    lowerer: &'a mut Lowerer,
+    /// The number of times every name is used in the function.
+    totals: HashMap<String, usize>,
+}
+
+impl<'a> BlockRenamer<'a> {
+    fn rename(&mut self, mut region: Region<'_>, names: Vec<String>) {
+        if names.is_empty() {
+            return;
+        }
+        let mut counter = NameCounter::default();
+        region.visit(&mut counter);
+
+        for name in names {
+            let count = counter.counts.get(&name).copied().unwrap_or(0);
+            let total = self.totals.get(&name).copied().unwrap_or(0);
+            if count == 0 || count >= total {
+                continue;
+            }
+
+            let renamed = self.lowerer.generate_name(&name);
+            region.visit(&mut RenameAll {
+                from: &name,
+                to: &renamed,
+            });
+            self.totals.insert(name, total - count);
+            self.totals.insert(renamed, count);
+        }
+    }
+}
+
+impl<'a> VisitorMut for BlockRenamer<'a> {
+    // Nested functions are renamed when they are visited.
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+
+    fn visit_arrow_function(&mut self, _: &mut ArrowFunctionExpression) {}
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::BlockStatement(b) => {
+                let names = block_scoped_names(&b.statements);
+                self.rename(Region::Statement(statement), names);
+            }
+            Statement::ForStatement(ForStatement {
+                init: Some(head), ..
+            })
+            | Statement::ForInStatement(ForInStatement { left: head, .. })
+            | Statement::ForOfStatement(ForOfStatement { left: head, .. }) => {
+                let names = block_scoped_names(std::slice::from_ref(&**head));
+                self.rename(Region::Statement(statement), names);
+            }
+            Statement::SwitchStatement(s) => {
+                let mut names = Vec::new();
+                for case in &s.cases {
+                    for consequent in &case.consequent {
+                        names.extend(block_scoped_names(std::slice::from_ref(&**consequent)));
+                    }
+                }
+                self.rename(Region::Statement(statement), names);
+            }
+            Statement::TryStatement(t) => {
+                let handler = t.handler.as_mut().map(|handler| &mut handler.body);
+                let blocks = std::iter::once(&mut t.block)
+                    .chain(handler)
+                    .chain(t.finalizer.as_mut());
+                for block in blocks {
+                    let names = block_scoped_names(&block.statements);
+                    self.rename(Region::Block(block), names);
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+}
+
+/// A part of the program that block scoped declarations are visible in.
+enum Region<'a> {
+    Statement(&'a mut Statement),
+    Block(&'a mut BlockStatement),
+}
+
+impl<'a> Region<'a> {
+    fn visit<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Region::Statement(statement) => visitor.visit_statement(statement),
+            Region::Block(block) => walk_block(visitor, block),
+        }
+    }
+}
+
+/// Counts how many times every name is used, as a reference or a declaration.
+#[derive(Default)]
+struct NameCounter {
+    counts: HashMap<String, usize>,
+}
+
+impl NameCounter {
+    fn add(&mut self, name: &str) {
+        *self.counts.entry(name.to_string()).or_insert(0) += 1;
+    }
+}
+
+impl VisitorMut for NameCounter {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::FunctionDeclaration(FunctionDeclaration { identifier, .. })
+            | Statement::ClassDeclaration(ClassDeclaration { identifier, .. })
+            | Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration:
+                    ExportNamedDeclarationKind::FunctionDeclaration(FunctionDeclaration {
+                        identifier,
+                        ..
+                    })
+                    | ExportNamedDeclarationKind::ClassDeclaration(ClassDeclaration {
+                        identifier, ..
+                    }),
+            })
+            | Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration:
+                    ExportDefaultDeclarationKind::FunctionDeclaration(FunctionDeclaration {
+                        identifier,
+                        ..
+                    })
+                    | ExportDefaultDeclarationKind::ClassDeclaration(ClassDeclaration {
+                        identifier, ..
+                    }),
+            }) => self.add(&identifier.name),
+            Statement::ExportNamedSpecifiers(ExportNamedSpecifiers {
+                specifiers,
+                source: None,
+            }) => {
+                for specifier in specifiers.iter() {
+                    self.add(&specifier.local.name);
+                }
+            }
+            Statement::ImportDeclaration(i) => {
+                let locals = i.default.iter().chain(&i.namespace);
+                let specifiers = i.specifiers.iter().map(|s| &s.local);
+                for local in locals.chain(specifiers) {
+                    self.add(&local.name);
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(Identifier { name })
+            | Expression::Function(FunctionExpression {
+                identifier: Some(Identifier { name }),
+                ..
+            })
+            | Expression::Class(ClassExpression {
+                identifier: Some(Identifier { name }),
+                ..
+            }) => self.add(name),
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        match binding {
+            Binding::Identifier(i) => self.add(&i.name),
+            Binding::Object(o) => {
+                for property in &o.properties {
+                    match property {
+                        ObjectBindingPropertyKind::Shorthand(ObjectBindingPropertyShorthand {
+                            key,
+                            ..
+                        })
+                        | ObjectBindingPropertyKind::Rest(ObjectBindingPropertyRest { key }) => {
+                            self.add(&key.name)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Binding::Array(_) => {}
+        }
+        walk_binding(self, binding);
+    }
+
+    fn visit_shorthand_property(&mut self, property: &mut ObjectExpressionPropertyShorthand) {
+        self.add(&property.key.name);
+    }
+}
+
+/// Renames every use of a name. This is only used for the region that a block scoped
+/// declaration is visible in, where every use of the name refers to a declaration
+/// in the region, so nested declarations with the same name can be renamed as well.
+struct RenameAll<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+impl<'a> RenameAll<'a> {
+    fn rename(&self, identifier: &mut Identifier) {
+        if identifier.name == self.from {
+            identifier.name = self.to.to_string();
+        }
+    }
+}
+
+impl<'a> VisitorMut for RenameAll<'a> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::FunctionDeclaration(f) => self.rename(&mut f.identifier),
+            Statement::ClassDeclaration(c) => self.rename(&mut c.identifier),
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(i)
+            | Expression::Function(FunctionExpression {
+                identifier: Some(i),
+                ..
+            })
+            | Expression::Class(ClassExpression {
+                identifier: Some(i),
+                ..
+            }) => self.rename(i),
+            // { a } becomes { a: a2 }
+            Expression::Object(o) => {
+                for property in o.properties.iter_mut() {
+                    if let ObjectExpressionPropertyKind::Shorthand(s) = property {
+                        if s.key.name == self.from {
+                            *property =
+                                ObjectExpressionPropertyKind::Property(ObjectExpressionProperty {
+                                    key: LiteralPropertyName::Identifier(s.key.clone()),
+                                    value: identifier(self.to),
+                                });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        match binding {
+            Binding::Identifier(i) => self.rename(i),
+            Binding::Object(o) => {
+                for property in o.properties.iter_mut() {
+                    match property {
+                        ObjectBindingPropertyKind::Shorthand(s) if s.key.name == self.from => {
+                            *property =
+                                ObjectBindingPropertyKind::Property(ObjectBindingProperty {
+                                    key: LiteralPropertyName::Identifier(s.key.clone()),
+                                    binding: Binding::Identifier(Identifier {
+                                        name: self.to.to_string(),
+                                    }),
+                                    initializer: s.initializer.take(),
+                                });
+                        }
+                        ObjectBindingPropertyKind::Rest(r) => self.rename(&mut r.key),
+                        _ => {}
+                    }
+                }
+            }
+            Binding::Array(_) => {}
+        }
+        walk_binding(self, binding);
+    }
+}
+
+/// Collects the block scoped declarations in the body of a loop, the ones
+/// in nested loops and functions belong to them instead.
+struct BodyNames<'a> {
+    names: &'a mut Vec<String>,
+}
+
+impl<'a> VisitorMut for BodyNames<'a> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if is_loop(statement) {
+            return;
+        }
+        self.names
+            .extend(block_scoped_names(std::slice::from_ref(statement)));
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, _: &mut Expression) {}
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+}
+
+/// Finds the names that are used by a function.
+struct CaptureFinder<'a> {
+    names: &'a [String],
+    /// The number of functions around the current node.
+    depth: usize,
+    found: bool,
+}
+
+impl<'a> CaptureFinder<'a> {
+    fn check(&mut self, name: &str) {
+        if self.depth > 0 && self.names.iter().any(|n| n == name) {
+            self.found = true;
+        }
+    }
+}
+
+impl<'a> VisitorMut for CaptureFinder<'a> {
+    fn visit_function(&mut self, parameters: &mut Vec<ParameterKind>, body: &mut BlockStatement) {
+        self.depth += 1;
+        walk_function(self, parameters, body);
+        self.depth -= 1;
+    }
+
+    fn visit_arrow_function(&mut self, arrow: &mut ArrowFunctionExpression) {
+        self.depth += 1;
+        js_ast::visit::walk_arrow_function(self, arrow);
+        self.depth -= 1;
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier(i) = expression {
+            let name = i.name.clone();
+            self.check(&name);
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_shorthand_property(&mut self, property: &mut ObjectExpressionPropertyShorthand) {
+        let name = property.key.name.clone();
+        self.check(&name);
+    }
+}
+
+/// Finds assignments to the variables of a for loop.
+struct HeadAssignments<'a> {
+    names: &'a [String],
+    found: bool,
+}
+
+impl<'a> VisitorMut for HeadAssignments<'a> {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        let mut names = Vec::new();
+        match expression {
+            Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Binding(binding),
+                ..
+            }) => binding_names(binding, &mut names),
+            Expression::Update(UpdateExpression { argument, .. }) => {
+                if let Expression::Identifier(i) = &**argument {
+                    names.push(i.name.clone());
+                }
+            }
+            _ => {}
+        }
+        if names.iter().any(|name| self.names.contains(name)) {
+            self.found = true;
+        }
+        walk_expression(self, expression);
+    }
+}
+
+/// Turns the `var` declarations in the body of a loop, or of a lowered
+/// generator function, into assignments.
+pub(crate) struct VarHoister;
+
+impl VisitorMut for VarHoister {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        walk_statements(self, statements);
+        statements.retain(|statement| !matches!(statement, Statement::EmptyStatement(_)));
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        // The left side of a for-in or for-of loop is assigned by the loop.
+        if let Statement::ForInStatement(ForInStatement { left, body, .. })
+        | Statement::ForOfStatement(ForOfStatement { left, body, .. }) = statement
+        {
+            if let Statement::VariableDeclaration(v) = &**left {
+                if let [VariableDeclarator {
+                    binding: Binding::Identifier(i),
+                    initializer: None,
+                }] = &v.declarations[..]
+                {
+                    if v.kind == VariableDeclarationKind::Var {
+                        **left = Statement::Expression(ExpressionStatement {
+                            expression: identifier(&i.name),
+                        });
+                    }
+                }
+            }
+            self.visit_statement(body);
+            return;
+        }
+
+        walk_statement(self, statement);
+
+        match statement {
+            Statement::ForStatement(ForStatement { init, .. }) => {
+                if let Some(Statement::EmptyStatement(_)) = init.as_deref() {
+                    *init = None;
+                }
+            }
+            Statement::VariableDeclaration(v) if v.kind == VariableDeclarationKind::Var => {
+                let assignments: Vec<Expression> = std::mem::take(&mut v.declarations)
+                    .into_iter()
+                    .filter_map(|declarator| {
+                        let initializer = declarator.initializer?;
+                        Some(match declarator.binding {
+                            Binding::Identifier(i) => assign(&i.name, initializer),
+                            binding => Expression::Assignment(AssignmentExpression {
+                                left: AssignmentExpressionLeft::Binding(binding),
+                                operator: AssignmentExpressionOperator::Assign,
+                                right: Box::new(initializer),
+                            }),
+                        })
+                    })
+                    .collect();
+                *statement = match assignments.len() {
+                    0 => empty(),
+                    1 => Statement::Expression(ExpressionStatement {
+                        expression: assignments.into_iter().next().unwrap(),
+                    }),
+                    _ => Statement::Expression(ExpressionStatement {
+                        expression: Expression::Sequence(SequenceExpression {
+                            expressions: assignments,
+                        }),
+                    }),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_expression(&mut self, _: &mut Expression) {}
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+}
+
+/// Rewrites the jumps in the body of a loop that is moved into a function.
+/// `continue` returns from the function and `break` returns "break", a
+/// `return` returns an object holding the value.
+struct Jumps {
+    /// The label of the loop.
+    label: Option<String>,
+    /// The number of loops inside of the body around the current node.
+    loops: usize,
+    /// The number of loops and switch statements inside of the body around the current node.
+    breakables: usize,
+    /// The labels inside of the body around the current node.
+    labels: Vec<String>,
+    breaks: bool,
+    returns: bool,
+    /// Set when a jump goes to a label outside of the loop.
+    outside: bool,
+}
+
+impl Jumps {
+    /// Returns whether a jump to the label leaves the body, or reports it
+    /// when the label belongs to a statement around the loop.
+    fn leaves_body(&mut self, label: &Option<Identifier>, is_break: bool) -> bool {
+        match label {
+            None => match is_break {
+                true => self.breakables == 0,
+                false => self.loops == 0,
+            },
+            Some(label) if self.labels.contains(&label.name) => false,
+            Some(label) if self.label.as_ref() == Some(&label.name) => true,
+            Some(_) => {
+                self.outside = true;
+                false
+            }
+        }
+    }
+}
+
+impl VisitorMut for Jumps {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::ContinueStatement(c) => {
+                if self.leaves_body(&c.label, false) {
+                    *statement = Statement::ReturnStatement(ReturnStatement { expression: None });
+                }
+            }
+            Statement::BreakStatement(b) => {
+                if self.leaves_body(&b.label, true) {
+                    self.breaks = true;
+                    *statement = Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(string("break")),
+                    });
+                }
+            }
+            // return a becomes return { v: a }
+            Statement::ReturnStatement(r) => {
+                self.returns = true;
+                let value = r.expression.take().unwrap_or_else(void_zero);
+                r.expression = Some(Expression::Object(ObjectExpression {
+                    properties: vec![ObjectExpressionPropertyKind::Property(
+                        ObjectExpressionProperty {
+                            key: LiteralPropertyName::Identifier(Identifier {
+                                name: "v".to_string(),
+                            }),
+                            value,
+                        },
+                    )],
+                }));
+            }
+            Statement::LabeledStatement(l) => {
+                self.labels.push(l.identifier.name.clone());
+                walk_statement(self, statement);
+                self.labels.pop();
+            }
+            Statement::SwitchStatement(_) => {
+                self.breakables += 1;
+                walk_statement(self, statement);
+                self.breakables -= 1;
+            }
+            statement if is_loop(statement) => {
+                self.loops += 1;
+                self.breakables += 1;
+                walk_statement(self, statement);
+                self.loops -= 1;
+                self.breakables -= 1;
+            }
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, _: &mut Expression) {}
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+}
+
+fn is_loop(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::ForStatement(_)
+            | Statement::ForInStatement(_)
+            | Statement::ForOfStatement(_)
+            | Statement::WhileStatement(_)
+            | Statement::DoWhileStatement(_)
+    )
+}
+
+fn loop_body(statement: &mut Statement) -> &mut Statement {
+    match statement {
+        Statement::ForStatement(f) => &mut f.body,
+        Statement::ForInStatement(f) => &mut f.body,
+        Statement::ForOfStatement(f) => &mut f.body,
+        Statement::WhileStatement(w) => &mut w.body,
+        Statement::DoWhileStatement(d) => &mut d.body,
+        _ => unreachable!("The statement is a loop"),
+    }
+}
+
+/// Returns the names declared with `let`, `const` and `class` directly in the statements.
+fn block_scoped_names(statements: &[Statement]) -> Vec<String> {
+    let mut names = Vec::new();
+    for statement in statements {
+        match statement {
+            Statement::VariableDeclaration(v) if v.kind != VariableDeclarationKind::Var => {
+                for declarator in &v.declarations {
+                    binding_names(&declarator.binding, &mut names);
+                }
+            }
+            Statement::ClassDeclaration(c) => names.push(c.identifier.name.clone()),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Returns the names declared with `const` directly in the statements.
+fn constant_names(statements: &[Statement]) -> Vec<String> {
+    let mut names = Vec::new();
+    for statement in statements {
+        match statement {
+            Statement::VariableDeclaration(v)
+            | Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration: ExportNamedDeclarationKind::VariableDeclaration(v),
+            }) if v.kind == VariableDeclarationKind::Const => {
+                for declarator in &v.declarations {
+                    binding_names(&declarator.binding, &mut names);
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn empty() -> Statement {
+    Statement::EmptyStatement(EmptyStatement {})
+}
+
+fn strict_equals(left: Expression, right: Expression) -> Expression {
+    Expression::Binary(BinaryExpression {
+        left: Box::new(left),
+        operator: BinaryExpressionOperator::StrictEquals,
+        right: Box::new(right),
+    })
+}
+
+fn if_statement(test: Expression, consequent: Statement) -> Statement {
+    Statement::IfStatement(IfStatement {
+        test,
+        consequent: Box::new(consequent),
+        alternate: None,
+    })
+}
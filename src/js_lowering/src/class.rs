@@ -0,0 +1,460 @@
+/// This file contains the lowering of classes to constructor functions.
+///
+/// `class A extends B { constructor() { super(); } m() {} }` becomes
+///
+/// ```js
+/// var A = function(_super) {
+///     __extends(A, _super);
+///     function A() { var _this;
+///         _this = _super.call(this) || this;
+///         return _this;
+///     }
+///     A.prototype.m = function() {};
+///     return A;
+/// }(B);
+/// ```
+///
+/// Classes are lowered before their contents are visited, so the functions
+/// created here are lowered like any other function afterwards.
+use js_ast::visit::{
+    walk_arguments, walk_arrow_function, walk_expression, walk_function, walk_statement, VisitorMut,
+};
+use js_ast::*;
+
+use crate::destructuring::{key_string, property_member};
+use crate::helpers::{EXTENDS, SUPER_GET};
+use crate::lower::{
+    assign, call, computed_member, declarator, identifier, logical, member, string, Lowerer,
+};
+
+impl Lowerer {
+    /// Replaces a class declaration, or an exported class declaration, with a variable.
+    pub(crate) fn lower_class_declaration(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::ClassDeclaration(c) => {
+                *statement = Statement::VariableDeclaration(self.class_variable(c));
+            }
+            Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration: ExportNamedDeclarationKind::ClassDeclaration(c),
+            }) => {
+                let declaration = self.class_variable(c);
+                *statement = Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                    declaration: ExportNamedDeclarationKind::VariableDeclaration(declaration),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// `export default class A {}` becomes `var A = ...; export default A;`,
+    /// anonymous classes are stored in a variable named `_default`.
+    pub(crate) fn lower_default_exported_class(
+        &mut self,
+        declaration: ExportDefaultDeclarationKind,
+    ) -> Vec<Statement> {
+        let (name, extends, body) = match declaration {
+            ExportDefaultDeclarationKind::ClassDeclaration(c) => {
+                (c.identifier.name, c.extends, c.body)
+            }
+            ExportDefaultDeclarationKind::AnonymousDefaultExportedClassDeclaration(c) => {
+                (self.generate_name("_default"), c.extends, c.body)
+            }
+            declaration => {
+                return vec![Statement::ExportDefaultDeclaration(
+                    ExportDefaultDeclaration { declaration },
+                )]
+            }
+        };
+
+        let class = self.lower_class(&name, extends, body);
+        vec![
+            Statement::VariableDeclaration(VariableDeclaration {
+                kind: VariableDeclarationKind::Var,
+                declarations: vec![declarator(&name, Some(class))],
+            }),
+            Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration: ExportDefaultDeclarationKind::Expression(identifier(&name)),
+            }),
+        ]
+    }
+
+    /// Replaces a class expression with the function creating the class.
+    pub(crate) fn lower_class_expression(&mut self, class: &mut ClassExpression) -> Expression {
+        let name = match class.identifier.take() {
+            Some(identifier) => identifier.name,
+            None => self.generate_name("_class"),
+        };
+        let extends = class.extends.take().map(|extends| *extends);
+        self.lower_class(&name, extends, std::mem::take(&mut class.body))
+    }
+
+    fn class_variable(&mut self, class: &mut ClassDeclaration) -> VariableDeclaration {
+        let extends = class.extends.take();
+        let body = std::mem::take(&mut class.body);
+        let value = self.lower_class(&class.identifier.name, extends, body);
+        VariableDeclaration {
+            kind: VariableDeclarationKind::Var,
+            declarations: vec![declarator(&class.identifier.name, Some(value))],
+        }
+    }
+
+    /// Returns an immediately invoked function that creates the class, the
+    /// parent class is passed to it as `_super`.
+    fn lower_class(
+        &mut self,
+        name: &str,
+        extends: Option<Expression>,
+        body: Vec<ClassPropertyKind>,
+    ) -> Expression {
+        let super_name = extends.as_ref().map(|_| self.generate_name("_super"));
+        let prototype = member(identifier(name), "prototype");
+        let home = |is_static: bool| match is_static {
+            true => identifier(name),
+            false => prototype.clone(),
+        };
+
+        let mut constructor = None;
+        let mut members = Vec::new();
+        for property in body {
+            let statement = match property {
+                ClassPropertyKind::Constructor(c) => {
+                    constructor = Some(c);
+                    continue;
+                }
+                ClassPropertyKind::Method(m) => {
+                    let value = self.method(
+                        m.parameters,
+                        m.body,
+                        m.is_async,
+                        m.generator,
+                        home(m.is_static),
+                    );
+                    assign_member(property_member(home(m.is_static), m.identifier), value)
+                }
+                ClassPropertyKind::MethodComputed(m) => {
+                    let value = self.method(
+                        m.parameters,
+                        m.body,
+                        m.is_async,
+                        m.generator,
+                        home(m.is_static),
+                    );
+                    assign_member(computed_member(home(m.is_static), m.key), value)
+                }
+                ClassPropertyKind::MethodGet(m) => {
+                    let value = self.method(m.parameters, m.body, false, false, home(m.is_static));
+                    define_property(home(m.is_static), key_string(&m.identifier), "get", value)
+                }
+                ClassPropertyKind::MethodGetComputed(m) => {
+                    let value = self.method(m.parameters, m.body, false, false, home(m.is_static));
+                    define_property(home(m.is_static), m.key, "get", value)
+                }
+                ClassPropertyKind::MethodSet(m) => {
+                    let value = self.method(m.parameters, m.body, false, false, home(m.is_static));
+                    define_property(home(m.is_static), key_string(&m.identifier), "set", value)
+                }
+                ClassPropertyKind::MethodSetComputed(m) => {
+                    let value = self.method(m.parameters, m.body, false, false, home(m.is_static));
+                    define_property(home(m.is_static), m.key, "set", value)
+                }
+            };
+            members.push(statement);
+        }
+
+        let (parameters, body) = match (constructor, &super_name) {
+            (Some(mut c), Some(super_name)) => {
+                self.rewrite_super_members(&mut c.parameters, &mut c.body, prototype.clone());
+                let this_name = self.generate_name("_this");
+                let mut rewriter = DerivedConstructor {
+                    this_name: this_name.clone(),
+                    super_name: super_name.clone(),
+                    arrow_depth: 0,
+                };
+                walk_function(&mut rewriter, &mut c.parameters, &mut c.body);
+
+                let statements = &mut c.body.statements;
+                if !matches!(statements.last(), Some(Statement::ReturnStatement(_))) {
+                    statements.push(Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(identifier(&this_name)),
+                    }));
+                }
+                statements.insert(
+                    0,
+                    Statement::VariableDeclaration(VariableDeclaration {
+                        kind: VariableDeclarationKind::Var,
+                        declarations: vec![declarator(&this_name, None)],
+                    }),
+                );
+                (c.parameters, c.body)
+            }
+            (Some(mut c), None) => {
+                self.rewrite_super_members(&mut c.parameters, &mut c.body, prototype.clone());
+                (c.parameters, c.body)
+            }
+            // The default constructor of a derived class passes its arguments to the parent.
+            (None, Some(super_name)) => {
+                let parent = call(
+                    member(identifier(super_name), "apply"),
+                    vec![Expression::This(ThisExpression {}), identifier("arguments")],
+                );
+                let value = logical(
+                    parent,
+                    LogicalExpressionOperator::Or,
+                    Expression::This(ThisExpression {}),
+                );
+                let body = BlockStatement {
+                    statements: vec![Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(value),
+                    })],
+                };
+                (Vec::new(), body)
+            }
+            (None, None) => (
+                Vec::new(),
+                BlockStatement {
+                    statements: Vec::new(),
+                },
+            ),
+        };
+
+        let mut statements = Vec::new();
+        if let Some(super_name) = &super_name {
+            let helper = self.use_helper(EXTENDS);
+            statements.push(expression_statement(call(
+                helper,
+                vec![identifier(name), identifier(super_name)],
+            )));
+        }
+        statements.push(Statement::FunctionDeclaration(FunctionDeclaration {
+            identifier: Identifier {
+                name: name.to_string(),
+            },
+            parameters,
+            body,
+            generator: false,
+            is_async: false,
+            no_side_effects: false,
+        }));
+        statements.extend(members);
+        statements.push(Statement::ReturnStatement(ReturnStatement {
+            expression: Some(identifier(name)),
+        }));
+
+        let parameters = super_name
+            .iter()
+            .map(|name| {
+                ParameterKind::Parameter(Parameter {
+                    binding: Binding::Identifier(Identifier { name: name.clone() }),
+                    initializer: None,
+                })
+            })
+            .collect();
+        let function = Expression::Function(FunctionExpression {
+            identifier: None,
+            parameters,
+            body: BlockStatement { statements },
+            generator: false,
+            is_async: false,
+        });
+        call(function, extends.into_iter().collect())
+    }
+
+    /// Returns the function expression for a method, `home` is the object
+    /// that the method is stored on.
+    fn method(
+        &mut self,
+        mut parameters: Vec<ParameterKind>,
+        mut body: BlockStatement,
+        is_async: bool,
+        generator: bool,
+        home: Expression,
+    ) -> Expression {
+        self.rewrite_super_members(&mut parameters, &mut body, home);
+        Expression::Function(FunctionExpression {
+            identifier: None,
+            parameters,
+            body,
+            generator,
+            is_async,
+        })
+    }
+
+    fn rewrite_super_members(
+        &mut self,
+        parameters: &mut [ParameterKind],
+        body: &mut BlockStatement,
+        home: Expression,
+    ) {
+        let mut rewriter = SuperMembers {
+            home,
+            used: false,
+            assigned: false,
+        };
+        walk_function(&mut rewriter, parameters, body);
+        if rewriter.used {
+            self.use_helper(SUPER_GET);
+        }
+        if rewriter.assigned {
+            self.unsupported("assignments to super properties");
+        }
+    }
+}
+
+/// Replaces `super.a` with `__superGet(home, "a", this)` and `super.a(b)`
+/// with `__superGet(home, "a", this).call(this, b)`.
+struct SuperMembers {
+    home: Expression,
+    used: bool,
+    assigned: bool,
+}
+
+impl SuperMembers {
+    fn lookup(&mut self, member: &mut MemberExpression) -> Expression {
+        self.used = true;
+        let property = std::mem::replace(&mut *member.property, identifier("undefined"));
+        let key = match (member.computed, property) {
+            (false, Expression::Identifier(i)) => string(&i.name),
+            (_, property) => property,
+        };
+        call(
+            identifier(SUPER_GET),
+            vec![self.home.clone(), key, Expression::This(ThisExpression {})],
+        )
+    }
+}
+
+impl VisitorMut for SuperMembers {
+    // Functions have their own `super`, or none at all.
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Expression(left),
+                ..
+            }) if is_super_member(left) => {
+                self.assigned = true;
+                return;
+            }
+            Expression::Call(c) if is_super_member(&c.callee) => {
+                walk_arguments(self, &mut c.arguments);
+                if let Expression::Member(m) = &mut *c.callee {
+                    if m.computed {
+                        self.visit_expression(&mut m.property);
+                    }
+                    let method = self.lookup(m);
+                    *c.callee = member(method, "call");
+                    let this = Expression::This(ThisExpression {});
+                    c.arguments.insert(0, ArgumentKind::Expression(this));
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        walk_expression(self, expression);
+
+        if let Expression::Member(m) = expression {
+            if matches!(*m.object, Expression::Super(_)) {
+                *expression = self.lookup(m);
+            }
+        }
+    }
+}
+
+fn is_super_member(expression: &Expression) -> bool {
+    matches!(expression, Expression::Member(m) if matches!(*m.object, Expression::Super(_)))
+}
+
+/// Rewrites the constructor of a derived class, the object created by the
+/// parent constructor is stored in `_this` by `super()` and returned at the end.
+struct DerivedConstructor {
+    this_name: String,
+    super_name: String,
+    arrow_depth: usize,
+}
+
+impl VisitorMut for DerivedConstructor {
+    // Functions have their own `this`.
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+
+    fn visit_arrow_function(&mut self, arrow: &mut ArrowFunctionExpression) {
+        self.arrow_depth += 1;
+        walk_arrow_function(self, arrow);
+        self.arrow_depth -= 1;
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+
+        if let Statement::ReturnStatement(r) = statement {
+            if r.expression.is_none() && self.arrow_depth == 0 {
+                r.expression = Some(identifier(&self.this_name));
+            }
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+
+        match expression {
+            Expression::This(_) => *expression = identifier(&self.this_name),
+            // super(a) becomes _this = _super.call(this, a) || this
+            Expression::Call(c) if matches!(*c.callee, Expression::Super(_)) => {
+                let mut arguments = vec![ArgumentKind::Expression(Expression::This(
+                    ThisExpression {},
+                ))];
+                arguments.append(&mut c.arguments);
+                let parent = Expression::Call(CallExpression {
+                    callee: Box::new(member(identifier(&self.super_name), "call")),
+                    arguments,
+                    pure: false,
+                });
+                let value = logical(
+                    parent,
+                    LogicalExpressionOperator::Or,
+                    Expression::This(ThisExpression {}),
+                );
+                *expression = assign(&self.this_name, value);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(ExpressionStatement { expression })
+}
+
+fn assign_member(target: Expression, value: Expression) -> Statement {
+    expression_statement(Expression::Assignment(AssignmentExpression {
+        left: AssignmentExpressionLeft::Expression(Box::new(target)),
+        operator: AssignmentExpressionOperator::Assign,
+        right: Box::new(value),
+    }))
+}
+
+/// Object.defineProperty(home, key, { get: value, configurable: true });
+fn define_property(home: Expression, key: Expression, kind: &str, value: Expression) -> Statement {
+    let property = |name: &str, value: Expression| {
+        ObjectExpressionPropertyKind::Property(ObjectExpressionProperty {
+            key: LiteralPropertyName::Identifier(Identifier {
+                name: name.to_string(),
+            }),
+            value,
+        })
+    };
+    let descriptor = Expression::Object(ObjectExpression {
+        properties: vec![
+            property(kind, value),
+            property(
+                "configurable",
+                Expression::BooleanLiteral(BooleanLiteral { value: true }),
+            ),
+        ],
+    });
+    expression_statement(call(
+        member(identifier("Object"), "defineProperty"),
+        vec![home, key, descriptor],
+    ))
+}
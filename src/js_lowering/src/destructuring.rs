@@ -0,0 +1,460 @@
+/// This file contains the lowering of destructuring, a binding is turned into
+/// one plain variable or assignment for every name that it binds.
+///
+/// `var { a, b: [c] } = d;` becomes `var a = d.a, _a = __toArray(d.b), c = _a[0];`,
+/// temporary variables hold the values that are read more than once. Function
+/// parameters with patterns, default values or rest elements are replaced by
+/// plain parameters and declarations at the top of the function body.
+use js_ast::scope::binding_names;
+use js_ast::*;
+
+use crate::helpers::{REST, TO_ARRAY};
+use crate::lower::{
+    assign, call, computed_member, identifier, member, number, string, void_zero, Lowerer,
+};
+use crate::Target;
+
+impl Lowerer {
+    /// Returns true if the binding has to be lowered for the target, object
+    /// rest elements are newer than the rest of the destructuring syntax.
+    pub(crate) fn needs_lowering(&self, binding: &Binding) -> bool {
+        match binding {
+            Binding::Identifier(_) => false,
+            _ => {
+                self.target < Target::ES2015
+                    || (self.target < Target::ES2018 && has_object_rest(binding))
+            }
+        }
+    }
+
+    pub(crate) fn lower_variable_declaration(&mut self, declaration: &mut VariableDeclaration) {
+        // Declarations without an initializer are the heads of for-in and for-of
+        // loops, they are lowered together with the loop.
+        let needs_lowering = declaration
+            .declarations
+            .iter()
+            .any(|d| d.initializer.is_some() && self.needs_lowering(&d.binding));
+        if !needs_lowering {
+            return;
+        }
+
+        let mut declarations = Vec::new();
+        for declarator in std::mem::take(&mut declaration.declarations) {
+            match declarator.initializer {
+                Some(initializer) if self.needs_lowering(&declarator.binding) => {
+                    let mut pairs = Vec::new();
+                    self.destructure(declarator.binding, initializer, &mut pairs, false);
+                    declarations.extend(pairs.into_iter().map(|(name, value)| {
+                        VariableDeclarator {
+                            binding: Binding::Identifier(Identifier { name }),
+                            initializer: Some(value),
+                        }
+                    }));
+                }
+                initializer => declarations.push(VariableDeclarator {
+                    binding: declarator.binding,
+                    initializer,
+                }),
+            }
+        }
+        declaration.declarations = declarations;
+    }
+
+    /// Lowers a destructuring assignment to a sequence of assignments. The value
+    /// of the assignment is the right side, it is only kept if it is used.
+    pub(crate) fn lower_assignment(
+        &mut self,
+        binding: Binding,
+        right: Expression,
+        keep_value: bool,
+    ) -> Expression {
+        let mut names = Vec::new();
+        binding_names(&binding, &mut names);
+
+        let mut pairs = Vec::new();
+        let is_array = matches!(right, Expression::Array(_));
+        let root = match right {
+            Expression::Identifier(i) if !names.contains(&i.name) => Expression::Identifier(i),
+            right => self.destructure_temp(right, &mut pairs, true),
+        };
+        match binding {
+            // An array literal does not have to be converted to an array.
+            Binding::Array(a) if is_array => {
+                self.destructure_array_items(a, root.clone(), &mut pairs, true)
+            }
+            binding => self.destructure(binding, root.clone(), &mut pairs, true),
+        }
+
+        let mut expressions: Vec<Expression> = pairs
+            .into_iter()
+            .map(|(name, value)| assign(&name, value))
+            .collect();
+        if keep_value || expressions.is_empty() {
+            expressions.push(root);
+        }
+        if expressions.len() == 1 {
+            expressions.remove(0)
+        } else {
+            Expression::Sequence(SequenceExpression { expressions })
+        }
+    }
+
+    /// Lowers the parameters that the target does not support and returns the
+    /// statements that have to be added to the top of the function body.
+    ///
+    /// `function(a = 1, { b }, ...c) {}` becomes `function(a, _a) { if (a === void 0) a = 1;
+    /// var b = _a.b; var c = Array.prototype.slice.call(arguments, 2); }`.
+    pub(crate) fn lower_parameters(
+        &mut self,
+        parameters: &mut Vec<ParameterKind>,
+    ) -> Vec<Statement> {
+        let lower_all = self.target < Target::ES2015
+            && parameters.iter().any(|parameter| {
+                !matches!(
+                    parameter,
+                    ParameterKind::Parameter(Parameter {
+                        binding: Binding::Identifier(_),
+                        initializer: None,
+                    })
+                )
+            });
+        let lower_patterns = parameters.iter().any(|parameter| match parameter {
+            ParameterKind::Parameter(p) => self.needs_lowering(&p.binding),
+            ParameterKind::Rest(r) => self.needs_lowering(&r.binding),
+        });
+        let mut prologue = Vec::new();
+        if !lower_all && !lower_patterns {
+            return prologue;
+        }
+
+        // The statements are added in the order of the parameters since a
+        // default value may reference the parameters before it.
+        for (index, parameter) in std::mem::take(parameters).into_iter().enumerate() {
+            match parameter {
+                ParameterKind::Parameter(p) => {
+                    let (name, pattern) = match p.binding {
+                        Binding::Identifier(i) => (i.name, None),
+                        binding if self.needs_lowering(&binding) => (self.temp(), Some(binding)),
+                        binding => {
+                            parameters.push(ParameterKind::Parameter(Parameter {
+                                binding,
+                                initializer: p.initializer,
+                            }));
+                            continue;
+                        }
+                    };
+
+                    let mut initializer = p.initializer;
+                    if lower_all {
+                        if let Some(initializer) = initializer.take() {
+                            prologue.push(default_value(&name, initializer));
+                        }
+                    }
+                    parameters.push(ParameterKind::Parameter(Parameter {
+                        binding: Binding::Identifier(Identifier { name: name.clone() }),
+                        initializer,
+                    }));
+                    if let Some(pattern) = pattern {
+                        prologue.push(self.declaration(pattern, identifier(&name)));
+                    }
+                }
+
+                ParameterKind::Rest(r) if lower_all => {
+                    let slice = member(
+                        member(member(identifier("Array"), "prototype"), "slice"),
+                        "call",
+                    );
+                    let value = call(slice, vec![identifier("arguments"), number(index as f64)]);
+                    prologue.push(self.declaration(r.binding, value));
+                }
+
+                ParameterKind::Rest(r) if self.needs_lowering(&r.binding) => {
+                    let name = self.temp();
+                    parameters.push(ParameterKind::Rest(RestElement {
+                        binding: Binding::Identifier(Identifier { name: name.clone() }),
+                    }));
+                    prologue.push(self.declaration(r.binding, identifier(&name)));
+                }
+
+                parameter => parameters.push(parameter),
+            }
+        }
+        prologue
+    }
+
+    /// Moves a pattern in the parameter of a catch clause to the body.
+    pub(crate) fn lower_catch_clause(&mut self, handler: &mut CatchClause) {
+        if !self.needs_lowering(&handler.param) {
+            return;
+        }
+
+        let name = self.temp();
+        let binding = std::mem::replace(
+            &mut handler.param,
+            Binding::Identifier(Identifier { name: name.clone() }),
+        );
+        let declaration = self.declaration(binding, identifier(&name));
+        handler.body.statements.insert(0, declaration);
+    }
+
+    /// Moves a pattern declared in the head of a for-in or for-of loop to the body,
+    /// the loop then declares a temporary variable that the pattern is read from.
+    pub(crate) fn lower_loop_left(&mut self, left: &mut Statement, body: &mut Statement) {
+        let declaration = match left {
+            Statement::VariableDeclaration(v)
+                if v.declarations.len() == 1 && self.needs_lowering(&v.declarations[0].binding) =>
+            {
+                v
+            }
+            _ => return,
+        };
+
+        let name = self.temp();
+        let binding = std::mem::replace(
+            &mut declaration.declarations[0].binding,
+            Binding::Identifier(Identifier { name: name.clone() }),
+        );
+        let mut inner = VariableDeclaration {
+            kind: declaration.kind.clone(),
+            declarations: vec![VariableDeclarator {
+                binding,
+                initializer: Some(identifier(&name)),
+            }],
+        };
+        self.lower_variable_declaration(&mut inner);
+        let inner = Statement::VariableDeclaration(inner);
+
+        // Lexical declarations get their own block so that they can not
+        // collide with the declarations in the body.
+        match body {
+            Statement::BlockStatement(b) if inner_is_var(&inner) => b.statements.insert(0, inner),
+            _ => {
+                let original =
+                    std::mem::replace(body, Statement::EmptyStatement(EmptyStatement {}));
+                *body = Statement::BlockStatement(BlockStatement {
+                    statements: vec![inner, original],
+                });
+            }
+        }
+    }
+
+    /// Returns a lowered declaration of the binding, it is a `var` declaration
+    /// unless the target supports `let`.
+    fn declaration(&mut self, binding: Binding, initializer: Expression) -> Statement {
+        let kind = if self.target < Target::ES2015 {
+            VariableDeclarationKind::Var
+        } else {
+            VariableDeclarationKind::Let
+        };
+        let mut declaration = VariableDeclaration {
+            kind,
+            declarations: vec![VariableDeclarator {
+                binding,
+                initializer: Some(initializer),
+            }],
+        };
+        self.lower_variable_declaration(&mut declaration);
+        Statement::VariableDeclaration(declaration)
+    }
+
+    /// Adds a pair for every name bound by the binding, with the expression reading
+    /// the value of the name from the source. Temporary variables are added as pairs
+    /// as well, they are declared in the function scope if `declare_temps` is set.
+    fn destructure(
+        &mut self,
+        binding: Binding,
+        source: Expression,
+        pairs: &mut Vec<(String, Expression)>,
+        declare_temps: bool,
+    ) {
+        let mut names = Vec::new();
+        binding_names(&binding, &mut names);
+
+        match binding {
+            Binding::Identifier(i) => pairs.push((i.name, source)),
+
+            Binding::Object(o) => {
+                let source = match source {
+                    Expression::Identifier(i) if !names.contains(&i.name) => {
+                        Expression::Identifier(i)
+                    }
+                    source => self.destructure_temp(source, pairs, declare_temps),
+                };
+                let has_rest = o
+                    .properties
+                    .iter()
+                    .any(|p| matches!(p, ObjectBindingPropertyKind::Rest(_)));
+
+                // The keys that are read before the rest, it gets all of the other properties.
+                let mut excluded = Vec::new();
+                for property in o.properties {
+                    match property {
+                        ObjectBindingPropertyKind::Property(p) => {
+                            excluded.push(key_string(&p.key));
+                            let value = property_member(source.clone(), p.key);
+                            let value =
+                                self.with_default(value, p.initializer, pairs, declare_temps);
+                            self.destructure(p.binding, value, pairs, declare_temps);
+                        }
+                        ObjectBindingPropertyKind::Shorthand(p) => {
+                            excluded.push(string(&p.key.name));
+                            let value = member(source.clone(), &p.key.name);
+                            let value =
+                                self.with_default(value, p.initializer, pairs, declare_temps);
+                            pairs.push((p.key.name, value));
+                        }
+                        ObjectBindingPropertyKind::Computed(p) => {
+                            let key = if has_rest {
+                                let key = self.destructure_temp(p.key, pairs, declare_temps);
+                                excluded.push(key.clone());
+                                key
+                            } else {
+                                p.key
+                            };
+                            let value = computed_member(source.clone(), key);
+                            let value =
+                                self.with_default(value, p.initializer, pairs, declare_temps);
+                            self.destructure(p.binding, value, pairs, declare_temps);
+                        }
+                        ObjectBindingPropertyKind::Rest(r) => {
+                            let excluded = Expression::Array(ArrayExpression {
+                                items: excluded
+                                    .drain(..)
+                                    .map(|key| Some(ArrayExpressionItem::Expression(key)))
+                                    .collect(),
+                            });
+                            let value = call(self.use_helper(REST), vec![source.clone(), excluded]);
+                            pairs.push((r.key.name, value));
+                        }
+                    }
+                }
+            }
+
+            Binding::Array(a) => {
+                let source = match source {
+                    Expression::Array(_) => source,
+                    source => call(self.use_helper(TO_ARRAY), vec![source]),
+                };
+                let source = self.destructure_temp(source, pairs, declare_temps);
+                self.destructure_array_items(a, source, pairs, declare_temps);
+            }
+        }
+    }
+
+    /// Adds the pairs of the items of an array binding, the source is an array
+    /// that can be read more than once.
+    fn destructure_array_items(
+        &mut self,
+        binding: ArrayBinding,
+        source: Expression,
+        pairs: &mut Vec<(String, Expression)>,
+        declare_temps: bool,
+    ) {
+        for (index, item) in binding.items.into_iter().enumerate() {
+            match item {
+                None => {}
+                Some(ArrayBindingItemKind::Item(i)) => {
+                    let value = computed_member(source.clone(), number(index as f64));
+                    let value = self.with_default(value, i.initializer, pairs, declare_temps);
+                    self.destructure(i.binding, value, pairs, declare_temps);
+                }
+                Some(ArrayBindingItemKind::Rest(r)) => {
+                    let value = call(member(source.clone(), "slice"), vec![number(index as f64)]);
+                    self.destructure(r.binding, value, pairs, declare_temps);
+                }
+            }
+        }
+    }
+
+    /// Returns the value, or the default value if it is undefined.
+    fn with_default(
+        &mut self,
+        value: Expression,
+        initializer: Option<Expression>,
+        pairs: &mut Vec<(String, Expression)>,
+        declare_temps: bool,
+    ) -> Expression {
+        match initializer {
+            None => value,
+            Some(initializer) => {
+                let value = self.destructure_temp(value, pairs, declare_temps);
+                Expression::Conditional(ConditionalExpression {
+                    test: Box::new(Expression::Binary(BinaryExpression {
+                        left: Box::new(value.clone()),
+                        operator: BinaryExpressionOperator::StrictEquals,
+                        right: Box::new(void_zero()),
+                    })),
+                    consequence: Box::new(initializer),
+                    alternate: Box::new(value),
+                })
+            }
+        }
+    }
+
+    fn destructure_temp(
+        &mut self,
+        value: Expression,
+        pairs: &mut Vec<(String, Expression)>,
+        declare_temps: bool,
+    ) -> Expression {
+        let name = if declare_temps {
+            self.declare_temp()
+        } else {
+            self.temp()
+        };
+        pairs.push((name.clone(), value));
+        identifier(&name)
+    }
+}
+
+fn has_object_rest(binding: &Binding) -> bool {
+    match binding {
+        Binding::Identifier(_) => false,
+        Binding::Object(o) => o.properties.iter().any(|property| match property {
+            ObjectBindingPropertyKind::Rest(_) => true,
+            ObjectBindingPropertyKind::Property(p) => has_object_rest(&p.binding),
+            ObjectBindingPropertyKind::Computed(p) => has_object_rest(&p.binding),
+            ObjectBindingPropertyKind::Shorthand(_) => false,
+        }),
+        Binding::Array(a) => a.items.iter().flatten().any(|item| match item {
+            ArrayBindingItemKind::Item(i) => has_object_rest(&i.binding),
+            ArrayBindingItemKind::Rest(r) => has_object_rest(&r.binding),
+        }),
+    }
+}
+
+fn inner_is_var(statement: &Statement) -> bool {
+    matches!(statement, Statement::VariableDeclaration(v) if v.kind == VariableDeclarationKind::Var)
+}
+
+/// `if (a === void 0) a = b;`
+fn default_value(name: &str, initializer: Expression) -> Statement {
+    Statement::IfStatement(IfStatement {
+        test: Expression::Binary(BinaryExpression {
+            left: Box::new(identifier(name)),
+            operator: BinaryExpressionOperator::StrictEquals,
+            right: Box::new(void_zero()),
+        }),
+        consequent: Box::new(Statement::Expression(ExpressionStatement {
+            expression: assign(name, initializer),
+        })),
+        alternate: None,
+    })
+}
+
+/// Returns the key as a string, in the form that `for in` produces it.
+pub(crate) fn key_string(key: &LiteralPropertyName) -> Expression {
+    match key {
+        LiteralPropertyName::Identifier(i) => string(&i.name),
+        LiteralPropertyName::String(s) => Expression::StringLiteral(s.clone()),
+        LiteralPropertyName::Numeric(n) => string(&n.value.to_string()),
+    }
+}
+
+pub(crate) fn property_member(object: Expression, key: LiteralPropertyName) -> Expression {
+    match key {
+        LiteralPropertyName::Identifier(i) => member(object, &i.name),
+        LiteralPropertyName::String(s) => computed_member(object, Expression::StringLiteral(s)),
+        LiteralPropertyName::Numeric(n) => computed_member(object, Expression::NumericLiteral(n)),
+    }
+}
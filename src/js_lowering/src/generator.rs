@@ -0,0 +1,1135 @@
+/// This file contains the lowering of generator functions for targets without
+/// them. The body is compiled to a state machine, a function run by the
+/// `__generator` helper that is called again every time the generator resumes:
+///
+/// ```js
+/// function a() {
+///     var b;
+///     return __generator(this, function (_a) {
+///         switch (_a.label) {
+///             case 0: return [4, 1];
+///             case 1:
+///                 b = _a.sent();
+///                 return [2, b];
+///         }
+///     });
+/// }
+/// ```
+///
+/// The state machine returns an instruction for the helper, such as `[4, value]`
+/// to yield a value or `[3, label]` to jump to the case of a label. The cases
+/// start after every `yield` and at every place that a jump goes to. Statements
+/// without a `yield` in them stay as they are, apart from their jumps and returns.
+///
+/// The variables of the function are declared outside of the state machine so
+/// that they keep their values between its calls. Operands that are evaluated
+/// before a `yield` are stored in temporary variables declared there too.
+use js_ast::scope::{binding_names, var_names};
+use js_ast::visit::{walk_expression, walk_statement, VisitorMut};
+use js_ast::*;
+
+use crate::async_function::split_directives;
+use crate::block_scope::VarHoister;
+use crate::helpers::{GENERATOR, VALUES};
+use crate::iterators::for_of_loop;
+use crate::lower::{
+    assign, binary, call, declarator, identifier, member, number, void_zero, Lowerer,
+};
+
+/// The instructions of the state machine, see `__generator`.
+const RETURN: f64 = 2.0;
+const JUMP: f64 = 3.0;
+const YIELD: f64 = 4.0;
+const YIELD_STAR: f64 = 5.0;
+const END_FINALLY: f64 = 7.0;
+
+impl Lowerer {
+    /// Replaces the body of a generator function with a state machine run by `__generator`.
+    pub(crate) fn lower_generator_body(&mut self, body: &mut BlockStatement) {
+        let mut statements = std::mem::take(&mut body.statements);
+        let directives = split_directives(&mut statements);
+
+        // The state machine is a function, so it has its own `arguments`.
+        let arguments_name = match uses_arguments(&mut statements) {
+            true => {
+                let name = self.generate_name("_arguments");
+                let mut replacer = Arguments {
+                    replacement: Some(&name),
+                    found: false,
+                };
+                replacer.visit_statements(&mut statements);
+                Some(name)
+            }
+            false => None,
+        };
+
+        let mut variables = Vec::new();
+        var_names(&statements, &mut variables);
+        VarHoister.visit_statements(&mut statements);
+
+        let state = self.temp();
+        let mut generator = Generator {
+            lowerer: self,
+            state: state.clone(),
+            variables,
+            temps: Vec::new(),
+            functions: Vec::new(),
+            cases: vec![Vec::new()],
+            labels: Vec::new(),
+            targets: Vec::new(),
+            pending_labels: Vec::new(),
+        };
+        for statement in statements {
+            generator.statement(statement);
+        }
+        let mut variables = std::mem::take(&mut generator.variables);
+        let functions = std::mem::take(&mut generator.functions);
+        let machine = generator.finish();
+
+        let mut declarations: Vec<VariableDeclarator> = arguments_name
+            .iter()
+            .map(|name| declarator(name, Some(identifier("arguments"))))
+            .collect();
+        let mut seen = std::collections::HashSet::new();
+        variables.retain(|name| seen.insert(name.clone()));
+        declarations.extend(variables.iter().map(|name| declarator(name, None)));
+
+        body.statements = directives;
+        if !declarations.is_empty() {
+            body.statements
+                .push(Statement::VariableDeclaration(VariableDeclaration {
+                    kind: VariableDeclarationKind::Var,
+                    declarations,
+                }));
+        }
+        body.statements.extend(functions);
+        let machine = Expression::Function(FunctionExpression {
+            identifier: None,
+            parameters: vec![ParameterKind::Parameter(Parameter {
+                binding: Binding::Identifier(Identifier { name: state }),
+                initializer: None,
+            })],
+            body: BlockStatement {
+                statements: machine,
+            },
+            generator: false,
+            is_async: false,
+        });
+        let helper = self.use_helper(GENERATOR);
+        body.statements
+            .push(Statement::ReturnStatement(ReturnStatement {
+                expression: Some(call(
+                    helper,
+                    vec![Expression::This(ThisExpression {}), machine],
+                )),
+            }));
+    }
+}
+
+/// A statement that `break` or `continue` can jump out of.
+struct JumpTarget {
+    labels: Vec<String>,
+    break_label: usize,
+    /// The label that `continue` jumps to, loops have one.
+    continue_label: Option<usize>,
+    /// Whether a `break` without a label jumps out of it, which is not the
+    /// case for labeled blocks.
+    unlabeled: bool,
+}
+
+/// Compiles the statements of a generator function to the cases of a state machine.
+struct Generator<'a> {
+    lowerer: &'a mut Lowerer,
+    /// The parameter of the state machine, the helper passes its state in it.
+    state: String,
+    /// The variables declared outside of the state machine.
+    variables: Vec<String>,
+    /// The temporary variables holding the operands evaluated before a `yield`.
+    temps: Vec<String>,
+    /// The function declarations of the body, they are moved out of the state machine.
+    functions: Vec<Statement>,
+    cases: Vec<Vec<Statement>>,
+    /// The case that every label starts at, labels are created before the case is known.
+    labels: Vec<Option<usize>>,
+    targets: Vec<JumpTarget>,
+    /// The labels of the labeled statement being compiled, for the loop or
+    /// switch statement that it labels.
+    pending_labels: Vec<String>,
+}
+
+impl Generator<'_> {
+    fn finish(mut self) -> Vec<Statement> {
+        let last = self.cases.last_mut().expect("There is always a case");
+        if !ends_with_exit(last) {
+            last.push(instruction(RETURN, None));
+        }
+
+        let labels: Vec<usize> = self
+            .labels
+            .iter()
+            .map(|case| case.expect("Every label is marked"))
+            .collect();
+        let mut resolver = LabelResolver { labels: &labels };
+        for case in self.cases.iter_mut() {
+            resolver.visit_statements(case);
+        }
+
+        if self.cases.len() == 1 {
+            return self.cases.pop().expect("There is one case");
+        }
+        vec![Statement::SwitchStatement(SwitchStatement {
+            discriminant: member(identifier(&self.state), "label"),
+            cases: self
+                .cases
+                .into_iter()
+                .enumerate()
+                .map(|(index, statements)| SwitchStatementCase {
+                    test: Some(number(index as f64)),
+                    consequent: statements.into_iter().map(Box::new).collect(),
+                })
+                .collect(),
+        })]
+    }
+
+    fn new_label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    /// Starts the case of the label, the current case is used when it is still empty.
+    fn mark(&mut self, label: usize) {
+        let next = self.cases.len();
+        let current = self.cases.last_mut().expect("There is always a case");
+        if !current.is_empty() {
+            if !ends_with_exit(current) {
+                let target = member(identifier(&self.state), "label");
+                current.push(expression_statement(assign_to(target, number(next as f64))));
+            }
+            self.cases.push(Vec::new());
+        }
+        self.labels[label] = Some(self.cases.len() - 1);
+    }
+
+    /// Adds a statement to the current case, unless the case has already returned.
+    fn emit(&mut self, statement: Statement) {
+        let current = self.cases.last_mut().expect("There is always a case");
+        if !ends_with_exit(current) {
+            current.push(statement);
+        }
+    }
+
+    fn jump(&mut self, label: usize) {
+        self.emit(instruction(JUMP, Some(label_reference(label))));
+    }
+
+    fn jump_if(&mut self, test: Expression, label: usize) {
+        self.emit(Statement::IfStatement(IfStatement {
+            test,
+            consequent: Box::new(instruction(JUMP, Some(label_reference(label)))),
+            alternate: None,
+        }));
+    }
+
+    /// Emits a statement without a `yield`, its jumps out of the compiled
+    /// statements and its returns become instructions.
+    fn emit_native(&mut self, mut statement: Statement) {
+        let mut jumps = Jumps {
+            targets: &self.targets,
+            labels: Vec::new(),
+            loops: 0,
+            breakables: 0,
+        };
+        jumps.visit_statement(&mut statement);
+        self.emit(statement);
+    }
+
+    fn temp(&mut self) -> String {
+        let name = self.lowerer.temp();
+        self.temps.push(name.clone());
+        self.variables.push(name.clone());
+        name
+    }
+
+    /// Stores a value in a temporary variable, so that it is not changed by
+    /// the code that runs before it is used.
+    fn spill(&mut self, value: Expression) -> Expression {
+        match &value {
+            Expression::NullLiteral(_)
+            | Expression::BooleanLiteral(_)
+            | Expression::NumericLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::This(_) => return value,
+            Expression::Identifier(i) if self.temps.contains(&i.name) => return value,
+            _ => {}
+        }
+        let name = self.temp();
+        self.emit(expression_statement(assign(&name, value)));
+        identifier(&name)
+    }
+
+    fn statement(&mut self, mut statement: Statement) {
+        if let Statement::FunctionDeclaration(_) = statement {
+            self.functions.push(statement);
+            return;
+        }
+        if !contains_yield(&mut statement) {
+            self.emit_native(statement);
+            return;
+        }
+        // The loop is lowered first, the rest of the lowering would lower it inside
+        // of a case of the state machine.
+        if for_of_loop(&statement).is_some() {
+            self.lowerer.lower_for_of(&mut statement);
+            var_names(std::slice::from_ref(&statement), &mut self.variables);
+            VarHoister.visit_statement(&mut statement);
+        }
+
+        match statement {
+            Statement::BlockStatement(b) => {
+                for statement in b.statements {
+                    self.statement(statement);
+                }
+            }
+            Statement::Expression(e) => {
+                let expression = self.expression(e.expression);
+                self.emit(expression_statement(expression));
+            }
+            Statement::VariableDeclaration(v) => {
+                for declarator in v.declarations {
+                    binding_names(&declarator.binding, &mut self.variables);
+                    if let Some(initializer) = declarator.initializer {
+                        let expression =
+                            self.expression(Expression::Assignment(AssignmentExpression {
+                                left: AssignmentExpressionLeft::Binding(declarator.binding),
+                                operator: AssignmentExpressionOperator::Assign,
+                                right: Box::new(initializer),
+                            }));
+                        self.emit(expression_statement(expression));
+                    }
+                }
+            }
+            Statement::ReturnStatement(r) => {
+                let value = r.expression.map(|e| self.expression(e));
+                self.emit(instruction(RETURN, value));
+            }
+            Statement::ThrowStatement(t) => {
+                let argument = self.expression(t.argument);
+                self.emit(Statement::ThrowStatement(ThrowStatement { argument }));
+            }
+            Statement::IfStatement(mut i) => {
+                let test = self.expression(i.test);
+                let branches_yield = contains_yield(&mut i.consequent)
+                    || i.alternate.as_mut().is_some_and(|a| contains_yield(a));
+                if !branches_yield {
+                    self.emit_native(Statement::IfStatement(IfStatement { test, ..i }));
+                    return;
+                }
+
+                let alternate_label = self.new_label();
+                self.jump_if(not(test), alternate_label);
+                self.statement(*i.consequent);
+                match i.alternate {
+                    Some(alternate) => {
+                        let end = self.new_label();
+                        self.jump(end);
+                        self.mark(alternate_label);
+                        self.statement(*alternate);
+                        self.mark(end);
+                    }
+                    None => self.mark(alternate_label),
+                }
+            }
+            Statement::WhileStatement(w) => {
+                let start = self.new_label();
+                let end = self.new_label();
+                let labels = std::mem::take(&mut self.pending_labels);
+                self.mark(start);
+                let test = self.expression(w.test);
+                self.jump_if(not(test), end);
+                self.loop_body(*w.body, labels, end, start);
+                self.jump(start);
+                self.mark(end);
+            }
+            Statement::DoWhileStatement(d) => {
+                let start = self.new_label();
+                let next = self.new_label();
+                let end = self.new_label();
+                let labels = std::mem::take(&mut self.pending_labels);
+                self.mark(start);
+                self.loop_body(*d.body, labels, end, next);
+                self.mark(next);
+                let test = self.expression(d.test);
+                self.jump_if(test, start);
+                self.mark(end);
+            }
+            Statement::ForStatement(f) => {
+                let labels = std::mem::take(&mut self.pending_labels);
+                match f.init.map(|init| *init) {
+                    Some(Statement::Expression(e)) => {
+                        let expression = self.expression(e.expression);
+                        self.emit(expression_statement(expression));
+                    }
+                    Some(Statement::EmptyStatement(_)) | None => {}
+                    Some(init) => self.statement(init),
+                }
+                let start = self.new_label();
+                let next = self.new_label();
+                let end = self.new_label();
+                self.mark(start);
+                if let Some(test) = f.test {
+                    let test = self.expression(test);
+                    self.jump_if(not(test), end);
+                }
+                self.loop_body(*f.body, labels, end, next);
+                self.mark(next);
+                if let Some(update) = f.update {
+                    let update = self.expression(update);
+                    self.emit(expression_statement(update));
+                }
+                self.jump(start);
+                self.mark(end);
+            }
+            Statement::ForInStatement(f) => self.for_in(f),
+            Statement::LabeledStatement(l) => {
+                self.pending_labels.push(l.identifier.name);
+                match *l.body {
+                    body @ Statement::LabeledStatement(_)
+                    | body @ Statement::SwitchStatement(_)
+                    | body @ Statement::WhileStatement(_)
+                    | body @ Statement::DoWhileStatement(_)
+                    | body @ Statement::ForStatement(_)
+                    | body @ Statement::ForInStatement(_) => self.statement(body),
+                    body => {
+                        let end = self.new_label();
+                        self.targets.push(JumpTarget {
+                            labels: std::mem::take(&mut self.pending_labels),
+                            break_label: end,
+                            continue_label: None,
+                            unlabeled: false,
+                        });
+                        self.statement(body);
+                        self.targets.pop();
+                        self.mark(end);
+                    }
+                }
+            }
+            Statement::SwitchStatement(s) => self.switch(s),
+            Statement::TryStatement(t) => self.try_statement(t),
+            statement => {
+                self.lowerer
+                    .unsupported("yield expressions in this position");
+                self.pending_labels.clear();
+                self.emit_native(statement);
+            }
+        }
+    }
+
+    fn loop_body(&mut self, body: Statement, labels: Vec<String>, end: usize, next: usize) {
+        self.targets.push(JumpTarget {
+            labels,
+            break_label: end,
+            continue_label: Some(next),
+            unlabeled: true,
+        });
+        self.statement(body);
+        self.targets.pop();
+    }
+
+    /// `for (a in b)` collects the keys first, the keys deleted while the loop
+    /// runs are skipped like they are by the loop itself.
+    fn for_in(&mut self, f: ForInStatement) {
+        let labels = std::mem::take(&mut self.pending_labels);
+        let right = self.expression(f.right);
+        let object = self.temp();
+        let keys = self.temp();
+        let index = self.temp();
+        let key = self.temp();
+        self.emit(expression_statement(assign(&object, right)));
+        self.emit(expression_statement(assign(
+            &keys,
+            Expression::Array(ArrayExpression { items: Vec::new() }),
+        )));
+        self.emit(Statement::ForInStatement(ForInStatement {
+            left: Box::new(expression_statement(identifier(&key))),
+            right: identifier(&object),
+            body: Box::new(expression_statement(call(
+                member(identifier(&keys), "push"),
+                vec![identifier(&key)],
+            ))),
+        }));
+        self.emit(expression_statement(assign(&index, number(0.0))));
+
+        let start = self.new_label();
+        let next = self.new_label();
+        let end = self.new_label();
+        self.mark(start);
+        let in_bounds = binary(
+            identifier(&index),
+            BinaryExpressionOperator::LessThan,
+            member(identifier(&keys), "length"),
+        );
+        self.jump_if(not(in_bounds), end);
+        self.emit(expression_statement(assign(
+            &key,
+            Expression::Member(MemberExpression {
+                object: Box::new(identifier(&keys)),
+                property: Box::new(identifier(&index)),
+                computed: true,
+            }),
+        )));
+        let present = binary(
+            identifier(&key),
+            BinaryExpressionOperator::In,
+            identifier(&object),
+        );
+        self.jump_if(not(present), next);
+
+        let left = match *f.left {
+            Statement::VariableDeclaration(mut v) => {
+                let declarator = v.declarations.remove(0);
+                binding_names(&declarator.binding, &mut self.variables);
+                AssignmentExpressionLeft::Binding(declarator.binding)
+            }
+            Statement::Expression(ExpressionStatement {
+                expression: Expression::Identifier(i),
+            }) => AssignmentExpressionLeft::Binding(Binding::Identifier(i)),
+            Statement::Expression(e) => {
+                AssignmentExpressionLeft::Expression(Box::new(e.expression))
+            }
+            _ => unreachable!("The left side of a for-in loop is a declaration or an expression"),
+        };
+        let assignment = self.expression(Expression::Assignment(AssignmentExpression {
+            left,
+            operator: AssignmentExpressionOperator::Assign,
+            right: Box::new(identifier(&key)),
+        }));
+        self.emit(expression_statement(assignment));
+
+        self.loop_body(*f.body, labels, end, next);
+        self.mark(next);
+        self.emit(expression_statement(Expression::Update(UpdateExpression {
+            operator: UpdateExpressionOperator::PostfixIncrement,
+            argument: Box::new(identifier(&index)),
+        })));
+        self.jump(start);
+        self.mark(end);
+    }
+
+    /// The tests of the cases are compared one by one, jumping to the case that matches.
+    fn switch(&mut self, s: SwitchStatement) {
+        let labels = std::mem::take(&mut self.pending_labels);
+        let discriminant = self.expression(s.discriminant);
+        let discriminant = self.spill(discriminant);
+        let end = self.new_label();
+        let case_labels: Vec<usize> = s.cases.iter().map(|_| self.new_label()).collect();
+
+        let mut default = end;
+        let mut cases = Vec::new();
+        for (case, label) in s.cases.into_iter().zip(case_labels) {
+            match case.test {
+                Some(test) => {
+                    let test = self.expression(test);
+                    let matches = binary(
+                        discriminant.clone(),
+                        BinaryExpressionOperator::StrictEquals,
+                        test,
+                    );
+                    self.jump_if(matches, label);
+                }
+                None => default = label,
+            }
+            cases.push((case.consequent, label));
+        }
+        self.jump(default);
+
+        self.targets.push(JumpTarget {
+            labels,
+            break_label: end,
+            continue_label: None,
+            unlabeled: true,
+        });
+        for (consequent, label) in cases {
+            self.mark(label);
+            for statement in consequent {
+                self.statement(*statement);
+            }
+        }
+        self.targets.pop();
+        self.mark(end);
+    }
+
+    /// The helper keeps a stack of the try statements being run, an entry holds the
+    /// labels of the try block, the catch clause, the finally block and the end.
+    fn try_statement(&mut self, t: TryStatement) {
+        let try_label = self.new_label();
+        let catch_label = t.handler.as_ref().map(|_| self.new_label());
+        let finally_label = t.finalizer.as_ref().map(|_| self.new_label());
+        let end = self.new_label();
+
+        self.mark(try_label);
+        let entry = [Some(try_label), catch_label, finally_label, Some(end)]
+            .iter()
+            .map(|label| label.map(|l| ArrayExpressionItem::Expression(label_reference(l))))
+            .collect();
+        let trys = member(identifier(&self.state), "trys");
+        self.emit(expression_statement(call(
+            member(trys, "push"),
+            vec![Expression::Array(ArrayExpression { items: entry })],
+        )));
+        for statement in t.block.statements {
+            self.statement(statement);
+        }
+        self.jump(end);
+
+        if let (Some(handler), Some(catch_label)) = (t.handler, catch_label) {
+            self.mark(catch_label);
+            binding_names(&handler.param, &mut self.variables);
+            let exception = self.sent();
+            let assignment = self.expression(Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Binding(handler.param),
+                operator: AssignmentExpressionOperator::Assign,
+                right: Box::new(exception),
+            }));
+            self.emit(expression_statement(assignment));
+            for statement in handler.body.statements {
+                self.statement(statement);
+            }
+            self.jump(end);
+        }
+
+        if let (Some(finalizer), Some(finally_label)) = (t.finalizer, finally_label) {
+            self.mark(finally_label);
+            for statement in finalizer.statements {
+                self.statement(statement);
+            }
+            self.emit(instruction(END_FINALLY, None));
+        }
+        self.mark(end);
+    }
+
+    /// `_a.sent()` returns the value that the generator was resumed with.
+    fn sent(&self) -> Expression {
+        call(member(identifier(&self.state), "sent"), Vec::new())
+    }
+
+    fn expression(&mut self, mut expression: Expression) -> Expression {
+        if !expression_contains_yield(&mut expression) {
+            return expression;
+        }
+
+        match expression {
+            Expression::Yield(y) => {
+                let argument = y.argument.map(|a| self.expression(*a));
+                let instruction = match y.delegate {
+                    true => {
+                        let helper = self.lowerer.use_helper(VALUES);
+                        let iterable = argument.unwrap_or_else(void_zero);
+                        instruction(YIELD_STAR, Some(call(helper, vec![iterable])))
+                    }
+                    false => instruction(YIELD, argument),
+                };
+                self.emit(instruction);
+                self.cases.push(Vec::new());
+                self.sent()
+            }
+            Expression::Binary(b) if b.operator == BinaryExpressionOperator::NullishCoalescing => {
+                self.short_circuit(
+                    *b.left,
+                    LogicalExpressionOperator::NullishCoalescing,
+                    *b.right,
+                )
+            }
+            Expression::Logical(mut l) => match expression_contains_yield(&mut l.right) {
+                true => self.short_circuit(*l.left, l.operator, *l.right),
+                false => self.operands(Expression::Logical(l)),
+            },
+            Expression::Conditional(mut c) => {
+                let branches_yield = expression_contains_yield(&mut c.consequence)
+                    || expression_contains_yield(&mut c.alternate);
+                match branches_yield {
+                    true => self.conditional(c),
+                    false => self.operands(Expression::Conditional(c)),
+                }
+            }
+            Expression::Sequence(s) => {
+                // The expressions before the last yield run as statements.
+                let mut expressions = s.expressions;
+                let last = expressions
+                    .iter_mut()
+                    .rposition(expression_contains_yield)
+                    .expect("The sequence contains a yield");
+                let rest = expressions.split_off(last);
+                for expression in expressions {
+                    let expression = self.expression(expression);
+                    self.emit(expression_statement(expression));
+                }
+                let mut rest = rest.into_iter();
+                let first = rest.next().expect("The last yield is in the rest");
+                let mut expressions = vec![self.expression(first)];
+                expressions.extend(rest);
+                match expressions.len() {
+                    1 => expressions.pop().expect("There is one expression"),
+                    _ => Expression::Sequence(SequenceExpression { expressions }),
+                }
+            }
+            Expression::Assignment(a) => self.assignment(a),
+            expression => self.operands(expression),
+        }
+    }
+
+    /// Compiles the operands of an expression whose evaluation does not jump.
+    fn operands(&mut self, mut expression: Expression) -> Expression {
+        match &mut expression {
+            Expression::Binary(b) => self.ordered(vec![&mut *b.left, &mut *b.right]),
+            Expression::Logical(l) => self.ordered(vec![&mut *l.left]),
+            Expression::Conditional(c) => self.ordered(vec![&mut *c.test]),
+            Expression::Unary(u) => self.ordered(vec![&mut *u.argument]),
+            Expression::Update(u) => self.ordered(vec![&mut *u.argument]),
+            Expression::Import(i) => self.ordered(vec![&mut *i.source]),
+            Expression::Member(m) => self.ordered(member_operands(m)),
+            Expression::Call(c) => {
+                // The object of a method call is kept, it is the `this` of the call.
+                let mut operands = match &mut *c.callee {
+                    Expression::Member(m) => member_operands(m),
+                    callee => vec![callee],
+                };
+                operands.extend(argument_operands(&mut c.arguments));
+                self.ordered(operands);
+            }
+            Expression::New(n) => {
+                let mut operands = vec![&mut *n.callee];
+                operands.extend(argument_operands(&mut n.arguments));
+                self.ordered(operands);
+            }
+            Expression::Array(a) => {
+                let operands = a
+                    .items
+                    .iter_mut()
+                    .flatten()
+                    .map(|item| match item {
+                        ArrayExpressionItem::Expression(e) => e,
+                        ArrayExpressionItem::Spread(s) => &mut s.element,
+                    })
+                    .collect();
+                self.ordered(operands);
+            }
+            Expression::Object(o) => {
+                let mut operands = Vec::new();
+                for property in o.properties.iter_mut() {
+                    match property {
+                        ObjectExpressionPropertyKind::Property(p) => operands.push(&mut p.value),
+                        ObjectExpressionPropertyKind::Computed(c) => {
+                            operands.push(&mut c.key);
+                            operands.push(&mut c.value);
+                        }
+                        ObjectExpressionPropertyKind::Spread(s) => operands.push(&mut s.element),
+                        ObjectExpressionPropertyKind::MethodComputed(m) => {
+                            operands.push(&mut m.key)
+                        }
+                        ObjectExpressionPropertyKind::MethodGetComputed(m) => {
+                            operands.push(&mut m.key)
+                        }
+                        ObjectExpressionPropertyKind::MethodSetComputed(m) => {
+                            operands.push(&mut m.key)
+                        }
+                        _ => {}
+                    }
+                }
+                self.ordered(operands);
+            }
+            Expression::TemplateLiteral(t) => {
+                let operands = t.parts.iter_mut().map(|p| &mut p.expression).collect();
+                self.ordered(operands);
+            }
+            _ => self
+                .lowerer
+                .unsupported("yield expressions in this position"),
+        }
+        expression
+    }
+
+    fn conditional(&mut self, c: ConditionalExpression) -> Expression {
+        let result = self.temp();
+        let alternate_label = self.new_label();
+        let end = self.new_label();
+        let test = self.expression(*c.test);
+        self.jump_if(not(test), alternate_label);
+        let consequence = self.expression(*c.consequence);
+        self.emit(expression_statement(assign(&result, consequence)));
+        self.jump(end);
+        self.mark(alternate_label);
+        let alternate = self.expression(*c.alternate);
+        self.emit(expression_statement(assign(&result, alternate)));
+        self.mark(end);
+        identifier(&result)
+    }
+
+    /// Compiles the operands of an expression, which are evaluated in order. The
+    /// ones evaluated before the last operand with a yield are stored in temporary
+    /// variables, so that the code that runs before the yield can not change them.
+    fn ordered(&mut self, operands: Vec<&mut Expression>) {
+        let mut operands = operands;
+        let last = match operands
+            .iter_mut()
+            .rposition(|operand| expression_contains_yield(operand))
+        {
+            Some(last) => last,
+            None => return,
+        };
+        for (index, operand) in operands.into_iter().enumerate().take(last + 1) {
+            let value = self.expression(std::mem::replace(operand, void_zero()));
+            *operand = match index < last {
+                true => self.spill(value),
+                false => value,
+            };
+        }
+    }
+
+    /// `a && b` evaluates `b` only when `a` is truthy, which is a jump around `b`.
+    fn short_circuit(
+        &mut self,
+        left: Expression,
+        operator: LogicalExpressionOperator,
+        right: Expression,
+    ) -> Expression {
+        let result = self.temp();
+        let end = self.new_label();
+        let left = self.expression(left);
+        self.emit(expression_statement(assign(&result, left)));
+        let skip = match operator {
+            LogicalExpressionOperator::And => not(identifier(&result)),
+            LogicalExpressionOperator::Or => identifier(&result),
+            LogicalExpressionOperator::NullishCoalescing => binary(
+                identifier(&result),
+                BinaryExpressionOperator::LooseNotEquals,
+                Expression::NullLiteral(NullLiteral {}),
+            ),
+        };
+        self.jump_if(skip, end);
+        let right = self.expression(right);
+        self.emit(expression_statement(assign(&result, right)));
+        self.mark(end);
+        identifier(&result)
+    }
+
+    fn assignment(&mut self, mut a: AssignmentExpression) -> Expression {
+        let logical_operator = match a.operator {
+            AssignmentExpressionOperator::LogicalOrAssign => Some(LogicalExpressionOperator::Or),
+            AssignmentExpressionOperator::LogicalAndAssign => Some(LogicalExpressionOperator::And),
+            AssignmentExpressionOperator::NullishCoalescingAssign => {
+                Some(LogicalExpressionOperator::NullishCoalescing)
+            }
+            _ => None,
+        };
+        let right_yields = expression_contains_yield(&mut a.right);
+        if a.operator == AssignmentExpressionOperator::Assign || !right_yields {
+            let mut operands = match &mut a.left {
+                AssignmentExpressionLeft::Expression(e) => match &mut **e {
+                    Expression::Member(m) => member_operands(m),
+                    _ => Vec::new(),
+                },
+                AssignmentExpressionLeft::Binding(_) => Vec::new(),
+            };
+            operands.push(&mut *a.right);
+            self.ordered(operands);
+            return Expression::Assignment(a);
+        }
+
+        // The target is read before the value is evaluated, so its operands are stored first.
+        if let AssignmentExpressionLeft::Expression(e) = &mut a.left {
+            if let Expression::Member(m) = &mut **e {
+                for operand in member_operands(m) {
+                    let value = self.expression(std::mem::replace(operand, void_zero()));
+                    *operand = self.spill(value);
+                }
+            }
+        }
+        let target = match &a.left {
+            AssignmentExpressionLeft::Binding(Binding::Identifier(i)) => {
+                Expression::Identifier(i.clone())
+            }
+            AssignmentExpressionLeft::Expression(e) => (**e).clone(),
+            AssignmentExpressionLeft::Binding(_) => {
+                unreachable!("Patterns are only assigned with =")
+            }
+        };
+
+        // a ||= yield b becomes a || (a = yield b)
+        if let Some(operator) = logical_operator {
+            let assignment = Expression::Assignment(AssignmentExpression {
+                left: a.left,
+                operator: AssignmentExpressionOperator::Assign,
+                right: a.right,
+            });
+            return self.short_circuit(target, operator, assignment);
+        }
+
+        let current = self.temp();
+        self.emit(expression_statement(assign(&current, target)));
+        let right = self.expression(*a.right);
+        let operator = match a.operator {
+            AssignmentExpressionOperator::AdditionAssign => BinaryExpressionOperator::Addition,
+            AssignmentExpressionOperator::SubstitutionAssign => {
+                BinaryExpressionOperator::Substitution
+            }
+            AssignmentExpressionOperator::MultiplicationAssign => {
+                BinaryExpressionOperator::Multiplication
+            }
+            AssignmentExpressionOperator::DivisionAssign => BinaryExpressionOperator::Division,
+            AssignmentExpressionOperator::ModulusAssign => BinaryExpressionOperator::Modulus,
+            AssignmentExpressionOperator::ExponentiationAssign => {
+                BinaryExpressionOperator::Exponentiation
+            }
+            AssignmentExpressionOperator::LeftShiftAssign => BinaryExpressionOperator::LeftShift,
+            AssignmentExpressionOperator::RightShiftAssign => BinaryExpressionOperator::RightShift,
+            AssignmentExpressionOperator::UnsignedRightShiftAssign => {
+                BinaryExpressionOperator::UnsignedRightShift
+            }
+            AssignmentExpressionOperator::BitwiseOrAssign => BinaryExpressionOperator::BitwiseOr,
+            AssignmentExpressionOperator::BitwiseAndAssign => BinaryExpressionOperator::BitwiseAnd,
+            AssignmentExpressionOperator::BitwiseXorAssign => BinaryExpressionOperator::BitwiseXor,
+            _ => unreachable!("Logical assignments are handled above"),
+        };
+        Expression::Assignment(AssignmentExpression {
+            left: a.left,
+            operator: AssignmentExpressionOperator::Assign,
+            right: Box::new(binary(identifier(&current), operator, right)),
+        })
+    }
+}
+
+/// The object of a member expression and its computed key.
+fn member_operands(m: &mut MemberExpression) -> Vec<&mut Expression> {
+    match m.computed {
+        true => vec![&mut *m.object, &mut *m.property],
+        false => vec![&mut *m.object],
+    }
+}
+
+fn argument_operands(arguments: &mut [ArgumentKind]) -> impl Iterator<Item = &mut Expression> {
+    arguments.iter_mut().map(|argument| match argument {
+        ArgumentKind::Expression(e) => e,
+        ArgumentKind::Spread(s) => &mut s.element,
+    })
+}
+
+/// Rewrites the jumps and returns of a statement without a `yield` that leave
+/// it to instructions. The jumps inside of the statement stay as they are.
+struct Jumps<'a> {
+    targets: &'a [JumpTarget],
+    /// The labels inside of the statement around the current node.
+    labels: Vec<String>,
+    /// The number of loops inside of the statement around the current node.
+    loops: usize,
+    /// The number of loops and switch statements inside of the statement around the current node.
+    breakables: usize,
+}
+
+impl Jumps<'_> {
+    fn target(&self, label: &Option<Identifier>, is_break: bool) -> Option<usize> {
+        let target = match label {
+            Some(label) if self.labels.contains(&label.name) => return None,
+            Some(label) => self
+                .targets
+                .iter()
+                .rev()
+                .find(|target| target.labels.contains(&label.name))?,
+            None if is_break && self.breakables == 0 => {
+                self.targets.iter().rev().find(|target| target.unlabeled)?
+            }
+            None if !is_break && self.loops == 0 => self
+                .targets
+                .iter()
+                .rev()
+                .find(|target| target.continue_label.is_some())?,
+            None => return None,
+        };
+        match is_break {
+            true => Some(target.break_label),
+            false => target.continue_label,
+        }
+    }
+}
+
+impl VisitorMut for Jumps<'_> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::BreakStatement(b) => {
+                if let Some(label) = self.target(&b.label, true) {
+                    *statement = instruction(JUMP, Some(label_reference(label)));
+                }
+            }
+            Statement::ContinueStatement(c) => {
+                if let Some(label) = self.target(&c.label, false) {
+                    *statement = instruction(JUMP, Some(label_reference(label)));
+                }
+            }
+            Statement::ReturnStatement(r) => {
+                let value = r.expression.take();
+                *statement = instruction(RETURN, value);
+            }
+            Statement::LabeledStatement(l) => {
+                self.labels.push(l.identifier.name.clone());
+                walk_statement(self, statement);
+                self.labels.pop();
+            }
+            Statement::SwitchStatement(_) => {
+                self.breakables += 1;
+                walk_statement(self, statement);
+                self.breakables -= 1;
+            }
+            Statement::ForStatement(_)
+            | Statement::ForInStatement(_)
+            | Statement::ForOfStatement(_)
+            | Statement::WhileStatement(_)
+            | Statement::DoWhileStatement(_) => {
+                self.loops += 1;
+                self.breakables += 1;
+                walk_statement(self, statement);
+                self.loops -= 1;
+                self.breakables -= 1;
+            }
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, _: &mut Expression) {}
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+}
+
+/// Replaces the references to labels with the numbers of their cases.
+struct LabelResolver<'a> {
+    labels: &'a [usize],
+}
+
+impl VisitorMut for LabelResolver<'_> {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier(i) = expression {
+            if let Some(label) = i.name.strip_prefix('#') {
+                let label: usize = label.parse().expect("Label references are numbers");
+                *expression = number(self.labels[label] as f64);
+                return;
+            }
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+}
+
+/// Finds `yield` and `await` expressions outside of nested functions.
+struct YieldFinder {
+    found: bool,
+}
+
+impl VisitorMut for YieldFinder {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Yield(_) | Expression::Await(_) => self.found = true,
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+
+    fn visit_arrow_function(&mut self, _: &mut ArrowFunctionExpression) {}
+}
+
+/// Returns whether the statement contains a `yield` or an `await` of the function it is in.
+pub(crate) fn contains_yield(statement: &mut Statement) -> bool {
+    let mut finder = YieldFinder { found: false };
+    finder.visit_statement(statement);
+    finder.found
+}
+
+fn expression_contains_yield(expression: &mut Expression) -> bool {
+    let mut finder = YieldFinder { found: false };
+    finder.visit_expression(expression);
+    finder.found
+}
+
+/// Finds, and replaces, the references to `arguments` of the function the
+/// statements are in. Arrow functions use the `arguments` of the function around them.
+struct Arguments<'a> {
+    replacement: Option<&'a str>,
+    found: bool,
+}
+
+impl VisitorMut for Arguments<'_> {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(i) if i.name == "arguments" => {
+                self.found = true;
+                if let Some(replacement) = self.replacement {
+                    i.name = replacement.to_string();
+                }
+            }
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+}
+
+/// Returns whether the statements use the `arguments` of the function they are in.
+pub(crate) fn uses_arguments(statements: &mut Vec<Statement>) -> bool {
+    let mut finder = Arguments {
+        replacement: None,
+        found: false,
+    };
+    finder.visit_statements(statements);
+    finder.found
+}
+
+/// Returns whether the statements end with a return or a throw, which leave the state machine.
+fn ends_with_exit(statements: &[Statement]) -> bool {
+    matches!(
+        statements.last(),
+        Some(Statement::ReturnStatement(_)) | Some(Statement::ThrowStatement(_))
+    )
+}
+
+/// `return [operation, value]`
+fn instruction(operation: f64, value: Option<Expression>) -> Statement {
+    let mut items = vec![Some(ArrayExpressionItem::Expression(number(operation)))];
+    if let Some(value) = value {
+        items.push(Some(ArrayExpressionItem::Expression(value)));
+    }
+    Statement::ReturnStatement(ReturnStatement {
+        expression: Some(Expression::Array(ArrayExpression { items })),
+    })
+}
+
+/// A reference to the case of a label, it is replaced with the number of the
+/// case once the state machine is complete. Names starting with `#` are not
+/// used by programs.
+fn label_reference(label: usize) -> Expression {
+    identifier(&format!("#{}", label))
+}
+
+fn assign_to(target: Expression, value: Expression) -> Expression {
+    Expression::Assignment(AssignmentExpression {
+        left: AssignmentExpressionLeft::Expression(Box::new(target)),
+        operator: AssignmentExpressionOperator::Assign,
+        right: Box::new(value),
+    })
+}
+
+/// Negates the test of a jump, `!!a` is the same test as `a`.
+fn not(argument: Expression) -> Expression {
+    match argument {
+        Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::LogicalNot,
+            argument,
+        }) => *argument,
+        argument => Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::LogicalNot,
+            argument: Box::new(argument),
+        }),
+    }
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(ExpressionStatement { expression })
+}
@@ -0,0 +1,344 @@
+/// This file contains the helpers used by the lowered code, they are
+/// written in ES5 so that they can run in every target.
+use js_ast::{Statement, AST};
+use js_lexer::Lexer;
+use js_parser::Parser;
+use logger::LoggerImpl;
+
+/// Copies the own enumerable properties of the sources to the target,
+/// used for object spread.
+pub const ASSIGN: &str = "__assign";
+
+/// Runs the generator that an async function is lowered to, it resumes the
+/// generator with the value of every promise that it yields. Returns a promise.
+pub const ASYNC: &str = "__async";
+
+/// Sets up the prototype chain of a lowered class and its parent class,
+/// used for `class A extends B`.
+pub const EXTENDS: &str = "__extends";
+
+/// Returns the async iterator of a value, or wraps its iterator in one,
+/// used for `for await (a of b)`.
+pub const FOR_AWAIT: &str = "__forAwait";
+
+/// Runs the state machine that a generator function is lowered to and returns
+/// a generator object driving it, used for generators in ES5.
+pub const GENERATOR: &str = "__generator";
+
+/// Copies the own enumerable properties of an object except the excluded
+/// keys to a new object, used for the rest of an object binding.
+pub const REST: &str = "__rest";
+
+/// Looks up a property on the prototype of the object a method belongs to,
+/// used for `super.a` in lowered classes. Getters are called with the receiver.
+pub const SUPER_GET: &str = "__superGet";
+
+/// Converts an iterable to an array, used for array spread and array bindings.
+pub const TO_ARRAY: &str = "__toArray";
+
+/// Returns the iterator of a value, arrays are iterated without `Symbol.iterator`.
+/// Used for `yield*` in lowered generators.
+pub const VALUES: &str = "__values";
+
+pub const HELPERS: [&str; 9] = [
+    ASSIGN, ASYNC, EXTENDS, FOR_AWAIT, GENERATOR, REST, SUPER_GET, TO_ARRAY, VALUES,
+];
+
+fn helper_source(name: &str) -> &'static str {
+    match name {
+        ASSIGN => {
+            "var __assign = Object.assign || function(target) {
+                for (var i = 1; i < arguments.length; i++) {
+                    var source = arguments[i];
+                    for (var key in source) {
+                        if (Object.prototype.hasOwnProperty.call(source, key)) {
+                            target[key] = source[key];
+                        }
+                    }
+                }
+                return target;
+            };"
+        }
+        ASYNC => {
+            "var __async = function(thisArg, args, generator) {
+                return new Promise(function(resolve, reject) {
+                    var fulfilled = function(value) {
+                        try {
+                            step(generator.next(value));
+                        } catch (e) {
+                            reject(e);
+                        }
+                    };
+                    var rejected = function(value) {
+                        try {
+                            step(generator[\"throw\"](value));
+                        } catch (e) {
+                            reject(e);
+                        }
+                    };
+                    var step = function(result) {
+                        if (result.done) {
+                            resolve(result.value);
+                        } else {
+                            Promise.resolve(result.value).then(fulfilled, rejected);
+                        }
+                    };
+                    generator = generator.apply(thisArg, args);
+                    step(generator.next());
+                });
+            };"
+        }
+        EXTENDS => {
+            "var __extends = function(child, parent) {
+                if (typeof parent !== \"function\" && parent !== null) {
+                    throw new TypeError(\"Class extends value \" + String(parent) + \" is not a constructor or null\");
+                }
+                if (parent) {
+                    if (Object.setPrototypeOf) {
+                        Object.setPrototypeOf(child, parent);
+                    } else {
+                        child.__proto__ = parent;
+                    }
+                }
+                child.prototype = Object.create(parent && parent.prototype, {
+                    constructor: { value: child, writable: true, configurable: true }
+                });
+            };"
+        }
+        FOR_AWAIT => {
+            "var __forAwait = function(value) {
+                var method = typeof Symbol === \"function\" && Symbol.asyncIterator && value[Symbol.asyncIterator];
+                if (method) {
+                    return method.call(value);
+                }
+                var iterator = value[Symbol.iterator]();
+                var result = {};
+                var wrap = function(key) {
+                    var method = iterator[key];
+                    if (method) {
+                        result[key] = function(argument) {
+                            return new Promise(function(resolve, reject) {
+                                var step = method.call(iterator, argument);
+                                var done = step.done;
+                                Promise.resolve(step.value).then(function(value) {
+                                    resolve({ value: value, done: done });
+                                }, reject);
+                            });
+                        };
+                    }
+                };
+                wrap(\"next\");
+                wrap(\"return\");
+                return result;
+            };"
+        }
+        // The state machine returns an instruction every time it is called,
+        // [operation, value]. The operations are 2 return, 3 jump to the label
+        // in value, 4 yield, 5 yield* and 7 the end of a finally block. The
+        // helper uses 0 next, 1 throw and 6 for an exception caught by a try.
+        // `state.trys` holds the labels of the try statements that the state
+        // machine is in, [try, catch, finally, end].
+        GENERATOR => {
+            "var __generator = function(thisArg, body) {
+                var state = {
+                    label: 0,
+                    sent: function() {
+                        if (t[0] & 1) {
+                            throw t[1];
+                        }
+                        return t[1];
+                    },
+                    trys: [],
+                    ops: []
+                };
+                var started, running, delegate, t;
+                var step = function(op) {
+                    if (running) {
+                        throw new TypeError(\"Generator is already executing.\");
+                    }
+                    if (!started) {
+                        started = 1;
+                        if (op[0]) {
+                            state = 0;
+                        }
+                    }
+                    while (state) {
+                        try {
+                            running = 1;
+                            if (delegate) {
+                                t = op[0] & 2 ? delegate[\"return\"] : op[0] ? delegate[\"throw\"] || ((t = delegate[\"return\"]) && t.call(delegate), 0) : delegate.next;
+                                if (t && !(t = t.call(delegate, op[1])).done) {
+                                    return t;
+                                }
+                                delegate = 0;
+                                if (t) {
+                                    op = [op[0] & 2, t.value];
+                                }
+                            }
+                            switch (op[0]) {
+                                case 0:
+                                case 1:
+                                    t = op;
+                                    break;
+                                case 4:
+                                    state.label++;
+                                    return { value: op[1], done: false };
+                                case 5:
+                                    state.label++;
+                                    delegate = op[1];
+                                    op = [0];
+                                    continue;
+                                case 7:
+                                    op = state.ops.pop();
+                                    state.trys.pop();
+                                    continue;
+                                default:
+                                    t = state.trys;
+                                    t = t.length > 0 && t[t.length - 1];
+                                    if (!t && (op[0] === 6 || op[0] === 2)) {
+                                        state = 0;
+                                        continue;
+                                    }
+                                    if (op[0] === 3 && (!t || (op[1] > t[0] && op[1] < t[3]))) {
+                                        state.label = op[1];
+                                        break;
+                                    }
+                                    if (op[0] === 6 && state.label < t[1]) {
+                                        state.label = t[1];
+                                        t = op;
+                                        break;
+                                    }
+                                    if (t && state.label < t[2]) {
+                                        state.label = t[2];
+                                        state.ops.push(op);
+                                        break;
+                                    }
+                                    if (t[2]) {
+                                        state.ops.pop();
+                                    }
+                                    state.trys.pop();
+                                    continue;
+                            }
+                            op = body.call(thisArg, state);
+                        } catch (e) {
+                            op = [6, e];
+                            delegate = 0;
+                        } finally {
+                            running = t = 0;
+                        }
+                    }
+                    if (op[0] & 5) {
+                        throw op[1];
+                    }
+                    return { value: op[0] ? op[1] : void 0, done: true };
+                };
+                var verb = function(n) {
+                    return function(value) {
+                        return step([n, value]);
+                    };
+                };
+                var generator = { next: verb(0), \"throw\": verb(1), \"return\": verb(2) };
+                if (typeof Symbol === \"function\") {
+                    generator[Symbol.iterator] = function() {
+                        return this;
+                    };
+                }
+                return generator;
+            };"
+        }
+        REST => {
+            "var __rest = function(source, excluded) {
+                var target = {};
+                excluded = excluded.map(function(key) {
+                    return typeof key === \"symbol\" ? key : key + \"\";
+                });
+                for (var key in source) {
+                    if (Object.prototype.hasOwnProperty.call(source, key) && excluded.indexOf(key) < 0) {
+                        target[key] = source[key];
+                    }
+                }
+                if (source != null && typeof Object.getOwnPropertySymbols === \"function\") {
+                    var symbols = Object.getOwnPropertySymbols(source);
+                    for (var i = 0; i < symbols.length; i++) {
+                        if (excluded.indexOf(symbols[i]) < 0 && Object.prototype.propertyIsEnumerable.call(source, symbols[i])) {
+                            target[symbols[i]] = source[symbols[i]];
+                        }
+                    }
+                }
+                return target;
+            };"
+        }
+        SUPER_GET => {
+            "var __superGet = function(home, key, receiver) {
+                var object = Object.getPrototypeOf(home);
+                while (object !== null) {
+                    var descriptor = Object.getOwnPropertyDescriptor(object, key);
+                    if (descriptor) {
+                        return descriptor.get ? descriptor.get.call(receiver) : descriptor.value;
+                    }
+                    object = Object.getPrototypeOf(object);
+                }
+            };"
+        }
+        TO_ARRAY => {
+            "var __toArray = function(value) {
+                if (Array.isArray(value)) {
+                    return value;
+                }
+                if (typeof Symbol !== \"undefined\" && value != null && value[Symbol.iterator]) {
+                    var result = [];
+                    var iterator = value[Symbol.iterator]();
+                    for (var step = iterator.next(); !step.done; step = iterator.next()) {
+                        result.push(step.value);
+                    }
+                    return result;
+                }
+                return Array.prototype.slice.call(value);
+            };"
+        }
+        VALUES => {
+            "var __values = function(value) {
+                var method = typeof Symbol === \"function\" && Symbol.iterator && value[Symbol.iterator];
+                if (method) {
+                    return method.call(value);
+                }
+                if (value && typeof value.length === \"number\") {
+                    var index = 0;
+                    return {
+                        next: function() {
+                            if (value && index >= value.length) {
+                                value = void 0;
+                            }
+                            return { value: value && value[index++], done: !value };
+                        }
+                    };
+                }
+                throw new TypeError(\"Object is not iterable.\");
+            };"
+        }
+        _ => unreachable!("Unknown lowering helper \"{}\"", name),
+    }
+}
+
+/// Returns the statements declaring the helpers, in the order of `HELPERS`.
+pub fn helper_statements(names: &[&str]) -> Vec<Statement> {
+    let logger = LoggerImpl::new();
+    let mut statements = Vec::new();
+    for name in HELPERS.iter().filter(|helper| names.contains(helper)) {
+        let lexer = Lexer::new(helper_source(name), &logger);
+        statements.extend(Parser::new(lexer, &logger).parse_program().statements);
+    }
+    statements
+}
+
+/// Declares the helpers at the start of the program, after its imports.
+pub fn insert_helpers(program: &mut AST, names: &[&str]) {
+    let index = program
+        .statements
+        .iter()
+        .take_while(|statement| matches!(statement, Statement::ImportDeclaration(_)))
+        .count();
+    program
+        .statements
+        .splice(index..index, helper_statements(names));
+}
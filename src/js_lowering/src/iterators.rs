@@ -0,0 +1,325 @@
+/// This file contains the lowering of `for-of` loops and of spread arguments,
+/// which both go through the iterator of a value.
+///
+/// A `for-of` loop calls the methods of the iterator itself. The iterator is
+/// closed with its `return` method when the loop is left early, by a jump or
+/// by an exception:
+///
+/// ```js
+/// try {
+///     for (var _a = __values(b), _b = _a.next(), _c, _d; !_b.done; _b = _a.next()) {
+///         var a = _b.value;
+///     }
+/// } catch (_c) {
+///     _d = [_c];
+/// } finally {
+///     try {
+///         if (_b && !_b.done && (_c = _a.return)) _c.call(_a);
+///     } finally {
+///         if (_d) throw _d[0];
+///     }
+/// }
+/// ```
+///
+/// When the values are assumed to be arrays the loops use an index instead,
+/// and spread values are used as they are, which gives smaller output.
+use js_ast::*;
+
+use crate::helpers::{TO_ARRAY, VALUES};
+use crate::lower::{
+    assign, binary, call, computed_member, declarator, identifier, logical, member, number,
+    void_zero, Lowerer,
+};
+
+impl Lowerer {
+    /// Lowers a `for-of` loop, which may be labeled, to a `for` loop.
+    pub(crate) fn lower_for_of(&mut self, statement: &mut Statement) {
+        let (labels, f) = split_labels(statement);
+        if self.assume_arrays {
+            // for (var _a = 0, _b = b; _a < _b.length; _a++) { var a = _b[_a]; }
+            let index = self.temp();
+            let array = self.temp();
+            let value = computed_member(identifier(&array), identifier(&index));
+            let lowered = Statement::ForStatement(ForStatement {
+                init: Some(Box::new(Statement::VariableDeclaration(
+                    VariableDeclaration {
+                        kind: VariableDeclarationKind::Var,
+                        declarations: vec![
+                            declarator(&index, Some(number(0.0))),
+                            declarator(&array, Some(f.right)),
+                        ],
+                    },
+                ))),
+                test: Some(binary(
+                    identifier(&index),
+                    BinaryExpressionOperator::LessThan,
+                    member(identifier(&array), "length"),
+                )),
+                update: Some(Expression::Update(UpdateExpression {
+                    operator: UpdateExpressionOperator::PostfixIncrement,
+                    argument: Box::new(identifier(&index)),
+                })),
+                body: Box::new(loop_body(*f.left, value, *f.body)),
+            });
+            *statement = add_labels(labels, lowered);
+            return;
+        }
+
+        let iterator = self.temp();
+        let step = self.temp();
+        let close_name = self.temp();
+        let error = self.temp();
+        let close = &close_name;
+        let next = || call(member(identifier(&iterator), "next"), Vec::new());
+        let lowered = Statement::ForStatement(ForStatement {
+            init: Some(Box::new(Statement::VariableDeclaration(
+                VariableDeclaration {
+                    kind: VariableDeclarationKind::Var,
+                    declarations: vec![
+                        declarator(
+                            &iterator,
+                            Some(call(self.use_helper(VALUES), vec![f.right])),
+                        ),
+                        declarator(&step, Some(next())),
+                        declarator(close, None),
+                        declarator(&error, None),
+                    ],
+                },
+            ))),
+            test: Some(not(member(identifier(&step), "done"))),
+            update: Some(assign(&step, next())),
+            body: Box::new(loop_body(
+                *f.left,
+                member(identifier(&step), "value"),
+                *f.body,
+            )),
+        });
+
+        let is_open = logical(
+            logical(
+                identifier(&step),
+                LogicalExpressionOperator::And,
+                not(member(identifier(&step), "done")),
+            ),
+            LogicalExpressionOperator::And,
+            assign(close, member(identifier(&iterator), "return")),
+        );
+        let close = Statement::IfStatement(IfStatement {
+            test: is_open,
+            consequent: Box::new(expression_statement(call(
+                member(identifier(close), "call"),
+                vec![identifier(&iterator)],
+            ))),
+            alternate: None,
+        });
+        let lowered = add_labels(labels, lowered);
+        *statement = close_on_exit(lowered, close, &close_name, &error);
+    }
+
+    /// Returns the arguments of a call with spread arguments as a single array,
+    /// for `apply`.
+    fn spread_arguments(&mut self, arguments: Vec<ArgumentKind>) -> Expression {
+        let mut items: Vec<Option<ArrayExpressionItem>> = arguments
+            .into_iter()
+            .map(|argument| match argument {
+                ArgumentKind::Expression(e) => Some(ArrayExpressionItem::Expression(e)),
+                ArgumentKind::Spread(s) => Some(ArrayExpressionItem::Spread(s)),
+            })
+            .collect();
+        if let [Some(ArrayExpressionItem::Spread(_))] = &items[..] {
+            return match items.pop() {
+                Some(Some(ArrayExpressionItem::Spread(s))) => self.spread_value(s.element),
+                _ => unreachable!("There is one spread argument"),
+            };
+        }
+        let mut array = ArrayExpression { items };
+        self.lower_array(&mut array)
+            .unwrap_or(Expression::Array(array))
+    }
+
+    /// Returns a spread value as an array, `arguments` is not an array even
+    /// when the values are assumed to be arrays.
+    pub(crate) fn spread_value(&mut self, value: Expression) -> Expression {
+        match value {
+            value @ Expression::Array(_) => value,
+            Expression::Identifier(i) if i.name == "arguments" => {
+                call(self.use_helper(TO_ARRAY), vec![Expression::Identifier(i)])
+            }
+            value if self.assume_arrays => value,
+            value => call(self.use_helper(TO_ARRAY), vec![value]),
+        }
+    }
+
+    /// `a.b(...c)` becomes `a.b.apply(a, __toArray(c))` and `new A(...b)` becomes
+    /// `new (Function.prototype.bind.apply(A, [null].concat(__toArray(b))))()`.
+    pub(crate) fn lower_spread_arguments(
+        &mut self,
+        expression: &mut Expression,
+    ) -> Option<Expression> {
+        match expression {
+            Expression::Call(c) => {
+                let callee = std::mem::replace(&mut *c.callee, void_zero());
+                let arguments = self.spread_arguments(std::mem::take(&mut c.arguments));
+                let (callee, this) = match callee {
+                    Expression::Member(m) => {
+                        let (object, this) = self.reuse_value(*m.object);
+                        let callee = Expression::Member(MemberExpression {
+                            object: Box::new(object),
+                            property: m.property,
+                            computed: m.computed,
+                        });
+                        (callee, this)
+                    }
+                    callee => (callee, void_zero()),
+                };
+                Some(call(member(callee, "apply"), vec![this, arguments]))
+            }
+            Expression::New(n) => {
+                let callee = std::mem::replace(&mut *n.callee, void_zero());
+                let mut arguments = vec![ArgumentKind::Expression(Expression::NullLiteral(
+                    NullLiteral {},
+                ))];
+                arguments.append(&mut n.arguments);
+                let arguments = self.spread_arguments(arguments);
+                let bind = member(
+                    member(member(identifier("Function"), "prototype"), "bind"),
+                    "apply",
+                );
+                // A parenthesized expression, the constructor is the result of the call.
+                let constructor = Expression::Sequence(SequenceExpression {
+                    expressions: vec![call(bind, vec![callee, arguments])],
+                });
+                Some(Expression::New(NewExpression {
+                    callee: Box::new(constructor),
+                    arguments: Vec::new(),
+                    pure: n.pure,
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the `for-of` loop of a statement, which may be labeled.
+pub(crate) fn for_of_loop(statement: &Statement) -> Option<&ForOfStatement> {
+    match statement {
+        Statement::LabeledStatement(l) => for_of_loop(&l.body),
+        Statement::ForOfStatement(f) => Some(f),
+        _ => None,
+    }
+}
+
+/// Takes the `for-of` loop out of a statement and returns it with its labels.
+pub(crate) fn split_labels(statement: &mut Statement) -> (Vec<Identifier>, ForOfStatement) {
+    let mut labels = Vec::new();
+    let mut current = std::mem::replace(statement, Statement::EmptyStatement(EmptyStatement {}));
+    while let Statement::LabeledStatement(l) = current {
+        labels.push(l.identifier);
+        current = *l.body;
+    }
+    match current {
+        Statement::ForOfStatement(f) => (labels, f),
+        _ => unreachable!("Only for-of loops are lowered"),
+    }
+}
+
+/// Labels the loop that a labeled loop is lowered to.
+pub(crate) fn add_labels(labels: Vec<Identifier>, statement: Statement) -> Statement {
+    labels
+        .into_iter()
+        .rev()
+        .fold(statement, |body, identifier| {
+            Statement::LabeledStatement(LabeledStatement {
+                identifier,
+                body: Box::new(body),
+            })
+        })
+}
+
+/// Returns the body of a lowered loop, it starts by assigning the value to the
+/// left side of the loop.
+pub(crate) fn loop_body(left: Statement, value: Expression, body: Statement) -> Statement {
+    let left = match left {
+        Statement::VariableDeclaration(mut v) => {
+            v.declarations[0].initializer = Some(value);
+            Statement::VariableDeclaration(v)
+        }
+        Statement::Expression(ExpressionStatement {
+            expression: Expression::Identifier(i),
+        }) => expression_statement(assign(&i.name, value)),
+        Statement::Expression(e) => {
+            expression_statement(Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Expression(Box::new(e.expression)),
+                operator: AssignmentExpressionOperator::Assign,
+                right: Box::new(value),
+            }))
+        }
+        left => left,
+    };
+    let statements = match body {
+        Statement::BlockStatement(mut b) => {
+            b.statements.insert(0, left);
+            b.statements
+        }
+        body => vec![left, body],
+    };
+    Statement::BlockStatement(BlockStatement { statements })
+}
+
+/// Runs the loop in a try statement that closes the iterator when the loop is left.
+/// An exception thrown by the loop is caught in a temporary variable and stored in
+/// `error`, it is thrown again after the iterator is closed.
+pub(crate) fn close_on_exit(
+    lowered: Statement,
+    close: Statement,
+    caught: &str,
+    error: &str,
+) -> Statement {
+    let rethrow = Statement::IfStatement(IfStatement {
+        test: identifier(error),
+        consequent: Box::new(Statement::ThrowStatement(ThrowStatement {
+            argument: computed_member(identifier(error), number(0.0)),
+        })),
+        alternate: None,
+    });
+    Statement::TryStatement(TryStatement {
+        block: BlockStatement {
+            statements: vec![lowered],
+        },
+        handler: Some(CatchClause {
+            param: Binding::Identifier(Identifier {
+                name: caught.to_string(),
+            }),
+            body: BlockStatement {
+                statements: vec![expression_statement(assign(
+                    error,
+                    Expression::Array(ArrayExpression {
+                        items: vec![Some(ArrayExpressionItem::Expression(identifier(caught)))],
+                    }),
+                ))],
+            },
+        }),
+        finalizer: Some(BlockStatement {
+            statements: vec![Statement::TryStatement(TryStatement {
+                block: BlockStatement {
+                    statements: vec![close],
+                },
+                handler: None,
+                finalizer: Some(BlockStatement {
+                    statements: vec![rethrow],
+                }),
+            })],
+        }),
+    })
+}
+
+fn not(argument: Expression) -> Expression {
+    Expression::Unary(UnaryExpression {
+        operator: UnaryExpressionOperator::LogicalNot,
+        argument: Box::new(argument),
+    })
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(ExpressionStatement { expression })
+}
@@ -0,0 +1,165 @@
+/// This crate rewrites syntax that is newer than the target environment
+/// into older syntax that does the same thing, so that the output can run
+/// in older browsers.
+///
+/// The lowering is driven by a `Target`, syntax supported by the target is
+/// left as it is. Syntax that can not be lowered yet is reported as an error
+/// instead of being passed through to an environment that can not run it.
+///
+/// Some of the lowered forms rely on small helper functions, such as
+/// `__assign` for object spread. The helpers used by the program are
+/// returned by the lowering and are declared with `helper_statements`, or
+/// after the imports of the program with `insert_helpers`.
+use std::fmt;
+
+use js_ast::visit::VisitorMut;
+use js_ast::AST;
+
+mod async_function;
+mod block_scope;
+mod class;
+mod destructuring;
+mod generator;
+mod helpers;
+mod iterators;
+mod lower;
+mod regexp;
+
+pub use helpers::{helper_statements, insert_helpers};
+
+/// The version of JavaScript that the output should be able to run in.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+pub enum Target {
+    ES5,
+    ES2015,
+    ES2016,
+    ES2017,
+    ES2018,
+    ES2019,
+    ES2020,
+    ES2021,
+    ES2022,
+    /// The latest version, nothing is lowered.
+    #[default]
+    ESNext,
+}
+
+impl Target {
+    /// Returns the target with the name, such as "es5" or "es2015".
+    pub fn from_name(name: &str) -> Option<Target> {
+        let target = match name {
+            "es5" => Target::ES5,
+            "es2015" | "es6" => Target::ES2015,
+            "es2016" => Target::ES2016,
+            "es2017" => Target::ES2017,
+            "es2018" => Target::ES2018,
+            "es2019" => Target::ES2019,
+            "es2020" => Target::ES2020,
+            "es2021" => Target::ES2021,
+            "es2022" => Target::ES2022,
+            "esnext" => Target::ESNext,
+            _ => return None,
+        };
+        Some(target)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::ES5 => "es5",
+            Target::ES2015 => "es2015",
+            Target::ES2016 => "es2016",
+            Target::ES2017 => "es2017",
+            Target::ES2018 => "es2018",
+            Target::ES2019 => "es2019",
+            Target::ES2020 => "es2020",
+            Target::ES2021 => "es2021",
+            Target::ES2022 => "es2022",
+            Target::ESNext => "esnext",
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The options of the lowering.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct LoweringOptions {
+    pub target: Target,
+    /// Assumes that the values iterated by `for-of` loops and spread are arrays.
+    /// They are then lowered to indexed loops and `concat` calls instead of going
+    /// through their iterators, which gives smaller output.
+    pub assume_arrays: bool,
+}
+
+/// An error found while lowering the program.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LoweringError {
+    /// Syntax in the program that is not supported by the target and can not be lowered.
+    Unsupported {
+        /// The syntax that could not be lowered, such as "generator functions".
+        feature: &'static str,
+        target: Target,
+    },
+    /// An assignment to a constant. Constants are lowered to variables, so
+    /// the assignment would no longer throw when the program runs.
+    AssignmentToConstant { name: String },
+}
+
+impl fmt::Display for LoweringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoweringError::Unsupported { feature, target } => write!(
+                f,
+                "Transforming {} to the configured target environment (\"{}\") is not supported yet",
+                feature, target
+            ),
+            LoweringError::AssignmentToConstant { name } => {
+                write!(f, "Cannot assign to \"{}\" because it is a constant", name)
+            }
+        }
+    }
+}
+
+/// Lowers the syntax of the program that is not supported by the target.
+///
+/// Returns the names of the helpers that the lowered program uses, they are
+/// not declared by the program and have to be added with `helper_statements`
+/// or `insert_helpers`.
+/// Every kind of syntax that could not be lowered, and every constant that
+/// is assigned to, is reported once.
+pub fn lower_syntax(
+    program: &mut AST,
+    target: Target,
+) -> Result<Vec<&'static str>, Vec<LoweringError>> {
+    let options = LoweringOptions {
+        target,
+        ..LoweringOptions::default()
+    };
+    lower_syntax_with_options(program, options)
+}
+
+/// Lowers the syntax of the program like `lower_syntax`, with options.
+pub fn lower_syntax_with_options(
+    program: &mut AST,
+    options: LoweringOptions,
+) -> Result<Vec<&'static str>, Vec<LoweringError>> {
+    let target = options.target;
+    if target == Target::ESNext {
+        return Ok(Vec::new());
+    }
+
+    let mut lowerer = lower::Lowerer::new(program, options);
+    if target < Target::ES2015 {
+        lowerer.lower_block_scoping(program);
+    }
+    lowerer.visit_program(program);
+    if lowerer.errors.is_empty() {
+        Ok(lowerer.helpers())
+    } else {
+        Err(lowerer.errors)
+    }
+}
@@ -0,0 +1,1264 @@
+/// This file contains the lowering pass, it visits the program bottom up
+/// and replaces the nodes that the target does not support.
+///
+/// Arrow functions become function expressions. Since arrow functions see the
+/// `this` and `arguments` of the function around them, they are stored in
+/// variables declared at the top of that function when they are used by a
+/// lowered arrow function.
+///
+/// Expressions that read a value more than once, such as `a() ?? b` which becomes
+/// `(_a = a()) != null ? _a : b`, store it in a temporary variable. Temporary
+/// variables are declared at the top of the function that uses them, their names
+/// are chosen to not collide with any name used by the program.
+use std::collections::HashSet;
+
+use js_ast::scope::binding_names;
+use js_ast::visit::{
+    walk_arrow_function, walk_expression, walk_function, walk_statement, VisitorMut,
+};
+use js_ast::*;
+
+use crate::async_function::is_for_await;
+use crate::helpers::{ASSIGN, HELPERS};
+use crate::iterators::for_of_loop;
+use crate::regexp::lower_regexp;
+use crate::{LoweringError, LoweringOptions, Target};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ScopeKind {
+    Program,
+    Function,
+    Arrow { lowered: bool },
+}
+
+/// A function, or the top level of the program, that the variables
+/// created by the lowering are declared in.
+struct FunctionScope {
+    kind: ScopeKind,
+    /// The variable holding `this` for the lowered arrow functions in the function.
+    this_name: Option<String>,
+    /// The variable holding `arguments` for the lowered arrow functions in the function.
+    arguments_name: Option<String>,
+    temps: Vec<String>,
+}
+
+impl FunctionScope {
+    fn new(kind: ScopeKind) -> FunctionScope {
+        FunctionScope {
+            kind,
+            this_name: None,
+            arguments_name: None,
+            temps: Vec::new(),
+        }
+    }
+}
+
+pub(crate) struct Lowerer {
+    pub(crate) target: Target,
+    pub(crate) assume_arrays: bool,
+    pub(crate) errors: Vec<LoweringError>,
+    /// All of the names used by the program, generated names never collide with them.
+    used_names: HashSet<String>,
+    next_temp: usize,
+    scopes: Vec<FunctionScope>,
+    used_helpers: Vec<&'static str>,
+}
+
+impl Lowerer {
+    pub(crate) fn new(program: &mut AST, options: LoweringOptions) -> Lowerer {
+        let mut collector = NameCollector {
+            names: HashSet::new(),
+        };
+        collector.visit_program(program);
+
+        Lowerer {
+            target: options.target,
+            assume_arrays: options.assume_arrays,
+            errors: Vec::new(),
+            used_names: collector.names,
+            next_temp: 0,
+            scopes: Vec::new(),
+            used_helpers: Vec::new(),
+        }
+    }
+
+    /// Returns the helpers used by the lowered program, in the order of `HELPERS`.
+    pub(crate) fn helpers(&self) -> Vec<&'static str> {
+        HELPERS
+            .iter()
+            .filter(|helper| self.used_helpers.contains(helper))
+            .copied()
+            .collect()
+    }
+
+    /// Returns a reference to the helper and records that it is used.
+    pub(crate) fn use_helper(&mut self, helper: &'static str) -> Expression {
+        if !self.used_helpers.contains(&helper) {
+            self.used_helpers.push(helper);
+        }
+        identifier(helper)
+    }
+
+    /// Reports syntax that the target does not support and that can not be lowered.
+    pub(crate) fn unsupported(&mut self, feature: &'static str) {
+        let error = LoweringError::Unsupported {
+            feature,
+            target: self.target,
+        };
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Reports an assignment to a constant.
+    pub(crate) fn assignment_to_constant(&mut self, name: &str) {
+        let error = LoweringError::AssignmentToConstant {
+            name: name.to_string(),
+        };
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Returns a new name for a temporary variable, `_a`, `_b` and so on.
+    pub(crate) fn temp(&mut self) -> String {
+        loop {
+            let letter = (b'a' + (self.next_temp % 26) as u8) as char;
+            let name = match self.next_temp / 26 {
+                0 => format!("_{}", letter),
+                round => format!("_{}{}", letter, round),
+            };
+            self.next_temp += 1;
+            if self.used_names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    /// Returns a new temporary variable declared in the current function.
+    pub(crate) fn declare_temp(&mut self) -> String {
+        let name = self.temp();
+        self.scopes
+            .last_mut()
+            .expect("There is always a scope")
+            .temps
+            .push(name.clone());
+        name
+    }
+
+    /// Returns the value twice, the first one evaluates the value and the second
+    /// one reads the result again. A temporary variable holds the value unless it
+    /// can be evaluated twice, like a variable.
+    pub(crate) fn reuse_value(&mut self, value: Expression) -> (Expression, Expression) {
+        match value {
+            Expression::Identifier(_)
+            | Expression::This(_)
+            | Expression::NullLiteral(_)
+            | Expression::BooleanLiteral(_)
+            | Expression::NumericLiteral(_)
+            | Expression::StringLiteral(_) => (value.clone(), value),
+            value => {
+                let name = self.declare_temp();
+                (assign(&name, value), identifier(&name))
+            }
+        }
+    }
+
+    pub(crate) fn generate_name(&mut self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut count = 2;
+        while self.used_names.contains(&name) {
+            name = format!("{}{}", base, count);
+            count += 1;
+        }
+        self.used_names.insert(name.clone());
+        name
+    }
+
+    fn in_lowered_arrow(&self) -> bool {
+        matches!(
+            self.scopes.last().map(|scope| scope.kind),
+            Some(ScopeKind::Arrow { lowered: true })
+        )
+    }
+
+    /// Returns the variable holding `this`, or `arguments`, of the function
+    /// around the current arrow function.
+    fn capture(&mut self, this: bool) -> String {
+        let index = self
+            .scopes
+            .iter()
+            .rposition(|scope| !matches!(scope.kind, ScopeKind::Arrow { .. }))
+            .expect("The program scope is never an arrow function");
+        let existing = match this {
+            true => self.scopes[index].this_name.clone(),
+            false => self.scopes[index].arguments_name.clone(),
+        };
+        if let Some(name) = existing {
+            return name;
+        }
+
+        let name = self.generate_name(if this { "_this" } else { "_arguments" });
+        match this {
+            true => self.scopes[index].this_name = Some(name.clone()),
+            false => self.scopes[index].arguments_name = Some(name.clone()),
+        }
+        name
+    }
+
+    /// Returns the variable holding `arguments` of the function around the current
+    /// arrow function. Arrow functions at the top level of the program have no
+    /// `arguments` to capture, they are reported instead.
+    fn capture_arguments(&mut self) -> Option<String> {
+        let in_function = self
+            .scopes
+            .iter()
+            .any(|scope| scope.kind == ScopeKind::Function);
+        if !in_function {
+            self.unsupported("arguments in top-level arrow functions");
+            return None;
+        }
+        Some(self.capture(false))
+    }
+
+    fn lower_function(&mut self, parameters: &mut Vec<ParameterKind>, body: &mut BlockStatement) {
+        self.scopes.push(FunctionScope::new(ScopeKind::Function));
+        walk_function(self, parameters, body);
+        let prologue = self.lower_parameters(parameters);
+        let scope = self.scopes.pop().expect("The scope was pushed above");
+        add_prologue(&mut body.statements, scope, prologue);
+    }
+
+    fn lower_arrow_function(&mut self, expression: &mut Expression) {
+        let arrow = match expression {
+            Expression::ArrowFunction(a) => a,
+            _ => return,
+        };
+
+        let lowered = self.target < Target::ES2015;
+        self.scopes
+            .push(FunctionScope::new(ScopeKind::Arrow { lowered }));
+        walk_arrow_function(self, arrow);
+        let prologue = self.lower_parameters(&mut arrow.parameters);
+        let scope = self.scopes.pop().expect("The scope was pushed above");
+
+        // Declarations need a block body.
+        let needs_block = lowered
+            || !prologue.is_empty()
+            || !scope.temps.is_empty()
+            || scope.this_name.is_some()
+            || scope.arguments_name.is_some();
+        if needs_block {
+            if let ArrowFunctionExpressionBody::Expression(e) = &mut arrow.body {
+                let expression = std::mem::replace(&mut **e, void_zero());
+                arrow.body = ArrowFunctionExpressionBody::BlockStatement(BlockStatement {
+                    statements: vec![Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(expression),
+                    })],
+                });
+            }
+            if let ArrowFunctionExpressionBody::BlockStatement(b) = &mut arrow.body {
+                add_prologue(&mut b.statements, scope, prologue);
+            }
+        }
+
+        if lowered {
+            if let ArrowFunctionExpressionBody::BlockStatement(b) = &mut arrow.body {
+                *expression = Expression::Function(FunctionExpression {
+                    identifier: None,
+                    parameters: std::mem::take(&mut arrow.parameters),
+                    body: std::mem::replace(
+                        b,
+                        BlockStatement {
+                            statements: Vec::new(),
+                        },
+                    ),
+                    generator: false,
+                    is_async: arrow.is_async,
+                });
+            }
+        }
+    }
+
+    /// Lowers the body of an async function or a generator function that the target
+    /// does not support, the parameters stay as they are.
+    fn lower_function_kind(
+        &mut self,
+        is_async: &mut bool,
+        generator: &mut bool,
+        body: &mut BlockStatement,
+    ) {
+        if *is_async && *generator {
+            if self.target < Target::ES2018 {
+                self.unsupported("async generator functions");
+            }
+            return;
+        }
+        if *is_async && self.target < Target::ES2017 {
+            self.lower_async_body(body);
+            *is_async = false;
+        }
+        if *generator && self.target < Target::ES2015 {
+            self.lower_generator_body(body);
+            *generator = false;
+        }
+    }
+
+    /// Lowers the bodies of the async and generator methods of a class like
+    /// the bodies of functions.
+    fn lower_class_method_kinds(&mut self, body: &mut [ClassPropertyKind]) {
+        for property in body {
+            match property {
+                ClassPropertyKind::Method(m) => {
+                    self.lower_function_kind(&mut m.is_async, &mut m.generator, &mut m.body)
+                }
+                ClassPropertyKind::MethodComputed(m) => {
+                    self.lower_function_kind(&mut m.is_async, &mut m.generator, &mut m.body)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Lowers the bodies of the async and generator methods of an object literal.
+    fn lower_object_method_kinds(&mut self, object: &mut ObjectExpression) {
+        for property in object.properties.iter_mut() {
+            match property {
+                ObjectExpressionPropertyKind::Method(m) => {
+                    self.lower_function_kind(&mut m.is_async, &mut m.generator, &mut m.body)
+                }
+                ObjectExpressionPropertyKind::MethodComputed(m) => {
+                    self.lower_function_kind(&mut m.is_async, &mut m.generator, &mut m.body)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `async (a) => b` becomes `(a) => __async(this, null, function* () { return b; })`.
+    fn lower_async_arrow(&mut self, arrow: &mut ArrowFunctionExpression) {
+        let mut body = match std::mem::replace(
+            &mut arrow.body,
+            ArrowFunctionExpressionBody::Expression(Box::new(void_zero())),
+        ) {
+            ArrowFunctionExpressionBody::BlockStatement(b) => b,
+            ArrowFunctionExpressionBody::Expression(e) => BlockStatement {
+                statements: vec![Statement::ReturnStatement(ReturnStatement {
+                    expression: Some(*e),
+                })],
+            },
+        };
+        self.lower_async_body(&mut body);
+        arrow.is_async = false;
+        arrow.body = match body.statements.pop() {
+            Some(Statement::ReturnStatement(ReturnStatement {
+                expression: Some(e),
+            })) if body.statements.is_empty() => {
+                ArrowFunctionExpressionBody::Expression(Box::new(e))
+            }
+            statement => {
+                body.statements.extend(statement);
+                ArrowFunctionExpressionBody::BlockStatement(body)
+            }
+        };
+    }
+
+    /// `a ?? b` becomes `a != null ? a : b`.
+    fn lower_nullish(&mut self, left: Expression, right: Expression) -> Expression {
+        let (test, value) = self.reuse_value(left);
+        conditional(
+            binary(
+                test,
+                BinaryExpressionOperator::LooseNotEquals,
+                Expression::NullLiteral(NullLiteral {}),
+            ),
+            value,
+            right,
+        )
+    }
+
+    /// Returns a target of an assignment twice, one reading it and one assigning it.
+    /// The object and the computed key of a member expression are only evaluated by
+    /// the first one of them that runs, decided by `write_first`.
+    fn reference_twice(
+        &mut self,
+        left: AssignmentExpressionLeft,
+        write_first: bool,
+    ) -> (Expression, AssignmentExpressionLeft) {
+        let target = match left {
+            AssignmentExpressionLeft::Binding(Binding::Identifier(i)) => Expression::Identifier(i),
+            AssignmentExpressionLeft::Binding(b) => {
+                return (void_zero(), AssignmentExpressionLeft::Binding(b))
+            }
+            AssignmentExpressionLeft::Expression(e) => *e,
+        };
+
+        match target {
+            Expression::Identifier(i) => (
+                Expression::Identifier(i.clone()),
+                AssignmentExpressionLeft::Binding(Binding::Identifier(i)),
+            ),
+            Expression::Member(m) => {
+                let (object_first, object_second) = self.reuse_value(*m.object);
+                let (property_first, property_second) = match m.computed {
+                    true => self.reuse_value(*m.property),
+                    false => (*m.property.clone(), *m.property),
+                };
+                let first = Expression::Member(MemberExpression {
+                    object: Box::new(object_first),
+                    property: Box::new(property_first),
+                    computed: m.computed,
+                });
+                let second = Expression::Member(MemberExpression {
+                    object: Box::new(object_second),
+                    property: Box::new(property_second),
+                    computed: m.computed,
+                });
+                match write_first {
+                    true => (
+                        second,
+                        AssignmentExpressionLeft::Expression(Box::new(first)),
+                    ),
+                    false => (
+                        first,
+                        AssignmentExpressionLeft::Expression(Box::new(second)),
+                    ),
+                }
+            }
+            target => (
+                target.clone(),
+                AssignmentExpressionLeft::Expression(Box::new(target)),
+            ),
+        }
+    }
+
+    fn lower_assignment_operator(&mut self, expression: &mut Expression) {
+        let a = match expression {
+            Expression::Assignment(a) => a,
+            _ => return,
+        };
+
+        let lowered = match a.operator {
+            AssignmentExpressionOperator::ExponentiationAssign => self.target < Target::ES2016,
+            AssignmentExpressionOperator::NullishCoalescingAssign
+            | AssignmentExpressionOperator::LogicalOrAssign
+            | AssignmentExpressionOperator::LogicalAndAssign => self.target < Target::ES2021,
+            _ => false,
+        };
+        if !lowered {
+            return;
+        }
+
+        let left = std::mem::replace(
+            &mut a.left,
+            AssignmentExpressionLeft::Expression(Box::new(void_zero())),
+        );
+        let right = std::mem::replace(&mut *a.right, void_zero());
+        *expression = match a.operator {
+            // a **= b becomes a = Math.pow(a, b)
+            AssignmentExpressionOperator::ExponentiationAssign => {
+                let (read, write) = self.reference_twice(left, true);
+                Expression::Assignment(AssignmentExpression {
+                    left: write,
+                    operator: AssignmentExpressionOperator::Assign,
+                    right: Box::new(math_pow(read, right)),
+                })
+            }
+            // a ||= b becomes a || (a = b)
+            ref operator => {
+                let (read, write) = self.reference_twice(left, false);
+                let assignment = Expression::Assignment(AssignmentExpression {
+                    left: write,
+                    operator: AssignmentExpressionOperator::Assign,
+                    right: Box::new(right),
+                });
+                match operator {
+                    AssignmentExpressionOperator::LogicalOrAssign => {
+                        logical(read, LogicalExpressionOperator::Or, assignment)
+                    }
+                    AssignmentExpressionOperator::LogicalAndAssign => {
+                        logical(read, LogicalExpressionOperator::And, assignment)
+                    }
+                    _ if self.target < Target::ES2020 => self.lower_nullish(read, assignment),
+                    _ => logical(
+                        read,
+                        LogicalExpressionOperator::NullishCoalescing,
+                        assignment,
+                    ),
+                }
+            }
+        };
+    }
+
+    /// Lowers the properties of an object literal that the target does not support.
+    fn lower_object(&mut self, object: &mut ObjectExpression) -> Option<Expression> {
+        if self.target < Target::ES2015 {
+            for property in object.properties.iter_mut() {
+                let lowered = match property {
+                    // ({ a }) becomes ({ a: a })
+                    ObjectExpressionPropertyKind::Shorthand(s) => {
+                        let mut value = identifier(&s.key.name);
+                        self.visit_expression(&mut value);
+                        ObjectExpressionPropertyKind::Property(ObjectExpressionProperty {
+                            key: LiteralPropertyName::Identifier(s.key.clone()),
+                            value,
+                        })
+                    }
+                    // ({ a() {} }) becomes ({ a: function() {} })
+                    ObjectExpressionPropertyKind::Method(m) => {
+                        ObjectExpressionPropertyKind::Property(ObjectExpressionProperty {
+                            key: m.key.clone(),
+                            value: function(
+                                &mut m.parameters,
+                                &mut m.body,
+                                m.is_async,
+                                m.generator,
+                            ),
+                        })
+                    }
+                    ObjectExpressionPropertyKind::MethodComputed(m) => {
+                        ObjectExpressionPropertyKind::Computed(ObjectExpressionPropertyComputed {
+                            key: std::mem::replace(&mut m.key, void_zero()),
+                            value: function(
+                                &mut m.parameters,
+                                &mut m.body,
+                                m.is_async,
+                                m.generator,
+                            ),
+                        })
+                    }
+                    ObjectExpressionPropertyKind::MethodGetComputed(_)
+                    | ObjectExpressionPropertyKind::MethodSetComputed(_) => {
+                        self.unsupported("computed getters and setters");
+                        continue;
+                    }
+                    _ => continue,
+                };
+                *property = lowered;
+            }
+        }
+
+        let has_spread = object
+            .properties
+            .iter()
+            .any(|p| matches!(p, ObjectExpressionPropertyKind::Spread(_)));
+        if has_spread && self.target < Target::ES2018 {
+            // ({ a, ...b }) becomes __assign({ a }, b)
+            let mut arguments = Vec::new();
+            let mut properties = Vec::new();
+            for property in std::mem::take(&mut object.properties) {
+                match property {
+                    ObjectExpressionPropertyKind::Spread(s) => {
+                        if !properties.is_empty() || arguments.is_empty() {
+                            let properties = std::mem::take(&mut properties);
+                            arguments.push(self.object_with_computed_keys(properties));
+                        }
+                        arguments.push(s.element);
+                    }
+                    property => properties.push(property),
+                }
+            }
+            if !properties.is_empty() {
+                arguments.push(self.object_with_computed_keys(properties));
+            }
+            return Some(call(self.use_helper(ASSIGN), arguments));
+        }
+
+        let has_computed = object
+            .properties
+            .iter()
+            .any(|p| matches!(p, ObjectExpressionPropertyKind::Computed(_)));
+        if has_computed && self.target < Target::ES2015 {
+            let properties = std::mem::take(&mut object.properties);
+            return Some(self.object_with_computed_keys(properties));
+        }
+        None
+    }
+
+    /// Returns an object literal with the properties, computed keys are assigned after
+    /// the object is created for targets that do not support them.
+    ///
+    /// `({ a: 1, [b]: 2, c: 3 })` becomes `(_a = { a: 1 }, _a[b] = 2, _a.c = 3, _a)`.
+    fn object_with_computed_keys(
+        &mut self,
+        properties: Vec<ObjectExpressionPropertyKind>,
+    ) -> Expression {
+        let first_computed = properties
+            .iter()
+            .position(|p| matches!(p, ObjectExpressionPropertyKind::Computed(_)));
+        let first_computed = match first_computed {
+            Some(index) if self.target < Target::ES2015 => index,
+            _ => return Expression::Object(ObjectExpression { properties }),
+        };
+
+        let mut properties = properties;
+        let rest = properties.split_off(first_computed);
+        let name = self.declare_temp();
+        let mut expressions = vec![assign(
+            &name,
+            Expression::Object(ObjectExpression { properties }),
+        )];
+        for property in rest {
+            let (target, value) = match property {
+                ObjectExpressionPropertyKind::Computed(c) => {
+                    (computed_member(identifier(&name), c.key), c.value)
+                }
+                ObjectExpressionPropertyKind::Property(p) => {
+                    let target = match p.key {
+                        LiteralPropertyName::Identifier(i) => member(identifier(&name), &i.name),
+                        LiteralPropertyName::String(s) => {
+                            computed_member(identifier(&name), Expression::StringLiteral(s))
+                        }
+                        LiteralPropertyName::Numeric(n) => {
+                            computed_member(identifier(&name), Expression::NumericLiteral(n))
+                        }
+                    };
+                    (target, p.value)
+                }
+                _ => {
+                    self.unsupported("getters and setters after computed properties");
+                    continue;
+                }
+            };
+            expressions.push(Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Expression(Box::new(target)),
+                operator: AssignmentExpressionOperator::Assign,
+                right: Box::new(value),
+            }));
+        }
+        expressions.push(identifier(&name));
+        Expression::Sequence(SequenceExpression { expressions })
+    }
+
+    /// `[a, ...b]` becomes `[a].concat(__toArray(b))`.
+    pub(crate) fn lower_array(&mut self, array: &mut ArrayExpression) -> Option<Expression> {
+        let has_spread = array
+            .items
+            .iter()
+            .any(|item| matches!(item, Some(ArrayExpressionItem::Spread(_))));
+        if !has_spread || self.target >= Target::ES2015 {
+            return None;
+        }
+
+        // The first array is the one that concat is called on.
+        let starts_with_spread = matches!(
+            array.items.first(),
+            Some(Some(ArrayExpressionItem::Spread(_)))
+        );
+        let mut arguments = Vec::new();
+        let mut items = Vec::new();
+        for item in std::mem::take(&mut array.items) {
+            match item {
+                Some(ArrayExpressionItem::Spread(s)) => {
+                    if !items.is_empty() {
+                        arguments.push(Expression::Array(ArrayExpression {
+                            items: std::mem::take(&mut items),
+                        }));
+                    }
+                    arguments.push(self.spread_value(s.element));
+                }
+                item => items.push(item),
+            }
+        }
+        if !items.is_empty() {
+            arguments.push(Expression::Array(ArrayExpression { items }));
+        }
+
+        let first = match starts_with_spread {
+            true => Expression::Array(ArrayExpression { items: Vec::new() }),
+            false => arguments.remove(0),
+        };
+        Some(call(member(first, "concat"), arguments))
+    }
+}
+
+impl VisitorMut for Lowerer {
+    fn visit_program(&mut self, program: &mut AST) {
+        self.scopes.push(FunctionScope::new(ScopeKind::Program));
+        self.visit_statements(&mut program.statements);
+        let scope = self.scopes.pop().expect("The scope was pushed above");
+        add_prologue(&mut program.statements, scope, Vec::new());
+    }
+
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        let old = std::mem::take(statements);
+        let mut expanded = Vec::new();
+        for statement in old {
+            match statement {
+                Statement::ExportDefaultDeclaration(ExportDefaultDeclaration { declaration })
+                    if self.target < Target::ES2015 =>
+                {
+                    expanded.extend(self.lower_default_exported_class(declaration))
+                }
+                statement => expanded.push(statement),
+            }
+        }
+
+        for mut statement in expanded {
+            self.visit_statement(&mut statement);
+            match statement {
+                // export var { a } = b; becomes var a = b.a; export { a };
+                Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                    declaration: ExportNamedDeclarationKind::VariableDeclaration(mut v),
+                }) if v
+                    .declarations
+                    .iter()
+                    .any(|d| self.needs_lowering(&d.binding)) =>
+                {
+                    let mut names = Vec::new();
+                    for declarator in &v.declarations {
+                        binding_names(&declarator.binding, &mut names);
+                    }
+                    self.lower_variable_declaration(&mut v);
+                    statements.push(Statement::VariableDeclaration(v));
+                    statements.push(Statement::ExportNamedSpecifiers(ExportNamedSpecifiers {
+                        specifiers: names
+                            .into_iter()
+                            .map(|name| ExportNamedSpecifier {
+                                exported: Identifier { name: name.clone() },
+                                local: Identifier { name },
+                            })
+                            .collect(),
+                        source: None,
+                    }));
+                }
+                statement => statements.push(statement),
+            }
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if self.target < Target::ES2015 {
+            self.lower_class_declaration(statement);
+        }
+        if self.target < Target::ES2018 && is_for_await(statement) {
+            self.lower_for_await(statement);
+        } else if self.target < Target::ES2015 && for_of_loop(statement).is_some() {
+            self.lower_for_of(statement);
+        }
+        match statement {
+            Statement::FunctionDeclaration(f)
+            | Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration: ExportNamedDeclarationKind::FunctionDeclaration(f),
+            })
+            | Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration: ExportDefaultDeclarationKind::FunctionDeclaration(f),
+            }) => self.lower_function_kind(&mut f.is_async, &mut f.generator, &mut f.body),
+            Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration:
+                    ExportDefaultDeclarationKind::AnonymousDefaultExportedFunctionDeclaration(f),
+            }) => self.lower_function_kind(&mut f.is_async, &mut f.generator, &mut f.body),
+            Statement::ClassDeclaration(c)
+            | Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration: ExportNamedDeclarationKind::ClassDeclaration(c),
+            })
+            | Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration: ExportDefaultDeclarationKind::ClassDeclaration(c),
+            }) => self.lower_class_method_kinds(&mut c.body),
+            Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration:
+                    ExportDefaultDeclarationKind::AnonymousDefaultExportedClassDeclaration(c),
+            }) => self.lower_class_method_kinds(&mut c.body),
+            _ => {}
+        }
+
+        // The value of a destructuring assignment is not needed in an expression statement.
+        if let Statement::Expression(ExpressionStatement {
+            expression: Expression::Assignment(a),
+        }) = statement
+        {
+            if let AssignmentExpressionLeft::Binding(binding) = &mut a.left {
+                if self.needs_lowering(binding) {
+                    self.visit_binding(binding);
+                    self.visit_expression(&mut a.right);
+                    let binding = std::mem::replace(
+                        binding,
+                        Binding::Array(ArrayBinding { items: Vec::new() }),
+                    );
+                    let right = std::mem::replace(&mut *a.right, void_zero());
+                    *statement = Statement::Expression(ExpressionStatement {
+                        expression: self.lower_assignment(binding, right, false),
+                    });
+                    return;
+                }
+            }
+        }
+
+        walk_statement(self, statement);
+
+        match statement {
+            Statement::VariableDeclaration(v) => self.lower_variable_declaration(v),
+            Statement::ForInStatement(f) => self.lower_loop_left(&mut f.left, &mut f.body),
+            Statement::ForOfStatement(f) => self.lower_loop_left(&mut f.left, &mut f.body),
+            Statement::TryStatement(t) => {
+                if let Some(handler) = &mut t.handler {
+                    self.lower_catch_clause(handler);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_function(&mut self, parameters: &mut Vec<ParameterKind>, body: &mut BlockStatement) {
+        self.lower_function(parameters, body);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Function(f) = expression {
+            self.lower_function_kind(&mut f.is_async, &mut f.generator, &mut f.body);
+        }
+        if let Expression::ArrowFunction(a) = expression {
+            if a.is_async && self.target < Target::ES2017 {
+                self.lower_async_arrow(a);
+            }
+            self.lower_arrow_function(expression);
+            return;
+        }
+        if let Expression::Class(c) = expression {
+            if self.target < Target::ES2015 {
+                *expression = self.lower_class_expression(c);
+            } else {
+                self.lower_class_method_kinds(&mut c.body);
+            }
+        }
+        if let Expression::Object(o) = expression {
+            if self.target < Target::ES2015 && o.properties.iter_mut().any(method_uses_super) {
+                self.unsupported("super in object methods");
+            }
+            self.lower_object_method_kinds(o);
+        }
+
+        walk_expression(self, expression);
+
+        if let Expression::Assignment(a) = expression {
+            if let AssignmentExpressionLeft::Binding(binding) = &mut a.left {
+                if self.needs_lowering(binding) {
+                    let binding = std::mem::replace(
+                        binding,
+                        Binding::Array(ArrayBinding { items: Vec::new() }),
+                    );
+                    let right = std::mem::replace(&mut *a.right, void_zero());
+                    *expression = self.lower_assignment(binding, right, true);
+                    return;
+                }
+            }
+            self.lower_assignment_operator(expression);
+            return;
+        }
+
+        let lowered = match expression {
+            Expression::This(_) if self.in_lowered_arrow() => Some(identifier(&self.capture(true))),
+            Expression::Identifier(i) if i.name == "arguments" && self.in_lowered_arrow() => {
+                self.capture_arguments().map(|name| identifier(&name))
+            }
+
+            // `a${b}c` becomes "a" + b + "c"
+            Expression::TemplateLiteral(t) if self.target < Target::ES2015 => {
+                let mut result = string(&lower_unicode_escapes(&template_to_string(&t.head)));
+                for part in std::mem::take(&mut t.parts) {
+                    result = binary(result, BinaryExpressionOperator::Addition, part.expression);
+                    if !part.text.is_empty() {
+                        let text = lower_unicode_escapes(&template_to_string(&part.text));
+                        result = binary(result, BinaryExpressionOperator::Addition, string(&text));
+                    }
+                }
+                Some(result)
+            }
+
+            Expression::StringLiteral(s)
+                if self.target < Target::ES2015 && s.value.contains("\\u{") =>
+            {
+                Some(string(&lower_unicode_escapes(&s.value)))
+            }
+
+            // a ** b becomes Math.pow(a, b)
+            Expression::Binary(b)
+                if b.operator == BinaryExpressionOperator::Exponentiation
+                    && self.target < Target::ES2016 =>
+            {
+                let left = std::mem::replace(&mut *b.left, void_zero());
+                let right = std::mem::replace(&mut *b.right, void_zero());
+                Some(math_pow(left, right))
+            }
+
+            Expression::Binary(BinaryExpression {
+                left,
+                operator: BinaryExpressionOperator::NullishCoalescing,
+                right,
+            })
+            | Expression::Logical(LogicalExpression {
+                left,
+                operator: LogicalExpressionOperator::NullishCoalescing,
+                right,
+            }) if self.target < Target::ES2020 => {
+                let left = std::mem::replace(&mut **left, void_zero());
+                let right = std::mem::replace(&mut **right, void_zero());
+                Some(self.lower_nullish(left, right))
+            }
+
+            Expression::Object(o) => self.lower_object(o),
+            Expression::Array(a) => self.lower_array(a),
+
+            Expression::Call(CallExpression { arguments, .. })
+            | Expression::New(NewExpression { arguments, .. })
+                if self.target < Target::ES2015
+                    && arguments
+                        .iter()
+                        .any(|a| matches!(a, ArgumentKind::Spread(_))) =>
+            {
+                self.lower_spread_arguments(expression)
+            }
+
+            Expression::RegexpLiteral(r) => lower_regexp(r, self.target),
+
+            Expression::BigIntLiteral(_) if self.target < Target::ES2020 => {
+                self.unsupported("big integer literals");
+                None
+            }
+
+            _ => None,
+        };
+
+        if let Some(lowered) = lowered {
+            *expression = lowered;
+        }
+    }
+}
+
+/// Adds the declarations of the variables created for the scope, followed by the
+/// statements of the lowered parameters, to the top of a function body or program.
+fn add_prologue(statements: &mut Vec<Statement>, scope: FunctionScope, prologue: Vec<Statement>) {
+    let mut declarations = Vec::new();
+    if let Some(name) = scope.this_name {
+        declarations.push(declarator(&name, Some(Expression::This(ThisExpression {}))));
+    }
+    if let Some(name) = scope.arguments_name {
+        declarations.push(declarator(&name, Some(identifier("arguments"))));
+    }
+    for name in scope.temps {
+        declarations.push(declarator(&name, None));
+    }
+
+    let mut added = Vec::new();
+    if !declarations.is_empty() {
+        added.push(Statement::VariableDeclaration(VariableDeclaration {
+            kind: VariableDeclarationKind::Var,
+            declarations,
+        }));
+    }
+    added.extend(prologue);
+
+    // Directives such as "use strict" have to stay first.
+    let index = statements
+        .iter()
+        .take_while(|statement| {
+            matches!(
+                statement,
+                Statement::Expression(ExpressionStatement {
+                    expression: Expression::StringLiteral(_)
+                })
+            )
+        })
+        .count();
+    statements.splice(index..index, added);
+}
+
+/// Converts the text of a template literal, as written in the source,
+/// to the text of a string literal with the same value.
+fn template_to_string(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('`') => result.push('`'),
+                Some('$') => result.push('$'),
+                // A line continuation is not part of the value.
+                Some('\r') => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                }
+                Some('\n') | Some('\u{2028}') | Some('\u{2029}') => {}
+                Some(c) => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                None => result.push('\\'),
+            },
+            // Line breaks in templates are normalized to \n.
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                result.push_str("\\n");
+            }
+            '\n' => result.push_str("\\n"),
+            '\u{2028}' => result.push_str("\\u2028"),
+            '\u{2029}' => result.push_str("\\u2029"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Replaces the `\u{...}` escapes of the text of a string literal with `\uXXXX`
+/// escapes, code points outside of the basic plane become a surrogate pair.
+fn lower_unicode_escapes(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(index) = rest.find('\\') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let escape = rest[1..].chars().next();
+        let code_point = match escape {
+            Some('u') if rest[2..].starts_with('{') => rest[3..].find('}').and_then(|end| {
+                u32::from_str_radix(&rest[3..3 + end], 16)
+                    .ok()
+                    .map(|c| (c, end))
+            }),
+            _ => None,
+        };
+        match (code_point, escape) {
+            (Some((code_point, end)), _) => {
+                if code_point > 0xFFFF {
+                    let offset = code_point - 0x10000;
+                    let high = 0xD800 + (offset >> 10);
+                    let low = 0xDC00 + (offset & 0x3FF);
+                    result.push_str(&format!("\\u{:04X}\\u{:04X}", high, low));
+                } else {
+                    result.push_str(&format!("\\u{:04X}", code_point));
+                }
+                rest = &rest[4 + end..];
+            }
+            (None, Some(escape)) => {
+                let length = 1 + escape.len_utf8();
+                result.push_str(&rest[..length]);
+                rest = &rest[length..];
+            }
+            (None, None) => {
+                result.push_str(rest);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Returns true if the body of the object method uses `super`, which
+/// only exists in methods.
+fn method_uses_super(property: &mut ObjectExpressionPropertyKind) -> bool {
+    let body = match property {
+        ObjectExpressionPropertyKind::Method(m) => &mut m.body,
+        ObjectExpressionPropertyKind::MethodComputed(m) => &mut m.body,
+        ObjectExpressionPropertyKind::MethodGet(m) => &mut m.body,
+        ObjectExpressionPropertyKind::MethodGetComputed(m) => &mut m.body,
+        ObjectExpressionPropertyKind::MethodSet(m) => &mut m.body,
+        ObjectExpressionPropertyKind::MethodSetComputed(m) => &mut m.body,
+        _ => return false,
+    };
+    let mut finder = SuperFinder { found: false };
+    finder.visit_statements(&mut body.statements);
+    finder.found
+}
+
+/// Finds `super` in a method, arrow functions see the `super` of the method.
+struct SuperFinder {
+    found: bool,
+}
+
+impl VisitorMut for SuperFinder {
+    // Functions and the methods of nested objects and classes have their own `super`.
+    fn visit_function(&mut self, _: &mut Vec<ParameterKind>, _: &mut BlockStatement) {}
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Super(_) = expression {
+            self.found = true;
+        }
+        walk_expression(self, expression);
+    }
+}
+
+/// Collects all of the names used by the program.
+struct NameCollector {
+    names: HashSet<String>,
+}
+
+impl VisitorMut for NameCollector {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::FunctionDeclaration(f) => {
+                self.names.insert(f.identifier.name.clone());
+            }
+            Statement::ClassDeclaration(c) => {
+                self.names.insert(c.identifier.name.clone());
+            }
+            Statement::ImportDeclaration(i) => {
+                let locals = i.default.iter().chain(&i.namespace);
+                let specifiers = i.specifiers.iter().map(|s| &s.local);
+                for local in locals.chain(specifiers) {
+                    self.names.insert(local.name.clone());
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(i) => {
+                self.names.insert(i.name.clone());
+            }
+            Expression::Function(FunctionExpression {
+                identifier: Some(i),
+                ..
+            })
+            | Expression::Class(ClassExpression {
+                identifier: Some(i),
+                ..
+            }) => {
+                self.names.insert(i.name.clone());
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        let mut names = Vec::new();
+        binding_names(binding, &mut names);
+        self.names.extend(names);
+        js_ast::visit::walk_binding(self, binding);
+    }
+
+    fn visit_shorthand_property(&mut self, property: &mut ObjectExpressionPropertyShorthand) {
+        self.names.insert(property.key.name.clone());
+    }
+}
+
+fn function(
+    parameters: &mut Vec<ParameterKind>,
+    body: &mut BlockStatement,
+    is_async: bool,
+    generator: bool,
+) -> Expression {
+    Expression::Function(FunctionExpression {
+        identifier: None,
+        parameters: std::mem::take(parameters),
+        body: std::mem::replace(
+            body,
+            BlockStatement {
+                statements: Vec::new(),
+            },
+        ),
+        generator,
+        is_async,
+    })
+}
+
+pub(crate) fn declarator(name: &str, initializer: Option<Expression>) -> VariableDeclarator {
+    VariableDeclarator {
+        binding: Binding::Identifier(Identifier {
+            name: name.to_string(),
+        }),
+        initializer,
+    }
+}
+
+fn math_pow(left: Expression, right: Expression) -> Expression {
+    call(member(identifier("Math"), "pow"), vec![left, right])
+}
+
+pub(crate) fn binary(
+    left: Expression,
+    operator: BinaryExpressionOperator,
+    right: Expression,
+) -> Expression {
+    Expression::Binary(BinaryExpression {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
+    })
+}
+
+pub(crate) fn logical(
+    left: Expression,
+    operator: LogicalExpressionOperator,
+    right: Expression,
+) -> Expression {
+    Expression::Logical(LogicalExpression {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
+    })
+}
+
+fn conditional(test: Expression, consequence: Expression, alternate: Expression) -> Expression {
+    Expression::Conditional(ConditionalExpression {
+        test: Box::new(test),
+        consequence: Box::new(consequence),
+        alternate: Box::new(alternate),
+    })
+}
+
+pub(crate) fn identifier(name: &str) -> Expression {
+    Expression::Identifier(Identifier {
+        name: name.to_string(),
+    })
+}
+
+pub(crate) fn member(object: Expression, name: &str) -> Expression {
+    Expression::Member(MemberExpression {
+        object: Box::new(object),
+        property: Box::new(identifier(name)),
+        computed: false,
+    })
+}
+
+pub(crate) fn computed_member(object: Expression, property: Expression) -> Expression {
+    Expression::Member(MemberExpression {
+        object: Box::new(object),
+        property: Box::new(property),
+        computed: true,
+    })
+}
+
+pub(crate) fn call(callee: Expression, arguments: Vec<Expression>) -> Expression {
+    Expression::Call(CallExpression {
+        callee: Box::new(callee),
+        arguments: arguments
+            .into_iter()
+            .map(ArgumentKind::Expression)
+            .collect(),
+        pure: false,
+    })
+}
+
+pub(crate) fn assign(name: &str, value: Expression) -> Expression {
+    Expression::Assignment(AssignmentExpression {
+        left: AssignmentExpressionLeft::Binding(Binding::Identifier(Identifier {
+            name: name.to_string(),
+        })),
+        operator: AssignmentExpressionOperator::Assign,
+        right: Box::new(value),
+    })
+}
+
+pub(crate) fn string(value: &str) -> Expression {
+    Expression::StringLiteral(StringLiteral {
+        value: value.to_string(),
+    })
+}
+
+pub(crate) fn number(value: f64) -> Expression {
+    Expression::NumericLiteral(NumericLiteral { value })
+}
+
+pub(crate) fn void_zero() -> Expression {
+    Expression::Unary(UnaryExpression {
+        operator: UnaryExpressionOperator::Void,
+        argument: Box::new(number(0.0)),
+    })
+}
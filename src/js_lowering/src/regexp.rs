@@ -0,0 +1,69 @@
+/// This file contains the lowering of regexp literals. Newer regexp syntax can not
+/// be rewritten into older syntax, but a literal that the target can not parse
+/// would make the whole file fail to load. Such literals are therefore replaced
+/// with a call to the `RegExp` constructor, which only throws when it runs and
+/// works in environments that support the syntax or have it polyfilled:
+///
+/// ```js
+/// /(?<year>\d{4})/u
+/// new RegExp("(?<year>\\d{4})", "u")
+/// ```
+use js_ast::*;
+use js_regexp::{parse_regexp, RegexpFeature};
+
+use crate::lower::{identifier, string};
+use crate::Target;
+
+/// Returns the `RegExp` call for the literal when it uses syntax that is newer than the target.
+pub(crate) fn lower_regexp(literal: &RegexpLiteral, target: Target) -> Option<Expression> {
+    // The literal was validated when it was parsed.
+    let regexp = parse_regexp(&literal.value).ok()?;
+    if regexp
+        .features
+        .iter()
+        .all(|feature| feature_target(*feature) <= target)
+    {
+        return None;
+    }
+
+    let mut arguments = vec![string(&pattern_to_string(regexp.pattern))];
+    if !regexp.flags.is_empty() {
+        arguments.push(string(regexp.flags));
+    }
+    Some(Expression::New(NewExpression {
+        callee: Box::new(identifier("RegExp")),
+        arguments: arguments
+            .into_iter()
+            .map(ArgumentKind::Expression)
+            .collect(),
+        pure: false,
+    }))
+}
+
+/// Returns the first target that supports the feature.
+fn feature_target(feature: RegexpFeature) -> Target {
+    match feature {
+        RegexpFeature::UnicodeFlag | RegexpFeature::StickyFlag => Target::ES2015,
+        RegexpFeature::DotAllFlag
+        | RegexpFeature::NamedGroups
+        | RegexpFeature::Lookbehind
+        | RegexpFeature::PropertyEscapes => Target::ES2018,
+        RegexpFeature::HasIndicesFlag => Target::ES2022,
+        RegexpFeature::UnicodeSetsFlag => Target::ESNext,
+    }
+}
+
+/// Converts the pattern to the text of a string literal with the pattern as its value.
+/// The pattern can not contain line terminators, so only backslashes and quotes need
+/// to be escaped.
+fn pattern_to_string(pattern: &str) -> String {
+    let mut text = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        match c {
+            '\\' => text.push_str("\\\\"),
+            '"' => text.push_str("\\\""),
+            c => text.push(c),
+        }
+    }
+    text
+}
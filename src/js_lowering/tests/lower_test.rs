@@ -0,0 +1,756 @@
+use js_ast::AST;
+use js_lowering::{
+    helper_statements, lower_syntax, lower_syntax_with_options, LoweringError, LoweringOptions,
+    Target,
+};
+use js_parser::parse;
+use js_printer::Printer;
+use logger::LoggerImpl;
+use source::Source;
+
+fn lower(content: &str, target: Target) -> Result<AST, Vec<LoweringError>> {
+    let options = LoweringOptions {
+        target,
+        ..LoweringOptions::default()
+    };
+    lower_with_options(content, options)
+}
+
+fn lower_with_options(content: &str, options: LoweringOptions) -> Result<AST, Vec<LoweringError>> {
+    let source = Source {
+        absolute_path: "/test.js",
+        pretty_path: "./test.js",
+        content,
+    };
+
+    let logger = LoggerImpl::new();
+    let mut ast = parse(&source, &logger);
+    lower_syntax_with_options(&mut ast, options)?;
+    Ok(ast)
+}
+
+fn expect_lowered(content: &str, target: Target, expected: &str) {
+    let ast = lower(content, target).unwrap_or_else(|errors| {
+        panic!("Unexpected errors: {:?}", errors);
+    });
+    let output = Printer::new().print_program(&ast);
+    assert_eq!(output, expected);
+}
+
+fn expect_lowered_with_options(content: &str, options: LoweringOptions, expected: &str) {
+    let ast = lower_with_options(content, options).unwrap_or_else(|errors| {
+        panic!("Unexpected errors: {:?}", errors);
+    });
+    let output = Printer::new().print_program(&ast);
+    assert_eq!(output, expected);
+}
+
+fn expect_error(content: &str, target: Target, expected: &str) {
+    let errors = lower(content, target).expect_err("Expected an error");
+    let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    assert_eq!(errors, vec![expected.to_string()]);
+}
+
+#[test]
+fn test_target_names() {
+    assert_eq!(Target::from_name("es5"), Some(Target::ES5));
+    assert_eq!(Target::from_name("es2020"), Some(Target::ES2020));
+    assert_eq!(Target::from_name("esnext"), Some(Target::ESNext));
+    assert_eq!(Target::from_name("es3"), None);
+    assert_eq!(Target::ES2015.to_string(), "es2015");
+    assert!(Target::ES5 < Target::ES2015);
+}
+
+#[test]
+fn test_arrow_functions() {
+    expect_lowered(
+        "var a = (b) => b * 2;",
+        Target::ES5,
+        "var a = function(b) { return b * 2;\n };\n",
+    );
+    expect_lowered(
+        "var a = () => { b(); };",
+        Target::ES5,
+        "var a = function() { b();\n };\n",
+    );
+    expect_lowered(
+        "function a() { return () => this.b + arguments[0]; }",
+        Target::ES5,
+        "function a() { var _this = this, _arguments = arguments;\nreturn function() { return _this.b + _arguments[0];\n };\n }",
+    );
+    // Nested arrow functions share the variables of the function around them.
+    expect_lowered(
+        "function a() { return () => () => this; }",
+        Target::ES5,
+        "function a() { var _this = this;\nreturn function() { return function() { return _this;\n };\n };\n }",
+    );
+    expect_lowered(
+        "var a = () => this;",
+        Target::ES5,
+        "var _this = this;\nvar a = function() { return _this;\n };\n",
+    );
+    expect_lowered(
+        "a = { b() { return () => arguments; } };",
+        Target::ES5,
+        "a = { b: function() { var _arguments = arguments;\nreturn function() { return _arguments;\n };\n } };\n",
+    );
+    // Functions have their own this.
+    expect_lowered(
+        "var a = () => function() { return this; };",
+        Target::ES5,
+        "var a = function() { return function() { return this;\n };\n };\n",
+    );
+    expect_lowered(
+        "a = ({ b() { return function() { return { c() { return super.d; } }; }; } });",
+        Target::ES2015,
+        "a = ({ b() { return function() { return { c() { return super.d;\n } };\n };\n } });\n",
+    );
+    expect_lowered(
+        "var a = () => this;",
+        Target::ES2015,
+        "var a = () => this;\n",
+    );
+}
+
+#[test]
+fn test_template_literals() {
+    expect_lowered("a = `b`;", Target::ES5, "a = \"b\";\n");
+    expect_lowered(
+        "a = `b${c}d${e}`;",
+        Target::ES5,
+        "a = \"b\" + c + \"d\" + e;\n",
+    );
+    expect_lowered("a = `${b}`;", Target::ES5, "a = \"\" + b;\n");
+    expect_lowered("a = `${b + c}`;", Target::ES5, "a = \"\" + (b + c);\n");
+    expect_lowered("a = `\\`\"b\\$${c}`;", Target::ES5, "a = '`\"b$' + c;\n");
+    expect_lowered("a = `${b}\nc`;", Target::ES5, "a = \"\" + b + \"\\nc\";\n");
+    expect_lowered(
+        "a = `\\u{1F600}`;",
+        Target::ES5,
+        "a = \"\\uD83D\\uDE00\";\n",
+    );
+    expect_lowered("a = `b${c}`;", Target::ES2015, "a = `b${c}`;\n");
+}
+
+#[test]
+fn test_exponentiation() {
+    expect_lowered("a = b ** c;", Target::ES2015, "a = Math.pow(b, c);\n");
+    expect_lowered("a **= b;", Target::ES2015, "a = Math.pow(a, b);\n");
+    expect_lowered("a.b **= c;", Target::ES2015, "a.b = Math.pow(a.b, c);\n");
+    expect_lowered(
+        "a().b[c()] **= d;",
+        Target::ES2015,
+        "var _a, _b;\n(_a = a().b)[_b = c()] = Math.pow(_a[_b], d);\n",
+    );
+    expect_lowered("a = b ** c;", Target::ES2016, "a = b ** c;\n");
+}
+
+#[test]
+fn test_nullish_coalescing() {
+    expect_lowered("a = b ?? c;", Target::ES2019, "a = b != null ? b : c;\n");
+    expect_lowered(
+        "a = b() ?? c;",
+        Target::ES2019,
+        "var _a;\na = (_a = b()) != null ? _a : c;\n",
+    );
+    expect_lowered(
+        "function f() { return b() ?? c; }",
+        Target::ES2019,
+        "function f() { var _a;\nreturn (_a = b()) != null ? _a : c;\n }",
+    );
+    // The temporary variable of an arrow function needs a block body.
+    expect_lowered(
+        "f = () => b() ?? c;",
+        Target::ES2019,
+        "f = () => { var _a;\nreturn (_a = b()) != null ? _a : c;\n };\n",
+    );
+    expect_lowered("a = b ?? c;", Target::ES2020, "a = b ?? c;\n");
+}
+
+#[test]
+fn test_logical_assignment() {
+    expect_lowered("a ||= b;", Target::ES2020, "a || (a = b);\n");
+    expect_lowered("a &&= b;", Target::ES2020, "a && (a = b);\n");
+    expect_lowered("a ??= b;", Target::ES2020, "a ?? (a = b);\n");
+    expect_lowered("a ??= b;", Target::ES2019, "a != null ? a : a = b;\n");
+    expect_lowered(
+        "a().b ||= c;",
+        Target::ES2020,
+        "var _a;\n(_a = a()).b || (_a.b = c);\n",
+    );
+    expect_lowered("a ||= b;", Target::ES2021, "a ||= b;\n");
+}
+
+#[test]
+fn test_object_literals() {
+    expect_lowered(
+        "a = { b, c() {} };",
+        Target::ES5,
+        "a = { b: b, c: function() {} };\n",
+    );
+    expect_lowered(
+        "a = { b: 1, [c]: 2, d: 3 };",
+        Target::ES5,
+        "var _a;\na = (_a = { b: 1 }, _a[c] = 2, _a.d = 3, _a);\n",
+    );
+    expect_lowered(
+        "a = { ...b, c: 1, ...d };",
+        Target::ES2017,
+        "a = __assign({}, b, { c: 1 }, d);\n",
+    );
+    expect_lowered(
+        "a = { b, ...c };",
+        Target::ES2017,
+        "a = __assign({ b }, c);\n",
+    );
+    expect_lowered("a = { ...b };", Target::ES2018, "a = { ...b };\n");
+}
+
+#[test]
+fn test_array_spread() {
+    expect_lowered(
+        "a = [b, ...c, d];",
+        Target::ES5,
+        "a = [b].concat(__toArray(c), [d]);\n",
+    );
+    expect_lowered("a = [...[b]];", Target::ES5, "a = [].concat([b]);\n");
+}
+
+#[test]
+fn test_parameters() {
+    expect_lowered(
+        "function f(a, b = 1) {}",
+        Target::ES5,
+        "function f(a, b) { if (b === void 0) b = 1;\n }",
+    );
+    expect_lowered(
+        "function f(a, ...b) {}",
+        Target::ES5,
+        "function f(a) { var b = Array.prototype.slice.call(arguments, 1);\n }",
+    );
+    expect_lowered(
+        "function f({ a }, [b] = c) {}",
+        Target::ES5,
+        "function f(_a, _b) { var a = _a.a;\nif (_b === void 0) _b = c;\nvar _c = __toArray(_b), b = _c[0];\n }",
+    );
+    // Only the object rest has to be lowered for es2017.
+    expect_lowered(
+        "function f(a = 1, { b, ...c }) {}",
+        Target::ES2017,
+        "function f(a = 1, _a) { let b = _a.b, c = __rest(_a, [\"b\"]);\n }",
+    );
+    expect_lowered(
+        "var f = (a = 1) => a;",
+        Target::ES5,
+        "var f = function(a) { if (a === void 0) a = 1;\nreturn a;\n };\n",
+    );
+}
+
+#[test]
+fn test_destructuring() {
+    expect_lowered(
+        "var { a, b: c } = d;",
+        Target::ES5,
+        "var a = d.a, c = d.b;\n",
+    );
+    expect_lowered(
+        "var { a = 1 } = b();",
+        Target::ES5,
+        "var _a = b(), _b = _a.a, a = _b === void 0 ? 1 : _b;\n",
+    );
+    expect_lowered(
+        "var [a, b, ...c] = d;",
+        Target::ES5,
+        "var _a = __toArray(d), a = _a[0], b = _a[1], c = _a.slice(2);\n",
+    );
+    expect_lowered(
+        "var { a: { b }, ...c } = d;",
+        Target::ES5,
+        "var _a = d.a, b = _a.b, c = __rest(d, [\"a\"]);\n",
+    );
+    expect_lowered(
+        "var { [a]: b, ...c } = d;",
+        Target::ES5,
+        "var _a = a, b = d[_a], c = __rest(d, [_a]);\n",
+    );
+    expect_lowered(
+        "[a, b] = [b, a];",
+        Target::ES5,
+        "var _a;\n_a = [b, a], a = _a[0], b = _a[1];\n",
+    );
+    expect_lowered("x = { a } = b;", Target::ES5, "x = (a = b.a, b);\n");
+    expect_lowered(
+        "for (var [a, b] in c) d(a, b);",
+        Target::ES5,
+        "for (var _a in c) { var _b = __toArray(_a), a = _b[0], b = _b[1];\nd(a, b);\n }",
+    );
+    expect_lowered(
+        "try {} catch ({ message }) {}",
+        Target::ES5,
+        "try {} catch (_a) { var message = _a.message;\n }",
+    );
+    expect_lowered(
+        "export var { a } = b;",
+        Target::ES5,
+        "var a = b.a;\nexport { a };\n",
+    );
+    expect_lowered(
+        "var { a, ...b } = c;",
+        Target::ES2017,
+        "var a = c.a, b = __rest(c, [\"a\"]);\n",
+    );
+    expect_lowered("var [a] = b;", Target::ES2015, "var [a] = b;\n");
+}
+
+#[test]
+fn test_classes() {
+    expect_lowered(
+        "class A { constructor(a) { this.a = a; } b() {} static c() {} }",
+        Target::ES5,
+        "var A = function() { function A(a) { this.a = a;\n }A.prototype.b = function() {};\nA.c = function() {};\nreturn A;\n }();\n",
+    );
+    expect_lowered(
+        "class A { get a() { return 1; } set [b](c) {} 'd'() {} }",
+        Target::ES5,
+        "var A = function() { function A() {}Object.defineProperty(A.prototype, \"a\", { get: function() { return 1;\n }, configurable: true });\nObject.defineProperty(A.prototype, b, { set: function(c) {}, configurable: true });\nA.prototype[\"d\"] = function() {};\nreturn A;\n }();\n",
+    );
+    expect_lowered(
+        "a = class {};",
+        Target::ES5,
+        "a = function() { function _class() {}return _class;\n }();\n",
+    );
+    expect_lowered(
+        "export default class {}",
+        Target::ES5,
+        "var _default = function() { function _default() {}return _default;\n }();\nexport default _default;\n",
+    );
+    expect_lowered(
+        "export class A {}",
+        Target::ES5,
+        "export var A = function() { function A() {}return A;\n }();\n",
+    );
+    // Static methods are stored on the class whatever their name looks like.
+    expect_lowered(
+        "class A { static [a]() {} static 'b'() {} static *c() {} }",
+        Target::ES5,
+        "var A = function() { function A() {}A[a] = function() {};\nA[\"b\"] = function() {};\nA.c = function() { return __generator(this, function(_a) { return [2];\n });\n };\nreturn A;\n }();\n",
+    );
+    expect_lowered(
+        "class A { static() {} *[a]() {} }",
+        Target::ES2015,
+        "class A { static() {}\n*[a]() {} }",
+    );
+    // Methods are lowered like any other function.
+    expect_lowered(
+        "class A { a() { return () => this; } }",
+        Target::ES5,
+        "var A = function() { function A() {}A.prototype.a = function() { var _this = this;\nreturn function() { return _this;\n };\n };\nreturn A;\n }();\n",
+    );
+    expect_lowered("class A {}", Target::ES2015, "class A {}");
+}
+
+#[test]
+fn test_derived_classes() {
+    expect_lowered(
+        "class A extends B {}",
+        Target::ES5,
+        "var A = function(_super) { __extends(A, _super);\nfunction A() { return _super.apply(this, arguments) || this;\n }return A;\n }(B);\n",
+    );
+    // The constructor uses the object created by the parent constructor.
+    expect_lowered(
+        "class A extends B { constructor(a) { super(a); this.a = () => this; } }",
+        Target::ES5,
+        "var A = function(_super) { __extends(A, _super);\nfunction A(a) { var _this;\n_this = _super.call(this, a) || this;\n_this.a = function() { return _this;\n };\nreturn _this;\n }return A;\n }(B);\n",
+    );
+    expect_lowered(
+        "class A extends B { constructor() { if (a) return; super(); } }",
+        Target::ES5,
+        "var A = function(_super) { __extends(A, _super);\nfunction A() { var _this;\nif (a) return _this;\n_this = _super.call(this) || this;\nreturn _this;\n }return A;\n }(B);\n",
+    );
+    expect_lowered(
+        "class A extends B { a() { return super.a(1) + super[b]; } static c() { super.c(); } }",
+        Target::ES5,
+        "var A = function(_super) { __extends(A, _super);\nfunction A() { return _super.apply(this, arguments) || this;\n }A.prototype.a = function() { return __superGet(A.prototype, \"a\", this).call(this, 1) + __superGet(A.prototype, b, this);\n };\nA.c = function() { __superGet(A, \"c\", this).call(this);\n };\nreturn A;\n }(B);\n",
+    );
+    expect_lowered(
+        "var _super; class A extends B {}",
+        Target::ES5,
+        "var _super;\nvar A = function(_super2) { __extends(A, _super2);\nfunction A() { return _super2.apply(this, arguments) || this;\n }return A;\n }(B);\n",
+    );
+}
+
+#[test]
+fn test_block_scoping() {
+    expect_lowered(
+        "let a = 1; const b = 2;",
+        Target::ES5,
+        "var a = 1;\nvar b = 2;\n",
+    );
+    // Block scoped declarations are renamed when their name is used outside of the block.
+    expect_lowered(
+        "let a = 1; { let a = 2; b(a); }",
+        Target::ES5,
+        "var a = 1;\n{ var a2 = 2;\nb(a2);\n }",
+    );
+    expect_lowered(
+        "{ let a = 1; b({ a }); } a;",
+        Target::ES5,
+        "{ var a2 = 1;\nb({ a: a2 });\n }a;\n",
+    );
+    expect_lowered(
+        "{ class A {} } A;",
+        Target::ES5,
+        "{ var A2 = function() { function A2() {}return A2;\n }();\n }A;\n",
+    );
+    expect_lowered(
+        "{ let a = 1; b(a); }",
+        Target::ES5,
+        "{ var a = 1;\nb(a);\n }",
+    );
+    // A variable is undefined at the start of every iteration.
+    expect_lowered(
+        "while (a) { let b; c(b); }",
+        Target::ES5,
+        "while (a) { var b = void 0;\nc(b);\n }",
+    );
+    expect_lowered("let a = 1;", Target::ES2015, "let a = 1;\n");
+}
+
+#[test]
+fn test_loop_closures() {
+    expect_lowered(
+        "for (let i = 0; i < 3; i++) { a.push(() => i); }",
+        Target::ES5,
+        "var _loop = function(i) { a.push(function() { return i;\n });\n };\nfor (var i = 0; i < 3; i++) { _loop(i);\n }",
+    );
+    // Block scoped declarations in the body are captured as well.
+    expect_lowered(
+        "for (var a in b) { let c = a; d(function() { return c; }); }",
+        Target::ES5,
+        "var _loop = function() { var c = a;\nd(function() { return c;\n });\n };\nfor (var a in b) { _loop();\n }",
+    );
+    // Jumps out of the body are returned from the function.
+    expect_lowered(
+        "function f() { for (let i in a) { if (i) continue; if (b) break; if (c) return i; d(() => this[i]); } }",
+        Target::ES5,
+        "function f() { var _this = this;\nvar _loop = function(i) { if (i) return;\nif (b) return \"break\";\nif (c) return { v: i };\nd(function() { return _this[i];\n });\n };\nfor (var i in a) { var _ret = _loop(i);\nif (_ret === \"break\") break;\nif (typeof _ret === \"object\") return _ret.v;\n } }",
+    );
+    // Variables declared with var are still visible after the loop.
+    expect_lowered(
+        "for (let i in a) { var b = i; c(() => i); } d(b);",
+        Target::ES5,
+        "var b;\nvar _loop = function(i) { b = i;\nc(function() { return i;\n });\n };\nfor (var i in a) { _loop(i);\n }d(b);\n",
+    );
+    expect_lowered(
+        "for (let i in a) for (let j in i) b(() => i + j);",
+        Target::ES5,
+        "var _loop2 = function(i) { var _loop = function(j) { b(function() { return i + j;\n });\n };\nfor (var j in i) { _loop(j);\n } };\nfor (var i in a) { _loop2(i);\n }",
+    );
+    expect_lowered(
+        "for (let i in a) b(i);",
+        Target::ES5,
+        "for (var i in a) b(i);\n",
+    );
+    expect_error(
+        "for (let i = 0; i < 3; i++) { i++; a(() => i); }",
+        Target::ES5,
+        "Transforming assignments to loop variables captured by functions to the configured target environment (\"es5\") is not supported yet",
+    );
+    expect_error(
+        "a: for (;;) { for (let i in b) { c(() => i); continue a; } }",
+        Target::ES5,
+        "Transforming jumps to labels outside of loops capturing variables to the configured target environment (\"es5\") is not supported yet",
+    );
+}
+
+#[test]
+fn test_constant_assignments() {
+    expect_error(
+        "const a = 1; a = 2;",
+        Target::ES5,
+        "Cannot assign to \"a\" because it is a constant",
+    );
+    expect_error(
+        "const a = 1; function b() { a++; }",
+        Target::ES5,
+        "Cannot assign to \"a\" because it is a constant",
+    );
+    expect_error(
+        "for (const a in b) { [a] = c; }",
+        Target::ES5,
+        "Cannot assign to \"a\" because it is a constant",
+    );
+    // Assignments to a variable shadowing the constant are fine.
+    expect_lowered(
+        "const a = 1; function b(a) { a = 2; }",
+        Target::ES5,
+        "var a = 1;\nfunction b(a) { a = 2;\n }",
+    );
+}
+
+#[test]
+fn test_async_functions() {
+    expect_lowered(
+        "async function a() { await b; }",
+        Target::ES2017,
+        "async function a() { await b;\n }",
+    );
+    expect_lowered(
+        "async function a(b) { await b; return arguments; }",
+        Target::ES2015,
+        "function a(b) { return __async(this, arguments, function*() { yield b;\nreturn arguments;\n });\n }",
+    );
+    expect_lowered(
+        "var a = async () => { await b; };",
+        Target::ES2015,
+        "var a = () => __async(this, null, function*() { yield b;\n });\n",
+    );
+    expect_lowered(
+        "async function a() { await b; }",
+        Target::ES5,
+        "function a() { return __async(this, null, function() { return __generator(this, function(_a) { switch (_a.label) { case 0: return [4, b];\n case 1: _a.sent();\nreturn [2];\n } });\n });\n }",
+    );
+    // Async methods are lowered like async functions.
+    expect_lowered(
+        "class A { async a() { await b; } }",
+        Target::ES2015,
+        "class A { a() { return __async(this, null, function*() { yield b;\n });\n } }",
+    );
+    expect_lowered(
+        "a = { async [b]() { await c; } };",
+        Target::ES2015,
+        "a = { [b]() { return __async(this, null, function*() { yield c;\n });\n } };\n",
+    );
+    expect_lowered("class A { async a() { await b; } }", Target::ES5, "var A = function() { function A() {}A.prototype.a = function() { return __async(this, null, function() { return __generator(this, function(_a) { switch (_a.label) { case 0: return [4, b];\n case 1: _a.sent();\nreturn [2];\n } });\n });\n };\nreturn A;\n }();\n");
+    expect_lowered("a = { async b() {} };", Target::ES5, "a = { b: function() { return __async(this, null, function() { return __generator(this, function(_a) { return [2];\n });\n });\n } };\n");
+}
+
+#[test]
+fn test_for_await() {
+    expect_lowered(
+        "for await (const a of b) c(a);",
+        Target::ES2017,
+        "try { for (var _a = __forAwait(b), _b, _c, _d; _b = !(_c = await _a.next()).done; _b = false) { const a = _c.value;\nc(a);\n } } catch (_c) { _d = [_c];\n } finally { try { _b && (_c = _a.return) && await _c.call(_a);\n } finally { if (_d) throw _d[0];\n } }",
+    );
+    expect_lowered(
+        "async function a() { for await (b of c) {} }",
+        Target::ES2015,
+        "function a() { return __async(this, null, function*() { try { for (var _a = __forAwait(c), _b, _c, _d; _b = !(_c = yield _a.next()).done; _b = false) { b = _c.value;\n } } catch (_c) { _d = [_c];\n } finally { try { _b && (_c = _a.return) && (yield _c.call(_a));\n } finally { if (_d) throw _d[0];\n } } });\n }",
+    );
+}
+
+#[test]
+fn test_generators() {
+    expect_lowered(
+        "function* a() { yield; }",
+        Target::ES2015,
+        "function* a() { yield;\n }",
+    );
+    expect_lowered(
+        "function* a() { b(); }",
+        Target::ES5,
+        "function a() { return __generator(this, function(_a) { b();\nreturn [2];\n });\n }",
+    );
+    expect_lowered(
+        "function* a() { var b = yield 1; return b; }",
+        Target::ES5,
+        "function a() { var b;\nreturn __generator(this, function(_a) { switch (_a.label) { case 0: return [4, 1];\n case 1: b = _a.sent();\nreturn [2, b];\n } });\n }",
+    );
+    expect_lowered(
+        "function* a(b) { while (b) { if (yield b) break; b--; } }",
+        Target::ES5,
+        "function a(b) { return __generator(this, function(_a) { switch (_a.label) { case 0: if (!b) return [3, 2];\nreturn [4, b];\n case 1: if (_a.sent()) return [3, 2];\nb--;\nreturn [3, 0];\n case 2: return [2];\n } });\n }",
+    );
+    expect_lowered(
+        "function* a() { try { yield 1; } catch (e) { c(e); } finally { d(); } }",
+        Target::ES5,
+        "function a() { var e;\nreturn __generator(this, function(_a) { switch (_a.label) { case 0: _a.trys.push([0, 2, 3, 4]);\nreturn [4, 1];\n case 1: _a.sent();\nreturn [3, 4];\n case 2: e = _a.sent();\nc(e);\nreturn [3, 4];\n case 3: d();\nreturn [7];\n case 4: return [2];\n } });\n }",
+    );
+    expect_lowered(
+        "function* a(b) { c(yield b, yield* d); }",
+        Target::ES5,
+        "function a(b) { var _b, _c;\nreturn __generator(this, function(_a) { switch (_a.label) { case 0: _b = c;\nreturn [4, b];\n case 1: _c = _a.sent();\nreturn [5, __values(d)];\n case 2: _b(_c, _a.sent());\nreturn [2];\n } });\n }",
+    );
+    expect_lowered(
+        "function* a() { function b() {} yield arguments[0]; }",
+        Target::ES5,
+        "function a() { var _arguments = arguments;\nfunction b() {}return __generator(this, function(_a) { switch (_a.label) { case 0: return [4, _arguments[0]];\n case 1: _a.sent();\nreturn [2];\n } });\n }",
+    );
+}
+
+#[test]
+fn test_for_of() {
+    expect_lowered(
+        "for (var a of b) c(a);",
+        Target::ES2015,
+        "for (var a of b) c(a);\n",
+    );
+    expect_lowered(
+        "for (var a of b) c(a);",
+        Target::ES5,
+        "try { for (var _a = __values(b), _b = _a.next(), _c, _d; !_b.done; _b = _a.next()) { var a = _b.value;\nc(a);\n } } catch (_c) { _d = [_c];\n } finally { try { if (_b && !_b.done && (_c = _a.return)) _c.call(_a);\n } finally { if (_d) throw _d[0];\n } }",
+    );
+    expect_lowered(
+        "a: for (const [b, c] of d) { continue a; }",
+        Target::ES5,
+        "try { a: for (var _a = __values(d), _b = _a.next(), _c, _d; !_b.done; _b = _a.next()) { var _e = __toArray(_b.value), b = _e[0], c = _e[1];\ncontinue a;\n } } catch (_c) { _d = [_c];\n } finally { try { if (_b && !_b.done && (_c = _a.return)) _c.call(_a);\n } finally { if (_d) throw _d[0];\n } }",
+    );
+    expect_lowered(
+        "function* a(b) { for (var c of b) yield c; }",
+        Target::ES5,
+        "function a(b) { var c, _b, _c, _d, _e;\nreturn __generator(this, function(_a) { switch (_a.label) { case 0: _a.trys.push([0, 5, 6, 7]);\n_b = __values(b), _c = _b.next();\n_a.label = 1;\n case 1: if (_c.done) return [3, 4];\nc = _c.value;\nreturn [4, c];\n case 2: _a.sent();\n_a.label = 3;\n case 3: _c = _b.next();\nreturn [3, 1];\n case 4: return [3, 7];\n case 5: _d = _a.sent();\n_e = [_d];\nreturn [3, 7];\n case 6: try { if (_c && !_c.done && (_d = _b.return)) _d.call(_b);\n } finally { if (_e) throw _e[0];\n }return [7];\n case 7: return [2];\n } });\n }",
+    );
+}
+
+#[test]
+fn test_spread_arguments() {
+    expect_lowered(
+        "a(...b); c.d(e, ...f); new G(...h);",
+        Target::ES5,
+        "a.apply(void 0, __toArray(b));\nc.d.apply(c, [e].concat(__toArray(f)));\nnew (Function.prototype.bind.apply(G, [null].concat(__toArray(h))))();\n",
+    );
+    expect_lowered("a(...b);", Target::ES2015, "a(...b);\n");
+}
+
+#[test]
+fn test_assume_arrays() {
+    let options = LoweringOptions {
+        target: Target::ES5,
+        assume_arrays: true,
+    };
+    expect_lowered_with_options(
+        "for (var a of b) c(a);",
+        options,
+        "for (var _a = 0, _b = b; _a < _b.length; _a++) { var a = _b[_a];\nc(a);\n }",
+    );
+    expect_lowered_with_options(
+        "a(...b); c().d(...[e], f); g = [h, ...i];",
+        options,
+        "var _a;\na.apply(void 0, b);\n(_a = c()).d.apply(_a, [].concat([e], [f]));\ng = [h].concat(i);\n",
+    );
+    // `arguments` is not an array, concat would add it as a single item.
+    expect_lowered_with_options(
+        "function a() { b(c, ...arguments); d = [...arguments]; }",
+        options,
+        "function a() { b.apply(void 0, [c].concat(__toArray(arguments)));\nd = [].concat(__toArray(arguments));\n }",
+    );
+}
+
+#[test]
+fn test_generated_names() {
+    // Temporary variables do not collide with the names used by the program.
+    expect_lowered(
+        "var _a = 1; a = b() ?? _a;",
+        Target::ES2019,
+        "var _b;\nvar _a = 1;\na = (_b = b()) != null ? _b : _a;\n",
+    );
+    expect_lowered(
+        "var _this; function a() { return () => this; }",
+        Target::ES5,
+        "var _this;\nfunction a() { var _this2 = this;\nreturn function() { return _this2;\n };\n }",
+    );
+}
+
+#[test]
+fn test_helpers() {
+    let source = Source {
+        absolute_path: "/test.js",
+        pretty_path: "./test.js",
+        content: "a = { ...b }; var [c] = d;",
+    };
+    let logger = LoggerImpl::new();
+    let mut ast = parse(&source, &logger);
+    let helpers = lower_syntax(&mut ast, Target::ES5).unwrap();
+    assert_eq!(helpers, vec!["__assign", "__toArray"]);
+
+    let statements = helper_statements(&helpers);
+    assert_eq!(statements.len(), 2);
+
+    let source = Source {
+        absolute_path: "/test.js",
+        pretty_path: "./test.js",
+        content: "class A extends B { a() { return super.a(); } }",
+    };
+    let mut ast = parse(&source, &logger);
+    let helpers = lower_syntax(&mut ast, Target::ES5).unwrap();
+    assert_eq!(helpers, vec!["__extends", "__superGet"]);
+    assert_eq!(helper_statements(&helpers).len(), 2);
+
+    let source = Source {
+        absolute_path: "/test.js",
+        pretty_path: "./test.js",
+        content:
+            "async function a() { for await (const b of c) d(b); } function* e() { yield* f; }",
+    };
+    let mut ast = parse(&source, &logger);
+    let helpers = lower_syntax(&mut ast, Target::ES5).unwrap();
+    assert_eq!(
+        helpers,
+        vec!["__async", "__forAwait", "__generator", "__values"]
+    );
+    assert_eq!(helper_statements(&helpers).len(), 4);
+}
+
+#[test]
+fn test_regexp_literals() {
+    expect_lowered("a = /a/gi;", Target::ES5, "a = /a/gi;\n");
+    expect_lowered("a = /a/y;", Target::ES2015, "a = /a/y;\n");
+    expect_lowered("a = /a/y;", Target::ES5, "a = new RegExp(\"a\", \"y\");\n");
+    expect_lowered(
+        "a = /(?<year>\\d{4})\\/\"/;",
+        Target::ES2017,
+        "a = new RegExp(\"(?<year>\\\\d{4})\\\\/\\\"\");\n",
+    );
+    expect_lowered("a = /(?<=a)b/s;", Target::ES2018, "a = /(?<=a)b/s;\n");
+    expect_lowered(
+        "a = /[\\w--\\d]/v;",
+        Target::ES2022,
+        "a = new RegExp(\"[\\\\w--\\\\d]\", \"v\");\n",
+    );
+}
+
+#[test]
+fn test_unsupported_syntax() {
+    expect_error(
+        "class A extends B { a() { super.b = 1; } }",
+        Target::ES5,
+        "Transforming assignments to super properties to the configured target environment (\"es5\") is not supported yet",
+    );
+    expect_error(
+        "async function* a() {}",
+        Target::ES2017,
+        "Transforming async generator functions to the configured target environment (\"es2017\") is not supported yet",
+    );
+    expect_error(
+        "function* a() { for (let i = 0; i < 2; i++) { b(() => i); yield i; } }",
+        Target::ES5,
+        "Transforming await and yield in loops capturing variables to the configured target environment (\"es5\") is not supported yet",
+    );
+    expect_error(
+        "a = ({ b() { return super.x; } });",
+        Target::ES5,
+        "Transforming super in object methods to the configured target environment (\"es5\") is not supported yet",
+    );
+    expect_error(
+        "a = ({ get b() { return () => super.x; } });",
+        Target::ES5,
+        "Transforming super in object methods to the configured target environment (\"es5\") is not supported yet",
+    );
+    expect_error(
+        "var a = () => arguments[0];",
+        Target::ES5,
+        "Transforming arguments in top-level arrow functions to the configured target environment (\"es5\") is not supported yet",
+    );
+    expect_error(
+        "a = 1n;",
+        Target::ES2019,
+        "Transforming big integer literals to the configured target environment (\"es2019\") is not supported yet",
+    );
+
+    let errors = lower(
+        "async function* a() {} class A extends B { a() { super.b = 1; } }",
+        Target::ES5,
+    )
+    .unwrap_err();
+    assert_eq!(errors.len(), 2);
+}
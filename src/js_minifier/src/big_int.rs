@@ -0,0 +1,423 @@
+/// This file contains the arbitrary precision integers used when folding BigInt
+/// literals, so that `2n ** 64n` is folded to `18446744073709551616n` without
+/// losing any digits.
+///
+/// The operators follow the BigInt semantics of JavaScript, division truncates
+/// towards zero, the remainder has the sign of the dividend and the bitwise
+/// operators work on an infinite two's complement representation.
+use std::cmp::Ordering;
+
+/// The largest number of bits of a folded power, larger powers are
+/// left as they are since their digits are longer than the expression.
+const MAX_POWER_BITS: u64 = 1024;
+
+/// An integer of any size, stored as its sign and magnitude.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct BigInt {
+    /// Only set when the magnitude is not zero.
+    negative: bool,
+    /// The digits in base 2^32, least significant first and without trailing zeros.
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        BigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    fn from_u32(value: u32) -> BigInt {
+        BigInt::new(false, vec![value])
+    }
+
+    /// Parses the text of a literal without the n suffix, in decimal or with
+    /// a `0x`, `0o` or `0b` prefix. Numeric separators are ignored.
+    pub(crate) fn parse(text: &str) -> Option<BigInt> {
+        let prefix = text.get(..2).map(|prefix| prefix.to_ascii_lowercase());
+        let (radix, digits) = match prefix.as_deref() {
+            Some("0x") => (16, &text[2..]),
+            Some("0o") => (8, &text[2..]),
+            Some("0b") => (2, &text[2..]),
+            _ => (10, text),
+        };
+
+        let mut magnitude = Vec::new();
+        let mut has_digits = false;
+        for character in digits.chars() {
+            if character == '_' {
+                continue;
+            }
+            let digit = character.to_digit(radix)?;
+            multiply_add(&mut magnitude, radix, digit);
+            has_digits = true;
+        }
+        match has_digits {
+            true => Some(BigInt::new(false, magnitude)),
+            false => None,
+        }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub(crate) fn negate(&self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+
+    pub(crate) fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(
+                self.negative,
+                add_magnitudes(&self.magnitude, &other.magnitude),
+            );
+        }
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::new(
+                other.negative,
+                subtract_magnitudes(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::new(
+                self.negative,
+                subtract_magnitudes(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+
+    pub(crate) fn subtract(&self, other: &BigInt) -> BigInt {
+        self.add(&other.negate())
+    }
+
+    pub(crate) fn multiply(&self, other: &BigInt) -> BigInt {
+        let mut magnitude = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let value = magnitude[i + j] as u64 + a as u64 * b as u64 + carry;
+                magnitude[i + j] = value as u32;
+                carry = value >> 32;
+            }
+            magnitude[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, magnitude)
+    }
+
+    /// Returns the quotient truncated towards zero, None when dividing by zero.
+    pub(crate) fn divide(&self, other: &BigInt) -> Option<BigInt> {
+        let (quotient, _) = divide_magnitudes(&self.magnitude, &other.magnitude)?;
+        Some(BigInt::new(self.negative != other.negative, quotient))
+    }
+
+    /// Returns the remainder with the sign of the dividend, None when dividing by zero.
+    pub(crate) fn remainder(&self, other: &BigInt) -> Option<BigInt> {
+        let (_, remainder) = divide_magnitudes(&self.magnitude, &other.magnitude)?;
+        Some(BigInt::new(self.negative, remainder))
+    }
+
+    /// Returns None for negative exponents, which throw, and for results that are too large.
+    pub(crate) fn power(&self, exponent: &BigInt) -> Option<BigInt> {
+        if exponent.negative {
+            return None;
+        }
+        if self.is_zero() || self.magnitude == [1] {
+            let negative = self.negative && exponent.magnitude.first().is_some_and(|e| e & 1 == 1);
+            return match exponent.is_zero() {
+                true => Some(BigInt::from_u32(1)),
+                false => Some(BigInt::new(negative, self.magnitude.clone())),
+            };
+        }
+
+        let exponent = exponent.to_u64()?;
+        if self.bits().checked_mul(exponent)? > MAX_POWER_BITS {
+            return None;
+        }
+        let mut result = BigInt::from_u32(1);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply(&base);
+            }
+            base = base.multiply(&base);
+            exponent >>= 1;
+        }
+        Some(result)
+    }
+
+    /// `a << b`, a negative shift shifts to the right.
+    pub(crate) fn shift_left(&self, shift: &BigInt) -> Option<BigInt> {
+        let amount = shift.to_u64()?;
+        match shift.negative {
+            true => Some(self.shift_right_by(amount)),
+            false if amount > MAX_POWER_BITS => None,
+            false => Some(BigInt::new(
+                self.negative,
+                shift_magnitude_left(&self.magnitude, amount),
+            )),
+        }
+    }
+
+    /// `a >> b`, rounds towards negative infinity.
+    pub(crate) fn shift_right(&self, shift: &BigInt) -> Option<BigInt> {
+        self.shift_left(&shift.negate())
+    }
+
+    fn shift_right_by(&self, amount: u64) -> BigInt {
+        let words = (amount / 32) as usize;
+        let bits = (amount % 32) as u32;
+        if words >= self.magnitude.len() {
+            return match self.negative {
+                true => BigInt::new(true, vec![1]),
+                false => BigInt::new(false, Vec::new()),
+            };
+        }
+
+        let mut magnitude = self.magnitude[words..].to_vec();
+        let mut lost = self.magnitude[..words].iter().any(|&word| word != 0);
+        if bits > 0 {
+            lost |= magnitude[0] & ((1 << bits) - 1) != 0;
+            for i in 0..magnitude.len() {
+                let high = magnitude.get(i + 1).map_or(0, |&next| next << (32 - bits));
+                magnitude[i] = (magnitude[i] >> bits) | high;
+            }
+        }
+
+        let result = BigInt::new(self.negative, magnitude);
+        // Negative numbers round down, away from zero, when bits are shifted out.
+        match self.negative && lost {
+            true => result.subtract(&BigInt::from_u32(1)),
+            false => result,
+        }
+    }
+
+    /// `~a`, which is `-a - 1`.
+    pub(crate) fn not(&self) -> BigInt {
+        self.negate().subtract(&BigInt::from_u32(1))
+    }
+
+    pub(crate) fn and(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub(crate) fn or(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub(crate) fn xor(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    /// Applies the operator to the words of the two's complement of both
+    /// numbers, with one extra word so the sign bit is always present.
+    fn bitwise(&self, other: &BigInt, operator: impl Fn(u32, u32) -> u32) -> BigInt {
+        let length = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let a = self.twos_complement(length);
+        let b = other.twos_complement(length);
+        let words: Vec<u32> = a.iter().zip(&b).map(|(&a, &b)| operator(a, b)).collect();
+
+        let negative = words[length - 1] >> 31 == 1;
+        match negative {
+            true => BigInt::new(true, negate_words(&words)),
+            false => BigInt::new(false, words),
+        }
+    }
+
+    fn twos_complement(&self, length: usize) -> Vec<u32> {
+        let mut words = self.magnitude.clone();
+        words.resize(length, 0);
+        match self.negative {
+            true => negate_words(&words),
+            false => words,
+        }
+    }
+
+    fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(last) => self.magnitude.len() as u64 * 32 - last.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// Returns the magnitude if it fits in 64 bits.
+    fn to_u64(&self) -> Option<u64> {
+        match self.magnitude.as_slice() {
+            [] => Some(0),
+            [low] => Some(*low as u64),
+            [low, high] => Some((*high as u64) << 32 | *low as u64),
+            _ => None,
+        }
+    }
+
+    /// Returns the digits in decimal, with a leading `-` when negative.
+    pub(crate) fn to_decimal(&self) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+
+        // Collect the digits nine at a time, least significant first.
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            chunks.push(divide_small(&mut magnitude, 1_000_000_000));
+        }
+
+        let mut text = String::new();
+        if self.negative {
+            text.push('-');
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            text.push_str(&first.to_string());
+        }
+        for chunk in chunks {
+            text.push_str(&format!("{:09}", chunk));
+        }
+        text
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+/// `magnitude * factor + addend`, in place.
+fn multiply_add(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for word in magnitude.iter_mut() {
+        let value = *word as u64 * factor as u64 + carry;
+        *word = value as u32;
+        carry = value >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divides the magnitude by a small divisor in place and returns the remainder.
+fn divide_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for word in magnitude.iter_mut().rev() {
+        let value = remainder << 32 | *word as u64;
+        *word = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    remainder as u32
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let value = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(value as u32);
+        carry = value >> 32;
+    }
+    result.push(carry as u32);
+    result
+}
+
+/// `a - b`, where `a` is at least as large as `b`.
+fn subtract_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &word) in a.iter().enumerate() {
+        let mut value = word as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if value < 0 {
+            value += 1 << 32;
+            borrow = 1;
+        }
+        result.push(value as u32);
+    }
+    result
+}
+
+/// Long division one bit at a time, which is plenty for the size of literals.
+fn divide_magnitudes(a: &[u32], b: &[u32]) -> Option<(Vec<u32>, Vec<u32>)> {
+    if b.is_empty() {
+        return None;
+    }
+
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        remainder = shift_magnitude_left(&remainder, 1);
+        if a[bit / 32] >> (bit % 32) & 1 == 1 {
+            match remainder.first_mut() {
+                Some(first) => *first |= 1,
+                None => remainder.push(1),
+            }
+        }
+        if compare_magnitudes(&remainder, b) != Ordering::Less {
+            remainder = subtract_magnitudes(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    Some((quotient, remainder))
+}
+
+fn shift_magnitude_left(magnitude: &[u32], amount: u64) -> Vec<u32> {
+    if magnitude.is_empty() {
+        return Vec::new();
+    }
+    let bits = (amount % 32) as u32;
+    let mut result = vec![0u32; (amount / 32) as usize];
+    let mut carry = 0u32;
+    for &word in magnitude {
+        result.push(word << bits | carry);
+        carry = match bits {
+            0 => 0,
+            bits => word >> (32 - bits),
+        };
+    }
+    if carry > 0 {
+        result.push(carry);
+    }
+    result
+}
+
+/// The two's complement negation of a fixed number of words.
+fn negate_words(words: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(words.len());
+    let mut carry = 1u64;
+    for &word in words {
+        let value = !word as u64 + carry;
+        result.push(value as u32);
+        carry = value >> 32;
+    }
+    result
+}
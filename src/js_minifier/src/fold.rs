@@ -0,0 +1,578 @@
+/// This file contains the constant folding and dead code elimination.
+///
+/// Expressions whose operands are literals are evaluated, `"a" + "b"` becomes
+/// `"ab"` and `1 === 1` becomes `true`. Once a condition is known the branch
+/// that can never run is removed, both for if statements and for conditional
+/// and logical expressions. Statements after a `return`, `throw`, `break` or
+/// `continue` are removed as well.
+///
+/// Removed code can still declare names that are visible to the rest of the
+/// function: `var` declarations are hoisted to the top of the function and
+/// function declarations to the top of their block. Removed `var` declarations
+/// are therefore kept without their initializers and function declarations
+/// after a `return` are kept as they are.
+///
+/// String literals hold the text as it was written, escapes included. Their escapes
+/// are decoded before they are folded and the result is escaped again, since joining
+/// the text as written can change its meaning: `"\0" + "1"` is not `"\01"`. Strings
+/// with legacy octal escapes or lone surrogates are left alone.
+///
+/// BigInt literals are folded with arbitrary precision. Mixing them with numbers
+/// throws a TypeError in JavaScript, so those expressions are left alone.
+use crate::big_int::BigInt;
+use js_ast::scope::var_names;
+use js_ast::side_effects::has_side_effects;
+use js_ast::visit::{walk_expression, walk_statement, VisitorMut};
+use js_ast::*;
+
+/// Folds the constant expressions of the program and removes the code that can never run.
+pub fn fold_constants(program: &mut AST) {
+    Folder {
+        folded_block: false,
+    }
+    .visit_program(program);
+}
+
+struct Folder {
+    /// Set when the last visited statement was an if statement that was replaced
+    /// by a block, the block is then merged into the surrounding statements.
+    folded_block: bool,
+}
+
+impl VisitorMut for Folder {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        let old = std::mem::take(statements);
+        for mut statement in old {
+            self.visit_statement(&mut statement);
+            match statement {
+                Statement::BlockStatement(b) if std::mem::take(&mut self.folded_block) => {
+                    statements.extend(b.statements)
+                }
+                statement => statements.push(statement),
+            }
+        }
+        remove_unreachable(statements);
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+        self.folded_block = false;
+
+        if let Statement::IfStatement(i) = statement {
+            if let Some(test) = known_boolean(&i.test) {
+                let (kept, removed) = match test {
+                    true => (Some(*i.consequent.clone()), i.alternate.as_deref().cloned()),
+                    false => (i.alternate.as_deref().cloned(), Some(*i.consequent.clone())),
+                };
+
+                let mut statements = Vec::new();
+                match kept {
+                    // A block is merged with the surrounding statements unless it
+                    // declares names that are only visible inside of it.
+                    Some(Statement::BlockStatement(b)) if !has_lexical_declarations(&b) => {
+                        statements.extend(b.statements)
+                    }
+                    Some(statement) => statements.push(statement),
+                    None => {}
+                }
+                if let Some(removed) = removed {
+                    statements.extend(hoisted_vars(std::slice::from_ref(&removed)));
+                }
+
+                *statement = match statements.len() {
+                    0 => Statement::EmptyStatement(EmptyStatement {}),
+                    1 => statements.remove(0),
+                    _ => {
+                        self.folded_block = true;
+                        Statement::BlockStatement(BlockStatement { statements })
+                    }
+                };
+            }
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+        if let Some(folded) = fold_expression(expression) {
+            *expression = folded;
+        }
+    }
+}
+
+/// Removes the statements following a statement that always jumps away,
+/// keeping the declarations that are hoisted out of them.
+fn remove_unreachable(statements: &mut Vec<Statement>) {
+    let jump = statements.iter().position(|statement| {
+        matches!(
+            statement,
+            Statement::ReturnStatement(_)
+                | Statement::ThrowStatement(_)
+                | Statement::BreakStatement(_)
+                | Statement::ContinueStatement(_)
+        )
+    });
+    let Some(jump) = jump else {
+        return;
+    };
+
+    let mut removed = Vec::new();
+    for statement in statements.split_off(jump + 1) {
+        match statement {
+            // Imports and exports are declarations of the module, and so are hoisted.
+            Statement::FunctionDeclaration(_)
+            | Statement::Comment(_)
+            | Statement::ImportDeclaration(_)
+            | Statement::ExportAllDeclaration(_)
+            | Statement::ExportDefaultDeclaration(_)
+            | Statement::ExportNamedDeclaration(_)
+            | Statement::ExportNamedSpecifiers(_) => statements.push(statement),
+            statement => removed.push(statement),
+        }
+    }
+    statements.extend(hoisted_vars(&removed));
+}
+
+/// Returns a declaration without initializers of the `var`
+/// variables declared by the statements, if there are any.
+fn hoisted_vars(statements: &[Statement]) -> Option<Statement> {
+    let mut names = Vec::new();
+    var_names(statements, &mut names);
+    if names.is_empty() {
+        return None;
+    }
+
+    let mut declarations: Vec<VariableDeclarator> = Vec::new();
+    for name in names {
+        if !declarations
+            .iter()
+            .any(|d| matches!(&d.binding, Binding::Identifier(i) if i.name == name))
+        {
+            declarations.push(VariableDeclarator {
+                binding: Binding::Identifier(Identifier { name }),
+                initializer: None,
+            });
+        }
+    }
+    Some(Statement::VariableDeclaration(VariableDeclaration {
+        kind: VariableDeclarationKind::Var,
+        declarations,
+    }))
+}
+
+fn has_lexical_declarations(block: &BlockStatement) -> bool {
+    block.statements.iter().any(|statement| match statement {
+        Statement::VariableDeclaration(v) => v.kind != VariableDeclarationKind::Var,
+        Statement::ClassDeclaration(_) | Statement::FunctionDeclaration(_) => true,
+        _ => false,
+    })
+}
+
+/// A primitive value that is known at compile time.
+#[derive(Debug, PartialEq, Clone)]
+enum Constant {
+    Number(f64),
+    BigInt(BigInt),
+    /// The value of the string, with its escapes decoded.
+    String(String),
+    Boolean(bool),
+    Null,
+    Undefined,
+}
+
+fn constant(expression: &Expression) -> Option<Constant> {
+    match expression {
+        Expression::NumericLiteral(n) => Some(Constant::Number(n.value)),
+        Expression::BigIntLiteral(b) => BigInt::parse(&b.value).map(Constant::BigInt),
+        Expression::StringLiteral(s) => unescape_string(&s.value).map(Constant::String),
+        Expression::BooleanLiteral(b) => Some(Constant::Boolean(b.value)),
+        Expression::NullLiteral(_) => Some(Constant::Null),
+        // A parenthesized expression.
+        Expression::Sequence(s) if s.expressions.len() == 1 => constant(&s.expressions[0]),
+        // Negative numbers are negated literals.
+        Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::Negative,
+            argument,
+        }) => match constant(argument)? {
+            Constant::Number(value) => Some(Constant::Number(-value)),
+            Constant::BigInt(value) => Some(Constant::BigInt(value.negate())),
+            _ => None,
+        },
+        Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::Void,
+            argument,
+        }) if !has_side_effects(argument) => Some(Constant::Undefined),
+        _ => None,
+    }
+}
+
+/// Returns the expression for a constant, None for the numbers
+/// that do not have a literal such as `NaN` and `Infinity`.
+fn constant_expression(constant: Constant) -> Option<Expression> {
+    let expression = match constant {
+        Constant::Number(value) if !value.is_finite() => return None,
+        Constant::Number(value) if value < 0.0 || (value == 0.0 && value.is_sign_negative()) => {
+            Expression::Unary(UnaryExpression {
+                operator: UnaryExpressionOperator::Negative,
+                argument: Box::new(Expression::NumericLiteral(NumericLiteral { value: -value })),
+            })
+        }
+        Constant::Number(value) => Expression::NumericLiteral(NumericLiteral { value }),
+        Constant::BigInt(value) if value.is_negative() => Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::Negative,
+            argument: Box::new(Expression::BigIntLiteral(BigIntLiteral {
+                value: value.negate().to_decimal(),
+            })),
+        }),
+        Constant::BigInt(value) => Expression::BigIntLiteral(BigIntLiteral {
+            value: value.to_decimal(),
+        }),
+        Constant::String(value) => Expression::StringLiteral(StringLiteral {
+            value: escape_string(&value),
+        }),
+        Constant::Boolean(value) => Expression::BooleanLiteral(BooleanLiteral { value }),
+        Constant::Null => Expression::NullLiteral(NullLiteral {}),
+        Constant::Undefined => Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::Void,
+            argument: Box::new(Expression::NumericLiteral(NumericLiteral { value: 0.0 })),
+        }),
+    };
+    Some(expression)
+}
+
+/// Returns the value of the expression when converted to a boolean, if it is known
+/// and the expression has no side effects so that it can be removed.
+fn known_boolean(expression: &Expression) -> Option<bool> {
+    if has_side_effects(expression) {
+        return None;
+    }
+    match expression {
+        Expression::Array(_)
+        | Expression::Object(_)
+        | Expression::Function(_)
+        | Expression::ArrowFunction(_)
+        | Expression::Class(_)
+        | Expression::RegexpLiteral(_) => Some(true),
+        expression => match constant(expression)? {
+            Constant::Number(value) => Some(value != 0.0 && !value.is_nan()),
+            Constant::BigInt(value) => Some(!value.is_zero()),
+            Constant::String(value) => Some(!value.is_empty()),
+            Constant::Boolean(value) => Some(value),
+            Constant::Null | Constant::Undefined => Some(false),
+        },
+    }
+}
+
+fn fold_expression(expression: &Expression) -> Option<Expression> {
+    match expression {
+        Expression::Unary(u) => fold_unary(u),
+        Expression::Binary(b) => {
+            let left = constant(&b.left)?;
+            let right = constant(&b.right)?;
+            constant_expression(fold_binary(&b.operator, left, right)?)
+        }
+        Expression::Logical(l) => fold_logical(l),
+        Expression::Conditional(c) => match known_boolean(&c.test)? {
+            true => Some(*c.consequence.clone()),
+            false => Some(*c.alternate.clone()),
+        },
+        _ => None,
+    }
+}
+
+fn fold_unary(unary: &UnaryExpression) -> Option<Expression> {
+    let argument = &*unary.argument;
+    match unary.operator {
+        UnaryExpressionOperator::LogicalNot => Some(Expression::BooleanLiteral(BooleanLiteral {
+            value: !known_boolean(argument)?,
+        })),
+
+        UnaryExpressionOperator::Typeof => {
+            if has_side_effects(argument) {
+                return None;
+            }
+            let type_name = match argument {
+                Expression::Function(_) | Expression::ArrowFunction(_) | Expression::Class(_) => {
+                    "function"
+                }
+                Expression::Array(_) | Expression::Object(_) | Expression::RegexpLiteral(_) => {
+                    "object"
+                }
+                argument => match constant(argument)? {
+                    Constant::Number(_) => "number",
+                    Constant::BigInt(_) => "bigint",
+                    Constant::String(_) => "string",
+                    Constant::Boolean(_) => "boolean",
+                    Constant::Null => "object",
+                    Constant::Undefined => "undefined",
+                },
+            };
+            Some(Expression::StringLiteral(StringLiteral {
+                value: type_name.to_string(),
+            }))
+        }
+
+        // A negated number literal is how negative numbers are written, it is only
+        // folded if the argument is not a literal, as in `-(-1)`.
+        UnaryExpressionOperator::Negative => match argument {
+            Expression::NumericLiteral(_) | Expression::BigIntLiteral(_) => None,
+            argument => match constant(argument)? {
+                Constant::Number(value) => constant_expression(Constant::Number(-value)),
+                Constant::BigInt(value) => constant_expression(Constant::BigInt(value.negate())),
+                _ => None,
+            },
+        },
+
+        UnaryExpressionOperator::Positive => match constant(argument)? {
+            Constant::Number(value) => constant_expression(Constant::Number(value)),
+            _ => None,
+        },
+
+        UnaryExpressionOperator::BinaryNot => match constant(argument)? {
+            Constant::Number(value) => {
+                constant_expression(Constant::Number(!to_int32(value) as f64))
+            }
+            Constant::BigInt(value) => constant_expression(Constant::BigInt(value.not())),
+            _ => None,
+        },
+
+        UnaryExpressionOperator::Void | UnaryExpressionOperator::Delete => None,
+    }
+}
+
+fn fold_binary(
+    operator: &BinaryExpressionOperator,
+    left: Constant,
+    right: Constant,
+) -> Option<Constant> {
+    use BinaryExpressionOperator::*;
+    use Constant::*;
+
+    let value = match (operator, left, right) {
+        (Addition, String(a), b) => String(a + &to_string(&b)?),
+        (Addition, a, String(b)) => String(to_string(&a)? + &b),
+        (Addition, Number(a), Number(b)) => Number(a + b),
+        (Substitution, Number(a), Number(b)) => Number(a - b),
+        (Multiplication, Number(a), Number(b)) => Number(a * b),
+        (Division, Number(a), Number(b)) => Number(a / b),
+        (Modulus, Number(a), Number(b)) => Number(a % b),
+        // Rust and JavaScript disagree on powers of one with a NaN or infinite exponent.
+        (Exponentiation, Number(a), Number(b)) if b.is_finite() => Number(a.powf(b)),
+
+        (LessThan, Number(a), Number(b)) => Boolean(a < b),
+        (LessThanEquals, Number(a), Number(b)) => Boolean(a <= b),
+        (GreaterThan, Number(a), Number(b)) => Boolean(a > b),
+        (GreaterThanEquals, Number(a), Number(b)) => Boolean(a >= b),
+
+        (BitwiseAnd, Number(a), Number(b)) => Number((to_int32(a) & to_int32(b)) as f64),
+        (BitwiseOr, Number(a), Number(b)) => Number((to_int32(a) | to_int32(b)) as f64),
+        (BitwiseXor, Number(a), Number(b)) => Number((to_int32(a) ^ to_int32(b)) as f64),
+        (LeftShift, Number(a), Number(b)) => {
+            Number(to_int32(a).wrapping_shl(to_int32(b) as u32 & 31) as f64)
+        }
+        (RightShift, Number(a), Number(b)) => {
+            Number(to_int32(a).wrapping_shr(to_int32(b) as u32 & 31) as f64)
+        }
+        (UnsignedRightShift, Number(a), Number(b)) => {
+            Number(((to_int32(a) as u32) >> (to_int32(b) as u32 & 31)) as f64)
+        }
+
+        (Addition, BigInt(a), BigInt(b)) => BigInt(a.add(&b)),
+        (Substitution, BigInt(a), BigInt(b)) => BigInt(a.subtract(&b)),
+        (Multiplication, BigInt(a), BigInt(b)) => BigInt(a.multiply(&b)),
+        // Dividing by zero and negative exponents throw a RangeError.
+        (Division, BigInt(a), BigInt(b)) => BigInt(a.divide(&b)?),
+        (Modulus, BigInt(a), BigInt(b)) => BigInt(a.remainder(&b)?),
+        (Exponentiation, BigInt(a), BigInt(b)) => BigInt(a.power(&b)?),
+
+        (LessThan, BigInt(a), BigInt(b)) => Boolean(a < b),
+        (LessThanEquals, BigInt(a), BigInt(b)) => Boolean(a <= b),
+        (GreaterThan, BigInt(a), BigInt(b)) => Boolean(a > b),
+        (GreaterThanEquals, BigInt(a), BigInt(b)) => Boolean(a >= b),
+
+        (BitwiseAnd, BigInt(a), BigInt(b)) => BigInt(a.and(&b)),
+        (BitwiseOr, BigInt(a), BigInt(b)) => BigInt(a.or(&b)),
+        (BitwiseXor, BigInt(a), BigInt(b)) => BigInt(a.xor(&b)),
+        (LeftShift, BigInt(a), BigInt(b)) => BigInt(a.shift_left(&b)?),
+        (RightShift, BigInt(a), BigInt(b)) => BigInt(a.shift_right(&b)?),
+
+        (StrictEquals, a, b) => Boolean(strict_equals(&a, &b)?),
+        (StrictNotEquals, a, b) => Boolean(!strict_equals(&a, &b)?),
+        (LooseEquals, a, b) => Boolean(loose_equals(&a, &b)?),
+        (LooseNotEquals, a, b) => Boolean(!loose_equals(&a, &b)?),
+
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn fold_logical(logical: &LogicalExpression) -> Option<Expression> {
+    let left = &*logical.left;
+    let right = &*logical.right;
+    match logical.operator {
+        LogicalExpressionOperator::And => match known_boolean(left)? {
+            true => Some(right.clone()),
+            false => Some(left.clone()),
+        },
+        LogicalExpressionOperator::Or => match known_boolean(left)? {
+            true => Some(left.clone()),
+            false => Some(right.clone()),
+        },
+        LogicalExpressionOperator::NullishCoalescing => {
+            // Only literals without side effects are known to be nullish or not.
+            known_boolean(left)?;
+            match constant(left) {
+                Some(Constant::Null) | Some(Constant::Undefined) => Some(right.clone()),
+                _ => Some(left.clone()),
+            }
+        }
+    }
+}
+
+/// Returns the result of `===`, None if it is not known because of escapes in strings.
+fn strict_equals(left: &Constant, right: &Constant) -> Option<bool> {
+    match (left, right) {
+        (Constant::Number(a), Constant::Number(b)) => Some(a == b),
+        (a, b) => Some(a == b),
+    }
+}
+
+/// Returns the result of `==`, None for the comparisons that convert between types.
+fn loose_equals(left: &Constant, right: &Constant) -> Option<bool> {
+    match (left, right) {
+        (Constant::Null | Constant::Undefined, Constant::Null | Constant::Undefined) => Some(true),
+        (Constant::Null | Constant::Undefined, _) | (_, Constant::Null | Constant::Undefined) => {
+            Some(false)
+        }
+        (a, b) if std::mem::discriminant(a) == std::mem::discriminant(b) => strict_equals(a, b),
+        _ => None,
+    }
+}
+
+/// Converts a constant to a string like JavaScript does, None for the numbers
+/// where the formatting of Rust differs from the one of JavaScript.
+fn to_string(constant: &Constant) -> Option<String> {
+    let text = match constant {
+        Constant::String(value) => value.clone(),
+        Constant::Boolean(value) => value.to_string(),
+        Constant::Null => "null".to_string(),
+        Constant::Undefined => "undefined".to_string(),
+        Constant::Number(value) if value.is_nan() => "NaN".to_string(),
+        Constant::Number(value) if value.is_infinite() => match *value > 0.0 {
+            true => "Infinity".to_string(),
+            false => "-Infinity".to_string(),
+        },
+        Constant::Number(value) if *value == 0.0 => "0".to_string(),
+        // JavaScript uses exponents outside of this range.
+        Constant::Number(value) if (1e-6..1e21).contains(&value.abs()) => value.to_string(),
+        Constant::Number(_) => return None,
+        Constant::BigInt(value) => value.to_decimal(),
+    };
+    Some(text)
+}
+
+/// Decodes the escapes in the text of a string literal, None for the legacy octal
+/// escapes and for strings that are not valid UTF-16, such as lone surrogates.
+fn unescape_string(text: &str) -> Option<String> {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        let code_unit = match chars.next()? {
+            'n' => '\n' as u32,
+            'r' => '\r' as u32,
+            't' => '\t' as u32,
+            'b' => '\u{8}' as u32,
+            'f' => '\u{c}' as u32,
+            'v' => '\u{b}' as u32,
+            '0' if !chars.peek().is_some_and(char::is_ascii_digit) => 0,
+            // \1 to \9 and \0 followed by a digit are legacy octal escapes.
+            '0'..='9' => return None,
+            'x' => hex_value(&mut chars, 2)?,
+            'u' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut code_point = 0;
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        c => code_point = code_point * 16 + c.to_digit(16)?,
+                    }
+                }
+                value.push(char::from_u32(code_point)?);
+                continue;
+            }
+            'u' => hex_value(&mut chars, 4)?,
+            // Line continuations are not a part of the value.
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => continue,
+            c => c as u32,
+        };
+
+        // A surrogate pair written as two escapes.
+        if (0xD800..0xDC00).contains(&code_unit) {
+            let mut rest = chars.clone();
+            if rest.next() == Some('\\') && rest.next() == Some('u') {
+                let low = hex_value(&mut rest, 4)?;
+                if (0xDC00..0xE000).contains(&low) {
+                    chars = rest;
+                    value.push(char::from_u32(
+                        0x10000 + ((code_unit - 0xD800) << 10) + (low - 0xDC00),
+                    )?);
+                    continue;
+                }
+            }
+        }
+        value.push(char::from_u32(code_unit)?);
+    }
+    Some(value)
+}
+
+/// Reads the given number of hexadecimal digits.
+fn hex_value(chars: &mut impl Iterator<Item = char>, digits: usize) -> Option<u32> {
+    let mut value = 0;
+    for _ in 0..digits {
+        value = value * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(value)
+}
+
+/// Escapes a string value so that it can be used as the text of a string literal,
+/// the quotes and line terminators are escaped by the printer.
+fn escape_string(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.push_str("\\\\"),
+            '\t' => text.push_str("\\t"),
+            // "\01" would be a legacy octal escape.
+            '\0' if !chars.peek().is_some_and(char::is_ascii_digit) => text.push_str("\\0"),
+            c if c < ' ' && c != '\n' && c != '\r' => {
+                text.push_str(&format!("\\x{:02x}", c as u32))
+            }
+            c => text.push(c),
+        }
+    }
+    text
+}
+
+/// The ToInt32 conversion used by the bitwise operators.
+fn to_int32(value: f64) -> i32 {
+    if !value.is_finite() {
+        return 0;
+    }
+    let value = value.trunc() % 4294967296.0;
+    let value = if value < 0.0 {
+        value + 4294967296.0
+    } else {
+        value
+    };
+    value as u32 as i32
+}
@@ -0,0 +1,9 @@
+/// This crate contains the passes that make a program smaller without
+/// changing what it does. They work on the AST and are independent of
+/// the printer, so they can be used with or without whitespace removal.
+mod big_int;
+mod fold;
+mod syntax;
+
+pub use fold::fold_constants;
+pub use syntax::minify_syntax;
@@ -0,0 +1,323 @@
+/// This file contains the syntax minification, it rewrites the program
+/// into shorter code that does the same thing.
+///
+/// Statements are turned into expressions where possible since expressions
+/// can be joined with commas and nested in each other: `if (a) b();` becomes
+/// `a && b();` and an if statement returning in both branches becomes a single
+/// `return` with a conditional. Blocks are removed when they do not scope any
+/// declarations, consecutive variable declarations are merged and literals are
+/// replaced with shorter forms such as `!0` for `true` and `void 0` for
+/// `undefined`. Expression statements without side effects are removed,
+/// which includes the unused calls marked as pure.
+///
+/// The choice of quotes for strings is left to the printer, which always picks
+/// the one that needs the fewest escapes.
+use std::collections::HashMap;
+
+use js_ast::scope::replace_globals;
+use js_ast::side_effects::remove_unused_expressions;
+use js_ast::visit::{walk_expression, walk_statement, VisitorMut};
+use js_ast::*;
+
+/// Rewrites the program into shorter equivalent code.
+pub fn minify_syntax(program: &mut AST) {
+    // A local variable named undefined is left as it is.
+    let mut globals = HashMap::new();
+    globals.insert("undefined".to_string(), void_zero());
+    replace_globals(program, &globals);
+
+    remove_unused_expressions(program);
+    SyntaxMinifier {}.visit_program(program);
+}
+
+struct SyntaxMinifier {}
+
+impl VisitorMut for SyntaxMinifier {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        let old = std::mem::take(statements);
+        for mut statement in old {
+            self.visit_statement(&mut statement);
+            match statement {
+                Statement::EmptyStatement(_) => {}
+                Statement::BlockStatement(b) if !has_lexical_declarations(&b.statements) => {
+                    for statement in b.statements {
+                        push_statement(statements, statement);
+                    }
+                }
+                statement => push_statement(statements, statement),
+            }
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+
+        match statement {
+            Statement::IfStatement(i) => {
+                collapse_block(&mut i.consequent);
+                if let Some(alternate) = &mut i.alternate {
+                    collapse_block(alternate);
+                    // if (a) {} else b(); is the same as if (!a) b();
+                    if matches!(*i.consequent, Statement::EmptyStatement(_)) {
+                        i.test = not(std::mem::replace(&mut i.test, void_zero()));
+                        *i.consequent = std::mem::replace(
+                            &mut **alternate,
+                            Statement::EmptyStatement(EmptyStatement {}),
+                        );
+                        i.alternate = None;
+                    }
+                }
+                // An else after an if without one would be taken by the inner if.
+                if i.alternate.is_some() && ends_with_if_without_else(&i.consequent) {
+                    *i.consequent = Statement::BlockStatement(BlockStatement {
+                        statements: vec![*i.consequent.clone()],
+                    });
+                }
+                if let Some(replacement) = if_to_expression(i) {
+                    *statement = replacement;
+                }
+            }
+            Statement::WhileStatement(w) => collapse_block(&mut w.body),
+            Statement::DoWhileStatement(d) => collapse_block(&mut d.body),
+            Statement::ForStatement(f) => collapse_block(&mut f.body),
+            Statement::ForInStatement(f) => collapse_block(&mut f.body),
+            Statement::ForOfStatement(f) => collapse_block(&mut f.body),
+            Statement::WithStatement(w) => collapse_block(&mut w.body),
+            Statement::LabeledStatement(l) => collapse_block(&mut l.body),
+            _ => {}
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+
+        match expression {
+            Expression::BooleanLiteral(b) => {
+                *expression = not(Expression::NumericLiteral(NumericLiteral {
+                    value: if b.value { 0.0 } else { 1.0 },
+                }))
+            }
+
+            Expression::Member(m) if m.computed => {
+                if let Expression::StringLiteral(s) = &*m.property {
+                    if is_identifier_name(&s.value) {
+                        *m.property = Expression::Identifier(Identifier {
+                            name: s.value.clone(),
+                        });
+                        m.computed = false;
+                    }
+                }
+            }
+
+            // !a ? b : c is the same as a ? c : b
+            Expression::Conditional(c) => {
+                if let Expression::Unary(UnaryExpression {
+                    operator: UnaryExpressionOperator::LogicalNot,
+                    argument,
+                }) = &mut *c.test
+                {
+                    let test = std::mem::replace(&mut **argument, void_zero());
+                    *c.test = test;
+                    std::mem::swap(&mut c.consequence, &mut c.alternate);
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+/// Adds the statement to the list, merging it with the previous statement when possible.
+fn push_statement(statements: &mut Vec<Statement>, statement: Statement) {
+    let statement = match (statements.last_mut(), statement) {
+        (Some(Statement::VariableDeclaration(previous)), Statement::VariableDeclaration(v))
+            if previous.kind == v.kind =>
+        {
+            previous.declarations.extend(v.declarations);
+            return;
+        }
+
+        // A string at the start of a function may be a directive such as "use strict".
+        (Some(Statement::Expression(previous)), Statement::Expression(e))
+            if !matches!(previous.expression, Expression::StringLiteral(_)) =>
+        {
+            let left = std::mem::replace(&mut previous.expression, void_zero());
+            previous.expression = join_with_comma(left, e.expression);
+            return;
+        }
+
+        (_, statement) => statement,
+    };
+
+    // if (a) return b; return c; is the same as return a ? b : c;
+    if let Statement::ReturnStatement(r) = &statement {
+        let is_if_return = matches!(
+            statements.last(),
+            Some(Statement::IfStatement(IfStatement { consequent, alternate: None, .. }))
+                if matches!(**consequent, Statement::ReturnStatement(_))
+        );
+        if is_if_return {
+            if let Some(Statement::IfStatement(i)) = statements.pop() {
+                if let Statement::ReturnStatement(consequent) = *i.consequent {
+                    statements.push(Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(conditional(
+                            i.test,
+                            consequent.expression.unwrap_or_else(void_zero),
+                            r.expression.clone().unwrap_or_else(void_zero),
+                        )),
+                    }));
+                    return;
+                }
+            }
+        }
+    }
+
+    statements.push(statement);
+}
+
+/// Turns an if statement whose branches are expressions or returns into an expression.
+fn if_to_expression(i: &mut IfStatement) -> Option<Statement> {
+    let test = i.test.clone();
+    match (&*i.consequent, i.alternate.as_deref()) {
+        // if (a) b(); is the same as a && b(); and if (!a) b(); as a || b();
+        (Statement::Expression(e), None) => {
+            let expression = match test {
+                Expression::Unary(UnaryExpression {
+                    operator: UnaryExpressionOperator::LogicalNot,
+                    argument,
+                }) => logical(
+                    *argument,
+                    LogicalExpressionOperator::Or,
+                    e.expression.clone(),
+                ),
+                test => logical(test, LogicalExpressionOperator::And, e.expression.clone()),
+            };
+            Some(Statement::Expression(ExpressionStatement { expression }))
+        }
+
+        (Statement::Expression(consequent), Some(Statement::Expression(alternate))) => {
+            Some(Statement::Expression(ExpressionStatement {
+                expression: conditional(
+                    test,
+                    consequent.expression.clone(),
+                    alternate.expression.clone(),
+                ),
+            }))
+        }
+
+        (Statement::ReturnStatement(consequent), Some(Statement::ReturnStatement(alternate))) => {
+            Some(Statement::ReturnStatement(ReturnStatement {
+                expression: Some(conditional(
+                    test,
+                    consequent.expression.clone().unwrap_or_else(void_zero),
+                    alternate.expression.clone().unwrap_or_else(void_zero),
+                )),
+            }))
+        }
+
+        (Statement::EmptyStatement(_), None) => Some(Statement::Expression(ExpressionStatement {
+            expression: test,
+        })),
+
+        _ => None,
+    }
+}
+
+/// Replaces a block containing a single statement with the statement.
+fn collapse_block(statement: &mut Box<Statement>) {
+    if let Statement::BlockStatement(b) = &mut **statement {
+        if has_lexical_declarations(&b.statements) {
+            return;
+        }
+        match b.statements.len() {
+            0 => **statement = Statement::EmptyStatement(EmptyStatement {}),
+            1 => **statement = b.statements.remove(0),
+            _ => {}
+        }
+    }
+}
+
+/// Returns true if the statement ends with an if statement without an else,
+/// which would take an else following the statement.
+fn ends_with_if_without_else(statement: &Statement) -> bool {
+    match statement {
+        Statement::IfStatement(i) => match &i.alternate {
+            None => true,
+            Some(alternate) => ends_with_if_without_else(alternate),
+        },
+        Statement::WhileStatement(w) => ends_with_if_without_else(&w.body),
+        Statement::ForStatement(f) => ends_with_if_without_else(&f.body),
+        Statement::ForInStatement(f) => ends_with_if_without_else(&f.body),
+        Statement::ForOfStatement(f) => ends_with_if_without_else(&f.body),
+        Statement::WithStatement(w) => ends_with_if_without_else(&w.body),
+        Statement::LabeledStatement(l) => ends_with_if_without_else(&l.body),
+        _ => false,
+    }
+}
+
+fn has_lexical_declarations(statements: &[Statement]) -> bool {
+    statements.iter().any(|statement| match statement {
+        Statement::VariableDeclaration(v) => v.kind != VariableDeclarationKind::Var,
+        Statement::ClassDeclaration(_) | Statement::FunctionDeclaration(_) => true,
+        _ => false,
+    })
+}
+
+/// Returns true if the text can be used as the property name in a member expression.
+fn is_identifier_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn join_with_comma(left: Expression, right: Expression) -> Expression {
+    let mut expressions = match left {
+        Expression::Sequence(s) => s.expressions,
+        left => vec![left],
+    };
+    match right {
+        Expression::Sequence(s) => expressions.extend(s.expressions),
+        right => expressions.push(right),
+    }
+    Expression::Sequence(SequenceExpression { expressions })
+}
+
+fn logical(left: Expression, operator: LogicalExpressionOperator, right: Expression) -> Expression {
+    Expression::Logical(LogicalExpression {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
+    })
+}
+
+/// Returns a conditional expression, the branches are swapped if the test is negated.
+fn conditional(test: Expression, consequence: Expression, alternate: Expression) -> Expression {
+    let (test, consequence, alternate) = match test {
+        Expression::Unary(UnaryExpression {
+            operator: UnaryExpressionOperator::LogicalNot,
+            argument,
+        }) => (*argument, alternate, consequence),
+        test => (test, consequence, alternate),
+    };
+    Expression::Conditional(ConditionalExpression {
+        test: Box::new(test),
+        consequence: Box::new(consequence),
+        alternate: Box::new(alternate),
+    })
+}
+
+fn not(expression: Expression) -> Expression {
+    Expression::Unary(UnaryExpression {
+        operator: UnaryExpressionOperator::LogicalNot,
+        argument: Box::new(expression),
+    })
+}
+
+fn void_zero() -> Expression {
+    Expression::Unary(UnaryExpression {
+        operator: UnaryExpressionOperator::Void,
+        argument: Box::new(Expression::NumericLiteral(NumericLiteral { value: 0.0 })),
+    })
+}
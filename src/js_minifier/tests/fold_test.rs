@@ -0,0 +1,178 @@
+use js_minifier::fold_constants;
+use js_parser::parse;
+use js_printer::Printer;
+use logger::LoggerImpl;
+use source::Source;
+
+fn expect_folded(content: &str, expected: &str) {
+    let source = Source {
+        absolute_path: "/test.js",
+        pretty_path: "./test.js",
+        content,
+    };
+
+    let logger = LoggerImpl::new();
+    let mut ast = parse(&source, &logger);
+    fold_constants(&mut ast);
+    let output = Printer::new().print_program(&ast);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_fold_binary_expressions() {
+    expect_folded("a = \"a\" + \"b\";", "a = \"ab\";\n");
+    expect_folded("a = \"a\" + 1 + true;", "a = \"a1true\";\n");
+    expect_folded("a = 1 + 2 * 3;", "a = 7;\n");
+    expect_folded("a = 1 - 3;", "a = -2;\n");
+    expect_folded("a = 1 / 0;", "a = 1 / 0;\n");
+    expect_folded("a = 2 ** 10;", "a = 1024;\n");
+    expect_folded("a = 5 % 3;", "a = 2;\n");
+    expect_folded("a = 1 << 31;", "a = -2147483648;\n");
+    expect_folded("a = -1 >>> 0;", "a = 4294967295;\n");
+    expect_folded("a = 6 & 3 | 8;", "a = 10;\n");
+    expect_folded("a = 1 === 1;", "a = true;\n");
+    expect_folded("a = 1 !== \"1\";", "a = true;\n");
+    expect_folded("a = \"a\" === \"b\";", "a = false;\n");
+    expect_folded("a = null == void 0;", "a = true;\n");
+    expect_folded("a = null == 0;", "a = false;\n");
+    expect_folded("a = 1 < 2;", "a = true;\n");
+
+    // Conversions between types are left alone.
+    expect_folded("a = 1 == \"1\";", "a = 1 == \"1\";\n");
+    expect_folded("a = \"1\" - 1;", "a = \"1\" - 1;\n");
+    // Strings are compared and joined by their values, the escapes are decoded.
+    expect_folded("a = \"\\x61\" === \"a\";", "a = true;\n");
+    expect_folded("a = \"\\0\" + \"1\";", "a = \"\\x001\";\n");
+    expect_folded("a = `\\0` + \"1\";", "a = \"\\x001\";\n");
+    expect_folded("a = \"a\" + 1 + \"\\0\" + 7;", "a = \"a1\\x007\";\n");
+    expect_folded("a = \"\\0\" + \"b\";", "a = \"\\0b\";\n");
+    expect_folded(
+        "a = \"\\ud83d\\ude00\\u{1F600}\\\"'\" + 1;",
+        "a = \"😀😀\\\"'1\";\n",
+    );
+    // Legacy octal escapes and lone surrogates are left alone.
+    expect_folded("a = \"\\1\" + \"1\";", "a = \"\\1\" + \"1\";\n");
+    expect_folded("a = \"\\ud83d\" + \"1\";", "a = \"\\ud83d\" + \"1\";\n");
+    // Non-constant operands are left alone.
+    expect_folded("a = b + 1;", "a = b + 1;\n");
+}
+
+#[test]
+fn test_fold_unary_expressions() {
+    expect_folded("a = !0;", "a = true;\n");
+    expect_folded("a = !\"\";", "a = true;\n");
+    // A line continuation is not a part of the value.
+    expect_folded("a = !\"\\\n\";", "a = true;\n");
+    expect_folded("a = ![];", "a = false;\n");
+    expect_folded("a = !b;", "a = !b;\n");
+    expect_folded("a = -(-1);", "a = 1;\n");
+    expect_folded("a = +1;", "a = 1;\n");
+    expect_folded("a = ~5;", "a = -6;\n");
+    expect_folded("a = typeof \"a\";", "a = \"string\";\n");
+    expect_folded("a = typeof 1;", "a = \"number\";\n");
+    expect_folded("a = typeof null;", "a = \"object\";\n");
+    expect_folded("a = typeof void 0;", "a = \"undefined\";\n");
+    expect_folded("a = typeof function() {};", "a = \"function\";\n");
+    expect_folded("a = typeof {};", "a = \"object\";\n");
+    expect_folded("a = typeof b;", "a = typeof b;\n");
+    expect_folded("a = typeof [b()];", "a = typeof [b()];\n");
+}
+
+#[test]
+fn test_fold_big_int() {
+    expect_folded("a = 9007199254740993n + 1n;", "a = 9007199254740994n;\n");
+    expect_folded("a = 2n ** 64n;", "a = 18446744073709551616n;\n");
+    expect_folded(
+        "a = 0xFFFFFFFFFFFFFFFFn * 0x10n;",
+        "a = 295147905179352825840n;\n",
+    );
+    expect_folded("a = 1n - 3n;", "a = -2n;\n");
+    expect_folded("a = -7n / 2n;", "a = -3n;\n");
+    expect_folded("a = -7n % 2n;", "a = -1n;\n");
+    expect_folded(
+        "a = 100000000000000000000n / 3n;",
+        "a = 33333333333333333333n;\n",
+    );
+    expect_folded("a = -(-5n);", "a = 5n;\n");
+    expect_folded("a = ~5n;", "a = -6n;\n");
+    expect_folded("a = -6n & 0xFFn;", "a = 250n;\n");
+    expect_folded("a = -6n | 1n;", "a = -5n;\n");
+    expect_folded("a = 5n ^ -1n;", "a = -6n;\n");
+    expect_folded("a = 1n << 100n;", "a = 1267650600228229401496703205376n;\n");
+    expect_folded("a = -5n >> 1n;", "a = -3n;\n");
+    expect_folded("a = 5n >> 10n;", "a = 0n;\n");
+    expect_folded("a = 0b1010n === 10n;", "a = true;\n");
+    expect_folded("a = 1n < 0o2n;", "a = true;\n");
+    expect_folded("a = 1n === 1;", "a = false;\n");
+    expect_folded(
+        "a = \"a\" + 18446744073709551616n;",
+        "a = \"a18446744073709551616\";\n",
+    );
+    expect_folded("a = typeof 1n;", "a = \"bigint\";\n");
+    expect_folded("a = 0n ? b : c;", "a = c;\n");
+    expect_folded("if (1n) a();", "a();\n");
+
+    // These throw, or are longer than the expression, and are left alone.
+    expect_folded("a = 1n / 0n;", "a = 1n / 0n;\n");
+    expect_folded("a = 2n ** -1n;", "a = 2n ** -1n;\n");
+    expect_folded("a = 1n + 1;", "a = 1n + 1;\n");
+    expect_folded("a = 1n >>> 0n;", "a = 1n >>> 0n;\n");
+    expect_folded("a = +1n;", "a = +1n;\n");
+    expect_folded("a = 10n ** 1000n;", "a = 10n ** 1000n;\n");
+    expect_folded("a = 1n == 1;", "a = 1n == 1;\n");
+}
+
+#[test]
+fn test_fold_logical_and_conditional_expressions() {
+    expect_folded("a = 1 && b;", "a = b;\n");
+    expect_folded("a = 0 && b;", "a = 0;\n");
+    expect_folded("a = \"\" || b;", "a = b;\n");
+    expect_folded("a = \"a\" || b;", "a = \"a\";\n");
+    expect_folded("a = null ?? b;", "a = b;\n");
+    expect_folded("a = 0 ?? b;", "a = 0;\n");
+    expect_folded("a = b && c;", "a = b && c;\n");
+    expect_folded("a = true ? b : c;", "a = b;\n");
+    expect_folded("a = 1 === 2 ? b : c;", "a = c;\n");
+    // The test is kept when it has side effects.
+    expect_folded("a = [b()] ? c : d;", "a = [b()] ? c : d;\n");
+}
+
+#[test]
+fn test_remove_dead_branches() {
+    expect_folded("if (true) a(); else b();", "a();\n");
+    expect_folded("if (false) a(); else b();", "b();\n");
+    expect_folded("if (false) a();", ";");
+    expect_folded("if (1 === 1) { a(); b(); }", "a();\nb();\n");
+    expect_folded("if (\"production\" !== \"production\") { a(); }", ";");
+
+    // The var declarations of the removed branch are hoisted and have to be kept.
+    expect_folded(
+        "if (false) { var a = 1; a(); } else { b(); }",
+        "b();\nvar a;\n",
+    );
+    // Blocks with lexical declarations are kept to keep the names in their scope.
+    expect_folded("if (true) { let a = 1; }", "{ let a = 1;\n }");
+    expect_folded("while (a) if (false) b();", "while (a) ;");
+}
+
+#[test]
+fn test_remove_unreachable_code() {
+    expect_folded(
+        "function a() { return 1; b(); }",
+        "function a() { return 1;\n }",
+    );
+    expect_folded(
+        "function a() { throw b; var c = 1, d = 2; c(); }",
+        "function a() { throw b;\nvar c, d;\n }",
+    );
+    // Function declarations are hoisted to the top of the function.
+    expect_folded(
+        "function a() { return b(); function b() {} }",
+        "function a() { return b();\nfunction b() {} }",
+    );
+    expect_folded("for (;;) { break; a(); }", "for (; ; ) { break;\n }");
+    expect_folded(
+        "function a() { return; if (b) { var c; } }",
+        "function a() { return;\nvar c;\n }",
+    );
+}
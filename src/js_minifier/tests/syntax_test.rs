@@ -0,0 +1,103 @@
+use js_minifier::minify_syntax;
+use js_parser::parse;
+use js_printer::Printer;
+use logger::LoggerImpl;
+use source::Source;
+
+fn expect_minified(content: &str, expected: &str) {
+    let source = Source {
+        absolute_path: "/test.js",
+        pretty_path: "./test.js",
+        content,
+    };
+
+    let logger = LoggerImpl::new();
+    let mut ast = parse(&source, &logger);
+    minify_syntax(&mut ast);
+    let output = Printer::new().print_program(&ast);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_merge_variable_declarations() {
+    expect_minified("var a = 1; var b = 2;", "var a = 1, b = 2;\n");
+    expect_minified(
+        "let a = 1; let b; const c = 2;",
+        "let a = 1, b;\nconst c = 2;\n",
+    );
+}
+
+#[test]
+fn test_if_statements() {
+    expect_minified("if (a) b();", "a && b();\n");
+    expect_minified("if (!a) b();", "a || b();\n");
+    expect_minified("if (a) { b(); }", "a && b();\n");
+    expect_minified("if (a) b(); else c();", "a ? b() : c();\n");
+    expect_minified("if (!a) b(); else c();", "a ? c() : b();\n");
+    expect_minified("if (a) {} else b();", "a || b();\n");
+    expect_minified("if (a) b = 1;", "a && (b = 1);\n");
+    expect_minified(
+        "function f() { if (a) return b; else return c; }",
+        "function f() { return a ? b : c;\n }",
+    );
+    expect_minified(
+        "function f() { if (a) return b; return; }",
+        "function f() { return a ? b : void 0;\n }",
+    );
+    expect_minified("if (a) { if (b) c(); } else d();", "a ? b && c() : d();\n");
+    // The inner if would take the else if the block was removed.
+    expect_minified(
+        "if (a) { if (b) throw c; } else d();",
+        "if (a) { if (b) throw c;\n } else d();\n",
+    );
+}
+
+#[test]
+fn test_literals() {
+    expect_minified("a(true, false);", "a(!0, !1);\n");
+    expect_minified("a(undefined);", "a(void 0);\n");
+    expect_minified(
+        "function f(undefined) { return undefined; }",
+        "function f(undefined) { return undefined;\n }",
+    );
+    expect_minified("a[\"b\"];", "a.b;\n");
+    expect_minified("a[\"b-c\"];", "a[\"b-c\"];\n");
+    expect_minified("a = !b ? c : d;", "a = b ? d : c;\n");
+}
+
+#[test]
+fn test_blocks_and_expression_statements() {
+    expect_minified("a(); b(); c();", "a(), b(), c();\n");
+    expect_minified("{ a(); { b(); } }", "a(), b();\n");
+    expect_minified("{ let a = 1; }", "{ let a = 1;\n }");
+    expect_minified("while (a) { b(); }", "while (a) b();\n");
+    expect_minified("a(); ; b();", "a(), b();\n");
+    // Directives are not joined with the statements after them.
+    expect_minified(
+        "function f() { \"use strict\"; a(); }",
+        "function f() { \"use strict\";\na();\n }",
+    );
+}
+
+#[test]
+fn test_remove_unused_pure_calls() {
+    expect_minified("/* @__PURE__ */ a()", "");
+    expect_minified("/* @__PURE__ */ new a()", "");
+    expect_minified("/* @__PURE__ */ a(b())", "b();\n");
+    expect_minified("/* @__PURE__ */ a(1, b(), c())", "b(), c();\n");
+    expect_minified("/* @__PURE__ */ a(...b)", "[...b];\n");
+    expect_minified("a()", "a();\n");
+    expect_minified(
+        "let b = /* @__PURE__ */ a()",
+        "let b = /* @__PURE__ */ a();\n",
+    );
+    expect_minified("\"use strict\"; /* @__PURE__ */ a()", "\"use strict\";\n");
+    expect_minified(
+        "function b() { /* @__PURE__ */ a(); return 1 }",
+        "function b() { return 1;\n }",
+    );
+    expect_minified(
+        "/* #__NO_SIDE_EFFECTS__ */ function a() {} a(); a(b())",
+        "/* @__NO_SIDE_EFFECTS__ */ function a() {}b();\n",
+    );
+}
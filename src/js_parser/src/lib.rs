@@ -4,12 +4,14 @@ use js_token::Token;
 use logger::Logger;
 use source::Source;
 
+mod no_side_effects;
+
 /// Parses the given source into an AST.
 pub fn parse<L: Logger>(source: &Source, logger: &L) -> AST {
     let lexer = Lexer::new(source.content, logger);
     let mut parser = Parser::new(lexer, logger);
-    let ast = parser.parse_program();
-    ast
+
+    parser.parse_program()
 }
 
 pub struct ParserError(String);
@@ -22,6 +24,9 @@ pub struct Parser<'a, L: Logger> {
     logger: &'a L,
     /// in statement are only allowed in certain expressions.
     allow_in: bool,
+    /// yield is only an expression inside of generator functions,
+    /// everywhere else it is a regular identifier.
+    allow_yield: bool,
 }
 
 /// Public
@@ -29,28 +34,75 @@ impl<'a, L: Logger> Parser<'a, L> {
     pub fn new(lexer: Lexer<'a, L>, logger: &'a L) -> Parser<'a, L> {
         Parser {
             allow_in: true,
+            allow_yield: false,
             lexer,
             logger,
         }
     }
 
     pub fn parse_program(&mut self) -> AST {
+        // Modules are always strict mode code.
+        self.lexer.set_strict_mode(true);
         let mut statements = Vec::<Statement>::new();
 
-        while &self.lexer.token != &Token::EndOfFile {
+        loop {
+            self.parse_legal_comments(&mut statements);
+            if self.lexer.token == Token::EndOfFile {
+                break;
+            }
             match self.parse_statement() {
-                Ok(s) => statements.push(s),
-                Err(err) => panic!(err.0),
+                Ok(s) => self.push_statement(&mut statements, s),
+                Err(err) => panic!("{}", err.0),
             }
         }
 
-        AST { statements }
+        let mut ast = AST { statements };
+        no_side_effects::mark_no_side_effects_calls(&mut ast);
+        ast
+    }
+
+    /// Legal comments before the current token are kept as statements
+    /// so that they can be preserved in the output.
+    fn parse_legal_comments(&mut self, statements: &mut Vec<Statement>) {
+        for comment in self.lexer.legal_comments.drain(..) {
+            statements.push(Statement::Comment(Comment { text: comment.text }));
+        }
+    }
+
+    /// Adds the statement to the statements of the program or of a block.
+    ///
+    /// Comments are not kept inside of statements, so legal comments inside of
+    /// the statement, such as `a = /*! b */ c;`, are hoisted before it.
+    fn push_statement(&mut self, statements: &mut Vec<Statement>, statement: Statement) {
+        // The legal comments before the current token belong to the next statement.
+        let next = self
+            .lexer
+            .comments_before_token
+            .iter()
+            .filter(|c| c.is_legal())
+            .count();
+        let inside = self.lexer.legal_comments.len() - next;
+        for comment in self.lexer.legal_comments.drain(..inside) {
+            statements.push(Statement::Comment(Comment { text: comment.text }));
+        }
+        statements.push(statement);
+    }
+
+    /// True if the current token is the contextual keyword, such as `async` or `get`.
+    /// Contextual keywords are identifiers everywhere else and can not contain escapes.
+    fn is_contextual_keyword(&self, keyword: &str) -> bool {
+        self.lexer.token == Token::Identifier
+            && self.lexer.identifier == keyword
+            && !self.lexer.has_escape
     }
 
     /// Consumes the next semicolon
     fn consume_semicolon(&mut self) {
-        if self.lexer.token == Token::Semicolon {
-            self.lexer.next_token();
+        match self.lexer.token {
+            Token::Semicolon => self.lexer.next_token(),
+            Token::CloseBrace | Token::EndOfFile => {}
+            _ if self.lexer.has_newline_before => {}
+            _ => self.lexer.unexpected(),
         }
     }
 }
@@ -143,6 +195,9 @@ impl<'a, L: Logger> Parser<'a, L> {
         let mut items: Vec<Option<ArrayBindingItemKind>> = Vec::new();
         while self.lexer.token != Token::CloseBracket {
             match self.lexer.token {
+                // A hole, [, a] = b
+                Token::Comma => items.push(None),
+
                 Token::DotDotDot => {
                     items.push(
                         self.parse_rest_element()
@@ -188,11 +243,33 @@ impl<'a, L: Logger> Parser<'a, L> {
 // Expressions
 impl<'a, L: Logger> Parser<'a, L> {
     fn parse_expression(&mut self, precedence: &Precedence) -> ParseResult<Expression> {
+        if self.lexer.has_pure_comment_before() {
+            let left = self.parse_prefix()?;
+            return self.parse_pure_suffix(precedence, left);
+        }
+
         let left = self.parse_prefix()?;
 
         self.parse_suffix(precedence, left)
     }
 
+    /// Parses the suffix of an expression that was preceded by a pure comment.
+    /// The comment applies to the call or new expression that follows it,
+    /// /* @__PURE__ */ a() + b marks a() as pure but not the addition.
+    fn parse_pure_suffix(
+        &mut self,
+        precedence: &Precedence,
+        left: Expression,
+    ) -> ParseResult<Expression> {
+        let mut left = self.parse_suffix(&Precedence::Call.lower(), left)?;
+        match &mut left {
+            Expression::Call(c) => c.pure = true,
+            Expression::New(n) => n.pure = true,
+            _ => {}
+        }
+        self.parse_suffix(precedence, left)
+    }
+
     fn parse_prefix(&mut self) -> ParseResult<Expression> {
         match &self.lexer.token {
             Token::Null => {
@@ -219,28 +296,28 @@ impl<'a, L: Logger> Parser<'a, L> {
                 Ok(Expression::RegexpLiteral(RegexpLiteral { value }))
             }
 
+            Token::Identifier if self.is_contextual_keyword("async") => {
+                self.parse_async_expression()
+            }
+
+            Token::Identifier if self.allow_yield && self.is_contextual_keyword("yield") => {
+                self.parse_yield_expression()
+            }
+
             Token::Identifier => {
                 let identifier = self.parse_identifier()?;
 
                 // Arrow function
                 if self.lexer.token == Token::EqualsGreaterThan {
                     self.lexer.next_token();
-                    let body = match self.lexer.token {
-                        Token::OpenBrace => self
-                            .parse_block_statement()
-                            .map(ArrowFunctionExpressionBody::BlockStatement)?,
-                        _ => self
-                            .parse_expression(&Precedence::Comma)
-                            .map(Box::new)
-                            .map(ArrowFunctionExpressionBody::Expression)?,
-                    };
-
+                    let body = self.parse_arrow_function_body()?;
                     return Ok(Expression::ArrowFunction(ArrowFunctionExpression {
                         body,
                         parameters: vec![ParameterKind::Parameter(Parameter {
                             binding: Binding::Identifier(identifier),
                             initializer: None,
                         })],
+                        is_async: false,
                     }));
                 }
 
@@ -384,7 +461,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                 Ok(Expression::BooleanLiteral(BooleanLiteral { value: false }))
             }
 
-            Token::OpenParen => self.parse_parenthesized_expression().map(Ok)?,
+            Token::OpenParen => self.parse_parenthesized_expression(false),
 
             // Object expressions
             //
@@ -393,9 +470,12 @@ impl<'a, L: Logger> Parser<'a, L> {
                 self.lexer.next_token();
                 let mut properties: Vec<ObjectExpressionPropertyKind> = Vec::new();
                 while self.lexer.token != Token::CloseBrace {
-                    if self.lexer.token == Token::Comma {
-                        self.lexer.next_token();
-                        continue;
+                    // Properties are separated by a comma, the last one can be followed by one.
+                    if !properties.is_empty() {
+                        self.lexer.eat_token(Token::Comma);
+                        if self.lexer.token == Token::CloseBrace {
+                            break;
+                        }
                     }
 
                     // ...a
@@ -420,14 +500,23 @@ impl<'a, L: Logger> Parser<'a, L> {
                     // set() {} | set: {} | set: [] | set a
                     let mut identifier: Option<Identifier> = None;
 
+                    // async a() {} | async() {} | async: a | async
+                    let is_async = self.parse_async_method_marker(&mut identifier);
+
+                    // *a() {} | *[a]() {}
+                    let generator = identifier.is_none() && self.lexer.token == Token::Asterisk;
+                    if generator {
+                        self.lexer.next_token();
+                    }
+
                     // get a() {} | get() {}
-                    if self.lexer.token == Token::Identifier && self.lexer.identifier == "get" {
+                    if !is_async && !generator && self.is_contextual_keyword("get") {
                         self.lexer.next_token();
                         // get a() {}
                         if self.lexer.token == Token::Identifier {
                             let key = self.parse_literal_property_name()?;
                             let parameters = self.parse_parameters()?;
-                            let body = self.parse_block_statement()?;
+                            let body = self.parse_function_body(false)?;
                             properties.push(ObjectExpressionPropertyKind::MethodGet(
                                 ObjectExpressionMethodGet {
                                     key,
@@ -444,7 +533,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                             let key = self.parse_expression(&Precedence::Comma)?;
                             self.lexer.eat_token(Token::CloseBracket);
                             let parameters = self.parse_parameters()?;
-                            let body = self.parse_block_statement()?;
+                            let body = self.parse_function_body(false)?;
                             properties.push(ObjectExpressionPropertyKind::MethodGetComputed(
                                 ObjectExpressionMethodGetComputed {
                                     key,
@@ -462,13 +551,13 @@ impl<'a, L: Logger> Parser<'a, L> {
                     }
 
                     // set a() {} | set() {}
-                    if self.lexer.token == Token::Identifier && self.lexer.identifier == "set" {
+                    if !is_async && !generator && self.is_contextual_keyword("set") {
                         self.lexer.next_token();
                         // set a() {}
                         if self.lexer.token == Token::Identifier {
                             let key = self.parse_literal_property_name()?;
                             let parameters = self.parse_parameters()?;
-                            let body = self.parse_block_statement()?;
+                            let body = self.parse_function_body(false)?;
                             properties.push(ObjectExpressionPropertyKind::MethodSet(
                                 ObjectExpressionMethodSet {
                                     key,
@@ -485,7 +574,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                             let key = self.parse_expression(&Precedence::Comma)?;
                             self.lexer.eat_token(Token::CloseBracket);
                             let parameters = self.parse_parameters()?;
-                            let body = self.parse_block_statement()?;
+                            let body = self.parse_function_body(false)?;
                             properties.push(ObjectExpressionPropertyKind::MethodSetComputed(
                                 ObjectExpressionMethodSetComputed {
                                     key,
@@ -518,9 +607,11 @@ impl<'a, L: Logger> Parser<'a, L> {
                             ));
                         } else if self.lexer.token == Token::OpenParen {
                             let parameters = self.parse_parameters()?;
-                            let body = self.parse_block_statement()?;
+                            let body = self.parse_function_body(generator)?;
                             properties.push(ObjectExpressionPropertyKind::MethodComputed(
                                 ObjectExpressionMethodComputed {
+                                    is_async,
+                                    generator,
                                     key,
                                     parameters,
                                     body,
@@ -552,9 +643,11 @@ impl<'a, L: Logger> Parser<'a, L> {
 
                     if self.lexer.token == Token::OpenParen {
                         let parameters = self.parse_parameters()?;
-                        let body = self.parse_block_statement()?;
+                        let body = self.parse_function_body(generator)?;
                         properties.push(ObjectExpressionPropertyKind::Method(
                             ObjectExpressionMethod {
+                                is_async,
+                                generator,
                                 key,
                                 parameters,
                                 body,
@@ -585,6 +678,7 @@ impl<'a, L: Logger> Parser<'a, L> {
             // [a, b, c]
             Token::OpenBracket => {
                 self.lexer.next_token();
+                let allow_in = std::mem::replace(&mut self.allow_in, true);
                 let mut elements: Vec<Option<ArrayExpressionItem>> = Vec::new();
                 while self.lexer.token != Token::CloseBracket {
                     match self.lexer.token {
@@ -608,6 +702,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                     }
                 }
                 self.lexer.eat_token(Token::CloseBracket);
+                self.allow_in = allow_in;
                 Ok(Expression::Array(ArrayExpression { items: elements }))
             }
 
@@ -623,32 +718,23 @@ impl<'a, L: Logger> Parser<'a, L> {
                 if self.lexer.token == Token::OpenParen {
                     arguments = self.parse_arguments()?;
                 }
-                Ok(Expression::New(NewExpression { arguments, callee }))
+                Ok(Expression::New(NewExpression {
+                    arguments,
+                    callee,
+                    pure: false,
+                }))
             }
 
             // Function expression
             // let a = function b() {}
             // let a = function () {}
-            Token::Function => {
+            Token::Function => self.parse_function_expression(false),
+
+            // await a
+            Token::Await => {
                 self.lexer.next_token();
-                let generator = match self.lexer.token {
-                    Token::Asterisk => {
-                        self.lexer.next_token();
-                        true
-                    }
-                    _ => false,
-                };
-                let identifier = match self.lexer.token {
-                    Token::Identifier => self.parse_identifier().map(Some)?,
-                    _ => None,
-                };
-                let parameters = self.parse_parameters()?;
-                let body = self.parse_block_statement()?;
-                Ok(Expression::Function(FunctionExpression {
-                    generator,
-                    parameters,
-                    body,
-                    identifier,
+                Ok(Expression::Await(AwaitExpression {
+                    argument: self.parse_expression(&Precedence::Prefix).map(Box::new)?,
                 }))
             }
 
@@ -664,6 +750,12 @@ impl<'a, L: Logger> Parser<'a, L> {
                 Ok(Expression::Super(SuperExpression {}))
             }
 
+            // import("a"), import.meta
+            Token::Import => {
+                self.lexer.next_token();
+                self.parse_import_expression()
+            }
+
             _ => self.lexer.unexpected(),
         }
     }
@@ -680,7 +772,9 @@ impl<'a, L: Logger> Parser<'a, L> {
                 // a[b][c]
                 Token::OpenBracket => {
                     self.lexer.next_token();
+                    let allow_in = std::mem::replace(&mut self.allow_in, true);
                     let property = self.parse_expression(&Precedence::Lowest).map(Box::new)?;
+                    self.allow_in = allow_in;
                     self.lexer.eat_token(Token::CloseBracket);
                     expression = Expression::Member(MemberExpression {
                         object: Box::new(expression),
@@ -692,10 +786,18 @@ impl<'a, L: Logger> Parser<'a, L> {
                 // a.b.c
                 Token::Dot => {
                     self.lexer.next_token();
+                    // Keywords are allowed as property names, a.default is valid.
+                    let property = if self.lexer.is_identifier_or_keyword() {
+                        let name = self.lexer.identifier.clone();
+                        self.lexer.next_token();
+                        Expression::Identifier(Identifier { name })
+                    } else {
+                        self.parse_expression(&Precedence::Member)?
+                    };
                     expression = Expression::Member(MemberExpression {
                         object: Box::new(expression),
                         computed: false,
-                        property: self.parse_expression(&Precedence::Member).map(Box::new)?,
+                        property: Box::new(property),
                     });
                 }
 
@@ -1295,6 +1397,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                     expression = Expression::Call(CallExpression {
                         arguments,
                         callee: Box::new(expression),
+                        pure: false,
                     });
                 }
 
@@ -1384,6 +1487,162 @@ impl<'a, L: Logger> Parser<'a, L> {
         }
     }
 
+    /// Parses a function expression, the current token is the function keyword.
+    fn parse_function_expression(&mut self, is_async: bool) -> ParseResult<Expression> {
+        self.lexer.eat_token(Token::Function);
+        let generator = match self.lexer.token {
+            Token::Asterisk => {
+                self.lexer.next_token();
+                true
+            }
+            _ => false,
+        };
+        let identifier = match self.lexer.token {
+            Token::Identifier => self.parse_identifier().map(Some)?,
+            _ => None,
+        };
+        let parameters = self.parse_parameters()?;
+        let body = self.parse_function_body(generator)?;
+        Ok(Expression::Function(FunctionExpression {
+            generator,
+            is_async,
+            parameters,
+            body,
+            identifier,
+        }))
+    }
+
+    /// Parses an expression starting with the contextual keyword async.
+    ///
+    /// async function a() {} | async a => {} | async (a) => {} | async(a) | async
+    fn parse_async_expression(&mut self) -> ParseResult<Expression> {
+        let identifier = self.parse_identifier()?;
+        match self.lexer.token {
+            Token::Function => self.parse_function_expression(true),
+            Token::OpenParen => self.parse_parenthesized_expression(true),
+            // async a => {}
+            Token::Identifier => {
+                let parameter = self.parse_identifier()?;
+                self.lexer.eat_token(Token::EqualsGreaterThan);
+                let body = self.parse_arrow_function_body()?;
+                Ok(Expression::ArrowFunction(ArrowFunctionExpression {
+                    body,
+                    parameters: vec![ParameterKind::Parameter(Parameter {
+                        binding: Binding::Identifier(parameter),
+                        initializer: None,
+                    })],
+                    is_async: true,
+                }))
+            }
+            // async => {}, an arrow function with a parameter named async.
+            Token::EqualsGreaterThan => {
+                self.lexer.next_token();
+                let body = self.parse_arrow_function_body()?;
+                Ok(Expression::ArrowFunction(ArrowFunctionExpression {
+                    body,
+                    parameters: vec![ParameterKind::Parameter(Parameter {
+                        binding: Binding::Identifier(identifier),
+                        initializer: None,
+                    })],
+                    is_async: false,
+                }))
+            }
+            _ => Ok(Expression::Identifier(identifier)),
+        }
+    }
+
+    /// yield | yield a | yield* a
+    fn parse_yield_expression(&mut self) -> ParseResult<Expression> {
+        self.lexer.next_token();
+        let delegate = match self.lexer.token {
+            Token::Asterisk => {
+                self.lexer.next_token();
+                true
+            }
+            _ => false,
+        };
+        // The argument is optional, yield without one ends where an expression can not begin.
+        let argument = match self.lexer.token {
+            Token::CloseParen
+            | Token::CloseBracket
+            | Token::CloseBrace
+            | Token::Comma
+            | Token::Colon
+            | Token::Semicolon
+            | Token::EndOfFile => None,
+            _ => self
+                .parse_expression(&Precedence::Comma)
+                .map(Box::new)
+                .map(Some)?,
+        };
+        Ok(Expression::Yield(YieldExpression { argument, delegate }))
+    }
+
+    /// Parses the body of a function, yield is an expression in the body of generators.
+    fn parse_function_body(&mut self, generator: bool) -> ParseResult<BlockStatement> {
+        let allow_yield = std::mem::replace(&mut self.allow_yield, generator);
+        let allow_in = std::mem::replace(&mut self.allow_in, true);
+        let body = self.parse_function_block();
+        self.allow_yield = allow_yield;
+        self.allow_in = allow_in;
+        body
+    }
+
+    /// Parses the block of a function, a `"use strict"` directive in its
+    /// prologue makes the rest of the function strict mode code.
+    fn parse_function_block(&mut self) -> ParseResult<BlockStatement> {
+        let strict_mode = self.lexer.strict_mode;
+        self.lexer.eat_token(Token::OpenBrace);
+        let mut statements: Vec<Statement> = Vec::new();
+        while self.lexer.token == Token::StringLiteral {
+            self.parse_legal_comments(&mut statements);
+            // The directive has to be written without escapes, which the raw value guarantees.
+            if self.lexer.identifier == "use strict" {
+                self.lexer.set_strict_mode(true);
+            }
+            let statement = self.parse_statement()?;
+            let is_directive = matches!(
+                &statement,
+                Statement::Expression(ExpressionStatement {
+                    expression: Expression::StringLiteral(_)
+                })
+            );
+            self.push_statement(&mut statements, statement);
+            if !is_directive {
+                break;
+            }
+        }
+        loop {
+            self.parse_legal_comments(&mut statements);
+            if self.lexer.token == Token::CloseBrace {
+                break;
+            }
+            let statement = self.parse_statement()?;
+            self.push_statement(&mut statements, statement);
+        }
+        self.lexer.expect_token(Token::CloseBrace);
+        self.lexer.set_strict_mode(strict_mode);
+        self.lexer.next_token();
+        Ok(BlockStatement { statements })
+    }
+
+    /// Parses the body of an arrow function, the current token is the one following "=>".
+    fn parse_arrow_function_body(&mut self) -> ParseResult<ArrowFunctionExpressionBody> {
+        // Arrow functions can not be generators.
+        let allow_yield = std::mem::replace(&mut self.allow_yield, false);
+        let body = match self.lexer.token {
+            Token::OpenBrace => self
+                .parse_function_block()
+                .map(ArrowFunctionExpressionBody::BlockStatement),
+            _ => self
+                .parse_expression(&Precedence::Comma)
+                .map(Box::new)
+                .map(ArrowFunctionExpressionBody::Expression),
+        };
+        self.allow_yield = allow_yield;
+        body
+    }
+
     fn parse_parameters(&mut self) -> ParseResult<Vec<ParameterKind>> {
         self.lexer.eat_token(Token::OpenParen);
         let mut parameters: Vec<ParameterKind> = Vec::new();
@@ -1430,8 +1689,14 @@ impl<'a, L: Logger> Parser<'a, L> {
     // report it as a syntax error and assume that the user was attempting to write an arrow function.
     // Note: Another possible solution to this problem could be to make use of a backtracking algorithm,
     // but this is not something the lexer currently support.
-    fn parse_parenthesized_expression(&mut self) -> ParseResult<Expression> {
+    //
+    // The same applies to async arrow functions, async (a, b) => {} is an arrow function
+    // while async(a, b) is a call to a function named async. When is_async is true the
+    // async identifier has already been parsed and the parenthesis are its arguments.
+    fn parse_parenthesized_expression(&mut self, is_async: bool) -> ParseResult<Expression> {
         self.lexer.eat_token(Token::OpenParen);
+        // The in operator is allowed inside of parentheses in the head of a for loop.
+        let allow_in = std::mem::replace(&mut self.allow_in, true);
         let mut expressions: Vec<Expression> = Vec::new();
         let mut rest_element: Option<RestElement> = None;
         while self.lexer.token != Token::CloseParen {
@@ -1450,6 +1715,7 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
         }
         self.lexer.eat_token(Token::CloseParen);
+        self.allow_in = allow_in;
 
         // Arrow function
         if self.lexer.token == Token::EqualsGreaterThan {
@@ -1469,30 +1735,36 @@ impl<'a, L: Logger> Parser<'a, L> {
                 parameters.push(ParameterKind::Rest(rest_elem));
             }
 
-            let body = match self.lexer.token {
-                Token::OpenBrace => self
-                    .parse_block_statement()
-                    .map(ArrowFunctionExpressionBody::BlockStatement)?,
-                _ => self
-                    .parse_expression(&Precedence::Comma)
-                    .map(Box::new)
-                    .map(ArrowFunctionExpressionBody::Expression)?,
-            };
-
+            let body = self.parse_arrow_function_body()?;
             return Ok(Expression::ArrowFunction(ArrowFunctionExpression {
                 body,
                 parameters,
+                is_async,
             }));
         }
 
         // Rest elements are only allowed as a parameters
         // and in bindings, this is a syntax error.
-        if let Some(_) = rest_element {
+        if rest_element.is_some() {
             panic!("Rest elements are only allowed as bindings on parameters");
         }
 
+        // async(a, b)
+        if is_async {
+            return Ok(Expression::Call(CallExpression {
+                callee: Box::new(Expression::Identifier(Identifier {
+                    name: String::from("async"),
+                })),
+                arguments: expressions
+                    .into_iter()
+                    .map(ArgumentKind::Expression)
+                    .collect(),
+                pure: false,
+            }));
+        }
+
         // A parenthesized expression
-        if expressions.len() > 0 {
+        if !expressions.is_empty() {
             return Ok(Expression::Sequence(SequenceExpression { expressions }));
         }
 
@@ -1630,12 +1902,16 @@ impl<'a, L: Logger> Parser<'a, L> {
 
             Expression::Identifier(i) => Ok(Binding::Identifier(i)),
 
-            _ => return Err(ParserError("Not convertible".into())),
+            _ => Err(ParserError("Not convertible".into())),
         }
     }
 
     fn parse_class_body(&mut self) -> ParseResult<Vec<ClassPropertyKind>> {
-        self.lexer.eat_token(Token::OpenBrace);
+        // The body of a class is always strict mode code.
+        let strict_mode = self.lexer.strict_mode;
+        self.lexer.expect_token(Token::OpenBrace);
+        self.lexer.set_strict_mode(true);
+        self.lexer.next_token();
         let mut properties: Vec<ClassPropertyKind> = Vec::new();
         while self.lexer.token != Token::CloseBrace {
             if self.lexer.token == Token::Semicolon {
@@ -1656,9 +1932,9 @@ impl<'a, L: Logger> Parser<'a, L> {
             let mut identifier: Option<Identifier> = None;
 
             let mut is_static = false;
-            if self.lexer.token == Token::Identifier && self.lexer.identifier == "static" {
+            if self.is_contextual_keyword("static") {
                 self.lexer.next_token();
-                if self.lexer.token == Token::Identifier {
+                if self.is_property_name_start() {
                     is_static = true;
                 } else {
                     identifier = Some(Identifier {
@@ -1667,8 +1943,21 @@ impl<'a, L: Logger> Parser<'a, L> {
                 }
             }
 
+            // async a() {} | async() {}, there can not be a newline after async.
+            let is_async = self.parse_async_method_marker(&mut identifier);
+
+            // *a() {} | *[a]() {}
+            let generator = identifier.is_none() && self.lexer.token == Token::Asterisk;
+            if generator {
+                self.lexer.next_token();
+            }
+
             // Note: A constructor can't be a marker.
-            if self.lexer.token == Token::Identifier && self.lexer.identifier == "constructor" {
+            if !is_async
+                && !generator
+                && self.lexer.token == Token::Identifier
+                && self.lexer.identifier == "constructor"
+            {
                 self.lexer.next_token();
                 let parameters = self.parse_parameters()?;
                 let body = self.parse_block_statement()?;
@@ -1681,7 +1970,7 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
 
             // get a() {} | get() {}
-            if self.lexer.token == Token::Identifier && self.lexer.identifier == "get" {
+            if !is_async && !generator && self.is_contextual_keyword("get") {
                 self.lexer.next_token();
 
                 if self.lexer.token == Token::Identifier {
@@ -1721,7 +2010,7 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
 
             // set a() {} | set() {}
-            if self.lexer.token == Token::Identifier && self.lexer.identifier == "set" {
+            if !is_async && !generator && self.is_contextual_keyword("set") {
                 self.lexer.next_token();
 
                 if self.lexer.token == Token::Identifier {
@@ -1769,6 +2058,8 @@ impl<'a, L: Logger> Parser<'a, L> {
                 let body = self.parse_block_statement()?;
                 properties.push(ClassPropertyKind::MethodComputed(ClassMethodComputed {
                     body,
+                    is_async,
+                    generator,
                     is_static,
                     key,
                     parameters,
@@ -1786,18 +2077,45 @@ impl<'a, L: Logger> Parser<'a, L> {
             let body = self.parse_block_statement()?;
             properties.push(ClassPropertyKind::Method(ClassMethod {
                 body,
+                is_async,
+                generator,
                 identifier: actual_identifier,
                 is_static,
                 parameters,
             }));
         }
 
-        self.lexer.eat_token(Token::CloseBrace);
+        self.lexer.expect_token(Token::CloseBrace);
+        self.lexer.set_strict_mode(strict_mode);
+        self.lexer.next_token();
         Ok(properties)
     }
 
+    /// Parses the source of an import expression, the import keyword has already been consumed.
+    fn parse_import_expression(&mut self) -> ParseResult<Expression> {
+        if self.lexer.token == Token::Dot {
+            self.lexer.next_token();
+            if !self.is_contextual_keyword("meta") {
+                self.lexer.unexpected();
+            }
+            self.lexer.next_token();
+            return Ok(Expression::ImportMeta(ImportMetaExpression {}));
+        }
+
+        self.lexer.eat_token(Token::OpenParen);
+        let chunk_name = self
+            .lexer
+            .comments_before_token
+            .iter()
+            .find_map(|comment| comment.webpack_chunk_name());
+        let source = self.parse_expression(&Precedence::Comma).map(Box::new)?;
+        self.lexer.eat_token(Token::CloseParen);
+        Ok(Expression::Import(ImportExpression { source, chunk_name }))
+    }
+
     fn parse_arguments(&mut self) -> ParseResult<Vec<ArgumentKind>> {
         self.lexer.eat_token(Token::OpenParen);
+        let allow_in = std::mem::replace(&mut self.allow_in, true);
         let mut arguments: Vec<ArgumentKind> = Vec::new();
         while self.lexer.token != Token::CloseParen {
             if self.lexer.token == Token::DotDotDot {
@@ -1814,6 +2132,7 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
         }
         self.lexer.eat_token(Token::CloseParen);
+        self.allow_in = allow_in;
         Ok(arguments)
     }
 
@@ -1834,6 +2153,31 @@ impl<'a, L: Logger> Parser<'a, L> {
         Ok(string_literal)
     }
 
+    /// Parses the `async` marker of a method, `async` is stored in `identifier`
+    /// when it is the name of the property instead.
+    fn parse_async_method_marker(&mut self, identifier: &mut Option<Identifier>) -> bool {
+        if identifier.is_some() || !self.is_contextual_keyword("async") {
+            return false;
+        }
+        self.lexer.next_token();
+        if !self.lexer.has_newline_before && self.is_property_name_start() {
+            return true;
+        }
+        *identifier = Some(Identifier {
+            name: String::from("async"),
+        });
+        false
+    }
+
+    /// Returns true if the current token can start the name of a property,
+    /// used to tell markers like `static` apart from property names.
+    fn is_property_name_start(&self) -> bool {
+        matches!(
+            self.lexer.token,
+            Token::OpenBracket | Token::StringLiteral | Token::NumericLiteral | Token::Asterisk
+        ) || self.lexer.is_identifier_or_keyword()
+    }
+
     fn parse_literal_property_name(&mut self) -> ParseResult<LiteralPropertyName> {
         match self.lexer.token {
             Token::StringLiteral => {
@@ -1843,7 +2187,7 @@ impl<'a, L: Logger> Parser<'a, L> {
 
             Token::NumericLiteral => {
                 let numeric_literal = NumericLiteral {
-                    value: self.lexer.number.clone(),
+                    value: self.lexer.number,
                 };
                 self.lexer.next_token();
                 Ok(LiteralPropertyName::Numeric(numeric_literal))
@@ -1878,15 +2222,21 @@ impl<'a, L: Logger> Parser<'a, L> {
 impl<'a, L: Logger> Parser<'a, L> {
     fn parse_statement(&mut self) -> ParseResult<Statement> {
         match &self.lexer.token {
-            Token::Const | Token::Var | Token::Let => self
-                .parse_variable_declaration()
-                .map(Statement::VariableDeclaration),
+            Token::Const | Token::Var | Token::Let => {
+                let declaration = self.parse_variable_declaration()?;
+                self.consume_semicolon();
+                Ok(Statement::VariableDeclaration(declaration))
+            }
 
             Token::Import => {
                 self.lexer.next_token();
 
-                if self.lexer.token == Token::OpenParen {
-                    panic!("Import expressions are not yet supported");
+                // import("a").then(b), import.meta.a
+                if self.lexer.token == Token::OpenParen || self.lexer.token == Token::Dot {
+                    let import = self.parse_import_expression()?;
+                    let expression = self.parse_suffix(&Precedence::Lowest, import)?;
+                    self.consume_semicolon();
+                    return Ok(Statement::Expression(ExpressionStatement { expression }));
                 }
 
                 if self.lexer.token == Token::StringLiteral {
@@ -1955,6 +2305,8 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
 
             Token::Export => {
+                // The annotation may be placed before export, default or function.
+                let mut no_side_effects = self.lexer.has_no_side_effects_comment_before();
                 self.lexer.next_token();
 
                 // export * from "a";
@@ -1970,9 +2322,11 @@ impl<'a, L: Logger> Parser<'a, L> {
 
                 // export default
                 if self.lexer.token == Token::Default {
+                    no_side_effects |= self.lexer.has_no_side_effects_comment_before();
                     self.lexer.next_token();
                     let declaration = match self.lexer.token {
                         Token::Function => {
+                            no_side_effects |= self.lexer.has_no_side_effects_comment_before();
                             self.lexer.next_token();
                             let generator = match self.lexer.token {
                                 Token::Asterisk => {
@@ -1986,20 +2340,23 @@ impl<'a, L: Logger> Parser<'a, L> {
                                 _ => None,
                             };
                             let parameters = self.parse_parameters()?;
-                            let body = self.parse_block_statement()?;
+                            let body = self.parse_function_body(generator)?;
                             if let Some(ident) = identifier {
                                 ExportDefaultDeclarationKind::FunctionDeclaration(
                                     FunctionDeclaration {
                                         generator,
+                                        is_async: false,
                                         identifier: ident,
                                         parameters,
                                         body,
+                                        no_side_effects,
                                     },
                                 )
                             } else {
                                 ExportDefaultDeclarationKind::AnonymousDefaultExportedFunctionDeclaration(
                                     AnonymousDefaultExportedFunctionDeclaration {
                                         generator,
+                                        is_async: false,
                                         body,
                                         parameters,
                                     },
@@ -2007,6 +2364,40 @@ impl<'a, L: Logger> Parser<'a, L> {
                             }
                         }
 
+                        // export default async function () {}
+                        Token::Identifier if self.is_contextual_keyword("async") => {
+                            match self.parse_async_expression()? {
+                                Expression::Function(f) => match f.identifier {
+                                    Some(identifier) => {
+                                        ExportDefaultDeclarationKind::FunctionDeclaration(
+                                            FunctionDeclaration {
+                                                generator: f.generator,
+                                                is_async: true,
+                                                identifier,
+                                                parameters: f.parameters,
+                                                body: f.body,
+                                                no_side_effects,
+                                            },
+                                        )
+                                    }
+                                    None => ExportDefaultDeclarationKind::AnonymousDefaultExportedFunctionDeclaration(
+                                        AnonymousDefaultExportedFunctionDeclaration {
+                                            generator: f.generator,
+                                            is_async: true,
+                                            body: f.body,
+                                            parameters: f.parameters,
+                                        },
+                                    ),
+                                },
+                                expression => {
+                                    let expression =
+                                        self.parse_suffix(&Precedence::Comma, expression)?;
+                                    self.consume_semicolon();
+                                    ExportDefaultDeclarationKind::Expression(expression)
+                                }
+                            }
+                        }
+
                         Token::Class => {
                             self.lexer.next_token();
                             let identifier = match self.lexer.token {
@@ -2033,9 +2424,11 @@ impl<'a, L: Logger> Parser<'a, L> {
                             }
                         }
 
-                        _ => self
-                            .parse_expression(&Precedence::Comma)
-                            .map(ExportDefaultDeclarationKind::Expression)?,
+                        _ => {
+                            let expression = self.parse_expression(&Precedence::Comma)?;
+                            self.consume_semicolon();
+                            ExportDefaultDeclarationKind::Expression(expression)
+                        }
                     };
                     return Ok(Statement::ExportDefaultDeclaration(
                         ExportDefaultDeclaration { declaration },
@@ -2046,6 +2439,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                 match self.lexer.token {
                     // export function a() {}
                     Token::Function => {
+                        no_side_effects |= self.lexer.has_no_side_effects_comment_before();
                         self.lexer.next_token();
                         let generator = match self.lexer.token {
                             Token::Asterisk => {
@@ -2056,19 +2450,46 @@ impl<'a, L: Logger> Parser<'a, L> {
                         };
                         let identifier = self.parse_identifier()?;
                         let parameters = self.parse_parameters()?;
-                        let body = self.parse_block_statement()?;
+                        let body = self.parse_function_body(generator)?;
                         Ok(Statement::ExportNamedDeclaration(ExportNamedDeclaration {
                             declaration: ExportNamedDeclarationKind::FunctionDeclaration(
                                 FunctionDeclaration {
                                     generator,
+                                    is_async: false,
                                     parameters,
                                     body,
                                     identifier,
+                                    no_side_effects,
                                 },
                             ),
                         }))
                     }
 
+                    // export async function a() {}
+                    Token::Identifier if self.is_contextual_keyword("async") => {
+                        match self.parse_async_expression()? {
+                            Expression::Function(FunctionExpression {
+                                identifier: Some(identifier),
+                                parameters,
+                                body,
+                                generator,
+                                ..
+                            }) => Ok(Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                                declaration: ExportNamedDeclarationKind::FunctionDeclaration(
+                                    FunctionDeclaration {
+                                        generator,
+                                        is_async: true,
+                                        parameters,
+                                        body,
+                                        identifier,
+                                        no_side_effects,
+                                    },
+                                ),
+                            })),
+                            _ => self.lexer.unexpected(),
+                        }
+                    }
+
                     // export class A {}
                     Token::Class => {
                         self.lexer.next_token();
@@ -2150,6 +2571,7 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
 
             Token::Function => {
+                let no_side_effects = self.lexer.has_no_side_effects_comment_before();
                 self.lexer.next_token();
                 let generator = match self.lexer.token {
                     Token::Asterisk => {
@@ -2160,12 +2582,14 @@ impl<'a, L: Logger> Parser<'a, L> {
                 };
                 let identifier = self.parse_identifier()?;
                 let parameters = self.parse_parameters()?;
-                let body = self.parse_block_statement()?;
+                let body = self.parse_function_body(generator)?;
                 Ok(Statement::FunctionDeclaration(FunctionDeclaration {
                     generator,
+                    is_async: false,
                     identifier,
                     body,
                     parameters,
+                    no_side_effects,
                 }))
             }
 
@@ -2253,6 +2677,11 @@ impl<'a, L: Logger> Parser<'a, L> {
                 self.lexer.eat_token(Token::OpenParen);
                 let test = self.parse_expression(&Precedence::Lowest)?;
                 self.lexer.eat_token(Token::CloseParen);
+                // A semicolon is inserted after a do-while statement even without
+                // a line terminator, `do {} while (a) b` is two statements.
+                if self.lexer.token == Token::Semicolon {
+                    self.lexer.next_token();
+                }
                 Ok(Statement::DoWhileStatement(DoWhileStatement {
                     body: Box::new(body),
                     test,
@@ -2318,7 +2747,40 @@ impl<'a, L: Logger> Parser<'a, L> {
                 }))
             }
 
+            // async function a() {}
+            Token::Identifier if self.is_contextual_keyword("async") => {
+                let no_side_effects = self.lexer.has_no_side_effects_comment_before();
+                match self.parse_async_expression()? {
+                    Expression::Function(FunctionExpression {
+                        identifier: Some(identifier),
+                        parameters,
+                        body,
+                        generator,
+                        ..
+                    }) => Ok(Statement::FunctionDeclaration(FunctionDeclaration {
+                        generator,
+                        is_async: true,
+                        identifier,
+                        body,
+                        parameters,
+                        no_side_effects,
+                    })),
+                    expression => {
+                        let expression = self.parse_suffix(&Precedence::Lowest, expression)?;
+                        self.consume_semicolon();
+                        Ok(Statement::Expression(ExpressionStatement { expression }))
+                    }
+                }
+            }
+
+            Token::Identifier if self.allow_yield && self.is_contextual_keyword("yield") => {
+                let expression = self.parse_expression(&Precedence::Lowest)?;
+                self.consume_semicolon();
+                Ok(Statement::Expression(ExpressionStatement { expression }))
+            }
+
             Token::Identifier => {
+                let pure = self.lexer.has_pure_comment_before();
                 let identifier = self.parse_identifier()?;
                 // Parse a labeled statement
                 if self.lexer.token == Token::Colon {
@@ -2333,15 +2795,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                 // Arrow function
                 if self.lexer.token == Token::EqualsGreaterThan {
                     self.lexer.next_token();
-                    let body = match self.lexer.token {
-                        Token::OpenBrace => self
-                            .parse_block_statement()
-                            .map(ArrowFunctionExpressionBody::BlockStatement)?,
-                        _ => self
-                            .parse_expression(&Precedence::Comma)
-                            .map(Box::new)
-                            .map(ArrowFunctionExpressionBody::Expression)?,
-                    };
+                    let body = self.parse_arrow_function_body()?;
                     return Ok(Statement::Expression(ExpressionStatement {
                         expression: Expression::ArrowFunction(ArrowFunctionExpression {
                             body,
@@ -2349,20 +2803,26 @@ impl<'a, L: Logger> Parser<'a, L> {
                                 binding: Binding::Identifier(identifier),
                                 initializer: None,
                             })],
+                            is_async: false,
                         }),
                     }));
                 }
 
                 // Parse a normal expression
-                let expression =
-                    self.parse_suffix(&Precedence::Lowest, Expression::Identifier(identifier))?;
+                let left = Expression::Identifier(identifier);
+                let expression = if pure {
+                    self.parse_pure_suffix(&Precedence::Lowest, left)?
+                } else {
+                    self.parse_suffix(&Precedence::Lowest, left)?
+                };
                 self.consume_semicolon();
-                return Ok(Statement::Expression(ExpressionStatement { expression }));
+                Ok(Statement::Expression(ExpressionStatement { expression }))
             }
 
             Token::Throw => {
                 self.lexer.next_token();
                 let argument = self.parse_expression(&Precedence::Lowest)?;
+                self.consume_semicolon();
                 Ok(Statement::ThrowStatement(ThrowStatement { argument }))
             }
 
@@ -2445,8 +2905,13 @@ impl<'a, L: Logger> Parser<'a, L> {
     fn parse_block_statement(&mut self) -> ParseResult<BlockStatement> {
         self.lexer.eat_token(Token::OpenBrace);
         let mut statements: Vec<Statement> = Vec::new();
-        while self.lexer.token != Token::CloseBrace {
-            statements.push(self.parse_statement()?);
+        loop {
+            self.parse_legal_comments(&mut statements);
+            if self.lexer.token == Token::CloseBrace {
+                break;
+            }
+            let statement = self.parse_statement()?;
+            self.push_statement(&mut statements, statement);
         }
         self.lexer.eat_token(Token::CloseBrace);
         Ok(BlockStatement { statements })
@@ -2463,22 +2928,16 @@ impl<'a, L: Logger> Parser<'a, L> {
         self.lexer.eat_token(Token::CloseParen);
 
         let consequent = self.parse_statement().map(Box::new)?;
-        match consequent.as_ref() {
-            Statement::FunctionDeclaration(_) => {
-                panic!("Function declarations are not allowed to follow an if-statement in strict mode")
-            }
-            _ => {}
+        if let Statement::FunctionDeclaration(_) = consequent.as_ref() {
+            panic!("Function declarations are not allowed to follow an if-statement in strict mode")
         };
 
         let mut alternate: Option<Box<Statement>> = None;
         if self.lexer.token == Token::Else {
             self.lexer.next_token();
             let tmp_alternate = self.parse_statement()?;
-            match &tmp_alternate {
-                Statement::FunctionDeclaration(_) => {
-                    panic!("Function declarations are not allowed to follow an if-statement in strict mode")
-                }
-                _ => {}
+            if let Statement::FunctionDeclaration(_) = &tmp_alternate {
+                panic!("Function declarations are not allowed to follow an if-statement in strict mode")
             };
             alternate = Some(Box::new(tmp_alternate));
         }
@@ -2498,8 +2957,10 @@ impl<'a, L: Logger> Parser<'a, L> {
     fn parse_for_statement(&mut self) -> ParseResult<Statement> {
         self.lexer.next_token();
 
-        if self.lexer.token == Token::Await {
-            panic!("\"for await\" syntax is not yet supported");
+        // for await (a of b) {}
+        let is_await = self.lexer.token == Token::Await;
+        if is_await {
+            self.lexer.next_token();
         }
 
         self.lexer.eat_token(Token::OpenParen);
@@ -2518,19 +2979,19 @@ impl<'a, L: Logger> Parser<'a, L> {
                 None
             }
 
-            _ => {
-                let expression = self
-                    .parse_expression(&Precedence::Lowest)
-                    .map(|expression| Statement::Expression(ExpressionStatement { expression }))
-                    .map(Box::new)
-                    .map(Some)?;
-                self.consume_semicolon();
-                expression
-            }
+            _ => self
+                .parse_expression(&Precedence::Lowest)
+                .map(|expression| Statement::Expression(ExpressionStatement { expression }))
+                .map(Box::new)
+                .map(Some)?,
         };
 
         self.allow_in = true;
 
+        if is_await {
+            self.lexer.expect_token(Token::Of);
+        }
+
         if self.lexer.token == Token::Of {
             // TODO: We should check for declarations here and forbid them if they exist.
             self.lexer.next_token();
@@ -2542,6 +3003,7 @@ impl<'a, L: Logger> Parser<'a, L> {
                     body: Box::new(body),
                     left,
                     right,
+                    is_await,
                 }));
             } else {
                 // This essentially means we've somehow reached something like
@@ -2569,6 +3031,11 @@ impl<'a, L: Logger> Parser<'a, L> {
             }
         }
 
+        // An empty initializer has already consumed its semicolon.
+        if init.is_some() {
+            self.lexer.eat_token(Token::Semicolon);
+        }
+
         let test = match self.lexer.token {
             Token::Semicolon => {
                 self.lexer.next_token();
@@ -2634,8 +3101,6 @@ impl<'a, L: Logger> Parser<'a, L> {
             self.lexer.next_token();
         }
 
-        self.consume_semicolon();
-
         Ok(VariableDeclaration { declarations, kind })
     }
 }
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use js_ast::visit::{walk_binding, walk_expression, walk_statement, VisitorMut};
+use js_ast::*;
+
+/// Marks every call to a function declared with `/* #__NO_SIDE_EFFECTS__ */` as pure.
+///
+/// Only top level functions are considered, and only if their name is not
+/// declared or assigned anywhere else in the file. That way a call can never
+/// refer to another binding with the same name.
+pub(crate) fn mark_no_side_effects_calls(ast: &mut AST) {
+    let mut names: Vec<String> = Vec::new();
+    for statement in &ast.statements {
+        let function = match statement {
+            Statement::FunctionDeclaration(f) => f,
+            Statement::ExportNamedDeclaration(ExportNamedDeclaration {
+                declaration: ExportNamedDeclarationKind::FunctionDeclaration(f),
+            }) => f,
+            Statement::ExportDefaultDeclaration(ExportDefaultDeclaration {
+                declaration: ExportDefaultDeclarationKind::FunctionDeclaration(f),
+            }) => f,
+            _ => continue,
+        };
+        if function.no_side_effects {
+            names.push(function.identifier.name.clone());
+        }
+    }
+
+    if names.is_empty() {
+        return;
+    }
+
+    let mut counter = DeclarationCounter {
+        counts: HashMap::new(),
+    };
+    counter.visit_program(ast);
+    names.retain(|name| counter.counts.get(name) == Some(&1));
+
+    if !names.is_empty() {
+        PureCallMarker { names }.visit_program(ast);
+    }
+}
+
+/// Counts how many times each name is declared or assigned to.
+struct DeclarationCounter {
+    counts: HashMap<String, usize>,
+}
+
+impl DeclarationCounter {
+    fn add(&mut self, identifier: &Identifier) {
+        *self.counts.entry(identifier.name.clone()).or_insert(0) += 1;
+    }
+}
+
+impl VisitorMut for DeclarationCounter {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::FunctionDeclaration(f) => self.add(&f.identifier),
+            Statement::ClassDeclaration(c) => self.add(&c.identifier),
+            Statement::ImportDeclaration(i) => {
+                for identifier in i.default.iter().chain(i.namespace.iter()) {
+                    self.add(identifier);
+                }
+                for specifier in &i.specifiers {
+                    self.add(&specifier.local);
+                    self.add(&specifier.imported);
+                }
+            }
+            Statement::ExportNamedDeclaration(e) => match &e.declaration {
+                ExportNamedDeclarationKind::FunctionDeclaration(f) => self.add(&f.identifier),
+                ExportNamedDeclarationKind::ClassDeclaration(c) => self.add(&c.identifier),
+                ExportNamedDeclarationKind::VariableDeclaration(_) => {}
+            },
+            Statement::ExportDefaultDeclaration(e) => match &e.declaration {
+                ExportDefaultDeclarationKind::FunctionDeclaration(f) => self.add(&f.identifier),
+                ExportDefaultDeclarationKind::ClassDeclaration(c) => self.add(&c.identifier),
+                _ => {}
+            },
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Function(FunctionExpression {
+                identifier: Some(identifier),
+                ..
+            })
+            | Expression::Class(ClassExpression {
+                identifier: Some(identifier),
+                ..
+            }) => self.add(identifier),
+            Expression::Assignment(AssignmentExpression {
+                left: AssignmentExpressionLeft::Expression(left),
+                ..
+            })
+            | Expression::Update(UpdateExpression { argument: left, .. }) => {
+                if let Expression::Identifier(identifier) = left.as_ref() {
+                    self.add(identifier);
+                }
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        if let Binding::Identifier(identifier) = binding {
+            self.add(identifier);
+        }
+        walk_binding(self, binding);
+    }
+}
+
+struct PureCallMarker {
+    names: Vec<String>,
+}
+
+impl VisitorMut for PureCallMarker {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+        if let Expression::Call(c) = expression {
+            if let Expression::Identifier(identifier) = c.callee.as_ref() {
+                if self.names.contains(&identifier.name) {
+                    c.pure = true;
+                }
+            }
+        }
+    }
+}
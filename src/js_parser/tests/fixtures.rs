@@ -1,4 +1,5 @@
 use js_parser::parse;
+use js_printer::Printer;
 use logger::LoggerImpl;
 use source::Source;
 
@@ -28,6 +29,36 @@ macro_rules! test_fixture {
     };
 }
 
+/// Parses the printed output of the file again, the tree should not change.
+macro_rules! test_round_trip {
+    ($name:ident, $file:expr) => {
+        #[test]
+        fn $name() {
+            let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), $file);
+            let content = fs::read_to_string(&path).expect("Failed to read file");
+            let logger = LoggerImpl::new();
+            let source = Source {
+                absolute_path: &path,
+                pretty_path: &path,
+                content: &content,
+            };
+            let ast = parse(&source, &logger);
+
+            let printed = Printer::new().print_program(&ast);
+            let source = Source {
+                absolute_path: &path,
+                pretty_path: &path,
+                content: &printed,
+            };
+            assert!(parse(&source, &logger) == ast, "The printed tree differs");
+        }
+    };
+}
+
 test_fixture!(angular_1_2_5, "angular-1.2.5.js");
 
 test_fixture!(jquery_1_9_1, "jquery-1.9.1.js");
+
+test_round_trip!(angular_1_2_5_round_trip, "angular-1.2.5.js");
+
+test_round_trip!(jquery_1_9_1_round_trip, "jquery-1.9.1.js");
@@ -1,25 +1,371 @@
+use std::collections::HashMap;
+
+use js_ast::scope::replace_globals;
+use js_ast::*;
 use js_parser::parse;
-use js_printer::Printer;
-use logger::LoggerImpl;
+use js_printer::{LegalComments, Printer, PrinterOptions};
+use logger::{Logger, LoggerImpl, Message, Range};
 use source::Source;
 
-fn expect_printed(content: &str, expected: &str) {
+fn parse_test_source(content: &str) -> AST {
     let source = Source {
         absolute_path: "/test.js",
         pretty_path: "./test.js",
-        content: content.into(),
+        content,
     };
-
     let logger = LoggerImpl::new();
-    let ast = parse(&source, &logger);
+    parse(&source, &logger)
+}
+
+fn expect_printed(content: &str, expected: &str) {
+    let ast = parse_test_source(content);
     let output = Printer::new().print_program(&ast);
     assert_eq!(output, expected);
 }
 
+fn expect_printed_minified(content: &str, expected: &str) {
+    let ast = parse_test_source(content);
+    let mut printer = Printer::with_options(PrinterOptions {
+        minify_syntax: true,
+        ..PrinterOptions::default()
+    });
+    assert_eq!(printer.print_program(&ast), expected);
+}
+
+/// Prints the program with ASCII-only output and returns the errors of the printer.
+fn print_ascii_only(content: &str, es5: bool) -> (String, Vec<String>) {
+    let ast = parse_test_source(content);
+    let mut printer = Printer::with_options(PrinterOptions {
+        ascii_only: true,
+        es5,
+        ..PrinterOptions::default()
+    });
+    let text = printer.print_program(&ast);
+    (text, printer.errors().to_vec())
+}
+
+fn expect_printed_ascii_only(content: &str, expected: &str) {
+    assert_eq!(
+        print_ascii_only(content, false),
+        (expected.to_string(), Vec::new())
+    );
+}
+
+fn expect_printed_ascii_only_es5(content: &str, expected: &str) {
+    assert_eq!(
+        print_ascii_only(content, true),
+        (expected.to_string(), Vec::new())
+    );
+}
+
+fn expect_printed_statements(statements: Vec<Statement>, expected: &str) {
+    let output = Printer::new().print_program(&AST { statements });
+    assert_eq!(output, expected);
+}
+
+fn parse_define(value: &str) -> Expression {
+    match parse_test_source(&format!("({})", value))
+        .statements
+        .remove(0)
+    {
+        Statement::Expression(e) => match e.expression {
+            Expression::Sequence(mut s) if s.expressions.len() == 1 => s.expressions.remove(0),
+            expression => expression,
+        },
+        statement => panic!("Expected an expression, found {:?}", statement),
+    }
+}
+
+fn expect_printed_with_defines(content: &str, defines: Vec<(&str, &str)>, expected: &str) {
+    let mut ast = parse_test_source(content);
+    let defines: HashMap<String, Expression> = defines
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), parse_define(value)))
+        .collect();
+    replace_globals(&mut ast, &defines);
+    let output = Printer::new().print_program(&ast);
+    assert_eq!(output, expected);
+}
+
+fn expect_printed_legal_comments(
+    content: &str,
+    legal_comments: LegalComments,
+    expected: &str,
+    expected_extracted: Vec<&str>,
+) {
+    let ast = parse_test_source(content);
+    let mut printer = Printer::with_options(PrinterOptions {
+        legal_comments,
+        ..PrinterOptions::default()
+    });
+    let output = printer.print_program(&ast);
+    assert_eq!(output, expected);
+    assert_eq!(printer.legal_comments(), expected_extracted.as_slice());
+}
+
+/// A logger that panics with the error, so that the syntax errors
+/// can be asserted on instead of exiting the process.
+struct PanicLogger;
+
+impl Logger for PanicLogger {
+    fn add_message(&self, _message: Message) {}
+
+    fn has_errors(&self) -> bool {
+        false
+    }
+
+    fn has_warnings(&self) -> bool {
+        false
+    }
+
+    fn flush(&self) {}
+
+    fn add_error(&self, _source: &str, range: Range, text: String) {
+        panic!("{}..{}: {}", range.start, range.end, text);
+    }
+}
+
+fn expect_syntax_error(content: &str, expected: &str) {
+    let result = std::panic::catch_unwind(|| {
+        let source = Source {
+            absolute_path: "/test.js",
+            pretty_path: "./test.js",
+            content,
+        };
+        parse(&source, &PanicLogger);
+    });
+    let error = result.expect_err("Expected the source to be reported as an error");
+    let error = error
+        .downcast_ref::<String>()
+        .expect("Expected the error to be a message");
+    assert_eq!(error, expected);
+}
+
 #[test]
 fn test_string_literal() {
     expect_printed("\"hello_world\"", "\"hello_world\";\n");
     expect_printed("'hello_world'", "\"hello_world\";\n");
+    // The quote needing the fewest escapes is used.
+    expect_printed("'a\"b'", "'a\"b';\n");
+    expect_printed("\"a'b\"", "\"a'b\";\n");
+    expect_printed("'a\\'b'", "\"a\\'b\";\n");
+    expect_printed("'a\"b\"c\\'d'", "'a\"b\"c\\'d';\n");
+    expect_printed("\"a'b\\\"c'\"", "\"a'b\\\"c'\";\n");
+    // Escapes are kept as they are written, lone surrogates included.
+    expect_printed("'\\uD800'", "\"\\uD800\";\n");
+    expect_printed(
+        "'\\uD83D\\uDE00 \\\\ \\n'",
+        "\"\\uD83D\\uDE00 \\\\ \\n\";\n",
+    );
+    expect_printed("'a\\\nb'", "\"a\\\nb\";\n");
+    expect_printed("'a\u{2028}b'", "\"a\\u2028b\";\n");
+}
+
+#[test]
+fn test_numeric_literal() {
+    // Numbers are printed like Number.prototype.toString prints them.
+    expect_printed("1", "1;\n");
+    expect_printed("1.50", "1.5;\n");
+    expect_printed("0.5", "0.5;\n");
+    expect_printed(".000001", "0.000001;\n");
+    expect_printed(".0000001", "1e-7;\n");
+    expect_printed(".00000015", "1.5e-7;\n");
+    expect_printed("100000000000000000000", "100000000000000000000;\n");
+    expect_printed("1000000000000000000000", "1e+21;\n");
+    expect_printed("1500000000000000000000000", "1.5e+24;\n");
+    expect_printed("0xFF", "255;\n");
+    expect_printed("0.1 + 0.2", "0.1 + 0.2;\n");
+    expect_printed("({ 1000000000000000000000: a })", "({ 1e+21: a });\n");
+    expect_printed_statements(
+        vec![
+            expression_statement(number(5e-324)),
+            expression_statement(number(f64::MAX)),
+        ],
+        "5e-324;\n1.7976931348623157e+308;\n",
+    );
+
+    // The shortest text is used when minifying.
+    expect_printed_minified("1000", "1e3;\n");
+    expect_printed_minified("100", "100;\n");
+    expect_printed_minified("123000", "123e3;\n");
+    expect_printed_minified("0.5", ".5;\n");
+    expect_printed_minified("0.001", ".001;\n");
+    expect_printed_minified("0.0001", "1e-4;\n");
+    expect_printed_minified(".00000015", "15e-8;\n");
+    expect_printed_minified("1000000000000000000000", "1e21;\n");
+    expect_printed_minified("16777215", "16777215;\n");
+    expect_printed_minified("1099511627775", "0xFFFFFFFFFF;\n");
+    expect_printed_minified("1000..toString()", "1e3.toString();\n");
+    expect_printed_minified("({ 1000: a })", "({ 1e3: a });\n");
+}
+
+#[test]
+fn test_strict_mode_numeric_literals() {
+    // Modules are strict mode code, including their first token.
+    expect_syntax_error(
+        "010",
+        "0..3: Legacy octal literals are not allowed in strict mode",
+    );
+    expect_syntax_error(
+        "a = 08",
+        "4..6: Decimals with leading zeros are not allowed in strict mode",
+    );
+    expect_syntax_error(
+        "07.5",
+        "0..2: Legacy octal literals are not allowed in strict mode",
+    );
+    expect_syntax_error(
+        "class A { [010]() {} }",
+        "11..14: Legacy octal literals are not allowed in strict mode",
+    );
+    expect_syntax_error(
+        "class A extends B { a() { return 08 } }",
+        "33..35: Decimals with leading zeros are not allowed in strict mode",
+    );
+    expect_syntax_error(
+        "function a() { \"use strict\"; return 010 }",
+        "36..39: Legacy octal literals are not allowed in strict mode",
+    );
+    expect_syntax_error(
+        "() => { \"use strict\"\n010 }",
+        "21..24: Legacy octal literals are not allowed in strict mode",
+    );
+}
+
+#[test]
+fn test_big_int_literal() {
+    expect_printed("1n", "1n;\n");
+    expect_printed(
+        "123456789012345678901234567890n",
+        "123456789012345678901234567890n;\n",
+    );
+    expect_printed("0xFFFFFFFFFFFFFFFFFFn", "0xFFFFFFFFFFFFFFFFFFn;\n");
+    expect_printed("0b1010n", "0b1010n;\n");
+    expect_printed("0o777n", "0o777n;\n");
+    expect_printed("1_000_000n", "1000000n;\n");
+    expect_printed("-1n", "-1n;\n");
+    expect_printed("1n.toString()", "1n.toString();\n");
+}
+
+#[test]
+fn test_identifier_escapes() {
+    expect_printed("var \\u0061 = 1;", "var a = 1;\n");
+    expect_printed("var \\u{62}\\u0063;", "var bc;\n");
+    expect_printed("var ñ = \\u00f1;", "var ñ = ñ;\n");
+    // Escaped keywords are allowed as property names.
+    expect_printed("a.\\u0069f;", "a.if;\n");
+    expect_printed("({ \\u0069f: 1 });", "({ if: 1 });\n");
+    // Escaped contextual keywords are identifiers.
+    expect_printed("var \\u0061s = 1;", "var as = 1;\n");
+    expect_printed(
+        "var \\u0061sync = 1; \\u0061sync;",
+        "var async = 1;\nasync;\n",
+    );
+    expect_printed("({ \\u0067et: 1 });", "({ get: 1 });\n");
+
+    // Non-ASCII identifiers are escaped again when the output is ASCII only.
+    expect_printed_ascii_only("var ñ = 1;", "var \\u00F1 = 1;\n");
+    expect_printed_ascii_only("a.bñ;", "a.b\\u00F1;\n");
+    expect_printed_ascii_only("var \\u{20BB7};", "var \\u{20BB7};\n");
+    expect_printed_ascii_only("var a\\u0062;", "var ab;\n");
+}
+
+#[test]
+fn test_escaped_contextual_keywords() {
+    // Contextual keywords can not contain escapes in the positions where they are keywords.
+    expect_syntax_error(
+        "\\u0061sync function a() {}",
+        "11..19: Unexpected token \"function\"",
+    );
+    expect_syntax_error("\\u0061sync () => 1", "14..16: Unexpected token \"=>\"");
+    expect_syntax_error(
+        "({ \\u0067et a() {} })",
+        "12..13: Expected \",\" but found \"Identifier\"",
+    );
+    expect_syntax_error(
+        "class A { \\u0073tatic a() {} }",
+        "22..23: Expected \"(\" but found \"Identifier\"",
+    );
+    expect_syntax_error(
+        "import.\\u006Deta",
+        "7..16: Unexpected token \"Identifier\"",
+    );
+    expect_syntax_error(
+        "function* a() { \\u0079ield 1 }",
+        "27..28: Unexpected token \"NumericLiteral\"",
+    );
+    // Let and await are reserved words in modules.
+    expect_syntax_error(
+        "let \\u006Cet = 1",
+        "4..12: Keywords cannot contain escape characters",
+    );
+    expect_syntax_error(
+        "var \\u{61}wait = 1",
+        "4..14: Keywords cannot contain escape characters",
+    );
+}
+
+#[test]
+fn test_automatic_semicolon_insertion() {
+    expect_printed("a\nb", "a;\nb;\n");
+    expect_printed("{ a }", "{ a;\n }");
+    expect_printed("do {} while (a) b", "do {} while (a);\nb;\n");
+    expect_syntax_error("a b", "2..3: Unexpected token \"Identifier\"");
+    expect_syntax_error("({ a b })", "5..6: Expected \",\" but found \"Identifier\"");
+}
+
+#[test]
+fn test_reserved_word_identifiers() {
+    // Reserved words can only be printed as property names.
+    let mut printer = Printer::new();
+    let output = printer.print_program(&AST {
+        statements: vec![
+            expression_statement(identifier("let")),
+            expression_statement(member(identifier("a"), identifier("if"), false)),
+        ],
+    });
+    assert_eq!(output, "let;\na.if;\n");
+    assert_eq!(
+        printer.errors(),
+        ["Cannot use \"let\" as an identifier, it is a reserved word"]
+    );
+}
+
+#[test]
+fn test_ascii_only() {
+    expect_printed_ascii_only("'ñ';", "\"\\u00F1\";\n");
+    expect_printed_ascii_only("'a€b';", "\"a\\u20ACb\";\n");
+    expect_printed_ascii_only("'𠮷';", "\"\\u{20BB7}\";\n");
+    expect_printed_ascii_only_es5("'𠮷';", "\"\\uD842\\uDFB7\";\n");
+    // Escapes that are already in the value are kept.
+    expect_printed_ascii_only("'\\u00f1ñ';", "\"\\u00f1\\u00F1\";\n");
+    expect_printed_ascii_only("'\\\\ñ';", "\"\\\\\\u00F1\";\n");
+    // An escaped character is the character itself.
+    expect_printed_ascii_only("'\\ñ';", "\"\\u00F1\";\n");
+    // Line continuations are dropped, line terminators are escaped.
+    expect_printed_ascii_only("'a\\\u{2028}b';", "\"ab\";\n");
+    expect_printed_ascii_only("'a\u{2028}b';", "\"a\\u2028b\";\n");
+    expect_printed_ascii_only("({ 'ñ': 1 });", "({ \"\\u00F1\": 1 });\n");
+
+    // Template literals always allow code point escapes.
+    expect_printed_ascii_only("`ñ${a}𠮷`;", "`\\u00F1${a}\\u{20BB7}`;\n");
+    expect_printed_ascii_only_es5("`ñ${a}𠮷`;", "`\\u00F1${a}\\u{20BB7}`;\n");
+
+    // Regular expressions only read code point escapes in the unicode modes.
+    expect_printed_ascii_only("/ñ/;", "/\\u00F1/;\n");
+    expect_printed_ascii_only("/[𠮷]/;", "/[\\uD842\\uDFB7]/;\n");
+    expect_printed_ascii_only("/[𠮷]/u;", "/[\\u{20BB7}]/u;\n");
+    expect_printed_ascii_only("/\\ñ/;", "/\\u00F1/;\n");
+
+    // Identifiers can not contain surrogate pairs.
+    expect_printed_ascii_only_es5("var ñ;", "var \\u00F1;\n");
+    assert_eq!(
+        print_ascii_only("var 𠮷 = 𠮷;", true),
+        (
+            "var \\u{20BB7} = \\u{20BB7};\n".to_string(),
+            vec!["Cannot use \"𠮷\" as an identifier in ES5 with ASCII-only output, it contains characters that can not be escaped".to_string()]
+        )
+    );
 }
 
 #[test]
@@ -70,6 +416,9 @@ fn test_binding() {
     expect_printed("let [...[...[a]]] = b", "let [...[...[a]]] = b;\n");
 
     expect_printed("let { a, b, c } = b", "let { a,\nb,\nc } = b;\n");
+    expect_printed("let [, a] = b", "let [, a] = b;\n");
+    expect_printed("let [a, , b] = c", "let [a, , b] = c;\n");
+    expect_printed("let [a, ,] = b", "let [a, ,] = b;\n");
 }
 
 #[test]
@@ -81,6 +430,12 @@ fn test_prefix_expressions() {
     expect_printed("typeof a", "typeof a;\n");
     expect_printed("void a", "void a;\n");
     expect_printed("delete a", "delete a;\n");
+    expect_printed("- -a", "- -a;\n");
+    expect_printed("-(-a)", "-(-a);\n");
+    expect_printed("+ +a", "+ +a;\n");
+    expect_printed("- --a", "- --a;\n");
+    expect_printed("+ ++a", "+ ++a;\n");
+    expect_printed("- +a", "-+a;\n");
 }
 
 #[test]
@@ -112,6 +467,9 @@ fn test_binary_expressions() {
     expect_printed("a << b", "a << b;\n");
     expect_printed("a >> b", "a >> b;\n");
     expect_printed("a >>> b", "a >>> b;\n");
+    expect_printed("(-a) ** b", "(-a) ** b;\n");
+    expect_printed("(typeof a) ** b", "(typeof a) ** b;\n");
+    expect_printed("a ** -b", "a ** -b;\n");
 }
 
 #[test]
@@ -161,6 +519,20 @@ fn test_import_statement() {
     );
 }
 
+#[test]
+fn test_import_expression() {
+    expect_printed("import(\"a\")", "import(\"a\");\n");
+    expect_printed("import(\"a\").then(b)", "import(\"a\").then(b);\n");
+    expect_printed("const a = import(b + c)", "const a = import(b + c);\n");
+    expect_printed(
+        "import(/* webpackChunkName: 'b' */ \"a\")",
+        "import(/* webpackChunkName: \"b\" */ \"a\");\n",
+    );
+    expect_printed("import.meta", "import.meta;\n");
+    expect_printed("import.meta.env.MODE", "import.meta.env.MODE;\n");
+    expect_printed("const a = import.meta.url", "const a = import.meta.url;\n");
+}
+
 #[test]
 fn test_function_declaration() {
     expect_printed("function a() {}", "function a() {}");
@@ -219,6 +591,10 @@ fn test_if_statement() {
         "if (a) a(); else if (b) b(); else c();",
         "if (a) a();\n else if (b) b();\n else c();\n",
     );
+    expect_printed(
+        "if (a) { if (b) c(); } else d();",
+        "if (a) { if (b) c();\n } else d();\n",
+    );
 }
 
 #[test]
@@ -272,6 +648,22 @@ fn test_for_statement() {
         "for (i = 0, l = 10; i < l, x < 10; i++, x--) {}",
     );
     expect_printed("for (var c;;) {}", "for (var c; ; ) {}");
+    expect_printed(
+        "for (var a = (b in c); a; a++) {}",
+        "for (var a = (b in c); a; a++) {}",
+    );
+    expect_printed(
+        "for (a = (b in c) ? d : e;;) {}",
+        "for (a = (b in c) ? d : e; ; ) {}",
+    );
+    expect_printed(
+        "for (var a = [b in c], d = f(b in c);;) {}",
+        "for (var a = [b in c], d = f(b in c); ; ) {}",
+    );
+    expect_printed(
+        "for (var a = function() { return b in c; };;) {}",
+        "for (var a = function() { return b in c;\n }; ; ) {}",
+    );
 }
 
 #[test]
@@ -295,6 +687,7 @@ fn test_for_of_statement() {
         "for (let a of items) { return 3 + 3; }",
         "for (let a of items) { return 3 + 3;\n }",
     );
+    expect_printed("for (a of (b, c)) {}", "for (a of (b, c)) {}");
 }
 
 #[test]
@@ -334,8 +727,8 @@ fn test_assignment_expression() {
     expect_printed("a **= 3 * 3", "a **= 3 * 3;\n");
     expect_printed("[a] = b", "[a] = b;\n");
     expect_printed("[...a] = b", "[...a] = b;\n");
-    expect_printed("({ a } = b)", "{ a } = b;\n");
-    expect_printed("({ ...a } = b)", "{ ...a } = b;\n");
+    expect_printed("({ a } = b)", "({ a } = b);\n");
+    expect_printed("({ ...a } = b)", "({ ...a } = b);\n");
     expect_printed("a = 1, b = 2, c = 3", "a = 1, b = 2, c = 3;\n");
 }
 
@@ -344,6 +737,9 @@ fn test_logical_expression() {
     expect_printed("3 + 3 || 1 * 2", "3 + 3 || 1 * 2;\n");
     expect_printed("3 + 3 && 1 * 2", "3 + 3 && 1 * 2;\n");
     expect_printed("a || b && c", "a || b && c;\n");
+    expect_printed("a ?? b", "a ?? b;\n");
+    expect_printed("(a || b) ?? c", "(a || b) ?? c;\n");
+    expect_printed("a ?? (b && c)", "a ?? (b && c);\n");
 }
 
 #[test]
@@ -383,6 +779,7 @@ fn test_do_while_statement() {
         "do { return 3; } while (1 < 10)",
         "do { return 3;\n } while (1 < 10);\n",
     );
+    expect_printed("do a(); while (b); c();", "do a();\n while (b);\nc();\n");
 }
 
 #[test]
@@ -396,6 +793,11 @@ fn test_switch_statement() {
         "switch (a) { case \"1\": {} default: {} }",
     );
     expect_printed("switch (a) { default: {} }", "switch (a) { default: {} }");
+    expect_printed(
+        "switch (a) { default: b(); case 1: c(); }",
+        "switch (a) { default: b();\n case 1: c();\n }",
+    );
+    expect_printed("switch (a) { case b || c: }", "switch (a) { case b || c: }");
 }
 
 #[test]
@@ -423,6 +825,7 @@ fn test_throw_statement() {
     expect_printed("throw 3 + 3", "throw 3 + 3;\n");
     expect_printed("throw err", "throw err;\n");
     expect_printed("throw new Error()", "throw new Error();\n");
+    expect_printed("throw a; b();", "throw a;\nb();\n");
 }
 
 #[test]
@@ -505,6 +908,10 @@ fn test_new_expression() {
     expect_printed("new a.b.c(e)", "new a.b.c(e);\n");
     expect_printed("new a.b.c(...e, a)", "new a.b.c(...e, a);\n");
     expect_printed("new a", "new a();\n");
+    expect_printed("new (a())()", "new (a())();\n");
+    expect_printed("new (a().b)()", "new (a().b)();\n");
+    expect_printed("new (a.b().c)(d)", "new (a.b().c)(d);\n");
+    expect_printed("new a.b.c()", "new a.b.c();\n");
 }
 
 #[test]
@@ -514,6 +921,9 @@ fn test_member_expression() {
     expect_printed("a['a' + 'b'].d.[c]", "a[\"a\" + \"b\"].d.[c];\n");
     expect_printed("a.b.c.d()", "a.b.c.d();\n");
     expect_printed("a.b.c.d(e)", "a.b.c.d(e);\n");
+    expect_printed("1..a", "1..a;\n");
+    expect_printed("(1).a", "(1).a;\n");
+    expect_printed("1.5.a", "1.5.a;\n");
 }
 
 #[test]
@@ -564,6 +974,12 @@ fn test_export_default_declaration() {
     expect_printed("export default class {}", "export default class {}");
     expect_printed("export default 3 + 3", "export default 3 + 3;\n");
     expect_printed("export default { a: c }", "export default { a: c };\n");
+    expect_printed("export default a; b", "export default a;\nb;\n");
+    expect_printed(
+        "export default (function() {})",
+        "export default (function() {});\n",
+    );
+    expect_printed("export default (class {})", "export default (class {});\n");
 }
 
 #[test]
@@ -592,6 +1008,11 @@ fn test_class_declaration() {
     expect_printed("class A { set [b]() {} }", "class A { set [b]() {} }");
     expect_printed("class A { b() {}; c() {}; }", "class A { b() {}\nc() {} }");
     expect_printed("class A {;}", "class A {}");
+    expect_printed(
+        "class A { static a() {} static get b() {} static set [c](d) {} }",
+        "class A { static a() {}\nstatic get b() {}\nstatic set [c](d) {} }",
+    );
+    expect_printed("class A extends (B || C) {}", "class A extends (B || C) {}");
 }
 
 #[test]
@@ -625,6 +1046,7 @@ fn test_class_expression() {
         "let a = class A { set [b]() {} }",
         "let a = class A { set [b]() {} };\n",
     );
+    expect_printed("(class {})", "(class {});\n");
 }
 
 #[test]
@@ -641,6 +1063,110 @@ fn test_array_function_expression() {
     expect_printed("a = () => {}", "a = () => {};\n");
     expect_printed("let a = () => {}", "let a = () => {};\n");
     expect_printed("let a = b => {}", "let a = (b) => {};\n");
+    expect_printed("() => ({})", "() => ({});\n");
+    expect_printed("() => ({}).a", "() => ({}).a;\n");
+    expect_printed("() => ({ a } = b)", "() => ({ a } = b);\n");
+    expect_printed("(() => a) || b", "(() => a) || b;\n");
+}
+
+#[test]
+fn test_async_functions() {
+    expect_printed("async function a() {}", "async function a() {}");
+    expect_printed("async function* a() {}", "async function* a() {}");
+    expect_printed(
+        "let a = async function() {}",
+        "let a = async function() {};\n",
+    );
+    expect_printed("(async function() {})()", "(async function() {})();\n");
+    expect_printed("let a = async () => {}", "let a = async () => {};\n");
+    expect_printed("let a = async b => b", "let a = async (b) => b;\n");
+    expect_printed("let a = async (b, c) => b", "let a = async (b, c) => b;\n");
+    expect_printed("async(a, b)", "async(a, b);\n");
+    expect_printed("async()", "async();\n");
+    expect_printed("let a = async", "let a = async;\n");
+    expect_printed("let a = async => async", "let a = (async) => async;\n");
+    expect_printed(
+        "export async function a() {}",
+        "export async function a() {}",
+    );
+    expect_printed(
+        "export default async function() {}",
+        "export default async function() {}",
+    );
+    expect_printed(
+        "export default async function a() {}",
+        "export default async function a() {}",
+    );
+}
+
+#[test]
+fn test_async_methods() {
+    expect_printed("({ async a() {} })", "({ async a() {} });\n");
+    expect_printed(
+        "({ async [a]() {}, async *b() {} })",
+        "({ async [a]() {}, async *b() {} });\n",
+    );
+    expect_printed(
+        "({ async, async: 1, async() {} })",
+        "({ async, async: 1, async() {} });\n",
+    );
+    expect_printed("class A { async a() {} }", "class A { async a() {} }");
+    expect_printed(
+        "class A { static async [a]() {} async *b() {} }",
+        "class A { static async [a]() {}\nasync *b() {} }",
+    );
+    expect_printed(
+        "class A { async() {} static async() {} }",
+        "class A { async() {}\nstatic async() {} }",
+    );
+}
+
+#[test]
+fn test_await_expression() {
+    expect_printed(
+        "async function a() { await b; }",
+        "async function a() { await b;\n }",
+    );
+    expect_printed(
+        "async function a() { return (await b) + await c(); }",
+        "async function a() { return (await b) + await c();\n }",
+    );
+    expect_printed(
+        "async function a() { (await b).c; }",
+        "async function a() { (await b).c;\n }",
+    );
+    expect_printed(
+        "async function a() { for await (const b of c) {} }",
+        "async function a() { for await (const b of c) {} }",
+    );
+}
+
+#[test]
+fn test_yield_expression() {
+    expect_printed("function* a() { yield; }", "function* a() { yield;\n }");
+    expect_printed("function* a() { yield b; }", "function* a() { yield b;\n }");
+    expect_printed(
+        "function* a() { yield* b; }",
+        "function* a() { yield* b;\n }",
+    );
+    expect_printed(
+        "function* a() { b = yield c; }",
+        "function* a() { b = yield c;\n }",
+    );
+    expect_printed(
+        "function* a() { b(yield, yield c); }",
+        "function* a() { b(yield, yield c);\n }",
+    );
+    expect_printed(
+        "function* a() { (yield b) + c; }",
+        "function* a() { (yield b) + c;\n }",
+    );
+    // yield is an identifier outside of generators.
+    expect_printed("function a() { yield; }", "function a() { yield;\n }");
+    expect_printed(
+        "function* a() { () => yield; }",
+        "function* a() { () => yield;\n }",
+    );
 }
 
 #[test]
@@ -649,3 +1175,275 @@ fn test_template_literal() {
     expect_printed("`hello ${a} from ${b}`", "`hello ${a} from ${b}`;\n");
     expect_printed("`hello ${3 * 3}`", "`hello ${3 * 3}`;\n");
 }
+
+#[test]
+fn test_legal_comments() {
+    expect_printed("/* a */ b", "b;\n");
+    expect_printed("// a\nb", "b;\n");
+    expect_printed("/*! a */ b", "/*! a */\nb;\n");
+    expect_printed("//! a\nb", "//! a\nb;\n");
+    expect_printed("/** @license MIT */ b", "/** @license MIT */\nb;\n");
+    expect_printed("// @preserve\nb", "// @preserve\nb;\n");
+    expect_printed("a; /*! b */", "a;\n/*! b */\n");
+    expect_printed(
+        "function a() { /*! b */ return; }",
+        "function a() { /*! b */\nreturn;\n }",
+    );
+    // Comments inside of statements are moved before them.
+    expect_printed("a = /*! b */ c;", "/*! b */\na = c;\n");
+    expect_printed(
+        "a(/*! b */ c, /*! d */ e); f;",
+        "/*! b */\n/*! d */\na(c, e);\nf;\n",
+    );
+    expect_printed(
+        "a = b /*! c */; /*! d */ e",
+        "/*! c */\na = b;\n/*! d */\ne;\n",
+    );
+    expect_printed("a = b /*! c */\nd", "a = b;\n/*! c */\nd;\n");
+    expect_printed(
+        "if (a) b(/*! c */); { d = /*! e */ f; }",
+        "/*! c */\nif (a) b();\n{ /*! e */\nd = f;\n }",
+    );
+    expect_printed(
+        "function a() { return /*! b */ 1; }",
+        "function a() { /*! b */\nreturn 1;\n }",
+    );
+
+    expect_printed_legal_comments(
+        "/*! a */ b; /*! c */",
+        LegalComments::Inline,
+        "/*! a */\nb;\n/*! c */\n",
+        vec![],
+    );
+    expect_printed_legal_comments(
+        "/*! a */ b; /*! c */ /*! a */",
+        LegalComments::EndOfFile,
+        "b;\n/*! a */\n/*! c */\n",
+        vec!["/*! a */", "/*! c */"],
+    );
+    expect_printed_legal_comments(
+        "/*! a */ b; /*! c */",
+        LegalComments::External,
+        "b;\n",
+        vec!["/*! a */", "/*! c */"],
+    );
+    expect_printed_legal_comments("/*! a */ b;", LegalComments::None, "b;\n", vec![]);
+}
+
+#[test]
+fn test_pure_comments() {
+    expect_printed("/* @__PURE__ */ a()", "/* @__PURE__ */ a();\n");
+    expect_printed("/* #__PURE__ */ a()", "/* @__PURE__ */ a();\n");
+    expect_printed("/* @__PURE__ */ new a()", "/* @__PURE__ */ new a();\n");
+    expect_printed("/* @__PURE__ */ a.b(c)", "/* @__PURE__ */ a.b(c);\n");
+    expect_printed("/* @__PURE__ */ a()()", "/* @__PURE__ */ a()();\n");
+    expect_printed("(/* @__PURE__ */ a())()", "(/* @__PURE__ */ a())();\n");
+    expect_printed("(/* @__PURE__ */ a()).b", "(/* @__PURE__ */ a()).b;\n");
+    expect_printed("/* @__PURE__ */ a() + b", "/* @__PURE__ */ a() + b;\n");
+    expect_printed(
+        "let a = /* @__PURE__ */ b()",
+        "let a = /* @__PURE__ */ b();\n",
+    );
+    expect_printed("a(/* @__PURE__ */ b())", "a(/* @__PURE__ */ b());\n");
+    expect_printed("/* @__PURE__ */ a", "a;\n");
+    expect_printed("/* a */ b()", "b();\n");
+    expect_printed(
+        "/* @__PURE__ */ (function() {})()",
+        "/* @__PURE__ */ (function() {})();\n",
+    );
+}
+
+#[test]
+fn test_no_side_effects_comments() {
+    expect_printed(
+        "/* #__NO_SIDE_EFFECTS__ */ function a() {} a()",
+        "/* @__NO_SIDE_EFFECTS__ */ function a() {}/* @__PURE__ */ a();\n",
+    );
+    expect_printed(
+        "/* #__NO_SIDE_EFFECTS__ */ export function a() {} a()",
+        "export /* @__NO_SIDE_EFFECTS__ */ function a() {}/* @__PURE__ */ a();\n",
+    );
+    expect_printed(
+        "export /* #__NO_SIDE_EFFECTS__ */ function a() {} a()",
+        "export /* @__NO_SIDE_EFFECTS__ */ function a() {}/* @__PURE__ */ a();\n",
+    );
+    expect_printed(
+        "a(); /* #__NO_SIDE_EFFECTS__ */ function a() {}",
+        "/* @__PURE__ */ a();\n/* @__NO_SIDE_EFFECTS__ */ function a() {}",
+    );
+    expect_printed(
+        "/* #__NO_SIDE_EFFECTS__ */ function a() {} function b(a) { a() }",
+        "/* @__NO_SIDE_EFFECTS__ */ function a() {}function b(a) { a();\n }",
+    );
+    expect_printed("function a() {} a()", "function a() {}a();\n");
+}
+
+#[test]
+fn test_define() {
+    let defines = || {
+        vec![
+            ("__DEV__", "false"),
+            ("process.env.NODE_ENV", "\"production\""),
+            ("import.meta.env.MODE", "\"production\""),
+            ("CONFIG", "{ \"a\": [1, null] }"),
+            ("global", "window"),
+        ]
+    };
+    expect_printed_with_defines("if (__DEV__) a();", defines(), "if (false) a();\n");
+    expect_printed_with_defines(
+        "a(process.env.NODE_ENV, process.env.OTHER, process.env);",
+        defines(),
+        "a(\"production\", process.env.OTHER, process.env);\n",
+    );
+    expect_printed_with_defines(
+        "a(import.meta.env.MODE, import.meta.url);",
+        defines(),
+        "a(\"production\", import.meta.url);\n",
+    );
+    expect_printed_with_defines(
+        "a(CONFIG.a, global.b);",
+        defines(),
+        "a({ \"a\": [1, null] }.a, window.b);\n",
+    );
+
+    // Only globals are replaced, names declared by the program shadow them.
+    expect_printed_with_defines(
+        "function a(process) { return process.env.NODE_ENV; }",
+        defines(),
+        "function a(process) { return process.env.NODE_ENV;\n }",
+    );
+    expect_printed_with_defines(
+        "const __DEV__ = true; a(__DEV__);",
+        defines(),
+        "const __DEV__ = true;\na(__DEV__);\n",
+    );
+    expect_printed_with_defines("a.__DEV__;", defines(), "a.__DEV__;\n");
+
+    // Assignments to a global are kept since a literal can not be assigned to.
+    expect_printed_with_defines(
+        "process.env.NODE_ENV = \"test\";",
+        defines(),
+        "process.env.NODE_ENV = \"test\";\n",
+    );
+}
+
+fn identifier(name: &str) -> Expression {
+    Expression::Identifier(Identifier {
+        name: name.to_string(),
+    })
+}
+
+fn number(value: f64) -> Expression {
+    Expression::NumericLiteral(NumericLiteral { value })
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(ExpressionStatement { expression })
+}
+
+fn call(callee: Expression) -> Expression {
+    Expression::Call(CallExpression {
+        callee: Box::new(callee),
+        arguments: Vec::new(),
+        pure: false,
+    })
+}
+
+fn member(object: Expression, property: Expression, computed: bool) -> Expression {
+    Expression::Member(MemberExpression {
+        object: Box::new(object),
+        property: Box::new(property),
+        computed,
+    })
+}
+
+fn string(value: &str) -> Expression {
+    Expression::StringLiteral(StringLiteral {
+        value: value.to_string(),
+    })
+}
+
+/// Trees that the parser does not create but transforms may, without
+/// the parentheses of the source.
+#[test]
+fn test_printed_ast() {
+    expect_printed_statements(
+        vec![expression_statement(Expression::Assignment(
+            AssignmentExpression {
+                left: AssignmentExpressionLeft::Expression(Box::new(member(
+                    identifier("let"),
+                    identifier("a"),
+                    true,
+                ))),
+                operator: AssignmentExpressionOperator::Assign,
+                right: Box::new(number(1.)),
+            },
+        ))],
+        "(let)[a] = 1;\n",
+    );
+    expect_printed_statements(
+        vec![Statement::IfStatement(IfStatement {
+            test: identifier("a"),
+            consequent: Box::new(Statement::IfStatement(IfStatement {
+                test: identifier("b"),
+                consequent: Box::new(expression_statement(call(identifier("c")))),
+                alternate: None,
+            })),
+            alternate: Some(Box::new(expression_statement(call(identifier("d"))))),
+        })],
+        "if (a) { if (b) c();\n } else d();\n",
+    );
+    expect_printed_statements(
+        vec![Statement::ForStatement(ForStatement {
+            init: Some(Box::new(Statement::EmptyStatement(EmptyStatement {}))),
+            test: Some(identifier("a")),
+            update: None,
+            body: Box::new(Statement::BlockStatement(BlockStatement {
+                statements: Vec::new(),
+            })),
+        })],
+        "for (; a; ) {}",
+    );
+    expect_printed_statements(
+        vec![expression_statement(Expression::New(NewExpression {
+            callee: Box::new(member(call(identifier("a")), identifier("b"), false)),
+            arguments: Vec::new(),
+            pure: false,
+        }))],
+        "new (a().b)();\n",
+    );
+    expect_printed_statements(
+        vec![
+            expression_statement(member(number(-1.), identifier("a"), false)),
+            expression_statement(Expression::Unary(UnaryExpression {
+                operator: UnaryExpressionOperator::Negative,
+                argument: Box::new(number(-1.)),
+            })),
+        ],
+        "(-1).a;\n- -1;\n",
+    );
+    // Characters that would end the literal are escaped.
+    expect_printed_statements(
+        vec![
+            expression_statement(string("a\nb\rc\u{2029}")),
+            expression_statement(string("a\"b'c'")),
+            expression_statement(string("a\\")),
+        ],
+        "\"a\\nb\\rc\\u2029\";\n\"a\\\"b'c'\";\n\"a\\\\\";\n",
+    );
+    // Numbers without a literal are divisions, the names could be shadowed.
+    expect_printed_statements(
+        vec![
+            expression_statement(number(f64::INFINITY)),
+            expression_statement(number(f64::NEG_INFINITY)),
+            expression_statement(number(f64::NAN)),
+            expression_statement(member(number(f64::INFINITY), identifier("a"), false)),
+            expression_statement(Expression::Unary(UnaryExpression {
+                operator: UnaryExpressionOperator::Negative,
+                argument: Box::new(number(f64::NEG_INFINITY)),
+            })),
+            expression_statement(number(-0.0)),
+        ],
+        "1 / 0;\n-1 / 0;\n0 / 0;\n(1 / 0).a;\n-(-1 / 0);\n-0;\n",
+    );
+}
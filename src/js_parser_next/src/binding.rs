@@ -16,32 +16,59 @@ use span::Span;
 use crate::{Parser, ParserError};
 
 impl<'a> Parser<'a> {
+    /// Parses a binding identifier, an array binding pattern or an object binding pattern
+    pub fn parse_target_binding_pattern(&mut self) -> ParserError<TargetBindingPattern> {
+        match self.lexer.token {
+            Token::OpenBracket => self
+                .parse_array_binding_pattern()
+                .map(TargetBindingPattern::BindingArrayPattern),
+            Token::OpenBrace => self
+                .parse_object_binding_pattern()
+                .map(TargetBindingPattern::BindingObjectPattern),
+            _ => self
+                .parse_binding_identifier()
+                .map(TargetBindingPattern::BindingIdentifier),
+        }
+    }
+
     /// Parses a binding identifier
     pub fn parse_binding_identifier(&mut self) -> ParserError<BindingIdentifier> {
         let name = self.lexer.token_text.to_string();
 
-        if self.strict && self.lexer.token == Token::Yield {
+        if self.lexer.token == Token::EscapedKeyword {
+            return Err(JSError::new(
+                JSErrorKind::EscapedKeyword,
+                Span::new(self.lexer.token_start, self.lexer.token_end),
+            ));
+        }
+
+        // Escaped names are identifiers, but they are still reserved words.
+        let keyword = Token::from_potential_keyword(&name);
+        if self.strict && keyword == Token::Yield {
             return Err(JSError::new(
                 JSErrorKind::UnexpectedYieldAsBindingIdentifier,
                 Span::new(self.lexer.token_start, self.lexer.token_end),
             ));
         }
 
-        if self.module && self.lexer.token == Token::Await {
+        if self.module && keyword == Token::Await {
             return Err(JSError::new(
                 JSErrorKind::UnexpectedAwaitAsBindingIdentifier,
                 Span::new(self.lexer.token_start, self.lexer.token_end),
             ));
         }
 
-        if self.lexer.token.is_keyword() {
+        // Punctuators and literals can not be bound either
+        if self.lexer.token.is_keyword()
+            || !(self.lexer.token.is_identifier() || self.lexer.token.is_future_reserved())
+        {
             return Err(JSError::new(
                 JSErrorKind::ExpectedBindingIdentifier,
                 Span::new(self.lexer.token_start, self.lexer.token_end),
             ));
         }
 
-        if self.strict && self.lexer.token.is_future_reserved() {
+        if self.strict && keyword.is_future_reserved() {
             return Err(JSError::new(
                 JSErrorKind::StrictModeReserved,
                 Span::new(self.lexer.token_start, self.lexer.token_end),
@@ -75,7 +102,7 @@ impl<'a> Parser<'a> {
                 continue;
             }
 
-            if let Some(_) = rest {
+            if rest.is_some() {
                 return Err(JSError::new(
                     JSErrorKind::RestElementMustBeLast,
                     Span::new(self.lexer.token_start, self.lexer.token_end),
@@ -144,7 +171,7 @@ impl<'a> Parser<'a> {
                 continue;
             }
 
-            if let Some(_) = rest {
+            if rest.is_some() {
                 return Err(JSError::new(
                     JSErrorKind::RestElementMustBeLast,
                     Span::new(self.lexer.token_start, self.lexer.token_end),
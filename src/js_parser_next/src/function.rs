@@ -0,0 +1,108 @@
+use js_ast_next::{
+    function_declaration::{FormalParameter, FunctionDeclaration},
+    precedence::Precedence,
+    Statement, TargetBindingPattern,
+};
+use js_error::{JSError, JSErrorKind};
+use js_lexer_next::Token;
+use span::Span;
+
+use crate::{Parser, ParserError, StatementContext};
+
+impl<'a> Parser<'a> {
+    /// Parses a function starting at the function keyword, `start` is
+    /// the position of the async keyword of async functions
+    pub fn parse_function(
+        &mut self,
+        start: usize,
+        is_async: bool,
+    ) -> ParserError<FunctionDeclaration> {
+        self.lexer.consume(Token::Function)?;
+        let is_generator = self.lexer.token == Token::Asterisk;
+        if is_generator {
+            self.lexer.next()?;
+        }
+        let id = self.parse_binding_identifier()?;
+        let (parameters, rest) = self.parse_formal_parameters()?;
+
+        // Labels and loops around the function can not be targeted from its body
+        let context = std::mem::replace(
+            &mut self.context,
+            StatementContext {
+                in_function: true,
+                in_generator: is_generator,
+                ..StatementContext::default()
+            },
+        );
+        let body = self.parse_function_body();
+        self.context = context;
+        let body = body?;
+
+        let end = self.lexer.token_start;
+        Ok(FunctionDeclaration {
+            id,
+            is_async,
+            is_generator,
+            parameters,
+            rest,
+            body,
+            span: Span::new(start, end),
+        })
+    }
+
+    /// Parses the parameters of a function, including the parentheses
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-parameter-lists)
+    fn parse_formal_parameters(
+        &mut self,
+    ) -> ParserError<(Vec<FormalParameter>, Option<TargetBindingPattern>)> {
+        let mut parameters: Vec<FormalParameter> = Vec::new();
+        let mut rest: Option<TargetBindingPattern> = None;
+        self.lexer.consume(Token::OpenParen)?;
+        while self.lexer.token != Token::CloseParen {
+            // (...a)
+            if self.lexer.token == Token::DotDotDot {
+                self.lexer.next()?;
+                rest = self.parse_target_binding_pattern().map(Some)?;
+                if self.lexer.token != Token::CloseParen {
+                    return Err(JSError::new(
+                        JSErrorKind::RestElementMustBeLast,
+                        Span::new(self.lexer.token_start, self.lexer.token_end),
+                    ));
+                }
+                break;
+            }
+
+            let start = self.lexer.token_start;
+            let binding = self.parse_target_binding_pattern()?;
+            let initializer = match self.lexer.token {
+                Token::Equals => {
+                    self.lexer.next()?;
+                    self.parse_expression(&Precedence::Comma).map(Some)?
+                }
+                _ => None,
+            };
+            let end = self.lexer.token_start;
+            parameters.push(FormalParameter {
+                binding,
+                initializer,
+                span: Span::new(start, end),
+            });
+            if self.lexer.token != Token::CloseParen {
+                self.lexer.consume(Token::Comma)?;
+            }
+        }
+        self.lexer.consume(Token::CloseParen)?;
+        Ok((parameters, rest))
+    }
+
+    /// Parses the body of a function, including the braces
+    ///
+    /// See [spec](https://tc39.es/ecma262/#prod-FunctionBody)
+    fn parse_function_body(&mut self) -> ParserError<Vec<Statement>> {
+        self.lexer.consume(Token::OpenBrace)?;
+        let statements = self.parse_statement_list()?;
+        self.lexer.consume(Token::CloseBrace)?;
+        Ok(statements)
+    }
+}
@@ -1,19 +1,44 @@
 use js_ast_next::{
     array_expression::{ArrayExpression, ArrayExpressionElement},
     array_hole::ArrayHole,
+    assignment_expression::{
+        AssignmentExpression, AssignmentExpressionOperator, AssignmentExpressionTarget,
+    },
+    big_int_literal::BigIntLiteral,
     binary_expression::{BinaryExpression, BinaryExpressionOperator},
+    block_statement::BlockStatement,
     boolean_literal::BooleanLiteral,
+    break_statement::BreakStatement,
+    continue_statement::ContinueStatement,
+    debugger_statement::DebuggerStatement,
+    do_while_statement::DoWhileStatement,
+    empty_statement::EmptyStatement,
     expression_statement::ExpressionStatement,
+    for_in_statement::ForInStatement,
+    for_of_statement::ForOfStatement,
+    for_statement::{ForStatement, ForStatementInit},
     identifier_name::IdentifierName,
+    identifier_reference::IdentifierReference,
+    if_statement::IfStatement,
+    label_identifier::LabelIdentifier,
+    labelled_statement::LabelledStatement,
     lexical_binding::LexicalBinding,
     lexical_declaration::LexicalDeclaration,
     numeric_literal::NumericLiteral,
     precedence::Precedence,
     regexp_literal::RegexpLiteral,
+    return_statement::ReturnStatement,
     spread_element::SpreadElement,
     string_literal::StringLiteral,
+    switch_statement::{SwitchCase, SwitchStatement},
+    throw_statement::ThrowStatement,
+    try_statement::{CatchClause, TryStatement},
+    update_expression::{UpdateExpression, UpdateExpressionOperator},
     variable_declaration::VariableDeclaration,
     variable_statement::VariableStatement,
+    while_statement::WhileStatement,
+    with_statement::WithStatement,
+    yield_expression::YieldExpression,
     Expression, LiteralPropertyName, Statement, TargetBindingPattern, AST,
 };
 use js_error::{JSError, JSErrorKind};
@@ -23,6 +48,7 @@ use span::Span;
 pub type ParserError<T> = Result<T, JSError>;
 
 mod binding;
+mod function;
 
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
@@ -30,14 +56,47 @@ pub struct Parser<'a> {
     strict: bool,
     /// Are we in a module
     module: bool,
+    /// Is the in operator allowed, it is not in the initializer of a for statement
+    allow_in: bool,
+    /// Which statements are allowed at the current position
+    context: StatementContext,
+}
+
+/// Return, break and continue statements and labels depend on the statements
+/// around them, a function body starts a new context.
+#[derive(Default)]
+struct StatementContext {
+    /// Are we in a function body
+    in_function: bool,
+    /// Are we in the body of a generator, where yield is an expression
+    in_generator: bool,
+    /// Are we in the body of a loop
+    in_iteration: bool,
+    /// Are we in a switch statement
+    in_switch: bool,
+    /// The labels of the enclosing statements
+    labels: Vec<Label>,
+}
+
+/// The label of an enclosing labelled statement
+struct Label {
+    name: String,
+    /// The start of the labelled statement
+    start: usize,
+    /// The start of the labelled body, `b: c` is the body of `a: b: c`
+    body_start: usize,
+    /// Does the label label a loop, which allows it to be continued
+    is_loop: bool,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(lexer: Lexer<'a>) -> Parser {
+    pub fn new(lexer: Lexer<'a>) -> Parser<'a> {
         Parser {
             lexer,
             strict: true,
             module: true,
+            allow_in: true,
+            context: StatementContext::default(),
         }
     }
 
@@ -70,16 +129,19 @@ impl<'a> Parser<'a> {
                 .map(Statement::LexicalDeclaration),
             Token::Continue => self.parse_continue_statement(),
             Token::Break => self.parse_break_statement(),
-            Token::Return => self.parse_break_statement(),
-            Token::Throw => self.parse_break_statement(),
+            Token::Return => self.parse_return_statement(),
+            Token::Throw => self.parse_throw_statement(),
             Token::Try => self.parse_try_statement(),
             Token::Debugger => self.parse_debugger_statement(),
             Token::Switch => self.parse_switch_statement(),
             Token::With => self.parse_with_statement(),
             Token::Function => self.parse_function_declaration(),
-            _ => self
-                .parse_expression_statement()
-                .map(Statement::ExpressionStatement),
+            Token::Async => self.parse_async_function_declaration(),
+            _ => {
+                let start = self.lexer.token_start;
+                let expression = self.parse_expression(&Precedence::Comma)?;
+                self.parse_expression_or_labelled_statement(start, expression)
+            }
         }
     }
 
@@ -87,91 +149,581 @@ impl<'a> Parser<'a> {
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-block)
     fn parse_block_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        self.parse_block().map(Statement::BlockStatement)
+    }
+
+    /// Parses a block, which is also the body of try statements and catch clauses
+    fn parse_block(&mut self) -> ParserError<BlockStatement> {
+        let start = self.lexer.token_start;
+        self.lexer.consume(Token::OpenBrace)?;
+        let statements = self.parse_statement_list()?;
+        self.lexer.consume(Token::CloseBrace)?;
+        let end = self.lexer.token_start;
+        Ok(BlockStatement {
+            statements,
+            span: Span::new(start, end),
+        })
+    }
+
+    /// Parses statements until the closing brace of a block, a switch case or a function body
+    fn parse_statement_list(&mut self) -> ParserError<Vec<Statement>> {
+        let mut statements: Vec<Statement> = Vec::new();
+        while !matches!(self.lexer.token, Token::CloseBrace | Token::Eof) {
+            statements.push(self.parse_statement()?);
+        }
+        Ok(statements)
+    }
+
+    /// Parses the body of an if statement, a loop, a with statement or a labelled
+    /// statement, where declarations are not allowed in strict mode
+    fn parse_substatement(&mut self) -> ParserError<Statement> {
+        match self.lexer.token {
+            Token::Const | Token::Let | Token::Function => Err(JSError::new(
+                JSErrorKind::DeclarationInStatementPosition,
+                Span::new(self.lexer.token_start, self.lexer.token_end),
+            )),
+            _ => self.parse_statement(),
+        }
+    }
+
+    /// Parses the body of a loop, where break and continue statements are allowed
+    fn parse_iteration_body(&mut self) -> ParserError<Statement> {
+        let in_iteration = std::mem::replace(&mut self.context.in_iteration, true);
+        let body = self.parse_substatement();
+        self.context.in_iteration = in_iteration;
+        body
     }
 
     /// Parses an empty statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-empty-statement)
     fn parse_empty_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::EmptyStatement(EmptyStatement {
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses an if statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-if-statement)
     fn parse_if_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?; // if
+        let test = self.parse_parenthesized_condition()?;
+        let consequent = self.parse_substatement().map(Box::new)?;
+        let alternate = match self.lexer.token {
+            Token::Else => {
+                self.lexer.next()?;
+                self.parse_substatement().map(Box::new).map(Some)?
+            }
+            _ => None,
+        };
+        let end = self.lexer.token_start;
+        Ok(Statement::IfStatement(IfStatement {
+            test,
+            consequent,
+            alternate,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Consumes the semicolon at the end of a statement, which can be left out
+    /// before a closing brace, at the end of the file or after a newline
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-automatic-semicolon-insertion)
+    fn consume_semicolon(&mut self) -> ParserError<()> {
+        match self.lexer.token {
+            Token::Semicolon => self.lexer.next(),
+            Token::CloseBrace | Token::Eof => Ok(()),
+            _ if self.lexer.has_newline_before => Ok(()),
+            _ => Err(JSError::new(
+                JSErrorKind::ExpectedSemicolon,
+                Span::new(self.lexer.token_start, self.lexer.token_end),
+            )),
+        }
+    }
+
+    /// Parses the `(expression)` after if, while, switch and with
+    fn parse_parenthesized_condition(&mut self) -> ParserError<Expression> {
+        self.lexer.consume(Token::OpenParen)?;
+        let expression = self.parse_expression(&Precedence::Comma)?;
+        self.lexer.consume(Token::CloseParen)?;
+        Ok(expression)
     }
 
     /// Parses a do while statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-do-while-statement)
     fn parse_do_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?; // do
+        let body = self.parse_iteration_body().map(Box::new)?;
+        self.lexer.consume(Token::While)?;
+        let test = self.parse_parenthesized_condition()?;
+        self.consume_semicolon()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::DoWhileStatement(DoWhileStatement {
+            body,
+            test,
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses a while statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-while-statement)
     fn parse_while_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?; // while
+        let test = self.parse_parenthesized_condition()?;
+        let body = self.parse_iteration_body().map(Box::new)?;
+        let end = self.lexer.token_start;
+        Ok(Statement::WhileStatement(WhileStatement {
+            test,
+            body,
+            span: Span::new(start, end),
+        }))
     }
 
-    /// Parses a for statement
+    /// Parses a for statement, a for in statement or a for of statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-for-statement)
     fn parse_for_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?; // for
+        self.lexer.consume(Token::OpenParen)?;
+        let allow_in = std::mem::replace(&mut self.allow_in, false);
+        let init = self.parse_for_statement_init();
+        self.allow_in = allow_in;
+        let init = match init? {
+            Some(left) if matches!(self.lexer.token, Token::In | Token::Of) => {
+                return self.parse_for_in_of_statement(start, left)
+            }
+            init => init,
+        };
+
+        if let Some(ForStatementInit::LexicalDeclaration(declaration)) = &init {
+            if let Some(binding) = declaration
+                .declarations
+                .iter()
+                .find(|binding| declaration.is_const && binding.initializer.is_none())
+            {
+                return Err(JSError::new(
+                    JSErrorKind::MissingConstInitializer,
+                    binding.span.clone(),
+                ));
+            }
+        }
+
+        self.lexer.consume(Token::Semicolon)?;
+        let test = match self.lexer.token {
+            Token::Semicolon => None,
+            _ => self.parse_expression(&Precedence::Comma).map(Some)?,
+        };
+        self.lexer.consume(Token::Semicolon)?;
+        let update = match self.lexer.token {
+            Token::CloseParen => None,
+            _ => self.parse_expression(&Precedence::Comma).map(Some)?,
+        };
+        self.lexer.consume(Token::CloseParen)?;
+        let body = self.parse_iteration_body().map(Box::new)?;
+        let end = self.lexer.token_start;
+        Ok(Statement::ForStatement(ForStatement {
+            init,
+            test,
+            update,
+            body,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parses the initializer of a for statement, which is also the left side
+    /// of a for in or a for of statement
+    fn parse_for_statement_init(&mut self) -> ParserError<Option<ForStatementInit>> {
+        Ok(match self.lexer.token {
+            Token::Semicolon => None,
+            Token::Var => {
+                let start = self.lexer.token_start;
+                self.lexer.next()?;
+                let declarations = self.parse_variable_declaration_list()?;
+                let end = self.lexer.token_start;
+                Some(ForStatementInit::VariableStatement(VariableStatement {
+                    declarations,
+                    span: Span::new(start, end),
+                }))
+            }
+            Token::Const | Token::Let => {
+                let start = self.lexer.token_start;
+                let is_const = matches!(self.lexer.token, Token::Const);
+                self.lexer.next()?;
+                // The initializer of a const is only required in a plain for statement
+                let declarations = self.parse_lexical_binding_list(false)?;
+                let end = self.lexer.token_start;
+                Some(ForStatementInit::LexicalDeclaration(LexicalDeclaration {
+                    declarations,
+                    is_const,
+                    span: Span::new(start, end),
+                }))
+            }
+            _ => self
+                .parse_expression(&Precedence::Comma)
+                .map(ForStatementInit::Expression)
+                .map(Some)?,
+        })
+    }
+
+    /// Parses the rest of a for in or a for of statement, starting at the `in` or `of`
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-for-in-and-for-of-statements)
+    fn parse_for_in_of_statement(
+        &mut self,
+        start: usize,
+        left: ForStatementInit,
+    ) -> ParserError<Statement> {
+        // The declaration has to be a single binding without an initializer
+        let declaration = match &left {
+            ForStatementInit::VariableStatement(v) => Some((
+                &v.span,
+                v.declarations.len(),
+                v.declarations[0].initializer.is_some(),
+            )),
+            ForStatementInit::LexicalDeclaration(l) => Some((
+                &l.span,
+                l.declarations.len(),
+                l.declarations[0].initializer.is_some(),
+            )),
+            ForStatementInit::Expression(_) => None,
+        };
+        if let Some((span, count, has_initializer)) = declaration {
+            if count != 1 || has_initializer {
+                return Err(JSError::new(
+                    JSErrorKind::InvalidForInOfDeclaration,
+                    span.clone(),
+                ));
+            }
+        }
+
+        let is_of = self.lexer.token == Token::Of;
+        self.lexer.next()?; // in | of
+        let right = self.parse_expression(&Precedence::Comma)?;
+        self.lexer.consume(Token::CloseParen)?;
+        let body = self.parse_iteration_body().map(Box::new)?;
+        let end = self.lexer.token_start;
+        let span = Span::new(start, end);
+        Ok(match is_of {
+            true => Statement::ForOfStatement(ForOfStatement {
+                left,
+                right,
+                body,
+                span,
+            }),
+            false => Statement::ForInStatement(ForInStatement {
+                left,
+                right,
+                body,
+                span,
+            }),
+        })
+    }
+
+    /// Parses the label after break and continue, which has to be on the same line
+    fn parse_optional_label(&mut self) -> ParserError<Option<LabelIdentifier>> {
+        if !self.lexer.token.is_identifier() || self.lexer.has_newline_before {
+            return Ok(None);
+        }
+
+        let start = self.lexer.token_start;
+        let name = self.lexer.token_text.to_string();
+        let span = Span::new(start, self.lexer.token_end);
+        if !self.context.labels.iter().any(|label| label.name == name) {
+            return Err(JSError::new(JSErrorKind::UndefinedLabel, span));
+        }
+        self.lexer.next()?;
+        let end = self.lexer.token_start;
+        Ok(Some(LabelIdentifier {
+            name,
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses a continue statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-continue-statement)
     fn parse_continue_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        let keyword = Span::new(start, self.lexer.token_end);
+        self.lexer.next()?; // continue
+        let id = self.parse_optional_label()?;
+        // Only a label of a loop can be continued
+        let is_valid = match &id {
+            Some(id) => self
+                .context
+                .labels
+                .iter()
+                .any(|label| label.name == id.name && label.is_loop),
+            None => self.context.in_iteration,
+        };
+        if !is_valid {
+            return Err(JSError::new(JSErrorKind::IllegalContinue, keyword));
+        }
+        self.consume_semicolon()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::ContinueStatement(ContinueStatement {
+            id,
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses a break statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-break-statement)
     fn parse_break_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        let keyword = Span::new(start, self.lexer.token_end);
+        self.lexer.next()?; // break
+        let id = self.parse_optional_label()?;
+        if id.is_none() && !self.context.in_iteration && !self.context.in_switch {
+            return Err(JSError::new(JSErrorKind::IllegalBreak, keyword));
+        }
+        self.consume_semicolon()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::BreakStatement(BreakStatement {
+            id,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parses a return statement
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-return-statement)
+    fn parse_return_statement(&mut self) -> ParserError<Statement> {
+        let start = self.lexer.token_start;
+        if !self.context.in_function {
+            return Err(JSError::new(
+                JSErrorKind::ReturnOutsideFunction,
+                Span::new(start, self.lexer.token_end),
+            ));
+        }
+        self.lexer.next()?; // return
+        let argument = match self.lexer.token {
+            Token::Semicolon | Token::CloseBrace | Token::Eof => None,
+            _ if self.lexer.has_newline_before => None,
+            _ => self.parse_expression(&Precedence::Comma).map(Some)?,
+        };
+        self.consume_semicolon()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::ReturnStatement(ReturnStatement {
+            argument,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parses a throw statement
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-throw-statement)
+    fn parse_throw_statement(&mut self) -> ParserError<Statement> {
+        let start = self.lexer.token_start;
+        let keyword = Span::new(start, self.lexer.token_end);
+        self.lexer.next()?; // throw
+        if self.lexer.has_newline_before {
+            return Err(JSError::new(JSErrorKind::NewlineAfterThrow, keyword));
+        }
+        let argument = self.parse_expression(&Precedence::Comma)?;
+        self.consume_semicolon()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::ThrowStatement(ThrowStatement {
+            argument,
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses a try statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-try-statement)
     fn parse_try_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        let keyword = Span::new(start, self.lexer.token_end);
+        self.lexer.next()?; // try
+        let block = self.parse_block()?;
+        let handler = match self.lexer.token {
+            Token::Catch => {
+                let start = self.lexer.token_start;
+                self.lexer.next()?;
+                // try {} catch {}
+                let param = match self.lexer.token {
+                    Token::OpenParen => {
+                        self.lexer.next()?;
+                        let param = self.parse_target_binding_pattern()?;
+                        self.lexer.consume(Token::CloseParen)?;
+                        Some(param)
+                    }
+                    _ => None,
+                };
+                let body = self.parse_block()?;
+                let end = self.lexer.token_start;
+                Some(CatchClause {
+                    param,
+                    body,
+                    span: Span::new(start, end),
+                })
+            }
+            _ => None,
+        };
+        let finalizer = match self.lexer.token {
+            Token::Finally => {
+                self.lexer.next()?;
+                self.parse_block().map(Some)?
+            }
+            _ => None,
+        };
+        if handler.is_none() && finalizer.is_none() {
+            return Err(JSError::new(JSErrorKind::MissingCatchOrFinally, keyword));
+        }
+        let end = self.lexer.token_start;
+        Ok(Statement::TryStatement(TryStatement {
+            block,
+            handler,
+            finalizer,
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses a debugger statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-debugger-statement)
     fn parse_debugger_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?; // debugger
+        self.consume_semicolon()?;
+        let end = self.lexer.token_start;
+        Ok(Statement::DebuggerStatement(DebuggerStatement {
+            span: Span::new(start, end),
+        }))
     }
 
     /// Parses a switch statement
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-switch-statement)
     fn parse_switch_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.lexer.next()?; // switch
+        let discriminant = self.parse_parenthesized_condition()?;
+        self.lexer.consume(Token::OpenBrace)?;
+        let in_switch = std::mem::replace(&mut self.context.in_switch, true);
+        let cases = self.parse_switch_cases();
+        self.context.in_switch = in_switch;
+        let cases = cases?;
+        self.lexer.consume(Token::CloseBrace)?;
+        let end = self.lexer.token_start;
+        Ok(Statement::SwitchStatement(SwitchStatement {
+            discriminant,
+            cases,
+            span: Span::new(start, end),
+        }))
+    }
+
+    /// Parses the case and default clauses of a switch statement
+    fn parse_switch_cases(&mut self) -> ParserError<Vec<SwitchCase>> {
+        let mut cases: Vec<SwitchCase> = Vec::new();
+        let mut has_default = false;
+        while !matches!(self.lexer.token, Token::CloseBrace | Token::Eof) {
+            let start = self.lexer.token_start;
+            let test = match self.lexer.token {
+                Token::Case => {
+                    self.lexer.next()?;
+                    self.parse_expression(&Precedence::Comma).map(Some)?
+                }
+                Token::Default if has_default => {
+                    return Err(JSError::new(
+                        JSErrorKind::MultipleDefaultsInSwitch,
+                        Span::new(start, self.lexer.token_end),
+                    ))
+                }
+                Token::Default => {
+                    has_default = true;
+                    self.lexer.next()?;
+                    None
+                }
+                _ => {
+                    return Err(JSError::new(
+                        JSErrorKind::SyntaxError,
+                        Span::new(start, self.lexer.token_end),
+                    ))
+                }
+            };
+            self.lexer.consume(Token::Colon)?;
+
+            let mut consequent: Vec<Statement> = Vec::new();
+            while !matches!(
+                self.lexer.token,
+                Token::Case | Token::Default | Token::CloseBrace | Token::Eof
+            ) {
+                consequent.push(self.parse_statement()?);
+            }
+            let end = self.lexer.token_start;
+            cases.push(SwitchCase {
+                test,
+                consequent,
+                span: Span::new(start, end),
+            });
+        }
+        Ok(cases)
     }
 
-    /// Parses a with statement (14.7.4)
+    /// Parses a with statement (14.11)
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-with-statement)
     fn parse_with_statement(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        if self.strict {
+            return Err(JSError::new(
+                JSErrorKind::StrictModeWith,
+                Span::new(start, self.lexer.token_end),
+            ));
+        }
+        self.lexer.next()?; // with
+        let object = self.parse_parenthesized_condition()?;
+        let body = self.parse_substatement().map(Box::new)?;
+        let end = self.lexer.token_start;
+        Ok(Statement::WithStatement(WithStatement {
+            object,
+            body,
+            span: Span::new(start, end),
+        }))
     }
 
-    /// Parses a function declaration (14.7.4)
+    /// Parses a function declaration (14.1)
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-function-definitions)
     fn parse_function_declaration(&mut self) -> ParserError<Statement> {
-        todo!()
+        let start = self.lexer.token_start;
+        self.parse_function(start, false)
+            .map(Statement::FunctionDeclaration)
+    }
+
+    /// Parses a statement starting with async, which is either an async function
+    /// declaration or an expression statement where async is an identifier
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-async-function-definitions)
+    fn parse_async_function_declaration(&mut self) -> ParserError<Statement> {
+        let start = self.lexer.token_start;
+        let name = self.lexer.token_text.to_string();
+        self.lexer.next()?; // async
+        if self.lexer.token == Token::Function && !self.lexer.has_newline_before {
+            return self
+                .parse_function(start, true)
+                .map(Statement::FunctionDeclaration);
+        }
+
+        let identifier = Expression::IdentifierReference(IdentifierReference {
+            name,
+            span: Span::new(start, self.lexer.token_start),
+        });
+        let expression = self.parse_suffix(start, identifier, &Precedence::Comma)?;
+        self.parse_expression_or_labelled_statement(start, expression)
     }
 
     /// Parses a variable statement (14.7.4)
@@ -181,7 +733,7 @@ impl<'a> Parser<'a> {
         let start = self.lexer.token_start;
         self.lexer.next()?; // var
         let declarations = self.parse_variable_declaration_list()?;
-        self.lexer.consume_optional(Token::Semicolon)?;
+        self.consume_semicolon()?;
         let end = self.lexer.token_start;
         Ok(VariableStatement {
             declarations,
@@ -242,13 +794,10 @@ impl<'a> Parser<'a> {
     /// See [spec](https://tc39.es/ecma262/#sec-let-and-const-declarations)
     fn parse_lexical_declaration(&mut self) -> ParserError<LexicalDeclaration> {
         let start = self.lexer.token_start;
-        let is_const = match self.lexer.token {
-            Token::Const => true,
-            _ => false,
-        };
+        let is_const = matches!(self.lexer.token, Token::Const);
         self.lexer.next()?;
         let declarations = self.parse_lexical_binding_list(is_const)?;
-        self.lexer.consume_optional(Token::Semicolon)?;
+        self.consume_semicolon()?;
         let end = self.lexer.token_start;
         Ok(LexicalDeclaration {
             declarations,
@@ -257,11 +806,15 @@ impl<'a> Parser<'a> {
         })
     }
 
-    /// Parses a lexical binding list
-    fn parse_lexical_binding_list(&mut self, is_const: bool) -> ParserError<Vec<LexicalBinding>> {
+    /// Parses a lexical binding list, `require_initializer` is set for const declarations
+    /// outside of for statements
+    fn parse_lexical_binding_list(
+        &mut self,
+        require_initializer: bool,
+    ) -> ParserError<Vec<LexicalBinding>> {
         let mut declarations: Vec<LexicalBinding> = Vec::new();
         loop {
-            declarations.push(self.parse_lexical_binding(is_const)?);
+            declarations.push(self.parse_lexical_binding(require_initializer)?);
             if self.lexer.token != Token::Comma {
                 break;
             }
@@ -272,7 +825,7 @@ impl<'a> Parser<'a> {
 
     /// Parses a lexical binding
     ///
-    fn parse_lexical_binding(&mut self, is_const: bool) -> ParserError<LexicalBinding> {
+    fn parse_lexical_binding(&mut self, require_initializer: bool) -> ParserError<LexicalBinding> {
         let start = self.lexer.token_start;
         let binding = match self.lexer.token {
             Token::OpenBracket => self
@@ -291,7 +844,7 @@ impl<'a> Parser<'a> {
                 self.parse_expression(&Precedence::Comma).map(Some)?
             }
             _ => {
-                if is_const {
+                if require_initializer {
                     return Err(JSError::new(
                         JSErrorKind::MissingConstInitializer,
                         Span::new(start, self.lexer.token_end),
@@ -309,17 +862,75 @@ impl<'a> Parser<'a> {
         })
     }
 
-    /// Parses an expression statement
+    /// Parses the rest of an expression statement, or of a labelled statement
+    /// when the expression is an identifier followed by a colon
     ///
     /// See [spec](https://tc39.es/ecma262/#sec-expression-statement)
-    fn parse_expression_statement(&mut self) -> ParserError<ExpressionStatement> {
-        let start = self.lexer.token_start;
-        let expression = self.parse_expression(&Precedence::Comma)?;
+    fn parse_expression_or_labelled_statement(
+        &mut self,
+        start: usize,
+        expression: Expression,
+    ) -> ParserError<Statement> {
+        match expression {
+            Expression::IdentifierReference(identifier) if self.lexer.token == Token::Colon => {
+                self.parse_labelled_statement(start, identifier)
+            }
+            expression => {
+                self.consume_semicolon()?;
+                let end = self.lexer.token_start;
+                Ok(Statement::ExpressionStatement(ExpressionStatement {
+                    expression,
+                    span: Span::new(start, end),
+                }))
+            }
+        }
+    }
+
+    /// Parses a labelled statement, starting at the colon
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-labelled-statements)
+    fn parse_labelled_statement(
+        &mut self,
+        start: usize,
+        identifier: IdentifierReference,
+    ) -> ParserError<Statement> {
+        let name = identifier.name;
+        if self.context.labels.iter().any(|label| label.name == name) {
+            return Err(JSError::new(JSErrorKind::DuplicateLabel, identifier.span));
+        }
+        self.lexer.next()?; // :
+
+        // A continue statement can only target the label of a loop, in `a: b: for (;;) {}`
+        // both labels label the loop
+        let is_loop = matches!(self.lexer.token, Token::Do | Token::While | Token::For);
+        if is_loop {
+            let mut body_start = start;
+            for label in self.context.labels.iter_mut().rev() {
+                if label.body_start != body_start {
+                    break;
+                }
+                label.is_loop = true;
+                body_start = label.start;
+            }
+        }
+        self.context.labels.push(Label {
+            name: name.clone(),
+            start,
+            body_start: self.lexer.token_start,
+            is_loop,
+        });
+        let body = self.parse_substatement();
+        self.context.labels.pop();
+        let body = body.map(Box::new)?;
         let end = self.lexer.token_start;
-        Ok(ExpressionStatement {
-            expression,
+        Ok(Statement::LabelledStatement(LabelledStatement {
+            label: LabelIdentifier {
+                name,
+                span: identifier.span,
+            },
+            body,
             span: Span::new(start, end),
-        })
+        }))
     }
 
     /// Parses an expression
@@ -328,16 +939,18 @@ impl<'a> Parser<'a> {
     /// forward by [Vaughan Pratt](https://tdop.github.io), so called
     /// pratt parser or top down operator precedence parsing.
     fn parse_expression(&mut self, precedence: &Precedence) -> ParserError<Expression> {
+        let start = self.lexer.token_start;
         let expr = self.parse_prefix(precedence)?;
-        self.parse_suffix(expr, precedence)
+        self.parse_suffix(start, expr, precedence)
     }
 
     /// Parses an expression in a prefix position
     fn parse_prefix(&mut self, precedence: &Precedence) -> ParserError<Expression> {
         match self.lexer.token {
             Token::Number => self.parse_numeric_literal().map(Expression::NumericLiteral),
+            Token::BigInt => self.parse_big_int_literal().map(Expression::BigIntLiteral),
             Token::OpenBracket => self
-                .parse_array_expression()
+                .with_in_allowed(Self::parse_array_expression)
                 .map(Expression::ArrayExpression),
             Token::OpenParen => self.parse_parenthesized_expression(),
             Token::Slash => self.parse_regexp_literal().map(Expression::RegexpLiteral),
@@ -345,30 +958,142 @@ impl<'a> Parser<'a> {
             Token::True | Token::False => {
                 self.parse_boolean_literal().map(Expression::BooleanLiteral)
             }
-            _ => todo!(),
+            _ if self.lexer.token.is_identifier() => self
+                .parse_identifier_reference()
+                .map(Expression::IdentifierReference),
+            Token::PlusPlus | Token::MinusMinus => self
+                .parse_prefix_update_expression()
+                .map(Expression::UpdateExpression),
+            Token::Yield if self.context.in_generator => self
+                .parse_yield_expression(precedence)
+                .map(Expression::YieldExpression),
+            // yield is an identifier outside of generators in sloppy mode
+            Token::Yield if !self.strict => self
+                .parse_identifier_reference()
+                .map(Expression::IdentifierReference),
+            Token::Yield => Err(JSError::new(
+                JSErrorKind::StrictModeReserved,
+                Span::new(self.lexer.token_start, self.lexer.token_end),
+            )),
+            _ => Err(JSError::new(
+                JSErrorKind::SyntaxError,
+                Span::new(self.lexer.token_start, self.lexer.token_end),
+            )),
+        }
+    }
+
+    /// Parses a prefix increment or decrement, `++a` or `--a`
+    ///
+    /// See [spec](https://tc39.es/ecma262/#sec-update-expressions)
+    fn parse_prefix_update_expression(&mut self) -> ParserError<UpdateExpression> {
+        let start = self.lexer.token_start;
+        let operator = match self.lexer.token {
+            Token::PlusPlus => UpdateExpressionOperator::Increment,
+            _ => UpdateExpressionOperator::Decrement,
+        };
+        self.lexer.next()?; // ++ | --
+        let argument = self.parse_expression(&Precedence::Prefix)?;
+        let end = self.lexer.token_start;
+        let argument = self.expect_simple_assignment_target(argument, Span::new(start, end))?;
+        Ok(UpdateExpression {
+            argument: Box::new(Expression::IdentifierReference(argument)),
+            operator,
+            prefix: true,
+            span: Span::new(start, end),
+        })
+    }
+
+    /// Parses a yield expression, which is only allowed where an assignment expression is
+    ///
+    /// See [spec](https://tc39.es/ecma262/#prod-YieldExpression)
+    fn parse_yield_expression(&mut self, precedence: &Precedence) -> ParserError<YieldExpression> {
+        let start = self.lexer.token_start;
+        if precedence >= &Precedence::Assignment {
+            return Err(JSError::new(
+                JSErrorKind::SyntaxError,
+                Span::new(start, self.lexer.token_end),
+            ));
+        }
+        self.lexer.next()?; // yield
+        let delegate = self.lexer.token == Token::Asterisk && !self.lexer.has_newline_before;
+        if delegate {
+            self.lexer.next()?;
+        }
+        // The argument is optional unless the yield delegates, `yield\na` is `yield; a`
+        let argument = match self.lexer.token {
+            _ if delegate => self.parse_expression(&Precedence::Yield).map(Some)?,
+            Token::CloseParen
+            | Token::CloseBracket
+            | Token::CloseBrace
+            | Token::Colon
+            | Token::Comma
+            | Token::Semicolon
+            | Token::Eof => None,
+            _ if self.lexer.has_newline_before => None,
+            _ => self.parse_expression(&Precedence::Yield).map(Some)?,
+        };
+        let end = self.lexer.token_start;
+        Ok(YieldExpression {
+            argument: argument.map(Box::new),
+            delegate,
+            span: Span::new(start, end),
+        })
+    }
+
+    /// Only identifiers can be assigned to or updated, `a = 1` and `a++` but not `1 = a`
+    fn expect_simple_assignment_target(
+        &self,
+        expression: Expression,
+        span: Span,
+    ) -> ParserError<IdentifierReference> {
+        match expression {
+            Expression::IdentifierReference(identifier) => Ok(identifier),
+            _ => Err(JSError::new(JSErrorKind::InvalidAssignmentTarget, span)),
         }
     }
 
+    /// Parses with the in operator allowed, such as the elements of `for ([a in b];;) {}`
+    fn with_in_allowed<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> ParserError<T>,
+    ) -> ParserError<T> {
+        let allow_in = std::mem::replace(&mut self.allow_in, true);
+        let result = parse(self);
+        self.allow_in = allow_in;
+        result
+    }
+
     /// Parses a parenthesized expression
     fn parse_parenthesized_expression(&mut self) -> ParserError<Expression> {
+        let start = self.lexer.token_start;
         self.lexer.next()?;
+        let elements = self.with_in_allowed(Self::parse_parenthesized_elements)?;
+        let end = self.lexer.token_end;
+        self.lexer.consume(Token::CloseParen)?;
+
+        // TODO: Parse arrow functions, `()` is only valid as their parameters
+        elements
+            .into_iter()
+            .next()
+            .ok_or_else(|| JSError::new(JSErrorKind::SyntaxError, Span::new(start, end)))
+    }
+
+    /// Parses the expressions between the parentheses, a rest element such as
+    /// `(...a)` is only allowed in the parameters of arrow functions
+    fn parse_parenthesized_elements(&mut self) -> ParserError<Vec<Expression>> {
         let mut elements: Vec<Expression> = Vec::new();
         while self.lexer.token != Token::CloseParen {
             if self.lexer.token == Token::DotDotDot {
-                todo!()
+                return Err(JSError::new(
+                    JSErrorKind::SyntaxError,
+                    Span::new(self.lexer.token_start, self.lexer.token_end),
+                ));
             }
 
             let element = self.parse_expression(&Precedence::Comma)?;
             elements.push(element);
         }
-        self.lexer.consume(Token::CloseParen)?;
-
-        if elements.len() == 1 {
-            return Ok(elements[0].clone());
-        }
-
-        // TODO: Parse arrow functions
-        Ok(elements[0].clone())
+        Ok(elements)
     }
 
     /// Parses an array expression
@@ -462,6 +1187,18 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// Parses an identifier reference
+    fn parse_identifier_reference(&mut self) -> ParserError<IdentifierReference> {
+        let start = self.lexer.token_start;
+        let name = self.lexer.token_text.to_string();
+        self.lexer.next()?;
+        let end = self.lexer.token_start;
+        Ok(IdentifierReference {
+            name,
+            span: Span::new(start, end),
+        })
+    }
+
     /// Parses an numeric expression
     fn parse_numeric_literal(&mut self) -> ParserError<NumericLiteral> {
         let start = self.lexer.token_start;
@@ -474,6 +1211,18 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// Parses a big int expression
+    fn parse_big_int_literal(&mut self) -> ParserError<BigIntLiteral> {
+        let start = self.lexer.token_start;
+        let value = self.lexer.token_text.replace('_', "");
+        self.lexer.next()?;
+        let end = self.lexer.token_start;
+        Ok(BigIntLiteral {
+            value,
+            span: Span::new(start, end),
+        })
+    }
+
     /// Parses a string expression
     fn parse_string_literal(&mut self) -> ParserError<StringLiteral> {
         let start = self.lexer.token_start;
@@ -515,88 +1264,144 @@ impl<'a> Parser<'a> {
         })
     }
 
-    /// Parses an expression in an infix or suffix position
+    /// Parses an expression in an infix or suffix position, `start` is the
+    /// position of the left expression
     fn parse_suffix(
         &mut self,
+        start: usize,
         left: Expression,
         precedence: &Precedence,
     ) -> ParserError<Expression> {
         let mut expression = left;
 
         loop {
-            match self.lexer.token {
-                // a + b
-                Token::Plus => {
-                    if precedence >= &Precedence::Sum {
-                        return Ok(expression);
-                    }
-                    self.lexer.next()?;
-                    expression = Expression::BinaryExpression(BinaryExpression {
-                        span: Span::new(0, 0),
-                        left: Box::new(expression),
-                        operator: BinaryExpressionOperator::Addition,
-                        right: self.parse_expression(&Precedence::Sum).map(Box::new)?,
-                    })
-                }
-
-                // a - b
-                Token::Minus => {
-                    if precedence >= &Precedence::Sum {
-                        return Ok(expression);
-                    }
-                    self.lexer.next()?;
-                    expression = Expression::BinaryExpression(BinaryExpression {
-                        span: Span::new(0, 0),
-                        left: Box::new(expression),
-                        operator: BinaryExpressionOperator::Substitution,
-                        right: self.parse_expression(&Precedence::Sum).map(Box::new)?,
-                    })
-                }
-
-                // a * b
-                Token::Asterisk => {
-                    if precedence >= &Precedence::Product {
-                        return Ok(expression);
-                    }
-                    self.lexer.next()?;
-                    expression = Expression::BinaryExpression(BinaryExpression {
-                        span: Span::new(0, 0),
-                        left: Box::new(expression),
-                        operator: BinaryExpressionOperator::Multiplication,
-                        right: self.parse_expression(&Precedence::Product).map(Box::new)?,
-                    })
-                }
-
-                // a / b
-                Token::Slash => {
-                    if precedence >= &Precedence::Product {
-                        return Ok(expression);
-                    }
-                    self.lexer.next()?;
-                    expression = Expression::BinaryExpression(BinaryExpression {
-                        span: Span::new(0, 0),
-                        left: Box::new(expression),
-                        operator: BinaryExpressionOperator::Division,
-                        right: self.parse_expression(&Precedence::Product).map(Box::new)?,
-                    })
+            // a++ or a--
+            if matches!(self.lexer.token, Token::PlusPlus | Token::MinusMinus) {
+                // A line terminator ends the expression, `a\n++b` is `a; ++b`
+                if precedence >= &Precedence::Postfix || self.lexer.has_newline_before {
+                    return Ok(expression);
                 }
+                let operator = match self.lexer.token {
+                    Token::PlusPlus => UpdateExpressionOperator::Increment,
+                    _ => UpdateExpressionOperator::Decrement,
+                };
+                let argument = self.expect_simple_assignment_target(
+                    expression,
+                    Span::new(start, self.lexer.token_start),
+                )?;
+                self.lexer.next()?;
+                let end = self.lexer.token_start;
+                expression = Expression::UpdateExpression(UpdateExpression {
+                    argument: Box::new(Expression::IdentifierReference(argument)),
+                    operator,
+                    prefix: false,
+                    span: Span::new(start, end),
+                });
+                continue;
+            }
 
-                // a ** b
-                Token::AsteriskAsterisk => {
-                    if precedence >= &Precedence::Product {
-                        return Ok(expression);
-                    }
-                    self.lexer.next()?;
-                    expression = Expression::BinaryExpression(BinaryExpression {
-                        span: Span::new(0, 0),
-                        left: Box::new(expression),
-                        operator: BinaryExpressionOperator::Exponentiation,
-                        right: self.parse_expression(&Precedence::Product).map(Box::new)?,
-                    })
+            // a = b or a += b, assignments are right associative
+            if let Some(operator) = assignment_operator(&self.lexer.token) {
+                if precedence >= &Precedence::Assignment {
+                    return Ok(expression);
                 }
+                let target = self.expect_simple_assignment_target(
+                    expression,
+                    Span::new(start, self.lexer.token_start),
+                )?;
+                self.lexer.next()?;
+                let argument = self.parse_expression(&Precedence::Assignment.lower())?;
+                let end = self.lexer.token_start;
+                expression = Expression::AssignmentExpression(AssignmentExpression {
+                    target: AssignmentExpressionTarget::IdentifierReference(target),
+                    operator,
+                    argument: Box::new(argument),
+                    span: Span::new(start, end),
+                });
+                continue;
+            }
 
-                _ => return Ok(expression),
+            // a + b
+            let operator = match binary_operator(&self.lexer.token) {
+                Some(BinaryExpressionOperator::In) if !self.allow_in => return Ok(expression),
+                Some(operator) => operator,
+                None => return Ok(expression),
+            };
+            let operator_precedence = operator.precedence();
+            if precedence >= &operator_precedence {
+                return Ok(expression);
             }
+            self.lexer.next()?;
+            // Exponentiation is right associative, `a ** b ** c` is `a ** (b ** c)`
+            let right_precedence = match operator {
+                BinaryExpressionOperator::Exponentiation => operator_precedence.lower(),
+                _ => operator_precedence,
+            };
+            let right = self.parse_expression(&right_precedence)?;
+            let end = self.lexer.token_start;
+            expression = Expression::BinaryExpression(BinaryExpression {
+                span: Span::new(start, end),
+                left: Box::new(expression),
+                operator,
+                right: Box::new(right),
+            });
         }
     }
 }
+
+/// Returns the binary operator of the token, `in` is only an operator where it is allowed
+fn binary_operator(token: &Token) -> Option<BinaryExpressionOperator> {
+    Some(match token {
+        Token::Plus => BinaryExpressionOperator::Addition,
+        Token::Minus => BinaryExpressionOperator::Substitution,
+        Token::Asterisk => BinaryExpressionOperator::Multiplication,
+        Token::Slash => BinaryExpressionOperator::Division,
+        Token::Percent => BinaryExpressionOperator::Modulus,
+        Token::AsteriskAsterisk => BinaryExpressionOperator::Exponentiation,
+        Token::LessThan => BinaryExpressionOperator::LessThan,
+        Token::LessThanEquals => BinaryExpressionOperator::LessThanEquals,
+        Token::GreaterThan => BinaryExpressionOperator::GreaterThan,
+        Token::GreaterThanEquals => BinaryExpressionOperator::GreaterThanEquals,
+        Token::In => BinaryExpressionOperator::In,
+        Token::Instanceof => BinaryExpressionOperator::Instanceof,
+        Token::LessThanLessThan => BinaryExpressionOperator::LeftShift,
+        Token::GreaterThanGreaterThan => BinaryExpressionOperator::RightShift,
+        Token::GreaterThanGreaterThanGreaterThan => BinaryExpressionOperator::UnsignedRightShift,
+        Token::EqualsEquals => BinaryExpressionOperator::LooseEquals,
+        Token::ExclamationEquals => BinaryExpressionOperator::LooseNotEquals,
+        Token::EqualsEqualsEquals => BinaryExpressionOperator::StrictEquals,
+        Token::ExclamationEqualsEquals => BinaryExpressionOperator::StrictNotEquals,
+        Token::QuestionQuestion => BinaryExpressionOperator::NullishCoalescing,
+        Token::Bar => BinaryExpressionOperator::BitwiseOr,
+        Token::Ampersand => BinaryExpressionOperator::BitwiseAnd,
+        Token::Caret => BinaryExpressionOperator::BitwiseXor,
+        Token::AmpersandAmpersand => BinaryExpressionOperator::LogicalAnd,
+        Token::BarBar => BinaryExpressionOperator::LogicalOr,
+        _ => return None,
+    })
+}
+
+/// Returns the assignment operator of the token
+fn assignment_operator(token: &Token) -> Option<AssignmentExpressionOperator> {
+    Some(match token {
+        Token::Equals => AssignmentExpressionOperator::Assign,
+        Token::PlusEquals => AssignmentExpressionOperator::AdditionAssign,
+        Token::MinusEquals => AssignmentExpressionOperator::SubstitutionAssign,
+        Token::AsteriskEquals => AssignmentExpressionOperator::MultiplicationAssign,
+        Token::SlashEquals => AssignmentExpressionOperator::DivisionAssign,
+        Token::PercentEquals => AssignmentExpressionOperator::ModulusAssign,
+        Token::AsteriskAsteriskEquals => AssignmentExpressionOperator::ExponentiationAssign,
+        Token::LessThanLessThanEquals => AssignmentExpressionOperator::LeftShiftAssign,
+        Token::GreaterThanGreaterThanEquals => AssignmentExpressionOperator::RightShiftAssign,
+        Token::GreaterThanGreaterThanGreaterThanEquals => {
+            AssignmentExpressionOperator::UnsignedRightShiftAssign
+        }
+        Token::BarEquals => AssignmentExpressionOperator::BitwiseOrAssign,
+        Token::AmpersandEquals => AssignmentExpressionOperator::BitwiseAndAssign,
+        Token::CaretEquals => AssignmentExpressionOperator::BitwiseXorAssign,
+        Token::QuestionQuestionEquals => AssignmentExpressionOperator::NullishCoalescingAssign,
+        Token::BarBarEquals => AssignmentExpressionOperator::LogicalOrAssign,
+        Token::AmpersandAmpersandEquals => AssignmentExpressionOperator::LogicalAndAssign,
+        _ => return None,
+    })
+}
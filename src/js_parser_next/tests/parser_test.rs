@@ -166,23 +166,356 @@ fn test_binary_expressions() {
     expect_printed("5 - 5", "5 - 5;\n");
     expect_printed("5 * 5", "5 * 5;\n");
     expect_printed("5 / 5", "5 / 5;\n");
-    // expect_printed("5 % 5", "5 % 5;\n");
-    // expect_printed("5 ** 5", "5 ** 5;\n");
-    // expect_printed("5 < 5", "5 < 5;\n");
-    // expect_printed("5 <= 5", "5 <= 5;\n");
-    // expect_printed("5 > 5", "5 > 5;\n");
-    // expect_printed("5 >= 5", "5 >= 5;\n");
-    // expect_printed("5 in 5", "5 in 5;\n");
-    // expect_printed("5 instanceof 5", "5 instanceof 5;\n");
-    // expect_printed("5 << 5", "5 << 5;\n");
-    // expect_printed("5 >> 5", "5 >> 5;\n");
-    // expect_printed("5 >>> 5", "5 >>> 5;\n");
-    // expect_printed("5 == 5", "5 == 5;\n");
-    // expect_printed("5 != 5", "5 != 5;\n");
-    // expect_printed("5 === 5", "5 === 5;\n");
-    // expect_printed("5 !== 5", "5 !== 5;\n");
-    // expect_printed("5 ?? 5", "5 ?? 5;\n");
-    // expect_printed("5 | 5", "5 | 5;\n");
-    // expect_printed("5 & 5", "5 & 5;\n");
-    // expect_printed("5 ^ 5", "5 ^ 5;\n");
+    expect_printed("5 % 5", "5 % 5;\n");
+    expect_printed("5 ** 5", "5 ** 5;\n");
+    expect_printed("5 < 5", "5 < 5;\n");
+    expect_printed("5 <= 5", "5 <= 5;\n");
+    expect_printed("5 > 5", "5 > 5;\n");
+    expect_printed("5 >= 5", "5 >= 5;\n");
+    expect_printed("5 in 5", "5 in 5;\n");
+    expect_printed("5 instanceof 5", "5 instanceof 5;\n");
+    expect_printed("5 << 5", "5 << 5;\n");
+    expect_printed("5 >> 5", "5 >> 5;\n");
+    expect_printed("5 >>> 5", "5 >>> 5;\n");
+    expect_printed("5 == 5", "5 == 5;\n");
+    expect_printed("5 != 5", "5 != 5;\n");
+    expect_printed("5 === 5", "5 === 5;\n");
+    expect_printed("5 !== 5", "5 !== 5;\n");
+    expect_printed("5 ?? 5", "5 ?? 5;\n");
+    expect_printed("5 | 5", "5 | 5;\n");
+    expect_printed("5 & 5", "5 & 5;\n");
+    expect_printed("5 ^ 5", "5 ^ 5;\n");
+    expect_printed("5 && 5", "5 && 5;\n");
+    expect_printed("5 || 5", "5 || 5;\n");
+    expect_printed("1 + 2 * 3", "1 + 2 * 3;\n");
+    expect_printed("(1 + 2) * 3", "(1 + 2) * 3;\n");
+    expect_printed("1 - (2 - 3)", "1 - (2 - 3);\n");
+    expect_printed("2 ** 3 ** 2", "2 ** 3 ** 2;\n");
+    expect_printed("(2 ** 3) ** 2", "(2 ** 3) ** 2;\n");
+    expect_printed("a < 1;", "a < 1;\n");
+
+    expect_error("()", JSErrorKind::SyntaxError);
+    expect_error("(...a)", JSErrorKind::SyntaxError);
+    expect_error("5 +", JSErrorKind::SyntaxError);
+}
+
+#[test]
+fn test_assignment_expressions() {
+    expect_printed("a = 1;", "a = 1;\n");
+    expect_printed("a += 1", "a += 1;\n");
+    expect_printed("a **= 2", "a **= 2;\n");
+    expect_printed("a >>>= 2", "a >>>= 2;\n");
+    expect_printed("a ??= b", "a ??= b;\n");
+    expect_printed("a = b = c", "a = b = c;\n");
+    expect_printed("a = 1 + 2", "a = 1 + 2;\n");
+    expect_printed("(a) = 1", "a = 1;\n");
+
+    expect_error("1 = a", JSErrorKind::InvalidAssignmentTarget);
+    expect_error("a + b = c", JSErrorKind::InvalidAssignmentTarget);
+}
+
+#[test]
+fn test_update_expressions() {
+    expect_printed("i++;", "i++;\n");
+    expect_printed("i--", "i--;\n");
+    expect_printed("++i", "++i;\n");
+    expect_printed("--i", "--i;\n");
+    expect_printed("a\n++b", "a;\n++b;\n");
+    expect_printed("a + b++", "a + b++;\n");
+
+    expect_error("1++", JSErrorKind::InvalidAssignmentTarget);
+    expect_error("++1", JSErrorKind::InvalidAssignmentTarget);
+}
+
+#[test]
+fn test_yield_expressions() {
+    expect_printed(
+        "function* g(){ yield 1 }",
+        "function* g() {\n  yield 1;\n}\n",
+    );
+    expect_printed("function* g(){ yield }", "function* g() {\n  yield;\n}\n");
+    expect_printed(
+        "function* g(){ yield* a }",
+        "function* g() {\n  yield* a;\n}\n",
+    );
+    expect_printed(
+        "function* g(){ a = yield b }",
+        "function* g() {\n  a = yield b;\n}\n",
+    );
+    expect_printed(
+        "function* g(){ yield\na }",
+        "function* g() {\n  yield;\n  a;\n}\n",
+    );
+
+    expect_error("yield 1", JSErrorKind::StrictModeReserved);
+    expect_error("function g(){ yield 1 }", JSErrorKind::StrictModeReserved);
+    expect_error("function* g(){ 1 + yield }", JSErrorKind::SyntaxError);
+}
+
+#[test]
+fn test_block_statements() {
+    expect_printed("{}", "{}\n");
+    expect_printed("{;}", "{\n  ;\n}\n");
+    expect_printed("{ a; { b } }", "{\n  a;\n  {\n    b;\n  }\n}\n");
+    expect_printed("a; b", "a;\nb;\n");
+    expect_printed(";", ";\n");
+
+    expect_error("{ a", JSErrorKind::SyntaxError);
+}
+
+#[test]
+fn test_if_statements() {
+    expect_printed("if (a) b", "if (a)\n  b;\n");
+    expect_printed("if (a) {}", "if (a) {}\n");
+    expect_printed("if (a) { b }", "if (a) {\n  b;\n}\n");
+    expect_printed("if (a) {} else {}", "if (a) {} else {}\n");
+    expect_printed("if (a) b; else c", "if (a)\n  b;\nelse\n  c;\n");
+    expect_printed(
+        "if (a) {} else if (b) {} else {}",
+        "if (a) {} else if (b) {} else {}\n",
+    );
+    expect_printed(
+        "if (a) if (b) c; else d",
+        "if (a)\n  if (b)\n    c;\n  else\n    d;\n",
+    );
+
+    expect_error("if a {}", JSErrorKind::SyntaxError);
+    expect_error(
+        "if (a) let b = 1;",
+        JSErrorKind::DeclarationInStatementPosition,
+    );
+    expect_error(
+        "if (a) {} else const b = 1;",
+        JSErrorKind::DeclarationInStatementPosition,
+    );
+    expect_error(
+        "if (a) function b() {}",
+        JSErrorKind::DeclarationInStatementPosition,
+    );
+}
+
+#[test]
+fn test_loops() {
+    expect_printed("while (a) {}", "while (a) {}\n");
+    expect_printed("while (a) b", "while (a)\n  b;\n");
+    expect_printed("do {} while (a)", "do {} while (a);\n");
+    expect_printed("do a; while (b)\nc", "do\n  a;\nwhile (b);\nc;\n");
+    expect_printed("for (;;) {}", "for (;;) {}\n");
+    expect_printed("for (a; b; c) {}", "for (a; b; c) {}\n");
+    expect_printed("for (var a = 1, b;;) {}", "for (var a = 1, b;;) {}\n");
+    expect_printed("for (let a = 1; a;) {}", "for (let a = 1; a;) {}\n");
+    expect_printed("for (const a = 1;;) {}", "for (const a = 1;;) {}\n");
+    expect_printed("for (var a in b) {}", "for (var a in b) {}\n");
+    expect_printed("for (const [a] in b) {}", "for (const [a] in b) {}\n");
+    expect_printed("for (a in b) c", "for (a in b)\n  c;\n");
+    expect_printed("for (let {a} of b) {}", "for (let {a} of b) {}\n");
+    expect_printed("for (const a of b) {}", "for (const a of b) {}\n");
+    expect_printed("for (a of b) {}", "for (a of b) {}\n");
+    expect_printed(
+        "for (let i = 0; i < 1; i++) {}",
+        "for (let i = 0; i < 1; i++) {}\n",
+    );
+    expect_printed("for (a = (1 in b);;) {}", "for (a = (1 in b);;) {}\n");
+
+    expect_error("for (const a;;) {}", JSErrorKind::MissingConstInitializer);
+    expect_error(
+        "for (var a = 1 in b) {}",
+        JSErrorKind::InvalidForInOfDeclaration,
+    );
+    expect_error(
+        "for (let a, b of c) {}",
+        JSErrorKind::InvalidForInOfDeclaration,
+    );
+    expect_error(
+        "for (const a = 1 of b) {}",
+        JSErrorKind::InvalidForInOfDeclaration,
+    );
+    expect_error("for (a) {}", JSErrorKind::SyntaxError);
+    expect_error("do {} until (a)", JSErrorKind::SyntaxError);
+    expect_error(
+        "while (a) const b = 1;",
+        JSErrorKind::DeclarationInStatementPosition,
+    );
+}
+
+#[test]
+fn test_break_and_continue() {
+    expect_printed("while (a) break", "while (a)\n  break;\n");
+    expect_printed("while (a) { continue; }", "while (a) {\n  continue;\n}\n");
+    expect_printed("a: { break a; }", "a: {\n  break a;\n}\n");
+    expect_printed(
+        "a: while (b) { continue a; }",
+        "a: while (b) {\n  continue a;\n}\n",
+    );
+    expect_printed(
+        "a: for (;;) for (;;) break a",
+        "a: for (;;)\n  for (;;)\n    break a;\n",
+    );
+    expect_printed(
+        "switch (a) { case 1: break; }",
+        "switch (a) {\n  case 1:\n    break;\n}\n",
+    );
+    // Every label directly in front of a loop can be continued
+    expect_printed(
+        "a: b: for (;;) continue a;",
+        "a: b: for (;;)\n  continue a;\n",
+    );
+    // The label has to be on the same line as the break
+    expect_printed(
+        "a: while (b) { break\na }",
+        "a: while (b) {\n  break;\n  a;\n}\n",
+    );
+
+    expect_error("break", JSErrorKind::IllegalBreak);
+    expect_error("continue", JSErrorKind::IllegalContinue);
+    expect_error("{ break; }", JSErrorKind::IllegalBreak);
+    expect_error(
+        "switch (a) { case 1: continue; }",
+        JSErrorKind::IllegalContinue,
+    );
+    expect_error("while (a) break b", JSErrorKind::UndefinedLabel);
+    expect_error("a: { continue a; }", JSErrorKind::IllegalContinue);
+    expect_error("a: a: ;", JSErrorKind::DuplicateLabel);
+    expect_error(
+        "a: { b: for (;;) continue a; }",
+        JSErrorKind::IllegalContinue,
+    );
+    expect_error(
+        "while (a) { function b() { break; } }",
+        JSErrorKind::IllegalBreak,
+    );
+    expect_error(
+        "a: { function b() { break a; } }",
+        JSErrorKind::UndefinedLabel,
+    );
+}
+
+#[test]
+fn test_return_and_throw() {
+    expect_printed("function a() { return }", "function a() {\n  return;\n}\n");
+    expect_printed(
+        "function a() { return 1 }",
+        "function a() {\n  return 1;\n}\n",
+    );
+    expect_printed(
+        "function a() { return\n1 }",
+        "function a() {\n  return;\n  1;\n}\n",
+    );
+    expect_printed("throw a", "throw a;\n");
+    expect_printed("throw 1 + 2;", "throw 1 + 2;\n");
+
+    expect_error("return", JSErrorKind::ReturnOutsideFunction);
+    expect_error("{ return 1; }", JSErrorKind::ReturnOutsideFunction);
+    expect_error("throw\na", JSErrorKind::NewlineAfterThrow);
+}
+
+#[test]
+fn test_try_statements() {
+    expect_printed("try {} catch (a) {}", "try {} catch (a) {}\n");
+    expect_printed("try {} catch {}", "try {} catch {}\n");
+    expect_printed("try {} finally {}", "try {} finally {}\n");
+    expect_printed(
+        "try {} catch ({a, b}) {} finally {}",
+        "try {} catch ({a, b}) {} finally {}\n",
+    );
+    expect_printed(
+        "try { a } catch ([b]) { c }",
+        "try {\n  a;\n} catch ([b]) {\n  c;\n}\n",
+    );
+
+    expect_error("try {}", JSErrorKind::MissingCatchOrFinally);
+    expect_error("try a; catch {}", JSErrorKind::SyntaxError);
+    expect_error(
+        "try {} catch (1) {}",
+        JSErrorKind::ExpectedBindingIdentifier,
+    );
+}
+
+#[test]
+fn test_switch_statements() {
+    expect_printed("switch (a) {}", "switch (a) {}\n");
+    expect_printed(
+        "switch (a) { case 1: case 2: b; default: c; d }",
+        "switch (a) {\n  case 1:\n  case 2:\n    b;\n  default:\n    c;\n    d;\n}\n",
+    );
+    expect_printed(
+        "switch (a) { default: case b: }",
+        "switch (a) {\n  default:\n  case b:\n}\n",
+    );
+
+    expect_error(
+        "switch (a) { default: default: }",
+        JSErrorKind::MultipleDefaultsInSwitch,
+    );
+    expect_error("switch (a) { b; }", JSErrorKind::SyntaxError);
+    expect_error("switch (a) { case 1 }", JSErrorKind::SyntaxError);
+}
+
+#[test]
+fn test_other_statements() {
+    expect_printed("debugger", "debugger;\n");
+    expect_printed("debugger; a", "debugger;\na;\n");
+    expect_printed("a: b", "a: b;\n");
+    expect_printed("async: a", "async: a;\n");
+    expect_printed("async + 1", "async + 1;\n");
+
+    // Modules are always strict mode code
+    expect_error("with (a) {}", JSErrorKind::StrictModeWith);
+}
+
+#[test]
+fn test_function_declarations() {
+    expect_printed("function a() {}", "function a() {}\n");
+    expect_printed("function a(b, c) {}", "function a(b, c) {}\n");
+    expect_printed("function a(b = 1, [c]) {}", "function a(b = 1, [c]) {}\n");
+    expect_printed("function a({b}, ...c) {}", "function a({b}, ...c) {}\n");
+    expect_printed("function a(...[b]) {}", "function a(...[b]) {}\n");
+    expect_printed("function a(b,) {}", "function a(b) {}\n");
+    expect_printed("function* a() {}", "function* a() {}\n");
+    expect_printed("async function a() {}", "async function a() {}\n");
+    expect_printed("async function* a() {}", "async function* a() {}\n");
+    expect_printed(
+        "function a() { function b() { var c; } }",
+        "function a() {\n  function b() {\n    var c;\n  }\n}\n",
+    );
+
+    expect_error("function () {}", JSErrorKind::ExpectedBindingIdentifier);
+    expect_error("function a(...b, c) {}", JSErrorKind::RestElementMustBeLast);
+    expect_error("function a(...b,) {}", JSErrorKind::RestElementMustBeLast);
+    expect_error("function a(b c) {}", JSErrorKind::SyntaxError);
+    expect_error("function a() {", JSErrorKind::SyntaxError);
+    expect_error(
+        "function yield() {}",
+        JSErrorKind::UnexpectedYieldAsBindingIdentifier,
+    );
+}
+
+#[test]
+fn test_automatic_semicolon_insertion() {
+    expect_printed("a\nb", "a;\nb;\n");
+    expect_printed("{ a }", "{\n  a;\n}\n");
+    expect_printed(
+        "var a = 1\nlet b = 2\nconst c = 3",
+        "var a = 1;\nlet b = 2;\nconst c = 3;\n",
+    );
+    expect_printed("while (a) { continue }", "while (a) {\n  continue;\n}\n");
+    expect_printed(
+        "function a() { return\nb }",
+        "function a() {\n  return;\n  b;\n}\n",
+    );
+    expect_printed("throw a\nb", "throw a;\nb;\n");
+    expect_printed("debugger\na", "debugger;\na;\n");
+
+    expect_error("a b", JSErrorKind::ExpectedSemicolon);
+    expect_error("var a = 1 var b = 2", JSErrorKind::ExpectedSemicolon);
+    expect_error("let a = 1 b", JSErrorKind::ExpectedSemicolon);
+    expect_error("do x; while (y) z", JSErrorKind::ExpectedSemicolon);
+    expect_error("while (a) { break 1 }", JSErrorKind::ExpectedSemicolon);
+    expect_error("while (a) { continue 1 }", JSErrorKind::ExpectedSemicolon);
+    expect_error(
+        "function a() { return b c }",
+        JSErrorKind::ExpectedSemicolon,
+    );
+    expect_error("throw a b", JSErrorKind::ExpectedSemicolon);
+    expect_error("debugger a", JSErrorKind::ExpectedSemicolon);
 }
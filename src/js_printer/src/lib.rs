@@ -1,15 +1,76 @@
 use js_ast::{precedence::*, *};
 
+mod number;
+
+use number::{number_to_string, shortest_number};
+
+/// Decides what happens to legal comments, that is comments starting
+/// with `/*!` or containing `@license` or `@preserve`. Legal comments
+/// inside of a statement are moved before the statement.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum LegalComments {
+    /// Keep the comments where they were found.
+    #[default]
+    Inline,
+    /// Move all of the comments to the end of the file.
+    EndOfFile,
+    /// Leave the comments out of the output, they can be
+    /// retrieved with `Printer::legal_comments` and written
+    /// to a separate file.
+    External,
+    /// Drop the comments.
+    None,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PrinterOptions {
+    pub legal_comments: LegalComments,
+    /// Prints numbers in their shortest form, such as `1e3` and `.5`.
+    pub minify_syntax: bool,
+    /// Escapes the characters outside of ASCII, so the output does not
+    /// depend on the charset it is served with.
+    pub ascii_only: bool,
+    /// The output runs in ES5, which has no `\u{...}` escapes. Characters outside of
+    /// the Basic Multilingual Plane are escaped as surrogate pairs instead, which is
+    /// not possible in identifiers.
+    pub es5: bool,
+}
+
 pub struct Printer {
     text: String,
     statement_start: usize,
+    /// Where the expression body of an arrow function starts, an object
+    /// literal there would be read as a block.
+    arrow_expression_start: usize,
+    /// Set while printing the head of a `for` loop, where an `in` operator
+    /// must be wrapped so it is not read as a `for-in` loop.
+    forbid_in: bool,
+    options: PrinterOptions,
+    /// Legal comments that were not printed inline.
+    legal_comments: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Printer {
     pub fn new() -> Printer {
+        Printer::with_options(PrinterOptions::default())
+    }
+
+    pub fn with_options(options: PrinterOptions) -> Printer {
         Printer {
             text: String::new(),
             statement_start: 0,
+            arrow_expression_start: usize::MAX,
+            forbid_in: false,
+            options,
+            legal_comments: Vec::new(),
+            errors: Vec::new(),
         }
     }
 
@@ -18,7 +79,26 @@ impl Printer {
             self.print_statement(statement);
         }
 
-        return self.text.clone();
+        if self.options.legal_comments == LegalComments::EndOfFile {
+            for comment in self.legal_comments.clone() {
+                self.print(&comment);
+                self.print("\n");
+            }
+        }
+
+        self.text.clone()
+    }
+
+    /// Returns the legal comments that were moved out of the output,
+    /// in the order they were found and without duplicates.
+    pub fn legal_comments(&self) -> &[String] {
+        &self.legal_comments
+    }
+
+    /// Returns the errors for the parts of the program that can not be printed
+    /// with the options, such as identifiers that can not be escaped.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
     }
 }
 
@@ -32,15 +112,24 @@ impl Printer {
 
             Statement::EmptyStatement(_) => self.print(";"),
 
+            Statement::Comment(c) => match self.options.legal_comments {
+                LegalComments::Inline => {
+                    self.print(&c.text);
+                    self.print("\n");
+                }
+                LegalComments::EndOfFile | LegalComments::External => {
+                    if !self.legal_comments.contains(&c.text) {
+                        self.legal_comments.push(c.text.clone());
+                    }
+                }
+                LegalComments::None => {}
+            },
+
             Statement::ClassDeclaration(c) => {
                 self.print("class ");
                 self.print_identifier(&c.identifier);
                 self.print_space();
-                if let Some(super_class) = &c.extends {
-                    self.print("extends ");
-                    self.print_expression(super_class, Precedence::Comma);
-                    self.print_space();
-                }
+                self.print_class_extends(c.extends.as_ref());
                 self.print_class_body(&c.body);
             }
 
@@ -114,11 +203,14 @@ impl Printer {
 
             Statement::ForOfStatement(f) => {
                 self.print("for");
+                if f.is_await {
+                    self.print(" await");
+                }
                 self.print_space();
                 self.print("(");
                 self.print_for_loop_init(&f.left);
                 self.print(" of ");
-                self.print_expression(&f.right, Precedence::Lowest);
+                self.print_expression(&f.right, Precedence::Comma);
                 self.print(")");
                 self.print_space();
                 self.print_statement(&f.body);
@@ -156,33 +248,28 @@ impl Printer {
 
                 self.print_space();
                 self.print("{");
-                if s.cases.len() == 0 {
+                if s.cases.is_empty() {
                     self.print("}");
                     return;
                 }
                 self.print_space();
-                let cases: Vec<&SwitchStatementCase> =
-                    s.cases.iter().filter(|c| c.test != None).collect();
-                for (idx, case) in cases.iter().enumerate() {
+                // The cases are kept in order, the default case falls through
+                // to the cases after it.
+                for (idx, case) in s.cases.iter().enumerate() {
                     if idx != 0 {
                         self.print_space();
                     }
-                    self.print("case ");
-                    // Cases needs to have a test, only the default case is allowed to be none.
-                    self.print_expression(case.test.as_ref().unwrap(), Precedence::LogicalAnd);
-                    self.print(":");
-                    self.print_space();
-                    for consequent in &case.consequent {
-                        self.print_statement(consequent.as_ref());
+                    match &case.test {
+                        Some(test) => {
+                            self.print("case ");
+                            self.print_expression(test, Precedence::Lowest);
+                            self.print(":");
+                        }
+                        None => self.print("default:"),
                     }
-                }
-                let default: Option<&SwitchStatementCase> = s.cases.iter().find(|c| c.test == None);
-                if let Some(case) = default {
-                    if cases.len() > 0 {
+                    if !case.consequent.is_empty() {
                         self.print_space();
                     }
-                    self.print("default:");
-                    self.print_space();
                     for consequent in &case.consequent {
                         self.print_statement(consequent.as_ref());
                     }
@@ -240,7 +327,7 @@ impl Printer {
                 }
 
                 if let Some(namespace) = &i.namespace {
-                    if let Some(_) = &i.default {
+                    if i.default.is_some() {
                         self.print(",");
                         self.print_space();
                     }
@@ -248,8 +335,8 @@ impl Printer {
                     self.print_space();
                     self.print("as ");
                     self.print_identifier(namespace);
-                } else if i.specifiers.len() > 0 {
-                    if let Some(_) = &i.default {
+                } else if !i.specifiers.is_empty() {
+                    if i.default.is_some() {
                         self.print(",");
                         self.print_space();
                     }
@@ -261,10 +348,10 @@ impl Printer {
                             self.print(",");
                             self.print_space();
                         }
-                        self.print_identifier(&specifier.local);
+                        self.print_identifier_name(&specifier.local);
                         if specifier.local.name != specifier.imported.name {
                             self.print(" as ");
-                            self.print_identifier(&specifier.imported);
+                            self.print_identifier_name(&specifier.imported);
                         }
                     }
                     self.print_space();
@@ -272,15 +359,13 @@ impl Printer {
                 }
 
                 // Only print the from if one of the following is true
-                if i.default != None || i.namespace != None || i.specifiers.len() != 0 {
+                if i.default.is_some() || i.namespace.is_some() || !i.specifiers.is_empty() {
                     self.print(" ");
                     self.print("from");
                     self.print_space();
                 }
 
-                self.print("\"");
-                self.print(&i.source.value);
-                self.print("\"");
+                self.print_string_literal(&i.source);
                 self.print_semicolon_after_statement();
             }
 
@@ -326,11 +411,7 @@ impl Printer {
                         self.print("class ");
                         self.print_identifier(&c.identifier);
                         self.print_space();
-                        if let Some(super_class) = &c.extends {
-                            self.print("extends ");
-                            self.print_expression(super_class, Precedence::Comma);
-                            self.print_space();
-                        }
+                        self.print_class_extends(c.extends.as_ref());
                         self.print_class_body(&c.body);
                     }
                 }
@@ -348,13 +429,24 @@ impl Printer {
                     }
 
                     ExportDefaultDeclarationKind::Expression(exp) => {
+                        // A function or class here would be read as a declaration.
+                        let wrap = matches!(exp, Expression::Function(_) | Expression::Class(_));
+                        if wrap {
+                            self.print("(");
+                        }
                         self.print_expression(exp, Precedence::Comma);
+                        if wrap {
+                            self.print(")");
+                        }
                         self.print_semicolon_after_statement();
                     }
 
                     ExportDefaultDeclarationKind::AnonymousDefaultExportedFunctionDeclaration(
                         a,
                     ) => {
+                        if a.is_async {
+                            self.print("async ");
+                        }
                         self.print("function");
                         if a.generator {
                             self.print("*");
@@ -369,11 +461,7 @@ impl Printer {
                     ExportDefaultDeclarationKind::AnonymousDefaultExportedClassDeclaration(c) => {
                         self.print("class");
                         self.print_space();
-                        if let Some(super_class) = &c.extends {
-                            self.print("extends ");
-                            self.print_expression(super_class, Precedence::Comma);
-                            self.print_space();
-                        }
+                        self.print_class_extends(c.extends.as_ref());
                         self.print_class_body(&c.body);
                     }
 
@@ -381,11 +469,7 @@ impl Printer {
                         self.print("class ");
                         self.print_identifier(&c.identifier);
                         self.print_space();
-                        if let Some(super_class) = &c.extends {
-                            self.print("extends ");
-                            self.print_expression(super_class, Precedence::Comma);
-                            self.print_space();
-                        }
+                        self.print_class_extends(c.extends.as_ref());
                         self.print_class_body(&c.body);
                     }
                 }
@@ -395,7 +479,7 @@ impl Printer {
                 self.print("export");
                 self.print_space();
                 self.print("{");
-                if e.specifiers.len() > 0 {
+                if !e.specifiers.is_empty() {
                     self.print_space();
                 }
                 for (idx, specifier) in e.specifiers.iter().enumerate() {
@@ -403,13 +487,13 @@ impl Printer {
                         self.print(",");
                         self.print_space();
                     }
-                    self.print_identifier(&specifier.local);
+                    self.print_identifier_name(&specifier.local);
                     if specifier.local != specifier.exported {
                         self.print(" as ");
-                        self.print_identifier(&specifier.exported);
+                        self.print_identifier_name(&specifier.exported);
                     }
                 }
-                if e.specifiers.len() > 0 {
+                if !e.specifiers.is_empty() {
                     self.print_space();
                 }
                 self.print("}");
@@ -426,7 +510,7 @@ impl Printer {
 
     fn print_literal_property_name(&mut self, literal_property_name: &LiteralPropertyName) {
         match literal_property_name {
-            LiteralPropertyName::Identifier(i) => self.print_identifier(i),
+            LiteralPropertyName::Identifier(i) => self.print_identifier_name(i),
             LiteralPropertyName::String(s) => self.print_string_literal(s),
             LiteralPropertyName::Numeric(n) => self.print_numeric_literal(n),
         }
@@ -438,18 +522,83 @@ impl Printer {
         self.print("]");
     }
 
+    /// Prints the declaration or expression at the start of a `for` loop, an
+    /// empty statement leaves it out.
     fn print_for_loop_init(&mut self, init: &Statement) {
+        self.forbid_in = true;
         match init {
             Statement::Expression(exp) => {
+                self.statement_start = self.text.len();
                 self.print_expression(&exp.expression, Precedence::Lowest)
             }
             Statement::VariableDeclaration(v) => self.print_variable_declaration(v),
-            _ => panic!("Internal server error"),
+            Statement::EmptyStatement(_) => {}
+            statement => unreachable!(
+                "A for loop starts with a declaration or an expression, found {:?}",
+                statement
+            ),
         }
+        self.forbid_in = false;
     }
 
+    /// Prints the string with the quote that needs the fewest escapes, double quotes
+    /// are preferred. The value is the text as written so it may contain escapes already,
+    /// those are kept as they are. That includes escaped lone surrogates, which can not
+    /// be a part of the value in any other way.
+    ///
+    /// Characters that would end the literal are escaped: the quote, line terminators
+    /// and a backslash at the end of the value.
     fn print_string_literal(&mut self, string_literal: &StringLiteral) {
-        self.print(&format!("\"{}\"", string_literal.value));
+        let value = &string_literal.value;
+        let quote = if unescaped_count(value, '"') <= unescaped_count(value, '\'') {
+            '"'
+        } else {
+            '\''
+        };
+
+        let mut text = String::with_capacity(value.len() + 2);
+        text.push(quote);
+        let mut escaped = false;
+        for c in value.chars() {
+            if escaped {
+                // An escaped line terminator is a line continuation.
+                text.push(c);
+                escaped = false;
+                continue;
+            }
+            match c {
+                '\n' => text.push_str("\\n"),
+                '\r' => text.push_str("\\r"),
+                '\u{2028}' => text.push_str("\\u2028"),
+                '\u{2029}' => text.push_str("\\u2029"),
+                c if c == quote => {
+                    text.push('\\');
+                    text.push(c);
+                }
+                c => {
+                    escaped = c == '\\';
+                    text.push(c);
+                }
+            }
+        }
+        if escaped {
+            text.push('\\');
+        }
+        text.push(quote);
+        if self.options.ascii_only {
+            text = escape_non_ascii(&text, !self.options.es5);
+        }
+        self.print(&text);
+    }
+
+    /// Prints the text between the substitutions of a template literal. Template
+    /// literals are newer than `\u{...}` escapes so they can always be used.
+    fn print_template_text(&mut self, text: &str) {
+        if self.options.ascii_only {
+            self.print(&escape_non_ascii(text, true));
+        } else {
+            self.print(text);
+        }
     }
 
     fn print_variable_declaration(&mut self, variable_declaration: &VariableDeclaration) {
@@ -466,11 +615,7 @@ impl Printer {
         };
     }
 
-    fn print_declaration_statement(
-        &mut self,
-        keyword: &str,
-        declarations: &Vec<VariableDeclarator>,
-    ) {
+    fn print_declaration_statement(&mut self, keyword: &str, declarations: &[VariableDeclarator]) {
         self.print(keyword);
         self.print_space();
         // TODO: We currently only handle one declaration.
@@ -490,11 +635,12 @@ impl Printer {
     }
 
     fn print_block_statement(&mut self, block_statement: &BlockStatement) {
-        if block_statement.statements.len() == 0 {
+        if block_statement.statements.is_empty() {
             self.print("{}");
             return;
         }
 
+        let forbid_in = std::mem::replace(&mut self.forbid_in, false);
         self.print("{");
         self.print_space();
         for statement in &block_statement.statements {
@@ -502,6 +648,7 @@ impl Printer {
         }
         self.print_space();
         self.print("}");
+        self.forbid_in = forbid_in;
     }
 
     fn print_if_statement(&mut self, if_statement: &IfStatement) {
@@ -511,7 +658,17 @@ impl Printer {
         self.print_expression(&if_statement.test, Precedence::Lowest);
         self.print(")");
         self.print_space();
-        self.print_statement(&if_statement.consequent);
+        match &if_statement.alternate {
+            // The else would belong to an if statement at the end of the consequent.
+            Some(_) if ends_with_if_without_else(&if_statement.consequent) => {
+                self.print("{");
+                self.print_space();
+                self.print_statement(&if_statement.consequent);
+                self.print_space();
+                self.print("}");
+            }
+            _ => self.print_statement(&if_statement.consequent),
+        }
         if let Some(alternate) = &if_statement.alternate {
             self.print_space();
             self.print("else");
@@ -521,6 +678,12 @@ impl Printer {
     }
 
     fn print_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+        if function_declaration.no_side_effects {
+            self.print("/* @__NO_SIDE_EFFECTS__ */ ");
+        }
+        if function_declaration.is_async {
+            self.print("async ");
+        }
         self.print("function");
         if function_declaration.generator {
             self.print("*");
@@ -551,6 +714,10 @@ impl Printer {
             }
 
             Expression::Class(c) => {
+                let wrap = self.text.len() == self.statement_start;
+                if wrap {
+                    self.print("(");
+                }
                 self.print("class");
                 if let Some(id) = &c.identifier {
                     self.print(" ");
@@ -559,70 +726,66 @@ impl Printer {
                 } else {
                     self.print_space();
                 }
-                if let Some(super_class) = &c.extends {
-                    self.print("extends ");
-                    self.print_expression(super_class, Precedence::Comma);
-                    self.print_space();
-                }
+                self.print_class_extends(c.extends.as_deref());
                 self.print_class_body(&c.body);
+                if wrap {
+                    self.print(")");
+                }
             }
 
-            Expression::Identifier(e) => {
-                self.print(&e.name);
-            }
+            Expression::Identifier(e) => self.print_identifier(e),
 
-            Expression::NumericLiteral(e) => {
-                self.print(&e.value.to_string());
-            }
+            Expression::NumericLiteral(e) => self.print_number(e.value, precedence),
 
             Expression::RegexpLiteral(r) => {
-                self.print(&r.value);
+                if self.options.ascii_only {
+                    // Only the unicode modes read `\u{...}` as a code point, the other
+                    // modes match code units so a surrogate pair is the same pattern.
+                    let flags = r.value.rsplit('/').next().unwrap_or_default();
+                    let unicode = flags.contains('u') || flags.contains('v');
+                    self.print(&escape_non_ascii(&r.value, unicode));
+                } else {
+                    self.print(&r.value);
+                }
             }
 
             Expression::This(_) => self.print("this"),
 
             Expression::Super(_) => self.print("super"),
 
-            Expression::Array(a) => {
-                self.print("[");
-                for (idx, element) in a.items.iter().enumerate() {
-                    let is_last_element = idx < a.items.len() - 1;
-                    match element {
-                        Some(item) => {
-                            match item {
-                                ArrayExpressionItem::Spread(s) => self.print_spread_element(s),
-                                ArrayExpressionItem::Expression(e) => {
-                                    self.print_expression(e, Precedence::Comma)
-                                }
-                            }
+            Expression::ImportMeta(_) => self.print("import.meta"),
 
-                            if is_last_element {
-                                self.print(",");
-                            }
-                        }
-                        None => {
-                            self.print(",");
-                        }
-                    }
-
-                    // Do not print spaces for the last element
-                    if is_last_element {
-                        self.print_space();
-                    }
+            Expression::Import(i) => {
+                self.print("import(");
+                if let Some(chunk_name) = &i.chunk_name {
+                    self.print("/* webpackChunkName: ");
+                    // Webpack reads the annotation as JavaScript.
+                    self.print_string_literal(&StringLiteral {
+                        value: chunk_name.clone(),
+                    });
+                    self.print(" */ ");
                 }
-                self.print("]");
+                self.print_expression(&i.source, Precedence::Comma);
+                self.print(")");
             }
 
+            Expression::Array(a) => self.with_in_allowed(|printer| printer.print_array(a)),
+
             Expression::Binary(e) => {
                 let operator_precedence = e.operator.precedence();
-                let wrap = precedence >= operator_precedence;
+                let wrap = precedence >= operator_precedence
+                    || (self.forbid_in && e.operator == BinaryExpressionOperator::In);
+                let forbid_in = self.forbid_in && !wrap;
+                let forbid_in = std::mem::replace(&mut self.forbid_in, forbid_in);
                 if wrap {
                     self.print("(");
                 }
 
-                let left_precedence = match e.operator.is_right_associative() {
-                    true => operator_precedence.clone(),
-                    false => operator_precedence.lower(),
+                let left_precedence = match e.operator {
+                    // A unary expression on the left of `**` must be wrapped.
+                    BinaryExpressionOperator::Exponentiation => Precedence::Prefix,
+                    _ if e.operator.is_right_associative() => operator_precedence.clone(),
+                    _ => operator_precedence.lower(),
                 };
                 let right_precedence = match e.operator.is_left_associative() {
                     true => operator_precedence.clone(),
@@ -673,6 +836,7 @@ impl Printer {
                 if wrap {
                     self.print(")");
                 }
+                self.forbid_in = forbid_in;
             }
 
             Expression::Unary(e) => {
@@ -690,7 +854,17 @@ impl Printer {
                     UnaryExpressionOperator::Typeof => self.print("typeof "),
                     UnaryExpressionOperator::Delete => self.print("delete "),
                 };
+                let argument_start = self.text.len();
                 self.print_expression(&e.argument, operator_precedence.lower());
+                // `- -a` and `+ ++a` would be read as update expressions without the space.
+                let sign = match e.operator {
+                    UnaryExpressionOperator::Positive => Some('+'),
+                    UnaryExpressionOperator::Negative => Some('-'),
+                    _ => None,
+                };
+                if sign.is_some() && self.text[argument_start..].chars().next() == sign {
+                    self.text.insert(argument_start, ' ');
+                }
                 if wrap {
                     self.print(")");
                 }
@@ -702,7 +876,14 @@ impl Printer {
                 if wrap {
                     self.print("(");
                 }
-                self.print_expression(&l.left, operator_precedence.lower());
+                // `??` can not be mixed with `||` and `&&` without parentheses.
+                let (left_precedence, right_precedence) = match l.operator {
+                    LogicalExpressionOperator::NullishCoalescing => {
+                        (Precedence::LogicalAnd, Precedence::LogicalAnd)
+                    }
+                    _ => (operator_precedence.lower(), operator_precedence.clone()),
+                };
+                self.print_expression(&l.left, left_precedence);
                 self.print_space();
                 match &l.operator {
                     LogicalExpressionOperator::Or => self.print("||"),
@@ -710,34 +891,27 @@ impl Printer {
                     LogicalExpressionOperator::NullishCoalescing => self.print("??"),
                 }
                 self.print_space();
-                self.print_expression(&l.right, operator_precedence.clone());
+                self.print_expression(&l.right, right_precedence);
                 if wrap {
                     self.print(")");
                 }
             }
 
-            Expression::StringLiteral(e) => {
-                self.print("\"");
-                self.print(&e.value);
-                self.print("\"");
-            }
+            Expression::StringLiteral(e) => self.print_string_literal(e),
 
             Expression::Call(c) => {
+                let wrap = c.pure && precedence >= Precedence::Postfix;
+                if wrap {
+                    self.print("(");
+                }
+                if c.pure {
+                    self.print_pure_comment();
+                }
                 self.print_expression(&c.callee, Precedence::Postfix);
-                self.print("(");
-
-                for (idx, argument) in c.arguments.iter().enumerate() {
-                    if idx != 0 {
-                        self.print(",");
-                        self.print_space();
-                    }
-
-                    match &argument {
-                        ArgumentKind::Expression(e) => self.print_expression(e, Precedence::Comma),
-                        ArgumentKind::Spread(s) => self.print_spread_element(s),
-                    }
+                self.with_in_allowed(|printer| printer.print_arguments(&c.arguments));
+                if wrap {
+                    self.print(")");
                 }
-                self.print(")");
             }
 
             Expression::Function(f) => {
@@ -745,6 +919,9 @@ impl Printer {
                 if wrap {
                     self.print("(");
                 }
+                if f.is_async {
+                    self.print("async ");
+                }
                 self.print("function");
                 if f.generator {
                     self.print("*");
@@ -770,7 +947,9 @@ impl Printer {
                 }
                 self.print_expression(&c.test, Precedence::Conditional);
                 self.print(" ? ");
-                self.print_expression(&c.consequence, Precedence::Yield);
+                self.with_in_allowed(|printer| {
+                    printer.print_expression(&c.consequence, Precedence::Yield)
+                });
                 self.print(" : ");
                 self.print_expression(&c.alternate, Precedence::Yield);
                 if wrap {
@@ -779,37 +958,74 @@ impl Printer {
             }
 
             Expression::New(n) => {
+                let wrap = n.pure && precedence >= Precedence::Postfix;
+                if wrap {
+                    self.print("(");
+                }
+                if n.pure {
+                    self.print_pure_comment();
+                }
                 self.print("new ");
-                self.print_expression(&n.callee, Precedence::New);
-                self.print("(");
-                for (idx, argument) in n.arguments.iter().enumerate() {
-                    if idx != 0 {
-                        self.print(",");
-                        self.print_space();
-                    }
-                    match &argument {
-                        ArgumentKind::Expression(e) => self.print_expression(e, Precedence::Comma),
-                        ArgumentKind::Spread(s) => self.print_spread_element(s),
-                    }
+                // The arguments would belong to a call in the callee, `new (a())()`.
+                if has_call(&n.callee) {
+                    self.print("(");
+                    self.with_in_allowed(|printer| {
+                        printer.print_expression(&n.callee, Precedence::Lowest)
+                    });
+                    self.print(")");
+                } else {
+                    self.print_expression(&n.callee, Precedence::New);
+                }
+                self.with_in_allowed(|printer| printer.print_arguments(&n.arguments));
+                if wrap {
+                    self.print(")");
                 }
-                self.print(")");
             }
 
             Expression::Member(m) => {
-                self.print_expression(&m.object, Precedence::Postfix);
+                match &*m.object {
+                    // `let[` starts a declaration.
+                    Expression::Identifier(i)
+                        if m.computed
+                            && i.name == "let"
+                            && self.text.len() == self.statement_start =>
+                    {
+                        self.print("(let)")
+                    }
+                    // The dot would be read as a decimal point, `1..a` ends the number first.
+                    Expression::NumericLiteral(n)
+                        if !m.computed
+                            && n.value.is_finite()
+                            && !n.value.is_sign_negative()
+                            && self
+                                .number_text(n.value)
+                                .bytes()
+                                .all(|b| b.is_ascii_digit()) =>
+                    {
+                        self.print_expression(&m.object, Precedence::Postfix);
+                        self.print(".");
+                    }
+                    object => self.print_expression(object, Precedence::Postfix),
+                }
                 if m.computed {
                     self.print("[");
+                    self.with_in_allowed(|printer| {
+                        printer.print_expression(&m.property, Precedence::Lowest)
+                    });
+                    self.print("]");
                 } else {
                     self.print(".");
-                }
-                self.print_expression(&m.property, Precedence::Lowest);
-                if m.computed {
-                    self.print("]");
+                    match m.property.as_ref() {
+                        Expression::Identifier(property) => self.print_identifier_name(property),
+                        property => self.print_expression(property, Precedence::Lowest),
+                    }
                 }
             }
 
             Expression::Object(o) => {
-                let wrap = self.text.len() == self.statement_start;
+                let wrap = self.text.len() == self.statement_start
+                    || self.text.len() == self.arrow_expression_start;
+                let forbid_in = std::mem::replace(&mut self.forbid_in, false);
                 if wrap {
                     self.print("(");
                 }
@@ -832,9 +1048,20 @@ impl Printer {
                 if wrap {
                     self.print(")");
                 }
+                self.forbid_in = forbid_in;
             }
 
             Expression::Assignment(a) => {
+                // An object pattern at the start of a statement would be read as a block.
+                let wrap = precedence >= Precedence::Assign
+                    || matches!(
+                        a.left,
+                        AssignmentExpressionLeft::Binding(Binding::Object(_))
+                    ) && (self.text.len() == self.statement_start
+                        || self.text.len() == self.arrow_expression_start);
+                if wrap {
+                    self.print("(");
+                }
                 match &a.left {
                     AssignmentExpressionLeft::Binding(b) => self.print_binding(b),
                     AssignmentExpressionLeft::Expression(e) => {
@@ -862,9 +1089,19 @@ impl Printer {
                 }
                 self.print_space();
                 self.print_expression(&a.right, Precedence::Assign.lower());
+                if wrap {
+                    self.print(")");
+                }
             }
 
             Expression::ArrowFunction(a) => {
+                let wrap = precedence >= Precedence::Assign;
+                if wrap {
+                    self.print("(");
+                }
+                if a.is_async {
+                    self.print("async ");
+                }
                 self.print("(");
                 self.print_parameters(&a.parameters);
                 self.print(")");
@@ -874,13 +1111,20 @@ impl Printer {
                 match &a.body {
                     ArrowFunctionExpressionBody::BlockStatement(b) => self.print_block_statement(b),
                     ArrowFunctionExpressionBody::Expression(e) => {
+                        self.arrow_expression_start = self.text.len();
                         self.print_expression(e, Precedence::Comma)
                     }
                 }
+                if wrap {
+                    self.print(")");
+                }
             }
 
             Expression::Sequence(s) => {
-                let wrap = precedence >= Precedence::Comma;
+                // A sequence of a single expression is how the parser keeps parentheses.
+                let wrap = precedence >= Precedence::Comma || s.expressions.len() == 1;
+                let forbid_in = self.forbid_in && !wrap;
+                let forbid_in = std::mem::replace(&mut self.forbid_in, forbid_in);
                 if wrap {
                     self.print("(");
                 }
@@ -890,7 +1134,38 @@ impl Printer {
                         self.print_space();
                     }
 
-                    self.print_expression(&expression, Precedence::Comma);
+                    self.print_expression(expression, Precedence::Comma);
+                }
+                if wrap {
+                    self.print(")");
+                }
+                self.forbid_in = forbid_in;
+            }
+
+            Expression::Await(a) => {
+                let wrap = precedence >= Precedence::Prefix;
+                if wrap {
+                    self.print("(");
+                }
+                self.print("await ");
+                self.print_expression(&a.argument, Precedence::Prefix.lower());
+                if wrap {
+                    self.print(")");
+                }
+            }
+
+            Expression::Yield(y) => {
+                let wrap = precedence >= Precedence::Assign;
+                if wrap {
+                    self.print("(");
+                }
+                self.print("yield");
+                if y.delegate {
+                    self.print("*");
+                }
+                if let Some(argument) = &y.argument {
+                    self.print(" ");
+                    self.print_expression(argument, Precedence::Comma);
                 }
                 if wrap {
                     self.print(")");
@@ -913,20 +1188,62 @@ impl Printer {
 
             Expression::TemplateLiteral(t) => {
                 self.print("`");
-                self.print(&t.head);
+                self.print_template_text(&t.head);
                 for part in &t.parts {
                     self.print("${");
-                    self.print_expression(&part.expression, Precedence::Comma);
+                    self.with_in_allowed(|printer| {
+                        printer.print_expression(&part.expression, Precedence::Comma)
+                    });
                     self.print("}");
-                    self.print(&part.text);
+                    self.print_template_text(&part.text);
                 }
                 self.print("`");
             }
         }
     }
 
-    fn print_class_body(&mut self, properties: &Vec<ClassPropertyKind>) {
-        if properties.len() == 0 {
+    fn print_array(&mut self, array: &ArrayExpression) {
+        self.print("[");
+        for (idx, element) in array.items.iter().enumerate() {
+            let is_last_element = idx < array.items.len() - 1;
+            match element {
+                Some(item) => {
+                    match item {
+                        ArrayExpressionItem::Spread(s) => self.print_spread_element(s),
+                        ArrayExpressionItem::Expression(e) => {
+                            self.print_expression(e, Precedence::Comma)
+                        }
+                    }
+
+                    if is_last_element {
+                        self.print(",");
+                    }
+                }
+                None => {
+                    self.print(",");
+                }
+            }
+
+            // Do not print spaces for the last element
+            if is_last_element {
+                self.print_space();
+            }
+        }
+        self.print("]");
+    }
+
+    /// The class being extended is a left-hand side expression, anything
+    /// with a lower precedence than a call is wrapped.
+    fn print_class_extends(&mut self, extends: Option<&Expression>) {
+        if let Some(super_class) = extends {
+            self.print("extends ");
+            self.print_expression(super_class, Precedence::Postfix);
+            self.print_space();
+        }
+    }
+
+    fn print_class_body(&mut self, properties: &[ClassPropertyKind]) {
+        if properties.is_empty() {
             self.print("{}");
             return;
         }
@@ -946,6 +1263,8 @@ impl Printer {
                     self.print_block_statement(&c.body);
                 }
                 ClassPropertyKind::Method(c) => {
+                    self.print_static(c.is_static);
+                    self.print_method_kind(c.is_async, c.generator);
                     self.print_literal_property_name(&c.identifier);
                     self.print("(");
                     self.print_parameters(&c.parameters);
@@ -954,6 +1273,8 @@ impl Printer {
                     self.print_block_statement(&c.body);
                 }
                 ClassPropertyKind::MethodComputed(c) => {
+                    self.print_static(c.is_static);
+                    self.print_method_kind(c.is_async, c.generator);
                     self.print_computed_property_name(&c.key);
                     self.print("(");
                     self.print_parameters(&c.parameters);
@@ -962,6 +1283,7 @@ impl Printer {
                     self.print_block_statement(&c.body);
                 }
                 ClassPropertyKind::MethodGet(c) => {
+                    self.print_static(c.is_static);
                     self.print("get ");
                     self.print_literal_property_name(&c.identifier);
                     self.print("(");
@@ -971,6 +1293,7 @@ impl Printer {
                     self.print_block_statement(&c.body);
                 }
                 ClassPropertyKind::MethodGetComputed(c) => {
+                    self.print_static(c.is_static);
                     self.print("get");
                     self.print_space();
                     self.print_computed_property_name(&c.key);
@@ -981,6 +1304,7 @@ impl Printer {
                     self.print_block_statement(&c.body);
                 }
                 ClassPropertyKind::MethodSet(c) => {
+                    self.print_static(c.is_static);
                     self.print("set ");
                     self.print_literal_property_name(&c.identifier);
                     self.print("(");
@@ -990,6 +1314,7 @@ impl Printer {
                     self.print_block_statement(&c.body);
                 }
                 ClassPropertyKind::MethodSetComputed(c) => {
+                    self.print_static(c.is_static);
                     self.print("set");
                     self.print_space();
                     self.print_computed_property_name(&c.key);
@@ -1006,7 +1331,23 @@ impl Printer {
         self.print("}");
     }
 
-    fn print_parameters(&mut self, parameters: &Vec<ParameterKind>) {
+    fn print_static(&mut self, is_static: bool) {
+        if is_static {
+            self.print("static ");
+        }
+    }
+
+    /// Prints the `async` and `*` markers of a method.
+    fn print_method_kind(&mut self, is_async: bool, generator: bool) {
+        if is_async {
+            self.print("async ");
+        }
+        if generator {
+            self.print("*");
+        }
+    }
+
+    fn print_parameters(&mut self, parameters: &[ParameterKind]) {
         for (idx, parameter) in parameters.iter().enumerate() {
             if idx != 0 {
                 self.print(",");
@@ -1034,6 +1375,21 @@ impl Printer {
         }
     }
 
+    fn print_arguments(&mut self, arguments: &[ArgumentKind]) {
+        self.print("(");
+        for (idx, argument) in arguments.iter().enumerate() {
+            if idx != 0 {
+                self.print(",");
+                self.print_space();
+            }
+            match &argument {
+                ArgumentKind::Expression(e) => self.print_expression(e, Precedence::Comma),
+                ArgumentKind::Spread(s) => self.print_spread_element(s),
+            }
+        }
+        self.print(")");
+    }
+
     fn print_spread_element(&mut self, spread_expression: &SpreadElement) {
         self.print("...");
         self.print_expression(&spread_expression.element, Precedence::Comma);
@@ -1058,6 +1414,7 @@ impl Printer {
                 self.print_expression(&p.value, Precedence::Comma);
             }
             ObjectExpressionPropertyKind::Method(m) => {
+                self.print_method_kind(m.is_async, m.generator);
                 self.print_literal_property_name(&m.key);
                 self.print("(");
                 self.print_parameters(&m.parameters);
@@ -1066,6 +1423,7 @@ impl Printer {
                 self.print_block_statement(&m.body);
             }
             ObjectExpressionPropertyKind::MethodComputed(m) => {
+                self.print_method_kind(m.is_async, m.generator);
                 self.print_computed_property_name(&m.key);
                 self.print("(");
                 self.print_parameters(&m.parameters);
@@ -1112,8 +1470,49 @@ impl Printer {
         }
     }
 
+    /// Prints an identifier that is a binding or a reference, reserved words can
+    /// only be printed as property names. An escaped reserved word such as
+    /// `l\u0065t` would otherwise be printed as the keyword.
     fn print_identifier(&mut self, id: &Identifier) {
-        self.print(&id.name);
+        if is_reserved_word(&id.name) {
+            let error = format!(
+                "Cannot use \"{}\" as an identifier, it is a reserved word",
+                id.name
+            );
+            if !self.errors.contains(&error) {
+                self.errors.push(error);
+            }
+        }
+        self.print_identifier_name(id);
+    }
+
+    /// Prints an identifier in a position where reserved words are allowed,
+    /// such as a property name or the exported name of an export specifier.
+    fn print_identifier_name(&mut self, id: &Identifier) {
+        if !self.options.ascii_only || id.name.is_ascii() {
+            self.print(&id.name);
+            return;
+        }
+
+        // Identifiers can contain the same escapes as strings, ñ is \u00F1. A surrogate
+        // pair is not a valid escape in an identifier, so the characters outside of
+        // the Basic Multilingual Plane can not be escaped in ES5.
+        if self.options.es5 && id.name.chars().any(|c| c > '\u{FFFF}') {
+            let error = format!(
+                "Cannot use \"{}\" as an identifier in ES5 with ASCII-only output, it contains characters that can not be escaped",
+                id.name
+            );
+            if !self.errors.contains(&error) {
+                self.errors.push(error);
+            }
+        }
+        for character in id.name.chars() {
+            if character.is_ascii() {
+                self.text.push(character);
+            } else {
+                push_unicode_escape(&mut self.text, character, true);
+            }
+        }
     }
 
     fn print_binding(&mut self, binding: &Binding) {
@@ -1125,7 +1524,7 @@ impl Printer {
     }
 
     fn print_object_binding(&mut self, object_binding: &ObjectBinding) {
-        if object_binding.properties.len() == 0 {
+        if object_binding.properties.is_empty() {
             self.print("{}");
             return;
         }
@@ -1184,7 +1583,7 @@ impl Printer {
     }
 
     fn print_array_binding(&mut self, array_binding: &ArrayBinding) {
-        if array_binding.items.len() == 0 {
+        if array_binding.items.is_empty() {
             self.print("[]");
         } else {
             self.print("[");
@@ -1210,7 +1609,8 @@ impl Printer {
                             self.print_binding(&r.binding);
                         }
                     }
-                } else {
+                } else if idx == array_binding.items.len() - 1 {
+                    // A hole at the end needs a comma of its own.
                     self.print(",");
                 }
             }
@@ -1218,14 +1618,83 @@ impl Printer {
         }
     }
 
+    /// Prints a number as a property name, a number that has no literal is
+    /// printed as the string it is converted to.
     fn print_numeric_literal(&mut self, numeric_literal: &NumericLiteral) {
-        self.print(&format!("{}", numeric_literal.value));
+        let value = numeric_literal.value;
+        if value.is_finite() && !value.is_sign_negative() {
+            let text = self.number_text(value);
+            self.print(&text);
+        } else {
+            let value = match value {
+                v if v.is_nan() => "NaN".to_string(),
+                v if v.is_infinite() => format!("{}Infinity", if v < 0.0 { "-" } else { "" }),
+                v => format!("-{}", number_to_string(-v)),
+            };
+            self.print_string_literal(&StringLiteral { value });
+        }
+    }
+
+    /// Prints a number, negative numbers are printed like a negation. `Infinity` and
+    /// `NaN` are printed as a division since the names could be shadowed.
+    fn print_number(&mut self, value: f64, precedence: Precedence) {
+        let wrap = match value.is_finite() {
+            true => value.is_sign_negative() && precedence >= Precedence::Prefix,
+            false => precedence >= Precedence::Product,
+        };
+        if wrap {
+            self.print("(");
+        }
+        if value.is_nan() {
+            self.print("0 / 0");
+        } else {
+            if value.is_sign_negative() {
+                self.print("-");
+            }
+            match value.is_infinite() {
+                true => self.print("1 / 0"),
+                false => {
+                    let text = self.number_text(value.abs());
+                    self.print(&text);
+                }
+            }
+        }
+        if wrap {
+            self.print(")");
+        }
+    }
+
+    /// Returns the text of a finite number that is not negative.
+    fn number_text(&self, value: f64) -> String {
+        match self.options.minify_syntax {
+            true => shortest_number(value),
+            false => number_to_string(value),
+        }
+    }
+
+    /// Prints inside of brackets, where the `in` operator is allowed again
+    /// in the head of a `for` loop.
+    fn with_in_allowed(&mut self, print: impl FnOnce(&mut Printer)) {
+        let forbid_in = std::mem::replace(&mut self.forbid_in, false);
+        print(self);
+        self.forbid_in = forbid_in;
     }
 
     fn print_newline(&mut self) {
         self.print("\n");
     }
 
+    /// Prints the annotation that marks the following call as pure. The
+    /// comment is not a part of the expression, so an expression statement
+    /// is considered to start after it.
+    fn print_pure_comment(&mut self) {
+        let at_statement_start = self.text.len() == self.statement_start;
+        self.print("/* @__PURE__ */ ");
+        if at_statement_start {
+            self.statement_start = self.text.len();
+        }
+    }
+
     fn print_semicolon_after_statement(&mut self) {
         self.print(";\n");
     }
@@ -1238,3 +1707,136 @@ impl Printer {
         self.text.push_str(text);
     }
 }
+
+/// Returns true if the expression calls a function before any arguments
+/// that follow it, which would otherwise belong to the call.
+fn has_call(expression: &Expression) -> bool {
+    match expression {
+        Expression::Call(_) | Expression::Import(_) => true,
+        Expression::Member(m) => has_call(&m.object),
+        _ => false,
+    }
+}
+
+/// Returns true if the statement ends with an if statement that has no else branch.
+fn ends_with_if_without_else(statement: &Statement) -> bool {
+    match statement {
+        Statement::IfStatement(i) => match &i.alternate {
+            Some(alternate) => ends_with_if_without_else(alternate),
+            None => true,
+        },
+        Statement::ForStatement(f) => ends_with_if_without_else(&f.body),
+        Statement::ForInStatement(f) => ends_with_if_without_else(&f.body),
+        Statement::ForOfStatement(f) => ends_with_if_without_else(&f.body),
+        Statement::WhileStatement(w) => ends_with_if_without_else(&w.body),
+        Statement::WithStatement(w) => ends_with_if_without_else(&w.body),
+        Statement::LabeledStatement(l) => ends_with_if_without_else(&l.body),
+        _ => false,
+    }
+}
+
+/// Returns true if the name is a reserved word in strict mode code, which
+/// includes modules, such as `if`, `let` and `await`.
+fn is_reserved_word(name: &str) -> bool {
+    matches!(
+        name,
+        "await"
+            | "break"
+            | "case"
+            | "catch"
+            | "class"
+            | "const"
+            | "continue"
+            | "debugger"
+            | "default"
+            | "delete"
+            | "do"
+            | "else"
+            | "enum"
+            | "export"
+            | "extends"
+            | "false"
+            | "finally"
+            | "for"
+            | "function"
+            | "if"
+            | "implements"
+            | "import"
+            | "in"
+            | "instanceof"
+            | "interface"
+            | "let"
+            | "new"
+            | "null"
+            | "package"
+            | "private"
+            | "protected"
+            | "public"
+            | "return"
+            | "static"
+            | "super"
+            | "switch"
+            | "this"
+            | "throw"
+            | "true"
+            | "try"
+            | "typeof"
+            | "var"
+            | "void"
+            | "while"
+            | "with"
+            | "yield"
+    )
+}
+
+/// Counts the occurrences of the character that are not escaped with a backslash.
+fn unescaped_count(text: &str, character: char) -> usize {
+    let mut count = 0;
+    let mut escaped = false;
+    for c in text.chars() {
+        if c == character && !escaped {
+            count += 1;
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    count
+}
+
+/// Escapes the characters outside of ASCII in the text of a literal, which may contain
+/// escapes already. Characters outside of the Basic Multilingual Plane are escaped as
+/// `\u{1F600}` when the literal allows it and as a surrogate pair otherwise.
+fn escape_non_ascii(text: &str, code_point_escapes: bool) -> String {
+    let mut escaped_text = String::with_capacity(text.len());
+    let mut escaped = false;
+    for c in text.chars() {
+        if c.is_ascii() {
+            escaped = c == '\\' && !escaped;
+            escaped_text.push(c);
+            continue;
+        }
+        if escaped {
+            // An escaped character without an escape sequence is the character itself,
+            // and an escaped line terminator is a line continuation, which is dropped.
+            escaped_text.pop();
+            escaped = false;
+            if c == '\u{2028}' || c == '\u{2029}' {
+                continue;
+            }
+        }
+        push_unicode_escape(&mut escaped_text, c, code_point_escapes);
+    }
+    escaped_text
+}
+
+/// Pushes the escape of the character, `\u00F1` for ñ.
+fn push_unicode_escape(text: &mut String, character: char, code_point_escapes: bool) {
+    match character as u32 {
+        code @ 0..=0xFFFF => text.push_str(&format!("\\u{:04X}", code)),
+        code if code_point_escapes => text.push_str(&format!("\\u{{{:X}}}", code)),
+        _ => {
+            for unit in character.encode_utf16(&mut [0; 2]) {
+                text.push_str(&format!("\\u{:04X}", unit));
+            }
+        }
+    }
+}
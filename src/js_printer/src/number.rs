@@ -0,0 +1,86 @@
+//! This file contains the printing of numbers, both the way `Number.prototype.toString`
+//! prints them and the shortest text that reads back as the same number.
+//!
+//! Both functions expect a finite number that is not negative, the sign and the
+//! values that have no literal are handled by the printer.
+
+/// Prints the number the same way as `Number.prototype.toString`.
+pub(crate) fn number_to_string(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let (digits, exponent) = decimal_digits(value);
+    let length = digits.len() as i32;
+    if length <= exponent && exponent <= 21 {
+        // 1000
+        format!("{}{}", digits, "0".repeat((exponent - length) as usize))
+    } else if 0 < exponent && exponent <= 21 {
+        // 1.5
+        let (integer, fraction) = digits.split_at(exponent as usize);
+        format!("{}.{}", integer, fraction)
+    } else if -6 < exponent && exponent <= 0 {
+        // 0.005
+        format!("0.{}{}", "0".repeat(-exponent as usize), digits)
+    } else {
+        // 1e+21, 1.5e-7
+        let (first, rest) = digits.split_at(1);
+        let fraction = match rest.is_empty() {
+            true => String::new(),
+            false => format!(".{}", rest),
+        };
+        let sign = if exponent > 0 { "+" } else { "-" };
+        format!("{}{}e{}{}", first, fraction, sign, (exponent - 1).abs())
+    }
+}
+
+/// Prints the number with as few characters as possible, `1e3` instead of `1000`,
+/// `.5` instead of `0.5` and `0xFFFFFF` instead of `16777215`. Decimal text is
+/// preferred when there is a tie.
+pub(crate) fn shortest_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let text = number_to_string(value);
+    let mut candidates = vec![match text.strip_prefix("0.") {
+        Some(fraction) => format!(".{}", fraction),
+        None => text.replace("e+", "e"),
+    }];
+
+    // The digits as an integer with an exponent, 15e4 or 15e-7.
+    let (digits, exponent) = decimal_digits(value);
+    let length = digits.len() as i32;
+    if exponent != length {
+        candidates.push(format!("{}e{}", digits, exponent - length));
+    }
+
+    // Only integers that are exactly represented, hexadecimal text of larger
+    // integers reads back as the same number but is never shorter.
+    if value.fract() == 0.0 && value <= 9007199254740991.0 {
+        candidates.push(format!("0x{:X}", value as u64));
+    }
+
+    candidates
+        .into_iter()
+        .fold(None, |shortest: Option<String>, candidate| match shortest {
+            Some(shortest) if shortest.len() <= candidate.len() => Some(shortest),
+            _ => Some(candidate),
+        })
+        .unwrap_or(text)
+}
+
+/// Returns the digits of the shortest decimal that reads back as the number,
+/// and the exponent `n` such that the number is `0.digits * 10^n`.
+fn decimal_digits(value: f64) -> (String, i32) {
+    // The exponent format prints the shortest digits, such as "1.5e-7".
+    let text = format!("{:e}", value);
+    let (mantissa, exponent) = text
+        .split_once('e')
+        .expect("The exponent format always has an exponent");
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent
+        .parse()
+        .expect("The exponent format prints an integer exponent");
+    (digits, exponent + 1)
+}
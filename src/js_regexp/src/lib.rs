@@ -0,0 +1,125 @@
+/// This crate validates regular expression literals, the lexers only find where
+/// a literal ends and leave its pattern and flags to be checked here.
+///
+/// Patterns are validated by the grammar of ES2022, including the `u` and `v` modes
+/// and the legacy syntax of Annex B that is allowed outside of them, such as `]`
+/// and `{` as plain characters and octal escapes. Errors have spans within the
+/// literal, where the leading slash is at 0.
+///
+/// The syntax used by a literal is returned as features, so that a literal which
+/// the target environment can not parse can be replaced by a `new RegExp` call.
+use js_error::{JSError, JSErrorKind};
+use span::Span;
+
+mod parser;
+mod property;
+
+use parser::Parser;
+
+/// Syntax of a regexp that is newer than ES5.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum RegexpFeature {
+    /// The `u` flag, from ES2015.
+    UnicodeFlag,
+    /// The `y` flag, from ES2015.
+    StickyFlag,
+    /// The `s` flag, from ES2018.
+    DotAllFlag,
+    /// `(?<name>a)` and `\k<name>`, from ES2018.
+    NamedGroups,
+    /// `(?<=a)` and `(?<!a)`, from ES2018.
+    Lookbehind,
+    /// `\p{Letter}` and `\P{Letter}`, from ES2018.
+    PropertyEscapes,
+    /// The `d` flag, from ES2022.
+    HasIndicesFlag,
+    /// The `v` flag, from ES2024.
+    UnicodeSetsFlag,
+}
+
+/// A regexp literal that has been validated.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Regexp<'a> {
+    /// The pattern between the slashes, as written.
+    pub pattern: &'a str,
+    pub flags: &'a str,
+    /// The features used by the literal, in the order they were found.
+    pub features: Vec<RegexpFeature>,
+}
+
+/// The flags of a regexp that change how its pattern is read.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct RegexpFlags {
+    pub(crate) unicode: bool,
+    pub(crate) unicode_sets: bool,
+}
+
+/// Validates a regexp literal, such as `/(?<year>\d{4})-\d\d/u`.
+pub fn parse_regexp(literal: &str) -> Result<Regexp<'_>, JSError> {
+    // The flags can not contain a slash, so the last one ends the pattern.
+    let end = match literal.rfind('/') {
+        Some(end) if end > 0 && literal.starts_with('/') => end,
+        _ => {
+            return Err(JSError::new(
+                JSErrorKind::UnterminatedRegexp,
+                Span::new(0, literal.len()),
+            ))
+        }
+    };
+
+    let pattern = &literal[1..end];
+    let flags = &literal[end + 1..];
+    let mut features = Vec::new();
+    let regexp_flags = parse_flags(flags, end + 1, &mut features)?;
+    let mut parser = Parser::new(pattern, 1, regexp_flags);
+    parser.parse()?;
+    for feature in parser.features {
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+
+    Ok(Regexp {
+        pattern,
+        flags,
+        features,
+    })
+}
+
+/// Checks that the flags are known, not repeated and not both `u` and `v`.
+fn parse_flags(
+    flags: &str,
+    offset: usize,
+    features: &mut Vec<RegexpFeature>,
+) -> Result<RegexpFlags, JSError> {
+    let mut regexp_flags = RegexpFlags::default();
+    for (index, flag) in flags.char_indices() {
+        let span = Span::new(offset + index, offset + index + flag.len_utf8());
+        if !matches!(flag, 'd' | 'g' | 'i' | 'm' | 's' | 'u' | 'v' | 'y') {
+            return Err(JSError::new(JSErrorKind::InvalidRegexpFlag, span));
+        }
+        if flags[..index].contains(flag) {
+            return Err(JSError::new(JSErrorKind::DuplicateRegexpFlag, span));
+        }
+
+        match flag {
+            'u' => regexp_flags.unicode = true,
+            'v' => regexp_flags.unicode_sets = true,
+            _ => {}
+        }
+        if regexp_flags.unicode && regexp_flags.unicode_sets {
+            return Err(JSError::new(JSErrorKind::IncompatibleRegexpFlags, span));
+        }
+
+        let feature = match flag {
+            'u' => RegexpFeature::UnicodeFlag,
+            'y' => RegexpFeature::StickyFlag,
+            's' => RegexpFeature::DotAllFlag,
+            'd' => RegexpFeature::HasIndicesFlag,
+            'v' => RegexpFeature::UnicodeSetsFlag,
+            _ => continue,
+        };
+        features.push(feature);
+    }
+    Ok(regexp_flags)
+}
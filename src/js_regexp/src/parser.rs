@@ -0,0 +1,962 @@
+/// This file contains the parser of regexp patterns. It follows the grammar of
+/// the specification closely, with the legacy syntax of Annex B outside of the
+/// unicode modes, and only checks the pattern, no tree is built.
+///
+/// A backreference such as `\2` can refer to a group that comes later, and the
+/// legacy meaning of `\k` depends on whether the pattern has named groups anywhere,
+/// so the groups are counted before the pattern is parsed. References to names
+/// are checked once the whole pattern has been read.
+use std::cmp::Ordering;
+
+use js_error::{JSError, JSErrorKind};
+use span::Span;
+use unicode::{id_continue, id_start};
+
+use crate::property::{is_lone_property, is_property_of_strings, is_property_value};
+use crate::{RegexpFeature, RegexpFlags};
+
+type RegexpResult<T> = Result<T, JSError>;
+
+/// What a term is, which decides whether it can be quantified.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum TermKind {
+    /// Characters, classes, groups and backreferences.
+    Atom,
+    /// `(?=a)` and `(?!a)`, which can only be quantified in the legacy syntax.
+    Lookahead,
+    /// `(?<=a)` and `(?<!a)`.
+    Lookbehind,
+    /// `^`, `$`, `\b` and `\B`.
+    Assertion,
+}
+
+/// A part of a class, either one character or an escape such
+/// as `\d` that stands for several characters.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ClassAtom {
+    Character(u32),
+    Class,
+}
+
+/// An operand of a class in the `v` mode, `a`, `\d`, `[a-z]` or `\q{abc}`.
+struct ClassSetOperand {
+    /// The character when the operand is one character, only those can form ranges.
+    character: Option<u32>,
+    may_contain_strings: bool,
+}
+
+pub(crate) struct Parser<'a> {
+    pattern: &'a str,
+    /// Where the pattern starts in the literal, the spans are relative to the literal.
+    offset: usize,
+    index: usize,
+    /// The `u` or the `v` mode, which leave out the legacy syntax.
+    unicode_mode: bool,
+    /// The `v` mode, which adds nested classes, set operations and strings to classes.
+    unicode_sets_mode: bool,
+    /// Whether the pattern has a named group, `\k` is then always a reference.
+    has_named_groups: bool,
+    /// The number of groups in the whole pattern.
+    group_count: usize,
+    group_names: Vec<String>,
+    /// The names referenced by `\k<name>`, with the start and end of the references.
+    named_references: Vec<(String, usize, usize)>,
+    /// Outside of the unicode modes a character outside of the Basic Multilingual
+    /// Plane is two characters, the second one is kept here while reading a class.
+    pending_low_surrogate: Option<u32>,
+    pub(crate) features: Vec<RegexpFeature>,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(pattern: &'a str, offset: usize, flags: RegexpFlags) -> Parser<'a> {
+        let (group_count, has_named_groups) = count_groups(pattern);
+        Parser {
+            pattern,
+            offset,
+            index: 0,
+            unicode_mode: flags.unicode || flags.unicode_sets,
+            unicode_sets_mode: flags.unicode_sets,
+            has_named_groups,
+            group_count,
+            group_names: Vec::new(),
+            named_references: Vec::new(),
+            pending_low_surrogate: None,
+            features: Vec::new(),
+        }
+    }
+
+    pub(crate) fn parse(&mut self) -> RegexpResult<()> {
+        self.parse_disjunction()?;
+        // Only a `)` ends the disjunction before the end of the pattern.
+        if self.index < self.pattern.len() {
+            return Err(self.error(
+                JSErrorKind::UnmatchedRegexpParenthesis,
+                self.index,
+                self.index + 1,
+            ));
+        }
+
+        for (name, start, end) in &self.named_references {
+            if !self.group_names.contains(name) {
+                return Err(self.error(JSErrorKind::UndefinedRegexpGroupName, *start, *end));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_disjunction(&mut self) -> RegexpResult<()> {
+        loop {
+            while !matches!(self.current(), None | Some('|') | Some(')')) {
+                self.parse_term()?;
+            }
+            if !self.eat('|') {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads an assertion or an atom, followed by a quantifier.
+    fn parse_term(&mut self) -> RegexpResult<()> {
+        let start = self.index;
+        let kind = match self.current() {
+            Some('^') | Some('$') => {
+                self.advance();
+                TermKind::Assertion
+            }
+            Some('\\') if matches!(self.peek(), Some('b') | Some('B')) => {
+                self.index += 2;
+                TermKind::Assertion
+            }
+            Some('(') => self.parse_group()?,
+            Some('*') | Some('+') | Some('?') => {
+                return Err(self.error(JSErrorKind::RegexpNothingToRepeat, start, start + 1));
+            }
+            Some('{') => {
+                // A braced quantifier is never a character, even in the legacy syntax.
+                if let Some((end, _)) = self.braced_quantifier() {
+                    return Err(self.error(JSErrorKind::RegexpNothingToRepeat, start, end));
+                }
+                if self.unicode_mode {
+                    return Err(self.error(
+                        JSErrorKind::LoneRegexpQuantifierBracket,
+                        start,
+                        start + 1,
+                    ));
+                }
+                self.advance();
+                TermKind::Atom
+            }
+            Some('}') | Some(']') if self.unicode_mode => {
+                return Err(self.error(JSErrorKind::LoneRegexpQuantifierBracket, start, start + 1));
+            }
+            Some('[') => {
+                self.parse_class()?;
+                TermKind::Atom
+            }
+            Some('\\') => {
+                self.parse_atom_escape()?;
+                TermKind::Atom
+            }
+            _ => {
+                self.advance();
+                TermKind::Atom
+            }
+        };
+        self.parse_quantifier(kind)
+    }
+
+    /// Reads the quantifier after a term, if there is one.
+    fn parse_quantifier(&mut self, kind: TermKind) -> RegexpResult<()> {
+        let start = self.index;
+        let mut in_order = true;
+        match self.current() {
+            Some('*') | Some('+') | Some('?') => self.advance(),
+            Some('{') => match self.braced_quantifier() {
+                Some((end, bounds_in_order)) => {
+                    self.index = end;
+                    in_order = bounds_in_order;
+                }
+                None if self.unicode_mode && kind == TermKind::Atom => {
+                    return Err(self.error(
+                        JSErrorKind::IncompleteRegexpQuantifier,
+                        start,
+                        start + 1,
+                    ));
+                }
+                // The `{` is read as the next term, which is a character in the legacy syntax.
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        }
+        self.eat('?');
+
+        let kind = match kind {
+            TermKind::Assertion => JSErrorKind::RegexpNothingToRepeat,
+            TermKind::Lookbehind => JSErrorKind::InvalidRegexpQuantifier,
+            TermKind::Lookahead if self.unicode_mode => JSErrorKind::InvalidRegexpQuantifier,
+            _ if !in_order => JSErrorKind::RegexpQuantifierOutOfOrder,
+            _ => return Ok(()),
+        };
+        Err(self.error(kind, start, self.index))
+    }
+
+    /// Finds a quantifier such as `{2}`, `{2,}` or `{2,5}` at the current position, without
+    /// reading it. Returns where it ends and whether its bounds are in order.
+    fn braced_quantifier(&self) -> Option<(usize, bool)> {
+        let rest = self.rest().strip_prefix('{')?;
+        let min_length = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if min_length == 0 {
+            return None;
+        }
+
+        let (min, mut rest) = rest.split_at(min_length);
+        let mut in_order = true;
+        if let Some(after_comma) = rest.strip_prefix(',') {
+            let max_length = after_comma.bytes().take_while(u8::is_ascii_digit).count();
+            let (max, after_max) = after_comma.split_at(max_length);
+            if max_length > 0 {
+                in_order = compare_decimals(min, max) != Ordering::Greater;
+            }
+            rest = after_max;
+        }
+
+        let rest = rest.strip_prefix('}')?;
+        Some((self.pattern.len() - rest.len(), in_order))
+    }
+
+    /// Reads a group or a lookaround, such as `(a)`, `(?:a)`, `(?<name>a)` or `(?<=a)`.
+    fn parse_group(&mut self) -> RegexpResult<TermKind> {
+        let start = self.index;
+        let rest = self.rest();
+        let kind = if rest.starts_with("(?:") {
+            self.index += 3;
+            TermKind::Atom
+        } else if rest.starts_with("(?=") || rest.starts_with("(?!") {
+            self.index += 3;
+            TermKind::Lookahead
+        } else if rest.starts_with("(?<=") || rest.starts_with("(?<!") {
+            self.index += 4;
+            self.add_feature(RegexpFeature::Lookbehind);
+            TermKind::Lookbehind
+        } else if rest.starts_with("(?<") {
+            self.index += 3;
+            let name = self.parse_group_name(start)?;
+            if self.group_names.contains(&name) {
+                return Err(self.error(JSErrorKind::DuplicateRegexpGroupName, start, self.index));
+            }
+            self.group_names.push(name);
+            self.add_feature(RegexpFeature::NamedGroups);
+            TermKind::Atom
+        } else if rest.starts_with("(?") {
+            return Err(self.error(JSErrorKind::InvalidRegexpGroup, start, start + 2));
+        } else {
+            self.index += 1;
+            TermKind::Atom
+        };
+
+        let opening_end = self.index;
+        self.parse_disjunction()?;
+        if !self.eat(')') {
+            return Err(self.error(JSErrorKind::UnterminatedRegexpGroup, start, opening_end));
+        }
+        Ok(kind)
+    }
+
+    /// Reads the name of a group after the `<`, up to and including the `>`. Names
+    /// are identifiers, which can contain escapes such as `\u0061` and `\u{61}`.
+    fn parse_group_name(&mut self, start: usize) -> RegexpResult<String> {
+        let mut name = String::new();
+        loop {
+            let character = match self.current() {
+                Some('>') if !name.is_empty() => break,
+                Some('\\') => {
+                    self.advance();
+                    match self.eat('u') {
+                        true => self.parse_unicode_escape(true).and_then(char::from_u32),
+                        false => None,
+                    }
+                }
+                Some(c) => {
+                    self.advance();
+                    Some(c)
+                }
+                None => None,
+            };
+
+            match character {
+                Some(c) if name.is_empty() && is_group_name_start(c) => name.push(c),
+                Some(c) if !name.is_empty() && is_group_name_continue(c) => name.push(c),
+                _ => {
+                    return Err(self.error(JSErrorKind::InvalidRegexpGroupName, start, self.index))
+                }
+            }
+        }
+        self.advance();
+        Ok(name)
+    }
+
+    /// Reads an escape outside of a class, such as `\d`, `\1` or `\k<name>`.
+    fn parse_atom_escape(&mut self) -> RegexpResult<()> {
+        let start = self.index;
+        self.advance();
+        match self.current() {
+            Some('1'..='9') => {
+                let length = self.rest().bytes().take_while(u8::is_ascii_digit).count();
+                let group = self.rest()[..length].parse().unwrap_or(usize::MAX);
+                self.index += length;
+                // The legacy syntax reads a reference to a group that does not exist as
+                // an octal escape, or as the digits when they are not octal.
+                if self.unicode_mode && group > self.group_count {
+                    return Err(self.error(JSErrorKind::InvalidRegexpEscape, start, self.index));
+                }
+                Ok(())
+            }
+            Some('k') if self.unicode_mode || self.has_named_groups => {
+                self.advance();
+                let name = match self.eat('<') {
+                    true => self.parse_group_name(start).ok(),
+                    false => None,
+                };
+                match name {
+                    Some(name) => {
+                        self.named_references.push((name, start, self.index));
+                        Ok(())
+                    }
+                    None => {
+                        Err(self.error(JSErrorKind::InvalidRegexpNamedReference, start, self.index))
+                    }
+                }
+            }
+            _ => self.parse_character_escape(start, false).map(|_| ()),
+        }
+    }
+
+    /// Reads the escape after a `\`, other than a backreference, that starts at `start`.
+    fn parse_character_escape(&mut self, start: usize, in_class: bool) -> RegexpResult<ClassAtom> {
+        let c = match self.current() {
+            Some(c) => c,
+            None => return Err(self.error(JSErrorKind::InvalidRegexpEscape, start, self.index)),
+        };
+        self.advance();
+
+        let value = match c {
+            'd' | 'D' | 's' | 'S' | 'w' | 'W' => return Ok(ClassAtom::Class),
+            'p' | 'P' if self.unicode_mode => {
+                self.parse_property_escape(start, c == 'P')?;
+                return Ok(ClassAtom::Class);
+            }
+            'f' => 0x0C,
+            'n' => 0x0A,
+            'r' => 0x0D,
+            't' => 0x09,
+            'v' => 0x0B,
+            'b' if in_class => 0x08,
+            'c' => match self.current() {
+                Some(letter) if letter.is_ascii_alphabetic() => {
+                    self.advance();
+                    letter as u32 % 32
+                }
+                // The legacy syntax also allows digits and `_` in classes.
+                Some(letter)
+                    if in_class
+                        && !self.unicode_mode
+                        && (letter.is_ascii_digit() || letter == '_') =>
+                {
+                    self.advance();
+                    letter as u32 % 32
+                }
+                _ if self.unicode_mode => {
+                    return Err(self.error(JSErrorKind::InvalidRegexpEscape, start, self.index));
+                }
+                // Otherwise the backslash is a character, and the `c` is read after it.
+                _ => {
+                    self.index -= 1;
+                    '\\' as u32
+                }
+            },
+            '0' if !self.current().is_some_and(|c| c.is_ascii_digit()) => 0,
+            '0'..='9' if self.unicode_mode => {
+                let kind = match in_class {
+                    true => JSErrorKind::InvalidRegexpClassEscape,
+                    false => JSErrorKind::InvalidRegexpDecimalEscape,
+                };
+                return Err(self.error(kind, start, self.index));
+            }
+            // Legacy octal escapes such as `\12` are at most `\377`.
+            '0'..='7' => {
+                let max_length = if c <= '3' { 2 } else { 1 };
+                let length = self
+                    .rest()
+                    .bytes()
+                    .take(max_length)
+                    .take_while(|digit| (b'0'..=b'7').contains(digit))
+                    .count();
+                let digits = &self.pattern[self.index - 1..self.index + length];
+                self.index += length;
+                u32::from_str_radix(digits, 8).unwrap_or_default()
+            }
+            'x' => match self.rest().get(..2).filter(|digits| is_hex_digits(digits)) {
+                Some(digits) => {
+                    let value = u32::from_str_radix(digits, 16).unwrap_or_default();
+                    self.index += 2;
+                    value
+                }
+                None if self.unicode_mode => {
+                    return Err(self.error(JSErrorKind::InvalidRegexpEscape, start, self.index));
+                }
+                None => 'x' as u32,
+            },
+            'u' => match self.parse_unicode_escape(self.unicode_mode) {
+                Some(value) => value,
+                None if self.unicode_mode => {
+                    return Err(self.error(
+                        JSErrorKind::InvalidRegexpUnicodeEscape,
+                        start,
+                        self.index,
+                    ));
+                }
+                None => 'u' as u32,
+            },
+            // `\k` is a reference once there are named groups, which classes can not contain.
+            'k' if self.has_named_groups => {
+                return Err(self.error(JSErrorKind::InvalidRegexpEscape, start, self.index));
+            }
+            c if self.unicode_mode => {
+                if !(is_syntax_character(c) || c == '/' || (in_class && c == '-')) {
+                    return Err(self.error(JSErrorKind::InvalidRegexpEscape, start, self.index));
+                }
+                c as u32
+            }
+            c => c as u32,
+        };
+        Ok(ClassAtom::Character(value))
+    }
+
+    /// Reads the escape after `\u`, `0061`, or `\u{61}` in the unicode mode. The unicode
+    /// mode also reads a surrogate pair of escapes as one character. Nothing is read
+    /// when the escape is invalid.
+    fn parse_unicode_escape(&mut self, unicode_mode: bool) -> Option<u32> {
+        let rest = self.rest();
+        if unicode_mode && rest.starts_with('{') {
+            let (digits, after_digits) =
+                rest[1..].split_at(rest[1..].bytes().take_while(u8::is_ascii_hexdigit).count());
+            if digits.is_empty() || !after_digits.starts_with('}') {
+                return None;
+            }
+            let significant = digits.trim_start_matches('0');
+            let value = match significant.len() {
+                0 => 0,
+                1..=6 => u32::from_str_radix(significant, 16).ok()?,
+                _ => return None,
+            };
+            if value > 0x10FFFF {
+                return None;
+            }
+            self.index += digits.len() + 2;
+            return Some(value);
+        }
+
+        let value = hex4(rest)?;
+        self.index += 4;
+        if unicode_mode && (0xD800..0xDC00).contains(&value) {
+            let low = self.rest().strip_prefix("\\u").and_then(hex4);
+            if let Some(low @ 0xDC00..=0xDFFF) = low {
+                self.index += 6;
+                return Some(0x10000 + ((value - 0xD800) << 10) + (low - 0xDC00));
+            }
+        }
+        Some(value)
+    }
+
+    /// Reads the property of `\p{Letter}` or `\p{Script=Greek}` after the `p`. Returns
+    /// whether it is a property of strings, which are only allowed in the `v` mode.
+    fn parse_property_escape(&mut self, start: usize, negated: bool) -> RegexpResult<bool> {
+        let body = self
+            .rest()
+            .strip_prefix('{')
+            .and_then(|rest| rest.split_once('}'))
+            .map(|(body, _)| body);
+        let body = match body {
+            Some(body) => body,
+            None => {
+                return Err(self.error(
+                    JSErrorKind::InvalidRegexpPropertyName,
+                    start,
+                    self.pattern.len(),
+                ))
+            }
+        };
+        self.index += body.len() + 2;
+
+        let is_property_of_strings = match body.split_once('=') {
+            Some((name, value)) if is_property_value(name, value) => false,
+            None if is_lone_property(body) => false,
+            None if self.unicode_sets_mode && !negated && is_property_of_strings(body) => true,
+            _ => return Err(self.error(JSErrorKind::InvalidRegexpPropertyName, start, self.index)),
+        };
+        self.add_feature(RegexpFeature::PropertyEscapes);
+        Ok(is_property_of_strings)
+    }
+
+    /// Reads a class such as `[a-z\d]`.
+    fn parse_class(&mut self) -> RegexpResult<()> {
+        let start = self.index;
+        self.advance();
+        if self.unicode_sets_mode {
+            return self.parse_class_set(start).map(|_| ());
+        }
+
+        self.eat('^');
+        loop {
+            if self.pending_low_surrogate.is_none() {
+                match self.current() {
+                    Some(']') => {
+                        self.advance();
+                        return Ok(());
+                    }
+                    Some(_) => {}
+                    None => {
+                        return Err(self.error(
+                            JSErrorKind::UnterminatedRegexpCharacterClass,
+                            start,
+                            self.index,
+                        ))
+                    }
+                }
+            }
+
+            // The second half of a split character starts where the character does,
+            // which is four bytes back as it is outside of the Basic Multilingual Plane.
+            let atom_start = match self.pending_low_surrogate {
+                Some(_) => self.index - 4,
+                None => self.index,
+            };
+            let from = self.parse_class_atom()?;
+            // A `-` at the end of the class is a character.
+            if self.pending_low_surrogate.is_some()
+                || self.current() != Some('-')
+                || matches!(self.peek(), Some(']') | None)
+            {
+                continue;
+            }
+
+            self.advance();
+            let to = self.parse_class_atom()?;
+            match (from, to) {
+                (ClassAtom::Character(from), ClassAtom::Character(to)) if from > to => {
+                    return Err(self.error(
+                        JSErrorKind::RegexpRangeOutOfOrder,
+                        atom_start,
+                        self.index,
+                    ));
+                }
+                (ClassAtom::Character(_), ClassAtom::Character(_)) => {}
+                // The legacy syntax reads `[\d-a]` as the three parts.
+                _ if self.unicode_mode => {
+                    return Err(self.error(
+                        JSErrorKind::InvalidRegexpCharacterClass,
+                        atom_start,
+                        self.index,
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a character or an escape in a class.
+    fn parse_class_atom(&mut self) -> RegexpResult<ClassAtom> {
+        if let Some(low) = self.pending_low_surrogate.take() {
+            return Ok(ClassAtom::Character(low));
+        }
+
+        let start = self.index;
+        let atom = match self.current() {
+            Some('\\') => {
+                self.advance();
+                self.parse_character_escape(start, true)?
+            }
+            Some(c) => {
+                self.advance();
+                ClassAtom::Character(c as u32)
+            }
+            None => {
+                return Err(self.error(JSErrorKind::UnterminatedRegexpCharacterClass, start, start))
+            }
+        };
+
+        match atom {
+            ClassAtom::Character(value) if value > 0xFFFF && !self.unicode_mode => {
+                let value = value - 0x10000;
+                self.pending_low_surrogate = Some(0xDC00 + (value & 0x3FF));
+                Ok(ClassAtom::Character(0xD800 + (value >> 10)))
+            }
+            atom => Ok(atom),
+        }
+    }
+
+    /// Reads a class in the `v` mode after the `[` at `start`. Classes can then be nested
+    /// and combined with `&&` and `--`. Returns whether the class may contain strings.
+    fn parse_class_set(&mut self, start: usize) -> RegexpResult<bool> {
+        let negated = self.eat('^');
+        let may_contain_strings = self.parse_class_contents(start)?;
+        if negated && may_contain_strings {
+            return Err(self.error(
+                JSErrorKind::NegatedRegexpClassWithStrings,
+                start,
+                self.index,
+            ));
+        }
+        Ok(may_contain_strings)
+    }
+
+    /// Reads the union, intersection or subtraction in a class up to and including the `]`.
+    fn parse_class_contents(&mut self, start: usize) -> RegexpResult<bool> {
+        if self.eat(']') {
+            return Ok(false);
+        }
+
+        let first_start = self.index;
+        let first = self.parse_class_set_operand(start)?;
+        let operator = ["&&", "--"]
+            .iter()
+            .copied()
+            .find(|operator| self.rest().starts_with(operator));
+        let may_contain_strings = match operator {
+            Some(operator) => {
+                // An intersection may contain strings when all of its operands may,
+                // and a subtraction when its first operand may.
+                let mut may_contain_strings = first.may_contain_strings;
+                while self.eat_str(operator) {
+                    if self.current() == Some('&') {
+                        return Err(self.error(
+                            JSErrorKind::InvalidRegexpClassCharacter,
+                            self.index,
+                            self.index + 1,
+                        ));
+                    }
+                    let operand = self.parse_class_set_operand(start)?;
+                    if operator == "&&" {
+                        may_contain_strings &= operand.may_contain_strings;
+                    }
+                }
+                // The operators can not be mixed, and ranges are not operands.
+                if !matches!(self.current(), Some(']') | None) {
+                    return Err(self.error(
+                        JSErrorKind::InvalidRegexpSetOperation,
+                        self.index,
+                        self.index + 1,
+                    ));
+                }
+                may_contain_strings
+            }
+            None => {
+                let mut may_contain_strings = false;
+                let mut operand = first;
+                let mut operand_start = first_start;
+                loop {
+                    if let Some(from) = operand.character {
+                        if self.current() == Some('-') && !self.rest().starts_with("--") {
+                            self.advance();
+                            match self.parse_class_set_operand(start)?.character {
+                                Some(to) if from <= to => {}
+                                Some(_) => {
+                                    return Err(self.error(
+                                        JSErrorKind::RegexpRangeOutOfOrder,
+                                        operand_start,
+                                        self.index,
+                                    ))
+                                }
+                                None => {
+                                    return Err(self.error(
+                                        JSErrorKind::InvalidRegexpCharacterClass,
+                                        operand_start,
+                                        self.index,
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                    may_contain_strings |= operand.may_contain_strings;
+
+                    if matches!(self.current(), Some(']') | None) {
+                        break;
+                    }
+                    if self.rest().starts_with("&&") || self.rest().starts_with("--") {
+                        return Err(self.error(
+                            JSErrorKind::InvalidRegexpSetOperation,
+                            self.index,
+                            self.index + 2,
+                        ));
+                    }
+                    operand_start = self.index;
+                    operand = self.parse_class_set_operand(start)?;
+                }
+                may_contain_strings
+            }
+        };
+
+        if !self.eat(']') {
+            return Err(self.error(
+                JSErrorKind::UnterminatedRegexpCharacterClass,
+                start,
+                self.index,
+            ));
+        }
+        Ok(may_contain_strings)
+    }
+
+    /// Reads an operand of a class in the `v` mode, which is in the class starting at `start`.
+    fn parse_class_set_operand(&mut self, start: usize) -> RegexpResult<ClassSetOperand> {
+        let operand_start = self.index;
+        let c = match self.current() {
+            Some(c) => c,
+            None => {
+                return Err(self.error(
+                    JSErrorKind::UnterminatedRegexpCharacterClass,
+                    start,
+                    self.index,
+                ))
+            }
+        };
+        if is_reserved_double_punctuator(self.rest()) {
+            return Err(self.error(
+                JSErrorKind::InvalidRegexpSetOperation,
+                operand_start,
+                operand_start + 2,
+            ));
+        }
+
+        self.advance();
+        let (character, may_contain_strings) = match c {
+            '[' => (None, self.parse_class_set(operand_start)?),
+            '\\' => match self.current() {
+                Some('q') if self.peek() == Some('{') => {
+                    self.index += 2;
+                    (None, self.parse_class_strings(operand_start)?)
+                }
+                Some(negation @ ('p' | 'P')) => {
+                    self.advance();
+                    let strings = self.parse_property_escape(operand_start, negation == 'P')?;
+                    (None, strings)
+                }
+                Some(c) if is_class_set_reserved_punctuator(c) => {
+                    self.advance();
+                    (Some(c as u32), false)
+                }
+                _ => match self.parse_character_escape(operand_start, true)? {
+                    ClassAtom::Character(value) => (Some(value), false),
+                    ClassAtom::Class => (None, false),
+                },
+            },
+            c if is_class_set_syntax_character(c) => {
+                return Err(self.error(
+                    JSErrorKind::InvalidRegexpClassCharacter,
+                    operand_start,
+                    self.index,
+                ))
+            }
+            c => (Some(c as u32), false),
+        };
+        Ok(ClassSetOperand {
+            character,
+            may_contain_strings,
+        })
+    }
+
+    /// Reads the strings of `\q{abc|def}` after the `{`, that starts at `start`. Returns
+    /// whether one of the strings is not a single character.
+    fn parse_class_strings(&mut self, start: usize) -> RegexpResult<bool> {
+        let mut may_contain_strings = false;
+        let mut length = 0;
+        loop {
+            let character_start = self.index;
+            match self.current() {
+                Some('}') => {
+                    self.advance();
+                    return Ok(may_contain_strings || length != 1);
+                }
+                Some('|') => {
+                    self.advance();
+                    may_contain_strings |= length != 1;
+                    length = 0;
+                    continue;
+                }
+                Some('\\') => {
+                    self.advance();
+                    match self.current() {
+                        Some(c) if is_class_set_reserved_punctuator(c) => self.advance(),
+                        _ => {
+                            let atom = self.parse_character_escape(character_start, true)?;
+                            if atom == ClassAtom::Class {
+                                return Err(self.error(
+                                    JSErrorKind::InvalidRegexpEscape,
+                                    character_start,
+                                    self.index,
+                                ));
+                            }
+                        }
+                    }
+                }
+                Some(_) if is_reserved_double_punctuator(self.rest()) => {
+                    return Err(self.error(
+                        JSErrorKind::InvalidRegexpSetOperation,
+                        character_start,
+                        character_start + 2,
+                    ));
+                }
+                Some(c) if is_class_set_syntax_character(c) => {
+                    return Err(self.error(
+                        JSErrorKind::InvalidRegexpClassCharacter,
+                        character_start,
+                        character_start + 1,
+                    ));
+                }
+                Some(_) => self.advance(),
+                None => {
+                    return Err(self.error(
+                        JSErrorKind::UnterminatedRegexpCharacterClass,
+                        start,
+                        self.index,
+                    ))
+                }
+            }
+            length += 1;
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.pattern[self.index..]
+    }
+
+    fn current(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().nth(1)
+    }
+
+    fn advance(&mut self) {
+        if let Some(c) = self.current() {
+            self.index += c.len_utf8();
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.current() == Some(c) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    fn eat_str(&mut self, text: &str) -> bool {
+        if self.rest().starts_with(text) {
+            self.index += text.len();
+            return true;
+        }
+        false
+    }
+
+    fn add_feature(&mut self, feature: RegexpFeature) {
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+    }
+
+    /// Creates an error for the part of the pattern from `start` to `end`.
+    fn error(&self, kind: JSErrorKind, start: usize, end: usize) -> JSError {
+        JSError::new(kind, Span::new(self.offset + start, self.offset + end))
+    }
+}
+
+/// Counts the groups of the pattern and finds whether any of them is named. The
+/// pattern may be invalid, that is found by the parser.
+fn count_groups(pattern: &str) -> (usize, bool) {
+    let bytes = pattern.as_bytes();
+    let mut count = 0;
+    let mut has_named_groups = false;
+    let mut in_class = false;
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'(' if !in_class => {
+                let rest = &pattern[index..];
+                if !rest.starts_with("(?") {
+                    count += 1;
+                } else if rest.starts_with("(?<")
+                    && !rest.starts_with("(?<=")
+                    && !rest.starts_with("(?<!")
+                {
+                    count += 1;
+                    has_named_groups = true;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    (count, has_named_groups)
+}
+
+/// Compares two decimal numbers of any length.
+fn compare_decimals(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn hex4(text: &str) -> Option<u32> {
+    let digits = text.get(..4).filter(|digits| is_hex_digits(digits))?;
+    u32::from_str_radix(digits, 16).ok()
+}
+
+fn is_hex_digits(text: &str) -> bool {
+    text.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+fn is_group_name_start(c: char) -> bool {
+    c == '$' || c == '_' || id_start(c)
+}
+
+fn is_group_name_continue(c: char) -> bool {
+    c == '$' || c == '\u{200C}' || c == '\u{200D}' || id_continue(c)
+}
+
+fn is_syntax_character(c: char) -> bool {
+    matches!(
+        c,
+        '^' | '$' | '\\' | '.' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|'
+    )
+}
+
+/// The characters that have to be escaped in a class in the `v` mode.
+fn is_class_set_syntax_character(c: char) -> bool {
+    matches!(
+        c,
+        '(' | ')' | '[' | ']' | '{' | '}' | '/' | '-' | '\\' | '|'
+    )
+}
+
+/// The characters that can be escaped in a class in the `v` mode.
+fn is_class_set_reserved_punctuator(c: char) -> bool {
+    matches!(
+        c,
+        '&' | '-' | '!' | '#' | '%' | ',' | ':' | ';' | '<' | '=' | '>' | '@' | '`' | '~'
+    )
+}
+
+/// Doubled punctuators such as `!!` are reserved in classes in the `v` mode.
+fn is_reserved_double_punctuator(text: &str) -> bool {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(first), Some(second)) => first == second && "&!#$%*+,.:;<=>?@^`~".contains(first),
+        _ => false,
+    }
+}
@@ -0,0 +1,283 @@
+use js_error::JSErrorKind;
+use js_regexp::{parse_regexp, RegexpFeature};
+
+fn expect_valid(literals: &[&str]) {
+    for literal in literals {
+        if let Err(error) = parse_regexp(literal) {
+            panic!("Expected {} to be valid, found {:?}", literal, error);
+        }
+    }
+}
+
+/// Asserts that the literal is reported with the error, the expected span is
+/// the part of the literal that the error covers.
+fn expect_error(literal: &str, kind: JSErrorKind, expected_span: &str) {
+    let error = parse_regexp(literal).expect_err(literal);
+    assert_eq!(error.kind, kind, "{}", literal);
+    assert_eq!(
+        &literal[error.span.start..error.span.end],
+        expected_span,
+        "{}",
+        literal
+    );
+}
+
+fn expect_features(literal: &str, expected: Vec<RegexpFeature>) {
+    assert_eq!(parse_regexp(literal).unwrap().features, expected);
+}
+
+#[test]
+fn test_flags() {
+    expect_valid(&["/a/", "/a/dgimsuy", "/a/dgimsvy"]);
+    expect_error("/a/gx", JSErrorKind::InvalidRegexpFlag, "x");
+    expect_error("/a/gig", JSErrorKind::DuplicateRegexpFlag, "g");
+    expect_error("/a/uv", JSErrorKind::IncompatibleRegexpFlags, "v");
+
+    let regexp = parse_regexp("/a[/]b/gi").unwrap();
+    assert_eq!(regexp.pattern, "a[/]b");
+    assert_eq!(regexp.flags, "gi");
+}
+
+#[test]
+fn test_quantifiers() {
+    expect_valid(&[
+        "/a*b+c?/",
+        "/a*?b+?c??/",
+        "/a{2}b{2,}c{2,5}d{2,5}?/",
+        "/a{99999999999999999999}/",
+        // The legacy syntax reads braces that are not a quantifier as characters.
+        "/a{/",
+        "/a{,5}/",
+        "/}/",
+        "/]/",
+        // Lookaheads can be quantified in the legacy syntax.
+        "/(?=a)*/",
+    ]);
+    expect_error("/*a/", JSErrorKind::RegexpNothingToRepeat, "*");
+    expect_error("/a**/", JSErrorKind::RegexpNothingToRepeat, "*");
+    expect_error("/a|+/", JSErrorKind::RegexpNothingToRepeat, "+");
+    expect_error("/{1}/", JSErrorKind::RegexpNothingToRepeat, "{1}");
+    expect_error("/^*/", JSErrorKind::RegexpNothingToRepeat, "*");
+    expect_error("/\\b{2}/", JSErrorKind::RegexpNothingToRepeat, "{2}");
+    expect_error("/a{5,2}/", JSErrorKind::RegexpQuantifierOutOfOrder, "{5,2}");
+    expect_error(
+        "/a{100000000000000000001,100000000000000000000}/",
+        JSErrorKind::RegexpQuantifierOutOfOrder,
+        "{100000000000000000001,100000000000000000000}",
+    );
+    expect_error("/(?<=a)+/", JSErrorKind::InvalidRegexpQuantifier, "+");
+    expect_error("/(?=a)+/u", JSErrorKind::InvalidRegexpQuantifier, "+");
+    expect_error("/a{/u", JSErrorKind::IncompleteRegexpQuantifier, "{");
+    expect_error("/{/u", JSErrorKind::LoneRegexpQuantifierBracket, "{");
+    expect_error("/a}/u", JSErrorKind::LoneRegexpQuantifierBracket, "}");
+    expect_error("/a]/v", JSErrorKind::LoneRegexpQuantifierBracket, "]");
+}
+
+#[test]
+fn test_groups() {
+    expect_valid(&[
+        "/(a)(?:b)(?=c)(?!d)(?<=e)(?<!f)/",
+        "/(?<year>\\d{4})-(?<month>\\d{2})/",
+        "/(?<$_a1>a)/",
+        "/(?<ñ>a)/",
+        "/(?<\\u0061>a)\\k<a>/",
+        "/(?<\\u{61}>a)/",
+        "/(?<\\ud835\\udc9c>a)/",
+        // References can come before the group.
+        "/\\k<a>(?<a>b)/",
+        "/\\1(a)/u",
+    ]);
+    expect_error("/(a/", JSErrorKind::UnterminatedRegexpGroup, "(");
+    expect_error("/(?:a/", JSErrorKind::UnterminatedRegexpGroup, "(?:");
+    expect_error("/(?<a>b/", JSErrorKind::UnterminatedRegexpGroup, "(?<a>");
+    expect_error("/a)/", JSErrorKind::UnmatchedRegexpParenthesis, ")");
+    expect_error("/(?i:a)/", JSErrorKind::InvalidRegexpGroup, "(?");
+    expect_error("/(?<>a)/", JSErrorKind::InvalidRegexpGroupName, "(?<>");
+    expect_error("/(?<1a>a)/", JSErrorKind::InvalidRegexpGroupName, "(?<1");
+    expect_error("/(?<a-b>a)/", JSErrorKind::InvalidRegexpGroupName, "(?<a-");
+    expect_error(
+        "/(?<\\u0031>a)/",
+        JSErrorKind::InvalidRegexpGroupName,
+        "(?<\\u0031",
+    );
+    expect_error(
+        "/(?<a>x)|(?<a>y)/",
+        JSErrorKind::DuplicateRegexpGroupName,
+        "(?<a>",
+    );
+    expect_error(
+        "/(?<a>x)\\k<b>/",
+        JSErrorKind::UndefinedRegexpGroupName,
+        "\\k<b>",
+    );
+    expect_error(
+        "/(?<a>x)\\k/",
+        JSErrorKind::InvalidRegexpNamedReference,
+        "\\k",
+    );
+    expect_error(
+        "/\\k<a/u",
+        JSErrorKind::InvalidRegexpNamedReference,
+        "\\k<a",
+    );
+}
+
+#[test]
+fn test_escapes() {
+    expect_valid(&[
+        "/\\d\\D\\s\\S\\w\\W\\b\\B/",
+        "/\\f\\n\\r\\t\\v\\0/u",
+        "/\\cA\\x41\\u0041\\u{1F600}\\uD83D\\uDE00/u",
+        "/\\^\\$\\\\\\.\\*\\+\\?\\(\\)\\[\\]\\{\\}\\|\\//u",
+        // The legacy syntax allows any character to be escaped, as well as
+        // incomplete escapes and octal escapes.
+        "/\\a\\-\\ñ\\c\\x4\\u004\\u{41}\\8\\377/",
+        "/\\k/",
+        "/\\1/",
+        "/\\p{Foo}/",
+    ]);
+    expect_error("/\\a/u", JSErrorKind::InvalidRegexpEscape, "\\a");
+    expect_error("/\\-/u", JSErrorKind::InvalidRegexpEscape, "\\-");
+    expect_error("/\\c1/u", JSErrorKind::InvalidRegexpEscape, "\\c");
+    expect_error("/\\x4/u", JSErrorKind::InvalidRegexpEscape, "\\x");
+    expect_error("/\\u004/u", JSErrorKind::InvalidRegexpUnicodeEscape, "\\u");
+    expect_error(
+        "/\\u{110000}/u",
+        JSErrorKind::InvalidRegexpUnicodeEscape,
+        "\\u",
+    );
+    expect_error("/\\00/u", JSErrorKind::InvalidRegexpDecimalEscape, "\\0");
+    expect_error("/(a)\\2/u", JSErrorKind::InvalidRegexpEscape, "\\2");
+    expect_error("/\\k/u", JSErrorKind::InvalidRegexpNamedReference, "\\k");
+}
+
+#[test]
+fn test_property_escapes() {
+    expect_valid(&[
+        "/\\p{L}\\p{Letter}\\P{Lu}\\p{ASCII}\\p{Any}/u",
+        "/\\p{General_Category=Letter}\\p{gc=Lu}/u",
+        "/\\p{Script=Greek}\\p{sc=Grek}\\p{Script_Extensions=Latin}\\p{scx=Latn}/u",
+        "/\\p{RGI_Emoji}[\\p{Basic_Emoji}a]/v",
+    ]);
+    expect_error(
+        "/\\p{Foo}/u",
+        JSErrorKind::InvalidRegexpPropertyName,
+        "\\p{Foo}",
+    );
+    expect_error(
+        "/\\p{Script=Foo}/u",
+        JSErrorKind::InvalidRegexpPropertyName,
+        "\\p{Script=Foo}",
+    );
+    expect_error(
+        "/\\p{ASCII=Yes}/u",
+        JSErrorKind::InvalidRegexpPropertyName,
+        "\\p{ASCII=Yes}",
+    );
+    expect_error("/\\p/u", JSErrorKind::InvalidRegexpPropertyName, "\\p");
+    expect_error("/\\p{L/u", JSErrorKind::InvalidRegexpPropertyName, "\\p{L");
+    // Properties of strings are only allowed in the v mode, and can not be negated.
+    expect_error(
+        "/\\p{RGI_Emoji}/u",
+        JSErrorKind::InvalidRegexpPropertyName,
+        "\\p{RGI_Emoji}",
+    );
+    expect_error(
+        "/\\P{RGI_Emoji}/v",
+        JSErrorKind::InvalidRegexpPropertyName,
+        "\\P{RGI_Emoji}",
+    );
+}
+
+#[test]
+fn test_classes() {
+    expect_valid(&[
+        "/[abc][^abc][a-z0-9][]/",
+        "/[-a][a-][\\d-a][\\b\\-]/",
+        "/[\\c1\\c_][\\1\\377]/",
+        "/[\\u{1F600}-\\u{1F64F}]/u",
+        "/[😀-😏]/u",
+        "/[a-😀]/",
+        "/[(){}/|]/u",
+    ]);
+    expect_error("/[z-a]/", JSErrorKind::RegexpRangeOutOfOrder, "z-a");
+    // Outside of the unicode modes the emoji are two characters each.
+    expect_error("/[😀-😏]/", JSErrorKind::RegexpRangeOutOfOrder, "😀-😏");
+    expect_error(
+        "/[\\d-a]/u",
+        JSErrorKind::InvalidRegexpCharacterClass,
+        "\\d-a",
+    );
+    expect_error("/[\\1]/u", JSErrorKind::InvalidRegexpClassEscape, "\\1");
+    expect_error("/[a/", JSErrorKind::UnterminatedRegexpCharacterClass, "[a");
+}
+
+#[test]
+fn test_class_sets() {
+    expect_valid(&[
+        "/[[a-z]&&[aeiou]]/v",
+        "/[\\w--\\d]/v",
+        "/[\\w--\\d--_]/v",
+        "/[[a-z]--[aeiou]]/v",
+        "/[a[b[c]]]/v",
+        "/[\\q{abc|d}a]/v",
+        "/[^\\q{a|b}]/v",
+        "/[\\&\\-\\!]/v",
+        "/[^\\q{ab}&&a]/v",
+        "/[^a--\\q{ab}]/v",
+    ]);
+    expect_error("/[(]/v", JSErrorKind::InvalidRegexpClassCharacter, "(");
+    expect_error("/[a-]/v", JSErrorKind::InvalidRegexpClassCharacter, "]");
+    expect_error("/[a&&&b]/v", JSErrorKind::InvalidRegexpClassCharacter, "&");
+    expect_error("/[a&&b--c]/v", JSErrorKind::InvalidRegexpSetOperation, "-");
+    expect_error("/[a-z&&b]/v", JSErrorKind::InvalidRegexpSetOperation, "&&");
+    expect_error("/[a!!b]/v", JSErrorKind::InvalidRegexpSetOperation, "!!");
+    expect_error("/[z-a]/v", JSErrorKind::RegexpRangeOutOfOrder, "z-a");
+    expect_error(
+        "/[[a]/v",
+        JSErrorKind::UnterminatedRegexpCharacterClass,
+        "[[a]",
+    );
+    expect_error(
+        "/[^\\q{ab}]/v",
+        JSErrorKind::NegatedRegexpClassWithStrings,
+        "[^\\q{ab}]",
+    );
+    expect_error(
+        "/[^\\p{RGI_Emoji}]/v",
+        JSErrorKind::NegatedRegexpClassWithStrings,
+        "[^\\p{RGI_Emoji}]",
+    );
+    expect_error(
+        "/[^\\q{ab}--a]/v",
+        JSErrorKind::NegatedRegexpClassWithStrings,
+        "[^\\q{ab}--a]",
+    );
+    expect_error("/\\q{a}/v", JSErrorKind::InvalidRegexpEscape, "\\q");
+}
+
+#[test]
+fn test_features() {
+    expect_features("/a/gim", vec![]);
+    expect_features(
+        "/a/suy",
+        vec![
+            RegexpFeature::DotAllFlag,
+            RegexpFeature::UnicodeFlag,
+            RegexpFeature::StickyFlag,
+        ],
+    );
+    expect_features(
+        "/(?<=a)(?<b>c)\\k<b>\\p{L}/du",
+        vec![
+            RegexpFeature::HasIndicesFlag,
+            RegexpFeature::UnicodeFlag,
+            RegexpFeature::Lookbehind,
+            RegexpFeature::NamedGroups,
+            RegexpFeature::PropertyEscapes,
+        ],
+    );
+    expect_features("/[\\w--\\d]/v", vec![RegexpFeature::UnicodeSetsFlag]);
+    // Without the unicode modes `\p` is the character `p`.
+    expect_features("/\\p{L}/", vec![]);
+}
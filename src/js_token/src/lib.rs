@@ -5,6 +5,10 @@ pub enum Token {
 
     Hashbang,
 
+    /// A keyword written with unicode escapes, such as `\u0069f`. It is
+    /// not a keyword and is only allowed where any name is, `a.\u0069f`.
+    EscapedKeyword,
+
     // Literals
     StringLiteral,
     NumericLiteral,
@@ -134,6 +138,7 @@ impl std::fmt::Display for Token {
             Token::EndOfFile => write!(f, "eof"),
 
             Token::Hashbang => write!(f, "!"),
+            Token::EscapedKeyword => write!(f, "EscapedKeyword"),
 
             Token::Identifier => write!(f, "Identifier"),
             Token::NumericLiteral => write!(f, "NumericLiteral"),
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
@@ -27,9 +28,10 @@ pub struct Message {
     pub location: MessageLocation,
 }
 
-impl ToString for Message {
-    fn to_string(&self) -> String {
-        format!(
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             ">[{}:{}] \x1b[0;1;31mError\x1b[0m: \x1b[0;1m{}\x1b[0m\n  {} | {}\n",
             self.location.line,
             self.location.column,
@@ -85,7 +87,7 @@ fn compute_line_and_column(content: &str, offset: usize) -> (usize, usize, usize
 
 fn location_data(source: &str, range: Range) -> MessageLocation {
     let (line_count, column_count, line_start, line_end) =
-        compute_line_and_column(&source, range.start);
+        compute_line_and_column(source, range.start);
 
     MessageLocation {
         column: column_count,
@@ -109,6 +111,12 @@ pub struct LoggerImpl {
     messages: Arc<Mutex<Vec<Message>>>,
 }
 
+impl Default for LoggerImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LoggerImpl {
     pub fn new() -> LoggerImpl {
         LoggerImpl {
@@ -123,25 +131,25 @@ impl Logger for LoggerImpl {
     fn add_message(&self, message: Message) {
         let mut messages = self.messages.lock().unwrap();
         match message.kind {
-            MessageKind::Error => eprintln!("{}", message.to_string()),
-            MessageKind::Warning => println!("{}", message.to_string()),
+            MessageKind::Error => eprintln!("{}", message),
+            MessageKind::Warning => println!("{}", message),
         }
         messages.push(message);
     }
 
     fn has_errors(&self) -> bool {
         if let Ok(errors) = self.errors.lock() {
-            return errors.gt(&0);
+            errors.gt(&0)
         } else {
-            return false;
+            false
         }
     }
 
     fn has_warnings(&self) -> bool {
         if let Ok(warnings) = self.warnings.lock() {
-            return warnings.gt(&0);
+            warnings.gt(&0)
         } else {
-            return false;
+            false
         }
     }
 
@@ -152,7 +160,7 @@ impl Logger for LoggerImpl {
     fn add_error(&self, source: &str, range: Range, text: String) {
         self.add_message(Message {
             kind: MessageKind::Error,
-            text: text,
+            text,
             location: location_data(source, range),
         });
     }